@@ -42,12 +42,16 @@ use turbopack::{
 use turbopack_cli_utils::issue::{ConsoleUiVc, IssueSeverityCliOption, LogOptions};
 use turbopack_core::{
     asset::{Asset, AssetVc, AssetsVc},
-    compile_time_info::{CompileTimeDefinesVc, CompileTimeInfo},
+    compile_time_info::{CompileTimeDefinesVc, CompileTimeInfo, FeatureFlagsVc},
     context::{AssetContext, AssetContextVc},
     environment::{EnvironmentIntention, EnvironmentVc, ExecutionEnvironment, NodeJsEnvironment},
     issue::{IssueContextExt, IssueReporter, IssueSeverity, IssueVc},
     reference::all_assets,
-    resolve::options::{ImportMapping, ResolvedMap},
+    resolve::{
+        explain::explain_resolve,
+        options::{ImportMapping, ResolvedMap},
+        parse::RequestVc,
+    },
     source_asset::SourceAssetVc,
 };
 
@@ -177,6 +181,16 @@ pub enum Args {
         #[cfg_attr(feature = "node-api", serde(flatten))]
         common: CommonArgs,
     },
+
+    // Print every step taken to resolve the specifier from each input file
+    ExplainResolve {
+        #[cfg_attr(feature = "cli", clap(flatten))]
+        #[cfg_attr(feature = "node-api", serde(flatten))]
+        common: CommonArgs,
+
+        #[cfg_attr(feature = "cli", clap(short, long))]
+        specifier: String,
+    },
 }
 
 #[cfg(feature = "node-api")]
@@ -190,7 +204,8 @@ impl Args {
             Args::Print { common, .. }
             | Args::Annotate { common, .. }
             | Args::Build { common, .. }
-            | Args::Size { common, .. } => common,
+            | Args::Size { common, .. }
+            | Args::ExplainResolve { common, .. } => common,
         }
     }
 }
@@ -463,8 +478,10 @@ async fn run<B: Backend + 'static, F: Future<Output = ()>>(
             result
         }
     };
-    let has_return_value =
-        matches!(&*args, Args::Annotate { .. }) || matches!(&*args, Args::Print { .. });
+    let has_return_value = matches!(
+        &*args,
+        Args::Annotate { .. } | Args::Print { .. } | Args::ExplainResolve { .. }
+    );
     let (sender, mut receiver) = channel(1);
     let dir = current_dir().unwrap();
     let tt = create_tt();
@@ -625,6 +642,35 @@ async fn main_operation(
             }
         }
         Args::Size { common: _ } => todo!(),
+        Args::ExplainResolve {
+            ref specifier,
+            common: _,
+        } => {
+            let input = process_input(&dir, &context, input).unwrap();
+            let root = fs.root();
+            let process_cwd = process_cwd
+                .clone()
+                .map(|p| p.trim_start_matches(&context).to_owned());
+            let asset_context: AssetContextVc =
+                create_module_asset(root, process_cwd, module_options, resolve_options).into();
+            let mut output = Vec::new();
+            for input in input {
+                let origin_path = root.join(&input);
+                let options = asset_context.resolve_options(
+                    origin_path,
+                    Value::new(turbopack_core::reference_type::ReferenceType::Undefined),
+                );
+                let explanation = explain_resolve(
+                    origin_path.parent(),
+                    RequestVc::parse_string(specifier.clone()),
+                    options,
+                )
+                .await?;
+                output.push(format!("{specifier} from {input}"));
+                output.extend(explanation.to_text().lines().map(|line| line.to_string()));
+            }
+            return Ok(StringsVc::cell(output));
+        }
     }
     Ok(StringsVc::cell(Vec::new()))
 }
@@ -649,6 +695,7 @@ async fn create_module_asset(
     let compile_time_info = CompileTimeInfo {
         environment: env,
         defines: CompileTimeDefinesVc::empty(),
+        feature_flags: FeatureFlagsVc::empty(),
     }
     .cell();
     let glob_mappings = vec![
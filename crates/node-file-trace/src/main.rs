@@ -14,7 +14,7 @@ async fn main() -> Result<()> {
     #[cfg(feature = "tokio_console")]
     console_subscriber::init();
     let args = Arc::new(Args::parse());
-    let should_print = matches!(&*args, Args::Print { .. });
+    let should_print = matches!(&*args, Args::Print { .. } | Args::ExplainResolve { .. });
     let result = start(args, None, None, None).await?;
     if should_print {
         for file in result.iter() {
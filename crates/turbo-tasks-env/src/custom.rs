@@ -26,7 +26,10 @@ impl ProcessEnv for CustomProcessEnv {
         let prior = self.prior.read_all().await?;
         let custom = self.custom.await?;
 
+        // Prior variables are replaced by custom ones with a different casing,
+        // too, since lookups ignore casing.
         let mut extended = prior.clone_value();
+        extended.retain(|name, _| !custom.keys().any(|c| c.eq_ignore_ascii_case(name)));
         extended.extend(custom.clone_value());
         Ok(EnvMapVc::cell(extended))
     }
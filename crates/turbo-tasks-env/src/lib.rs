@@ -4,6 +4,7 @@ mod command_line;
 mod custom;
 mod dotenv;
 mod filter;
+mod interpolate;
 
 use std::{env, sync::Mutex};
 
@@ -13,7 +14,7 @@ use turbo_tasks::primitives::OptionStringVc;
 
 pub use self::{
     command_line::CommandLineProcessEnvVc, custom::CustomProcessEnvVc, dotenv::DotenvProcessEnvVc,
-    filter::FilterProcessEnvVc,
+    filter::FilterProcessEnvVc, interpolate::interpolate_env,
 };
 
 #[turbo_tasks::value(transparent)]
@@ -1,5 +1,9 @@
 #![feature(min_specialization)]
 
+pub mod url_imports;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use anyhow::Result;
 use turbo_tasks::primitives::{OptionStringVc, StringVc};
 use turbo_tasks_fs::FileSystemPathVc;
@@ -12,6 +16,22 @@ pub fn register() {
     include!(concat!(env!("OUT_DIR"), "/register.rs"));
 }
 
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Disallows network access for the whole process, e.g. for an `--offline`
+/// flag. Requests fail with [FetchErrorKind::Offline] instead of being sent,
+/// so hermetic builds fail fast instead of hanging on the network.
+///
+/// This needs to be set before the first request, as results are cached.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// Whether network access is disallowed, see [set_offline].
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
 #[turbo_tasks::value(transparent)]
 pub struct FetchResult(Result<HttpResponseVc, FetchErrorVc>);
 
@@ -38,6 +58,14 @@ impl HttpResponseBodyVc {
 #[turbo_tasks::function]
 pub async fn fetch(url: StringVc, user_agent: OptionStringVc) -> Result<FetchResultVc> {
     let url = &*url.await?;
+    if is_offline() {
+        return Ok(FetchResultVc::cell(Err(FetchError {
+            detail: StringVc::cell("network access is disabled in offline mode".to_string()),
+            url: StringVc::cell(url.to_owned()),
+            kind: FetchErrorKind::Offline.into(),
+        }
+        .cell())));
+    }
     let user_agent = &*user_agent.await?;
     let client = reqwest::Client::new();
 
@@ -71,6 +99,9 @@ pub enum FetchErrorKind {
     Connect,
     Timeout,
     Status(u16),
+    /// The request wasn't sent, because the process is offline, see
+    /// [set_offline].
+    Offline,
     Other,
 }
 
@@ -143,8 +174,11 @@ impl Issue for FetchIssue {
     }
 
     #[turbo_tasks::function]
-    fn title(&self) -> StringVc {
-        StringVc::cell("Error while requesting resource".to_string())
+    async fn title(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(match &*self.kind.await? {
+            FetchErrorKind::Offline => "Network access is not allowed in offline mode".to_string(),
+            _ => "Error while requesting resource".to_string(),
+        }))
     }
 
     #[turbo_tasks::function]
@@ -169,6 +203,11 @@ impl Issue for FetchIssue {
                 )
             }
             FetchErrorKind::Timeout => format!("Connection timed out when requesting {}", url),
+            FetchErrorKind::Offline => format!(
+                "{} was not requested, because network access is disabled in offline mode. Make \
+                 the resource available locally or build without offline mode.",
+                url
+            ),
             FetchErrorKind::Other => format!("There was an issue requesting {}", url),
         }))
     }
@@ -0,0 +1,464 @@
+//! Imports of modules from `http://` and `https://` URLs, e.g.
+//! `import x from "https://esm.sh/lodash-es"`.
+//!
+//! Modules are downloaded on first use into a content-addressed cache
+//! directory, and the integrity of every URL is recorded in a lockfile which
+//! is meant to be committed. Once a URL is locked, it's served from the cache,
+//! also in offline mode, and a download which doesn't match the locked
+//! integrity fails with an issue instead of changing the build.
+//!
+//! Relative and server-relative imports of remote modules resolve against
+//! their URL, so modules can import their own dependencies.
+
+use std::{collections::BTreeMap, fs, sync::Mutex};
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use reqwest::Url;
+use sha2::{Digest, Sha384};
+use turbo_tasks::{
+    primitives::{OptionStringVc, StringVc},
+    CompletionVc, ValueToString, ValueToStringVc,
+};
+use turbo_tasks_fs::{
+    to_sys_path, DirectoryContentVc, File, FileContent, FileContentVc, FileJsonContent, FileMeta,
+    FileMetaVc, FileSystem, FileSystemPathVc, FileSystemVc, LinkContent, LinkContentVc,
+};
+use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64};
+use turbopack_core::{
+    asset::AssetOptionVc,
+    issue::{Issue, IssueSeverity, IssueSeverityVc, IssueVc},
+    resolve::{
+        options::{
+            ImportMap, ImportMapResult, ImportMapResultVc, ImportMapVc, ImportMapping,
+            ImportMappingReplacement, ImportMappingReplacementVc, ImportMappingVc,
+        },
+        parse::RequestVc,
+        ResolveResult,
+    },
+    virtual_asset::VirtualAssetVc,
+};
+
+use crate::fetch;
+
+/// The version of the lockfile format.
+const LOCKFILE_VERSION: u32 = 1;
+
+/// Options for importing modules from URLs.
+#[turbo_tasks::value(shared)]
+pub struct UrlImportsOptions {
+    /// The directory downloaded modules are cached in, by their integrity.
+    pub cache_dir: FileSystemPathVc,
+    /// The lockfile which pins the integrity of every imported URL.
+    pub lockfile: FileSystemPathVc,
+}
+
+#[turbo_tasks::value_impl]
+impl UrlImportsOptionsVc {
+    #[turbo_tasks::function]
+    pub fn new(cache_dir: FileSystemPathVc, lockfile: FileSystemPathVc) -> Self {
+        UrlImportsOptions {
+            cache_dir,
+            lockfile,
+        }
+        .cell()
+    }
+
+    /// An import map which resolves URL imports. It can be added to the
+    /// resolve options with `with_extended_import_map`.
+    #[turbo_tasks::function]
+    pub fn import_map(self) -> ImportMapVc {
+        let mut import_map = ImportMap::empty();
+        let mapping = ImportMapping::Dynamic(UrlImportMappingVc::new(self).into()).cell();
+        for prefix in ["http://", "https://"] {
+            import_map.insert_wildcard_alias(prefix, mapping);
+        }
+        // These are only resolved against a URL when they are imported by a
+        // remote module, see [UrlImportMapping::result].
+        for prefix in ["./", "../", "/"] {
+            import_map.insert_wildcard_alias(prefix, mapping);
+        }
+        import_map.cell()
+    }
+}
+
+/// The [ImportMappingReplacement] of URL imports.
+#[turbo_tasks::value(shared)]
+struct UrlImportMapping {
+    options: UrlImportsOptionsVc,
+}
+
+#[turbo_tasks::value_impl]
+impl UrlImportMappingVc {
+    #[turbo_tasks::function]
+    fn new(options: UrlImportsOptionsVc) -> Self {
+        UrlImportMapping { options }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMappingReplacement for UrlImportMapping {
+    #[turbo_tasks::function]
+    fn replace(self_vc: UrlImportMappingVc, _capture: &str) -> ImportMappingVc {
+        ImportMapping::Dynamic(self_vc.into()).cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn result(
+        &self,
+        context: FileSystemPathVc,
+        request: RequestVc,
+    ) -> Result<ImportMapResultVc> {
+        let Some(request) = request.await?.request() else {
+            return Ok(ImportMapResult::NoEntry.into());
+        };
+        let url = if request.starts_with("http://") || request.starts_with("https://") {
+            Url::parse(&request).ok()
+        } else if RemoteFileSystemVc::resolve_from(context.fs())
+            .await?
+            .is_some()
+        {
+            directory_url(&context.await?.path).and_then(|base| base.join(&request).ok())
+        } else {
+            // A local request which matches one of the relative prefixes.
+            return Ok(ImportMapResult::NoEntry.into());
+        };
+        let Some(url) = url else {
+            return Ok(ImportMapResult::NoEntry.into());
+        };
+        let result = match &*remote_module(url.to_string(), self.options).await? {
+            Some(asset) => ResolveResult::asset(*asset),
+            None => ResolveResult::unresolveable(),
+        };
+        Ok(ImportMapResult::Result(result.into()).into())
+    }
+}
+
+/// The module downloaded from `url`, or `None` when it's not available. An
+/// issue is emitted in that case.
+#[turbo_tasks::function]
+async fn remote_module(url: String, options: UrlImportsOptionsVc) -> Result<AssetOptionVc> {
+    let parsed = Url::parse(&url)?;
+    let content = remote_module_content(url, options);
+    if matches!(&*content.await?, FileContent::NotFound) {
+        return Ok(AssetOptionVc::cell(None));
+    }
+    let path = FileSystemVc::from(RemoteFileSystemVc::new())
+        .root()
+        .join(&remote_module_path(&parsed));
+    Ok(AssetOptionVc::cell(Some(
+        VirtualAssetVc::new(path, content.into()).into(),
+    )))
+}
+
+/// The content of the module at `url`, served from the cache when it's
+/// locked. Otherwise, it's downloaded, cached and added to the lockfile.
+#[turbo_tasks::function]
+async fn remote_module_content(url: String, options: UrlImportsOptionsVc) -> Result<FileContentVc> {
+    let options = options.await?;
+    let locked = read_lockfile(options.lockfile)
+        .await?
+        .remote
+        .get(&url)
+        .cloned();
+
+    if let Some(integrity) = &locked {
+        let cached = options.cache_dir.join(&cache_file_name(integrity)).read();
+        if let FileContent::Content(file) = &*cached.await? {
+            // Guards against a corrupted cache.
+            if integrity_of(&file.content().to_bytes()?) == *integrity {
+                return Ok(cached);
+            }
+        }
+    }
+
+    let response = match &*fetch(StringVc::cell(url.clone()), OptionStringVc::cell(None)).await? {
+        Ok(response) => response.await?,
+        Err(error) => {
+            error
+                .to_issue(IssueSeverity::Error.into(), options.lockfile)
+                .as_issue()
+                .emit();
+            return Ok(FileContent::NotFound.cell());
+        }
+    };
+    let body = &response.body.await?.0;
+    let integrity = integrity_of(body);
+    if let Some(locked) = &locked {
+        if *locked != integrity {
+            IntegrityMismatchIssue {
+                lockfile: options.lockfile,
+                url,
+                locked: locked.clone(),
+                actual: integrity,
+            }
+            .cell()
+            .as_issue()
+            .emit();
+            return Ok(FileContent::NotFound.cell());
+        }
+    }
+
+    let content = FileContent::Content(File::from(body.clone())).cell();
+    options
+        .cache_dir
+        .join(&cache_file_name(&integrity))
+        .write(content)
+        .await?;
+    if locked.is_none() {
+        add_to_lockfile(options.lockfile, &url, &integrity).await?;
+    }
+    Ok(content)
+}
+
+/// The content of the lockfile.
+#[turbo_tasks::value]
+#[derive(Default)]
+struct Lockfile {
+    version: u32,
+    /// The integrity of every imported URL.
+    remote: BTreeMap<String, String>,
+}
+
+#[turbo_tasks::function]
+async fn read_lockfile(lockfile: FileSystemPathVc) -> Result<LockfileVc> {
+    Ok(match &*lockfile.read_json().await? {
+        FileJsonContent::Content(json) => match serde_json::from_value::<Lockfile>(json.clone()) {
+            Ok(content) => content,
+            Err(err) => bail!("invalid lockfile {}: {err}", lockfile.to_string().await?),
+        },
+        FileJsonContent::Unparseable(_) => {
+            bail!("unable to parse lockfile {}", lockfile.to_string().await?)
+        }
+        FileJsonContent::NotFound => Lockfile::default(),
+    }
+    .cell())
+}
+
+/// Serializes updates of the lockfile, which are made by concurrent tasks.
+static LOCKFILE_UPDATE: Mutex<()> = Mutex::new(());
+
+/// Adds the `integrity` of `url` to the lockfile. The lockfile is updated
+/// on disk directly, so concurrent updates don't overwrite each other.
+async fn add_to_lockfile(lockfile: FileSystemPathVc, url: &str, integrity: &str) -> Result<()> {
+    let Some(path) = to_sys_path(lockfile).await? else {
+        bail!(
+            "the lockfile {} needs to be on disk",
+            lockfile.to_string().await?
+        );
+    };
+    let _guard = LOCKFILE_UPDATE.lock().unwrap();
+    let mut content: Lockfile = match fs::read_to_string(&path) {
+        Ok(json) => serde_json::from_str(&json)
+            .with_context(|| format!("unable to parse lockfile {}", path.display()))?,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Lockfile::default(),
+        Err(error) => return Err(error.into()),
+    };
+    content.version = LOCKFILE_VERSION;
+    content
+        .remote
+        .insert(url.to_string(), integrity.to_string());
+    fs::write(&path, serde_json::to_string_pretty(&content)? + "\n")
+        .with_context(|| format!("unable to write lockfile {}", path.display()))?;
+    Ok(())
+}
+
+/// The [subresource integrity] of `content`.
+///
+/// [subresource integrity]: https://developer.mozilla.org/en-US/docs/Web/Security/Subresource_Integrity
+fn integrity_of(content: &[u8]) -> String {
+    let digest = Sha384::digest(content);
+    format!(
+        "sha384-{}",
+        base64::engine::general_purpose::STANDARD.encode(digest)
+    )
+}
+
+/// The name of the cache file of the content with `integrity`. Base64 can
+/// contain `/`, so the URL safe alphabet is used.
+fn cache_file_name(integrity: &str) -> String {
+    integrity.replace('/', "_").replace('+', "-")
+}
+
+/// The path of the module at `url` in the [RemoteFileSystem], e.g.
+/// `https/esm.sh/lodash-es.js`. The directories mirror the URL, so relative
+/// imports can be resolved against it with [directory_url]. Modules without
+/// an extension are treated as JavaScript, and the query is part of the file
+/// name.
+fn remote_module_path(url: &Url) -> String {
+    let host = match url.port() {
+        Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+    let (dir, name) = url.path().rsplit_once('/').unwrap_or(("", url.path()));
+    let mut name = if name.is_empty() {
+        "index".to_string()
+    } else {
+        name.to_string()
+    };
+    if let Some(query) = url.query() {
+        let hash = encode_hex(hash_xxh3_hash64(query));
+        name = match name.rsplit_once('.') {
+            Some((stem, extension)) => format!("{stem}_{hash}.{extension}"),
+            None => format!("{name}_{hash}"),
+        };
+    }
+    if !name.contains('.') {
+        name.push_str(".js");
+    }
+    format!("{}/{host}{dir}/{name}", url.scheme())
+}
+
+/// The URL of a directory of the [RemoteFileSystem], see
+/// [remote_module_path].
+fn directory_url(path: &str) -> Option<Url> {
+    let (scheme, rest) = path.split_once('/')?;
+    Url::parse(&format!("{scheme}://{rest}/")).ok()
+}
+
+/// The file system of remote modules. It's only a namespace for their paths,
+/// the modules themselves are virtual assets.
+#[turbo_tasks::value]
+struct RemoteFileSystem {}
+
+#[turbo_tasks::value_impl]
+impl RemoteFileSystemVc {
+    #[turbo_tasks::function]
+    fn new() -> Self {
+        Self::cell(RemoteFileSystem {})
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl FileSystem for RemoteFileSystem {
+    #[turbo_tasks::function]
+    fn read(&self, _fs_path: FileSystemPathVc) -> FileContentVc {
+        FileContent::NotFound.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn read_link(&self, _fs_path: FileSystemPathVc) -> LinkContentVc {
+        LinkContent::NotFound.into()
+    }
+
+    #[turbo_tasks::function]
+    fn read_dir(&self, _fs_path: FileSystemPathVc) -> DirectoryContentVc {
+        DirectoryContentVc::not_found()
+    }
+
+    #[turbo_tasks::function]
+    fn track(&self, _fs_path: FileSystemPathVc) -> CompletionVc {
+        CompletionVc::immutable()
+    }
+
+    #[turbo_tasks::function]
+    fn write(&self, _fs_path: FileSystemPathVc, _content: FileContentVc) -> Result<CompletionVc> {
+        bail!("Writing is not possible to the file system of remote modules")
+    }
+
+    #[turbo_tasks::function]
+    fn write_link(
+        &self,
+        _fs_path: FileSystemPathVc,
+        _target: LinkContentVc,
+    ) -> Result<CompletionVc> {
+        bail!("Writing is not possible to the file system of remote modules")
+    }
+
+    #[turbo_tasks::function]
+    fn metadata(&self, _fs_path: FileSystemPathVc) -> FileMetaVc {
+        FileMeta::default().cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for RemoteFileSystem {
+    #[turbo_tasks::function]
+    fn to_string(&self) -> StringVc {
+        StringVc::cell("remote modules".to_string())
+    }
+}
+
+#[turbo_tasks::value(shared)]
+pub struct IntegrityMismatchIssue {
+    pub lockfile: FileSystemPathVc,
+    pub url: String,
+    pub locked: String,
+    pub actual: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for IntegrityMismatchIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("fetch".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell(format!("Integrity check failed for {}", self.url))
+    }
+
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.lockfile
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> StringVc {
+        StringVc::cell(format!(
+            "The content of {} has changed since it was added to the lockfile. If the change is \
+             expected, remove the URL from the lockfile to download it again.\n\nLocked: \
+             {}\nActual: {}",
+            self.url, self.locked, self.actual
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::Url;
+
+    use super::{cache_file_name, directory_url, remote_module_path};
+
+    #[test]
+    fn remote_module_paths() {
+        let path = |url: &str| remote_module_path(&Url::parse(url).unwrap());
+        assert_eq!(
+            path("https://esm.sh/lodash-es"),
+            "https/esm.sh/lodash-es.js"
+        );
+        assert_eq!(
+            path("https://esm.sh/stable/react@18.2.0/es2022/react.mjs"),
+            "https/esm.sh/stable/react@18.2.0/es2022/react.mjs"
+        );
+        assert_eq!(
+            path("http://localhost:8080/"),
+            "http/localhost:8080/index.js"
+        );
+        assert!(path("https://esm.sh/react?dev").starts_with("https/esm.sh/react_"));
+    }
+
+    #[test]
+    fn directory_urls() {
+        let url = directory_url("https/esm.sh/stable/react@18.2.0").unwrap();
+        assert_eq!(
+            url.join("./react.mjs").unwrap().as_str(),
+            "https://esm.sh/stable/react@18.2.0/react.mjs"
+        );
+        assert_eq!(
+            url.join("/v118/react.mjs").unwrap().as_str(),
+            "https://esm.sh/v118/react.mjs"
+        );
+    }
+
+    #[test]
+    fn cache_file_names() {
+        assert_eq!(cache_file_name("sha384-a+b/c="), "sha384-a-b_c=");
+    }
+}
@@ -0,0 +1,48 @@
+#![cfg(test)]
+
+use turbo_tasks::primitives::{OptionStringVc, StringVc};
+use turbo_tasks_fetch::{fetch, register, set_offline, FetchErrorKind};
+use turbo_tasks_fs::{DiskFileSystemVc, FileSystem, FileSystemPathVc, FileSystemVc};
+use turbo_tasks_testing::{register, run};
+use turbopack_core::issue::{Issue, IssueSeverity};
+
+register!();
+
+// Offline mode is process-wide, so this is separate from the other fetch
+// tests, which run in parallel.
+#[tokio::test]
+async fn errors_when_offline() {
+    run! {
+        register();
+        set_offline(true);
+
+        let server = httpmock::MockServer::start();
+        let resource_mock = server.mock(|when, then| {
+            when.path("/foo.woff");
+            then.status(200)
+                .body("responsebody");
+        });
+
+        let url = server.url("/foo.woff");
+        let result = &*fetch(StringVc::cell(url.clone()), OptionStringVc::cell(None)).await?;
+        resource_mock.assert_hits(0);
+        let Err(err_vc) = result else {
+            panic!()
+        };
+        let err = &*err_vc.await?;
+        assert_eq!(*err.kind.await?, FetchErrorKind::Offline);
+        assert_eq!(*err.url.await?, url);
+
+        let issue = err_vc.to_issue(IssueSeverity::Error.into(), get_issue_context());
+        assert_eq!(*issue.title().await?, "Network access is not allowed in offline mode");
+        assert!(issue.description().await?.starts_with(&url));
+    }
+}
+
+fn get_issue_context() -> FileSystemPathVc {
+    std::convert::Into::<FileSystemVc>::into(DiskFileSystemVc::new(
+        "root".to_owned(),
+        "/".to_owned(),
+    ))
+    .root()
+}
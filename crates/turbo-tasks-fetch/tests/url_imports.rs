@@ -0,0 +1,68 @@
+#![cfg(test)]
+
+use turbo_tasks::{TurboTasks, Value};
+use turbo_tasks_fetch::{register, url_imports::UrlImportsOptionsVc};
+use turbo_tasks_fs::{DiskFileSystemVc, FileContent, FileSystem, FileSystemVc};
+use turbo_tasks_memory::MemoryBackend;
+use turbo_tasks_testing::register;
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    resolve::{options::ImportMapResult, parse::RequestVc},
+};
+
+register!();
+
+#[tokio::test]
+async fn downloads_and_locks_url_imports() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_str().unwrap().to_owned();
+    // Unlike `run!`, the task owns the temporary directory.
+    *REGISTER;
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.run_once(async move {
+        register();
+
+        let server = httpmock::MockServer::start();
+        let resource_mock = server.mock(|when, then| {
+            when.path("/answer.js");
+            then.status(200).body("export default 42;");
+        });
+
+        let root =
+            FileSystemVc::from(DiskFileSystemVc::new("project".to_owned(), path.clone())).root();
+        let options = UrlImportsOptionsVc::new(root.join("cache"), root.join("url-imports.lock"));
+        let url = server.url("/answer.js");
+        let request = RequestVc::parse(Value::new(url.clone().into()));
+        let result = &*options.import_map().lookup(root, request).await?;
+        resource_mock.assert();
+
+        let ImportMapResult::Result(result) = result else {
+            panic!()
+        };
+        let asset = result.first_asset().await?.unwrap();
+        let AssetContent::File(file) = &*asset.content().await? else {
+            panic!()
+        };
+        let FileContent::Content(file) = &*file.await? else {
+            panic!()
+        };
+        assert_eq!(file.content().to_str()?, "export default 42;");
+
+        let lockfile = std::fs::read_to_string(dir.path().join("url-imports.lock"))?;
+        let lockfile: serde_json::Value = serde_json::from_str(&lockfile)?;
+        assert!(lockfile["remote"][&url]
+            .as_str()
+            .unwrap()
+            .starts_with("sha384-"));
+
+        // Relative imports of a remote module resolve against its URL.
+        let relative = RequestVc::parse(Value::new("./answer.js".to_string().into()));
+        let context = asset.ident().path().parent();
+        let result = &*options.import_map().lookup(context, relative).await?;
+        assert!(matches!(result, ImportMapResult::Result(_)));
+        resource_mock.assert_hits(1);
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
@@ -1,8 +1,51 @@
-use std::fmt::{Display, Formatter};
+use std::{
+    fmt::{Display, Formatter},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Instant,
+};
 
 use indexmap::IndexSet;
 use turbo_tasks::{util::StaticOrArc, InvalidationReason, InvalidationReasonKind};
 
+static LAST_WATCH_EVENT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// When the file watcher of any [DiskFileSystem] last received a change. This
+/// is where the latency of an update caused by a file change starts.
+///
+/// [DiskFileSystem]: crate::DiskFileSystem
+pub fn last_watch_event() -> Option<Instant> {
+    *LAST_WATCH_EVENT.lock().unwrap()
+}
+
+pub(crate) fn record_watch_event(time: Instant) {
+    *LAST_WATCH_EVENT.lock().unwrap() = Some(time);
+}
+
+static WATCHING_PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Pauses the file watchers of all [DiskFileSystem]s. Changes are queued
+/// instead of invalidating the reads of the changed files, so nothing is
+/// recomputed in the background until [resume_watching] applies them.
+///
+/// [DiskFileSystem]: crate::DiskFileSystem
+pub fn pause_watching() {
+    WATCHING_PAUSED.store(true, Ordering::Release);
+}
+
+/// Resumes the file watchers paused by [pause_watching]. The changes queued
+/// meanwhile are applied shortly after.
+pub fn resume_watching() {
+    WATCHING_PAUSED.store(false, Ordering::Release);
+}
+
+/// Whether the file watchers are paused, see [pause_watching].
+pub fn is_watching_paused() -> bool {
+    WATCHING_PAUSED.load(Ordering::Acquire)
+}
+
 /// Invalidation was caused by a file change detected by the file watcher
 #[derive(PartialEq, Eq, Hash)]
 pub struct WatchChange {
@@ -18,6 +18,7 @@ mod retry;
 pub mod rope;
 pub mod source_context;
 pub mod util;
+pub mod zip;
 
 use std::{
     borrow::Cow,
@@ -29,10 +30,10 @@ use std::{
     mem::take,
     path::{Path, PathBuf, MAIN_SEPARATOR},
     sync::{
-        mpsc::{channel, RecvError, TryRecvError},
+        mpsc::{channel, RecvError, RecvTimeoutError, TryRecvError},
         Arc, Mutex,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use anyhow::{anyhow, bail, Context, Result};
@@ -40,6 +41,7 @@ use auto_hash_map::AutoMap;
 use bitflags::bitflags;
 use dunce::simplified;
 use glob::GlobVc;
+pub use invalidation::{is_watching_paused, last_watch_event, pause_watching, resume_watching};
 use invalidator_map::InvalidatorMap;
 use jsonc_parser::{parse_to_serde_value, ParseOptions};
 use mime::Mime;
@@ -53,6 +55,7 @@ use tokio::{
     io::{AsyncBufReadExt, AsyncReadExt, BufReader},
 };
 use turbo_tasks::{
+    concurrency::ConcurrencyClass,
     mark_stateful,
     primitives::{BoolVc, StringReadRef, StringVc},
     spawn_thread,
@@ -65,7 +68,7 @@ use util::{extract_disk_access, join_path, normalize_path, sys_to_unix, unix_to_
 use self::{invalidation::WatchStart, json::UnparseableJson, mutex_map::MutexMap};
 use crate::{
     attach::AttachedFileSystemVc,
-    invalidation::WatchChange,
+    invalidation::{record_watch_event, WatchChange},
     retry::{retry_blocking, retry_future},
     rope::{Rope, RopeReadRef, RopeReader},
 };
@@ -85,6 +88,9 @@ pub trait FileSystem: ValueToString {
     fn metadata(&self, fs_path: FileSystemPathVc) -> FileMetaVc;
 }
 
+/// How often a paused file watcher checks whether it was resumed.
+const PAUSED_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 #[derive(Default)]
 struct DiskWatcher {
     watcher: Mutex<Option<RecommendedWatcher>>,
@@ -284,10 +290,24 @@ impl DiskFileSystem {
             #[cfg(not(any(target_os = "macos", target_os = "windows")))]
             let mut batched_new_paths = HashSet::new();
 
+            // While watching is paused, changes are queued and the thread checks
+            // regularly whether it was resumed to apply them.
+            let mut paused = false;
+
             'outer: loop {
-                let mut event = rx.recv().map_err(|e| match e {
-                    RecvError => TryRecvError::Disconnected,
-                });
+                let mut event = if paused {
+                    rx.recv_timeout(PAUSED_POLL_INTERVAL).map_err(|e| match e {
+                        RecvTimeoutError::Timeout => TryRecvError::Empty,
+                        RecvTimeoutError::Disconnected => TryRecvError::Disconnected,
+                    })
+                } else {
+                    rx.recv().map_err(|e| match e {
+                        RecvError => TryRecvError::Disconnected,
+                    })
+                };
+                if event.is_ok() {
+                    record_watch_event(Instant::now());
+                }
                 loop {
                     match event {
                         Ok(DebouncedEvent::Write(path)) => {
@@ -407,6 +427,10 @@ impl DiskFileSystem {
                         let _ = disk_watcher.restore_if_watching(&path, &root_path);
                     }
                 }
+                paused = is_watching_paused();
+                if paused {
+                    continue;
+                }
                 {
                     let mut invalidator_map = invalidator_map.lock().unwrap();
                     invalidate_path(
@@ -511,6 +535,7 @@ impl FileSystem for DiskFileSystem {
         self.register_invalidator(&full_path)?;
 
         let _lock = self.mutex_map.lock(full_path.clone()).await;
+        let _permit = ConcurrencyClass::FsRead.acquire().await;
         let content = match retry_future(|| File::from_path(full_path.clone())).await {
             Ok(file) => FileContent::new(file),
             Err(e) if e.kind() == ErrorKind::NotFound => FileContent::NotFound,
@@ -527,6 +552,9 @@ impl FileSystem for DiskFileSystem {
         self.register_dir_invalidator(&full_path)?;
         let fs_path = fs_path.await?;
 
+        // The permit is held until all entries have been read, as the directory stays
+        // open until then.
+        let _permit = ConcurrencyClass::FsRead.acquire().await;
         // we use the sync std function here as it's a lot faster (600%) in
         // node-file-trace
         let read_dir = match retry_blocking(&full_path, |path| std::fs::read_dir(path)).await {
@@ -581,6 +609,7 @@ impl FileSystem for DiskFileSystem {
         self.register_invalidator(&full_path)?;
 
         let _lock = self.mutex_map.lock(full_path.clone()).await;
+        let _permit = ConcurrencyClass::FsRead.acquire().await;
         let link_path = match retry_future(|| fs::read_link(&full_path)).await {
             Ok(res) => res,
             Err(_) => return Ok(LinkContent::NotFound.cell()),
@@ -1485,9 +1514,14 @@ impl File {
         let mut output = Vec::with_capacity(metadata.len() as usize);
         file.read_to_end(&mut output).await?;
 
+        // Text is kept as a `String`, so parsers can share it instead of copying it.
+        let content = match String::from_utf8(output) {
+            Ok(text) => Rope::from_shared_str(Arc::new(text)),
+            Err(err) => Rope::from(err.into_bytes()),
+        };
         Ok(File {
             meta: metadata.into(),
-            content: Rope::from(output),
+            content,
         })
     }
 
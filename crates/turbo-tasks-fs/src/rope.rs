@@ -16,7 +16,7 @@ use futures::Stream;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use tokio::io::{AsyncRead, ReadBuf};
 use turbo_tasks_hash::{DeterministicHash, DeterministicHasher};
-use RopeElem::{Local, Shared};
+use RopeElem::{Local, Shared, Text};
 
 static EMPTY_BUF: &[u8] = &[];
 
@@ -47,6 +47,11 @@ enum RopeElem {
     /// Local bytes are owned directly by this rope.
     Local(Bytes),
 
+    /// Text owned directly by this rope, which can be handed out as a shared
+    /// `String` without copying it, e.g. as the source text of a swc
+    /// `SourceFile`.
+    Text(Arc<String>),
+
     /// Shared holds the Arc container of another rope.
     Shared(InnerRope),
 }
@@ -114,6 +119,24 @@ impl Rope {
     pub fn to_bytes(&self) -> Result<Cow<'_, [u8]>> {
         self.data.to_bytes()
     }
+
+    /// Creates a Rope from the `text`, which is shared instead of copied.
+    pub fn from_shared_str(text: Arc<String>) -> Self {
+        if text.is_empty() {
+            Default::default()
+        } else {
+            Rope {
+                length: text.len(),
+                data: InnerRope(Arc::from([Text(text)])),
+            }
+        }
+    }
+
+    /// Returns a shared String instance of all bytes. This only copies the
+    /// bytes when the rope wasn't created by [Rope::from_shared_str].
+    pub fn to_shared_str(&self) -> Result<Arc<String>> {
+        self.data.to_shared_str()
+    }
 }
 
 impl<T: Into<Bytes>> From<T> for Rope {
@@ -447,6 +470,7 @@ impl InnerRope {
                 utf8.context("failed to convert rope into string")
                     .map(Cow::Borrowed)
             }
+            [Text(text)] => Ok(Cow::Borrowed(text)),
             _ => {
                 let mut read = RopeReader::new(self, 0);
                 let mut string = String::with_capacity(self.len());
@@ -463,6 +487,7 @@ impl InnerRope {
             [] => Ok(Cow::Borrowed(EMPTY_BUF)),
             [Shared(inner)] => inner.to_bytes(),
             [Local(bytes)] => Ok(Cow::Borrowed(bytes)),
+            [Text(text)] => Ok(Cow::Borrowed(text.as_bytes())),
             _ => {
                 let mut read = RopeReader::new(self, 0);
                 let mut buf = Vec::with_capacity(self.len());
@@ -471,6 +496,15 @@ impl InnerRope {
             }
         }
     }
+
+    /// Returns a shared String instance of all bytes.
+    pub fn to_shared_str(&self) -> Result<Arc<String>> {
+        match &self[..] {
+            [Shared(inner)] => inner.to_shared_str(),
+            [Text(text)] => Ok(text.clone()),
+            _ => Ok(Arc::new(self.to_str()?.into_owned())),
+        }
+    }
 }
 
 impl Default for InnerRope {
@@ -498,6 +532,7 @@ impl From<Vec<RopeElem>> for InnerRope {
             for el in els.iter() {
                 match el {
                     Local(b) => debug_assert!(!b.is_empty(), "must not have empty Bytes"),
+                    Text(t) => debug_assert!(!t.is_empty(), "must not have empty Text"),
                     Shared(s) => {
                         // We check whether the shared slice is empty, and not its elements. The
                         // only way to construct the Shared's InnerRope is
@@ -521,9 +556,20 @@ impl Deref for InnerRope {
 }
 
 impl RopeElem {
+    /// The bytes of a Local or Text section.
+    fn as_slice(&self) -> Option<&[u8]> {
+        match self {
+            Local(bytes) => Some(bytes),
+            Text(text) => Some(text.as_bytes()),
+            Shared(_) => None,
+        }
+    }
+
     fn maybe_eq(&self, other: &Self) -> Option<bool> {
         match (self, other) {
-            (Local(a), Local(b)) => {
+            (Text(a), Text(b)) if Arc::ptr_eq(a, b) => Some(true),
+            (Local(_) | Text(_), Local(_) | Text(_)) => {
+                let (a, b) = (self.as_slice()?, other.as_slice()?);
                 if a.len() == b.len() {
                     return Some(a == b);
                 }
@@ -553,6 +599,7 @@ impl DeterministicHash for RopeElem {
     fn deterministic_hash<H: DeterministicHasher>(&self, state: &mut H) {
         match self {
             Local(bytes) => state.write_bytes(bytes),
+            Text(text) => state.write_bytes(text.as_bytes()),
             Shared(inner) => inner.deterministic_hash(state),
         }
     }
@@ -703,10 +750,20 @@ impl Stream for RopeReader {
     }
 }
 
+/// Lets [Bytes] reference the text of a [Text] section without copying it.
+struct SharedText(Arc<String>);
+
+impl AsRef<[u8]> for SharedText {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
 impl From<RopeElem> for StackElem {
     fn from(el: RopeElem) -> Self {
         match el {
             Local(bytes) => Self::Local(bytes),
+            Text(text) => Self::Local(Bytes::from_owner(SharedText(text))),
             Shared(inner) => Self::Shared(inner, 0),
         }
     }
@@ -718,9 +775,11 @@ mod test {
         borrow::Cow,
         cmp::min,
         io::{BufRead, Read},
+        sync::Arc,
     };
 
     use anyhow::Result;
+    use bytes::Bytes;
 
     use super::{InnerRope, Rope, RopeBuilder, RopeElem};
 
@@ -759,6 +818,7 @@ mod test {
         fn len(&self) -> usize {
             match self {
                 RopeElem::Local(b) => b.len(),
+                RopeElem::Text(t) => t.len(),
                 RopeElem::Shared(r) => r.len(),
             }
         }
@@ -982,4 +1042,29 @@ mod test {
         assert_eq!(rope.to_bytes()?, Cow::Borrowed::<[u8]>(&[0x61, 0x62, 0x63]));
         Ok(())
     }
+
+    #[test]
+    fn shared_str() -> Result<()> {
+        let text = Arc::new("abc".to_string());
+        let rope = Rope::from_shared_str(text.clone());
+        assert!(Arc::ptr_eq(&rope.to_shared_str()?, &text));
+        assert_eq!(rope.to_str()?, Cow::Borrowed("abc"));
+        assert_eq!(rope, Rope::from("abc"));
+        assert_eq!(
+            rope.read().collect::<Vec<_>>(),
+            vec![Bytes::from_static(b"abc")]
+        );
+
+        let mut builder = RopeBuilder::default();
+        builder += &rope;
+        let concatenated = builder.build();
+        assert!(Arc::ptr_eq(&concatenated.to_shared_str()?, &text));
+
+        let mut builder = RopeBuilder::from(b"x".to_vec());
+        builder += &rope;
+        assert_eq!(*builder.build().to_shared_str()?, "xabc");
+
+        assert!(Rope::from_shared_str(Arc::new(String::new())).is_empty());
+        Ok(())
+    }
 }
@@ -0,0 +1,173 @@
+use std::{
+    collections::BTreeMap,
+    io::{Cursor, Read},
+};
+
+use ::zip::ZipArchive;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{
+    primitives::StringVc, trace::TraceRawVcs, CompletionVc, ValueToString, ValueToStringVc,
+};
+
+use crate::{
+    DirectoryContent, DirectoryContentVc, DirectoryEntry, File, FileContent, FileContentVc,
+    FileMeta, FileMetaVc, FileSystem, FileSystemPathVc, FileSystemVc, LinkContent, LinkContentVc,
+};
+
+/// A read-only [FileSystem] of the files in a zip archive, e.g. of a package
+/// in the cache of Yarn Plug'n'Play.
+///
+/// The archive is read from the `archive` path, so replacing it invalidates
+/// the files read from it.
+#[turbo_tasks::value]
+pub struct ZipFileSystem {
+    pub archive: FileSystemPathVc,
+}
+
+#[turbo_tasks::value_impl]
+impl ZipFileSystemVc {
+    #[turbo_tasks::function]
+    pub fn new(archive: FileSystemPathVc) -> Self {
+        ZipFileSystem { archive }.cell()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+enum ZipEntryKind {
+    File,
+    Directory,
+}
+
+/// The entries of a zip archive by their path. Directories which only appear
+/// in the paths of files are included, the root is `""`.
+#[turbo_tasks::value(transparent)]
+struct ZipIndex(BTreeMap<String, ZipEntryKind>);
+
+async fn read_archive(archive: FileSystemPathVc) -> Result<Option<ZipArchive<Cursor<Vec<u8>>>>> {
+    let FileContent::Content(file) = &*archive.read().await? else {
+        return Ok(None);
+    };
+    let bytes = file.content().to_bytes()?.into_owned();
+    match ZipArchive::new(Cursor::new(bytes)) {
+        Ok(zip) => Ok(Some(zip)),
+        Err(err) => Err(err).context(format!(
+            "reading zip archive {}",
+            archive.to_string().await?
+        )),
+    }
+}
+
+#[turbo_tasks::function]
+async fn zip_index(archive: FileSystemPathVc) -> Result<ZipIndexVc> {
+    let mut index = BTreeMap::new();
+    if let Some(zip) = read_archive(archive).await? {
+        index.insert(String::new(), ZipEntryKind::Directory);
+        for name in zip.file_names() {
+            let (path, kind) = match name.strip_suffix('/') {
+                Some(path) => (path, ZipEntryKind::Directory),
+                None => (name, ZipEntryKind::File),
+            };
+            let mut parent = path;
+            while let Some((directory, _)) = parent.rsplit_once('/') {
+                index.insert(directory.to_string(), ZipEntryKind::Directory);
+                parent = directory;
+            }
+            index.insert(path.to_string(), kind);
+        }
+    }
+    Ok(ZipIndexVc::cell(index))
+}
+
+#[turbo_tasks::value_impl]
+impl FileSystem for ZipFileSystem {
+    #[turbo_tasks::function]
+    async fn read(&self, path: FileSystemPathVc) -> Result<FileContentVc> {
+        let path = &path.await?.path;
+        if zip_index(self.archive).await?.get(path) != Some(&ZipEntryKind::File) {
+            return Ok(FileContent::NotFound.cell());
+        }
+        let Some(mut zip) = read_archive(self.archive).await? else {
+            return Ok(FileContent::NotFound.cell());
+        };
+        let mut file = zip.by_name(path)?;
+        let mut content = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut content)?;
+        Ok(File::from(content).into())
+    }
+
+    #[turbo_tasks::function]
+    fn read_link(&self, _path: FileSystemPathVc) -> LinkContentVc {
+        LinkContent::NotFound.cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn read_dir(&self, path: FileSystemPathVc) -> Result<DirectoryContentVc> {
+        let path_value = path.await?;
+        let index = zip_index(self.archive).await?;
+        if index.get(&path_value.path) != Some(&ZipEntryKind::Directory) {
+            return Ok(DirectoryContent::NotFound.cell());
+        }
+        let prefix = if path_value.path.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", path_value.path)
+        };
+        let entries = index
+            .range(prefix.clone()..)
+            .take_while(|(entry_path, _)| entry_path.starts_with(&prefix))
+            .filter_map(|(entry_path, kind)| {
+                let name = &entry_path[prefix.len()..];
+                if name.is_empty() || name.contains('/') {
+                    return None;
+                }
+                let entry_path = path.join(name);
+                Some((
+                    name.to_string(),
+                    match kind {
+                        ZipEntryKind::File => DirectoryEntry::File(entry_path),
+                        ZipEntryKind::Directory => DirectoryEntry::Directory(entry_path),
+                    },
+                ))
+            })
+            .collect();
+        Ok(DirectoryContentVc::new(entries))
+    }
+
+    #[turbo_tasks::function]
+    fn track(&self, _path: FileSystemPathVc) -> CompletionVc {
+        self.archive.track()
+    }
+
+    #[turbo_tasks::function]
+    fn write(&self, _path: FileSystemPathVc, _content: FileContentVc) -> Result<CompletionVc> {
+        bail!("Writing is not possible to a zip archive")
+    }
+
+    #[turbo_tasks::function]
+    fn write_link(&self, _path: FileSystemPathVc, _target: LinkContentVc) -> Result<CompletionVc> {
+        bail!("Writing is not possible to a zip archive")
+    }
+
+    #[turbo_tasks::function]
+    async fn metadata(&self, path: FileSystemPathVc) -> Result<FileMetaVc> {
+        if !zip_index(self.archive)
+            .await?
+            .contains_key(&path.await?.path)
+        {
+            bail!("path not found, can't read metadata");
+        }
+        Ok(FileMeta::default().cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ZipFileSystem {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(format!(
+            "zip {}",
+            self.archive.to_string().await?
+        )))
+    }
+}
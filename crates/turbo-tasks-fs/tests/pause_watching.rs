@@ -0,0 +1,66 @@
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use tempfile::TempDir;
+use turbo_tasks::TurboTasks;
+use turbo_tasks_fs::{
+    pause_watching, register, resume_watching, DiskFileSystemVc, FileContent, FileSystem,
+};
+use turbo_tasks_memory::MemoryBackend;
+
+/// Reads `file.txt` in `dir` through a watched disk file system.
+async fn read(tt: &TurboTasks<MemoryBackend>, dir: &Path) -> Result<String> {
+    let root = dir.to_str().unwrap().to_string();
+    tt.run_once(async move {
+        let disk_fs = DiskFileSystemVc::new("test".to_string(), root);
+        disk_fs.await?.start_watching()?;
+        match &*disk_fs.root().join("file.txt").read().await? {
+            FileContent::Content(file) => Ok(file.content().to_str()?.into_owned()),
+            FileContent::NotFound => Err(anyhow!("file.txt was not found")),
+        }
+    })
+    .await
+}
+
+/// Reads `file.txt` until it has the `expected` content.
+async fn wait_for(tt: &TurboTasks<MemoryBackend>, dir: &Path, expected: &str) -> String {
+    let start = Instant::now();
+    loop {
+        let content = read(tt, dir).await.unwrap();
+        if content == expected || start.elapsed() > Duration::from_secs(10) {
+            return content;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+#[tokio::test]
+async fn changes_are_queued_while_paused_and_applied_on_resume() {
+    register();
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("file.txt");
+    fs::write(&file, "before").unwrap();
+    let tt = TurboTasks::new(MemoryBackend::default());
+
+    assert_eq!(read(&tt, dir.path()).await.unwrap(), "before");
+
+    pause_watching();
+    fs::write(&file, "while paused").unwrap();
+    // Give the watcher time to pick up the change, which it only queues.
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(read(&tt, dir.path()).await.unwrap(), "before");
+
+    resume_watching();
+    assert_eq!(
+        wait_for(&tt, dir.path(), "while paused").await,
+        "while paused"
+    );
+
+    // Changes are applied right away again after resuming.
+    fs::write(&file, "after").unwrap();
+    assert_eq!(wait_for(&tt, dir.path(), "after").await, "after");
+}
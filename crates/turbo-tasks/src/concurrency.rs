@@ -0,0 +1,104 @@
+//! Limits for how many operations of a [ConcurrencyClass] run at the same
+//! time.
+//!
+//! Tasks run with a lot of parallelism, which is fine for computations, but
+//! operations that use OS resources can exhaust them, e.g. reading thousands
+//! of files at once during a cold build hits the limit of open file
+//! descriptors. Such operations acquire a [ConcurrencyPermit] of their class
+//! first.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use anyhow::{bail, Result};
+use once_cell::sync::OnceCell;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConcurrencyClass {
+    /// Spawning a process and waiting for it to be ready.
+    ProcessSpawn,
+    /// Reading a file, a directory or a link from the file system.
+    FsRead,
+}
+
+struct Limit {
+    limit: AtomicUsize,
+    semaphore: OnceCell<Arc<Semaphore>>,
+}
+
+impl Limit {
+    const fn new(limit: usize) -> Self {
+        Limit {
+            limit: AtomicUsize::new(limit),
+            semaphore: OnceCell::new(),
+        }
+    }
+
+    fn semaphore(&self) -> &Arc<Semaphore> {
+        self.semaphore
+            .get_or_init(|| Arc::new(Semaphore::new(self.limit.load(Ordering::Acquire))))
+    }
+}
+
+/// Spawning processes is mostly limited by the CPU time their startup takes.
+/// `0` means that the number of CPUs is used.
+static PROCESS_SPAWN: Limit = Limit::new(0);
+/// Low enough to stay below the default limit of open file descriptors on
+/// macOS (256), which also needs room for sockets and watchers.
+static FS_READ: Limit = Limit::new(64);
+
+impl ConcurrencyClass {
+    fn limit(&self) -> &'static Limit {
+        match self {
+            ConcurrencyClass::ProcessSpawn => &PROCESS_SPAWN,
+            ConcurrencyClass::FsRead => &FS_READ,
+        }
+    }
+
+    /// Sets the maximum number of concurrent operations of this class. This
+    /// needs to happen before the first operation of this class, i.e. when
+    /// setting up turbo-tasks.
+    pub fn set_limit(&self, limit: usize) -> Result<()> {
+        if limit == 0 {
+            bail!("the concurrency limit of {:?} must be at least 1", self);
+        }
+        let state = self.limit();
+        if state.semaphore.get().is_some() {
+            bail!(
+                "the concurrency limit of {:?} can't be changed after it has been used",
+                self
+            );
+        }
+        state.limit.store(limit, Ordering::Release);
+        Ok(())
+    }
+
+    /// Waits until an operation of this class can start. The operation may run
+    /// as long as the returned permit is alive.
+    pub async fn acquire(&self) -> ConcurrencyPermit {
+        let state = self.limit();
+        if state.semaphore.get().is_none() && state.limit.load(Ordering::Acquire) == 0 {
+            let cpus = std::thread::available_parallelism().map_or(1, |cpus| cpus.get());
+            // Another thread might have stored a limit in the meantime.
+            let _ = state
+                .limit
+                .compare_exchange(0, cpus, Ordering::AcqRel, Ordering::Acquire);
+        }
+        let permit = state
+            .semaphore()
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("concurrency semaphores are never closed");
+        ConcurrencyPermit { _permit: permit }
+    }
+}
+
+/// Allows an operation of a [ConcurrencyClass] to run, see
+/// [ConcurrencyClass::acquire].
+pub struct ConcurrencyPermit {
+    _permit: OwnedSemaphorePermit,
+}
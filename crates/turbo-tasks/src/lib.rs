@@ -37,6 +37,7 @@
 pub mod backend;
 mod collectibles;
 mod completion;
+pub mod concurrency;
 pub mod debug;
 mod display;
 pub mod event;
@@ -436,7 +436,7 @@ impl<B: Backend + 'static> TurboTasks<B> {
         self.begin_primary_job();
         self.scheduled_tasks.fetch_add(1, Ordering::AcqRel);
 
-        #[cfg(feature = "tokio_tracing")]
+        #[cfg(any(feature = "tokio_tracing", feature = "tracing_spans"))]
         let description = self.backend.get_task_description(task_id);
 
         let this = self.pin();
@@ -495,6 +495,12 @@ impl<B: Backend + 'static> TurboTasks<B> {
             CURRENT_TASK_ID.scope(task_id, self.backend.execution_scope(task_id, future)),
         );
 
+        #[cfg(feature = "tracing_spans")]
+        let future = tracing::Instrument::instrument(
+            future,
+            tracing::info_span!("turbo_tasks::execute", task = %description),
+        );
+
         #[cfg(feature = "tokio_tracing")]
         tokio::task::Builder::new()
             .name(&description)
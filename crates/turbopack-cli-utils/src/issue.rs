@@ -166,6 +166,9 @@ pub fn format_issue(
     if !description.is_empty() {
         writeln!(styled_issue, "\n{description}").unwrap();
     }
+    for suggestion in &plain_issue.suggestions {
+        writeln!(styled_issue, "suggestion: {}", suggestion.title).unwrap();
+    }
 
     if log_detail {
         styled_issue.push('\n');
@@ -426,6 +429,9 @@ impl IssueReporter for ConsoleUi {
             if !description.is_empty() {
                 writeln!(&mut styled_issue, "\n{description}")?;
             }
+            for suggestion in &plain_issue.suggestions {
+                writeln!(&mut styled_issue, "suggestion: {}", suggestion.title)?;
+            }
 
             if log_detail {
                 styled_issue.push('\n');
@@ -3,6 +3,8 @@
 #![feature(round_char_boundary)]
 
 pub mod issue;
+#[cfg(feature = "otlp")]
+pub mod otlp;
 pub mod source_context;
 
 pub fn register() {
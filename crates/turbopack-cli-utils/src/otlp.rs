@@ -0,0 +1,52 @@
+//! Exports `tracing` spans to an OpenTelemetry collector via OTLP.
+//!
+//! This covers the dev server's request spans and, with the `tracing_spans`
+//! feature of turbo-tasks, the execution of every task, so the performance of
+//! builds and dev servers can be monitored with existing observability stacks.
+
+use anyhow::{Context, Result};
+use opentelemetry::{
+    global,
+    sdk::{trace, Resource},
+    KeyValue,
+};
+use opentelemetry_otlp::WithExportConfig;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+/// Flushes the spans which haven't been exported yet when dropped.
+#[must_use]
+pub struct OtlpExporterGuard(());
+
+impl Drop for OtlpExporterGuard {
+    fn drop(&mut self) {
+        global::shutdown_tracer_provider();
+    }
+}
+
+/// Installs a global `tracing` subscriber which exports spans for
+/// `service_name` to the collector at `endpoint`. Without an `endpoint`, the
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` environment variable or the OTLP default is
+/// used.
+///
+/// Needs to be called from within a tokio runtime, which the spans are
+/// exported on in batches.
+pub fn init_otlp_exporter(service_name: &str, endpoint: Option<&str>) -> Result<OtlpExporterGuard> {
+    let mut exporter = opentelemetry_otlp::new_exporter().tonic().with_env();
+    if let Some(endpoint) = endpoint {
+        exporter = exporter.with_endpoint(endpoint);
+    }
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(exporter)
+        .with_trace_config(trace::config().with_resource(Resource::new([KeyValue::new(
+            "service.name",
+            service_name.to_string(),
+        )])))
+        .install_batch(opentelemetry::runtime::Tokio)
+        .context("installing the OTLP exporter")?;
+    tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(tracer))
+        .try_init()
+        .context("installing the tracing subscriber")?;
+    Ok(OtlpExporterGuard(()))
+}
@@ -1,3 +1,5 @@
+use std::time::Instant;
+
 use anyhow::Result;
 use indexmap::IndexSet;
 use turbo_tasks::CompletionVc;
@@ -7,6 +9,7 @@ use turbo_tasks_fs::{
 };
 
 use crate::{
+    compile_report::{self, CompilePhase},
     ident::AssetIdentVc,
     reference::AssetReferencesVc,
     version::{VersionedAssetContentVc, VersionedContentVc},
@@ -128,7 +131,8 @@ impl AssetContentVc {
     #[turbo_tasks::function]
     pub async fn write(self, path: FileSystemPathVc) -> Result<CompletionVc> {
         let this = self.await?;
-        Ok(match &*this {
+        let start = Instant::now();
+        let completion = match &*this {
             AssetContent::File(file) => path.write(*file),
             AssetContent::Redirect { target, link_type } => path.write_link(
                 LinkContent::Link {
@@ -137,6 +141,12 @@ impl AssetContentVc {
                 }
                 .cell(),
             ),
-        })
+        };
+        if compile_report::is_collecting() {
+            completion.await?;
+            compile_report::record(CompilePhase::Emit, None, start.elapsed());
+            compile_report::record_emitted_asset();
+        }
+        Ok(completion)
     }
 }
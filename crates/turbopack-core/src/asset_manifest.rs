@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{CompletionVc, TryJoinIterExt, Value};
+use turbo_tasks_fs::{File, FileContent, FileSystemPathVc};
+use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64};
+
+use crate::{
+    asset::{Asset, AssetContent, AssetVc},
+    reference::all_assets,
+};
+
+/// The file the content hashes of a build are written to, within the output
+/// directory.
+pub const ASSET_MANIFEST: &str = "asset-manifest.json";
+
+/// The file the assets that changed since the previous build are written to,
+/// within the output directory.
+pub const CHANGED_ASSETS: &str = "changed-assets.json";
+
+/// The content hashes of the emitted assets, by their path relative to the
+/// output directory.
+#[turbo_tasks::value(shared, serialization = "auto_for_input")]
+#[derive(Debug, Clone, Default, Hash, PartialOrd, Ord)]
+pub struct AssetManifest {
+    pub assets: BTreeMap<String, String>,
+}
+
+impl AssetManifest {
+    /// Parses the [ASSET_MANIFEST] of a previous build.
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// The assets that differ from the `previous` build.
+    pub fn changes_since(&self, previous: &AssetManifest) -> ChangedAssets {
+        let mut changes = ChangedAssets::default();
+        for (path, hash) in &self.assets {
+            match previous.assets.get(path) {
+                Some(previous_hash) if previous_hash == hash => {}
+                Some(_) => changes.changed.push(path.clone()),
+                None => changes.added.push(path.clone()),
+            }
+        }
+        changes.removed = previous
+            .assets
+            .keys()
+            .filter(|path| !self.assets.contains_key(*path))
+            .cloned()
+            .collect();
+        changes
+    }
+}
+
+/// The contents of [CHANGED_ASSETS]. Caches need to be purged for the
+/// `changed` and `removed` paths, while `added` paths can't be cached yet.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangedAssets {
+    pub changed: Vec<String>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Computes the content hashes of all assets referenced from `asset` that are
+/// emitted into `output_dir`.
+#[turbo_tasks::function]
+pub async fn asset_manifest(
+    asset: AssetVc,
+    output_dir: FileSystemPathVc,
+) -> Result<AssetManifestVc> {
+    let output_dir = output_dir.await?;
+    let hashes = all_assets(asset)
+        .await?
+        .iter()
+        .map(|asset| {
+            let output_dir = &output_dir;
+            async move {
+                let path = asset.ident().path().await?;
+                let Some(relative) = output_dir.get_path_to(&path) else {
+                    return Ok(None);
+                };
+                let AssetContent::File(content) = &*asset.content().await? else {
+                    return Ok(None);
+                };
+                let FileContent::Content(file) = &*content.await? else {
+                    return Ok(None);
+                };
+                let hash = encode_hex(hash_xxh3_hash64(file.content()));
+                Ok(Some((relative.to_string(), hash)))
+            }
+        })
+        .try_join()
+        .await?;
+    Ok(AssetManifest {
+        assets: hashes.into_iter().flatten().collect(),
+    }
+    .cell())
+}
+
+/// Writes the [ASSET_MANIFEST] of the assets emitted for `asset` and the
+/// [CHANGED_ASSETS] compared to the `previous` manifest, so deploy scripts can
+/// purge only the CDN paths that were invalidated.
+///
+/// The previous manifest needs to be read before the build starts, since the
+/// new one replaces it. An empty manifest reports all assets as added.
+#[turbo_tasks::function]
+pub async fn emit_asset_manifests(
+    asset: AssetVc,
+    output_dir: FileSystemPathVc,
+    previous: Value<AssetManifest>,
+) -> Result<CompletionVc> {
+    let manifest = asset_manifest(asset, output_dir).await?;
+    let changes = manifest.changes_since(&previous);
+    let files = [
+        (ASSET_MANIFEST, serde_json::to_string_pretty(&*manifest)?),
+        (CHANGED_ASSETS, serde_json::to_string_pretty(&changes)?),
+    ];
+    files
+        .into_iter()
+        .map(|(name, json)| {
+            output_dir
+                .join(name)
+                .write(FileContent::Content(File::from(json)).cell())
+        })
+        .try_join()
+        .await?;
+    Ok(CompletionVc::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AssetManifest, ChangedAssets};
+
+    fn manifest(assets: &[(&str, &str)]) -> AssetManifest {
+        AssetManifest {
+            assets: assets
+                .iter()
+                .map(|(path, hash)| (path.to_string(), hash.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn changes_since_previous_build() {
+        let previous = manifest(&[("a.js", "1"), ("b.js", "2"), ("c.css", "3")]);
+        let current = manifest(&[("a.js", "1"), ("b.js", "4"), ("d.png", "5")]);
+        assert_eq!(
+            current.changes_since(&previous),
+            ChangedAssets {
+                changed: vec!["b.js".to_string()],
+                added: vec!["d.png".to_string()],
+                removed: vec!["c.css".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn parses_previous_manifest() {
+        let previous = AssetManifest::from_json(r#"{"assets":{"a.js":"1"}}"#).unwrap();
+        assert_eq!(previous, manifest(&[("a.js", "1")]));
+    }
+}
@@ -0,0 +1,424 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fmt::Write,
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{primitives::StringVc, trace::TraceRawVcs, CompletionVc, TryJoinIterExt};
+use turbo_tasks_fs::{File, FileContent, FileSystemPathVc};
+
+use crate::{
+    asset::{Asset, AssetContent, AssetVc},
+    chunk::{Chunk, ChunkGroupVc, ChunkVc},
+    duplicate_packages::format_size,
+    issue::{Issue, IssueSeverity, IssueSeverityVc, IssueVc},
+    reference::all_assets,
+};
+
+/// The file the [BundleStats] of a build are written to, within the output
+/// directory.
+pub const BUNDLE_STATS: &str = "bundle-stats.json";
+
+/// The sizes of the chunks of an entry, in bytes.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    TraceRawVcs,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryStats {
+    /// The JavaScript chunks that are loaded with the entry.
+    pub initial_js: u64,
+    /// The CSS chunks that are loaded with the entry.
+    pub initial_css: u64,
+    /// The chunks that are loaded on demand, e.g. by `import()`.
+    pub async_bytes: u64,
+    /// The initial JavaScript chunks of modules in `node_modules`.
+    pub third_party: u64,
+}
+
+impl EntryStats {
+    /// The share of the initial JavaScript that's third-party code, between
+    /// 0 and 1.
+    pub fn third_party_share(&self) -> f64 {
+        if self.initial_js == 0 {
+            0.0
+        } else {
+            self.third_party as f64 / self.initial_js as f64
+        }
+    }
+}
+
+/// The [EntryStats] of the entries of a build, by the path of their entry
+/// chunk relative to the output directory.
+///
+/// The stats are meant to be compared between builds, e.g. to enforce a
+/// bundle budget in CI, see [BundleStats::compare].
+#[turbo_tasks::value(shared, serialization = "auto_for_input")]
+#[derive(Debug, Clone, Default, Hash, PartialOrd, Ord)]
+pub struct BundleStats {
+    pub entries: BTreeMap<String, EntryStats>,
+}
+
+impl BundleStats {
+    /// Parses the [BUNDLE_STATS] of a build.
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Compares the stats to those of the `base` build, e.g. of the target
+    /// branch of a pull request.
+    pub fn compare(&self, base: &BundleStats) -> BundleStatsDiff {
+        let names: BTreeSet<&String> = self.entries.keys().chain(base.entries.keys()).collect();
+        BundleStatsDiff {
+            entries: names
+                .into_iter()
+                .map(|name| EntryStatsDiff {
+                    name: name.clone(),
+                    base: base.entries.get(name).copied(),
+                    current: self.entries.get(name).copied(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The difference of the stats of an entry between two builds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryStatsDiff {
+    pub name: String,
+    /// The stats of the base build, unless the entry is new.
+    pub base: Option<EntryStats>,
+    /// The stats of the current build, unless the entry was removed.
+    pub current: Option<EntryStats>,
+}
+
+impl EntryStatsDiff {
+    /// Whether any size of the entry changed.
+    pub fn is_changed(&self) -> bool {
+        self.base != self.current
+    }
+
+    /// The change of the initial JavaScript and CSS, in bytes.
+    pub fn initial_delta(&self) -> i64 {
+        self.delta(|stats| stats.initial_js + stats.initial_css)
+    }
+
+    fn delta(&self, size: impl Fn(&EntryStats) -> u64) -> i64 {
+        let total = |stats: Option<EntryStats>| stats.as_ref().map_or(0, &size) as i64;
+        total(self.current) - total(self.base)
+    }
+}
+
+/// The result of [BundleStats::compare], with an entry for every entry of
+/// either build.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BundleStatsDiff {
+    pub entries: Vec<EntryStatsDiff>,
+}
+
+impl BundleStatsDiff {
+    /// The entries whose sizes changed.
+    pub fn changed(&self) -> impl Iterator<Item = &EntryStatsDiff> {
+        self.entries.iter().filter(|entry| entry.is_changed())
+    }
+
+    /// Renders the changed entries as a markdown table, e.g. for a pull
+    /// request comment.
+    pub fn to_markdown(&self) -> String {
+        if self.changed().next().is_none() {
+            return "No changes to the bundle sizes.\n".to_string();
+        }
+        let mut markdown = String::from(
+            "| Entry | Initial JS | Initial CSS | Async | Third-party |\n| --- | --: | --: | --: \
+             | --: |\n",
+        );
+        for entry in self.changed() {
+            let name = match (&entry.base, &entry.current) {
+                (None, _) => format!("`{}` (new)", entry.name),
+                (_, None) => format!("`{}` (removed)", entry.name),
+                _ => format!("`{}`", entry.name),
+            };
+            let stats = entry.current.unwrap_or_default();
+            let share = |stats: Option<EntryStats>| stats.map_or(0.0, |s| s.third_party_share());
+            let share_delta = (share(entry.current) - share(entry.base)) * 100.0;
+            // Writing to a String can't fail.
+            let _ = writeln!(
+                markdown,
+                "| {name} | {} | {} | {} | {:.1}%{} |",
+                format_size_with_delta(stats.initial_js, entry.delta(|s| s.initial_js)),
+                format_size_with_delta(stats.initial_css, entry.delta(|s| s.initial_css)),
+                format_size_with_delta(stats.async_bytes, entry.delta(|s| s.async_bytes)),
+                stats.third_party_share() * 100.0,
+                if share_delta.abs() < 0.05 {
+                    String::new()
+                } else {
+                    format!(" ({share_delta:+.1} pp)")
+                },
+            );
+        }
+        markdown
+    }
+}
+
+fn format_size_with_delta(size: u64, delta: i64) -> String {
+    match delta {
+        0 => format_size(size),
+        delta if delta > 0 => format!("{} (+{})", format_size(size), format_size(delta as u64)),
+        delta => format!(
+            "{} (-{})",
+            format_size(size),
+            format_size(delta.unsigned_abs())
+        ),
+    }
+}
+
+/// Computes the [BundleStats] of the entries of `chunk_groups` that are
+/// emitted into `output_dir`.
+///
+/// Async chunks are the chunks reachable from the initial chunks that aren't
+/// initial chunks themselves. Chunks count as third-party when their modules
+/// are in a `node_modules` directory.
+#[turbo_tasks::function]
+pub async fn bundle_stats(
+    chunk_groups: Vec<ChunkGroupVc>,
+    output_dir: FileSystemPathVc,
+) -> Result<BundleStatsVc> {
+    let output_dir = output_dir.await?;
+    let entries = chunk_groups
+        .into_iter()
+        .map(|chunk_group| {
+            let output_dir = &output_dir;
+            async move {
+                let entry_path = chunk_group.entry().path().await?;
+                let name = output_dir
+                    .get_path_to(&entry_path)
+                    .unwrap_or(&entry_path.path)
+                    .to_string();
+                Ok((name, entry_stats(chunk_group).await?))
+            }
+        })
+        .try_join()
+        .await?;
+    Ok(BundleStats {
+        entries: entries.into_iter().collect(),
+    }
+    .cell())
+}
+
+async fn entry_stats(chunk_group: ChunkGroupVc) -> Result<EntryStats> {
+    let mut stats = EntryStats::default();
+    let initial_chunks = chunk_group.chunks().await?;
+    let mut initial_paths = HashSet::new();
+    for &chunk in initial_chunks.iter() {
+        let path = chunk.path().await?;
+        let size = asset_size(chunk.as_asset()).await?;
+        match path.extension() {
+            Some("css") => stats.initial_css += size,
+            _ => {
+                stats.initial_js += size;
+                if is_third_party(&chunk.ident().path().await?.path) {
+                    stats.third_party += size;
+                }
+            }
+        }
+        initial_paths.insert(path.path.clone());
+    }
+
+    let reachable = initial_chunks
+        .iter()
+        .map(|&chunk| all_assets(chunk.as_asset()))
+        .try_join()
+        .await?;
+    let mut async_chunks = HashSet::new();
+    for &asset in reachable.iter().flat_map(|assets| assets.iter()) {
+        let Some(chunk) = ChunkVc::resolve_from(asset).await? else {
+            continue;
+        };
+        let path = chunk.path().await?;
+        if !initial_paths.contains(&path.path) && async_chunks.insert(path.path.clone()) {
+            stats.async_bytes += asset_size(asset).await?;
+        }
+    }
+    Ok(stats)
+}
+
+async fn asset_size(asset: AssetVc) -> Result<u64> {
+    let AssetContent::File(content) = &*asset.content().await? else {
+        return Ok(0);
+    };
+    let FileContent::Content(file) = &*content.await? else {
+        return Ok(0);
+    };
+    Ok(file.content().len() as u64)
+}
+
+fn is_third_party(path: &str) -> bool {
+    path.split('/').any(|segment| segment == "node_modules")
+}
+
+/// Writes the [BUNDLE_STATS] of the entries of `chunk_groups`.
+#[turbo_tasks::function]
+pub async fn emit_bundle_stats(
+    chunk_groups: Vec<ChunkGroupVc>,
+    output_dir: FileSystemPathVc,
+) -> Result<CompletionVc> {
+    let stats = bundle_stats(chunk_groups, output_dir).await?;
+    output_dir
+        .join(BUNDLE_STATS)
+        .write(FileContent::Content(File::from(serde_json::to_string_pretty(&*stats)?)).cell())
+        .await?;
+    Ok(CompletionVc::new())
+}
+
+/// The size of the script of a platform with a script size limit, e.g. a
+/// Cloudflare Worker, which is deployed as a whole.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Copy)]
+pub struct ScriptSize {
+    /// The size of all chunks of the script, including async chunks, since
+    /// they are deployed together, in bytes.
+    pub size: u64,
+    pub limit: u64,
+}
+
+/// Computes the [ScriptSize] of the chunks of `chunk_group` and emits a
+/// [ScriptSizeLimitIssue] when it exceeds `limit` bytes.
+#[turbo_tasks::function]
+pub async fn script_size(chunk_group: ChunkGroupVc, limit: u64) -> Result<ScriptSizeVc> {
+    let stats = entry_stats(chunk_group).await?;
+    let size = stats.initial_js + stats.initial_css + stats.async_bytes;
+    if size > limit {
+        ScriptSizeLimitIssue {
+            path: chunk_group.entry().path(),
+            size,
+            limit,
+        }
+        .cell()
+        .as_issue()
+        .emit();
+    }
+    Ok(ScriptSize { size, limit }.cell())
+}
+
+#[turbo_tasks::value(shared)]
+pub struct ScriptSizeLimitIssue {
+    pub path: FileSystemPathVc,
+    pub size: u64,
+    pub limit: u64,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for ScriptSizeLimitIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("bundle size".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell(format!(
+            "Script is {} larger than the limit of the platform",
+            format_size(self.size - self.limit)
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> StringVc {
+        StringVc::cell(format!(
+            "The script is {}, but the platform only accepts scripts up to {}. Remove large \
+             dependencies or load them from elsewhere at runtime.",
+            format_size(self.size),
+            format_size(self.limit)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_third_party, BundleStats, EntryStats};
+
+    fn stats(entries: &[(&str, EntryStats)]) -> BundleStats {
+        BundleStats {
+            entries: entries
+                .iter()
+                .map(|(name, stats)| (name.to_string(), *stats))
+                .collect(),
+        }
+    }
+
+    fn entry(initial_js: u64, initial_css: u64, async_bytes: u64, third_party: u64) -> EntryStats {
+        EntryStats {
+            initial_js,
+            initial_css,
+            async_bytes,
+            third_party,
+        }
+    }
+
+    #[test]
+    fn parses_stats() {
+        let parsed = BundleStats::from_json(
+            r#"{"entries":{"main.js":{"initialJs":10,"initialCss":2,"asyncBytes":5,"thirdParty":4}}}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed, stats(&[("main.js", entry(10, 2, 5, 4))]));
+    }
+
+    #[test]
+    fn compares_builds() {
+        let base = stats(&[
+            ("a.js", entry(1000, 0, 0, 500)),
+            ("b.js", entry(100, 0, 0, 0)),
+            ("c.js", entry(200, 0, 0, 0)),
+        ]);
+        let current = stats(&[
+            ("a.js", entry(2000, 0, 0, 1500)),
+            ("b.js", entry(100, 0, 0, 0)),
+            ("d.js", entry(300, 50, 0, 0)),
+        ]);
+        let diff = current.compare(&base);
+        let changed: Vec<_> = diff.changed().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(changed, ["a.js", "c.js", "d.js"]);
+        assert_eq!(diff.entries[0].initial_delta(), 1000);
+        assert_eq!(diff.entries[2].initial_delta(), -200);
+        assert_eq!(
+            diff.to_markdown(),
+            "| Entry | Initial JS | Initial CSS | Async | Third-party |\n| --- | --: | --: | --: \
+             | --: |\n| `a.js` | 2.0 kB (+1.0 kB) | 0 B | 0 B | 75.0% (+25.0 pp) |\n| `c.js` \
+             (removed) | 0 B (-200 B) | 0 B | 0 B | 0.0% |\n| `d.js` (new) | 300 B (+300 B) | 50 \
+             B (+50 B) | 0 B | 0.0% |\n"
+        );
+        assert_eq!(
+            base.compare(&base).to_markdown(),
+            "No changes to the bundle sizes.\n"
+        );
+    }
+
+    #[test]
+    fn third_party_paths() {
+        assert!(is_third_party("node_modules/react/index.js"));
+        assert!(is_third_party("app/node_modules"));
+        assert!(!is_third_party("src/my_node_modules/a.js"));
+    }
+}
@@ -8,6 +8,7 @@ use crate::{
     ident::AssetIdentVc,
     introspect::{
         asset::{children_from_asset_references, content_to_details, IntrospectableAssetVc},
+        chunk::{ChunkItemInfosVc, IntrospectableChunk, IntrospectableChunkVc},
         Introspectable, IntrospectableChildrenVc, IntrospectableVc,
     },
     reference::AssetReferencesVc,
@@ -78,6 +79,17 @@ impl Asset for ChunkInGroup {
     }
 }
 
+#[turbo_tasks::value_impl]
+impl IntrospectableChunk for ChunkInGroup {
+    #[turbo_tasks::function]
+    async fn chunk_item_infos(&self) -> Result<ChunkItemInfosVc> {
+        let Some(inner) = IntrospectableChunkVc::resolve_from(self.inner).await? else {
+            return Ok(ChunkItemInfosVc::empty())
+        };
+        Ok(inner.chunk_item_infos())
+    }
+}
+
 #[turbo_tasks::value_impl]
 impl GenerateSourceMap for ChunkInGroup {
     #[turbo_tasks::function]
@@ -1,6 +1,6 @@
 use anyhow::Result;
 use indexmap::IndexMap;
-use turbo_tasks::{IntoTraitRef, TryJoinIterExt};
+use turbo_tasks::{primitives::JsonValueVc, IntoTraitRef, TryJoinIterExt};
 use turbo_tasks_fs::{FileContent, FileSystemPathReadRef, FileSystemPathVc};
 
 use super::{
@@ -11,8 +11,9 @@ use crate::{
     asset::{Asset, AssetContent, AssetContentVc},
     chunk::ChunksVc,
     version::{
-        MergeableVersionedContent, MergeableVersionedContentVc, UpdateVc, VersionVc,
-        VersionedContent, VersionedContentMerger, VersionedContentVc, VersionedContentsVc,
+        MergeableVersionedContent, MergeableVersionedContentVc, NotFoundVersionVc, UpdateVc,
+        VersionVc, VersionedContent, VersionedContentMerger, VersionedContentVc,
+        VersionedContentsVc,
     },
 };
 
@@ -116,4 +117,72 @@ impl VersionedContent for ChunkListContent {
     fn update(self_vc: ChunkListContentVc, from_version: VersionVc) -> UpdateVc {
         update_chunk_list(self_vc, from_version)
     }
+
+    #[turbo_tasks::function]
+    async fn restore_version(self_vc: ChunkListContentVc, state: JsonValueVc) -> Result<VersionVc> {
+        let this = self_vc.await?;
+        let state = state.await?;
+        let (Some(by_path_state), Some(by_merger_state)) = (
+            state.get("byPath").and_then(|by_path| by_path.as_object()),
+            state.get("byMerger").and_then(|by_merger| by_merger.as_array()),
+        ) else {
+            return Ok(NotFoundVersionVc::new().into());
+        };
+
+        let mut by_merger_contents = IndexMap::<_, Vec<_>>::new();
+        let mut by_path = IndexMap::<_, _>::new();
+
+        for (chunk_path, chunk_content) in &this.chunks_contents {
+            if let Some(mergeable) =
+                MergeableVersionedContentVc::resolve_from(chunk_content).await?
+            {
+                let merger = mergeable.get_merger().resolve().await?;
+                by_merger_contents
+                    .entry(merger)
+                    .or_default()
+                    .push(*chunk_content);
+            } else if let Some(chunk_state) = by_path_state.get(chunk_path) {
+                by_path.insert(
+                    chunk_path.clone(),
+                    chunk_content
+                        .restore_version(JsonValueVc::cell(chunk_state.clone()))
+                        .into_trait_ref()
+                        .await?,
+                );
+            }
+        }
+
+        // Chunks which were removed in the meantime only need to be known by
+        // their path, so that the update reports them as deleted.
+        for chunk_path in by_path_state.keys() {
+            if !this.chunks_contents.contains_key(chunk_path) {
+                let version: VersionVc = NotFoundVersionVc::new().into();
+                by_path.insert(chunk_path.clone(), version.into_trait_ref().await?);
+            }
+        }
+
+        let mut merger_states = by_merger_state.clone();
+        let mut by_merger = IndexMap::<_, _>::new();
+
+        for (merger, chunks_contents) in by_merger_contents {
+            let content = merger.merge(VersionedContentsVc::cell(chunks_contents));
+            let mut restored = None;
+            for (index, merger_state) in merger_states.iter().enumerate() {
+                let version = content.restore_version(JsonValueVc::cell(merger_state.clone()));
+                if NotFoundVersionVc::resolve_from(version).await?.is_none() {
+                    restored = Some((index, version));
+                    break;
+                }
+            }
+            let Some((index, version)) = restored else {
+                // Updates are only computed for mergers that are part of the
+                // previous version, so the client can't be updated partially.
+                return Ok(NotFoundVersionVc::new().into());
+            };
+            merger_states.remove(index);
+            by_merger.insert(merger, version.into_trait_ref().await?);
+        }
+
+        Ok(ChunkListVersion { by_path, by_merger }.cell().into())
+    }
 }
@@ -1,6 +1,10 @@
 use anyhow::Result;
 use indexmap::IndexMap;
-use turbo_tasks::{primitives::StringVc, TraitRef, TryJoinIterExt};
+use serde_json::json;
+use turbo_tasks::{
+    primitives::{JsonValueVc, StringVc},
+    TraitRef, TryJoinIterExt,
+};
 use turbo_tasks_hash::{encode_hex, Xxh3Hash64Hasher};
 
 use crate::version::{Version, VersionVc, VersionedContentMergerVc};
@@ -61,4 +65,33 @@ impl Version for ChunkListVersion {
         let hex_hash = encode_hex(hash);
         Ok(StringVc::cell(hex_hash))
     }
+
+    #[turbo_tasks::function]
+    async fn state(&self) -> Result<JsonValueVc> {
+        let by_path = self
+            .by_path
+            .iter()
+            .map(|(path, version)| async move {
+                let state = TraitRef::cell(version.clone()).state().await?.clone_value();
+                Ok((path.clone(), state))
+            })
+            .try_join()
+            .await?
+            .into_iter()
+            .collect::<serde_json::Map<_, _>>();
+        // Mergers can't be serialized, so restoring matches merged states with
+        // the current mergers by trying to restore them.
+        let by_merger = self
+            .by_merger
+            .values()
+            .map(|version| async move {
+                Ok(TraitRef::cell(version.clone()).state().await?.clone_value())
+            })
+            .try_join()
+            .await?;
+        Ok(JsonValueVc::cell(json!({
+            "byPath": by_path,
+            "byMerger": by_merger,
+        })))
+    }
 }
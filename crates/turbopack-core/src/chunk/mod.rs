@@ -3,6 +3,7 @@ pub mod available_assets;
 pub mod chunk_in_group;
 pub(crate) mod list;
 pub mod optimize;
+pub mod reserved_ids;
 
 use std::{
     collections::HashSet,
@@ -32,19 +33,25 @@ use self::{
 };
 use crate::{
     asset::{Asset, AssetVc, AssetsVc},
+    compile_report::{measure, CompilePhase},
     environment::EnvironmentVc,
     ident::AssetIdentVc,
+    interned::InternedString,
+    introspect::{
+        asset::IntrospectableAssetVc, Introspectable, IntrospectableChildrenVc, IntrospectableVc,
+    },
     reference::{AssetReference, AssetReferenceVc, AssetReferencesVc},
     resolve::{PrimaryResolveResult, ResolveResult, ResolveResultVc},
 };
 
-/// A module id, which can be a number or string
+/// A module id, which can be a number or string. String ids are interned,
+/// since they are repeated in every chunk and update that contains the module.
 #[turbo_tasks::value(shared)]
 #[derive(Debug, Clone, Hash, Ord, PartialOrd, DeterministicHash)]
 #[serde(untagged)]
 pub enum ModuleId {
     Number(u32),
-    String(String),
+    String(InternedString),
 }
 
 impl Display for ModuleId {
@@ -68,7 +75,7 @@ impl ModuleId {
     pub fn parse(id: &str) -> Result<ModuleId> {
         Ok(match id.parse::<u32>() {
             Ok(i) => ModuleId::Number(i),
-            Err(_) => ModuleId::String(id.to_string()),
+            Err(_) => ModuleId::String(id.into()),
         })
     }
 }
@@ -100,6 +107,11 @@ pub trait ChunkingContext {
 
     fn asset_path(&self, content_hash: &str, extension: &str) -> FileSystemPathVc;
 
+    /// The id of the module with `ident`. Readable ids are used by default.
+    async fn module_id(&self, ident: AssetIdentVc) -> Result<ModuleIdVc> {
+        Ok(ModuleId::String(ident.to_string().await?.as_str().into()).cell())
+    }
+
     fn is_hot_module_replacement_enabled(&self) -> BoolVc {
         BoolVc::cell(false)
     }
@@ -241,6 +253,41 @@ impl ValueToString for ChunkGroup {
     }
 }
 
+#[turbo_tasks::function]
+fn chunk_group_ty() -> StringVc {
+    StringVc::cell("chunk group".to_string())
+}
+
+#[turbo_tasks::function]
+fn chunk_key() -> StringVc {
+    StringVc::cell("chunk".to_string())
+}
+
+#[turbo_tasks::value_impl]
+impl Introspectable for ChunkGroup {
+    #[turbo_tasks::function]
+    fn ty(&self) -> StringVc {
+        chunk_group_ty()
+    }
+
+    #[turbo_tasks::function]
+    fn title(self_vc: ChunkGroupVc) -> StringVc {
+        self_vc.to_string()
+    }
+
+    #[turbo_tasks::function]
+    async fn children(self_vc: ChunkGroupVc) -> Result<IntrospectableChildrenVc> {
+        Ok(IntrospectableChildrenVc::cell(
+            self_vc
+                .chunks()
+                .await?
+                .iter()
+                .map(|chunk| (chunk_key(), IntrospectableAssetVc::new(chunk.as_asset())))
+                .collect(),
+        ))
+    }
+}
+
 /// A chunk is one type of asset.
 /// It usually contains multiple chunk items.
 /// There is an optional trait [ParallelChunkReference] that
@@ -274,7 +321,17 @@ pub trait ParallelChunkReference: AssetReference + ValueToString {
 /// Specifies how a chunk interacts with other chunks when building a chunk
 /// group
 #[derive(
-    Copy, Default, Clone, Hash, TraceRawVcs, Serialize, Deserialize, Eq, PartialEq, ValueDebugFormat,
+    Copy,
+    Default,
+    Clone,
+    Hash,
+    Debug,
+    TraceRawVcs,
+    Serialize,
+    Deserialize,
+    Eq,
+    PartialEq,
+    ValueDebugFormat,
 )]
 pub enum ChunkingType {
     /// Asset is always placed into the referencing chunk and loaded with it.
@@ -297,6 +354,35 @@ pub enum ChunkingType {
     /// An async loader is placed into the referencing chunk and loads the
     /// separate chunk group in which the asset is placed.
     SeparateAsync,
+    /// Like [ChunkingType::SeparateAsync], but the chunks of the separate
+    /// chunk group are prefetched before the asset is loaded.
+    SeparateAsyncPrefetch(ChunkPrefetch),
+}
+
+/// When the chunks of an async chunk group are prefetched, i.e. fetched
+/// without being executed, ahead of the import that loads them.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(PartialOrd, Ord, Hash, Debug, Copy, Clone)]
+pub enum ChunkPrefetch {
+    /// When the referencing chunk is evaluated. HTML pages which load the
+    /// referencing chunk also announce the chunks with `<link
+    /// rel="prefetch">`.
+    Eager,
+    /// When the browser is idle after the referencing chunk is evaluated.
+    Idle,
+    /// When the page is visible after the referencing chunk is evaluated, so
+    /// pages opened in background tabs don't compete for bandwidth.
+    Visible,
+}
+
+impl ChunkPrefetch {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChunkPrefetch::Eager => "eager",
+            ChunkPrefetch::Idle => "idle",
+            ChunkPrefetch::Visible => "visible",
+        }
+    }
 }
 
 #[turbo_tasks::value(transparent)]
@@ -315,11 +401,13 @@ pub trait ChunkableAssetReference: AssetReference + ValueToString {
     }
 }
 
-/// A reference to a [Chunk]. Can be loaded in parallel, see [Chunk].
+/// A reference to a [Chunk]. Can be loaded in parallel, see [Chunk], or be
+/// prefetched eagerly, see [ChunkPrefetch::Eager].
 #[turbo_tasks::value]
 pub struct ChunkReference {
     chunk: ChunkVc,
     parallel: bool,
+    prefetch: bool,
 }
 
 #[turbo_tasks::value_impl]
@@ -329,6 +417,7 @@ impl ChunkReferenceVc {
         Self::cell(ChunkReference {
             chunk,
             parallel: false,
+            prefetch: false,
         })
     }
 
@@ -337,8 +426,24 @@ impl ChunkReferenceVc {
         Self::cell(ChunkReference {
             chunk,
             parallel: true,
+            prefetch: false,
         })
     }
+
+    #[turbo_tasks::function]
+    pub fn new_prefetch(chunk: ChunkVc) -> Self {
+        Self::cell(ChunkReference {
+            chunk,
+            parallel: false,
+            prefetch: true,
+        })
+    }
+
+    /// Whether the chunk is prefetched eagerly by the referencing chunk.
+    #[turbo_tasks::function]
+    pub async fn is_prefetched(self) -> Result<BoolVc> {
+        Ok(BoolVc::cell(self.await?.prefetch))
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -423,6 +528,7 @@ pub trait FromChunkableAsset: ChunkItem + Sized + Debug {
         context: ChunkingContextVc,
         asset: ChunkableAssetVc,
         availability_info: Value<AvailabilityInfo>,
+        prefetch: Option<ChunkPrefetch>,
     ) -> Result<Option<Self>>;
 }
 
@@ -435,9 +541,19 @@ pub async fn chunk_content_split<I>(
 where
     I: FromChunkableAsset + Eq + std::hash::Hash + Clone,
 {
-    chunk_content_internal_parallel(context, entry, additional_entries, availability_info, true)
-        .await
-        .map(|o| o.unwrap())
+    measure(
+        CompilePhase::Chunk,
+        None,
+        chunk_content_internal_parallel(
+            context,
+            entry,
+            additional_entries,
+            availability_info,
+            true,
+        ),
+    )
+    .await
+    .map(|o| o.unwrap())
 }
 
 pub async fn chunk_content<I>(
@@ -449,8 +565,18 @@ pub async fn chunk_content<I>(
 where
     I: FromChunkableAsset + Eq + std::hash::Hash + Clone,
 {
-    chunk_content_internal_parallel(context, entry, additional_entries, availability_info, false)
-        .await
+    measure(
+        CompilePhase::Chunk,
+        None,
+        chunk_content_internal_parallel(
+            context,
+            entry,
+            additional_entries,
+            availability_info,
+            false,
+        ),
+    )
+    .await
 }
 
 #[derive(Eq, PartialEq, Clone, Hash)]
@@ -475,35 +601,93 @@ struct ChunkContentContext {
     split: bool,
 }
 
+/// A reference of a chunk item, reduced to what chunking needs to know about
+/// it.
+#[derive(PartialEq, Eq, Clone, TraceRawVcs, Serialize, Deserialize, ValueDebugFormat)]
+enum ChunkingEdge {
+    /// A reference to assets that are chunked according to `chunking_type`.
+    Chunkable {
+        reference: AssetReferenceVc,
+        chunking_type: ChunkingType,
+        assets: Vec<AssetVc>,
+    },
+    /// A reference that isn't chunked, but exposed as an external reference of
+    /// the chunk.
+    External(AssetReferenceVc),
+}
+
+#[turbo_tasks::value(transparent)]
+struct ChunkingEdges(Vec<ChunkingEdge>);
+
+/// Computes the [ChunkingEdge]s of the references of a chunk item.
+///
+/// This is a separate task for every chunk item, so chunk content only
+/// depends on a single cell per chunk item. Editing a module re-runs this task
+/// for the module, but chunking is only invalidated when its edges actually
+/// change, not when only its content does.
+#[turbo_tasks::function]
+async fn chunking_edges(references: AssetReferencesVc) -> Result<ChunkingEdgesVc> {
+    let edges = references
+        .await?
+        .iter()
+        .map(|&reference| async move {
+            let Some(chunkable_asset_reference) =
+                ChunkableAssetReferenceVc::resolve_from(reference).await? else {
+                return Ok(ChunkingEdge::External(reference));
+            };
+
+            let Some(chunking_type) = *chunkable_asset_reference.chunking_type().await? else {
+                return Ok(ChunkingEdge::External(reference));
+            };
+
+            let assets = reference
+                .resolve_reference()
+                .await?
+                .primary
+                .iter()
+                .filter_map(|result| {
+                    if let PrimaryResolveResult::Asset(asset) = *result {
+                        return Some(asset);
+                    }
+                    None
+                })
+                .collect();
+
+            Ok(ChunkingEdge::Chunkable {
+                reference,
+                chunking_type,
+                assets,
+            })
+        })
+        .try_join()
+        .await?;
+    Ok(ChunkingEdgesVc::cell(edges))
+}
+
 async fn reference_to_graph_nodes<I>(
     context: ChunkContentContext,
-    reference: AssetReferenceVc,
+    edge: &ChunkingEdge,
 ) -> Result<Vec<(Option<(AssetVc, ChunkingType)>, ChunkContentGraphNode<I>)>>
 where
     I: FromChunkableAsset + Eq + std::hash::Hash + Clone,
 {
-    let Some(chunkable_asset_reference) = ChunkableAssetReferenceVc::resolve_from(reference).await? else {
-        return Ok(vec![(None, ChunkContentGraphNode::ExternalAssetReference(reference))]);
-    };
-
-    let Some(chunking_type) = *chunkable_asset_reference.chunking_type().await? else {
-        return Ok(vec![(None, ChunkContentGraphNode::ExternalAssetReference(reference))]);
-    };
-
-    let result = reference.resolve_reference().await?;
-
-    let assets = result.primary.iter().filter_map({
-        |result| {
-            if let PrimaryResolveResult::Asset(asset) = *result {
-                return Some(asset);
-            }
-            None
+    let (reference, chunking_type, assets) = match edge {
+        ChunkingEdge::Chunkable {
+            reference,
+            chunking_type,
+            assets,
+        } => (*reference, *chunking_type, assets),
+        ChunkingEdge::External(reference) => {
+            return Ok(vec![(
+                None,
+                ChunkContentGraphNode::ExternalAssetReference(*reference),
+            )]);
         }
-    });
+    };
 
     let mut graph_nodes = vec![];
 
-    for asset in assets {
+    for &asset in assets {
         if let Some(available_assets) = context.availability_info.available_assets() {
             if *available_assets.includes(asset).await? {
                 graph_nodes.push((
@@ -595,11 +779,16 @@ where
                     )),
                 ));
             }
-            ChunkingType::SeparateAsync => {
+            ChunkingType::SeparateAsync | ChunkingType::SeparateAsyncPrefetch(_) => {
+                let prefetch = match chunking_type {
+                    ChunkingType::SeparateAsyncPrefetch(prefetch) => Some(prefetch),
+                    _ => None,
+                };
                 if let Some(manifest_loader_item) = I::from_async_asset(
                     context.chunking_context,
                     chunkable_asset,
                     context.availability_info,
+                    prefetch,
                 )
                 .await?
                 {
@@ -686,11 +875,10 @@ where
                 return Ok(vec![].into_iter().flatten());
             };
 
-            Ok(chunk_item
-                .references()
-                .await?
-                .into_iter()
-                .map(|reference| reference_to_graph_nodes::<I>(context, *reference))
+            let edges = chunking_edges(chunk_item.references()).await?;
+            Ok(edges
+                .iter()
+                .map(|edge| reference_to_graph_nodes::<I>(context, edge))
                 .try_join()
                 .await?
                 .into_iter()
@@ -0,0 +1,131 @@
+//! Reservations of module ids and chunk names, for code which hardcodes them,
+//! e.g. runtime interop or A/B testing frameworks.
+//!
+//! A reserved id is always given to the module it's reserved for, and never to
+//! any other module, no matter which modules are part of a build. Mangled ids
+//! are derived from the readable ids only, so they are stable across builds
+//! as well.
+
+use indexmap::IndexMap;
+use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64};
+
+use super::ModuleId;
+
+/// The number of hex digits of mangled module ids.
+const MANGLED_ID_LENGTH: usize = 10;
+
+#[turbo_tasks::value(shared)]
+#[derive(Default, Debug)]
+pub struct ReservedIds {
+    /// Reserved module ids by the readable id the module would get otherwise,
+    /// e.g. `[project]/src/analytics.js (ecmascript)`.
+    pub modules: IndexMap<String, ModuleId>,
+    /// Reserved chunk file names by the file name the chunk would get
+    /// otherwise, e.g. `src_index_b53fce.js`.
+    pub chunks: IndexMap<String, String>,
+}
+
+#[turbo_tasks::value_impl]
+impl ReservedIdsVc {
+    #[turbo_tasks::function]
+    pub fn empty() -> Self {
+        ReservedIds::default().cell()
+    }
+}
+
+impl ReservedIds {
+    fn is_reserved_module_id(&self, id: &ModuleId) -> bool {
+        self.modules.values().any(|reserved| reserved == id)
+    }
+
+    /// The id of the module with `readable_id`, which is mangled into a short
+    /// hash when `mangle` is set.
+    pub fn module_id(&self, readable_id: &str, mangle: bool) -> ModuleId {
+        if let Some(id) = self.modules.get(readable_id) {
+            return id.clone();
+        }
+        let id_for = |attempt: u32| {
+            let key = if attempt == 0 {
+                readable_id.to_string()
+            } else {
+                format!("{readable_id} #{attempt}")
+            };
+            if mangle {
+                let hash = encode_hex(hash_xxh3_hash64(key.as_bytes()));
+                ModuleId::String(hash[..MANGLED_ID_LENGTH].into())
+            } else {
+                ModuleId::String(key.into())
+            }
+        };
+        // Skip ids which are reserved for other modules.
+        (0..)
+            .map(id_for)
+            .find(|id| !self.is_reserved_module_id(id))
+            .unwrap()
+    }
+
+    /// The file name of a chunk which would be called `name` otherwise.
+    pub fn chunk_name(&self, name: &str) -> String {
+        if let Some(reserved) = self.chunks.get(name) {
+            return reserved.clone();
+        }
+        let mut name = name.to_string();
+        // Names which are reserved for other chunks get a prefix.
+        while self.chunks.values().any(|reserved| reserved == &name) {
+            name.insert(0, '_');
+        }
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use super::{ReservedIds, MANGLED_ID_LENGTH};
+    use crate::chunk::ModuleId;
+
+    fn reserved_ids() -> ReservedIds {
+        ReservedIds {
+            modules: IndexMap::from([
+                ("[project]/a.js".to_string(), ModuleId::Number(1)),
+                (
+                    "[project]/b.js".to_string(),
+                    ModuleId::String("[project]/c.js".into()),
+                ),
+            ]),
+            chunks: IndexMap::from([("a.js".to_string(), "main.js".to_string())]),
+        }
+    }
+
+    #[test]
+    fn module_ids() {
+        let ids = reserved_ids();
+        assert_eq!(ids.module_id("[project]/a.js", true), ModuleId::Number(1));
+        assert_eq!(
+            ids.module_id("[project]/d.js", false),
+            ModuleId::String("[project]/d.js".into())
+        );
+        // The readable id of c.js is reserved for b.js.
+        assert_eq!(
+            ids.module_id("[project]/c.js", false),
+            ModuleId::String("[project]/c.js #1".into())
+        );
+        let ModuleId::String(mangled) = ids.module_id("[project]/d.js", true) else {
+            panic!("expected a string id");
+        };
+        assert_eq!(mangled.len(), MANGLED_ID_LENGTH);
+        assert_eq!(
+            ids.module_id("[project]/d.js", true),
+            ModuleId::String(mangled)
+        );
+    }
+
+    #[test]
+    fn chunk_names() {
+        let ids = reserved_ids();
+        assert_eq!(ids.chunk_name("a.js"), "main.js");
+        assert_eq!(ids.chunk_name("b.js"), "b.js");
+        assert_eq!(ids.chunk_name("main.js"), "_main.js");
+    }
+}
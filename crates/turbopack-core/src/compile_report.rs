@@ -0,0 +1,260 @@
+//! Timings of the first compilation, summarized as a [CompileReport].
+//!
+//! Collecting is enabled with [start] and stops with [finish], so the
+//! instrumentation costs nothing once the first compilation is done. Phase
+//! times are the time the work spent on a thread, summed over all threads, so
+//! they can add up to more than the total time.
+
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    future::{poll_fn, Future},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use lazy_static::lazy_static;
+use turbo_tasks::util::FormatDuration;
+
+/// The number of modules and packages listed in a [CompileReport].
+const SLOWEST_COUNT: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompilePhase {
+    Resolve,
+    Transform,
+    Chunk,
+    Emit,
+}
+
+impl CompilePhase {
+    const ALL: [CompilePhase; 4] = [
+        CompilePhase::Resolve,
+        CompilePhase::Transform,
+        CompilePhase::Chunk,
+        CompilePhase::Emit,
+    ];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            CompilePhase::Resolve => "resolve",
+            CompilePhase::Transform => "transform",
+            CompilePhase::Chunk => "chunk",
+            CompilePhase::Emit => "emit",
+        }
+    }
+}
+
+#[derive(Default)]
+struct Timings {
+    start: Option<Instant>,
+    phases: HashMap<CompilePhase, Duration>,
+    modules: HashMap<String, Duration>,
+    emitted_assets: usize,
+}
+
+static COLLECTING: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    static ref TIMINGS: Mutex<Timings> = Mutex::new(Timings::default());
+}
+
+/// Starts collecting timings for a [CompileReport].
+pub fn start() {
+    *TIMINGS.lock().unwrap() = Timings {
+        start: Some(Instant::now()),
+        ..Default::default()
+    };
+    COLLECTING.store(true, Ordering::Release);
+}
+
+/// Whether timings are currently collected.
+pub fn is_collecting() -> bool {
+    COLLECTING.load(Ordering::Acquire)
+}
+
+/// Stops collecting timings and summarizes them. Returns `None` when timings
+/// weren't collected, e.g. because the report has already been created.
+pub fn finish() -> Option<CompileReport> {
+    if !COLLECTING.swap(false, Ordering::AcqRel) {
+        return None;
+    }
+    let timings = std::mem::take(&mut *TIMINGS.lock().unwrap());
+    Some(CompileReport::new(timings))
+}
+
+/// Records work of a `phase`, optionally attributed to the module at `path`.
+pub fn record(phase: CompilePhase, path: Option<&str>, duration: Duration) {
+    if !is_collecting() {
+        return;
+    }
+    let mut timings = TIMINGS.lock().unwrap();
+    *timings.phases.entry(phase).or_default() += duration;
+    if let Some(path) = path {
+        *timings.modules.entry(path.to_string()).or_default() += duration;
+    }
+}
+
+/// Records that an asset has been emitted.
+pub fn record_emitted_asset() {
+    if !is_collecting() {
+        return;
+    }
+    TIMINGS.lock().unwrap().emitted_assets += 1;
+}
+
+/// Runs `future` and records the time spent polling it as work of `phase`.
+/// Time spent waiting for other tasks isn't included.
+pub async fn measure<T>(
+    phase: CompilePhase,
+    path: Option<&str>,
+    future: impl Future<Output = T>,
+) -> T {
+    if !is_collecting() {
+        return future.await;
+    }
+    let mut future = Box::pin(future);
+    let mut duration = Duration::ZERO;
+    let result = poll_fn(|cx| {
+        let start = Instant::now();
+        let result = future.as_mut().poll(cx);
+        duration += start.elapsed();
+        result
+    })
+    .await;
+    record(phase, path, duration);
+    result
+}
+
+/// The package a module belongs to, based on the last `node_modules` directory
+/// in its path.
+fn package_name(path: &str) -> Option<&str> {
+    let (_, rest) = path.rsplit_once("node_modules/")?;
+    let end = if rest.starts_with('@') {
+        rest.match_indices('/').nth(1).map(|(index, _)| index)
+    } else {
+        rest.find('/')
+    };
+    Some(end.map_or(rest, |end| &rest[..end]))
+}
+
+fn slowest(durations: HashMap<String, Duration>) -> Vec<(String, Duration)> {
+    let mut durations: Vec<_> = durations.into_iter().collect();
+    durations.sort_by(|(a_name, a), (b_name, b)| b.cmp(a).then_with(|| a_name.cmp(b_name)));
+    durations.truncate(SLOWEST_COUNT);
+    durations
+}
+
+/// A summary of the first compilation.
+#[derive(Debug, Clone)]
+pub struct CompileReport {
+    pub total: Duration,
+    pub phases: Vec<(CompilePhase, Duration)>,
+    pub slowest_modules: Vec<(String, Duration)>,
+    pub slowest_packages: Vec<(String, Duration)>,
+    pub emitted_assets: usize,
+}
+
+impl CompileReport {
+    fn new(timings: Timings) -> Self {
+        let mut packages: HashMap<String, Duration> = HashMap::new();
+        for (path, duration) in &timings.modules {
+            if let Some(package) = package_name(path) {
+                *packages.entry(package.to_string()).or_default() += *duration;
+            }
+        }
+        CompileReport {
+            total: timings
+                .start
+                .map_or(Duration::ZERO, |start| start.elapsed()),
+            phases: CompilePhase::ALL
+                .iter()
+                .map(|phase| {
+                    (
+                        *phase,
+                        timings.phases.get(phase).copied().unwrap_or_default(),
+                    )
+                })
+                .collect(),
+            slowest_modules: slowest(timings.modules),
+            slowest_packages: slowest(packages),
+            emitted_assets: timings.emitted_assets,
+        }
+    }
+}
+
+impl Display for CompileReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "first compile finished in {} ({} assets emitted)",
+            FormatDuration(self.total),
+            self.emitted_assets
+        )?;
+        writeln!(f, "  time per phase (summed over all threads):")?;
+        for (phase, duration) in &self.phases {
+            writeln!(
+                f,
+                "    {:<10} {}",
+                phase.as_str(),
+                FormatDuration(*duration)
+            )?;
+        }
+        if !self.slowest_modules.is_empty() {
+            writeln!(f, "  slowest modules to transform:")?;
+            for (path, duration) in &self.slowest_modules {
+                writeln!(
+                    f,
+                    "    {:>8} {}",
+                    FormatDuration(*duration).to_string(),
+                    path
+                )?;
+            }
+        }
+        if !self.slowest_packages.is_empty() {
+            writeln!(f, "  slowest packages to transform:")?;
+            for (package, duration) in &self.slowest_packages {
+                writeln!(
+                    f,
+                    "    {:>8} {}",
+                    FormatDuration(*duration).to_string(),
+                    package
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn package_names() {
+        assert_eq!(package_name("src/index.js"), None);
+        assert_eq!(package_name("node_modules/react/index.js"), Some("react"));
+        assert_eq!(
+            package_name("node_modules/@swc/helpers/lib/index.js"),
+            Some("@swc/helpers")
+        );
+        assert_eq!(
+            package_name("node_modules/a/node_modules/b/dist/b.js"),
+            Some("b")
+        );
+    }
+
+    #[test]
+    fn slowest_are_sorted_and_truncated() {
+        let durations = (0..20)
+            .map(|i| (format!("m{i:02}"), Duration::from_millis(i)))
+            .collect();
+        let slowest = slowest(durations);
+        assert_eq!(slowest.len(), SLOWEST_COUNT);
+        assert_eq!(slowest[0], ("m19".to_string(), Duration::from_millis(19)));
+        assert_eq!(slowest[9], ("m10".to_string(), Duration::from_millis(10)));
+    }
+}
@@ -86,10 +86,32 @@ impl CompileTimeDefinesVc {
     }
 }
 
+/// The object that feature flags are read from in code, e.g.
+/// `if (__turbopack_feature_flags__.newCheckout) { ... }`.
+pub const FEATURE_FLAGS_OBJECT: &str = "__turbopack_feature_flags__";
+
+/// Flags that are known at compile time, by name. Unlike defines, flags also
+/// decide which modules are part of the module graph: modules that are only
+/// loaded with `import()` or `require()` in code behind a disabled flag are
+/// not referenced at all, so they are excluded from chunk groups together
+/// with everything they reference, like CSS and static assets. Flags that
+/// aren't listed are disabled.
+#[turbo_tasks::value(transparent)]
+pub struct FeatureFlags(pub HashMap<String, bool>);
+
+#[turbo_tasks::value_impl]
+impl FeatureFlagsVc {
+    #[turbo_tasks::function]
+    pub fn empty() -> Self {
+        Self::cell(HashMap::new())
+    }
+}
+
 #[turbo_tasks::value(shared)]
 pub struct CompileTimeInfo {
     pub environment: EnvironmentVc,
     pub defines: CompileTimeDefinesVc,
+    pub feature_flags: FeatureFlagsVc,
 }
 
 #[turbo_tasks::value_impl]
@@ -99,6 +121,7 @@ impl CompileTimeInfoVc {
         CompileTimeInfo {
             environment,
             defines: CompileTimeDefinesVc::empty(),
+            feature_flags: FeatureFlagsVc::empty(),
         }
         .cell()
     }
@@ -0,0 +1,287 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use indexmap::{IndexMap, IndexSet};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{primitives::StringVc, trace::TraceRawVcs, ValueToString};
+use turbo_tasks_fs::{FileContent, FileJsonContent, FileSystemPathVc};
+
+use crate::{
+    asset::{Asset, AssetContent, AssetVc},
+    issue::{Issue, IssueSeverity, IssueSeverityVc, IssueVc},
+    reference::all_referenced_assets,
+};
+
+/// A copy of an npm package in the graph, i.e. one of its installation
+/// directories.
+#[derive(Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub struct PackageInstance {
+    /// The `version` of its `package.json`, if it has one.
+    pub version: Option<String>,
+    /// The directory the package is installed in.
+    pub path: FileSystemPathVc,
+    /// The sum of the sizes of its modules in the graph, in bytes.
+    pub size: u64,
+    /// The modules from the entry to the first module of this copy. These are
+    /// the imports pinning this version.
+    pub import_chain: Vec<String>,
+}
+
+/// An npm package with more than one copy in the graph.
+#[derive(Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub struct DuplicatePackage {
+    pub name: String,
+    /// The copies of the package, the largest first.
+    pub instances: Vec<PackageInstance>,
+}
+
+impl DuplicatePackage {
+    /// The bytes that could be saved by deduplicating the package to a single
+    /// copy, i.e. the size of all copies except the largest one.
+    pub fn duplicated_size(&self) -> u64 {
+        self.instances.iter().skip(1).map(|i| i.size).sum()
+    }
+}
+
+/// The duplicate packages of a graph, the most costly first.
+#[turbo_tasks::value(transparent)]
+pub struct DuplicatePackages(Vec<DuplicatePackage>);
+
+/// Detects npm packages which have multiple copies in the graph of `entry`,
+/// e.g. because dependencies require incompatible versions of them.
+///
+/// When a `warning_threshold` is passed, a [DuplicatePackageIssue] is emitted
+/// for every package whose [DuplicatePackage::duplicated_size] exceeds it.
+#[turbo_tasks::function]
+pub async fn find_duplicate_packages(
+    entry: AssetVc,
+    warning_threshold: Option<u64>,
+) -> Result<DuplicatePackagesVc> {
+    // The graph is traversed breadth-first, so the chains to the first module
+    // of a package are the shortest ones.
+    let mut parents = IndexMap::new();
+    parents.insert(entry, None);
+    let mut queue = VecDeque::from([entry]);
+    // The modules of every package copy by package name and install directory,
+    // and by path, so files with multiple assets count once.
+    let mut packages: IndexMap<String, IndexMap<String, IndexMap<String, AssetVc>>> =
+        IndexMap::new();
+    while let Some(asset) = queue.pop_front() {
+        let path = asset.ident().path().await?;
+        if let Some((root, name)) = package_root(&path.path) {
+            packages
+                .entry(name.to_string())
+                .or_default()
+                .entry(root.to_string())
+                .or_default()
+                .entry(path.path.clone())
+                .or_insert(asset);
+        }
+        for referenced in all_referenced_assets(asset).await?.iter() {
+            if !parents.contains_key(referenced) {
+                parents.insert(*referenced, Some(asset));
+                queue.push_back(*referenced);
+            }
+        }
+    }
+
+    let mut duplicates = Vec::new();
+    for (name, roots) in packages {
+        if roots.len() < 2 {
+            continue;
+        }
+        let mut instances = Vec::new();
+        for (root, assets) in roots {
+            let assets: Vec<_> = assets.into_values().collect();
+            let path = assets[0].ident().path().root().join(&root);
+            let version = match &*path.join("package.json").read_json().await? {
+                FileJsonContent::Content(package_json) => package_json["version"]
+                    .as_str()
+                    .map(|version| version.to_string()),
+                _ => None,
+            };
+            let mut size = 0;
+            for asset in &assets {
+                if let AssetContent::File(file) = &*asset.content().await? {
+                    if let FileContent::Content(file) = &*file.await? {
+                        size += file.content().len() as u64;
+                    }
+                }
+            }
+            instances.push(PackageInstance {
+                version,
+                path,
+                size,
+                import_chain: import_chain(&parents, assets[0]).await?,
+            });
+        }
+        instances.sort_by(|a, b| b.size.cmp(&a.size));
+        duplicates.push(DuplicatePackage { name, instances });
+    }
+    duplicates.sort_by(|a, b| {
+        b.duplicated_size()
+            .cmp(&a.duplicated_size())
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    if let Some(warning_threshold) = warning_threshold {
+        for duplicate in &duplicates {
+            if duplicate.duplicated_size() > warning_threshold {
+                DuplicatePackageIssue {
+                    context: entry.ident().path(),
+                    package: duplicate.clone(),
+                }
+                .cell()
+                .as_issue()
+                .emit();
+            }
+        }
+    }
+
+    Ok(DuplicatePackagesVc::cell(duplicates))
+}
+
+/// The paths of the assets from the entry of the traversal to `asset`.
+async fn import_chain(
+    parents: &IndexMap<AssetVc, Option<AssetVc>>,
+    asset: AssetVc,
+) -> Result<Vec<String>> {
+    let mut chain = Vec::new();
+    // Guards against cycles in `parents`, which can't happen with a traversal,
+    // but would hang otherwise.
+    let mut seen = IndexSet::new();
+    let mut current = Some(asset);
+    while let Some(asset) = current {
+        if !seen.insert(asset) {
+            break;
+        }
+        chain.push(asset.ident().to_string().await?.clone_value());
+        current = parents.get(&asset).copied().flatten();
+    }
+    chain.reverse();
+    Ok(chain)
+}
+
+/// The install directory and the name of the package a module belongs to,
+/// based on the last `node_modules` directory in its path.
+fn package_root(path: &str) -> Option<(&str, &str)> {
+    let start = path
+        .rmatch_indices("node_modules/")
+        .map(|(index, _)| index)
+        .find(|&index| index == 0 || path[..index].ends_with('/'))?
+        + "node_modules/".len();
+    let rest = &path[start..];
+    let end = if rest.starts_with('@') {
+        rest.match_indices('/').nth(1).map(|(index, _)| index)
+    } else {
+        rest.find('/')
+    }
+    .unwrap_or(rest.len());
+    if end == 0 {
+        return None;
+    }
+    Some((&path[..start + end], &rest[..end]))
+}
+
+/// Formats a number of bytes for humans, e.g. `12.3 kB`.
+pub(crate) fn format_size(size: u64) -> String {
+    if size < 1000 {
+        format!("{size} B")
+    } else if size < 1_000_000 {
+        format!("{:.1} kB", size as f64 / 1000.0)
+    } else {
+        format!("{:.1} MB", size as f64 / 1_000_000.0)
+    }
+}
+
+#[turbo_tasks::value(shared)]
+pub struct DuplicatePackageIssue {
+    pub context: FileSystemPathVc,
+    pub package: DuplicatePackage,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for DuplicatePackageIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("bundle size".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell(format!(
+            "Package {} is included {} times",
+            self.package.name,
+            self.package.instances.len()
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.context
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> StringVc {
+        StringVc::cell(format!(
+            "Deduplicating {} to a single version would save {}. Check whether the importers can \
+             agree on a version, e.g. by updating them or with the overrides of the package \
+             manager.",
+            self.package.name,
+            format_size(self.package.duplicated_size())
+        ))
+    }
+
+    #[turbo_tasks::function]
+    async fn detail(&self) -> Result<StringVc> {
+        let mut detail = String::new();
+        for instance in &self.package.instances {
+            detail.push_str(&format!(
+                "{}@{} ({}) in {}\n",
+                self.package.name,
+                instance.version.as_deref().unwrap_or("unknown"),
+                format_size(instance.size),
+                instance.path.to_string().await?
+            ));
+            for module in &instance.import_chain {
+                detail.push_str(&format!("  {module}\n"));
+            }
+        }
+        Ok(StringVc::cell(detail))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_size, package_root};
+
+    #[test]
+    fn package_roots() {
+        assert_eq!(package_root("src/index.js"), None);
+        assert_eq!(
+            package_root("node_modules/react/index.js"),
+            Some(("node_modules/react", "react"))
+        );
+        assert_eq!(
+            package_root("app/node_modules/@swc/helpers/lib/index.js"),
+            Some(("app/node_modules/@swc/helpers", "@swc/helpers"))
+        );
+        assert_eq!(
+            package_root("node_modules/a/node_modules/b/dist/b.js"),
+            Some(("node_modules/a/node_modules/b", "b"))
+        );
+        assert_eq!(package_root("src/my_node_modules/a.js"), None);
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(format_size(999), "999 B");
+        assert_eq!(format_size(12_345), "12.3 kB");
+        assert_eq!(format_size(2_500_000), "2.5 MB");
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use turbo_tasks::{primitives::StringVc, ValueToString};
+use turbo_tasks_fs::FileSystemPathVc;
+
+use crate::{
+    asset::{Asset, AssetVc},
+    issue::{Issue, IssueSeverity, IssueSeverityVc, IssueVc},
+};
+
+/// The idents of the assets emitted to each output path, keyed by path.
+#[turbo_tasks::value(transparent)]
+pub struct EmittedPaths(BTreeMap<String, (FileSystemPathVc, BTreeSet<String>)>);
+
+#[turbo_tasks::value_impl]
+impl EmittedPathsVc {
+    /// The output path of `asset`, if it's emitted into `output_dir`.
+    #[turbo_tasks::function]
+    pub async fn of_asset(asset: AssetVc, output_dir: FileSystemPathVc) -> Result<Self> {
+        let path = asset.ident().path();
+        let path_value = path.await?;
+        let output_dir_value = output_dir.await?;
+        let mut paths = BTreeMap::new();
+        if path_value.is_inside(&output_dir_value) {
+            let producer = asset.ident().to_string().await?.clone_value();
+            paths.insert(path_value.path.clone(), (path, [producer].into()));
+        }
+        Ok(Self::cell(paths))
+    }
+}
+
+impl EmittedPathsVc {
+    /// Merges the output paths emitted by the parts of an asset graph.
+    pub async fn merge(parts: impl IntoIterator<Item = EmittedPathsVc>) -> Result<Self> {
+        let mut paths = BTreeMap::<String, (FileSystemPathVc, BTreeSet<String>)>::new();
+        for part in parts {
+            for (key, (path, producers)) in part.await?.iter() {
+                paths
+                    .entry(key.clone())
+                    .or_insert_with(|| (*path, BTreeSet::new()))
+                    .1
+                    .extend(producers.iter().cloned());
+            }
+        }
+        Ok(Self::cell(paths))
+    }
+}
+
+/// Two or more assets which are written to the same output path. Only one of
+/// them would end up on disk, depending on which is written last.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct EmittedPathCollision {
+    pub path: FileSystemPathVc,
+    /// The idents of the assets emitted to `path`, in sorted order.
+    pub producers: Vec<String>,
+}
+
+/// The collisions of the assets emitted for an entry, ordered by path.
+#[turbo_tasks::value(transparent)]
+pub struct EmittedPathCollisions(Vec<EmittedPathCollision>);
+
+/// Reports every output path in `paths` that is claimed by more than one asset
+/// with an [EmittedPathCollisionIssue], e.g. a static `public/app.js` and a
+/// generated chunk `app.js`.
+///
+/// Assets with the same ident are the same producer, so they don't collide.
+#[turbo_tasks::function]
+pub async fn emitted_path_collisions(paths: EmittedPathsVc) -> Result<EmittedPathCollisionsVc> {
+    let collisions = paths
+        .await?
+        .values()
+        .filter(|(_, producers)| producers.len() > 1)
+        .map(|(path, producers)| EmittedPathCollision {
+            path: *path,
+            producers: producers.iter().cloned().collect(),
+        })
+        .collect::<Vec<_>>();
+
+    for collision in &collisions {
+        EmittedPathCollisionIssue {
+            collision: collision.clone(),
+        }
+        .cell()
+        .as_issue()
+        .emit();
+    }
+
+    Ok(EmittedPathCollisionsVc::cell(collisions))
+}
+
+#[turbo_tasks::value(shared)]
+pub struct EmittedPathCollisionIssue {
+    pub collision: EmittedPathCollision,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for EmittedPathCollisionIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("emit".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell(format!(
+            "{} assets are emitted to the same output path",
+            self.collision.producers.len()
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.collision.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> StringVc {
+        StringVc::cell(format!(
+            "Only one of them could be written. Rename or move one of them so each output path \
+             has a single producer:\n{}",
+            self.collision
+                .producers
+                .iter()
+                .map(|producer| format!("  {producer}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ))
+    }
+}
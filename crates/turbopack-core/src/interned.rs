@@ -0,0 +1,192 @@
+use std::{
+    cmp::Ordering,
+    fmt::{Debug, Display},
+    hash::Hash,
+    ops::Deref,
+    sync::{Arc, Weak},
+};
+
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::trace::{TraceRawVcs, TraceRawVcsContext};
+use turbo_tasks_hash::{DeterministicHash, DeterministicHasher};
+
+lazy_static! {
+    /// All strings that are currently interned. The map only holds weak
+    /// references, and the last [InternedString] of a string removes its entry
+    /// when it's dropped.
+    static ref STRINGS: DashMap<Box<str>, Weak<str>> = DashMap::new();
+}
+
+/// A string that is stored only once per process, no matter how often it is
+/// created. Clones share the same allocation, and equality checks compare
+/// pointers instead of contents.
+///
+/// This is meant for strings that are repeated across large module graphs,
+/// like string module ids. A string is only kept while there are
+/// [InternedString]s of it.
+#[derive(Clone)]
+pub struct InternedString(Arc<str>);
+
+impl InternedString {
+    pub fn new(value: &str) -> Self {
+        if let Some(string) = STRINGS.get(value).and_then(|entry| entry.upgrade()) {
+            return InternedString(string);
+        }
+        // Another thread might have interned the same string in the meantime, so
+        // the entry decides which allocation is used.
+        let mut entry = STRINGS.entry(value.into()).or_insert_with(Weak::new);
+        if let Some(string) = entry.upgrade() {
+            return InternedString(string);
+        }
+        let string: Arc<str> = value.into();
+        *entry = Arc::downgrade(&string);
+        InternedString(string)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Drop for InternedString {
+    fn drop(&mut self) {
+        if Arc::strong_count(&self.0) != 1 {
+            return;
+        }
+        // The check is repeated while the entry is locked, since `new` only
+        // hands out new references to the string while holding that lock.
+        STRINGS.remove_if(&*self.0, |_, weak| {
+            Arc::strong_count(&self.0) == 1 && weak.as_ptr() == Arc::as_ptr(&self.0)
+        });
+    }
+}
+
+impl Deref for InternedString {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<str> for InternedString {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for InternedString {
+    fn from(value: &str) -> Self {
+        InternedString::new(value)
+    }
+}
+
+impl From<String> for InternedString {
+    fn from(value: String) -> Self {
+        InternedString::new(&value)
+    }
+}
+
+impl PartialEq for InternedString {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for InternedString {}
+
+impl Hash for InternedString {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl PartialOrd for InternedString {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for InternedString {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self == other {
+            Ordering::Equal
+        } else {
+            self.0.cmp(&other.0)
+        }
+    }
+}
+
+impl Display for InternedString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&*self.0, f)
+    }
+}
+
+impl Debug for InternedString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&*self.0, f)
+    }
+}
+
+impl Serialize for InternedString {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for InternedString {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        Ok(InternedString::new(&value))
+    }
+}
+
+impl DeterministicHash for InternedString {
+    fn deterministic_hash<H: DeterministicHasher>(&self, state: &mut H) {
+        (&*self.0).deterministic_hash(state);
+    }
+}
+
+impl TraceRawVcs for InternedString {
+    fn trace_raw_vcs(&self, _context: &mut TraceRawVcsContext) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interned_strings_share_allocations() {
+        let a = InternedString::new("[project]/src/index.js");
+        let b = InternedString::from("[project]/src/index.js".to_string());
+        assert!(Arc::ptr_eq(&a.0, &b.0));
+        assert_eq!(a, b);
+        assert_ne!(a, InternedString::new("[project]/src/other.js"));
+    }
+
+    #[test]
+    fn interned_strings_are_removed_when_dropped() {
+        let value = "[project]/src/dropped.js";
+        let a = InternedString::new(value);
+        let b = a.clone();
+        drop(a);
+        assert!(STRINGS.contains_key(value));
+        drop(b);
+        assert!(!STRINGS.contains_key(value));
+        // Interning it again creates a new entry.
+        let c = InternedString::new(value);
+        assert_eq!(&*c, value);
+        assert!(STRINGS.contains_key(value));
+    }
+
+    #[test]
+    fn interned_strings_order_by_content() {
+        let a = InternedString::new("a");
+        let b = InternedString::new("b");
+        assert!(a < b);
+        assert_eq!(a.cmp(&a.clone()), Ordering::Equal);
+    }
+}
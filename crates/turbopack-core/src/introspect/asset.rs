@@ -126,7 +126,9 @@ pub async fn children_from_asset_references(
                 Some(ChunkingType::IsolatedParallel) => key = isolated_parallel_reference_ty(),
                 Some(ChunkingType::Separate) => key = separate_reference_ty(),
                 Some(ChunkingType::PlacedOrParallel) => key = placed_or_parallel_reference_ty(),
-                Some(ChunkingType::SeparateAsync) => key = async_reference_ty(),
+                Some(ChunkingType::SeparateAsync | ChunkingType::SeparateAsyncPrefetch(_)) => {
+                    key = async_reference_ty()
+                }
             }
         } else if let Some(parallel) = ParallelChunkReferenceVc::resolve_from(reference).await? {
             if *parallel.is_loaded_in_parallel().await? {
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+use turbo_tasks::trace::TraceRawVcs;
+
+use crate::{
+    asset::{Asset, AssetVc},
+    chunk::{Chunk, ChunkVc},
+    ident::AssetIdentVc,
+};
+
+/// A chunk item as listed by [IntrospectableChunk::chunk_item_infos].
+#[derive(PartialEq, Eq, Debug, Clone, TraceRawVcs, Serialize, Deserialize)]
+pub struct ChunkItemInfo {
+    /// The ident of the asset the chunk item was created from.
+    pub ident: AssetIdentVc,
+    /// The size of the code generated for the chunk item, in bytes.
+    pub size: usize,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct ChunkItemInfos(Vec<ChunkItemInfo>);
+
+#[turbo_tasks::value_impl]
+impl ChunkItemInfosVc {
+    #[turbo_tasks::function]
+    pub fn empty() -> Self {
+        ChunkItemInfosVc::cell(Vec::new())
+    }
+}
+
+/// A [Chunk] that can list its chunk items, which allows introspection to show
+/// what ended up in the chunk.
+#[turbo_tasks::value_trait]
+pub trait IntrospectableChunk: Chunk + Asset {
+    fn chunk_item_infos(&self) -> ChunkItemInfosVc;
+}
@@ -1,4 +1,5 @@
 pub mod asset;
+pub mod chunk;
 
 use indexmap::IndexSet;
 use turbo_tasks::primitives::StringVc;
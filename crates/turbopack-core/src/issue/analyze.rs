@@ -47,6 +47,11 @@ impl Issue for AnalyzeIssue {
         self.message
     }
 
+    #[turbo_tasks::function]
+    fn code(&self) -> StringVc {
+        StringVc::cell(self.code.clone().unwrap_or_default())
+    }
+
     #[turbo_tasks::function]
     fn source(&self) -> OptionIssueSourceVc {
         OptionIssueSourceVc::cell(self.source)
@@ -119,6 +119,25 @@ pub trait Issue {
         StringVc::empty()
     }
 
+    /// A stable identifier of the kind of issue (eg "TP1001"), which stays the
+    /// same when the title is reworded, so it can be searched for.
+    fn code(&self) -> StringVc {
+        StringVc::empty()
+    }
+
+    /// The path of the documentation of the issue (eg
+    /// "resolve/missing-extension"). Unlike the [Issue::documentation_link],
+    /// it doesn't depend on where the documentation is hosted.
+    fn documentation_slug(&self) -> StringVc {
+        StringVc::empty()
+    }
+
+    /// Fixes of the issue which can be applied without further input, eg by a
+    /// single click in the error overlay or an editor.
+    fn suggestions(&self) -> IssueSuggestionsVc {
+        IssueSuggestionsVc::cell(Vec::new())
+    }
+
     /// The source location that caused the issue. Eg, for a parsing error it
     /// should point at the offending character. Displayed to the user alongside
     /// the title/description.
@@ -424,7 +443,7 @@ impl CapturedIssues {
     }
 }
 
-#[turbo_tasks::value]
+#[turbo_tasks::value(shared)]
 #[derive(Clone)]
 pub struct IssueSource {
     pub asset: AssetVc,
@@ -473,6 +492,27 @@ impl IssueSourceVc {
 #[turbo_tasks::value(transparent)]
 pub struct OptionIssueSource(Option<IssueSourceVc>);
 
+/// A fix of an issue, made of edits to the source code.
+#[turbo_tasks::value(shared)]
+#[derive(Clone)]
+pub struct IssueSuggestion {
+    /// A short description of the fix, eg "Add the extension to the import".
+    pub title: String,
+    pub edits: Vec<IssueSuggestionEdit>,
+}
+
+/// Replaces the code of `source` with `replacement`. An empty range inserts
+/// the replacement.
+#[turbo_tasks::value(shared)]
+#[derive(Clone)]
+pub struct IssueSuggestionEdit {
+    pub source: IssueSourceVc,
+    pub replacement: String,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct IssueSuggestions(Vec<IssueSuggestionVc>);
+
 #[turbo_tasks::value(serialization = "none")]
 #[derive(Clone, Debug)]
 pub struct PlainIssue {
@@ -484,8 +524,11 @@ pub struct PlainIssue {
     pub description: String,
     pub detail: String,
     pub documentation_link: String,
+    pub code: String,
+    pub documentation_slug: String,
 
     pub source: Option<PlainIssueSourceReadRef>,
+    pub suggestions: Vec<PlainIssueSuggestionReadRef>,
     pub sub_issues: Vec<PlainIssueReadRef>,
     pub processing_path: PlainIssueProcessingPathReadRef,
 }
@@ -506,6 +549,8 @@ impl PlainIssue {
         );
         hasher.write_ref(&self.detail);
         hasher.write_ref(&self.documentation_link);
+        hasher.write_ref(&self.code);
+        hasher.write_ref(&self.documentation_slug);
 
         if let Some(source) = &self.source {
             hasher.write_value(1_u8);
@@ -517,6 +562,16 @@ impl PlainIssue {
             hasher.write_value(0_u8);
         }
 
+        hasher.write_value(self.suggestions.len());
+        for suggestion in &self.suggestions {
+            hasher.write_ref(&suggestion.title);
+            for edit in &suggestion.edits {
+                hasher.write_ref(&edit.source.start);
+                hasher.write_ref(&edit.source.end);
+                hasher.write_ref(&edit.replacement);
+            }
+        }
+
         hasher.finish()
     }
 }
@@ -544,6 +599,8 @@ impl IssueVc {
             description: self.description().await?.clone_value(),
             detail: self.detail().await?.clone_value(),
             documentation_link: self.documentation_link().await?.clone_value(),
+            code: self.code().await?.clone_value(),
+            documentation_slug: self.documentation_slug().await?.clone_value(),
             source: {
                 if let Some(s) = *self.source().await? {
                     Some(s.into_plain().await?)
@@ -551,6 +608,13 @@ impl IssueVc {
                     None
                 }
             },
+            suggestions: self
+                .suggestions()
+                .await?
+                .iter()
+                .map(|suggestion| suggestion.into_plain())
+                .try_join()
+                .await?,
             sub_issues: self
                 .sub_issues()
                 .await?
@@ -591,6 +655,43 @@ impl IssueSourceVc {
     }
 }
 
+#[turbo_tasks::value(serialization = "none")]
+#[derive(Clone, Debug)]
+pub struct PlainIssueSuggestion {
+    pub title: String,
+    pub edits: Vec<PlainIssueSuggestionEdit>,
+}
+
+#[turbo_tasks::value(serialization = "none")]
+#[derive(Clone, Debug)]
+pub struct PlainIssueSuggestionEdit {
+    pub source: PlainIssueSourceReadRef,
+    pub replacement: String,
+}
+
+#[turbo_tasks::value_impl]
+impl IssueSuggestionVc {
+    #[turbo_tasks::function]
+    pub async fn into_plain(self) -> Result<PlainIssueSuggestionVc> {
+        let this = self.await?;
+        Ok(PlainIssueSuggestion {
+            title: this.title.clone(),
+            edits: this
+                .edits
+                .iter()
+                .map(|edit| async move {
+                    anyhow::Ok(PlainIssueSuggestionEdit {
+                        source: edit.source.into_plain().await?,
+                        replacement: edit.replacement.clone(),
+                    })
+                })
+                .try_join()
+                .await?,
+        }
+        .cell())
+    }
+}
+
 #[turbo_tasks::value(serialization = "none")]
 #[derive(Clone, Debug)]
 pub struct PlainAsset {
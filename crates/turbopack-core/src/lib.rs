@@ -6,16 +6,23 @@
 #![feature(lint_reasons)]
 
 pub mod asset;
+pub mod asset_manifest;
+pub mod bundle_stats;
 pub mod changed;
 pub mod chunk;
 pub mod code_builder;
+pub mod compile_report;
 pub mod compile_time_info;
 pub mod context;
+pub mod duplicate_packages;
+pub mod emitted_paths;
 pub mod environment;
 pub mod error;
 pub mod ident;
+pub mod interned;
 pub mod introspect;
 pub mod issue;
+pub mod module_graph;
 pub mod reference;
 pub mod reference_type;
 pub mod resolve;
@@ -25,6 +32,7 @@ pub mod source_map;
 pub mod source_pos;
 pub mod source_transform;
 pub mod target;
+pub mod url_validation;
 mod utils;
 pub mod version;
 pub mod virtual_asset;
@@ -0,0 +1,264 @@
+use anyhow::{bail, Result};
+use indexmap::IndexSet;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, CompletionVc, ValueToString};
+use turbo_tasks_fs::{File, FileContent, FileSystemPathVc};
+
+use crate::{
+    asset::{Asset, AssetVc},
+    chunk::{ChunkableAssetReference, ChunkableAssetReferenceVc, ChunkingType},
+    reference::{AssetReference, AssetReferenceSpan, AssetReferenceSpanVc},
+    source_pos::SourcePos,
+};
+
+/// The version of the [ModuleGraph] format. It's increased whenever the
+/// format changes in a way loaders of older versions can't read.
+pub const MODULE_GRAPH_VERSION: u32 = 1;
+
+/// An asset of a [ModuleGraph].
+#[derive(Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub struct ModuleGraphNode {
+    /// The ident of the asset, which is unique in the graph.
+    pub ident: String,
+    /// The path of the asset, relative to the root of its file system.
+    pub path: String,
+    /// The kind of the asset, which is the last modifier of its ident, e.g.
+    /// `ecmascript` or `css module`. Assets without a modifier, like source
+    /// files, are `source`.
+    pub ty: String,
+}
+
+/// A range of the code of a [ModuleGraphNode].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub struct ModuleGraphSpan {
+    pub start: SourcePos,
+    pub end: SourcePos,
+}
+
+/// A reference of a [ModuleGraphNode] to the nodes it resolves to.
+#[derive(Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub struct ModuleGraphReference {
+    /// The index of the referencing node.
+    pub from: usize,
+    /// The indices of the referenced nodes. It's empty when the reference
+    /// couldn't be resolved.
+    pub to: Vec<usize>,
+    /// A description of the reference, e.g. `import ./utils {}`.
+    pub description: String,
+    /// How the referenced nodes are chunked. References which aren't chunked,
+    /// e.g. to source maps, have none.
+    pub chunking_type: Option<ChunkingType>,
+    /// Where the reference is made in the code of the referencing node, e.g.
+    /// the module specifier of an import, when the reference knows it.
+    pub span: Option<ModuleGraphSpan>,
+}
+
+/// The resolved module graph of a set of entries, in a format that external
+/// tools, e.g. to enforce dependency rules, can analyze without building the
+/// project again. It's written with [ModuleGraph::to_json] and read with
+/// [ModuleGraph::from_json].
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct ModuleGraph {
+    /// The [MODULE_GRAPH_VERSION] of the format.
+    pub version: u32,
+    /// The nodes, in the order they were reached from the entries.
+    pub nodes: Vec<ModuleGraphNode>,
+    /// The indices of the entry nodes.
+    pub entries: Vec<usize>,
+    pub references: Vec<ModuleGraphReference>,
+}
+
+impl ModuleGraph {
+    /// Loads a graph written by [ModuleGraph::to_json]. Fails when it was
+    /// written in another version of the format.
+    pub fn from_json(json: &str) -> Result<Self> {
+        #[derive(Deserialize)]
+        struct Header {
+            version: u32,
+        }
+
+        let Header { version } = serde_json::from_str(json)?;
+        if version != MODULE_GRAPH_VERSION {
+            bail!(
+                "unsupported module graph version {version}, only version {MODULE_GRAPH_VERSION} \
+                 is supported"
+            );
+        }
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// The index of the node with the `ident`.
+    pub fn find(&self, ident: &str) -> Option<usize> {
+        self.nodes.iter().position(|node| node.ident == ident)
+    }
+
+    /// The indices of the nodes the `node` references.
+    pub fn dependencies(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        self.references
+            .iter()
+            .filter(move |reference| reference.from == node)
+            .flat_map(|reference| reference.to.iter().copied())
+    }
+
+    /// The indices of the nodes which reference the `node`.
+    pub fn dependents(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        self.references
+            .iter()
+            .filter(move |reference| reference.to.contains(&node))
+            .map(|reference| reference.from)
+    }
+}
+
+/// Computes the [ModuleGraph] of all assets reachable from the `entries`.
+#[turbo_tasks::function]
+pub async fn module_graph(entries: Vec<AssetVc>) -> Result<ModuleGraphVc> {
+    let mut assets: IndexSet<AssetVc> = entries.iter().copied().collect();
+    let entries = entries
+        .iter()
+        .filter_map(|entry| assets.get_index_of(entry))
+        .collect();
+    let mut references = Vec::new();
+    // Assets are appended while the graph is traversed.
+    let mut index = 0;
+    while let Some(&asset) = assets.get_index(index) {
+        for &reference in asset.references().await?.iter() {
+            let to = reference
+                .resolve_reference()
+                .primary_assets()
+                .await?
+                .iter()
+                .map(|&referenced| assets.insert_full(referenced).0)
+                .collect();
+            let chunking_type = match ChunkableAssetReferenceVc::resolve_from(reference).await? {
+                Some(reference) => *reference.chunking_type().await?,
+                None => None,
+            };
+            let span = match AssetReferenceSpanVc::resolve_from(reference).await? {
+                Some(reference) => match *reference.span().await? {
+                    Some(source) => {
+                        let source = source.await?;
+                        Some(ModuleGraphSpan {
+                            start: source.start,
+                            end: source.end,
+                        })
+                    }
+                    None => None,
+                },
+                None => None,
+            };
+            references.push(ModuleGraphReference {
+                from: index,
+                to,
+                description: reference.to_string().await?.clone_value(),
+                chunking_type,
+                span,
+            });
+        }
+        index += 1;
+    }
+
+    let mut nodes = Vec::with_capacity(assets.len());
+    for asset in assets {
+        let ident = asset.ident().await?;
+        let ty = match ident.modifiers.last() {
+            Some(modifier) => modifier.await?.clone_value(),
+            None => "source".to_string(),
+        };
+        nodes.push(ModuleGraphNode {
+            ident: asset.ident().to_string().await?.clone_value(),
+            path: ident.path.await?.path.clone(),
+            ty,
+        });
+    }
+
+    Ok(ModuleGraph {
+        version: MODULE_GRAPH_VERSION,
+        nodes,
+        entries,
+        references,
+    }
+    .cell())
+}
+
+/// Writes the [ModuleGraph] of the `entries` to the file at `path`.
+#[turbo_tasks::function]
+pub async fn emit_module_graph(
+    entries: Vec<AssetVc>,
+    path: FileSystemPathVc,
+) -> Result<CompletionVc> {
+    let graph = module_graph(entries).await?;
+    path.write(FileContent::Content(File::from(graph.to_json()?)).cell())
+        .await?;
+    Ok(CompletionVc::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ModuleGraph, ModuleGraphNode, ModuleGraphReference, ModuleGraphSpan, MODULE_GRAPH_VERSION,
+    };
+    use crate::{chunk::ChunkingType, source_pos::SourcePos};
+
+    fn node(ident: &str) -> ModuleGraphNode {
+        ModuleGraphNode {
+            ident: ident.to_string(),
+            path: ident.to_string(),
+            ty: "ecmascript".to_string(),
+        }
+    }
+
+    fn reference(from: usize, to: &[usize]) -> ModuleGraphReference {
+        ModuleGraphReference {
+            from,
+            to: to.to_vec(),
+            description: "import".to_string(),
+            chunking_type: Some(ChunkingType::PlacedOrParallel),
+            span: Some(ModuleGraphSpan {
+                start: SourcePos { line: 0, column: 7 },
+                end: SourcePos {
+                    line: 0,
+                    column: 12,
+                },
+            }),
+        }
+    }
+
+    fn graph() -> ModuleGraph {
+        ModuleGraph {
+            version: MODULE_GRAPH_VERSION,
+            nodes: vec![node("a.js"), node("b.js"), node("c.js")],
+            entries: vec![0],
+            references: vec![reference(0, &[1, 2]), reference(1, &[2]), reference(2, &[])],
+        }
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let graph = graph();
+        let loaded = ModuleGraph::from_json(&graph.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.nodes, graph.nodes);
+        assert_eq!(loaded.entries, graph.entries);
+        assert_eq!(loaded.references, graph.references);
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let mut graph = graph();
+        graph.version = MODULE_GRAPH_VERSION + 1;
+        assert!(ModuleGraph::from_json(&graph.to_json().unwrap()).is_err());
+    }
+
+    #[test]
+    fn follows_references() {
+        let graph = graph();
+        let c = graph.find("c.js").unwrap();
+        assert_eq!(graph.dependencies(0).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(graph.dependents(c).collect::<Vec<_>>(), [0, 1]);
+        assert_eq!(graph.find("d.js"), None);
+    }
+}
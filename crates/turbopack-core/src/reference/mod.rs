@@ -5,7 +5,7 @@ use turbo_tasks::{primitives::StringVc, TryJoinIterExt, ValueToString, ValueToSt
 
 use crate::{
     asset::{Asset, AssetVc, AssetsVc},
-    issue::IssueContextExt,
+    issue::{IssueContextExt, OptionIssueSourceVc},
     resolve::{PrimaryResolveResult, ResolveResult, ResolveResultVc},
 };
 pub mod source_map;
@@ -26,6 +26,13 @@ pub trait AssetReference: ValueToString {
     // fn kind(&self) -> AssetReferenceTypeVc;
 }
 
+/// An [AssetReference] which knows where it's made in the source code of the
+/// referencing asset, e.g. the module specifier of an import.
+#[turbo_tasks::value_trait]
+pub trait AssetReferenceSpan {
+    fn span(&self) -> OptionIssueSourceVc;
+}
+
 /// Multiple [AssetReference]s
 #[turbo_tasks::value(transparent)]
 pub struct AssetReferences(Vec<AssetReferenceVc>);
@@ -0,0 +1,164 @@
+//! Boundaries between client and server code. Modules can be marked as
+//! server-only (e.g. because they use `fs` or secrets) or client-only (e.g.
+//! because they use `window`). When such a module is imported from the other
+//! side, it's replaced with a poison module which throws a descriptive error
+//! when it's evaluated, and an issue is reported.
+//!
+//! This works like the `server-only` and `client-only` packages, but is
+//! enforced in the module graph instead of at runtime.
+
+use std::fmt::Display;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{primitives::StringVc, trace::TraceRawVcs, ValueToString};
+use turbo_tasks_fs::{glob::GlobVc, File, FileSystemPathVc};
+
+use super::{
+    parse::RequestVc,
+    plugin::{ResolvePlugin, ResolvePluginConditionVc, ResolvePluginVc},
+    ResolveResult, ResolveResultOptionVc,
+};
+use crate::{
+    ident::AssetIdentVc,
+    issue::{Issue, IssueSeverity, IssueSeverityVc, IssueVc},
+    virtual_asset::VirtualAssetVc,
+};
+
+/// The side of the boundary code runs on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+pub enum CodeSide {
+    Client,
+    Server,
+}
+
+impl CodeSide {
+    fn other(self) -> Self {
+        match self {
+            CodeSide::Client => CodeSide::Server,
+            CodeSide::Server => CodeSide::Client,
+        }
+    }
+}
+
+impl Display for CodeSide {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CodeSide::Client => "client",
+            CodeSide::Server => "server",
+        })
+    }
+}
+
+/// Marks the modules matching a glob as only usable on one side.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+pub struct CodeBoundaryRule {
+    /// A glob matching the paths of the modules, relative to the root of the
+    /// boundary, e.g. `src/server/**` or `**/node_modules/server-only/**`.
+    pub modules: String,
+    /// The only side the modules can be used on.
+    pub side: CodeSide,
+}
+
+/// A [ResolvePlugin] for the resolve options of one `side`, which replaces
+/// modules that are restricted to the other side with poison modules.
+#[turbo_tasks::value]
+pub struct CodeBoundaryResolvePlugin {
+    root: FileSystemPathVc,
+    side: CodeSide,
+    rules: Vec<CodeBoundaryRule>,
+}
+
+impl CodeBoundaryResolvePluginVc {
+    pub fn new(root: FileSystemPathVc, side: CodeSide, rules: Vec<CodeBoundaryRule>) -> Self {
+        CodeBoundaryResolvePlugin { root, side, rules }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ResolvePlugin for CodeBoundaryResolvePlugin {
+    #[turbo_tasks::function]
+    fn after_resolve_condition(&self) -> ResolvePluginConditionVc {
+        ResolvePluginConditionVc::new(self.root, GlobVc::new("**"))
+    }
+
+    #[turbo_tasks::function]
+    async fn after_resolve(
+        &self,
+        fs_path: FileSystemPathVc,
+        context: FileSystemPathVc,
+        request: RequestVc,
+    ) -> Result<ResolveResultOptionVc> {
+        let root = self.root.await?;
+        let path = fs_path.await?;
+        let Some(relative) = root.get_path_to(&path) else {
+            return Ok(ResolveResultOptionVc::none());
+        };
+        let forbidden = self.side.other();
+        for rule in self.rules.iter().filter(|rule| rule.side == forbidden) {
+            if !GlobVc::new(&rule.modules).await?.execute(relative) {
+                continue;
+            }
+            let request = request.to_string().await?;
+            let message = format!(
+                "{request} can only be used on the {forbidden}, but it has been imported from \
+                 {side} code",
+                side = self.side
+            );
+            CodeBoundaryIssue {
+                context,
+                request: request.to_string(),
+                message: message.clone(),
+            }
+            .cell()
+            .as_issue()
+            .emit();
+
+            let code = format!("throw new Error({});\n", serde_json::to_string(&message)?);
+            let poison = VirtualAssetVc::new_with_ident(
+                AssetIdentVc::from_path(fs_path.append(".poison.js"))
+                    .with_modifier(StringVc::cell(format!("{} poison", self.side))),
+                File::from(code).into(),
+            );
+            return Ok(ResolveResultOptionVc::some(
+                ResolveResult::asset(poison.into()).into(),
+            ));
+        }
+        Ok(ResolveResultOptionVc::none())
+    }
+}
+
+#[turbo_tasks::value(shared)]
+pub struct CodeBoundaryIssue {
+    pub context: FileSystemPathVc,
+    pub request: String,
+    pub message: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for CodeBoundaryIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("resolve".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell(format!("Invalid import of {}", self.request))
+    }
+
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.context
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> StringVc {
+        StringVc::cell(self.message.clone())
+    }
+}
@@ -0,0 +1,378 @@
+//! Explains how a request is resolved, for debugging "module not found"
+//! errors.
+//!
+//! [explain_resolve] resolves the request like [resolve], with a
+//! [ResolveTracer] the resolver reports its steps to: the import map entries
+//! it applies, the files and directories it probes, the package.json fields it
+//! reads and the conditions of exports fields it tries.
+//!
+//! [resolve]: super::resolve
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Write},
+    sync::Mutex,
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, ValueToString};
+use turbo_tasks_fs::FileSystemPathVc;
+
+use super::{
+    exports::ExportsValue,
+    handle_resolve_plugins,
+    options::{ConditionValue, ResolveOptionsVc},
+    parse::RequestVc,
+    pattern::Pattern,
+    resolve_internal_traced, PrimaryResolveResult,
+};
+use crate::asset::Asset;
+
+/// What a probed path turned out to be.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProbeResult {
+    File,
+    Directory,
+    NotFound,
+}
+
+/// A step the resolver took.
+#[derive(Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ResolveStep {
+    /// Resolves a request in a directory. The steps for it follow one level
+    /// deeper.
+    Request {
+        request: String,
+        context: String,
+    },
+    /// An entry of an import map matched the request.
+    #[serde(rename_all = "camelCase")]
+    Alias {
+        import_map: String,
+        mapping: String,
+    },
+    /// A file or directory was looked up.
+    Probe {
+        path: String,
+        result: ProbeResult,
+    },
+    /// A field of a package.json was read. It's `None` when the field doesn't
+    /// exist.
+    #[serde(rename_all = "camelCase")]
+    Field {
+        package_json: String,
+        field: String,
+        value: Option<String>,
+    },
+    /// A condition of an exports field was tried, or skipped when it's unset.
+    Condition {
+        condition: String,
+        value: ConditionValue,
+    },
+    /// The exports field maps the `subpath` to the `targets`, which are
+    /// resolved relative to the package.
+    Exports {
+        subpath: String,
+        targets: Vec<String>,
+    },
+    Note {
+        message: String,
+    },
+}
+
+impl Display for ResolveStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolveStep::Request { request, context } => {
+                write!(f, "resolving {request} in {context}")
+            }
+            ResolveStep::Alias {
+                import_map,
+                mapping,
+            } => write!(f, "{import_map}: {mapping}"),
+            ResolveStep::Probe { path, result } => match result {
+                ProbeResult::File => write!(f, "found file {path}"),
+                ProbeResult::Directory => write!(f, "found directory {path}"),
+                ProbeResult::NotFound => write!(f, "not found: {path}"),
+            },
+            ResolveStep::Field {
+                package_json,
+                field,
+                value,
+            } => match value {
+                Some(value) => write!(f, "field \"{field}\" of {package_json} is {value}"),
+                None => write!(f, "field \"{field}\" of {package_json} doesn't exist"),
+            },
+            ResolveStep::Condition { condition, value } => match value {
+                ConditionValue::Set => write!(f, "condition \"{condition}\" matches"),
+                ConditionValue::Unset => write!(f, "condition \"{condition}\" doesn't match"),
+                ConditionValue::Unknown => {
+                    write!(f, "condition \"{condition}\" is unknown and tried as well")
+                }
+            },
+            ResolveStep::Exports { subpath, targets } => {
+                if targets.is_empty() {
+                    write!(f, "the exports field doesn't export \"{subpath}\"")
+                } else {
+                    write!(
+                        f,
+                        "the exports field maps \"{subpath}\" to {}",
+                        targets.join(", ")
+                    )
+                }
+            }
+            ResolveStep::Note { message } => f.write_str(message),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub struct ResolveTraceEntry {
+    /// How deep the step is nested in the steps of other requests, e.g. of
+    /// the request an alias points to.
+    pub depth: usize,
+    pub step: ResolveStep,
+}
+
+/// Every step the resolver took for a request, and what it resolved to.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct ResolveExplanation {
+    pub steps: Vec<ResolveTraceEntry>,
+    /// The idents of the resolved assets, or what the request resolved to
+    /// otherwise, e.g. `external`. It's empty when the request couldn't be
+    /// resolved.
+    pub results: Vec<String>,
+}
+
+impl ResolveExplanation {
+    /// The steps as an indented list, followed by the results.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for ResolveTraceEntry { depth, step } in &self.steps {
+            // Writing to a String can't fail.
+            let _ = writeln!(text, "{}- {step}", "  ".repeat(*depth));
+        }
+        if self.results.is_empty() {
+            text.push_str("=> unresolveable\n");
+        }
+        for result in &self.results {
+            let _ = writeln!(text, "=> {result}");
+        }
+        text
+    }
+}
+
+/// Explains how the `request` is resolved in the `context` with the
+/// `options`, see [ResolveExplanation].
+#[turbo_tasks::function]
+pub async fn explain_resolve(
+    context: FileSystemPathVc,
+    request: RequestVc,
+    options: ResolveOptionsVc,
+) -> Result<ResolveExplanationVc> {
+    let tracer = ResolveTracer::default();
+    let raw_result = resolve_internal_traced(context, request, options, Some(&tracer)).await?;
+    let result = handle_resolve_plugins(context, request, options, raw_result);
+
+    let mut results = Vec::new();
+    for primary in result.await?.primary.iter() {
+        results.push(match primary {
+            PrimaryResolveResult::Asset(asset) => asset.ident().to_string().await?.clone_value(),
+            PrimaryResolveResult::OriginalReferenceExternal => "external".to_string(),
+            PrimaryResolveResult::OriginalReferenceTypeExternal(name) => {
+                format!("external {name}")
+            }
+            PrimaryResolveResult::Ignore => "ignored".to_string(),
+            PrimaryResolveResult::Empty => "empty module".to_string(),
+            PrimaryResolveResult::Custom(id) => format!("custom result {id}"),
+            PrimaryResolveResult::Unresolveable => continue,
+        });
+    }
+
+    Ok(ResolveExplanation {
+        steps: tracer.into_steps(),
+        results,
+    }
+    .cell())
+}
+
+/// Collects the steps of a traced resolve. The resolver reports to it while
+/// it resolves a request in place instead of through [resolve_internal], so
+/// the steps are in the order they are taken.
+///
+/// [resolve_internal]: super::resolve_internal
+#[derive(Default)]
+pub(super) struct ResolveTracer {
+    state: Mutex<TracerState>,
+}
+
+#[derive(Default)]
+struct TracerState {
+    steps: Vec<ResolveTraceEntry>,
+    depth: usize,
+}
+
+impl ResolveTracer {
+    pub(super) fn push(&self, step: ResolveStep) {
+        let mut state = self.state.lock().unwrap();
+        let depth = state.depth;
+        state.steps.push(ResolveTraceEntry { depth, step });
+    }
+
+    pub(super) fn note(&self, message: impl Into<String>) {
+        self.push(ResolveStep::Note {
+            message: message.into(),
+        });
+    }
+
+    pub(super) async fn probe(&self, path: FileSystemPathVc, result: ProbeResult) -> Result<()> {
+        self.push(ResolveStep::Probe {
+            path: path.to_string().await?.clone_value(),
+            result,
+        });
+        Ok(())
+    }
+
+    /// The following steps belong to the last one.
+    pub(super) fn nest(&self) {
+        self.state.lock().unwrap().depth += 1;
+    }
+
+    pub(super) fn unnest(&self) {
+        self.state.lock().unwrap().depth -= 1;
+    }
+
+    fn into_steps(self) -> Vec<ResolveTraceEntry> {
+        self.state.into_inner().unwrap().steps
+    }
+}
+
+/// The constant alternatives of a pattern, which are probed one by one.
+pub(super) fn constant_alternatives(pattern: &Pattern) -> Option<Vec<&str>> {
+    match pattern {
+        Pattern::Constant(constant) => Some(vec![constant.as_str()]),
+        Pattern::Alternatives(list) => list
+            .iter()
+            .map(|pattern| match pattern {
+                Pattern::Constant(constant) => Some(constant.as_str()),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Collects the conditions of the `value` in the order they are tried, until
+/// a result is found. Returns whether a result was found.
+pub(super) fn trace_conditions<'a>(
+    value: &'a ExportsValue,
+    conditions: &BTreeMap<String, ConditionValue>,
+    unspecified_conditions: &ConditionValue,
+    tried: &mut Vec<(&'a str, ConditionValue)>,
+) -> bool {
+    match value {
+        ExportsValue::Alternatives(list) => list
+            .iter()
+            .any(|value| trace_conditions(value, conditions, unspecified_conditions, tried)),
+        ExportsValue::Conditional(list) => {
+            for (condition, value) in list {
+                let condition_value = if condition == "default" {
+                    ConditionValue::Set
+                } else {
+                    conditions
+                        .get(condition)
+                        .unwrap_or(unspecified_conditions)
+                        .clone()
+                };
+                tried.push((condition, condition_value.clone()));
+                let found = condition_value != ConditionValue::Unset
+                    && trace_conditions(value, conditions, unspecified_conditions, tried);
+                // Unknown conditions may not match, so the following
+                // conditions are tried as well.
+                if found && condition_value == ConditionValue::Set {
+                    return true;
+                }
+            }
+            false
+        }
+        ExportsValue::Result(_) | ExportsValue::Excluded => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{
+        trace_conditions, ProbeResult, ResolveExplanation, ResolveStep, ResolveTraceEntry,
+    };
+    use crate::resolve::{exports::ExportsValue, options::ConditionValue};
+
+    #[test]
+    fn traces_conditions_until_a_result_is_found() {
+        let value = ExportsValue::Conditional(vec![
+            (
+                "browser".to_string(),
+                ExportsValue::Result("./browser.js".to_string()),
+            ),
+            (
+                "import".to_string(),
+                ExportsValue::Result("./index.mjs".to_string()),
+            ),
+            (
+                "default".to_string(),
+                ExportsValue::Result("./index.js".to_string()),
+            ),
+        ]);
+        let conditions = BTreeMap::from([
+            ("browser".to_string(), ConditionValue::Unset),
+            ("import".to_string(), ConditionValue::Set),
+        ]);
+        let mut tried = Vec::new();
+        assert!(trace_conditions(
+            &value,
+            &conditions,
+            &ConditionValue::Unset,
+            &mut tried
+        ));
+        assert_eq!(
+            tried,
+            [
+                ("browser", ConditionValue::Unset),
+                ("import", ConditionValue::Set)
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_nested_steps() {
+        let explanation = ResolveExplanation {
+            steps: vec![
+                ResolveTraceEntry {
+                    depth: 0,
+                    step: ResolveStep::Request {
+                        request: "./utils".to_string(),
+                        context: "[project]/src".to_string(),
+                    },
+                },
+                ResolveTraceEntry {
+                    depth: 1,
+                    step: ResolveStep::Probe {
+                        path: "[project]/src/utils".to_string(),
+                        result: ProbeResult::NotFound,
+                    },
+                },
+            ],
+            results: vec![],
+        };
+        assert_eq!(
+            explanation.to_text(),
+            "- resolving ./utils in [project]/src\n  - not found: [project]/src/utils\n=> \
+             unresolveable\n"
+        );
+    }
+}
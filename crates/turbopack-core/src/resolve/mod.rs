@@ -8,25 +8,29 @@ use std::{
 use anyhow::{anyhow, Result};
 use serde_json::Value as JsonValue;
 use turbo_tasks::{
-    primitives::{BoolVc, StringVc, StringsVc},
+    primitives::{BoolVc, JsonValueVc, StringVc, StringsVc},
     TryJoinIterExt, Value, ValueToString, ValueToStringVc,
 };
 use turbo_tasks_fs::{
     util::{normalize_path, normalize_request},
-    FileJsonContent, FileJsonContentVc, FileSystemEntryType, FileSystemPathVc, RealPathResult,
+    FileJsonContent, FileSystemEntryType, FileSystemPathVc, RealPathResult,
 };
 
 use self::{
+    explain::{constant_alternatives, trace_conditions, ProbeResult, ResolveStep, ResolveTracer},
     exports::ExportsField,
     options::{
-        resolve_modules_options, ImportMapResult, ResolveInPackage, ResolveIntoPackage,
-        ResolveModules, ResolveModulesOptionsVc, ResolveOptionsVc,
+        resolve_modules_options, ImportMapResult, ImportMapResultVc, ResolveInPackage,
+        ResolveIntoPackage, ResolveModules, ResolveModulesOptionsVc, ResolveOptionsVc,
     },
     parse::{Request, RequestVc},
     pattern::QueryMapVc,
+    pnp::find_pnp_package,
+    workspace::{find_workspace_dependency, injected_workspace_package},
 };
 use crate::{
     asset::{Asset, AssetOptionVc, AssetVc, AssetsVc},
+    compile_report::{measure, CompilePhase},
     issue::{
         package_json::{PackageJsonIssue, PackageJsonIssueVc},
         resolve::{ResolvingIssue, ResolvingIssueVc},
@@ -42,6 +46,8 @@ use crate::{
 };
 
 mod alias_map;
+pub mod boundary;
+pub mod explain;
 pub(crate) mod exports;
 pub mod node;
 pub mod options;
@@ -49,6 +55,9 @@ pub mod origin;
 pub mod parse;
 pub mod pattern;
 pub mod plugin;
+pub mod pnp;
+pub mod policy;
+pub mod workspace;
 
 pub use alias_map::{
     AliasMap, AliasMapIntoIter, AliasMapLookupIterator, AliasMatch, AliasPattern, AliasTemplate,
@@ -387,32 +396,47 @@ enum ExportsFieldResult {
     None,
 }
 
+/// Reads a top-level `field` of a package.json, or `null` when the field or
+/// the file doesn't exist.
+///
+/// Resolving reads package.json fields through this instead of reading the
+/// whole manifest. This way each manifest is parsed once per content and the
+/// fields are shared by all resolve options, i.e. across transitions. It also
+/// means that resolutions are only invalidated when a field they use changes.
+#[turbo_tasks::function]
+async fn package_json_field(
+    package_json_path: FileSystemPathVc,
+    field: &str,
+) -> Result<JsonValueVc> {
+    Ok(JsonValueVc::cell(
+        match &*package_json_path.read_json().await? {
+            FileJsonContent::Content(package_json) => package_json[field].clone(),
+            _ => JsonValue::Null,
+        },
+    ))
+}
+
 #[turbo_tasks::function]
 async fn exports_field(
     package_json_path: FileSystemPathVc,
-    package_json: FileJsonContentVc,
     field: &str,
 ) -> Result<ExportsFieldResultVc> {
-    if let FileJsonContent::Content(package_json) = &*package_json.await? {
-        let field_value = &package_json[field];
-        if let serde_json::Value::Null = field_value {
-            return Ok(ExportsFieldResult::None.into());
-        }
-        let exports_field: Result<ExportsField> = field_value.try_into();
-        match exports_field {
-            Ok(exports_field) => Ok(ExportsFieldResult::Some(exports_field).into()),
-            Err(err) => {
-                let issue: PackageJsonIssueVc = PackageJsonIssue {
-                    path: package_json_path,
-                    error_message: err.to_string(),
-                }
-                .into();
-                issue.as_issue().emit();
-                Ok(ExportsFieldResult::None.into())
+    let field_value = &*package_json_field(package_json_path, field).await?;
+    if let serde_json::Value::Null = field_value {
+        return Ok(ExportsFieldResult::None.into());
+    }
+    let exports_field: Result<ExportsField> = field_value.try_into();
+    match exports_field {
+        Ok(exports_field) => Ok(ExportsFieldResult::Some(exports_field).into()),
+        Err(err) => {
+            let issue: PackageJsonIssueVc = PackageJsonIssue {
+                path: package_json_path,
+                error_message: err.to_string(),
             }
+            .into();
+            issue.as_issue().emit();
+            Ok(ExportsFieldResult::None.into())
         }
-    } else {
-        Ok(ExportsFieldResult::None.into())
     }
 }
 
@@ -477,6 +501,13 @@ async fn find_package(
     let mut packages = vec![];
     let mut references = vec![];
     let options = options.await?;
+    let workspace_root = options.modules.iter().find_map(|resolve_modules| {
+        if let ResolveModules::Workspace(root) = resolve_modules {
+            Some(*root)
+        } else {
+            None
+        }
+    });
     for resolve_modules in &options.modules {
         match resolve_modules {
             ResolveModules::Nested(root_vc, names) => {
@@ -490,7 +521,14 @@ async fn find_package(
                         if let Some(fs_path) = dir_exists(fs_path, &mut references).await? {
                             let fs_path = fs_path.join(&package_name);
                             if let Some(fs_path) = dir_exists(fs_path, &mut references).await? {
-                                packages.push(fs_path);
+                                let injected = match workspace_root {
+                                    Some(root) => {
+                                        injected_workspace_package(fs_path, &package_name, root)
+                                            .await?
+                                    }
+                                    None => None,
+                                };
+                                packages.push(injected.unwrap_or(fs_path));
                             }
                         }
                     }
@@ -509,6 +547,27 @@ async fn find_package(
                 }
             }
             ResolveModules::Registry(_, _) => todo!(),
+            ResolveModules::Workspace(root) => {
+                if let Some(package) =
+                    find_workspace_dependency(context, &package_name, *root, &mut references)
+                        .await?
+                {
+                    return Ok(FindPackageResultVc::cell(FindPackageResult {
+                        packages: vec![package],
+                        references,
+                    }));
+                }
+            }
+            ResolveModules::PnP(root) => {
+                if let Some(package) =
+                    find_pnp_package(context, &package_name, *root, &mut references).await?
+                {
+                    return Ok(FindPackageResultVc::cell(FindPackageResult {
+                        packages: vec![package],
+                        references,
+                    }));
+                }
+            }
         }
     }
     Ok(FindPackageResultVc::cell(FindPackageResult {
@@ -682,6 +741,44 @@ async fn resolve_internal(
     context: FileSystemPathVc,
     request: RequestVc,
     options: ResolveOptionsVc,
+) -> Result<ResolveResultVc> {
+    measure(
+        CompilePhase::Resolve,
+        None,
+        resolve_internal_inner(context, request, options, None),
+    )
+    .await
+}
+
+/// Resolves a nested request. Without a `tracer`, this is [resolve_internal].
+/// With one, the request is resolved again in place, so its steps are reported
+/// to the tracer as well, one level deeper.
+fn resolve_internal_traced<'a>(
+    context: FileSystemPathVc,
+    request: RequestVc,
+    options: ResolveOptionsVc,
+    tracer: Option<&'a ResolveTracer>,
+) -> Pin<Box<dyn Future<Output = Result<ResolveResultVc>> + Send + 'a>> {
+    Box::pin(async move {
+        let Some(tracer) = tracer else {
+            return Ok(resolve_internal(context, request, options));
+        };
+        tracer.push(ResolveStep::Request {
+            request: request.to_string().await?.clone_value(),
+            context: context.to_string().await?.clone_value(),
+        });
+        tracer.nest();
+        let result = resolve_internal_inner(context, request, options, Some(tracer)).await;
+        tracer.unnest();
+        result
+    })
+}
+
+async fn resolve_internal_inner(
+    context: FileSystemPathVc,
+    request: RequestVc,
+    options: ResolveOptionsVc,
+    tracer: Option<&ResolveTracer>,
 ) -> Result<ResolveResultVc> {
     // This explicit deref of `options` is necessary
     #[allow(clippy::explicit_auto_deref)]
@@ -689,11 +786,14 @@ async fn resolve_internal(
 
     // Apply import mappings if provided
     if let Some(import_map) = &options_value.import_map {
-        let result_ref = import_map.lookup(context, request).await?;
+        let result_vc = import_map.lookup(context, request);
+        let result_ref = result_vc.await?;
         let result = &*result_ref;
         if !matches!(result, ImportMapResult::NoEntry) {
+            trace_import_map_result(tracer, "import map", result_vc).await?;
             let resolved_result =
-                resolve_import_map_result(result, context, context, request, options).await?;
+                resolve_import_map_result(result, context, context, request, options, tracer)
+                    .await?;
             // We might have matched an alias in the import map, but there is no guarantee
             // the alias actually resolves to something. For instance, a tsconfig.json
             // `compilerOptions.paths` option might alias "@*" to "./*", which
@@ -703,17 +803,25 @@ async fn resolve_internal(
             if let Some(result) = resolved_result {
                 return Ok(result);
             }
+            if let Some(tracer) = tracer {
+                tracer.note("the alias doesn't apply, so the request is resolved as is");
+            }
         }
     }
 
     let request_value = request.await?;
     let result = match &*request_value {
-        Request::Dynamic => ResolveResult::unresolveable().into(),
+        Request::Dynamic => {
+            if let Some(tracer) = tracer {
+                tracer.note("the request is dynamic and can't be resolved statically");
+            }
+            ResolveResult::unresolveable().into()
+        }
         Request::Alternatives { requests } => {
-            let results = requests
-                .iter()
-                .map(|req| resolve_internal(context, *req, options))
-                .collect();
+            let mut results = Vec::with_capacity(requests.len());
+            for req in requests {
+                results.push(resolve_internal_traced(context, *req, options, tracer).await?);
+            }
             merge_results(results)
         }
         Request::Raw {
@@ -728,18 +836,27 @@ async fn resolve_internal(
                 PatternVc::new(path.clone()),
             )
             .await?;
+            if let Some(tracer) = tracer {
+                trace_misses(tracer, context, path, &matches).await?;
+            }
             for m in matches.iter() {
                 match m {
                     PatternMatch::File(_, path) => {
-                        results
-                            .push(resolved(*path, context, request, options_value, options).await?);
+                        if let Some(tracer) = tracer {
+                            tracer.probe(*path, ProbeResult::File).await?;
+                        }
+                        results.push(
+                            resolved(*path, context, request, options_value, options, tracer)
+                                .await?,
+                        );
                     }
                     PatternMatch::Directory(_, path) => {
+                        if let Some(tracer) = tracer {
+                            tracer.probe(*path, ProbeResult::Directory).await?;
+                        }
                         let package_json_path = path.join("package.json");
-                        let package_json = package_json_path.read_json();
                         results.push(
-                            resolve_into_folder(*path, package_json, package_json_path, options)
-                                .await?,
+                            resolve_into_folder(*path, package_json_path, options, tracer).await?,
                         );
                     }
                 }
@@ -757,18 +874,31 @@ async fn resolve_internal(
                 patterns.push(path);
             }
             let new_pat = Pattern::alternatives(patterns);
+            if let Some(tracer) = tracer {
+                if !options_value.extensions.is_empty() {
+                    tracer.note(format!(
+                        "trying the request as is and with the extensions {}",
+                        options_value.extensions.join(", ")
+                    ));
+                }
+            }
 
-            resolve_internal(
+            resolve_internal_traced(
                 context,
                 RequestVc::raw(Value::new(new_pat), *force_in_context),
                 options,
+                tracer,
             )
+            .await?
         }
         Request::Module {
             module,
             path,
             query,
-        } => resolve_module_request(context, options, options_value, module, path, query).await?,
+        } => {
+            resolve_module_request(context, options, options_value, module, path, query, tracer)
+                .await?
+        }
         Request::ServerRelative { path } => {
             let mut new_pat = path.clone();
             new_pat.push_front(".".to_string().into());
@@ -788,7 +918,7 @@ async fn resolve_internal(
             .into();
             issue.as_issue().emit();
 
-            resolve_internal(context.root(), relative, options)
+            resolve_internal_traced(context.root(), relative, options, tracer).await?
         }
         Request::Windows { path: _ } => {
             let issue: ResolvingIssueVc = ResolvingIssue {
@@ -800,10 +930,18 @@ async fn resolve_internal(
             }
             .into();
             issue.as_issue().emit();
+            if let Some(tracer) = tracer {
+                tracer.note("windows imports are not implemented yet");
+            }
 
             ResolveResult::unresolveable().into()
         }
-        Request::Empty => ResolveResult::unresolveable().into(),
+        Request::Empty => {
+            if let Some(tracer) = tracer {
+                tracer.note("the request is empty");
+            }
+            ResolveResult::unresolveable().into()
+        }
         Request::PackageInternal { path: _ } => {
             let issue: ResolvingIssueVc = ResolvingIssue {
                 request_type: "package internal import: not implemented yet".to_string(),
@@ -814,15 +952,23 @@ async fn resolve_internal(
             }
             .into();
             issue.as_issue().emit();
+            if let Some(tracer) = tracer {
+                tracer.note("package internal imports are not implemented yet");
+            }
             ResolveResult::unresolveable().into()
         }
         Request::Uri {
             protocol,
             remainder,
-        } => ResolveResult::primary(PrimaryResolveResult::OriginalReferenceTypeExternal(
-            format!("{}{}", protocol, remainder),
-        ))
-        .into(),
+        } => {
+            if let Some(tracer) = tracer {
+                tracer.note("URLs are external");
+            }
+            ResolveResult::primary(PrimaryResolveResult::OriginalReferenceTypeExternal(
+                format!("{}{}", protocol, remainder),
+            ))
+            .into()
+        }
         Request::Unknown { path } => {
             let issue: ResolvingIssueVc = ResolvingIssue {
                 request_type: format!("unknown import: `{}`", path),
@@ -833,6 +979,9 @@ async fn resolve_internal(
             }
             .into();
             issue.as_issue().emit();
+            if let Some(tracer) = tracer {
+                tracer.note("the request has an unknown format");
+            }
             ResolveResult::unresolveable().into()
         }
     };
@@ -840,10 +989,15 @@ async fn resolve_internal(
     // Apply fallback import mappings if provided
     if let Some(import_map) = &options_value.fallback_import_map {
         if *result.is_unresolveable().await? {
-            let result_ref = import_map.lookup(context, request).await?;
+            let result_vc = import_map.lookup(context, request);
+            let result_ref = result_vc.await?;
             let result = &*result_ref;
+            if !matches!(result, ImportMapResult::NoEntry) {
+                trace_import_map_result(tracer, "fallback import map", result_vc).await?;
+            }
             let resolved_result =
-                resolve_import_map_result(result, context, context, request, options).await?;
+                resolve_import_map_result(result, context, context, request, options, tracer)
+                    .await?;
             if let Some(result) = resolved_result {
                 return Ok(result);
             }
@@ -853,11 +1007,55 @@ async fn resolve_internal(
     Ok(result)
 }
 
+/// Reports that an entry of the `import_map` matched.
+async fn trace_import_map_result(
+    tracer: Option<&ResolveTracer>,
+    import_map: &str,
+    result: ImportMapResultVc,
+) -> Result<()> {
+    if let Some(tracer) = tracer {
+        tracer.push(ResolveStep::Alias {
+            import_map: import_map.to_string(),
+            mapping: result.to_string().await?.clone_value(),
+        });
+    }
+    Ok(())
+}
+
+/// Reports the constant alternatives of the `pattern` that weren't found,
+/// which is what's usually missing from a "module not found" error.
+async fn trace_misses(
+    tracer: &ResolveTracer,
+    context: FileSystemPathVc,
+    pattern: &Pattern,
+    matches: &[PatternMatch],
+) -> Result<()> {
+    let Some(candidates) = constant_alternatives(pattern) else {
+        if matches.is_empty() {
+            tracer.note(format!("no file or directory matches {pattern}"));
+        }
+        return Ok(());
+    };
+    for candidate in candidates {
+        let found = matches.iter().any(|m| match m {
+            PatternMatch::File(matched, _) | PatternMatch::Directory(matched, _) => {
+                matched == candidate
+            }
+        });
+        if !found {
+            tracer
+                .probe(context.join(candidate), ProbeResult::NotFound)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
 async fn resolve_into_folder(
     package_path: FileSystemPathVc,
-    package_json: FileJsonContentVc,
     package_json_path: FileSystemPathVc,
     options: ResolveOptionsVc,
+    tracer: Option<&ResolveTracer>,
 ) -> Result<ResolveResultVc> {
     let options_value = options.await?;
     for resolve_into_package in options_value.into_package.iter() {
@@ -871,24 +1069,38 @@ async fn resolve_into_folder(
                         )
                     })?;
                 let request = RequestVc::parse(Value::new(str.into()));
-                return Ok(resolve_internal(package_path, request, options));
+                return resolve_internal_traced(package_path, request, options, tracer).await;
             }
             ResolveIntoPackage::MainField(name) => {
-                if let FileJsonContent::Content(package_json) = &*package_json.await? {
-                    if let Some(field_value) = package_json[name].as_str() {
-                        let request =
-                            RequestVc::parse(Value::new(normalize_request(field_value).into()));
-
-                        let result = &*resolve_internal(package_path, request, options).await?;
-                        // we are not that strict when a main field fails to resolve
-                        // we continue to try other alternatives
-                        if !result.is_unresolveable() {
-                            let mut result = result.clone();
-                            result.add_reference(
-                                AffectingResolvingAssetReferenceVc::new(package_json_path).into(),
-                            );
-                            return Ok(result.into());
-                        }
+                let field_value = package_json_field(package_json_path, name).await?;
+                let field_value = field_value.as_str();
+                if let Some(tracer) = tracer {
+                    tracer.push(ResolveStep::Field {
+                        package_json: package_json_path.to_string().await?.clone_value(),
+                        field: name.clone(),
+                        value: field_value.map(|value| format!("\"{value}\"")),
+                    });
+                }
+                if let Some(field_value) = field_value {
+                    let request =
+                        RequestVc::parse(Value::new(normalize_request(field_value).into()));
+
+                    let result = &*resolve_internal_traced(package_path, request, options, tracer)
+                        .await?
+                        .await?;
+                    // we are not that strict when a main field fails to resolve
+                    // we continue to try other alternatives
+                    if !result.is_unresolveable() {
+                        let mut result = result.clone();
+                        result.add_reference(
+                            AffectingResolvingAssetReferenceVc::new(package_json_path).into(),
+                        );
+                        return Ok(result.into());
+                    }
+                    if let Some(tracer) = tracer {
+                        tracer.note(format!(
+                            "field \"{name}\" doesn't resolve, trying the next one"
+                        ));
                     }
                 }
             }
@@ -898,8 +1110,14 @@ async fn resolve_into_folder(
                 unspecified_conditions,
             } => {
                 if let ExportsFieldResult::Some(exports_field) =
-                    &*exports_field(package_json_path, package_json, field).await?
+                    &*exports_field(package_json_path, field).await?
                 {
+                    if let Some(tracer) = tracer {
+                        tracer.note(format!(
+                            "{} has an \"{field}\" field, so other fields don't apply",
+                            package_json_path.to_string().await?
+                        ));
+                    }
                     // other options do not apply anymore when an exports field exist
                     return handle_exports_field(
                         package_path,
@@ -909,7 +1127,9 @@ async fn resolve_into_folder(
                         ".",
                         conditions,
                         unspecified_conditions,
-                    );
+                        tracer,
+                    )
+                    .await;
                 }
             }
         }
@@ -924,6 +1144,7 @@ async fn resolve_module_request(
     module: &str,
     path: &Pattern,
     _: &QueryMapVc,
+    tracer: Option<&ResolveTracer>,
 ) -> Result<ResolveResultVc> {
     let result = find_package(
         context,
@@ -933,6 +1154,9 @@ async fn resolve_module_request(
     .await?;
 
     if result.packages.is_empty() {
+        if let Some(tracer) = tracer {
+            tracer.note(format!("no package named \"{module}\" was found"));
+        }
         return Ok(ResolveResult::unresolveable_with_references(result.references.clone()).into());
     }
 
@@ -946,12 +1170,13 @@ async fn resolve_module_request(
     // "[baseUrl]/foo/bar" or "[baseUrl]/node_modules/foo/bar", and we'll need to
     // try both.
     for package_path in &result.packages {
+        if let Some(tracer) = tracer {
+            tracer.probe(*package_path, ProbeResult::Directory).await?;
+        }
         let package_json_path = package_path.join("package.json");
-        let package_json = package_json_path.read_json();
         if is_match {
             results.push(
-                resolve_into_folder(*package_path, package_json, package_json_path, options)
-                    .await?,
+                resolve_into_folder(*package_path, package_json_path, options, tracer).await?,
             );
         }
         if could_match_others {
@@ -963,9 +1188,9 @@ async fn resolve_module_request(
                             results.push(
                                 resolve_into_folder(
                                     *package_path,
-                                    package_json,
                                     package_json_path,
                                     options,
+                                    tracer,
                                 )
                                 .await?,
                             );
@@ -977,18 +1202,22 @@ async fn resolve_module_request(
                         unspecified_conditions,
                     } => {
                         if let ExportsFieldResult::Some(exports_field) =
-                            &*exports_field(package_json_path, package_json, field).await?
+                            &*exports_field(package_json_path, field).await?
                         {
                             if let Some(path) = path.clone().into_string() {
-                                results.push(handle_exports_field(
-                                    *package_path,
-                                    package_json_path,
-                                    options,
-                                    exports_field,
-                                    &format!(".{path}"),
-                                    conditions,
-                                    unspecified_conditions,
-                                )?);
+                                results.push(
+                                    handle_exports_field(
+                                        *package_path,
+                                        package_json_path,
+                                        options,
+                                        exports_field,
+                                        &format!(".{path}"),
+                                        conditions,
+                                        unspecified_conditions,
+                                        tracer,
+                                    )
+                                    .await?,
+                                );
                             } else {
                                 todo!("pattern into an exports field is not implemented yet");
                             }
@@ -1002,7 +1231,7 @@ async fn resolve_module_request(
             let mut new_pat = path.clone();
             new_pat.push_front(".".to_string().into());
             let relative = RequestVc::relative(Value::new(new_pat), true);
-            results.push(resolve_internal(*package_path, relative, options));
+            results.push(resolve_internal_traced(*package_path, relative, options, tracer).await?);
         }
     }
 
@@ -1018,6 +1247,7 @@ async fn resolve_import_map_result(
     original_context: FileSystemPathVc,
     original_request: RequestVc,
     options: ResolveOptionsVc,
+    tracer: Option<&ResolveTracer>,
 ) -> Result<Option<ResolveResultVc>> {
     Ok(match result {
         ImportMapResult::Result(result) => Some(*result),
@@ -1030,23 +1260,48 @@ async fn resolve_import_map_result(
             {
                 None
             } else {
-                Some(resolve_internal(context, request, options))
+                Some(resolve_internal_traced(context, request, options, tracer).await?)
             }
         }
         ImportMapResult::Alternatives(list) => {
-            let results = list
-                .iter()
-                .map(|result| {
-                    resolve_import_map_result_boxed(
-                        result,
-                        context,
-                        original_context,
-                        original_request,
-                        options,
-                    )
-                })
-                .try_join()
-                .await?;
+            let results = if let Some(tracer) = tracer {
+                // The alternatives are resolved one after the other, so their steps
+                // don't interleave.
+                tracer.note("the first alternative which resolves is used");
+                tracer.nest();
+                let mut results = Vec::with_capacity(list.len());
+                for result in list {
+                    trace_import_map_result(Some(tracer), "alternative", result.clone().cell())
+                        .await?;
+                    results.push(
+                        resolve_import_map_result_boxed(
+                            result,
+                            context,
+                            original_context,
+                            original_request,
+                            options,
+                            Some(tracer),
+                        )
+                        .await?,
+                    );
+                }
+                tracer.unnest();
+                results
+            } else {
+                list.iter()
+                    .map(|result| {
+                        resolve_import_map_result_boxed(
+                            result,
+                            context,
+                            original_context,
+                            original_request,
+                            options,
+                            None,
+                        )
+                    })
+                    .try_join()
+                    .await?
+            };
             Some(ResolveResultVc::select_first(
                 results.into_iter().flatten().collect(),
             ))
@@ -1061,13 +1316,22 @@ fn resolve_import_map_result_boxed<'a>(
     original_context: FileSystemPathVc,
     original_request: RequestVc,
     options: ResolveOptionsVc,
+    tracer: Option<&'a ResolveTracer>,
 ) -> Pin<Box<dyn Future<Output = Result<Option<ResolveResultVc>>> + Send + 'a>> {
     Box::pin(async move {
-        resolve_import_map_result(result, context, original_context, original_request, options)
-            .await
+        resolve_import_map_result(
+            result,
+            context,
+            original_context,
+            original_request,
+            options,
+            tracer,
+        )
+        .await
     })
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn resolve_alias_field_result(
     result: &JsonValue,
     refs: Vec<AssetReferenceVc>,
@@ -1076,18 +1340,28 @@ async fn resolve_alias_field_result(
     issue_context: FileSystemPathVc,
     issue_request: &str,
     field_name: &str,
+    tracer: Option<&ResolveTracer>,
 ) -> Result<ResolveResultVc> {
+    if let Some(tracer) = tracer {
+        tracer.push(ResolveStep::Field {
+            package_json: issue_context.to_string().await?.clone_value(),
+            field: format!("{field_name}[\"{issue_request}\"]"),
+            value: Some(result.to_string()),
+        });
+    }
     if result.as_bool() == Some(false) {
         return Ok(
             ResolveResult::primary_with_references(PrimaryResolveResult::Ignore, refs).cell(),
         );
     }
     if let Some(value) = result.as_str() {
-        return Ok(resolve_internal(
+        return Ok(resolve_internal_traced(
             package_path,
             RequestVc::parse(Value::new(Pattern::Constant(value.to_string()))),
             resolve_options,
+            tracer,
         )
+        .await?
         .add_references(refs));
     }
     let issue: ResolvingIssueVc = ResolvingIssue {
@@ -1112,6 +1386,7 @@ async fn resolved(
         ..
     }: &ResolveOptions,
     options: ResolveOptionsVc,
+    tracer: Option<&ResolveTracer>,
 ) -> Result<ResolveResultVc> {
     let RealPathResult { path, symlinks } = &*fs_path.realpath_with_links().await?;
     for resolve_in in in_package.iter() {
@@ -1120,24 +1395,24 @@ async fn resolved(
                 if let FindContextFileResult::Found(package_json, refs) =
                     &*find_context_file(fs_path.parent(), package_json()).await?
                 {
-                    if let FileJsonContent::Content(package) = &*package_json.read_json().await? {
-                        if let Some(field_value) = package[field].as_object() {
-                            let package_path = package_json.parent();
-                            if let Some(rel_path) =
-                                package_path.await?.get_relative_path_to(&*fs_path.await?)
-                            {
-                                if let Some(value) = field_value.get(&rel_path) {
-                                    return resolve_alias_field_result(
-                                        value,
-                                        refs.clone(),
-                                        package_path,
-                                        options,
-                                        *package_json,
-                                        &rel_path,
-                                        field,
-                                    )
-                                    .await;
-                                }
+                    let field_value = package_json_field(*package_json, field).await?;
+                    if let Some(field_value) = field_value.as_object() {
+                        let package_path = package_json.parent();
+                        if let Some(rel_path) =
+                            package_path.await?.get_relative_path_to(&*fs_path.await?)
+                        {
+                            if let Some(value) = field_value.get(&rel_path) {
+                                return resolve_alias_field_result(
+                                    value,
+                                    refs.clone(),
+                                    package_path,
+                                    options,
+                                    *package_json,
+                                    &rel_path,
+                                    field,
+                                    tracer,
+                                )
+                                .await;
                             }
                         }
                     }
@@ -1147,15 +1422,18 @@ async fn resolved(
     }
 
     if let Some(resolved_map) = resolved_map {
-        let result = resolved_map
-            .lookup(*path, original_context, original_request)
-            .await?;
+        let result_vc = resolved_map.lookup(*path, original_context, original_request);
+        let result = result_vc.await?;
+        if !matches!(&*result, ImportMapResult::NoEntry) {
+            trace_import_map_result(tracer, "resolved map", result_vc).await?;
+        }
         let resolved_result = resolve_import_map_result(
             &result,
             path.parent(),
             original_context,
             original_request,
             options,
+            tracer,
         )
         .await?;
         if let Some(result) = resolved_result {
@@ -1173,7 +1451,8 @@ async fn resolved(
     .into())
 }
 
-fn handle_exports_field(
+#[allow(clippy::too_many_arguments)]
+async fn handle_exports_field(
     package_path: FileSystemPathVc,
     package_json: FileSystemPathVc,
     options: ResolveOptionsVc,
@@ -1181,6 +1460,7 @@ fn handle_exports_field(
     path: &str,
     conditions: &BTreeMap<String, ConditionValue>,
     unspecified_conditions: &ConditionValue,
+    tracer: Option<&ResolveTracer>,
 ) -> Result<ResolveResultVc> {
     let mut results = Vec::new();
     let mut conditions_state = HashMap::new();
@@ -1189,6 +1469,16 @@ fn handle_exports_field(
         .map(AliasMatch::try_into_self)
         .collect::<Result<Vec<Cow<'_, ExportsValue>>>>()?;
     for value in values.iter() {
+        if let Some(tracer) = tracer {
+            let mut tried = Vec::new();
+            trace_conditions(value, conditions, unspecified_conditions, &mut tried);
+            for (condition, value) in tried {
+                tracer.push(ResolveStep::Condition {
+                    condition: condition.to_string(),
+                    value,
+                });
+            }
+        }
         if value.add_results(
             conditions,
             unspecified_conditions,
@@ -1202,11 +1492,18 @@ fn handle_exports_field(
         let mut duplicates_set = HashSet::new();
         results.retain(|item| duplicates_set.insert(*item));
     }
+    if let Some(tracer) = tracer {
+        tracer.push(ResolveStep::Exports {
+            subpath: path.to_string(),
+            targets: results.iter().map(|target| target.to_string()).collect(),
+        });
+    }
     let mut resolved_results = Vec::new();
     for path in results {
         if let Some(path) = normalize_path(path) {
             let request = RequestVc::relative(Value::new(format!("./{}", path).into()), false);
-            resolved_results.push(resolve_internal(package_path, request, options));
+            resolved_results
+                .push(resolve_internal_traced(package_path, request, options, tracer).await?);
         }
     }
     // other options do not apply anymore when an exports field exist
@@ -32,6 +32,16 @@ pub enum ResolveModules {
     /// registry filesystem is assumed to have structure like
     /// @scope/module/version/<path-in-package>
     Registry(FileSystemPathVc, LockedVersionsVc),
+    /// resolve `workspace:` dependencies of the package of the context to the
+    /// packages of the workspace at that directory, and copies of injected
+    /// workspace packages in node_modules to their source, see
+    /// [workspace](super::workspace). A workspace package takes precedence
+    /// over all other locations.
+    Workspace(FileSystemPathVc),
+    /// lookup packages with the Yarn Plug'n'Play manifest of the project at
+    /// that directory, see [pnp](super::pnp). The manifest takes precedence
+    /// over all other locations for packages it knows the importing file of.
+    PnP(FileSystemPathVc),
 }
 
 #[derive(TraceRawVcs, Hash, PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
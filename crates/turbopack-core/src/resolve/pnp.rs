@@ -0,0 +1,474 @@
+//! Resolving packages with the manifest of Yarn Plug'n'Play.
+//!
+//! Projects using Plug'n'Play have no `node_modules`. Instead, Yarn writes
+//! the location of every package and the dependencies it may import to a
+//! manifest, either `.pnp.data.json` or inlined into `.pnp.cjs`. Packages are
+//! stored as zip archives in the cache, which are read through a
+//! [ZipFileSystem](turbo_tasks_fs::zip::ZipFileSystem).
+//!
+//! Packages with peer dependencies have a virtual location for every set of
+//! peer dependencies they're used with. Virtual locations are mapped to the
+//! location of the package itself, so when a file of such a package imports
+//! one of its peer dependencies, the first virtual instance decides which
+//! version it gets.
+
+use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use turbo_tasks::trace::TraceRawVcs;
+use turbo_tasks_fs::{
+    zip::ZipFileSystemVc, FileContent, FileSystem, FileSystemPathVc, FileSystemVc,
+};
+
+use super::AffectingResolvingAssetReferenceVc;
+use crate::reference::AssetReferenceVc;
+
+/// Identifies a package of a [PnpManifest].
+#[derive(Clone, PartialEq, Eq, Hash, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub struct PnpLocator {
+    /// `None` for the top-level package of the project.
+    pub name: Option<String>,
+    pub reference: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub struct PnpPackage {
+    pub locator: PnpLocator,
+    /// The location relative to the project, without virtual directories and
+    /// with a trailing `/` unless it's the project itself, e.g.
+    /// `.yarn/cache/lodash-npm-4.17.21-6382451519-eb835a2e51.zip/node_modules/
+    /// lodash/`.
+    pub location: String,
+    /// The packages it depends on, by the name they're imported with. Peer
+    /// dependencies which aren't provided have no locator.
+    pub dependencies: IndexMap<String, Option<PnpLocator>>,
+}
+
+/// How a dependency of a file is resolved with a [PnpManifest].
+#[derive(Debug, PartialEq, Eq)]
+pub enum PnpResolution<'a> {
+    Package(&'a PnpPackage),
+    /// The dependency is a peer dependency which isn't provided.
+    MissingPeerDependency,
+    /// The package of the file doesn't depend on a package with that name.
+    NotADependency,
+    /// The file isn't part of any package of the manifest.
+    UnknownIssuer,
+}
+
+/// The manifest of Yarn Plug'n'Play, see [pnp_manifest].
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct PnpManifest {
+    pub packages: Vec<PnpPackage>,
+    /// Whether packages may import the dependencies of the top-level package
+    /// and the [fallback_pool](PnpManifest::fallback_pool) that they don't
+    /// declare, like they could with `node_modules` hoisting.
+    pub enable_top_level_fallback: bool,
+    pub fallback_pool: IndexMap<String, Option<PnpLocator>>,
+    /// Packages which don't get the top-level fallback.
+    pub fallback_exclusion_list: Vec<PnpLocator>,
+}
+
+impl PnpManifest {
+    /// Parses the content of a `.pnp.data.json`.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let data: JsonValue = serde_json::from_str(json)?;
+        let mut packages = Vec::new();
+        for entry in json_array(&data["packageRegistryData"])? {
+            let [name, references] = json_pair(entry)?;
+            let name = name.as_str().map(|name| name.to_string());
+            for entry in json_array(references)? {
+                let [reference, info] = json_pair(entry)?;
+                let location = info["packageLocation"]
+                    .as_str()
+                    .context("packageLocation needs to be a string")?;
+                packages.push(PnpPackage {
+                    locator: PnpLocator {
+                        name: name.clone(),
+                        reference: reference.as_str().map(|reference| reference.to_string()),
+                    },
+                    location: normalize_location(&devirtualize(location)),
+                    dependencies: dependencies(&info["packageDependencies"])?,
+                });
+            }
+        }
+        let mut fallback_exclusion_list = Vec::new();
+        if !data["fallbackExclusionList"].is_null() {
+            for entry in json_array(&data["fallbackExclusionList"])? {
+                let [name, references] = json_pair(entry)?;
+                for reference in json_array(references)? {
+                    fallback_exclusion_list.push(PnpLocator {
+                        name: name.as_str().map(|name| name.to_string()),
+                        reference: reference.as_str().map(|reference| reference.to_string()),
+                    });
+                }
+            }
+        }
+        Ok(PnpManifest {
+            packages,
+            enable_top_level_fallback: data["enableTopLevelFallback"].as_bool().unwrap_or(false),
+            fallback_pool: if data["fallbackPool"].is_null() {
+                IndexMap::new()
+            } else {
+                dependencies(&data["fallbackPool"])?
+            },
+            fallback_exclusion_list,
+        })
+    }
+
+    /// Parses a `.pnp.cjs` with the manifest inlined as a JSON string literal.
+    pub fn from_pnp_cjs(code: &str) -> Result<Self> {
+        let start = ["RAW_RUNTIME_STATE", "hydrateRuntimeState(JSON.parse("]
+            .iter()
+            .find_map(|marker| code.find(marker))
+            .context("no inlined manifest found")?;
+        let literal = code[start..]
+            .find(['\'', '"'])
+            .map(|quote| &code[start + quote..])
+            .context("no inlined manifest found")?;
+        Self::from_json(&parse_js_string(literal)?)
+    }
+
+    /// The package which contains the file at the `location` relative to the
+    /// project.
+    pub fn package_of(&self, location: &str) -> Option<&PnpPackage> {
+        self.packages
+            .iter()
+            .filter(|package| location.starts_with(&package.location))
+            .max_by_key(|package| package.location.len())
+    }
+
+    pub fn package(&self, locator: &PnpLocator) -> Option<&PnpPackage> {
+        self.packages
+            .iter()
+            .find(|package| package.locator == *locator)
+    }
+
+    /// Resolves the dependency called `name` of the file at the `location`
+    /// relative to the project.
+    pub fn resolve(&self, location: &str, name: &str) -> PnpResolution<'_> {
+        let Some(issuer) = self.package_of(location) else {
+            return PnpResolution::UnknownIssuer;
+        };
+        let mut dependency = issuer.dependencies.get(name);
+        if dependency.is_none()
+            && self.enable_top_level_fallback
+            && !self.fallback_exclusion_list.contains(&issuer.locator)
+        {
+            let top_level = PnpLocator {
+                name: None,
+                reference: None,
+            };
+            dependency = self
+                .package(&top_level)
+                .and_then(|top_level| top_level.dependencies.get(name))
+                .or_else(|| self.fallback_pool.get(name));
+        }
+        match dependency {
+            Some(Some(locator)) => match self.package(locator) {
+                Some(package) => PnpResolution::Package(package),
+                None => PnpResolution::NotADependency,
+            },
+            Some(None) => PnpResolution::MissingPeerDependency,
+            None => PnpResolution::NotADependency,
+        }
+    }
+}
+
+fn json_array(value: &JsonValue) -> Result<&Vec<JsonValue>> {
+    value.as_array().context("expected an array")
+}
+
+fn json_pair(value: &JsonValue) -> Result<[&JsonValue; 2]> {
+    match json_array(value)?.as_slice() {
+        [first, second] => Ok([first, second]),
+        _ => bail!("expected a pair"),
+    }
+}
+
+/// Parses `packageDependencies`, whose entries are a name and either a
+/// reference, an alias of another name and its reference, or `null` for a
+/// missing peer dependency.
+fn dependencies(value: &JsonValue) -> Result<IndexMap<String, Option<PnpLocator>>> {
+    let mut dependencies = IndexMap::new();
+    for entry in json_array(value)? {
+        let [name, target] = json_pair(entry)?;
+        let name = name
+            .as_str()
+            .context("dependency names need to be strings")?;
+        let locator = match target {
+            JsonValue::Null => None,
+            JsonValue::String(reference) => Some(PnpLocator {
+                name: Some(name.to_string()),
+                reference: Some(reference.clone()),
+            }),
+            target => {
+                let [alias, reference] = json_pair(target)?;
+                Some(PnpLocator {
+                    name: alias.as_str().map(|alias| alias.to_string()),
+                    reference: reference.as_str().map(|reference| reference.to_string()),
+                })
+            }
+        };
+        dependencies.insert(name.to_string(), locator);
+    }
+    Ok(dependencies)
+}
+
+/// Maps a virtual location, `<base>/__virtual__/<hash>/<depth>/<path>`, to
+/// the location of the package itself, which is `<path>` relative to `<base>`
+/// after going `<depth>` directories up.
+fn devirtualize(location: &str) -> String {
+    let segments: Vec<&str> = location.split('/').collect();
+    let Some(index) = segments.iter().position(|segment| *segment == "__virtual__") else {
+        return location.to_string();
+    };
+    let Some(depth) = segments
+        .get(index + 2)
+        .and_then(|depth| depth.parse::<usize>().ok())
+    else {
+        return location.to_string();
+    };
+    let mut base = segments[..index].to_vec();
+    for _ in 0..depth {
+        if matches!(base.last(), None | Some(&".") | Some(&"..")) {
+            base.push("..");
+        } else {
+            base.pop();
+        }
+    }
+    base.extend_from_slice(&segments[index + 3..]);
+    base.join("/")
+}
+
+fn normalize_location(location: &str) -> String {
+    let location = location.trim_start_matches("./").trim_matches('/');
+    if location.is_empty() || location == "." {
+        String::new()
+    } else {
+        format!("{location}/")
+    }
+}
+
+/// Parses the JavaScript string literal at the start of `code`.
+fn parse_js_string(code: &str) -> Result<String> {
+    let mut chars = code.chars();
+    let quote = chars.next().context("expected a string literal")?;
+    let mut string = String::new();
+    while let Some(char) = chars.next() {
+        match char {
+            '\\' => match chars.next() {
+                // A line continuation.
+                Some('\n') => {}
+                Some('n') => string.push('\n'),
+                Some('r') => string.push('\r'),
+                Some('t') => string.push('\t'),
+                Some(char) => string.push(char),
+                None => break,
+            },
+            char if char == quote => return Ok(string),
+            char => string.push(char),
+        }
+    }
+    bail!("unterminated string literal")
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct PnpManifestOption(Option<PnpManifestVc>);
+
+/// Reads the Plug'n'Play manifest of the project at `root`. There's none when
+/// the project doesn't use Plug'n'Play.
+#[turbo_tasks::function]
+pub async fn pnp_manifest(root: FileSystemPathVc) -> Result<PnpManifestOptionVc> {
+    if let FileContent::Content(file) = &*root.join(".pnp.data.json").read().await? {
+        let manifest =
+            PnpManifest::from_json(&file.content().to_str()?).context("parsing .pnp.data.json")?;
+        return Ok(PnpManifestOptionVc::cell(Some(manifest.cell())));
+    }
+    if let FileContent::Content(file) = &*root.join(".pnp.cjs").read().await? {
+        let manifest =
+            PnpManifest::from_pnp_cjs(&file.content().to_str()?).context("parsing .pnp.cjs")?;
+        return Ok(PnpManifestOptionVc::cell(Some(manifest.cell())));
+    }
+    Ok(PnpManifestOptionVc::cell(None))
+}
+
+/// The location of the `path` relative to the project at `root`, the way the
+/// manifest refers to it. Paths in zip archives are located by the path of
+/// their archive.
+async fn pnp_location(path: FileSystemPathVc, root: FileSystemPathVc) -> Result<Option<String>> {
+    let mut path_value = path.await?;
+    let mut inner = None;
+    if let Some(zip) = ZipFileSystemVc::resolve_from(path_value.fs).await? {
+        inner = Some(path_value.path.clone());
+        path_value = zip.await?.archive.await?;
+    }
+    let root_value = root.await?;
+    let location = if root_value.fs == path_value.fs && root_value.path == path_value.path {
+        ""
+    } else {
+        let Some(location) = root_value.get_path_to(&path_value) else {
+            return Ok(None);
+        };
+        location
+    };
+    Ok(Some(normalize_location(&match inner {
+        Some(inner) => format!("{location}/{inner}"),
+        None => location.to_string(),
+    })))
+}
+
+/// The directory of a package at the `location` relative to the project at
+/// `root`. Packages in zip archives are read through a [ZipFileSystemVc].
+async fn pnp_package_path(
+    root: FileSystemPathVc,
+    location: &str,
+) -> Result<Option<FileSystemPathVc>> {
+    let location = location.trim_end_matches('/');
+    let archive_end = location
+        .find(".zip/")
+        .map(|index| index + ".zip".len())
+        .or_else(|| location.ends_with(".zip").then_some(location.len()));
+    let Some(archive_end) = archive_end else {
+        return Ok(*root.try_join(location).await?);
+    };
+    let Some(archive) = *root.try_join(&location[..archive_end]).await? else {
+        return Ok(None);
+    };
+    let fs: FileSystemVc = ZipFileSystemVc::new(archive).into();
+    Ok(Some(
+        fs.root()
+            .join(location[archive_end..].trim_start_matches('/')),
+    ))
+}
+
+/// Finds the directory of the package called `package_name` which the files
+/// in `context` may import according to the Plug'n'Play manifest of the
+/// project at `root`.
+pub(super) async fn find_pnp_package(
+    context: FileSystemPathVc,
+    package_name: &str,
+    root: FileSystemPathVc,
+    references: &mut Vec<AssetReferenceVc>,
+) -> Result<Option<FileSystemPathVc>> {
+    let Some(manifest) = *pnp_manifest(root).await? else {
+        return Ok(None);
+    };
+    let Some(location) = pnp_location(context, root).await? else {
+        return Ok(None);
+    };
+    let manifest = manifest.await?;
+    let PnpResolution::Package(package) = manifest.resolve(&location, package_name) else {
+        return Ok(None);
+    };
+    for manifest in [".pnp.data.json", ".pnp.cjs"] {
+        references.push(AffectingResolvingAssetReferenceVc::new(root.join(manifest)).into());
+    }
+    pnp_package_path(root, &package.location).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{devirtualize, PnpLocator, PnpManifest, PnpResolution};
+
+    const DATA: &str = r#"{
+        "enableTopLevelFallback": true,
+        "fallbackExclusionList": [["strict", ["npm:1.0.0"]]],
+        "packageRegistryData": [
+            [null, [[null, {
+                "packageLocation": "./",
+                "packageDependencies": [["app", "workspace:."], ["lodash", "npm:4.17.21"]]
+            }]]],
+            ["app", [["workspace:.", {
+                "packageLocation": "./",
+                "packageDependencies": [
+                    ["lodash", "npm:4.17.21"],
+                    ["underscore", ["lodash", "npm:4.17.21"]],
+                    ["react", null]
+                ]
+            }]]],
+            ["lodash", [["npm:4.17.21", {
+                "packageLocation": "./.yarn/cache/lodash-npm-4.17.21-6382451519-eb835a2e51.zip/node_modules/lodash/",
+                "packageDependencies": [["lodash", "npm:4.17.21"]]
+            }]]],
+            ["strict", [["npm:1.0.0", {
+                "packageLocation": "./.yarn/__virtual__/strict-virtual-0123456789/0/cache/strict-npm-1.0.0-0123456789-abcdef.zip/node_modules/strict/",
+                "packageDependencies": [["strict", "npm:1.0.0"]]
+            }]]]
+        ]
+    }"#;
+
+    const LODASH: &str =
+        ".yarn/cache/lodash-npm-4.17.21-6382451519-eb835a2e51.zip/node_modules/lodash/";
+
+    fn resolved_location(resolution: PnpResolution) -> Option<&str> {
+        match resolution {
+            PnpResolution::Package(package) => Some(&package.location),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn resolves_dependencies() {
+        let manifest = PnpManifest::from_json(DATA).unwrap();
+        assert_eq!(
+            resolved_location(manifest.resolve("src/", "lodash")),
+            Some(LODASH)
+        );
+        assert_eq!(
+            resolved_location(manifest.resolve("src/", "underscore")),
+            Some(LODASH)
+        );
+        assert_eq!(
+            manifest.resolve("src/", "react"),
+            PnpResolution::MissingPeerDependency
+        );
+        assert_eq!(
+            manifest.resolve("src/", "left-pad"),
+            PnpResolution::NotADependency
+        );
+    }
+
+    #[test]
+    fn falls_back_to_top_level_dependencies() {
+        let manifest = PnpManifest::from_json(DATA).unwrap();
+        // lodash doesn't depend on app, but the top-level package does.
+        assert!(matches!(
+            manifest.resolve(LODASH, "app"),
+            PnpResolution::Package(package) if package.locator == PnpLocator {
+                name: Some("app".to_string()),
+                reference: Some("workspace:.".to_string()),
+            }
+        ));
+        let strict = ".yarn/cache/strict-npm-1.0.0-0123456789-abcdef.zip/node_modules/strict/";
+        assert_eq!(
+            manifest.resolve(strict, "lodash"),
+            PnpResolution::NotADependency
+        );
+    }
+
+    #[test]
+    fn devirtualizes_locations() {
+        assert_eq!(
+            devirtualize("./.yarn/__virtual__/a-virtual-0123456789/0/cache/a.zip/node_modules/a/"),
+            "./.yarn/cache/a.zip/node_modules/a/"
+        );
+        assert_eq!(
+            devirtualize("./.yarn/__virtual__/b-virtual-0123456789/2/packages/b/"),
+            "./../packages/b/"
+        );
+        assert_eq!(devirtualize("./packages/b/"), "./packages/b/");
+    }
+
+    #[test]
+    fn reads_inlined_manifest() {
+        let code = format!(
+            "#!/usr/bin/env node\nconst RAW_RUNTIME_STATE =\n'{}';\n",
+            DATA.replace('\n', "\\\n")
+        );
+        let manifest = PnpManifest::from_pnp_cjs(&code).unwrap();
+        assert_eq!(manifest, PnpManifest::from_json(DATA).unwrap());
+    }
+}
@@ -0,0 +1,140 @@
+//! Module policies forbid importing certain packages or paths from parts of a
+//! project, e.g. the internal files of a package, or server-only packages
+//! from client code. They are enforced by a [ResolvePlugin] that reports an
+//! issue citing the importer of a forbidden request.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{primitives::StringVc, trace::TraceRawVcs};
+use turbo_tasks_fs::{glob::GlobVc, FileSystemPathVc};
+
+use super::{
+    parse::RequestVc,
+    plugin::{ResolvePlugin, ResolvePluginConditionVc, ResolvePluginVc},
+    ResolveResultOptionVc,
+};
+use crate::issue::{Issue, IssueSeverity, IssueSeverityVc, IssueVc};
+
+/// A request that is forbidden for some importers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+pub struct ModulePolicyRule {
+    /// A glob matching the forbidden requests, e.g. `lodash/**` for the
+    /// internal files of `lodash` or `@org/server-only`.
+    pub request: String,
+    /// A glob matching the directories of the importers the request is
+    /// forbidden for, relative to the root of the policy. It's forbidden for
+    /// all importers when `None`.
+    pub importers: Option<String>,
+    /// Explains why the request is forbidden, or what to use instead.
+    pub message: Option<String>,
+}
+
+/// A [ResolvePlugin] which reports requests that are forbidden by its
+/// [ModulePolicyRule]s. The requests are still resolved, so a forbidden
+/// import doesn't cause further errors.
+#[turbo_tasks::value]
+pub struct ModulePolicyResolvePlugin {
+    root: FileSystemPathVc,
+    rules: Vec<ModulePolicyRule>,
+}
+
+impl ModulePolicyResolvePluginVc {
+    pub fn new(root: FileSystemPathVc, rules: Vec<ModulePolicyRule>) -> Self {
+        ModulePolicyResolvePlugin { root, rules }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ResolvePlugin for ModulePolicyResolvePlugin {
+    #[turbo_tasks::function]
+    fn after_resolve_condition(&self) -> ResolvePluginConditionVc {
+        // Forbidden requests might resolve to any file.
+        ResolvePluginConditionVc::new(self.root.root(), GlobVc::new("**"))
+    }
+
+    #[turbo_tasks::function]
+    async fn after_resolve(
+        &self,
+        _fs_path: FileSystemPathVc,
+        context: FileSystemPathVc,
+        request: RequestVc,
+    ) -> Result<ResolveResultOptionVc> {
+        let Some(request_str) = request.await?.request() else {
+            return Ok(ResolveResultOptionVc::none());
+        };
+        let root = self.root.await?;
+        let importer = context.await?;
+        let importer = root.get_path_to(&importer);
+        for rule in &self.rules {
+            if !GlobVc::new(&rule.request).await?.execute(&request_str) {
+                continue;
+            }
+            if let Some(importers) = &rule.importers {
+                let Some(importer) = importer else {
+                    continue;
+                };
+                if !GlobVc::new(importers).await?.execute(importer) {
+                    continue;
+                }
+            }
+            ForbiddenImportIssue {
+                context,
+                request: request_str.clone(),
+                rule: rule.clone(),
+            }
+            .cell()
+            .as_issue()
+            .emit();
+        }
+        Ok(ResolveResultOptionVc::none())
+    }
+}
+
+#[turbo_tasks::value(shared)]
+pub struct ForbiddenImportIssue {
+    pub context: FileSystemPathVc,
+    pub request: String,
+    pub rule: ModulePolicyRule,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for ForbiddenImportIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("resolve".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell(format!("Forbidden import of {}", self.request))
+    }
+
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.context
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> StringVc {
+        let mut description = match &self.rule.importers {
+            Some(importers) => format!(
+                "Importing {} is forbidden in {} by the module policy",
+                self.request, importers
+            ),
+            None => format!(
+                "Importing {} is forbidden by the module policy",
+                self.request
+            ),
+        };
+        if let Some(message) = &self.rule.message {
+            description.push_str(": ");
+            description.push_str(message);
+        }
+        StringVc::cell(description)
+    }
+}
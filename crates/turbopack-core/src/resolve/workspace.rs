@@ -0,0 +1,286 @@
+//! Resolving packages of a pnpm, yarn or npm workspace.
+//!
+//! Dependencies declared with the `workspace:` protocol, e.g. `"ui":
+//! "workspace:*"`, are resolved to the source directory of the workspace
+//! package, independent of how the package manager linked it into
+//! `node_modules`. Edits of the package are picked up like edits of any other
+//! source file of the workspace, without publishing it first.
+//!
+//! pnpm copies [injected] workspace dependencies to
+//! `node_modules/.pnpm/<name>@file+<path>/node_modules/<name>`. Copies like
+//! that are also resolved to the source directory, since they're only updated
+//! when the dependencies are installed again.
+//!
+//! [injected]: https://pnpm.io/package_json#dependenciesmetainjected
+
+use std::{future::Future, pin::Pin};
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use serde_json::Value as JsonValue;
+use turbo_tasks_fs::{
+    glob::GlobVc, DirectoryEntry, FileContent, FileSystemEntryType, FileSystemPathVc,
+    ReadGlobResultVc,
+};
+
+use super::{
+    find_context_file, package_json, package_json_field, type_exists,
+    AffectingResolvingAssetReferenceVc, FindContextFileResult,
+};
+use crate::reference::AssetReferenceVc;
+
+/// The fields of a package.json which declare dependencies.
+const DEPENDENCY_FIELDS: [&str; 4] = [
+    "dependencies",
+    "devDependencies",
+    "peerDependencies",
+    "optionalDependencies",
+];
+
+/// The packages of a workspace by their name.
+#[turbo_tasks::value(transparent)]
+pub struct WorkspacePackages(IndexMap<String, FileSystemPathVc>);
+
+/// What a `workspace:` dependency refers to.
+#[derive(Debug, PartialEq, Eq)]
+enum WorkspaceDependency<'a> {
+    /// The workspace package with the name, which is the name of the
+    /// dependency unless it's aliased, e.g. with `workspace:ui@*`.
+    Package(&'a str),
+    /// The package in the directory with the path relative to the dependent
+    /// package, e.g. with `workspace:../ui`.
+    Path(&'a str),
+}
+
+/// Parses the version of a dependency called `name`. Returns `None` for
+/// versions without the `workspace:` protocol.
+fn parse_workspace_dependency<'a>(
+    name: &'a str,
+    version: &'a str,
+) -> Option<WorkspaceDependency<'a>> {
+    let spec = version.strip_prefix("workspace:")?;
+    if spec.starts_with('.') || spec.starts_with('/') {
+        return Some(WorkspaceDependency::Path(spec));
+    }
+    // The first character is skipped, since it's the `@` of a scoped alias.
+    let alias = spec
+        .char_indices()
+        .skip(1)
+        .find(|&(_, c)| c == '@')
+        .map(|(index, _)| &spec[..index]);
+    Some(WorkspaceDependency::Package(alias.unwrap_or(name)))
+}
+
+/// Reads the `packages` globs of a pnpm-workspace.yaml. Only the block list
+/// of the `packages` key is read, since that's all pnpm supports there.
+fn parse_pnpm_workspace_globs(yaml: &str) -> Vec<String> {
+    let mut globs = Vec::new();
+    let mut in_packages = false;
+    for line in yaml.lines() {
+        let line = match line.find(" #") {
+            Some(index) => &line[..index],
+            None => line,
+        };
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        if !line.starts_with(' ') && !line.starts_with('-') {
+            in_packages = line.trim_end() == "packages:";
+            continue;
+        }
+        if !in_packages {
+            continue;
+        }
+        if let Some(item) = line.trim_start().strip_prefix('-') {
+            let item = item.trim();
+            let item = item
+                .strip_prefix('\'')
+                .and_then(|item| item.strip_suffix('\''))
+                .or_else(|| {
+                    item.strip_prefix('"')
+                        .and_then(|item| item.strip_suffix('"'))
+                })
+                .unwrap_or(item);
+            globs.push(item.to_string());
+        }
+    }
+    globs
+}
+
+/// Reads the globs of the workspace packages at `root`, from a
+/// pnpm-workspace.yaml or the `workspaces` field of the package.json.
+async fn workspace_globs(root: FileSystemPathVc) -> Result<Vec<String>> {
+    if let FileContent::Content(file) = &*root.join("pnpm-workspace.yaml").read().await? {
+        return Ok(parse_pnpm_workspace_globs(&file.content().to_str()?));
+    }
+    let workspaces = package_json_field(root.join("package.json"), "workspaces").await?;
+    let globs = match &*workspaces {
+        JsonValue::Object(workspaces) => workspaces.get("packages"),
+        workspaces => Some(workspaces),
+    };
+    Ok(globs
+        .and_then(|globs| globs.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|glob| glob.as_str())
+        .map(|glob| glob.to_string())
+        .collect())
+}
+
+/// Collects the directories of a [ReadGlobResultVc] and its nested results.
+fn collect_directories<'a>(
+    result: ReadGlobResultVc,
+    directories: &'a mut Vec<(String, FileSystemPathVc)>,
+) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
+    Box::pin(async move {
+        let result = result.await?;
+        for (path, entry) in result.results.iter() {
+            if let DirectoryEntry::Directory(directory) = entry {
+                directories.push((path.clone(), *directory));
+            }
+        }
+        for inner in result.inner.values() {
+            collect_directories(*inner, directories).await?;
+        }
+        Ok(())
+    })
+}
+
+/// Finds the packages of the workspace at `root`. Globs starting with `!`
+/// exclude directories, as do `node_modules` directories.
+#[turbo_tasks::function]
+pub async fn workspace_packages(root: FileSystemPathVc) -> Result<WorkspacePackagesVc> {
+    let (excludes, includes): (Vec<_>, Vec<_>) = workspace_globs(root)
+        .await?
+        .into_iter()
+        .partition(|glob| glob.starts_with('!'));
+    let excludes = excludes
+        .iter()
+        .map(|glob| GlobVc::new(&glob[1..]))
+        .collect::<Vec<_>>();
+    let mut directories = Vec::new();
+    for glob in includes {
+        let glob = glob.trim_start_matches("./").trim_end_matches('/');
+        collect_directories(root.read_glob(GlobVc::new(glob), false), &mut directories).await?;
+    }
+    // The order of glob results is random.
+    directories.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut packages = IndexMap::new();
+    'directories: for (path, directory) in directories {
+        if path.split('/').any(|segment| segment == "node_modules") {
+            continue;
+        }
+        for &exclude in excludes.iter() {
+            if exclude.await?.execute(&path) {
+                continue 'directories;
+            }
+        }
+        let name = package_json_field(directory.join("package.json"), "name").await?;
+        if let Some(name) = name.as_str() {
+            packages.entry(name.to_string()).or_insert(directory);
+        }
+    }
+    Ok(WorkspacePackagesVc::cell(packages))
+}
+
+/// Finds the workspace package which the package of the `context` declares
+/// as a `workspace:` dependency called `package_name`.
+pub(super) async fn find_workspace_dependency(
+    context: FileSystemPathVc,
+    package_name: &str,
+    root: FileSystemPathVc,
+    references: &mut Vec<AssetReferenceVc>,
+) -> Result<Option<FileSystemPathVc>> {
+    let FindContextFileResult::Found(package_json_path, refs) =
+        &*find_context_file(context, package_json()).await?
+    else {
+        return Ok(None);
+    };
+    let package_json_path = *package_json_path;
+    for field in DEPENDENCY_FIELDS {
+        let dependencies = package_json_field(package_json_path, field).await?;
+        let Some(version) = dependencies.get(package_name).and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let package_path = match parse_workspace_dependency(package_name, version) {
+            Some(WorkspaceDependency::Package(name)) => {
+                workspace_packages(root).await?.get(name).copied()
+            }
+            Some(WorkspaceDependency::Path(path)) => {
+                *package_json_path.parent().try_join(path).await?
+            }
+            None => None,
+        };
+        let Some(package_path) = package_path else {
+            return Ok(None);
+        };
+        references.extend(refs.iter().copied());
+        references.push(AffectingResolvingAssetReferenceVc::new(package_json_path).into());
+        return type_exists(package_path, FileSystemEntryType::Directory, references).await;
+    }
+    Ok(None)
+}
+
+/// Maps a `package_path` in `node_modules` to the source directory of the
+/// workspace package at `root` when it's a copy of an injected dependency.
+pub(super) async fn injected_workspace_package(
+    package_path: FileSystemPathVc,
+    package_name: &str,
+    root: FileSystemPathVc,
+) -> Result<Option<FileSystemPathVc>> {
+    let prefix = format!("{}@file+", package_name.replace('/', "+"));
+    let package_path_value = package_path.await?;
+    let is_injected = package_path_value
+        .path
+        .split("node_modules/.pnpm/")
+        .skip(1)
+        .any(|rest| rest.starts_with(&prefix));
+    if !is_injected {
+        return Ok(None);
+    }
+    Ok(workspace_packages(root).await?.get(package_name).copied())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_pnpm_workspace_globs, parse_workspace_dependency, WorkspaceDependency};
+
+    #[test]
+    fn workspace_dependencies() {
+        assert_eq!(
+            parse_workspace_dependency("ui", "workspace:*"),
+            Some(WorkspaceDependency::Package("ui"))
+        );
+        assert_eq!(
+            parse_workspace_dependency("ui", "workspace:^1.0.0"),
+            Some(WorkspaceDependency::Package("ui"))
+        );
+        assert_eq!(
+            parse_workspace_dependency("ui", "workspace:@acme/ui@*"),
+            Some(WorkspaceDependency::Package("@acme/ui"))
+        );
+        assert_eq!(
+            parse_workspace_dependency("@acme/ui", "workspace:../ui"),
+            Some(WorkspaceDependency::Path("../ui"))
+        );
+        assert_eq!(parse_workspace_dependency("ui", "^1.0.0"), None);
+    }
+
+    #[test]
+    fn pnpm_workspace_globs() {
+        let yaml = r#"
+# The packages of the monorepo.
+packages:
+  - 'packages/*'
+  - "apps/**" # apps can be nested
+  - '!**/test/**'
+catalog:
+  - react
+"#;
+        assert_eq!(
+            parse_pnpm_workspace_globs(yaml),
+            ["packages/*", "apps/**", "!**/test/**"]
+        );
+    }
+}
@@ -3,7 +3,7 @@ use std::{io::Write, ops::Deref, sync::Arc};
 use anyhow::Result;
 use indexmap::IndexMap;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use sourcemap::{SourceMap as CrateMap, SourceMapBuilder};
+use sourcemap::{DecodedMap, SourceMap as CrateMap, SourceMapBuilder};
 use turbo_tasks::TryJoinIterExt;
 use turbo_tasks_fs::rope::{Rope, RopeBuilder, RopeVc};
 
@@ -107,6 +107,18 @@ impl SourceMap {
     pub fn new_sectioned(sections: Vec<SourceMapSection>) -> Self {
         SourceMap::Sectioned(SectionedSourceMap::new(sections))
     }
+
+    /// Parses a source map from its JSON form, like the output of
+    /// [SourceMapVc::to_rope]. Sectioned maps are flattened into a regular
+    /// map.
+    pub fn from_json(json: &[u8]) -> Result<Self> {
+        let map = match sourcemap::decode_slice(json)? {
+            DecodedMap::Regular(map) => map,
+            DecodedMap::Index(index) => index.flatten()?,
+            DecodedMap::Hermes(map) => map.into_inner(),
+        };
+        Ok(SourceMap::new_regular(map))
+    }
 }
 
 #[turbo_tasks::value_impl]
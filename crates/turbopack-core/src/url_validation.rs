@@ -0,0 +1,222 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use turbo_tasks::{primitives::StringVc, CompletionVc, TryJoinIterExt, ValueToString};
+use turbo_tasks_fs::{FileContent, FileSystemPathVc};
+
+use crate::{
+    asset::{Asset, AssetContent, AssetVc},
+    issue::{Issue, IssueSeverity, IssueSeverityVc, IssueVc},
+    reference::all_assets,
+};
+
+/// Validates that every URL referenced from the emitted HTML and CSS files
+/// within `output_dir` points to an asset that is emitted as well.
+///
+/// Only URLs that refer to local files are checked, i.e. absolute URLs resolve
+/// against `output_dir` and relative URLs against the referencing file. Paths
+/// are compared exactly, so a URL that only differs in casing is reported,
+/// even if it would work on a case-insensitive file system.
+///
+/// A [BrokenUrlIssue] is emitted for every URL that doesn't resolve.
+#[turbo_tasks::function]
+pub async fn validate_emitted_urls(
+    asset: AssetVc,
+    output_dir: FileSystemPathVc,
+) -> Result<CompletionVc> {
+    let output_dir_value = output_dir.await?;
+    let paths = all_assets(asset)
+        .await?
+        .iter()
+        .map(|asset| async move { Ok((*asset, asset.ident().path().await?)) })
+        .try_join()
+        .await?;
+    let emitted: Vec<_> = paths
+        .into_iter()
+        .filter(|(_, path)| path.is_inside(&output_dir_value))
+        .collect();
+    let emitted_paths: HashSet<_> = emitted.iter().map(|(_, path)| path.path.clone()).collect();
+
+    for (asset, path) in &emitted {
+        let urls = match path.extension() {
+            Some("html") => extract_html_urls,
+            Some("css") => extract_css_urls,
+            _ => continue,
+        };
+        let AssetContent::File(content) = &*asset.content().await? else {
+            continue;
+        };
+        let FileContent::Content(file) = &*content.await? else {
+            continue;
+        };
+        let code = file.content().to_str()?;
+        let path_vc = asset.ident().path();
+        for url in urls(&code) {
+            let Some(file_path) = url_path(url) else {
+                continue;
+            };
+            let target = if let Some(absolute) = file_path.strip_prefix('/') {
+                output_dir.try_join(absolute)
+            } else {
+                path_vc.parent().try_join(file_path)
+            };
+            let found = match &*target.await? {
+                Some(target) => emitted_paths.contains(&target.await?.path),
+                None => false,
+            };
+            if !found {
+                BrokenUrlIssue {
+                    context: path_vc,
+                    url: url.to_string(),
+                }
+                .cell()
+                .as_issue()
+                .emit();
+            }
+        }
+    }
+
+    Ok(CompletionVc::new())
+}
+
+/// Returns the file path part of a URL that refers to a local file, without
+/// query and fragment.
+fn url_path(url: &str) -> Option<&str> {
+    let url = url.trim();
+    if url.is_empty()
+        || url.starts_with('#')
+        || url.starts_with("//")
+        || url.contains("://")
+        || url.starts_with("data:")
+        || url.starts_with("blob:")
+        || url.starts_with("mailto:")
+        || url.starts_with("javascript:")
+    {
+        return None;
+    }
+    let end = url.find(['?', '#']).unwrap_or(url.len());
+    let path = &url[..end];
+    (!path.is_empty()).then_some(path)
+}
+
+/// Extracts the values of `src` and `href` attributes from `html`.
+fn extract_html_urls(html: &str) -> Vec<&str> {
+    let mut urls = Vec::new();
+    let lower = html.to_ascii_lowercase();
+    for attribute in ["src", "href"] {
+        let mut pos = 0;
+        while let Some(offset) = lower[pos..].find(attribute) {
+            let start = pos + offset;
+            pos = start + attribute.len();
+            // The attribute name must be preceded by whitespace inside a tag.
+            if !html[..start].ends_with(|c: char| c.is_ascii_whitespace()) {
+                continue;
+            }
+            let rest = html[pos..].trim_start();
+            let Some(rest) = rest.strip_prefix('=') else {
+                continue;
+            };
+            let rest = rest.trim_start();
+            let value = match rest.chars().next() {
+                Some(quote @ ('"' | '\'')) => rest[1..].split(quote).next(),
+                Some(_) => rest
+                    .split(|c: char| c.is_ascii_whitespace() || c == '>')
+                    .next(),
+                None => None,
+            };
+            if let Some(value) = value {
+                urls.push(value);
+            }
+        }
+    }
+    urls
+}
+
+/// Extracts the arguments of `url()` functions from `css`.
+fn extract_css_urls(css: &str) -> Vec<&str> {
+    let mut urls = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = css[pos..].find("url(") {
+        let start = pos + offset + "url(".len();
+        let Some(end) = css[start..].find(')') else {
+            break;
+        };
+        pos = start + end + 1;
+        let value = css[start..start + end].trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+            .unwrap_or(value);
+        urls.push(value);
+    }
+    urls
+}
+
+#[turbo_tasks::value(shared)]
+pub struct BrokenUrlIssue {
+    pub context: FileSystemPathVc,
+    pub url: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for BrokenUrlIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("emit".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell(format!("Broken URL {}", self.url))
+    }
+
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.context
+    }
+
+    #[turbo_tasks::function]
+    async fn description(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(format!(
+            "The URL {} referenced from {} does not resolve to an emitted asset. Check the URL \
+             for typos and the casing of the file name.",
+            self.url,
+            self.context.to_string().await?
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_css_urls, extract_html_urls, url_path};
+
+    #[test]
+    fn html_urls() {
+        let html = r#"<link rel="stylesheet" href="/main.css"><img src='logo.PNG' alt="src"><a href=about.html>About</a>"#;
+        assert_eq!(
+            extract_html_urls(html),
+            vec!["logo.PNG", "/main.css", "about.html"]
+        );
+    }
+
+    #[test]
+    fn css_urls() {
+        let css = r#"a { background: url("./a.png"); } b { background: url( b.svg#icon ) }"#;
+        assert_eq!(extract_css_urls(css), vec!["./a.png", "b.svg#icon"]);
+    }
+
+    #[test]
+    fn local_paths() {
+        assert_eq!(url_path("b.svg#icon"), Some("b.svg"));
+        assert_eq!(url_path("/main.css?v=1"), Some("/main.css"));
+        assert_eq!(url_path("https://example.com/a.png"), None);
+        assert_eq!(url_path("data:image/png;base64,AAAA"), None);
+        assert_eq!(url_path("#section"), None);
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use turbo_tasks::{
     debug::ValueDebugFormat,
     primitives::{JsonValueVc, StringVc},
@@ -49,6 +50,22 @@ pub trait VersionedContent {
             Update::Total(TotalUpdate { to }).into()
         })
     }
+
+    /// Restores a version of this content from a state previously returned by
+    /// [`Version::state`], possibly in a different process. Updates computed
+    /// from the restored version bring a client that is still at that version
+    /// up to date without reloading it.
+    ///
+    /// Returns a [`NotFoundVersion`] when the state can't be restored, which
+    /// results in a total update.
+    async fn restore_version(&self, state: JsonValueVc) -> Result<VersionVc> {
+        // By default, only the id of the version is known, which is enough for
+        // the default `update` implementation.
+        Ok(match state.await?.get("id").and_then(|id| id.as_str()) {
+            Some(id) => RestoredVersionVc::new(id.to_string()).into(),
+            None => NotFoundVersionVc::new().into(),
+        })
+    }
 }
 
 /// A versioned file content.
@@ -123,8 +140,19 @@ impl From<AssetContentVc> for VersionedContentVc {
 pub trait Version {
     /// Get a unique identifier of the version as a string. There is no way
     /// to convert an id back to its original `Version`, so the original object
-    /// needs to be stored somewhere.
+    /// needs to be stored somewhere, or be restored from its
+    /// [`Version::state`].
     fn id(&self) -> StringVc;
+
+    /// Get a serializable state of the version, which is handed to clients so
+    /// that [`VersionedContent::restore_version`] can restore the version when
+    /// they reconnect, e.g. after a restart of the server.
+    ///
+    /// By default, the state only contains the id of the version.
+    async fn state(self_vc: VersionVc) -> Result<JsonValueVc> {
+        let id = self_vc.id().await?;
+        Ok(JsonValueVc::cell(json!({ "id": &*id })))
+    }
 }
 
 /// This trait allows multiple `VersionedContent` to declare which
@@ -166,6 +194,29 @@ impl Version for NotFoundVersion {
     }
 }
 
+/// A version which was restored from the id of a [`Version`] through the
+/// default implementation of [`VersionedContent::restore_version`].
+#[turbo_tasks::value]
+pub struct RestoredVersion {
+    id: String,
+}
+
+#[turbo_tasks::value_impl]
+impl RestoredVersionVc {
+    #[turbo_tasks::function]
+    pub fn new(id: String) -> Self {
+        RestoredVersion { id }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Version for RestoredVersion {
+    #[turbo_tasks::function]
+    fn id(&self) -> StringVc {
+        StringVc::cell(self.id.clone())
+    }
+}
+
 /// Describes an update to a versioned object.
 #[turbo_tasks::value(shared)]
 #[derive(Debug)]
@@ -6,7 +6,7 @@ use std::fmt::Write;
 
 use anyhow::{anyhow, Result};
 use indexmap::IndexSet;
-use turbo_tasks::{primitives::StringVc, Value, ValueToString};
+use turbo_tasks::{primitives::StringVc, TryJoinIterExt, Value, ValueToString};
 use turbo_tasks_fs::{rope::Rope, File, FileSystemPathOptionVc};
 use turbopack_core::{
     asset::{Asset, AssetContentVc, AssetVc},
@@ -15,13 +15,14 @@ use turbopack_core::{
         chunk_content, chunk_content_split,
         optimize::{ChunkOptimizerVc, OptimizableChunk, OptimizableChunkVc},
         Chunk, ChunkContentResult, ChunkGroupReferenceVc, ChunkGroupVc, ChunkItem, ChunkItemVc,
-        ChunkReferenceVc, ChunkVc, ChunkableAssetVc, ChunkingContext, ChunkingContextVc,
-        FromChunkableAsset, ModuleId, ModuleIdVc,
+        ChunkPrefetch, ChunkReferenceVc, ChunkVc, ChunkableAssetVc, ChunkingContext,
+        ChunkingContextVc, FromChunkableAsset, ModuleIdVc,
     },
     code_builder::{CodeBuilder, CodeVc},
     ident::{AssetIdent, AssetIdentVc},
     introspect::{
         asset::{children_from_asset_references, content_to_details, IntrospectableAssetVc},
+        chunk::{ChunkItemInfo, ChunkItemInfosVc, IntrospectableChunk, IntrospectableChunkVc},
         Introspectable, IntrospectableChildrenVc, IntrospectableVc,
     },
     reference::{AssetReference, AssetReferenceVc, AssetReferencesVc},
@@ -387,12 +388,13 @@ impl CssChunkContextVc {
 
     #[turbo_tasks::function]
     pub async fn chunk_item_id(self, chunk_item: CssChunkItemVc) -> Result<ModuleIdVc> {
-        let layer = self.await?.context.layer();
+        let context = self.await?.context;
+        let layer = context.layer();
         let mut ident = chunk_item.asset_ident();
         if !layer.await?.is_empty() {
             ident = ident.with_modifier(layer)
         }
-        Ok(ModuleId::String(ident.to_string().await?.clone_value()).cell())
+        Ok(context.module_id(ident))
     }
 }
 
@@ -441,11 +443,37 @@ impl FromChunkableAsset for CssChunkItemVc {
         _context: ChunkingContextVc,
         _asset: ChunkableAssetVc,
         _availability_info: Value<AvailabilityInfo>,
+        _prefetch: Option<ChunkPrefetch>,
     ) -> Result<Option<Self>> {
         Ok(None)
     }
 }
 
+#[turbo_tasks::value_impl]
+impl IntrospectableChunk for CssChunk {
+    #[turbo_tasks::function]
+    async fn chunk_item_infos(&self) -> Result<ChunkItemInfosVc> {
+        let chunk_content = css_chunk_content(
+            self.context,
+            self.main_entries,
+            Value::new(self.availability_info),
+        )
+        .await?;
+        let infos = chunk_content
+            .chunk_items
+            .iter()
+            .map(|chunk_item| async move {
+                Ok(ChunkItemInfo {
+                    ident: chunk_item.asset_ident(),
+                    size: chunk_item.content().await?.inner_code.len(),
+                })
+            })
+            .try_join()
+            .await?;
+        Ok(ChunkItemInfosVc::cell(infos))
+    }
+}
+
 #[turbo_tasks::function]
 fn introspectable_type() -> StringVc {
     StringVc::cell("css chunk".to_string())
@@ -1,3 +1,4 @@
+use turbo_tasks::primitives::OptionStringVc;
 use turbopack_core::{
     asset::{Asset, AssetVc},
     chunk::{ChunkableAsset, ChunkableAssetVc, ChunkingContextVc},
@@ -17,4 +18,9 @@ pub trait CssEmbed {
     /// references.
     fn references(&self) -> AssetReferencesVc;
     fn embeddable_asset(&self) -> AssetVc;
+    /// A data URI the asset is inlined as, instead of referring to the
+    /// `embeddable_asset`.
+    fn data_uri(&self) -> OptionStringVc {
+        OptionStringVc::cell(None)
+    }
 }
@@ -13,8 +13,8 @@ use turbopack_core::{
     asset::{Asset, AssetContentVc, AssetVc},
     chunk::{
         availability_info::AvailabilityInfo, ChunkItem, ChunkItemVc, ChunkVc, ChunkableAsset,
-        ChunkableAssetReference, ChunkableAssetReferenceVc, ChunkableAssetVc, ChunkingContextVc,
-        ChunkingType, ChunkingTypeOptionVc,
+        ChunkableAssetReference, ChunkableAssetReferenceVc, ChunkableAssetVc, ChunkingContext,
+        ChunkingContextVc, ChunkingType, ChunkingTypeOptionVc,
     },
     context::AssetContextVc,
     ident::AssetIdentVc,
@@ -29,8 +29,9 @@ use turbopack_core::{
 use turbopack_ecmascript::{
     chunk::{
         EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkItemContentVc,
-        EcmascriptChunkItemVc, EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc,
-        EcmascriptChunkVc, EcmascriptChunkingContextVc, EcmascriptExports, EcmascriptExportsVc,
+        EcmascriptChunkItemOptions, EcmascriptChunkItemVc, EcmascriptChunkPlaceable,
+        EcmascriptChunkPlaceableVc, EcmascriptChunkVc, EcmascriptChunkingContextVc,
+        EcmascriptExports, EcmascriptExportsVc,
     },
     utils::StringifyJs,
     ParseResultSourceMap, ParseResultSourceMapVc,
@@ -242,6 +243,26 @@ impl ResolveOrigin for ModuleCssModuleAsset {
     }
 }
 
+/// Applies an update of a CSS module to the class map of the previous module
+/// instance, which the importers already hold, and accepts it. As only the
+/// class map changes, the importers don't need to be re-evaluated, so e.g.
+/// React components aren't re-mounted. Changes of the rules are applied by
+/// the update of the CSS chunk.
+const HOT_CLASSES_UPDATE: &str = r#"const prevClasses = module.hot.data.classes;
+if (prevClasses) {
+  for (const name of Object.keys(prevClasses)) {
+    if (!(name in classes)) delete prevClasses[name];
+  }
+  Object.defineProperties(prevClasses, Object.getOwnPropertyDescriptors(classes));
+  classes = prevClasses;
+}
+module.hot.dispose((data) => {
+  data.classes = classes;
+});
+module.hot.accept();
+__turbopack_export_value__(classes);
+"#;
+
 #[turbo_tasks::value]
 struct ModuleChunkItem {
     module: ModuleCssModuleAssetVc,
@@ -284,10 +305,16 @@ impl EcmascriptChunkItem for ModuleChunkItem {
     #[turbo_tasks::function]
     async fn content(&self) -> Result<EcmascriptChunkItemContentVc> {
         let classes = self.module.classes().await?;
+        let hot = *self.context.is_hot_module_replacement_enabled().await?;
 
-        let mut code = "__turbopack_export_value__({\n".to_string();
+        let mut code = if hot {
+            "let classes = {\n".to_string()
+        } else {
+            "__turbopack_export_value__({\n".to_string()
+        };
         for (export_name, class_names) in &*classes {
             let mut exported_class_names = Vec::with_capacity(class_names.len());
+            let mut composes_import = false;
 
             for class_name in class_names {
                 match class_name {
@@ -338,6 +365,7 @@ impl EcmascriptChunkItem for ModuleChunkItem {
                         exported_class_names.push(format! {
                             "__turbopack_import__({module_id})[{original_name}]"
                         });
+                        composes_import = true;
                     }
                     ModuleCssClass::Local { name: class_name }
                     | ModuleCssClass::Global { name: class_name } => {
@@ -346,14 +374,30 @@ impl EcmascriptChunkItem for ModuleChunkItem {
                 }
             }
 
-            writeln!(
-                code,
-                "  {}: {},",
-                StringifyJs(export_name),
-                exported_class_names.join(" + \" \" + ")
-            )?;
+            if hot && composes_import {
+                // The imported class names are looked up on access, so they stay up to date
+                // when the imported module is patched by an update.
+                writeln!(
+                    code,
+                    "  get {}() {{ return {}; }},",
+                    StringifyJs(export_name),
+                    exported_class_names.join(" + \" \" + ")
+                )?;
+            } else {
+                writeln!(
+                    code,
+                    "  {}: {},",
+                    StringifyJs(export_name),
+                    exported_class_names.join(" + \" \" + ")
+                )?;
+            }
+        }
+        if hot {
+            code += "};\n";
+            code += HOT_CLASSES_UPDATE;
+        } else {
+            code += "});\n";
         }
-        code += "});\n";
         Ok(EcmascriptChunkItemContent {
             inner_code: code.clone().into(),
             // We generate a minimal map for runtime code so that the filename is
@@ -362,6 +406,10 @@ impl EcmascriptChunkItem for ModuleChunkItem {
                 self.module.ident().to_string().await?.to_string(),
                 code,
             )),
+            options: EcmascriptChunkItemOptions {
+                module: hot,
+                ..Default::default()
+            },
             ..Default::default()
         }
         .cell())
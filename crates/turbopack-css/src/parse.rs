@@ -22,7 +22,7 @@ use turbopack_core::{
     source_map::{GenerateSourceMap, GenerateSourceMapVc, OptionSourceMapVc},
     SOURCE_MAP_ROOT_NAME,
 };
-use turbopack_swc_utils::emitter::IssueEmitter;
+use turbopack_swc_utils::{emitter::IssueEmitter, source_file::new_shared_source_file};
 
 use crate::{
     transform::{CssInputTransform, CssInputTransformsVc, TransformContext},
@@ -130,19 +130,11 @@ pub async fn parse(
         AssetContent::Redirect { .. } => ParseResult::Unparseable.cell(),
         AssetContent::File(file) => match &*file.await? {
             FileContent::NotFound => ParseResult::NotFound.cell(),
-            FileContent::Content(file) => match file.content().to_str() {
+            FileContent::Content(file) => match file.content().to_shared_str() {
                 Err(_err) => ParseResult::Unparseable.cell(),
                 Ok(string) => {
                     let transforms = &*transforms.await?;
-                    parse_content(
-                        string.into_owned(),
-                        fs_path,
-                        ident_str,
-                        source,
-                        ty,
-                        transforms,
-                    )
-                    .await?
+                    parse_content(string, fs_path, ident_str, source, ty, transforms).await?
                 }
             },
         },
@@ -150,7 +142,7 @@ pub async fn parse(
 }
 
 async fn parse_content(
-    string: String,
+    string: Arc<String>,
     fs_path: &FileSystemPath,
     ident_str: &str,
     source: AssetVc,
@@ -168,7 +160,7 @@ async fn parse_content(
         },
     );
 
-    let fm = source_map.new_source_file(FileName::Custom(ident_str.to_string()), string);
+    let fm = new_shared_source_file(&source_map, FileName::Custom(ident_str.to_string()), string);
 
     let config = ParserConfig {
         css_modules: matches!(ty, CssModuleAssetType::Module),
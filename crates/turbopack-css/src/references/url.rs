@@ -28,6 +28,7 @@ use crate::{
 #[turbo_tasks::value(into = "new")]
 pub enum ReferencedAsset {
     Some(AssetVc),
+    DataUri(String),
     None,
 }
 
@@ -55,10 +56,11 @@ impl UrlAssetReferenceVc {
         for result in self.resolve_reference().await?.primary.iter() {
             if let PrimaryResolveResult::Asset(asset) = result {
                 if let Some(embeddable) = CssEmbeddableVc::resolve_from(asset).await? {
-                    return Ok(ReferencedAsset::Some(
-                        embeddable.as_css_embed(context).embeddable_asset(),
-                    )
-                    .into());
+                    let embed = embeddable.as_css_embed(context);
+                    if let Some(data_uri) = &*embed.data_uri().await? {
+                        return Ok(ReferencedAsset::DataUri(data_uri.clone()).into());
+                    }
+                    return Ok(ReferencedAsset::Some(embed.embeddable_asset()).into());
                 }
             }
         }
@@ -105,19 +107,27 @@ impl CodeGenerateable for UrlAssetReference {
 
         let mut visitors = Vec::new();
 
-        if let ReferencedAsset::Some(asset) = &*self_vc.get_referenced_asset(context).await? {
-            // TODO(WEB-662) This is not the correct way to get the path of the asset.
-            // `asset` is on module-level, but we need the output-level asset instead.
-            let path = asset.ident().path().await?;
-            let relative_path = context_path
-                .get_relative_path_to(&path)
-                .unwrap_or_else(|| format!("/{}", path.path));
+        let url = match &*self_vc.get_referenced_asset(context).await? {
+            ReferencedAsset::Some(asset) => {
+                // TODO(WEB-662) This is not the correct way to get the path of the asset.
+                // `asset` is on module-level, but we need the output-level asset instead.
+                let path = asset.ident().path().await?;
+                Some(
+                    context_path
+                        .get_relative_path_to(&path)
+                        .unwrap_or_else(|| format!("/{}", path.path)),
+                )
+            }
+            ReferencedAsset::DataUri(data_uri) => Some(data_uri.clone()),
+            ReferencedAsset::None => None,
+        };
 
+        if let Some(url) = url {
             visitors.push(
                 create_visitor!((&this.path.await?), visit_mut_url(u: &mut Url) {
                     u.value = Some(box UrlValue::Str(Str {
                         span: DUMMY_SP,
-                        value: relative_path.as_str().into(),
+                        value: url.as_str().into(),
                         raw: None,
                     }))
                 }),
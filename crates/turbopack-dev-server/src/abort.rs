@@ -0,0 +1,77 @@
+//! Aborting the work of requests whose client disconnected.
+//!
+//! Content sources are computed in turbo tasks, which keep running when the
+//! client of a request disconnects and the dev server stops waiting for the
+//! response. Sources which do expensive work per request, like rendering, can
+//! watch the [abort_signal] of the request to stop early.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use once_cell::sync::Lazy;
+use tokio_util::sync::CancellationToken;
+
+static REQUESTS: Lazy<Mutex<HashMap<String, CancellationToken>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The signal which is cancelled when the client of the request with the
+/// `request_id` disconnects before the response is ready. There's none when
+/// the request isn't in flight anymore.
+pub fn abort_signal(request_id: &str) -> Option<CancellationToken> {
+    REQUESTS.lock().unwrap().get(request_id).cloned()
+}
+
+/// Cancels the [abort_signal] of a request when it's dropped before it's
+/// [disarm](AbortOnDrop::disarm)ed, e.g. because hyper dropped the handler of
+/// the request after the client disconnected.
+pub(crate) struct AbortOnDrop {
+    request_id: String,
+    armed: bool,
+}
+
+impl AbortOnDrop {
+    pub(crate) fn new(request_id: String) -> Self {
+        REQUESTS
+            .lock()
+            .unwrap()
+            .insert(request_id.clone(), CancellationToken::new());
+        Self {
+            request_id,
+            armed: true,
+        }
+    }
+
+    /// Unregisters the request without cancelling its signal, once the
+    /// response is ready.
+    pub(crate) fn disarm(mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        let token = REQUESTS.lock().unwrap().remove(&self.request_id);
+        if let Some(token) = token.filter(|_| self.armed) {
+            token.cancel();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{abort_signal, AbortOnDrop};
+
+    #[test]
+    fn cancels_unless_disarmed() {
+        let guard = AbortOnDrop::new("aborted".to_string());
+        let signal = abort_signal("aborted").unwrap();
+        drop(guard);
+        assert!(signal.is_cancelled());
+        assert!(abort_signal("aborted").is_none());
+
+        let guard = AbortOnDrop::new("completed".to_string());
+        let signal = abort_signal("completed").unwrap();
+        guard.disarm();
+        assert!(!signal.is_cancelled());
+        assert!(abort_signal("completed").is_none());
+    }
+}
@@ -0,0 +1,78 @@
+//! Cache-busting query params on chunk URLs.
+//!
+//! The runtime appends `?v=<version>` to the URLs of chunks it loads after an
+//! update, so stale service workers or aggressive proxies can't serve outdated
+//! chunks. These params, and timestamps like `?t=` or `?_=`, are removed from
+//! chunk requests before they are resolved, so they don't affect the content
+//! that's served. Chunks are also served with `Cache-Control: no-cache`, so
+//! caches need to revalidate them.
+
+use anyhow::Result;
+use hyper::{http::uri::PathAndQuery, Uri};
+
+/// The query params which only bust caches of chunk requests.
+pub const CACHE_BUSTING_PARAMS: [&str; 3] = ["v", "t", "_"];
+
+const CHUNK_EXTENSIONS: [&str; 4] = [".js", ".css", ".map", ".json"];
+
+/// Whether a request for `path` is a request for a chunk, a chunk list or a
+/// source map of a chunk.
+pub(crate) fn is_chunk_path(path: &str) -> bool {
+    CHUNK_EXTENSIONS
+        .iter()
+        .any(|extension| path.ends_with(extension))
+}
+
+/// Removes the [CACHE_BUSTING_PARAMS] from the query of a chunk `uri`.
+pub(crate) fn normalize_chunk_uri(uri: &Uri) -> Result<Uri> {
+    let Some(query) = uri.query() else {
+        return Ok(uri.clone());
+    };
+    if !is_chunk_path(uri.path()) {
+        return Ok(uri.clone());
+    }
+    let params = query
+        .split('&')
+        .filter(|param| {
+            let name = param.split_once('=').map_or(*param, |(name, _)| name);
+            !param.is_empty() && !CACHE_BUSTING_PARAMS.contains(&name)
+        })
+        .collect::<Vec<_>>();
+    let path_and_query = if params.is_empty() {
+        uri.path().to_string()
+    } else {
+        format!("{}?{}", uri.path(), params.join("&"))
+    };
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(PathAndQuery::try_from(path_and_query)?);
+    Ok(Uri::from_parts(parts)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::Uri;
+
+    use super::normalize_chunk_uri;
+
+    fn normalize(uri: &str) -> String {
+        normalize_chunk_uri(&uri.parse::<Uri>().unwrap())
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn removes_cache_busting_params() {
+        assert_eq!(normalize("/_chunks/a.js?v=3"), "/_chunks/a.js");
+        assert_eq!(
+            normalize("/_chunks/a.css?t=1680000000000"),
+            "/_chunks/a.css"
+        );
+        assert_eq!(normalize("/_chunks/a.js?x=1&_=2&v"), "/_chunks/a.js?x=1");
+        assert_eq!(normalize("/_chunks/a.js.map"), "/_chunks/a.js.map");
+    }
+
+    #[test]
+    fn keeps_params_of_other_requests() {
+        assert_eq!(normalize("/page?v=3"), "/page?v=3");
+    }
+}
@@ -0,0 +1,123 @@
+use rand::{distributions::Alphanumeric, Rng};
+
+/// Placeholder for the nonce of a Content-Security-Policy.
+///
+/// Content is cached across requests, so it can only contain this
+/// placeholder. The dev server replaces it with a fresh nonce for every
+/// request, in HTML responses as well as in response headers.
+pub const CSP_NONCE_PLACEHOLDER: &str = "__TURBOPACK_CSP_NONCE__";
+
+/// A Content-Security-Policy that is applied to HTML pages.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct CspConfig {
+    /// The policy, where [CSP_NONCE_PLACEHOLDER] stands for the nonce of the
+    /// request.
+    pub policy: String,
+}
+
+#[turbo_tasks::value_impl]
+impl CspConfigVc {
+    #[turbo_tasks::function]
+    pub fn new(policy: String) -> Self {
+        CspConfig { policy }.cell()
+    }
+
+    /// A strict policy that only allows scripts and styles carrying the nonce
+    /// and the scripts those load. `'unsafe-eval'` is needed to apply HMR
+    /// updates.
+    #[turbo_tasks::function]
+    pub fn strict() -> Self {
+        CspConfig {
+            policy: format!(
+                "script-src 'nonce-{CSP_NONCE_PLACEHOLDER}' 'strict-dynamic' 'unsafe-eval'; \
+                 style-src 'self' 'nonce-{CSP_NONCE_PLACEHOLDER}'"
+            ),
+        }
+        .cell()
+    }
+}
+
+/// Creates a random nonce for a single request.
+pub(crate) fn generate_nonce() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(24)
+        .map(char::from)
+        .collect()
+}
+
+/// Replaces all [CSP_NONCE_PLACEHOLDER]s in `content` with `nonce`.
+pub(crate) fn replace_nonce_placeholder(content: &str, nonce: &str) -> String {
+    content.replace(CSP_NONCE_PLACEHOLDER, nonce)
+}
+
+/// Replaces [CSP_NONCE_PLACEHOLDER]s in a body that is streamed in chunks. A
+/// placeholder can be split across chunks, so the end of a chunk that might be
+/// the start of one is held back until the next chunk.
+pub(crate) struct NonceReplacer {
+    nonce: String,
+    pending: Vec<u8>,
+}
+
+impl NonceReplacer {
+    pub(crate) fn new(nonce: String) -> Self {
+        NonceReplacer {
+            nonce,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Returns the content of `chunk` that is ready to be sent.
+    pub(crate) fn push(&mut self, chunk: &[u8]) -> Vec<u8> {
+        self.pending.extend_from_slice(chunk);
+        let placeholder = CSP_NONCE_PLACEHOLDER.as_bytes();
+        let mut output = Vec::with_capacity(self.pending.len());
+        let mut pos = 0;
+        while pos < self.pending.len() {
+            let rest = &self.pending[pos..];
+            if rest.starts_with(placeholder) {
+                output.extend_from_slice(self.nonce.as_bytes());
+                pos += placeholder.len();
+            } else if placeholder.starts_with(rest) {
+                break;
+            } else {
+                output.push(rest[0]);
+                pos += 1;
+            }
+        }
+        self.pending.drain(..pos);
+        output
+    }
+
+    /// Returns the content that has been held back at the end of the body.
+    pub(crate) fn finish(self) -> Vec<u8> {
+        self.pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NonceReplacer, CSP_NONCE_PLACEHOLDER};
+
+    #[test]
+    fn replaces_placeholders_split_across_chunks() {
+        let (start, end) = CSP_NONCE_PLACEHOLDER.split_at(5);
+        let mut replacer = NonceReplacer::new("abc".to_string());
+        let mut output = replacer.push(format!("<script nonce=\"{start}").as_bytes());
+        assert_eq!(output, b"<script nonce=\"");
+        output.extend(replacer.push(format!("{end}\"></script>_").as_bytes()));
+        output.extend(replacer.finish());
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "<script nonce=\"abc\"></script>_"
+        );
+    }
+
+    #[test]
+    fn releases_partial_placeholders_at_the_end() {
+        let mut replacer = NonceReplacer::new("abc".to_string());
+        assert_eq!(replacer.push(b"a __TURBO"), b"a ");
+        assert_eq!(replacer.finish(), b"__TURBO");
+    }
+}
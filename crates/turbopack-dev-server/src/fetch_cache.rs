@@ -0,0 +1,48 @@
+//! The generation of the `fetch` cache of renderers in development.
+//!
+//! Renderers can cache the responses of `fetch` requests between renders, so
+//! reloading a page that fetches slow upstream APIs doesn't hit them on every
+//! render. The cache is dropped whenever the generation changes, which
+//! happens on a `POST` or `DELETE` request to [FETCH_CACHE_PATH].
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::Serialize;
+
+/// The path of the endpoint which invalidates the `fetch` cache.
+pub const FETCH_CACHE_PATH: &str = "/turbopack-fetch-cache";
+
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// The response of the [FETCH_CACHE_PATH] endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FetchCacheStatus {
+    pub generation: u64,
+}
+
+/// The current generation of the `fetch` cache. Responses cached in an
+/// earlier generation aren't used anymore.
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::Acquire)
+}
+
+/// Invalidates the cached responses of all renderers by starting a new
+/// generation.
+pub fn invalidate() -> FetchCacheStatus {
+    FetchCacheStatus {
+        generation: GENERATION.fetch_add(1, Ordering::AcqRel) + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generation, invalidate};
+
+    #[test]
+    fn invalidate_starts_a_new_generation() {
+        let before = generation();
+        let status = invalidate();
+        assert!(status.generation > before);
+        assert!(generation() >= status.generation);
+    }
+}
@@ -0,0 +1,85 @@
+//! `<link rel="preload">` hints for the fonts used by the chunks of a page.
+//!
+//! Fonts are only requested once the stylesheet that uses them is parsed and
+//! text using them is laid out. Preloading them from the `<head>` lets the
+//! browser fetch them in parallel to the stylesheets, which avoids a late
+//! swap of fallback fonts for text-heavy pages.
+
+use anyhow::Result;
+use indexmap::IndexSet;
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::{asset::Asset, chunk::ChunkVc, reference::AssetReference};
+
+/// The mime types of font files by extension.
+const FONT_TYPES: [(&str, &str); 4] = [
+    (".woff2", "font/woff2"),
+    (".woff", "font/woff"),
+    (".ttf", "font/ttf"),
+    (".otf", "font/otf"),
+];
+
+fn font_type(path: &str) -> Option<&'static str> {
+    FONT_TYPES
+        .iter()
+        .find(|(extension, _)| path.ends_with(extension))
+        .map(|(_, ty)| *ty)
+}
+
+/// The paths of the fonts referenced by `chunks`, relative to `context_path`
+/// and starting with `/`. Fonts which are inlined as data URIs aren't
+/// referenced, so they aren't included.
+pub(crate) async fn referenced_font_paths(
+    chunks: &[ChunkVc],
+    context_path: &FileSystemPath,
+) -> Result<Vec<String>> {
+    let mut paths = IndexSet::new();
+    for chunk in chunks {
+        for reference in chunk.references().await?.iter() {
+            for asset in reference.resolve_reference().primary_assets().await?.iter() {
+                let path = &*asset.ident().path().await?;
+                if font_type(&path.path).is_none() {
+                    continue;
+                }
+                if let Some(relative_path) = context_path.get_path_to(path) {
+                    paths.insert(format!("/{relative_path}"));
+                }
+            }
+        }
+    }
+    Ok(paths.into_iter().collect())
+}
+
+/// The `<link rel="preload">` tag for the font at `href`. `attributes` are
+/// added to the tag as they are, e.g. a CSP nonce.
+pub(crate) fn font_preload_tag(href: &str, attributes: &str) -> Option<String> {
+    let ty = font_type(href)?;
+    // Fonts are always fetched in CORS mode, so the preload needs to be too to
+    // be reused.
+    Some(format!(
+        "<link rel=\"preload\" href=\"{href}\" as=\"font\" type=\"{ty}\" crossorigin{attributes}>"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::font_preload_tag;
+
+    #[test]
+    fn preload_tags() {
+        assert_eq!(
+            font_preload_tag("/_assets/inter.woff2", "").as_deref(),
+            Some(
+                "<link rel=\"preload\" href=\"/_assets/inter.woff2\" as=\"font\" \
+                 type=\"font/woff2\" crossorigin>"
+            )
+        );
+        assert_eq!(
+            font_preload_tag("/_assets/inter.ttf", " nonce=\"n\"").as_deref(),
+            Some(
+                "<link rel=\"preload\" href=\"/_assets/inter.ttf\" as=\"font\" type=\"font/ttf\" \
+                 crossorigin nonce=\"n\">"
+            )
+        );
+        assert_eq!(font_preload_tag("/_assets/logo.png", ""), None);
+    }
+}
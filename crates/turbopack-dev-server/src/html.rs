@@ -1,25 +1,64 @@
 use anyhow::{anyhow, Result};
+use indexmap::IndexSet;
 use mime_guess::mime::TEXT_HTML_UTF_8;
-use turbo_tasks::primitives::StringVc;
+use turbo_tasks::{primitives::StringVc, ValueToString};
 use turbo_tasks_fs::{File, FileSystemPathVc};
 use turbo_tasks_hash::{encode_hex, Xxh3Hash64Hasher};
 use turbopack_core::{
     asset::{Asset, AssetContentVc, AssetVc},
     chunk::{Chunk, ChunkGroupVc, ChunkReferenceVc},
     ident::AssetIdentVc,
+    introspect::{
+        asset::content_to_details, Introspectable, IntrospectableChildrenVc, IntrospectableVc,
+    },
     reference::AssetReferencesVc,
     version::{Version, VersionVc, VersionedContent, VersionedContentVc},
 };
 
+use crate::{
+    csp::{CspConfigVc, CSP_NONCE_PLACEHOLDER},
+    font_preload::{font_preload_tag, referenced_font_paths},
+    html_transform::{HtmlTransform, HtmlTransformVc},
+    module_preload::{chunk_prefetch_tag, chunk_preload_tag, prefetched_chunk_paths},
+};
+
 /// The HTML entry point of the dev server.
 ///
 /// Generates an HTML page that includes the ES and CSS chunks.
+///
+/// When legacy chunk groups are provided, the ES chunks are loaded via
+/// `<script type="module">` and the legacy chunks via `<script nomodule>`, so
+/// each browser only executes one of them.
+///
+/// When an import map is provided, it's inlined as `<script type="importmap">`
+/// and the ES chunks are loaded as modules.
+///
+/// When a Content-Security-Policy is configured, it's added as `<meta>` tag and
+/// all scripts and stylesheets carry the nonce of the request, which the chunk
+/// runtime also applies to the chunks it loads.
+///
+/// When font preloading is enabled, the fonts referenced by the chunks are
+/// preloaded via `<link rel="preload">` in the `<head>`. When module
+/// preloading is enabled, the same is done for the ES chunks, which are only
+/// loaded at the end of the `<body>` otherwise.
+///
+/// The chunks of dynamic imports which are prefetched eagerly, e.g. with
+/// `/* turbopackPrefetch: "eager" */`, are announced with `<link
+/// rel="prefetch">`.
+///
+/// The generated HTML can be post-processed by [HtmlTransform]s.
 #[turbo_tasks::value(shared)]
 #[derive(Clone)]
 pub struct DevHtmlAsset {
     path: FileSystemPathVc,
     chunk_groups: Vec<ChunkGroupVc>,
+    legacy_chunk_groups: Vec<ChunkGroupVc>,
+    import_map: Option<StringVc>,
+    csp: Option<CspConfigVc>,
     body: Option<String>,
+    preload_fonts: bool,
+    preload_modules: bool,
+    transforms: Vec<HtmlTransformVc>,
 }
 
 #[turbo_tasks::value_impl]
@@ -37,7 +76,7 @@ impl Asset for DevHtmlAsset {
     #[turbo_tasks::function]
     async fn references(&self) -> Result<AssetReferencesVc> {
         let mut references = Vec::new();
-        for chunk_group in &self.chunk_groups {
+        for chunk_group in self.chunk_groups.iter().chain(&self.legacy_chunk_groups) {
             let chunks = chunk_group.chunks().await?;
             for chunk in chunks.iter() {
                 references.push(ChunkReferenceVc::new(*chunk).into());
@@ -52,13 +91,64 @@ impl Asset for DevHtmlAsset {
     }
 }
 
+#[turbo_tasks::function]
+fn introspectable_type() -> StringVc {
+    StringVc::cell("dev html".to_string())
+}
+
+#[turbo_tasks::function]
+fn chunk_group_key() -> StringVc {
+    StringVc::cell("chunk group".to_string())
+}
+
+#[turbo_tasks::function]
+fn legacy_chunk_group_key() -> StringVc {
+    StringVc::cell("legacy chunk group".to_string())
+}
+
+#[turbo_tasks::value_impl]
+impl Introspectable for DevHtmlAsset {
+    #[turbo_tasks::function]
+    fn ty(&self) -> StringVc {
+        introspectable_type()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        self.path.to_string()
+    }
+
+    #[turbo_tasks::function]
+    fn details(self_vc: DevHtmlAssetVc) -> StringVc {
+        content_to_details(self_vc.content())
+    }
+
+    #[turbo_tasks::function]
+    fn children(&self) -> IntrospectableChildrenVc {
+        let mut children = IndexSet::new();
+        for &chunk_group in &self.chunk_groups {
+            children.insert((chunk_group_key(), chunk_group.as_introspectable()));
+        }
+        for &chunk_group in &self.legacy_chunk_groups {
+            children.insert((legacy_chunk_group_key(), chunk_group.as_introspectable()));
+        }
+        IntrospectableChildrenVc::cell(children)
+    }
+}
+
 impl DevHtmlAssetVc {
     /// Create a new dev HTML asset.
     pub fn new(path: FileSystemPathVc, chunk_groups: Vec<ChunkGroupVc>) -> Self {
         DevHtmlAsset {
             path,
             chunk_groups,
+            legacy_chunk_groups: Vec::new(),
+            import_map: None,
+            csp: None,
             body: None,
+            preload_fonts: false,
+            preload_modules: false,
+            transforms: Vec::new(),
         }
         .cell()
     }
@@ -72,7 +162,13 @@ impl DevHtmlAssetVc {
         DevHtmlAsset {
             path,
             chunk_groups,
+            legacy_chunk_groups: Vec::new(),
+            import_map: None,
+            csp: None,
             body: Some(body),
+            preload_fonts: false,
+            preload_modules: false,
+            transforms: Vec::new(),
         }
         .cell()
     }
@@ -93,6 +189,62 @@ impl DevHtmlAssetVc {
         html.body = Some(body);
         Ok(html.cell())
     }
+
+    /// Adds legacy variants of the chunk groups, which are loaded as
+    /// `<script nomodule>` by browsers that don't support ES modules.
+    #[turbo_tasks::function]
+    pub async fn with_legacy_chunk_groups(
+        self,
+        legacy_chunk_groups: Vec<ChunkGroupVc>,
+    ) -> Result<Self> {
+        let mut html: DevHtmlAsset = self.await?.clone_value();
+        html.legacy_chunk_groups = legacy_chunk_groups;
+        Ok(html.cell())
+    }
+
+    /// Sets the import map (as JSON) that is used to load chunks, e.g. the one
+    /// created by `turbopack_dev::import_map::chunk_import_map`.
+    #[turbo_tasks::function]
+    pub async fn with_import_map(self, import_map: StringVc) -> Result<Self> {
+        let mut html: DevHtmlAsset = self.await?.clone_value();
+        html.import_map = Some(import_map);
+        Ok(html.cell())
+    }
+
+    /// Applies a Content-Security-Policy to the page.
+    #[turbo_tasks::function]
+    pub async fn with_csp(self, csp: CspConfigVc) -> Result<Self> {
+        let mut html: DevHtmlAsset = self.await?.clone_value();
+        html.csp = Some(csp);
+        Ok(html.cell())
+    }
+
+    /// Preloads the fonts referenced by the chunks of the page.
+    #[turbo_tasks::function]
+    pub async fn with_font_preloading(self) -> Result<Self> {
+        let mut html: DevHtmlAsset = self.await?.clone_value();
+        html.preload_fonts = true;
+        Ok(html.cell())
+    }
+
+    /// Preloads the ES chunks of the page from the `<head>`, so they are
+    /// fetched in parallel to the stylesheets. Legacy chunks are not
+    /// preloaded, as modern browsers would fetch them without using them.
+    #[turbo_tasks::function]
+    pub async fn with_module_preloading(self) -> Result<Self> {
+        let mut html: DevHtmlAsset = self.await?.clone_value();
+        html.preload_modules = true;
+        Ok(html.cell())
+    }
+
+    /// Adds a transform that post-processes the HTML of the page. Transforms
+    /// are applied in the order they are added.
+    #[turbo_tasks::function]
+    pub async fn with_transform(self, transform: HtmlTransformVc) -> Result<Self> {
+        let mut html: DevHtmlAsset = self.await?.clone_value();
+        html.transforms.push(transform);
+        Ok(html.cell())
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -103,72 +255,204 @@ impl DevHtmlAssetVc {
         let context_path = this.path.parent().await?;
 
         let mut chunk_paths = vec![];
+        let mut font_paths = IndexSet::new();
+        let mut prefetch_paths = IndexSet::new();
         for chunk_group in &this.chunk_groups {
-            for chunk in chunk_group.chunks().await?.iter() {
+            let chunks = chunk_group.chunks().await?;
+            for chunk in chunks.iter() {
                 let chunk_path = &*chunk.path().await?;
                 if let Some(relative_path) = context_path.get_path_to(chunk_path) {
                     chunk_paths.push(format!("/{relative_path}"));
                 }
             }
+            if this.preload_fonts {
+                font_paths.extend(referenced_font_paths(&chunks, &context_path).await?);
+            }
+            prefetch_paths.extend(prefetched_chunk_paths(&chunks, &context_path).await?);
         }
+        // Chunks which are loaded by the page anyway don't need to be prefetched.
+        prefetch_paths.retain(|path| !chunk_paths.contains(path));
 
-        Ok(DevHtmlAssetContentVc::new(chunk_paths, this.body.clone()))
+        let mut legacy_chunk_paths = vec![];
+        for chunk_group in &this.legacy_chunk_groups {
+            for chunk in chunk_group.chunks().await?.iter() {
+                let chunk_path = &*chunk.path().await?;
+                if let Some(relative_path) = context_path.get_path_to(chunk_path) {
+                    legacy_chunk_paths.push(format!("/{relative_path}"));
+                }
+            }
+        }
+
+        let import_map = match this.import_map {
+            Some(import_map) => Some(import_map.await?.clone_value()),
+            None => None,
+        };
+
+        let csp_policy = match this.csp {
+            Some(csp) => Some(csp.await?.policy.clone()),
+            None => None,
+        };
+
+        let mut head_tags = Vec::new();
+        for transform in &this.transforms {
+            head_tags.extend(transform.head_tags().await?.iter().cloned());
+        }
+
+        Ok(DevHtmlAssetContent {
+            chunk_paths,
+            legacy_chunk_paths,
+            font_paths: font_paths.into_iter().collect(),
+            prefetch_paths: prefetch_paths.into_iter().collect(),
+            import_map,
+            csp_policy,
+            body: this.body.clone(),
+            preload_modules: this.preload_modules,
+            head_tags,
+            transforms: this.transforms.clone(),
+        }
+        .cell())
     }
 }
 
 #[turbo_tasks::value]
 struct DevHtmlAssetContent {
     chunk_paths: Vec<String>,
+    legacy_chunk_paths: Vec<String>,
+    font_paths: Vec<String>,
+    prefetch_paths: Vec<String>,
+    import_map: Option<String>,
+    csp_policy: Option<String>,
     body: Option<String>,
-}
-
-impl DevHtmlAssetContentVc {
-    pub fn new(chunk_paths: Vec<String>, body: Option<String>) -> Self {
-        DevHtmlAssetContent { chunk_paths, body }.cell()
-    }
+    preload_modules: bool,
+    /// The `<head>` tags of the transforms.
+    head_tags: Vec<String>,
+    transforms: Vec<HtmlTransformVc>,
 }
 
 #[turbo_tasks::value_impl]
 impl DevHtmlAssetContentVc {
     #[turbo_tasks::function]
     async fn content(self) -> Result<AssetContentVc> {
+        Ok(File::from(self.html().await?)
+            .with_content_type(TEXT_HTML_UTF_8)
+            .into())
+    }
+
+    /// The document, after it has been post-processed by the transforms.
+    #[turbo_tasks::function]
+    async fn html(self) -> Result<StringVc> {
         let this = self.await?;
 
         let mut scripts = Vec::new();
         let mut stylesheets = Vec::new();
 
+        let mut head = Vec::new();
+        let nonce = if let Some(policy) = &this.csp_policy {
+            head.push(format!(
+                "<meta http-equiv=\"Content-Security-Policy\" content=\"{}\">",
+                policy.replace('"', "&quot;")
+            ));
+            format!(" nonce=\"{CSP_NONCE_PLACEHOLDER}\"")
+        } else {
+            String::new()
+        };
+        head.extend(this.head_tags.iter().cloned());
+
+        let script_type = if this.legacy_chunk_paths.is_empty() && this.import_map.is_none() {
+            ""
+        } else {
+            " type=\"module\""
+        };
+
+        if let Some(import_map) = &this.import_map {
+            // The import map must be defined before any module is loaded. It's JSON,
+            // so `<` can only appear within strings, where it can be escaped.
+            scripts.push(format!(
+                "<script type=\"importmap\"{}>{}</script>",
+                nonce,
+                import_map.replace('<', "\\u003c")
+            ));
+        }
+
         for relative_path in &*this.chunk_paths {
             if relative_path.ends_with(".js") {
-                scripts.push(format!("<script src=\"{}\"></script>", relative_path));
+                scripts.push(format!(
+                    "<script{}{} src=\"{}\"></script>",
+                    script_type, nonce, relative_path
+                ));
             } else if relative_path.ends_with(".css") {
                 stylesheets.push(format!(
-                    "<link data-turbopack rel=\"stylesheet\" href=\"{}\">",
-                    relative_path
+                    "<link data-turbopack rel=\"stylesheet\"{} href=\"{}\">",
+                    nonce, relative_path
                 ));
             } else {
                 return Err(anyhow!("chunk with unknown asset type: {}", relative_path));
             }
         }
 
+        for relative_path in &*this.legacy_chunk_paths {
+            // Styles are shared with the module chunks.
+            if relative_path.ends_with(".js") {
+                scripts.push(format!(
+                    "<script nomodule{} src=\"{}\"></script>",
+                    nonce, relative_path
+                ));
+            }
+        }
+
         let body = match &this.body {
             Some(body) => body.as_str(),
             None => "",
         };
 
+        if this.preload_modules {
+            head.extend(this.chunk_paths.iter().filter_map(|relative_path| {
+                chunk_preload_tag(relative_path, !script_type.is_empty(), &nonce)
+            }));
+        }
+        // Fonts are preloaded before the stylesheets that use them are
+        // requested.
+        head.extend(
+            this.font_paths
+                .iter()
+                .filter_map(|relative_path| font_preload_tag(relative_path, &nonce)),
+        );
+        head.extend(stylesheets);
+        head.extend(
+            this.prefetch_paths
+                .iter()
+                .map(|relative_path| chunk_prefetch_tag(relative_path, &nonce)),
+        );
+
         let html = format!(
             "<!DOCTYPE html>\n<html>\n<head>\n{}\n</head>\n<body>\n{}\n{}\n</body>\n</html>",
-            stylesheets.join("\n"),
+            head.join("\n"),
             body,
             scripts.join("\n"),
         );
 
-        Ok(File::from(html).with_content_type(TEXT_HTML_UTF_8).into())
+        let mut html = StringVc::cell(html);
+        for transform in &this.transforms {
+            html = transform.transform_document(html);
+        }
+        Ok(html)
     }
 
     #[turbo_tasks::function]
     async fn version(self) -> Result<DevHtmlAssetVersionVc> {
         let this = self.await?;
-        Ok(DevHtmlAssetVersion { content: this }.cell())
+        // The output of the document transforms can change independently of
+        // the content, so it's part of the version.
+        let transformed_html = if this.transforms.is_empty() {
+            None
+        } else {
+            Some(self.html().await?.clone_value())
+        };
+        Ok(DevHtmlAssetVersion {
+            content: this,
+            transformed_html,
+        }
+        .cell())
     }
 }
 
@@ -188,6 +472,7 @@ impl VersionedContent for DevHtmlAssetContent {
 #[turbo_tasks::value]
 struct DevHtmlAssetVersion {
     content: DevHtmlAssetContentReadRef,
+    transformed_html: Option<String>,
 }
 
 #[turbo_tasks::value_impl]
@@ -198,9 +483,31 @@ impl Version for DevHtmlAssetVersion {
         for relative_path in &*self.content.chunk_paths {
             hasher.write_ref(relative_path);
         }
+        for relative_path in &*self.content.legacy_chunk_paths {
+            hasher.write_ref(relative_path);
+        }
+        for relative_path in &*self.content.font_paths {
+            hasher.write_ref(relative_path);
+        }
+        for relative_path in &*self.content.prefetch_paths {
+            hasher.write_ref(relative_path);
+        }
+        if let Some(import_map) = &self.content.import_map {
+            hasher.write_ref(import_map);
+        }
+        if let Some(csp_policy) = &self.content.csp_policy {
+            hasher.write_ref(csp_policy);
+        }
         if let Some(body) = &self.content.body {
             hasher.write_ref(body);
         }
+        hasher.write_value(self.content.preload_modules);
+        for tag in &*self.content.head_tags {
+            hasher.write_ref(tag);
+        }
+        if let Some(transformed_html) = &self.transformed_html {
+            hasher.write_ref(transformed_html);
+        }
         let hash = hasher.finish();
         let hex_hash = encode_hex(hash);
         Ok(StringVc::cell(hex_hash))
@@ -0,0 +1,654 @@
+use std::{collections::HashSet, ops::Range};
+
+use anyhow::{bail, Result};
+use indexmap::IndexSet;
+use mime_guess::mime::TEXT_HTML_UTF_8;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{primitives::StringVc, trace::TraceRawVcs, TryJoinIterExt, Value, ValueToString};
+use turbo_tasks_fs::{File, FileContent, FileSystemPath, FileSystemPathVc};
+use turbopack_core::{
+    asset::{Asset, AssetContentVc, AssetVc},
+    chunk::{
+        availability_info::AvailabilityInfo, ChunkGroupVc, ChunkReferenceVc, ChunkableAssetVc,
+        ChunkingContextVc,
+    },
+    context::AssetContextVc,
+    ident::AssetIdentVc,
+    reference::{AssetReferencesVc, SingleAssetReferenceVc},
+    reference_type::{EntryReferenceSubType, ReferenceType, UrlReferenceSubType},
+    resolve::{origin::PlainResolveOriginVc, parse::RequestVc, pattern::Pattern},
+};
+use turbopack_ecmascript::{
+    chunk::EcmascriptChunkPlaceablesVc, resolve::url_resolve, EcmascriptModuleAssetVc,
+};
+use turbopack_static::StaticModuleAssetVc;
+
+use crate::{
+    font_preload::{font_preload_tag, referenced_font_paths},
+    module_preload::chunk_preload_tag,
+    source::{
+        asset_graph::AssetGraphContentSourceVc, router::RouterContentSource, ContentSourceVc,
+        NoContentSourceVc,
+    },
+};
+
+#[turbo_tasks::function]
+fn modifier() -> StringVc {
+    StringVc::cell("html entry".to_string())
+}
+
+/// An HTML entry point provided by the user, e.g. an `index.html` of a single
+/// page application.
+///
+/// Every `<script src>`, `<link rel="stylesheet" href>` and `<img src>` that
+/// refers to a local file is resolved and bundled. The tags are then rewritten
+/// to point to the emitted chunks and assets.
+///
+/// When font preloading is enabled, the fonts referenced by the chunks of a
+/// tag are preloaded via `<link rel="preload">` tags in front of it. When
+/// module preloading is enabled, all JS chunks are preloaded at the end of the
+/// `<head>`, so they are fetched in parallel even when the scripts are only
+/// found at the end of the `<body>`.
+///
+/// The URLs of chunks and assets are their paths relative to the output root
+/// of the chunking context, behind the asset prefix (`/` by default).
+/// [HtmlEntryAssetVc::content_source] serves the page and everything it
+/// references at these URLs.
+#[turbo_tasks::value(shared)]
+#[derive(Clone)]
+pub struct HtmlEntryAsset {
+    source: AssetVc,
+    path: FileSystemPathVc,
+    context: AssetContextVc,
+    chunking_context: ChunkingContextVc,
+    runtime_entries: Option<EcmascriptChunkPlaceablesVc>,
+    preload_fonts: bool,
+    preload_modules: bool,
+    asset_prefix: String,
+}
+
+#[turbo_tasks::value_impl]
+impl HtmlEntryAssetVc {
+    /// Creates a new HTML entry asset that is served at `path`.
+    #[turbo_tasks::function]
+    pub fn new(
+        source: AssetVc,
+        path: FileSystemPathVc,
+        context: AssetContextVc,
+        chunking_context: ChunkingContextVc,
+        runtime_entries: Option<EcmascriptChunkPlaceablesVc>,
+    ) -> Self {
+        HtmlEntryAsset {
+            source,
+            path,
+            context,
+            chunking_context,
+            runtime_entries,
+            preload_fonts: false,
+            preload_modules: false,
+            asset_prefix: "/".to_string(),
+        }
+        .cell()
+    }
+
+    /// Preloads the fonts referenced by the chunks of the page.
+    #[turbo_tasks::function]
+    pub async fn with_font_preloading(self) -> Result<Self> {
+        let mut html: HtmlEntryAsset = self.await?.clone_value();
+        html.preload_fonts = true;
+        Ok(html.cell())
+    }
+
+    /// Preloads the JS chunks of the page from the `<head>`. Pages without a
+    /// `</head>` tag are left as they are.
+    #[turbo_tasks::function]
+    pub async fn with_module_preloading(self) -> Result<Self> {
+        let mut html: HtmlEntryAsset = self.await?.clone_value();
+        html.preload_modules = true;
+        Ok(html.cell())
+    }
+
+    /// Prefixes the URLs of chunks and assets with `asset_prefix` instead of
+    /// `/`, e.g. `/static/` or the URL of a CDN.
+    #[turbo_tasks::function]
+    pub async fn with_asset_prefix(self, asset_prefix: String) -> Result<Self> {
+        let mut html: HtmlEntryAsset = self.await?.clone_value();
+        html.asset_prefix = if asset_prefix.ends_with('/') {
+            asset_prefix
+        } else {
+            format!("{asset_prefix}/")
+        };
+        Ok(html.cell())
+    }
+
+    /// Serves the page and the chunks and assets it references at their URLs.
+    /// When the asset prefix is a path, everything is served below it.
+    /// Otherwise, e.g. for a CDN URL, everything is served from the root, so
+    /// the CDN can fetch it from the server.
+    #[turbo_tasks::function]
+    pub async fn content_source(self) -> Result<ContentSourceVc> {
+        let this = self.await?;
+        let source: ContentSourceVc =
+            AssetGraphContentSourceVc::new_eager(this.chunking_context.output_root(), self.into())
+                .into();
+        Ok(match this.asset_prefix.strip_prefix('/') {
+            Some(route) if !route.is_empty() => RouterContentSource {
+                routes: vec![(route.to_string(), source)],
+                fallback: NoContentSourceVc::new().into(),
+            }
+            .cell()
+            .into(),
+            _ => source,
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn source_html(self) -> Result<StringVc> {
+        let this = self.await?;
+        let FileContent::Content(file) = &*this.source.content().file_content().await? else {
+            bail!(
+                "HTML entry {} not found",
+                this.source.ident().to_string().await?
+            );
+        };
+        Ok(StringVc::cell(file.content().to_str()?.into_owned()))
+    }
+
+    #[turbo_tasks::function]
+    async fn entry_references(self) -> Result<HtmlEntryReferencesVc> {
+        let this = self.await?;
+        let html = self.source_html().await?;
+        let origin =
+            PlainResolveOriginVc::new(this.context, this.source.ident().path()).as_resolve_origin();
+        let references = find_entry_tags(&html)
+            .into_iter()
+            .map(|tag| async move {
+                let request = RequestVc::relative(
+                    Value::new(Pattern::Constant(relative_request(&tag.request))),
+                    false,
+                );
+                let output = match tag.kind {
+                    HtmlEntryTagKind::Script | HtmlEntryTagKind::Stylesheet => {
+                        let ty = Value::new(ReferenceType::Entry(EntryReferenceSubType::Web));
+                        let result =
+                            origin.resolve_asset(request, origin.resolve_options(ty.clone()), ty);
+                        match *result.first_asset().await? {
+                            Some(asset) => self.chunk_group(asset).await?,
+                            None => None,
+                        }
+                    }
+                    HtmlEntryTagKind::Image => {
+                        let result = url_resolve(
+                            origin,
+                            request,
+                            Value::new(UrlReferenceSubType::Undefined),
+                        );
+                        match *result.first_asset().await? {
+                            Some(asset) => self.static_asset(asset).await?,
+                            None => None,
+                        }
+                    }
+                };
+                Ok(output.map(|output| HtmlEntryReference { tag, output }))
+            })
+            .try_join()
+            .await?
+            .into_iter()
+            .flatten()
+            .collect();
+        Ok(HtmlEntryReferencesVc::cell(references))
+    }
+
+    #[turbo_tasks::function]
+    async fn html_content(self) -> Result<AssetContentVc> {
+        let this = self.await?;
+        let html = self.source_html().await?;
+        let output_root = this.chunking_context.output_root().await?;
+        let url = |relative_path: &str| format!("{}{relative_path}", this.asset_prefix);
+
+        // The preloads are inserted at the end of the `<head>`.
+        let mut preloads = None;
+        if this.preload_modules {
+            if let Some(head_end) = find_head_end(&html) {
+                let tags = self
+                    .chunk_preload_tags(&output_root, &this.asset_prefix)
+                    .await?;
+                if !tags.is_empty() {
+                    preloads = Some((head_end, format!("{}\n", tags.join("\n"))));
+                }
+            }
+        }
+
+        let mut output = String::with_capacity(html.len());
+        let mut last_end = 0;
+        let mut preloaded_fonts = HashSet::new();
+        for reference in self.entry_references().await?.iter() {
+            let tag = &reference.tag;
+            let replacement = match reference.output {
+                HtmlEntryOutput::ChunkGroup(chunk_group) => {
+                    let mut tags = Vec::new();
+                    let chunks = chunk_group.chunks().await?;
+                    if this.preload_fonts {
+                        for font_path in referenced_font_paths(&chunks, &output_root).await? {
+                            let font_path = url(font_path.trim_start_matches('/'));
+                            if let Some(preload) = font_preload_tag(&font_path, "") {
+                                if preloaded_fonts.insert(font_path) {
+                                    tags.push(preload);
+                                }
+                            }
+                        }
+                    }
+                    for chunk in chunks.iter() {
+                        let chunk_path = &*chunk.ident().path().await?;
+                        let Some(relative_path) = output_root.get_path_to(chunk_path) else {
+                            continue;
+                        };
+                        let chunk_url = url(relative_path);
+                        if relative_path.ends_with(".js") {
+                            tags.push(format!("<script src=\"{chunk_url}\"></script>"));
+                        } else if relative_path.ends_with(".css") {
+                            tags.push(format!(
+                                "<link data-turbopack rel=\"stylesheet\" href=\"{chunk_url}\">"
+                            ));
+                        } else {
+                            bail!("chunk with unknown asset type: {}", relative_path);
+                        }
+                    }
+                    push_html(&mut output, &html, last_end..tag.start, &mut preloads);
+                    last_end = tag.end;
+                    tags.join("\n")
+                }
+                HtmlEntryOutput::Asset(asset) => {
+                    let asset_path = &*asset.ident().path().await?;
+                    let Some(relative_path) = output_root.get_path_to(asset_path) else {
+                        continue;
+                    };
+                    push_html(&mut output, &html, last_end..tag.value_start, &mut preloads);
+                    last_end = tag.value_end;
+                    url(relative_path)
+                }
+            };
+            output.push_str(&replacement);
+        }
+        push_html(&mut output, &html, last_end..html.len(), &mut preloads);
+
+        Ok(File::from(output).with_content_type(TEXT_HTML_UTF_8).into())
+    }
+}
+
+impl HtmlEntryAssetVc {
+    /// The preload tags for the JS chunks of all chunk groups of the page.
+    async fn chunk_preload_tags(
+        self,
+        output_root: &FileSystemPath,
+        asset_prefix: &str,
+    ) -> Result<Vec<String>> {
+        let mut chunk_paths = IndexSet::new();
+        for reference in self.entry_references().await?.iter() {
+            let HtmlEntryOutput::ChunkGroup(chunk_group) = reference.output else {
+                continue;
+            };
+            for chunk in chunk_group.chunks().await?.iter() {
+                let chunk_path = &*chunk.ident().path().await?;
+                if let Some(relative_path) = output_root.get_path_to(chunk_path) {
+                    chunk_paths.insert(format!("{asset_prefix}{relative_path}"));
+                }
+            }
+        }
+        // Chunks are loaded as classic scripts.
+        Ok(chunk_paths
+            .iter()
+            .filter_map(|chunk_path| chunk_preload_tag(chunk_path, false, ""))
+            .collect())
+    }
+
+    async fn chunk_group(self, asset: AssetVc) -> Result<Option<HtmlEntryOutput>> {
+        let this = self.await?;
+        let chunk_group = if let Some(ecma) = EcmascriptModuleAssetVc::resolve_from(asset).await? {
+            ChunkGroupVc::from_chunk(
+                ecma.as_evaluated_chunk(this.chunking_context, this.runtime_entries),
+            )
+        } else if let Some(chunkable) = ChunkableAssetVc::resolve_from(asset).await? {
+            ChunkGroupVc::from_asset(
+                chunkable,
+                this.chunking_context,
+                Value::new(AvailabilityInfo::Root {
+                    current_availability_root: asset,
+                }),
+            )
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(HtmlEntryOutput::ChunkGroup(chunk_group)))
+    }
+
+    async fn static_asset(self, asset: AssetVc) -> Result<Option<HtmlEntryOutput>> {
+        let this = self.await?;
+        Ok(StaticModuleAssetVc::resolve_from(asset)
+            .await?
+            .map(|asset| HtmlEntryOutput::Asset(asset.static_asset(this.chunking_context).into())))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for HtmlEntryAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> AssetIdentVc {
+        AssetIdentVc::from_path(self.path).with_modifier(modifier())
+    }
+
+    #[turbo_tasks::function]
+    fn content(self_vc: HtmlEntryAssetVc) -> AssetContentVc {
+        self_vc.html_content()
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self_vc: HtmlEntryAssetVc) -> Result<AssetReferencesVc> {
+        let mut references = Vec::new();
+        for reference in self_vc.entry_references().await?.iter() {
+            match reference.output {
+                HtmlEntryOutput::ChunkGroup(chunk_group) => {
+                    for chunk in chunk_group.chunks().await?.iter() {
+                        references.push(ChunkReferenceVc::new(*chunk).into());
+                    }
+                }
+                HtmlEntryOutput::Asset(asset) => {
+                    references.push(
+                        SingleAssetReferenceVc::new(
+                            asset,
+                            StringVc::cell(format!("html entry {}", reference.tag.request)),
+                        )
+                        .into(),
+                    );
+                }
+            }
+        }
+        Ok(AssetReferencesVc::cell(references))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+enum HtmlEntryOutput {
+    ChunkGroup(ChunkGroupVc),
+    Asset(AssetVc),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+struct HtmlEntryReference {
+    tag: HtmlEntryTag,
+    output: HtmlEntryOutput,
+}
+
+#[turbo_tasks::value(transparent)]
+struct HtmlEntryReferences(Vec<HtmlEntryReference>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+enum HtmlEntryTagKind {
+    Script,
+    Stylesheet,
+    Image,
+}
+
+/// A tag in the HTML source that refers to a local file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+struct HtmlEntryTag {
+    kind: HtmlEntryTagKind,
+    request: String,
+    /// Byte range of the whole tag, including the closing `</script>`.
+    start: usize,
+    end: usize,
+    /// Byte range of the url attribute value, without quotes.
+    value_start: usize,
+    value_end: usize,
+}
+
+/// The position of the closing `</head>` tag in `html`.
+fn find_head_end(html: &str) -> Option<usize> {
+    html.to_ascii_lowercase().find("</head")
+}
+
+/// Pushes `html[range]` to `output`. `insertion` is pushed as well when its
+/// position is within the range.
+fn push_html(
+    output: &mut String,
+    html: &str,
+    range: Range<usize>,
+    insertion: &mut Option<(usize, String)>,
+) {
+    match insertion.take() {
+        Some((position, inserted)) if range.contains(&position) => {
+            output.push_str(&html[range.start..position]);
+            output.push_str(&inserted);
+            output.push_str(&html[position..range.end]);
+        }
+        other => {
+            *insertion = other;
+            output.push_str(&html[range]);
+        }
+    }
+}
+
+/// Requests starting with `/` are relative to the directory of the HTML file.
+fn relative_request(request: &str) -> String {
+    if let Some(request) = request.strip_prefix('/') {
+        format!("./{request}")
+    } else if request.starts_with("./") || request.starts_with("../") {
+        request.to_string()
+    } else {
+        format!("./{request}")
+    }
+}
+
+fn is_local_url(url: &str) -> bool {
+    !(url.is_empty()
+        || url.starts_with('#')
+        || url.starts_with("//")
+        || url.contains("://")
+        || url.starts_with("data:")
+        || url.starts_with("blob:"))
+}
+
+struct Attribute<'a> {
+    name: &'a str,
+    value: &'a str,
+    value_start: usize,
+}
+
+/// Finds all `<script src>`, `<link rel="stylesheet" href>` and `<img src>`
+/// tags in `html` that refer to a local file.
+fn find_entry_tags(html: &str) -> Vec<HtmlEntryTag> {
+    let mut tags = Vec::new();
+    let bytes = html.as_bytes();
+    let mut pos = 0;
+    while let Some(offset) = html[pos..].find('<') {
+        let start = pos + offset;
+        if html[start..].starts_with("<!--") {
+            pos = match html[start..].find("-->") {
+                Some(end) => start + end + 3,
+                None => html.len(),
+            };
+            continue;
+        }
+        let name_start = start + 1;
+        let mut name_end = name_start;
+        while name_end < bytes.len() && bytes[name_end].is_ascii_alphanumeric() {
+            name_end += 1;
+        }
+        if name_end == name_start {
+            pos = name_start;
+            continue;
+        }
+        let name = html[name_start..name_end].to_ascii_lowercase();
+        let (attributes, tag_end) = parse_attributes(html, name_end);
+        pos = tag_end;
+
+        let attribute = |name: &str| {
+            attributes
+                .iter()
+                .find(|attribute| attribute.name.eq_ignore_ascii_case(name))
+        };
+        let (kind, url) = match name.as_str() {
+            "script" => {
+                // Skip the content of the script, it's not HTML.
+                if let Some(close) = html[pos..].to_ascii_lowercase().find("</script") {
+                    pos += close;
+                    pos = html[pos..]
+                        .find('>')
+                        .map_or(html.len(), |end| pos + end + 1);
+                }
+                (HtmlEntryTagKind::Script, attribute("src"))
+            }
+            "link" => {
+                let is_stylesheet = attribute("rel").map_or(false, |rel| {
+                    rel.value
+                        .split_ascii_whitespace()
+                        .any(|rel| rel.eq_ignore_ascii_case("stylesheet"))
+                });
+                if !is_stylesheet {
+                    continue;
+                }
+                (HtmlEntryTagKind::Stylesheet, attribute("href"))
+            }
+            "img" => (HtmlEntryTagKind::Image, attribute("src")),
+            _ => continue,
+        };
+        let Some(url) = url else {
+            continue;
+        };
+        if !is_local_url(url.value) {
+            continue;
+        }
+        tags.push(HtmlEntryTag {
+            kind,
+            request: url.value.to_string(),
+            start,
+            end: pos,
+            value_start: url.value_start,
+            value_end: url.value_start + url.value.len(),
+        });
+    }
+    tags
+}
+
+/// Parses the attributes of a tag starting at `pos` (after the tag name).
+/// Returns the attributes and the position after the closing `>`.
+fn parse_attributes(html: &str, mut pos: usize) -> (Vec<Attribute<'_>>, usize) {
+    let bytes = html.as_bytes();
+    let mut attributes = Vec::new();
+    loop {
+        while pos < bytes.len() && (bytes[pos].is_ascii_whitespace() || bytes[pos] == b'/') {
+            pos += 1;
+        }
+        if pos >= bytes.len() {
+            return (attributes, bytes.len());
+        }
+        if bytes[pos] == b'>' {
+            return (attributes, pos + 1);
+        }
+        let name_start = pos;
+        while pos < bytes.len()
+            && !bytes[pos].is_ascii_whitespace()
+            && !matches!(bytes[pos], b'=' | b'>' | b'/')
+        {
+            pos += 1;
+        }
+        let name = &html[name_start..pos];
+        while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        if pos >= bytes.len() || bytes[pos] != b'=' {
+            attributes.push(Attribute {
+                name,
+                value: "",
+                value_start: pos,
+            });
+            continue;
+        }
+        pos += 1;
+        while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        let (value_start, value_end) = match bytes.get(pos) {
+            Some(&quote @ (b'"' | b'\'')) => {
+                let value_start = pos + 1;
+                let value_end = html[value_start..]
+                    .find(quote as char)
+                    .map_or(html.len(), |end| value_start + end);
+                pos = (value_end + 1).min(html.len());
+                (value_start, value_end)
+            }
+            _ => {
+                let value_start = pos;
+                while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() && bytes[pos] != b'>' {
+                    pos += 1;
+                }
+                (value_start, pos)
+            }
+        };
+        attributes.push(Attribute {
+            name,
+            value: &html[value_start..value_end],
+            value_start,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_entry_tags() {
+        let html = r#"<!DOCTYPE html>
+<html>
+<head>
+<link rel="icon" href="/favicon.ico">
+<link rel="stylesheet" href="/src/index.css">
+<!-- <script src="/commented.js"></script> -->
+</head>
+<body>
+<img src=logo.png alt="logo">
+<img src="https://example.com/remote.png">
+<script type="module" src="/src/main.tsx"></script>
+<script>console.log("<img src='inline.png'>")</script>
+</body>
+</html>"#;
+        let tags = find_entry_tags(html);
+        let requests: Vec<_> = tags.iter().map(|tag| tag.request.as_str()).collect();
+        assert_eq!(
+            requests,
+            vec!["/src/index.css", "logo.png", "/src/main.tsx"]
+        );
+
+        let script = &tags[2];
+        assert_eq!(
+            &html[script.start..script.end],
+            r#"<script type="module" src="/src/main.tsx"></script>"#
+        );
+        let image = &tags[1];
+        assert_eq!(&html[image.value_start..image.value_end], "logo.png");
+    }
+
+    #[test]
+    fn inserts_at_head_end() {
+        let html = "<html><HEAD><title>a</title></HEAD><body></body></html>";
+        let head_end = find_head_end(html).unwrap();
+        let mut insertion = Some((head_end, "<link>".to_string()));
+        let mut output = String::new();
+        push_html(&mut output, html, 0..6, &mut insertion);
+        assert!(insertion.is_some());
+        push_html(&mut output, html, 6..html.len(), &mut insertion);
+        assert!(insertion.is_none());
+        assert_eq!(
+            output,
+            "<html><HEAD><title>a</title><link></HEAD><body></body></html>"
+        );
+    }
+
+    #[test]
+    fn relative_requests() {
+        assert_eq!(relative_request("/src/main.ts"), "./src/main.ts");
+        assert_eq!(relative_request("main.ts"), "./main.ts");
+        assert_eq!(relative_request("../main.ts"), "../main.ts");
+    }
+}
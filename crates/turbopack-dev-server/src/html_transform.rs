@@ -0,0 +1,51 @@
+//! Hooks to post-process the HTML of a [DevHtmlAsset].
+//!
+//! Embedders can inject tags like analytics snippets, meta tags or
+//! preconnects into the `<head>`, or transform the whole document, without
+//! reimplementing the HTML asset. The hooks are turbo tasks functions, so the
+//! HTML is regenerated when what they depend on changes.
+//!
+//! [DevHtmlAsset]: crate::html::DevHtmlAsset
+
+use turbo_tasks::primitives::{StringVc, StringsVc};
+
+/// Post-processes the HTML of a [DevHtmlAsset](crate::html::DevHtmlAsset).
+///
+/// Tags can use [CSP_NONCE_PLACEHOLDER](crate::csp::CSP_NONCE_PLACEHOLDER) as
+/// the nonce of inline scripts and styles.
+#[turbo_tasks::value_trait]
+pub trait HtmlTransform {
+    /// Tags to add to the `<head>`. They are added after the
+    /// Content-Security-Policy, but before preloads and stylesheets.
+    fn head_tags(&self) -> StringsVc {
+        StringsVc::empty()
+    }
+
+    /// Transforms the whole document. It's called after the tags of all
+    /// transforms are added, in the order of the transforms.
+    fn transform_document(&self, html: StringVc) -> StringVc {
+        html
+    }
+}
+
+/// An [HtmlTransform] which adds a fixed list of tags to the `<head>`.
+#[turbo_tasks::value(shared)]
+pub struct HtmlHeadTags {
+    pub tags: Vec<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl HtmlHeadTagsVc {
+    #[turbo_tasks::function]
+    pub fn new(tags: Vec<String>) -> Self {
+        HtmlHeadTags { tags }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl HtmlTransform for HtmlHeadTags {
+    #[turbo_tasks::function]
+    fn head_tags(&self) -> StringsVc {
+        StringsVc::cell(self.tags.clone())
+    }
+}
@@ -1,24 +1,43 @@
-use std::io::{Error, ErrorKind};
+use std::{
+    io::{Error, ErrorKind},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::Poll,
+    time::Instant,
+};
 
-use anyhow::Result;
-use futures::{StreamExt, TryStreamExt};
+use anyhow::{bail, Result};
+use futures::{stream, Stream, StreamExt, TryStreamExt};
 use hyper::{
-    header::{HeaderName, CONTENT_ENCODING, CONTENT_LENGTH},
+    header::{HeaderName, CACHE_CONTROL, CONTENT_ENCODING, CONTENT_LENGTH, ETAG, IF_NONE_MATCH},
     http::HeaderValue,
-    Request, Response,
+    Request, Response, StatusCode,
 };
 use mime::Mime;
 use mime_guess::mime;
+use once_cell::sync::OnceCell;
 use tokio_util::io::{ReaderStream, StreamReader};
 use turbo_tasks::TransientInstance;
 use turbo_tasks_bytes::Bytes;
-use turbo_tasks_fs::{FileContent, FileContentReadRef};
+use turbo_tasks_fs::{rope::Rope, FileContent, FileContentReadRef};
 use turbopack_core::{asset::AssetContent, issue::IssueReporterVc, version::VersionedContent};
 
-use crate::source::{
-    request::SourceRequest,
-    resolve::{resolve_source_request, ResolveSourceRequestResult},
-    Body, BodyError, ContentSourceVc, HeaderListReadRef, ProxyResultReadRef,
+use crate::{
+    abort::AbortOnDrop,
+    cache_busting::{is_chunk_path, normalize_chunk_uri},
+    csp::{generate_nonce, replace_nonce_placeholder, NonceReplacer, CSP_NONCE_PLACEHOLDER},
+    metrics::{
+        record_latency, record_request_bytes, record_response_bytes, SourceType,
+        INTROSPECTION_PREFIX,
+    },
+    source::{
+        request::{generate_request_id, SourceRequest, REQUEST_ID_HEADER},
+        resolve::{resolve_source_request, ResolveSourceRequestResult},
+        Body, BodyError, ContentSourceVc, HeaderListReadRef, ProxyResultReadRef,
+    },
+    websocket_proxy,
 };
 
 #[turbo_tasks::value(serialization = "none")]
@@ -69,13 +88,47 @@ async fn get_from_source(
 /// response.
 pub async fn process_request_with_content_source(
     source: ContentSourceVc,
-    request: Request<hyper::Body>,
+    mut request: Request<hyper::Body>,
     issue_reporter: IssueReporterVc,
 ) -> Result<Response<hyper::Body>> {
+    let start = Instant::now();
     let original_path = request.uri().path().to_string();
-    let request = http_request_to_source_request(request).await?;
+    // The handshake is only completed when the content source accepts the
+    // upgrade, see [websocket_proxy].
+    let websocket = if hyper_tungstenite::is_upgrade_request(&request) {
+        Some(hyper_tungstenite::upgrade(&mut request, None)?)
+    } else {
+        None
+    };
+    let (mut request, request_bytes) = http_request_to_source_request(request);
+    let is_chunk = is_chunk_path(&original_path);
+    if is_chunk {
+        request.uri = normalize_chunk_uri(&request.uri)?;
+    }
+    let request_id = HeaderValue::try_from(request.request_id.as_str())?;
+    let is_introspection = original_path.starts_with(INTROSPECTION_PREFIX);
+    let record_request = |source_type| {
+        let source_type = if is_introspection {
+            SourceType::Introspection
+        } else {
+            source_type
+        };
+        record_latency(source_type, start.elapsed());
+        let _ = request_bytes.source_type.set(source_type);
+        source_type
+    };
+    let if_none_match = request
+        .headers
+        .get(IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+    // Aborts renders of the request when the client disconnects and this
+    // future is dropped before the result is ready.
+    let abort_on_drop = AbortOnDrop::new(request.request_id.clone());
     let result = get_from_source(source, TransientInstance::new(request), issue_reporter);
-    match &*result.strongly_consistent().await? {
+    let result = result.strongly_consistent().await?;
+    abort_on_drop.disarm();
+    match &*result {
         GetFromSourceResult::Static {
             content,
             status_code,
@@ -85,22 +138,82 @@ pub async fn process_request_with_content_source(
             if let FileContent::Content(file) = &**content {
                 let mut response = Response::builder().status(*status_code);
 
+                // Pages that use a Content-Security-Policy contain a nonce placeholder,
+                // which is replaced with a fresh nonce for every request.
+                let is_html = file
+                    .content_type()
+                    .map(|content_type| content_type.subtype() == mime::HTML)
+                    .unwrap_or_else(|| {
+                        headers
+                            .iter()
+                            .chain(header_overwrites.iter())
+                            .any(|(name, value)| {
+                                name.eq_ignore_ascii_case("content-type")
+                                    && value.starts_with("text/html")
+                            })
+                    });
+                let html_with_nonce = if is_html {
+                    let html = file.content().to_str()?;
+                    html.contains(CSP_NONCE_PLACEHOLDER)
+                        .then(|| html.into_owned())
+                } else {
+                    None
+                };
+                let nonce = (html_with_nonce.is_some()
+                    || headers
+                        .iter()
+                        .chain(header_overwrites.iter())
+                        .any(|(_, value)| value.contains(CSP_NONCE_PLACEHOLDER)))
+                .then(generate_nonce);
+                let with_nonce = |value: &str| match &nonce {
+                    Some(nonce) => replace_nonce_placeholder(value, nonce),
+                    None => value.to_string(),
+                };
+
                 let header_map = response.headers_mut().expect("headers must be defined");
 
                 for (header_name, header_value) in headers {
                     header_map.append(
                         HeaderName::try_from(header_name.clone())?,
-                        hyper::header::HeaderValue::try_from(header_value.as_str())?,
+                        hyper::header::HeaderValue::try_from(with_nonce(header_value))?,
                     );
                 }
 
                 for (header_name, header_value) in header_overwrites.iter() {
                     header_map.insert(
                         HeaderName::try_from(header_name.clone())?,
-                        hyper::header::HeaderValue::try_from(header_value)?,
+                        hyper::header::HeaderValue::try_from(with_nonce(header_value))?,
                     );
                 }
 
+                header_map.insert(REQUEST_ID_HEADER, request_id);
+
+                if is_chunk {
+                    header_map
+                        .entry(CACHE_CONTROL)
+                        .or_insert(HeaderValue::from_static("no-cache"));
+                }
+
+                // Content with a nonce differs for every request, so it's never
+                // reported as not modified.
+                let etag = header_map.get(ETAG).and_then(|etag| etag.to_str().ok());
+                let not_modified = *status_code == 200
+                    && nonce.is_none()
+                    && matches!(
+                        (&if_none_match, etag),
+                        (Some(if_none_match), Some(etag)) if etag_matches(if_none_match, etag)
+                    );
+                if not_modified {
+                    record_request(if is_html {
+                        SourceType::Ssr
+                    } else {
+                        SourceType::StaticAsset
+                    });
+                    return Ok(response
+                        .status(StatusCode::NOT_MODIFIED)
+                        .body(hyper::Body::empty())?);
+                }
+
                 // naively checking if content is `compressible`.
                 let mut should_compress = false;
                 let should_compress_predicate = |mime: &Mime| {
@@ -144,7 +257,15 @@ pub async fn process_request_with_content_source(
                     )?);
                 }
 
-                let content = file.content();
+                let content = match &html_with_nonce {
+                    Some(html) => Rope::from(with_nonce(html)),
+                    None => file.content().clone(),
+                };
+                let source_type = record_request(if is_html {
+                    SourceType::Ssr
+                } else {
+                    SourceType::StaticAsset
+                });
                 let response = if should_compress {
                     header_map.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
 
@@ -157,7 +278,8 @@ pub async fn process_request_with_content_source(
                     let gzipped_stream =
                         ReaderStream::new(async_compression::tokio::bufread::GzipEncoder::new(
                             StreamReader::new(stream_ext),
-                        ));
+                        ))
+                        .inspect_ok(move |chunk| record_response_bytes(source_type, chunk.len()));
 
                     response.body(hyper::Body::wrap_stream(gzipped_stream))?
                 } else {
@@ -165,6 +287,7 @@ pub async fn process_request_with_content_source(
                         CONTENT_LENGTH,
                         hyper::header::HeaderValue::try_from(content.len().to_string())?,
                     );
+                    record_response_bytes(source_type, content.len());
 
                     response.body(hyper::Body::wrap_stream(content.read()))?
                 };
@@ -173,45 +296,199 @@ pub async fn process_request_with_content_source(
             }
         }
         GetFromSourceResult::HttpProxy(proxy_result) => {
+            let source_type = record_request(SourceType::Ssr);
+            if let (Some(id), Some((mut response, websocket))) = (&proxy_result.upgrade, websocket)
+            {
+                let Some(proxy) = websocket_proxy::take(id) else {
+                    bail!("the WebSocket connection {id} doesn't exist anymore");
+                };
+                let headers = response.headers_mut();
+                for (name, value) in &proxy_result.headers {
+                    headers.append(
+                        HeaderName::from_bytes(name.as_bytes())?,
+                        hyper::header::HeaderValue::from_str(value)?,
+                    );
+                }
+                headers.insert(REQUEST_ID_HEADER, request_id);
+                websocket_proxy::relay(websocket, proxy);
+                return Ok(response);
+            }
             let mut response = Response::builder().status(proxy_result.status);
             let headers = response.headers_mut().expect("headers must be defined");
 
+            // Streamed HTML pages can contain nonce placeholders, like static ones.
+            let is_html = proxy_result.headers.iter().any(|(name, value)| {
+                name.eq_ignore_ascii_case("content-type") && value.starts_with("text/html")
+            });
+            let nonce = (is_html
+                || proxy_result
+                    .headers
+                    .iter()
+                    .any(|(_, value)| value.contains(CSP_NONCE_PLACEHOLDER)))
+            .then(generate_nonce);
+
             for (name, value) in &proxy_result.headers {
+                let value = match &nonce {
+                    Some(nonce) => replace_nonce_placeholder(value, nonce),
+                    None => value.clone(),
+                };
                 headers.append(
                     HeaderName::from_bytes(name.as_bytes())?,
-                    hyper::header::HeaderValue::from_str(value)?,
+                    hyper::header::HeaderValue::from_str(&value)?,
                 );
             }
 
-            return Ok(response.body(hyper::Body::wrap_stream(proxy_result.body.read()))?);
+            headers.insert(REQUEST_ID_HEADER, request_id);
+
+            let body = proxy_result.body.read();
+            let body = match nonce {
+                Some(nonce) if is_html => replace_nonce_in_body(body, nonce).boxed(),
+                _ => body.boxed(),
+            }
+            .inspect_ok(move |chunk| record_response_bytes(source_type, chunk.len()));
+            return Ok(response.body(hyper::Body::wrap_stream(body))?);
         }
         _ => {}
     }
 
-    Ok(Response::builder().status(404).body(hyper::Body::empty())?)
+    record_request(SourceType::StaticAsset);
+    Ok(Response::builder()
+        .status(404)
+        .header(REQUEST_ID_HEADER, request_id)
+        .body(hyper::Body::empty())?)
 }
 
-async fn http_request_to_source_request(request: Request<hyper::Body>) -> Result<SourceRequest> {
+/// Whether an `If-None-Match` header matches the `etag` of a response. Tags
+/// are compared weakly, as required for `If-None-Match`, i.e. ignoring the
+/// `W/` prefix.
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let etag = etag.trim_start_matches("W/");
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
+
+/// Replaces the nonce placeholders in the chunks of a streamed `body`.
+fn replace_nonce_in_body(
+    body: impl Stream<Item = Result<Bytes, BodyError>> + Send + Unpin + 'static,
+    nonce: String,
+) -> impl Stream<Item = Result<Bytes, BodyError>> + Send + 'static {
+    stream::unfold(
+        Some((body, NonceReplacer::new(nonce))),
+        |state| async move {
+            let (mut body, mut replacer) = state?;
+            Some(match body.next().await {
+                Some(Ok(chunk)) => {
+                    let chunk = Bytes::from(replacer.push(&chunk));
+                    (Ok(chunk), Some((body, replacer)))
+                }
+                Some(Err(err)) => (Err(err), None),
+                None => (Ok(Bytes::from(replacer.finish())), None),
+            })
+        },
+    )
+}
+
+/// Converts an HTTP request into a [SourceRequest]. The body is streamed, so
+/// content sources can forward it as it arrives, e.g. to a Node.js process,
+/// instead of waiting for large uploads to be buffered. The returned counter
+/// tracks the bytes of the body that are read.
+fn http_request_to_source_request(
+    request: Request<hyper::Body>,
+) -> (SourceRequest, Arc<RequestBytes>) {
     let (parts, body) = request.into_parts();
 
-    // For simplicity, we fully consume the body now and early return if there were
-    // any errors.
-    let bytes: Vec<_> = body
-        .map(|bytes| {
-            bytes.map_or_else(
-                |e| Err(BodyError::new(e.to_string())),
-                // The outer Ok is consumed by try_collect, but the Body type requires a Result, so
-                // we need to double wrap.
-                |b| Ok(Ok(Bytes::from(b))),
-            )
+    let request_bytes = Arc::new(RequestBytes::default());
+    let body = {
+        // Only the end of the body holds on to the counter, so it's recorded
+        // once the body has ended or is dropped.
+        let counter = Arc::downgrade(&request_bytes);
+        let mut end = Some(request_bytes.clone());
+        body.map(move |bytes| match bytes {
+            Ok(bytes) => {
+                if let Some(counter) = counter.upgrade() {
+                    counter.bytes.fetch_add(bytes.len(), Ordering::Relaxed);
+                }
+                Ok(Bytes::from(bytes))
+            }
+            Err(err) => Err(BodyError::new(err.to_string())),
         })
-        .try_collect::<Vec<_>>()
-        .await?;
-
-    Ok(SourceRequest {
-        method: parts.method.to_string(),
-        uri: parts.uri,
-        headers: parts.headers,
-        body: Body::new(bytes),
-    })
+        .chain(stream::poll_fn(move |_| {
+            end.take();
+            Poll::Ready(None)
+        }))
+    };
+
+    (
+        SourceRequest {
+            method: parts.method.to_string(),
+            uri: parts.uri,
+            headers: parts.headers,
+            body: Body::from_stream(body),
+            request_id: generate_request_id(),
+        },
+        request_bytes,
+    )
+}
+
+/// Counts the bytes of a request body. Content sources may read the body
+/// after the response has been created, e.g. when it's streamed to a proxied
+/// server, so the bytes are recorded once the body has ended or is dropped,
+/// and the request has been handled by a content source.
+#[derive(Default)]
+struct RequestBytes {
+    bytes: AtomicUsize,
+    source_type: OnceCell<SourceType>,
+}
+
+impl Drop for RequestBytes {
+    fn drop(&mut self) {
+        if let Some(&source_type) = self.source_type.get() {
+            record_request_bytes(source_type, *self.bytes.get_mut());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{executor::block_on, TryStreamExt};
+    use hyper::Request;
+
+    use super::{etag_matches, http_request_to_source_request};
+    use crate::metrics::{latest, SourceType};
+
+    #[test]
+    fn etags() {
+        assert!(etag_matches("\"abc\"", "\"abc\""));
+        assert!(etag_matches("\"xyz\", W/\"abc\"", "\"abc\""));
+        assert!(etag_matches("\"abc\"", "W/\"abc\""));
+        assert!(etag_matches("*", "\"abc\""));
+        assert!(!etag_matches("\"xyz\"", "\"abc\""));
+        assert!(!etag_matches("abc", "\"abc\""));
+    }
+
+    #[test]
+    fn request_bytes_are_recorded_once_the_body_has_been_read() {
+        let request = Request::builder()
+            .uri("/__turbopack__/upload")
+            .body(hyper::Body::from("hello"))
+            .unwrap();
+        let (request, request_bytes) = http_request_to_source_request(request);
+        let recorded = || latest()[&SourceType::Introspection].request_bytes;
+        let before = recorded();
+
+        // The request is handled before its body is read, like when the body
+        // is streamed to a proxied server.
+        request_bytes
+            .source_type
+            .set(SourceType::Introspection)
+            .unwrap();
+        drop(request_bytes);
+        assert_eq!(recorded(), before);
+
+        let chunks = block_on(request.body.read().try_collect::<Vec<_>>()).unwrap();
+        assert_eq!(chunks.iter().map(|chunk| chunk.len()).sum::<usize>(), 5);
+        assert_eq!(recorded(), before + 5);
+    }
 }
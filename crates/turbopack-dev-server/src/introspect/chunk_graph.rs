@@ -0,0 +1,199 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use turbo_tasks::{
+    graph::{GraphTraversal, NonDeterministic, SkipDuplicates},
+    TryJoinIterExt, ValueToString,
+};
+use turbo_tasks_fs::FileContent;
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{Chunk, ChunkGroupVc, ChunkVc},
+    introspect::{
+        asset::IntrospectableAssetVc,
+        chunk::{ChunkItemInfo, IntrospectableChunk, IntrospectableChunkVc},
+        Introspectable, IntrospectableVc,
+    },
+    source_asset::SourceAssetVc,
+};
+
+use super::{HtmlEscaped, HtmlStringEscaped};
+
+/// The path of the chunk graph page within the introspection source.
+pub(super) const CHUNK_GRAPH_PATH: &str = "chunk-graph";
+
+async fn introspectable_children(
+    parent: IntrospectableVc,
+) -> Result<impl Iterator<Item = IntrospectableVc> + Send> {
+    // The chunks of chunk groups are shown as part of the group.
+    let children = if ChunkGroupVc::resolve_from(parent).await?.is_some() {
+        Vec::new()
+    } else {
+        parent
+            .children()
+            .await?
+            .iter()
+            .map(|&(_, child)| child.resolve())
+            .try_join()
+            .await?
+    };
+    Ok(children.into_iter())
+}
+
+/// Finds all chunk groups in the introspection tree below the `roots`.
+async fn find_chunk_groups(roots: Vec<IntrospectableVc>) -> Result<Vec<ChunkGroupVc>> {
+    let introspectables = GraphTraversal::<SkipDuplicates<NonDeterministic<_>, _>>::visit(
+        roots,
+        introspectable_children,
+    )
+    .await
+    .completed()?
+    .into_inner();
+    let mut chunk_groups = Vec::new();
+    for introspectable in introspectables {
+        if let Some(chunk_group) = ChunkGroupVc::resolve_from(introspectable).await? {
+            if !chunk_groups.contains(&chunk_group) {
+                chunk_groups.push(chunk_group);
+            }
+        }
+    }
+    Ok(chunk_groups)
+}
+
+/// The link to the introspection page of an introspectable.
+async fn link(introspectable: IntrospectableVc) -> Result<String> {
+    let path = serde_json::to_string(&introspectable.resolve().await?)?;
+    Ok(format!(
+        "./{}",
+        HtmlStringEscaped(urlencoding::encode(&path))
+    ))
+}
+
+fn format_size(size: usize) -> String {
+    if size < 1024 {
+        format!("{size} B")
+    } else if size < 1024 * 1024 {
+        format!("{:.1} KiB", size as f64 / 1024.0)
+    } else {
+        format!("{:.1} MiB", size as f64 / (1024.0 * 1024.0))
+    }
+}
+
+async fn chunk_size(chunk: ChunkVc) -> Result<usize> {
+    Ok(match &*chunk.content().await? {
+        AssetContent::File(file) => match &*file.await? {
+            FileContent::Content(file) => file.content().len(),
+            FileContent::NotFound => 0,
+        },
+        AssetContent::Redirect { .. } => 0,
+    })
+}
+
+async fn chunk_item_row(info: &ChunkItemInfo) -> Result<String> {
+    let ident = info.ident.to_string().await?;
+    // Links to the source of the module, i.e. the file before any transforms.
+    let source = link(IntrospectableAssetVc::new(
+        SourceAssetVc::new(info.ident.path()).into(),
+    ))
+    .await?;
+    Ok(format!(
+        "<tr class=\"item\" data-ident=\"{filter}\"><td><a \
+         href=\"{source}\">{ident}</a></td><td>{size}</td></tr>",
+        filter = HtmlStringEscaped(ident.to_lowercase()),
+        ident = HtmlEscaped(&*ident),
+        size = format_size(info.size),
+    ))
+}
+
+async fn chunk_html(chunk: ChunkVc) -> Result<(String, usize)> {
+    let path = chunk.path().to_string().await?;
+    let size = chunk_size(chunk).await?;
+    let chunk_link = link(IntrospectableAssetVc::new(chunk.as_asset())).await?;
+    let mut items = match IntrospectableChunkVc::resolve_from(chunk).await? {
+        Some(chunk) => chunk.chunk_item_infos().await?.clone_value(),
+        None => Vec::new(),
+    };
+    items.sort_by(|a, b| b.size.cmp(&a.size));
+    let rows = items.iter().map(chunk_item_row).try_join().await?;
+    Ok((
+        format!(
+            "<details class=\"chunk\"><summary><a href=\"{chunk_link}\">{path}</a> ({size}, \
+             {count} chunk items)</summary><table><tr><th>Chunk \
+             item</th><th>Size</th></tr>{rows}</table></details>",
+            path = HtmlEscaped(&*path),
+            size = format_size(size),
+            count = items.len(),
+            rows = rows.concat(),
+        ),
+        size,
+    ))
+}
+
+async fn chunk_group_html(chunk_group: ChunkGroupVc) -> Result<String> {
+    let title = chunk_group.to_string().await?;
+    let group_link = link(chunk_group.as_introspectable()).await?;
+    let chunks = chunk_group
+        .chunks()
+        .await?
+        .iter()
+        .copied()
+        .map(chunk_html)
+        .try_join()
+        .await?;
+    let size: usize = chunks.iter().map(|(_, size)| size).sum();
+    let mut html = String::new();
+    write!(
+        html,
+        "<details class=\"group\" open><summary><h2 style=\"display: inline;\"><a \
+         href=\"{group_link}\">{title}</a></h2> ({size}, {count} chunks)</summary>",
+        title = HtmlEscaped(&*title),
+        size = format_size(size),
+        count = chunks.len(),
+    )?;
+    for (chunk, _) in chunks {
+        html.push_str(&chunk);
+    }
+    html.push_str("</details>");
+    Ok(html)
+}
+
+/// Renders a page that shows all chunk groups found in the introspection tree
+/// below the `roots`, with their chunks and the chunk items in these chunks.
+pub(super) async fn chunk_graph_html(roots: Vec<IntrospectableVc>) -> Result<String> {
+    let chunk_groups = find_chunk_groups(roots)
+        .await?
+        .into_iter()
+        .map(chunk_group_html)
+        .try_join()
+        .await?;
+    Ok(format!(
+        "<!DOCTYPE html>
+<html><head><title>Chunk graph</title>
+<style>
+  details.chunk {{ margin-left: 2em; }}
+  table {{ margin-left: 2em; border-collapse: collapse; }}
+  td, th {{ padding: 0 1em; text-align: left; }}
+</style>
+</head>
+<body>
+  <h1>Chunk graph</h1>
+  <p>{count} chunk groups. <input id=\"filter\" type=\"search\" placeholder=\"Filter chunk \
+         items\"></p>
+  {chunk_groups}
+  <script>
+    document.getElementById(\"filter\").addEventListener(\"input\", (event) => {{
+      const filter = event.target.value.toLowerCase();
+      for (const row of document.querySelectorAll(\"tr.item\")) {{
+        row.hidden = !row.dataset.ident.includes(filter);
+      }}
+      for (const chunk of document.querySelectorAll(\"details.chunk\")) {{
+        chunk.open = filter !== \"\" && chunk.querySelector(\"tr.item:not([hidden])\") !== null;
+      }}
+    }});
+  </script>
+</body>
+</html>",
+        count = chunk_groups.len(),
+        chunk_groups = chunk_groups.concat(),
+    ))
+}
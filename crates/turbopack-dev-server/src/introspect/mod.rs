@@ -1,3 +1,5 @@
+mod chunk_graph;
+
 use std::{borrow::Cow, collections::HashSet, fmt::Display};
 
 use anyhow::Result;
@@ -12,6 +14,7 @@ use turbopack_core::{
 };
 use turbopack_ecmascript::utils::FormatIter;
 
+use self::chunk_graph::{chunk_graph_html, CHUNK_GRAPH_PATH};
 use crate::source::{
     ContentSource, ContentSourceContentVc, ContentSourceData, ContentSourceResultVc,
     ContentSourceVc,
@@ -82,6 +85,10 @@ impl ContentSource for IntrospectionSource {
         path: &str,
         _data: turbo_tasks::Value<ContentSourceData>,
     ) -> Result<ContentSourceResultVc> {
+        if path == CHUNK_GRAPH_PATH {
+            let roots = self_vc.await?.roots.iter().copied().collect();
+            return Ok(html_result(chunk_graph_html(roots).await?));
+        }
         let introspectable = if path.is_empty() {
             let roots = &self_vc.await?.roots;
             if roots.len() == 1 {
@@ -153,6 +160,7 @@ impl ContentSource for IntrospectionSource {
             "<!DOCTYPE html>
 <html><head><title>{title}</title></head>
 <body>
+  {chunk_graph_link}
   <h3>{internal_ty}</h3>
   <h2>{ty}</h2>
   <h1>{title}</h1>
@@ -160,20 +168,29 @@ impl ContentSource for IntrospectionSource {
   <ul>{children}</ul>
 </body>
 </html>",
+            chunk_graph_link = if path.is_empty() {
+                format!("<p><a href=\"./{CHUNK_GRAPH_PATH}\">Chunk graph</a></p>")
+            } else {
+                String::new()
+            },
             title = HtmlEscaped(title),
             ty = HtmlEscaped(ty),
             children = FormatIter(|| children.iter())
         );
-        Ok(ContentSourceResultVc::exact(
-            ContentSourceContentVc::static_content(
-                AssetContent::File(
-                    FileContent::Content(File::from(html).with_content_type(mime::TEXT_HTML_UTF_8))
-                        .cell(),
-                )
-                .cell()
-                .into(),
+        Ok(html_result(html))
+    }
+}
+
+fn html_result(html: String) -> ContentSourceResultVc {
+    ContentSourceResultVc::exact(
+        ContentSourceContentVc::static_content(
+            AssetContent::File(
+                FileContent::Content(File::from(html).with_content_type(mime::TEXT_HTML_UTF_8))
+                    .cell(),
             )
+            .cell()
             .into(),
-        ))
-    }
+        )
+        .into(),
+    )
 }
@@ -3,18 +3,32 @@
 #![feature(array_chunks)]
 #![feature(iter_intersperse)]
 
+pub mod abort;
+pub mod cache_busting;
+pub mod csp;
+pub mod fetch_cache;
+mod font_preload;
 pub mod html;
+pub mod html_entry;
+pub mod html_transform;
 mod http;
 pub mod introspect;
 mod invalidation;
+pub mod metrics;
+mod module_preload;
+pub mod pause;
 pub mod source;
 pub mod update;
+pub mod websocket_proxy;
 
 use std::{
     future::Future,
     net::{SocketAddr, TcpListener},
     pin::Pin,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
 
@@ -22,13 +36,15 @@ use anyhow::{anyhow, Context, Result};
 use hyper::{
     server::{conn::AddrIncoming, Builder},
     service::{make_service_fn, service_fn},
-    Request, Response, Server,
+    Method, Request, Response, Server,
 };
+use tracing::Instrument;
 use turbo_tasks::{
     run_once_with_reason, trace::TraceRawVcs, util::FormatDuration, CollectiblesSource, RawVc,
     TransientInstance, TransientValue, TurboTasksApi,
 };
 use turbopack_core::{
+    compile_report,
     error::PrettyPrintError,
     issue::{IssueReporter, IssueReporterVc, IssueVc},
 };
@@ -63,6 +79,7 @@ pub struct DevServerBuilder {
     pub addr: SocketAddr,
     #[turbo_tasks(trace_ignore)]
     server: Builder<AddrIncoming>,
+    report_first_compile: bool,
 }
 
 #[derive(TraceRawVcs)]
@@ -110,27 +127,47 @@ impl DevServer {
             .context("not able to get bound address")?;
 
         let server = Server::from_tcp(listener).context("Not able to start server")?;
-        Ok(DevServerBuilder { addr, server })
+        Ok(DevServerBuilder {
+            addr,
+            server,
+            report_first_compile: false,
+        })
     }
 }
 
 impl DevServerBuilder {
+    /// Prints a breakdown of the time spent on the first compilation, i.e. the
+    /// compilation for the first request, once that request is handled.
+    pub fn with_first_compile_report(mut self) -> Self {
+        self.report_first_compile = true;
+        self
+    }
+
     pub fn serve(
         self,
         turbo_tasks: Arc<dyn TurboTasksApi>,
         source_provider: impl SourceProvider + Clone + Send + Sync,
         get_issue_reporter: Arc<dyn Fn() -> IssueReporterVc + Send + Sync>,
     ) -> DevServer {
+        let first_compile_pending = Arc::new(AtomicBool::new(self.report_first_compile));
         let make_svc = make_service_fn(move |_| {
             let tt = turbo_tasks.clone();
             let source_provider = source_provider.clone();
             let get_issue_reporter = get_issue_reporter.clone();
+            let first_compile_pending = first_compile_pending.clone();
             async move {
                 let handler = move |request: Request<hyper::Body>| {
                     let start = Instant::now();
                     let tt = tt.clone();
                     let get_issue_reporter = get_issue_reporter.clone();
                     let source_provider = source_provider.clone();
+                    let first_compile_pending = first_compile_pending.clone();
+                    let span = tracing::info_span!(
+                        "dev_server::request",
+                        method = %request.method(),
+                        path = %request.uri().path(),
+                        status = tracing::field::Empty,
+                    );
                     let future = async move {
                         let reason = ServerRequest {
                             method: request.method().clone(),
@@ -152,8 +189,8 @@ impl DevServerBuilder {
                                     return Ok(response);
                                 }
 
-                                println!("[404] {} (WebSocket)", path);
                                 if path == "/_next/webpack-hmr" {
+                                    println!("[404] {} (WebSocket)", path);
                                     // Special-case requests to webpack-hmr as these are made by
                                     // Next.js clients built
                                     // without turbopack, which may be making requests in
@@ -165,15 +202,64 @@ impl DevServerBuilder {
                                         "Make sure to reload/close any browser window which has \
                                          been opened without --turbo."
                                     );
+                                    return Ok(Response::builder()
+                                        .status(404)
+                                        .body(hyper::Body::empty())?);
                                 }
-
-                                return Ok(Response::builder()
-                                    .status(404)
-                                    .body(hyper::Body::empty())?);
+                                // Other upgrades are accepted by content
+                                // sources, see [websocket_proxy].
                             }
 
                             let uri = request.uri();
                             let path = uri.path().to_string();
+                            if path == update::metrics::METRICS_PATH {
+                                return Ok(Response::builder()
+                                    .header("content-type", "application/json")
+                                    .body(hyper::Body::from(serde_json::to_string(
+                                        &update::metrics::latest(),
+                                    )?))?);
+                            }
+                            if path == pause::PAUSE_PATH || path == pause::RESUME_PATH {
+                                let status = match *request.method() {
+                                    Method::POST if path == pause::PAUSE_PATH => pause::pause(),
+                                    Method::POST => pause::resume(),
+                                    _ => pause::status(),
+                                };
+                                return Ok(Response::builder()
+                                    .header("content-type", "application/json")
+                                    .body(hyper::Body::from(serde_json::to_string(&status)?))?);
+                            }
+                            if path == fetch_cache::FETCH_CACHE_PATH {
+                                let status = match *request.method() {
+                                    Method::POST | Method::DELETE => fetch_cache::invalidate(),
+                                    _ => fetch_cache::FetchCacheStatus {
+                                        generation: fetch_cache::generation(),
+                                    },
+                                };
+                                return Ok(Response::builder()
+                                    .header("content-type", "application/json")
+                                    .body(hyper::Body::from(serde_json::to_string(&status)?))?);
+                            }
+                            if path == metrics::METRICS_PATH {
+                                let metrics = metrics::latest();
+                                let response = if uri.query() == Some("format=prometheus") {
+                                    Response::builder()
+                                        .header("content-type", "text/plain; version=0.0.4")
+                                        .body(hyper::Body::from(metrics::to_prometheus(&metrics)))?
+                                } else {
+                                    Response::builder()
+                                        .header("content-type", "application/json")
+                                        .body(hyper::Body::from(serde_json::to_string(&metrics)?))?
+                                };
+                                return Ok(response);
+                            }
+                            // Only the request that triggers the first compilation reports it,
+                            // other requests might finish before it's done.
+                            let reports_first_compile =
+                                first_compile_pending.swap(false, Ordering::AcqRel);
+                            if reports_first_compile {
+                                compile_report::start();
+                            }
                             let source = source_provider.get_source();
                             handle_issues(source, &path, "get source", issue_reporter).await?;
                             let resolved_source = source.resolve_strongly_consistent().await?;
@@ -196,14 +282,24 @@ impl DevServerBuilder {
                                     duration = FormatDuration(elapsed)
                                 );
                             }
+                            if reports_first_compile {
+                                if let Some(report) = compile_report::finish() {
+                                    println!("{report}");
+                                }
+                            }
                             Ok(response)
                         })
                         .await
-                    };
+                    }
+                    .instrument(span.clone());
                     async move {
                         match future.await {
-                            Ok(r) => Ok::<_, hyper::http::Error>(r),
+                            Ok(r) => {
+                                span.record("status", r.status().as_u16());
+                                Ok::<_, hyper::http::Error>(r)
+                            }
                             Err(e) => {
+                                span.record("status", 500);
                                 println!(
                                     "[500] error ({}): {}",
                                     FormatDuration(start.elapsed()),
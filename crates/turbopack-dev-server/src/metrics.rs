@@ -0,0 +1,258 @@
+//! Bytes served and latency of the dev server, aggregated by the type of
+//! content source that handled a request.
+//!
+//! The metrics can be inspected at [METRICS_PATH], as JSON or, with a
+//! `?format=prometheus` query, in the Prometheus text exposition format.
+//! [crate::update::metrics] breaks down the latency of HMR updates further.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Write,
+    sync::Mutex,
+    time::Duration,
+};
+
+use serde::Serialize;
+
+/// The path of the endpoint which serves the [SourceMetrics].
+pub const METRICS_PATH: &str = "/turbopack-metrics";
+
+/// The path prefix the introspection source is mounted at.
+pub const INTROSPECTION_PREFIX: &str = "/__turbopack__/";
+
+/// The number of latencies kept per source type to compute percentiles.
+const MAX_SAMPLES: usize = 1000;
+
+/// The percentiles reported for latencies.
+const PERCENTILES: [f64; 3] = [0.5, 0.9, 0.99];
+
+/// The type of content source a request has been handled by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SourceType {
+    /// Assets served from the output, and requests that weren't found.
+    StaticAsset,
+    /// HTML pages and proxied responses, which are usually rendered on the
+    /// server.
+    Ssr,
+    /// HMR messages exchanged over the WebSocket connection.
+    Hmr,
+    /// The introspection pages below [INTROSPECTION_PREFIX].
+    Introspection,
+}
+
+impl SourceType {
+    const ALL: [SourceType; 4] = [
+        SourceType::StaticAsset,
+        SourceType::Ssr,
+        SourceType::Hmr,
+        SourceType::Introspection,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            SourceType::StaticAsset => "static_asset",
+            SourceType::Ssr => "ssr",
+            SourceType::Hmr => "hmr",
+            SourceType::Introspection => "introspection",
+        }
+    }
+}
+
+struct Samples {
+    requests: u64,
+    request_bytes: u64,
+    response_bytes: u64,
+    latencies: VecDeque<Duration>,
+}
+
+impl Samples {
+    const fn new() -> Self {
+        Samples {
+            requests: 0,
+            request_bytes: 0,
+            response_bytes: 0,
+            latencies: VecDeque::new(),
+        }
+    }
+}
+
+static SAMPLES: Mutex<[Samples; 4]> = Mutex::new([
+    Samples::new(),
+    Samples::new(),
+    Samples::new(),
+    Samples::new(),
+]);
+
+fn with_samples(source_type: SourceType, f: impl FnOnce(&mut Samples)) {
+    f(&mut SAMPLES.lock().unwrap()[source_type as usize])
+}
+
+/// Records that a request has been handled in `latency`.
+pub(crate) fn record_latency(source_type: SourceType, latency: Duration) {
+    with_samples(source_type, |samples| {
+        samples.requests += 1;
+        if samples.latencies.len() == MAX_SAMPLES {
+            samples.latencies.pop_front();
+        }
+        samples.latencies.push_back(latency);
+    })
+}
+
+/// Records the bytes received with a request.
+pub(crate) fn record_request_bytes(source_type: SourceType, bytes: usize) {
+    with_samples(source_type, |samples| samples.request_bytes += bytes as u64)
+}
+
+/// Records the bytes sent with a response. Streamed responses record every
+/// chunk separately.
+pub(crate) fn record_response_bytes(source_type: SourceType, bytes: usize) {
+    with_samples(source_type, |samples| {
+        samples.response_bytes += bytes as u64
+    })
+}
+
+fn millis<S: serde::Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+}
+
+/// The latency percentile `p` (between 0 and 1) of the `sorted` latencies.
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    sorted[((sorted.len() - 1) as f64 * p).round() as usize]
+}
+
+/// The metrics of a [SourceType]. Latencies are serialized as milliseconds
+/// and cover the latest requests only.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceMetrics {
+    pub requests: u64,
+    pub request_bytes: u64,
+    pub response_bytes: u64,
+    #[serde(serialize_with = "millis")]
+    pub p50: Duration,
+    #[serde(serialize_with = "millis")]
+    pub p90: Duration,
+    #[serde(serialize_with = "millis")]
+    pub p99: Duration,
+}
+
+/// The current metrics, by source type.
+pub fn latest() -> BTreeMap<SourceType, SourceMetrics> {
+    let samples = SAMPLES.lock().unwrap();
+    SourceType::ALL
+        .into_iter()
+        .map(|source_type| {
+            let samples = &samples[source_type as usize];
+            let mut latencies = samples.latencies.iter().copied().collect::<Vec<_>>();
+            latencies.sort();
+            let [p50, p90, p99] = PERCENTILES.map(|p| percentile(&latencies, p));
+            (
+                source_type,
+                SourceMetrics {
+                    requests: samples.requests,
+                    request_bytes: samples.request_bytes,
+                    response_bytes: samples.response_bytes,
+                    p50,
+                    p90,
+                    p99,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Formats `metrics` in the Prometheus text exposition format.
+pub fn to_prometheus(metrics: &BTreeMap<SourceType, SourceMetrics>) -> String {
+    let mut text = String::new();
+    let counters: [(&str, &str, fn(&SourceMetrics) -> u64); 3] = [
+        (
+            "turbopack_dev_server_requests_total",
+            "Requests handled.",
+            |metrics| metrics.requests,
+        ),
+        (
+            "turbopack_dev_server_request_bytes_total",
+            "Bytes received with requests.",
+            |metrics| metrics.request_bytes,
+        ),
+        (
+            "turbopack_dev_server_response_bytes_total",
+            "Bytes sent with responses.",
+            |metrics| metrics.response_bytes,
+        ),
+    ];
+    for (name, help, value) in counters {
+        writeln!(text, "# HELP {name} {help}").unwrap();
+        writeln!(text, "# TYPE {name} counter").unwrap();
+        for (source_type, metrics) in metrics {
+            writeln!(
+                text,
+                "{name}{{source=\"{}\"}} {}",
+                source_type.as_str(),
+                value(metrics)
+            )
+            .unwrap();
+        }
+    }
+    let name = "turbopack_dev_server_latency_seconds";
+    writeln!(text, "# HELP {name} Latency of the latest requests.").unwrap();
+    writeln!(text, "# TYPE {name} summary").unwrap();
+    for (source_type, metrics) in metrics {
+        let source = source_type.as_str();
+        let latencies = [metrics.p50, metrics.p90, metrics.p99];
+        for (quantile, latency) in PERCENTILES.into_iter().zip(latencies) {
+            writeln!(
+                text,
+                "{name}{{source=\"{source}\",quantile=\"{quantile}\"}} {}",
+                latency.as_secs_f64()
+            )
+            .unwrap();
+        }
+        writeln!(
+            text,
+            "{name}_count{{source=\"{source}\"}} {}",
+            metrics.requests
+        )
+        .unwrap();
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, time::Duration};
+
+    use super::{percentile, to_prometheus, SourceMetrics, SourceType};
+
+    #[test]
+    fn percentiles() {
+        let latencies = (1..=100).map(Duration::from_millis).collect::<Vec<_>>();
+        assert_eq!(percentile(&latencies, 0.5), Duration::from_millis(51));
+        assert_eq!(percentile(&latencies, 0.99), Duration::from_millis(99));
+        assert_eq!(percentile(&[], 0.5), Duration::ZERO);
+    }
+
+    #[test]
+    fn prometheus_format() {
+        let metrics = BTreeMap::from([(
+            SourceType::Ssr,
+            SourceMetrics {
+                requests: 2,
+                request_bytes: 10,
+                response_bytes: 300,
+                p50: Duration::from_millis(500),
+                p90: Duration::from_secs(1),
+                p99: Duration::from_secs(2),
+            },
+        )]);
+        let text = to_prometheus(&metrics);
+        assert!(text.contains("turbopack_dev_server_response_bytes_total{source=\"ssr\"} 300\n"));
+        assert!(text
+            .contains("turbopack_dev_server_latency_seconds{source=\"ssr\",quantile=\"0.9\"} 1\n"));
+        assert!(text.contains("turbopack_dev_server_latency_seconds_count{source=\"ssr\"} 2\n"));
+    }
+}
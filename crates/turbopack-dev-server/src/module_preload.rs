@@ -0,0 +1,108 @@
+//! Preload and prefetch hints for the JS chunks of a page.
+//!
+//! The chunks of a chunk group form the closure of the chunks that are loaded
+//! synchronously when its entry is evaluated. Chunks of async imports are not
+//! part of it. Announcing all of them in the `<head>` lets the browser fetch
+//! them in parallel, instead of discovering them one after another while
+//! scripts are executed.
+//!
+//! The chunks of async imports which are prefetched eagerly are announced with
+//! `<link rel="prefetch">` instead, since they aren't needed to render the
+//! page.
+
+use anyhow::Result;
+use indexmap::IndexSet;
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::{
+    asset::Asset,
+    chunk::{Chunk, ChunkReferenceVc, ChunkVc},
+    reference::AssetReference,
+};
+
+/// The paths of the chunks of the dynamic imports in `chunks` which are
+/// prefetched eagerly, relative to `context_path` and starting with `/`. These
+/// are the manifest chunk of each import and the chunks it loads.
+pub(crate) async fn prefetched_chunk_paths(
+    chunks: &[ChunkVc],
+    context_path: &FileSystemPath,
+) -> Result<Vec<String>> {
+    let mut paths = IndexSet::new();
+    for chunk in chunks {
+        for reference in chunk.references().await?.iter() {
+            let Some(reference) = ChunkReferenceVc::resolve_from(*reference).await? else {
+                continue;
+            };
+            if !*reference.is_prefetched().await? {
+                continue;
+            }
+            for manifest_chunk in reference.resolve_reference().primary_assets().await?.iter() {
+                let Some(manifest_chunk) = ChunkVc::resolve_from(*manifest_chunk).await? else {
+                    continue;
+                };
+                let mut prefetched = vec![manifest_chunk];
+                for reference in manifest_chunk.references().await?.iter() {
+                    if ChunkReferenceVc::resolve_from(*reference).await?.is_none() {
+                        continue;
+                    }
+                    for chunk in reference.resolve_reference().primary_assets().await?.iter() {
+                        prefetched.extend(ChunkVc::resolve_from(*chunk).await?);
+                    }
+                }
+                for chunk in prefetched {
+                    if let Some(relative_path) = context_path.get_path_to(&*chunk.path().await?) {
+                        paths.insert(format!("/{relative_path}"));
+                    }
+                }
+            }
+        }
+    }
+    Ok(paths.into_iter().collect())
+}
+
+/// The prefetch tag for the chunk at `href`, which the browser fetches with a
+/// low priority once the page is loaded. `attributes` are added to the tag as
+/// they are, e.g. a CSP nonce.
+pub(crate) fn chunk_prefetch_tag(href: &str, attributes: &str) -> String {
+    format!("<link rel=\"prefetch\" href=\"{href}\"{attributes}>")
+}
+
+/// The preload tag for the JS chunk at `href`. Chunks that are loaded as
+/// `<script type="module">` need to be preloaded as modules, so the fetched
+/// response can be reused. `attributes` are added to the tag as they are, e.g.
+/// a CSP nonce.
+pub(crate) fn chunk_preload_tag(href: &str, module: bool, attributes: &str) -> Option<String> {
+    if !href.ends_with(".js") {
+        return None;
+    }
+    Some(if module {
+        format!("<link rel=\"modulepreload\" href=\"{href}\"{attributes}>")
+    } else {
+        format!("<link rel=\"preload\" href=\"{href}\" as=\"script\"{attributes}>")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{chunk_prefetch_tag, chunk_preload_tag};
+
+    #[test]
+    fn preload_tags() {
+        assert_eq!(
+            chunk_preload_tag("/_chunks/a.js", true, "").as_deref(),
+            Some("<link rel=\"modulepreload\" href=\"/_chunks/a.js\">")
+        );
+        assert_eq!(
+            chunk_preload_tag("/_chunks/a.js", false, " nonce=\"n\"").as_deref(),
+            Some("<link rel=\"preload\" href=\"/_chunks/a.js\" as=\"script\" nonce=\"n\">")
+        );
+        assert_eq!(chunk_preload_tag("/_chunks/a.css", false, ""), None);
+    }
+
+    #[test]
+    fn prefetch_tags() {
+        assert_eq!(
+            chunk_prefetch_tag("/_chunks/b.js", " nonce=\"n\""),
+            "<link rel=\"prefetch\" href=\"/_chunks/b.js\" nonce=\"n\">"
+        );
+    }
+}
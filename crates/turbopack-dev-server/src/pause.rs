@@ -0,0 +1,48 @@
+//! Pausing background compilation, e.g. while a heavy test suite runs
+//! locally, so it doesn't compete with it for CPU.
+//!
+//! A `POST` request to [PAUSE_PATH] pauses the file watchers: changed files
+//! are queued instead of being recompiled. A `POST` request to [RESUME_PATH]
+//! applies the queued changes. Requests are still served meanwhile, but they
+//! don't see the queued changes: files that were read before pausing keep
+//! their previous content until compilation is resumed.
+
+use serde::Serialize;
+use turbo_tasks_fs::{is_watching_paused, pause_watching, resume_watching};
+
+/// The path of the endpoint which pauses background compilation.
+pub const PAUSE_PATH: &str = "/turbopack-pause";
+
+/// The path of the endpoint which resumes background compilation.
+pub const RESUME_PATH: &str = "/turbopack-resume";
+
+/// The response of the [PAUSE_PATH] and [RESUME_PATH] endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PauseStatus {
+    pub paused: bool,
+}
+
+/// Pauses background compilation until [resume] is called.
+pub fn pause() -> PauseStatus {
+    if !is_watching_paused() {
+        pause_watching();
+        println!("Paused compilation of changed files");
+    }
+    status()
+}
+
+/// Resumes background compilation and applies the changes made while it was
+/// paused.
+pub fn resume() -> PauseStatus {
+    if is_watching_paused() {
+        resume_watching();
+        println!("Resumed compilation of changed files");
+    }
+    status()
+}
+
+pub fn status() -> PauseStatus {
+    PauseStatus {
+        paused: is_watching_paused(),
+    }
+}
@@ -38,6 +38,11 @@ pub struct ProxyResult {
     pub headers: Vec<(String, String)>,
     /// The body to return.
     pub body: Body,
+    /// The id of the [WebSocketProxy] the connection is relayed to, when the
+    /// request is upgraded to a WebSocket with status 101.
+    ///
+    /// [WebSocketProxy]: crate::websocket_proxy::WebSocketProxy
+    pub upgrade: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Error)]
@@ -244,6 +249,9 @@ pub struct ContentSourceData {
     pub body: Option<BodyVc>,
     /// See [ContentSourceDataVary::cache_buster].
     pub cache_buster: u64,
+    /// The id of the request, if requested. See
+    /// [request::SourceRequest::request_id].
+    pub request_id: Option<String>,
 }
 
 type Chunk = Result<Bytes, BodyError>;
@@ -370,6 +378,10 @@ pub struct ContentSourceDataVary {
     /// This value will be different on every request, which ensures the
     /// content is never cached.
     pub cache_buster: bool,
+    /// When true, the id of the request is added to the [ContentSourceData].
+    /// Like the `cache_buster`, it's different on every request, so the
+    /// content is never cached.
+    pub request_id: bool,
     pub placeholder_for_future_extensions: (),
 }
 
@@ -386,12 +398,14 @@ impl ContentSourceDataVary {
             raw_headers,
             body,
             cache_buster,
+            request_id,
             placeholder_for_future_extensions: _,
         } = self;
         *method = *method || other.method;
         *url = *url || other.url;
         *body = *body || other.body;
         *cache_buster = *cache_buster || other.cache_buster;
+        *request_id = *request_id || other.request_id;
         *raw_query = *raw_query || other.raw_query;
         *raw_headers = *raw_headers || other.raw_headers;
         ContentSourceDataFilter::extend_options(query, &other.query);
@@ -411,6 +425,7 @@ impl ContentSourceDataVary {
             raw_headers,
             body,
             cache_buster,
+            request_id,
             placeholder_for_future_extensions: _,
         } = self;
         if other.method && !method {
@@ -431,6 +446,9 @@ impl ContentSourceDataVary {
         if other.cache_buster && !cache_buster {
             return false;
         }
+        if other.request_id && !request_id {
+            return false;
+        }
         if !ContentSourceDataFilter::fulfills(query, &other.query) {
             return false;
         }
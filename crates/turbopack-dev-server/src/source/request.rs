@@ -1,7 +1,11 @@
 use hyper::{HeaderMap, Uri};
+use rand::{distributions::Alphanumeric, Rng};
 
 use super::Body;
 
+/// The response header containing the [SourceRequest::request_id].
+pub const REQUEST_ID_HEADER: &str = "x-turbopack-request-id";
+
 /// A request to a content source.
 #[derive(Debug, Clone)]
 pub struct SourceRequest {
@@ -13,4 +17,17 @@ pub struct SourceRequest {
     pub headers: HeaderMap<hyper::header::HeaderValue>,
     /// The body to send.
     pub body: Body,
+    /// Identifies the request in logs and issues caused by it. It's generated
+    /// by the dev server and echoed in the [REQUEST_ID_HEADER] of the
+    /// response.
+    pub request_id: String,
+}
+
+/// Creates a random id for a single request.
+pub(crate) fn generate_request_id() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(16)
+        .map(char::from)
+        .collect()
 }
@@ -171,5 +171,8 @@ async fn request_to_data(
     if vary.cache_buster {
         data.cache_buster = CACHE_BUSTER.fetch_add(1, Ordering::SeqCst);
     }
+    if vary.request_id {
+        data.request_id = Some(request.request_id.clone());
+    }
     Ok(data)
 }
@@ -0,0 +1,166 @@
+//! Latency of the updates sent to HMR clients, from the file change that
+//! caused an update to the client acknowledging it.
+//!
+//! The latest updates are logged when they are slow and can be inspected at
+//! [METRICS_PATH].
+
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use serde::{Serialize, Serializer};
+use turbo_tasks::util::FormatDuration;
+
+/// The path of the endpoint which serves the latest [HmrLatency]s as JSON.
+pub const METRICS_PATH: &str = "/turbopack-hmr-metrics";
+
+/// The number of updates kept for the metrics endpoint.
+const MAX_SAMPLES: usize = 100;
+
+/// Updates slower than this are always logged.
+const SLOW_UPDATE: Duration = Duration::from_secs(1);
+
+static NEXT_UPDATE_ID: AtomicU64 = AtomicU64::new(1);
+
+static SAMPLES: Mutex<VecDeque<HmrLatency>> = Mutex::new(VecDeque::new());
+
+/// When the phases of computing an update were done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpdateTimings {
+    /// When the computation of the update started, i.e. when the
+    /// invalidation reached it.
+    pub started: Instant,
+    /// When the content has been compiled.
+    pub compiled: Instant,
+    /// When the code of the update has been generated.
+    pub generated: Instant,
+}
+
+fn millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+}
+
+fn optional_millis<S: Serializer>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match duration {
+        Some(duration) => millis(duration, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// The time an update spent in each phase. Durations are serialized as
+/// milliseconds.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HmrLatency {
+    pub update_id: u64,
+    pub resource: String,
+    /// From the file change to the start of the computation of the update.
+    /// `None` when the update wasn't caused by a file change.
+    #[serde(serialize_with = "optional_millis")]
+    pub invalidation: Option<Duration>,
+    #[serde(serialize_with = "millis")]
+    pub compile: Duration,
+    #[serde(serialize_with = "millis")]
+    pub codegen: Duration,
+    #[serde(serialize_with = "millis")]
+    pub send: Duration,
+    /// From sending the update to the client acknowledging that it has been
+    /// applied. `None` until the acknowledgement arrives.
+    #[serde(serialize_with = "optional_millis")]
+    pub ack: Option<Duration>,
+    #[serde(skip)]
+    sent: Instant,
+}
+
+impl HmrLatency {
+    /// The time until the update was sent.
+    pub fn total(&self) -> Duration {
+        self.invalidation.unwrap_or_default() + self.compile + self.codegen + self.send
+    }
+}
+
+impl Display for HmrLatency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[HMR] {} updated in {} (",
+            self.resource,
+            FormatDuration(self.total())
+        )?;
+        if let Some(invalidation) = self.invalidation {
+            write!(f, "invalidation {}, ", FormatDuration(invalidation))?;
+        }
+        write!(
+            f,
+            "compile {}, codegen {}, send {})",
+            FormatDuration(self.compile),
+            FormatDuration(self.codegen),
+            FormatDuration(self.send)
+        )
+    }
+}
+
+/// Returns the id the client acknowledges an update with.
+pub(super) fn next_update_id() -> u64 {
+    NEXT_UPDATE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Records that an update has been sent. `change` is the file change that
+/// caused it, if any.
+pub(super) fn record_sent(
+    update_id: u64,
+    resource: &str,
+    change: Option<Instant>,
+    timings: &UpdateTimings,
+) {
+    let sent = Instant::now();
+    let latency = HmrLatency {
+        update_id,
+        resource: resource.to_string(),
+        invalidation: change.map(|change| timings.started.saturating_duration_since(change)),
+        compile: timings.compiled.saturating_duration_since(timings.started),
+        codegen: timings
+            .generated
+            .saturating_duration_since(timings.compiled),
+        send: sent.saturating_duration_since(timings.generated),
+        ack: None,
+        sent,
+    };
+    crate::metrics::record_latency(crate::metrics::SourceType::Hmr, latency.total());
+    if cfg!(feature = "log_request_stats") || latency.total() > SLOW_UPDATE {
+        println!("{latency}");
+    }
+    let mut samples = SAMPLES.lock().unwrap();
+    if samples.len() == MAX_SAMPLES {
+        samples.pop_front();
+    }
+    samples.push_back(latency);
+}
+
+/// Records that the client has applied the update with `update_id`.
+pub(super) fn record_ack(update_id: u64) {
+    let mut samples = SAMPLES.lock().unwrap();
+    if let Some(latency) = samples
+        .iter_mut()
+        .rev()
+        .find(|latency| latency.update_id == update_id)
+    {
+        if latency.ack.is_none() {
+            latency.ack = Some(latency.sent.elapsed());
+        }
+    }
+}
+
+/// The latest updates, oldest first.
+pub fn latest() -> Vec<HmrLatency> {
+    SAMPLES.lock().unwrap().iter().cloned().collect()
+}
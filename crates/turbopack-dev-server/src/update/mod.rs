@@ -1,3 +1,4 @@
+pub mod metrics;
 pub mod protocol;
 pub mod server;
 pub mod stream;
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use turbopack_cli_utils::issue::{format_issue, LogOptions};
 use turbopack_core::{
-    issue::{IssueSeverity, PlainIssue},
+    issue::{IssueSeverity, PlainIssue, PlainIssueSource},
     source_pos::SourcePos,
 };
 
@@ -21,11 +21,40 @@ pub enum ClientMessage {
     Subscribe {
         #[serde(flatten)]
         resource: ResourceIdentifier,
+        /// The version the client is at when it resubscribes to the resource,
+        /// e.g. after reconnecting to a restarted server.
+        #[serde(default)]
+        resume: Option<ResumeState>,
     },
     Unsubscribe {
         #[serde(flatten)]
         resource: ResourceIdentifier,
     },
+    /// The client has applied the update with the `update_id` of a
+    /// [`ClientUpdateInstruction`].
+    Ack {
+        #[serde(rename = "updateId")]
+        update_id: u64,
+    },
+}
+
+/// The last [`VersionSnapshot`] a client received for a resource.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumeState {
+    pub session_id: String,
+    pub version: Value,
+}
+
+/// The version of a resource a client is at after applying an instruction.
+/// Clients keep the latest snapshot of every resource and send it back as a
+/// [`ResumeState`] when resubscribing.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionSnapshot<'a> {
+    /// Identifies the server process the version was computed by.
+    pub session_id: &'a str,
+    pub version: &'a Value,
 }
 
 #[derive(Serialize)]
@@ -35,6 +64,12 @@ pub struct ClientUpdateInstruction<'a> {
     #[serde(flatten)]
     pub ty: ClientUpdateInstructionType<'a>,
     pub issues: &'a [Issue<'a>],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<VersionSnapshot<'a>>,
+    /// Identifies updates the client should acknowledge once they have been
+    /// applied, see [`ClientMessage::Ack`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_id: Option<u64>,
 }
 
 pub const EMPTY_ISSUES: &[Issue<'static>] = &[];
@@ -49,6 +84,8 @@ impl<'a> ClientUpdateInstruction<'a> {
             resource,
             ty,
             issues,
+            snapshot: None,
+            update_id: None,
         }
     }
 
@@ -79,10 +116,17 @@ impl<'a> ClientUpdateInstruction<'a> {
     }
 
     pub fn with_issues(self, issues: &'a [Issue<'a>]) -> Self {
+        Self { issues, ..self }
+    }
+
+    pub fn with_snapshot(self, snapshot: Option<VersionSnapshot<'a>>) -> Self {
+        Self { snapshot, ..self }
+    }
+
+    pub fn with_update_id(self, update_id: u64) -> Self {
         Self {
-            resource: self.resource,
-            ty: self.ty,
-            issues,
+            update_id: Some(update_id),
+            ..self
         }
     }
 }
@@ -125,22 +169,58 @@ pub struct Issue<'a> {
     pub description: &'a str,
     pub detail: &'a str,
     pub documentation_link: &'a str,
+    pub code: &'a str,
+    pub documentation_slug: &'a str,
 
     pub source: Option<IssueSource<'a>>,
+    pub suggestions: Vec<IssueSuggestion<'a>>,
     pub sub_issues: Vec<Issue<'a>>,
 
     pub formatted: String,
 }
 
-impl<'a> From<&'a PlainIssue> for Issue<'a> {
-    fn from(plain: &'a PlainIssue) -> Self {
-        let source = plain.source.as_deref().map(|source| IssueSource {
+#[derive(Serialize)]
+pub struct IssueSuggestion<'a> {
+    pub title: &'a str,
+    pub edits: Vec<IssueSuggestionEdit<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct IssueSuggestionEdit<'a> {
+    pub source: IssueSource<'a>,
+    pub replacement: &'a str,
+}
+
+impl<'a> From<&'a PlainIssueSource> for IssueSource<'a> {
+    fn from(source: &'a PlainIssueSource) -> Self {
+        IssueSource {
             asset: Asset {
                 path: &source.asset.ident,
             },
             start: source.start,
             end: source.end,
-        });
+        }
+    }
+}
+
+impl<'a> From<&'a PlainIssue> for Issue<'a> {
+    fn from(plain: &'a PlainIssue) -> Self {
+        let source = plain.source.as_deref().map(IssueSource::from);
+        let suggestions = plain
+            .suggestions
+            .iter()
+            .map(|suggestion| IssueSuggestion {
+                title: &suggestion.title,
+                edits: suggestion
+                    .edits
+                    .iter()
+                    .map(|edit| IssueSuggestionEdit {
+                        source: edit.source.deref().into(),
+                        replacement: &edit.replacement,
+                    })
+                    .collect(),
+            })
+            .collect();
 
         Issue {
             severity: plain.severity,
@@ -150,7 +230,10 @@ impl<'a> From<&'a PlainIssue> for Issue<'a> {
             description: &plain.description,
             documentation_link: &plain.documentation_link,
             detail: &plain.detail,
+            code: &plain.code,
+            documentation_slug: &plain.documentation_slug,
             source,
+            suggestions,
             sub_issues: plain.sub_issues.iter().map(|p| p.deref().into()).collect(),
             // TODO(WEB-691) formatting the issue should be handled by the error overlay.
             // The browser could handle error formatting in a better way than the text only
@@ -1,29 +1,51 @@
 use std::{
+    collections::HashMap,
     pin::Pin,
     task::{Context, Poll},
+    time::Instant,
 };
 
 use anyhow::{Context as _, Error, Result};
 use futures::{prelude::*, ready, stream::FusedStream, SinkExt};
 use hyper::{upgrade::Upgraded, HeaderMap, Uri};
 use hyper_tungstenite::{tungstenite::Message, HyperWebsocket, WebSocketStream};
+use once_cell::sync::Lazy;
 use pin_project_lite::pin_project;
+use rand::{distributions::Alphanumeric, Rng};
 use tokio::select;
 use tokio_stream::StreamMap;
 use turbo_tasks::{TransientInstance, TurboTasksApi};
-use turbo_tasks_fs::json::parse_json_with_source_context;
+use turbo_tasks_fs::{json::parse_json_with_source_context, last_watch_event};
 use turbopack_core::{issue::IssueReporterVc, version::Update};
 
 use super::{
-    protocol::{ClientMessage, ClientUpdateInstruction, Issue, ResourceIdentifier},
+    metrics,
+    protocol::{
+        ClientMessage, ClientUpdateInstruction, Issue, ResourceIdentifier, VersionSnapshot,
+    },
     stream::UpdateStream,
 };
 use crate::{
-    source::{request::SourceRequest, resolve::resolve_source_request, Body},
+    metrics::{record_request_bytes, record_response_bytes, SourceType},
+    source::{
+        request::{generate_request_id, SourceRequest},
+        resolve::resolve_source_request,
+        Body,
+    },
     update::stream::UpdateStreamItem,
     SourceProvider,
 };
 
+/// Identifies the current server process. Clients can tell from the session
+/// id of their [`VersionSnapshot`]s that the server was restarted.
+static SESSION_ID: Lazy<String> = Lazy::new(|| {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(16)
+        .map(char::from)
+        .collect()
+});
+
 /// A server that listens for updates and sends them to connected clients.
 pub(crate) struct UpdateServer<P: SourceProvider> {
     source_provider: P,
@@ -53,12 +75,15 @@ impl<P: SourceProvider + Clone + Send + Sync> UpdateServer<P> {
         let mut client: UpdateClient = ws.await?.into();
 
         let mut streams = StreamMap::new();
+        // When the latest update of each resource started to be computed, so
+        // file changes are only attributed to the first update after them.
+        let mut last_started = HashMap::new();
 
         loop {
             select! {
                 message = client.try_next() => {
                     match message? {
-                        Some(ClientMessage::Subscribe { resource }) => {
+                        Some(ClientMessage::Subscribe { resource, resume }) => {
                             let get_content = {
                                 let source_provider = self.source_provider.clone();
                                 let request = resource_to_request(&resource)?;
@@ -72,12 +97,24 @@ impl<P: SourceProvider + Clone + Send + Sync> UpdateServer<P> {
                                     )
                                 }
                             };
-                            let stream = UpdateStream::new(TransientInstance::new(Box::new(get_content))).await?;
+                            // Versions are restored from their state, so a client can resume
+                            // regardless of whether the session is still the same.
+                            let resume = resume.map(|resume| resume.version);
+                            let stream = UpdateStream::new(
+                                TransientInstance::new(Box::new(get_content)),
+                                resume,
+                            )
+                            .await?;
+                            last_started.insert(resource.clone(), Instant::now());
                             streams.insert(resource, stream);
                         }
                         Some(ClientMessage::Unsubscribe { resource }) => {
+                            last_started.remove(&resource);
                             streams.remove(&resource);
                         }
+                        Some(ClientMessage::Ack { update_id }) => {
+                            metrics::record_ack(update_id);
+                        }
                         None => {
                             // WebSocket was closed, stop sending updates
                             break;
@@ -85,7 +122,14 @@ impl<P: SourceProvider + Clone + Send + Sync> UpdateServer<P> {
                     }
                 }
                 Some((resource, update)) = streams.next() => {
-                    Self::send_update(&mut client, &mut streams, resource, &update).await?;
+                    Self::send_update(
+                        &mut client,
+                        &mut streams,
+                        &mut last_started,
+                        resource,
+                        &update,
+                    )
+                    .await?;
                 }
                 else => break
             }
@@ -97,6 +141,7 @@ impl<P: SourceProvider + Clone + Send + Sync> UpdateServer<P> {
     async fn send_update(
         client: &mut UpdateClient,
         streams: &mut StreamMap<ResourceIdentifier, UpdateStream>,
+        last_started: &mut HashMap<ResourceIdentifier, Instant>,
         resource: ResourceIdentifier,
         item: &UpdateStreamItem,
     ) -> Result<()> {
@@ -109,30 +154,61 @@ impl<P: SourceProvider + Clone + Send + Sync> UpdateServer<P> {
                     .send(ClientUpdateInstruction::not_found(&resource))
                     .await?;
             }
-            UpdateStreamItem::Found { update, issues } => {
+            UpdateStreamItem::Found {
+                update,
+                issues,
+                version,
+                timings,
+            } => {
+                let change = last_watch_event().filter(|change| {
+                    *change <= timings.started
+                        && last_started
+                            .get(&resource)
+                            .map_or(true, |last_started| change > last_started)
+                });
+                last_started.insert(resource.clone(), timings.started);
                 let issues = issues
                     .iter()
                     .map(|p| (&**p).into())
                     .collect::<Vec<Issue<'_>>>();
+                let snapshot = version.as_ref().map(|version| VersionSnapshot {
+                    session_id: &SESSION_ID,
+                    version,
+                });
                 match &**update {
                     Update::Partial(partial) => {
                         let partial_instruction = partial.instruction.await?;
+                        let update_id = metrics::next_update_id();
                         client
-                            .send(ClientUpdateInstruction::partial(
-                                &resource,
-                                &partial_instruction,
-                                &issues,
-                            ))
+                            .send(
+                                ClientUpdateInstruction::partial(
+                                    &resource,
+                                    &partial_instruction,
+                                    &issues,
+                                )
+                                .with_snapshot(snapshot)
+                                .with_update_id(update_id),
+                            )
                             .await?;
+                        metrics::record_sent(update_id, &resource.path, change, timings);
                     }
                     Update::Total(_total) => {
+                        let update_id = metrics::next_update_id();
                         client
-                            .send(ClientUpdateInstruction::restart(&resource, &issues))
+                            .send(
+                                ClientUpdateInstruction::restart(&resource, &issues)
+                                    .with_snapshot(snapshot)
+                                    .with_update_id(update_id),
+                            )
                             .await?;
+                        metrics::record_sent(update_id, &resource.path, change, timings);
                     }
                     Update::None => {
                         client
-                            .send(ClientUpdateInstruction::issues(&resource, &issues))
+                            .send(
+                                ClientUpdateInstruction::issues(&resource, &issues)
+                                    .with_snapshot(snapshot),
+                            )
                             .await?;
                     }
                 }
@@ -160,6 +236,7 @@ fn resource_to_request(resource: &ResourceIdentifier) -> Result<SourceRequest> {
         headers,
         method: "GET".to_string(),
         body: Body::new(vec![]),
+        request_id: generate_request_id(),
     })
 }
 
@@ -183,7 +260,10 @@ impl Stream for UpdateClient {
         let item = ready!(this.ws.poll_next(cx));
 
         let msg = match item {
-            Some(Ok(Message::Text(msg))) => msg,
+            Some(Ok(Message::Text(msg))) => {
+                record_request_bytes(SourceType::Hmr, msg.len());
+                msg
+            }
             Some(Err(err)) => {
                 *this.ended = true;
 
@@ -230,7 +310,9 @@ impl<'a> Sink<ClientUpdateInstruction<'a>> for UpdateClient {
         self: Pin<&mut Self>,
         item: ClientUpdateInstruction<'a>,
     ) -> std::result::Result<(), Self::Error> {
-        let msg = Message::text(serde_json::to_string(&item)?);
+        let msg = serde_json::to_string(&item)?;
+        record_response_bytes(SourceType::Hmr, msg.len());
+        let msg = Message::text(msg);
 
         self.project()
             .ws
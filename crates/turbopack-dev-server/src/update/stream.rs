@@ -1,18 +1,23 @@
-use std::pin::Pin;
+use std::{pin::Pin, time::Instant};
 
 use anyhow::{bail, Result};
 use futures::{prelude::*, Stream};
+use serde_json::Value;
 use tokio::sync::mpsc::Sender;
 use tokio_stream::wrappers::ReceiverStream;
-use turbo_tasks::{CollectiblesSource, IntoTraitRef, State, TraitRef, TransientInstance};
+use turbo_tasks::{
+    primitives::{JsonValueReadRef, JsonValueVc},
+    CollectiblesSource, IntoTraitRef, State, TraitRef, TransientInstance,
+};
 use turbopack_core::{
     issue::{IssueVc, PlainIssueReadRef},
     version::{
-        NotFoundVersionVc, PartialUpdate, TotalUpdate, Update, UpdateReadRef, VersionVc,
+        NotFoundVersionVc, PartialUpdate, TotalUpdate, Update, UpdateReadRef, Version, VersionVc,
         VersionedContent,
     },
 };
 
+use super::metrics::UpdateTimings;
 use crate::source::resolve::{ResolveSourceRequestResult, ResolveSourceRequestResultVc};
 
 type GetContentFn = Box<dyn Fn() -> ResolveSourceRequestResultVc + Send + Sync>;
@@ -38,11 +43,13 @@ async fn get_update_stream_item(
     from: VersionStateVc,
     get_content: TransientInstance<GetContentFn>,
 ) -> Result<UpdateStreamItemVc> {
+    let started = Instant::now();
     let content = get_content();
 
     match &*content.await? {
         ResolveSourceRequestResult::Static(static_content_vc, _) => {
             let static_content = static_content_vc.await?;
+            let compiled = Instant::now();
 
             // This can happen when a chunk is removed from the asset graph.
             if static_content.status_code == 404 {
@@ -57,14 +64,33 @@ async fn get_update_stream_item(
             extend_issues(&mut plain_issues, peek_issues(content).await?);
 
             let update = update.await?;
+            if let Update::Partial(partial) = &*update {
+                partial.instruction.await?;
+            }
+            let generated = Instant::now();
+
+            // The version the client is at after applying the update.
+            let version = match &*update {
+                Update::Partial(PartialUpdate { to, .. }) | Update::Total(TotalUpdate { to }) => {
+                    *to
+                }
+                Update::None => from,
+            };
 
             Ok(UpdateStreamItem::Found {
                 update,
                 issues: plain_issues,
+                version: Some(version.state().await?),
+                timings: UpdateTimings {
+                    started,
+                    compiled,
+                    generated,
+                },
             }
             .cell())
         }
         _ => {
+            let compiled = Instant::now();
             let plain_issues = peek_issues(content).await?;
 
             let update = if plain_issues.is_empty() {
@@ -83,6 +109,12 @@ async fn get_update_stream_item(
             Ok(UpdateStreamItem::Found {
                 update: update.await?,
                 issues: plain_issues,
+                version: None,
+                timings: UpdateTimings {
+                    started,
+                    compiled,
+                    generated: Instant::now(),
+                },
             }
             .cell())
         }
@@ -139,7 +171,15 @@ impl VersionStateVc {
 pub(super) struct UpdateStream(Pin<Box<dyn Stream<Item = UpdateStreamItemReadRef> + Send + Sync>>);
 
 impl UpdateStream {
-    pub async fn new(get_content: TransientInstance<GetContentFn>) -> Result<UpdateStream> {
+    /// Creates a stream of updates of the content returned by `get_content`.
+    ///
+    /// When `resume` contains the state of a version the client is already at,
+    /// updates are computed from that version instead of the current one, so
+    /// the client is brought up to date without reloading it.
+    pub async fn new(
+        get_content: TransientInstance<GetContentFn>,
+        resume: Option<Value>,
+    ) -> Result<UpdateStream> {
         let (sx, rx) = tokio::sync::mpsc::channel(32);
 
         let content = get_content();
@@ -147,7 +187,11 @@ impl UpdateStream {
         // will handle them
         let version = match &*content.await? {
             ResolveSourceRequestResult::Static(static_content, _) => {
-                static_content.await?.content.version()
+                let content = static_content.await?.content;
+                match resume {
+                    Some(state) => content.restore_version(JsonValueVc::cell(state)),
+                    None => content.version(),
+                }
             }
             _ => NotFoundVersionVc::new().into(),
         };
@@ -156,8 +200,21 @@ impl UpdateStream {
         compute_update_stream(version_state, get_content, TransientInstance::new(sx));
 
         let mut last_had_issues = false;
+        let mut is_first = true;
 
         let stream = ReceiverStream::new(rx).filter_map(move |item| {
+            // The first item tells the client about its version, so it is
+            // always propagated when there is one.
+            let has_snapshot = is_first
+                && matches!(
+                    &*item,
+                    UpdateStreamItem::Found {
+                        version: Some(_),
+                        ..
+                    }
+                );
+            is_first = false;
+
             let (has_issues, issues_changed) =
                 if let UpdateStreamItem::Found { issues, .. } = &*item {
                     let has_issues = !issues.is_empty();
@@ -187,7 +244,7 @@ impl UpdateStream {
                             }
                             // Do not propagate empty updates.
                             Update::None => {
-                                if has_issues || issues_changed {
+                                if has_issues || issues_changed || has_snapshot {
                                     Some(item)
                                 } else {
                                     None
@@ -221,5 +278,11 @@ pub enum UpdateStreamItem {
     Found {
         update: UpdateReadRef,
         issues: Vec<PlainIssueReadRef>,
+        /// The state of the version the client is at after this item, see
+        /// [`Version::state`].
+        version: Option<JsonValueReadRef>,
+        /// How long computing this item took, see [`super::metrics`].
+        #[turbo_tasks(trace_ignore, debug_ignore)]
+        timings: UpdateTimings,
     },
 }
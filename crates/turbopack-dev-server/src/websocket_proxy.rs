@@ -0,0 +1,91 @@
+//! WebSocket connections of content sources.
+//!
+//! A content source accepts a request with `Upgrade: websocket` by
+//! [register]ing a [WebSocketProxy] and responding with a [ProxyResult] with
+//! status 101 and the id of the proxy as its `upgrade`. The dev server
+//! completes the handshake with the client and relays messages between the
+//! client and the proxy until either side closes the connection.
+//!
+//! [ProxyResult]: crate::source::ProxyResult
+
+use std::{collections::HashMap, sync::Mutex};
+
+use futures::{SinkExt, StreamExt};
+use hyper_tungstenite::{tungstenite::Message, HyperWebsocket};
+use once_cell::sync::Lazy;
+use tokio::{select, sync::mpsc};
+
+/// A message of a WebSocket connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebSocketMessage {
+    Text(String),
+    Binary(Vec<u8>),
+    /// Closes the connection. No messages follow.
+    Close,
+}
+
+/// The side of a WebSocket connection that's handled by a content source.
+pub struct WebSocketProxy {
+    /// Receives the messages of the client.
+    pub sender: mpsc::UnboundedSender<WebSocketMessage>,
+    /// The messages to send to the client.
+    pub receiver: mpsc::UnboundedReceiver<WebSocketMessage>,
+}
+
+static PROXIES: Lazy<Mutex<HashMap<String, WebSocketProxy>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Registers the `proxy` of a request that's upgraded under `id`, which needs
+/// to be unique, e.g. the id of the request. A proxy registered under the
+/// same id before is dropped, which closes its connection.
+pub fn register(id: String, proxy: WebSocketProxy) {
+    PROXIES.lock().unwrap().insert(id, proxy);
+}
+
+pub(crate) fn take(id: &str) -> Option<WebSocketProxy> {
+    PROXIES.lock().unwrap().remove(id)
+}
+
+/// Relays the messages between the client of the `websocket` and the `proxy`
+/// once the handshake is done. The `proxy` is dropped when the handshake
+/// fails.
+pub(crate) fn relay(websocket: HyperWebsocket, mut proxy: WebSocketProxy) {
+    tokio::spawn(async move {
+        let Ok(mut websocket) = websocket.await else {
+            return;
+        };
+        loop {
+            select! {
+                message = websocket.next() => {
+                    let message = match message {
+                        Some(Ok(Message::Text(text))) => WebSocketMessage::Text(text),
+                        Some(Ok(Message::Binary(data))) => WebSocketMessage::Binary(data),
+                        // Pings are answered by the WebSocket itself.
+                        Some(Ok(Message::Ping(_) | Message::Pong(_) | Message::Frame(_))) => {
+                            continue
+                        }
+                        Some(Ok(Message::Close(_)) | Err(_)) | None => WebSocketMessage::Close,
+                    };
+                    let closed = message == WebSocketMessage::Close;
+                    if proxy.sender.send(message).is_err() || closed {
+                        break;
+                    }
+                }
+                message = proxy.receiver.recv() => {
+                    let message = match message {
+                        Some(WebSocketMessage::Text(text)) => Message::Text(text),
+                        Some(WebSocketMessage::Binary(data)) => Message::Binary(data),
+                        Some(WebSocketMessage::Close) | None => {
+                            let _ = websocket.close(None).await;
+                            break;
+                        }
+                    };
+                    if websocket.send(message).await.is_err() {
+                        let _ = proxy.sender.send(WebSocketMessage::Close);
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
@@ -2,11 +2,31 @@
 /** @typedef {import('../types/dom').ChunkResolver} ChunkResolver */
 /** @typedef {import('../types').ChunkPath} ChunkPath */
 /** @typedef {import('../types').SourceInfo} SourceInfo */
+/** @typedef {import('../types').PrefetchStrategy} PrefetchStrategy */
 
 /** @type {RuntimeBackend} */
 let BACKEND;
 
 (() => {
+  /**
+   * Whether the runtime runs in a worker, e.g. a service worker, where
+   * classic chunks are loaded with `importScripts()` and there are no
+   * stylesheets.
+   */
+  const isWorker = typeof document === "undefined";
+
+  /**
+   * The nonce of the page's Content-Security-Policy. It's applied to all
+   * scripts and stylesheets that are inserted to load chunks.
+   *
+   * @type {string | undefined}
+   */
+  const cspNonce = isWorker
+    ? undefined
+    : document.currentScript?.nonce ||
+      document.querySelector("script[nonce]")?.nonce ||
+      undefined;
+
   BACKEND = {
     async registerChunk(chunkPath, params) {
       const resolver = getOrCreateResolver(chunkPath);
@@ -44,9 +64,12 @@ let BACKEND;
     unloadChunk(chunkPath) {
       deleteResolver(chunkPath);
 
+      if (isWorker) {
+        return;
+      }
+
       if (chunkPath.endsWith(".css")) {
-        const links = document.querySelectorAll(`link[href="/${chunkPath}"]`);
-        for (const link of Array.from(links)) {
+        for (const link of findChunkElements("link", "href", chunkPath)) {
           link.remove();
         }
       } else if (chunkPath.endsWith(".js")) {
@@ -54,10 +77,7 @@ let BACKEND;
         // runtime once evaluated.
         // However, we still want to remove the script tag from the DOM to keep
         // the HTML somewhat consistent from the user's perspective.
-        const scripts = document.querySelectorAll(
-          `script[src="/${chunkPath}"]`
-        );
-        for (const script of Array.from(scripts)) {
+        for (const script of findChunkElements("script", "src", chunkPath)) {
           script.remove();
         }
       } else {
@@ -72,9 +92,13 @@ let BACKEND;
           return;
         }
 
-        const previousLink = document.querySelector(
-          `link[href^="/${chunkPath}"]`
-        );
+        if (isWorker) {
+          // Workers don't apply stylesheets.
+          resolve();
+          return;
+        }
+
+        const [previousLink] = findChunkElements("link", "href", chunkPath);
 
         if (previousLink == null) {
           reject(new Error(`No link element found for chunk ${chunkPath}`));
@@ -83,7 +107,11 @@ let BACKEND;
 
         const link = document.createElement("link");
         link.rel = "stylesheet";
-        link.href = `/${chunkPath}?t=${Date.now()}`;
+        bumpChunkVersion();
+        link.href = chunkUrl(chunkPath);
+        if (cspNonce) {
+          link.nonce = cspNonce;
+        }
         link.onerror = () => {
           reject();
         };
@@ -104,9 +132,117 @@ let BACKEND;
       });
     },
 
+    prefetchChunks(strategy, getChunkPaths) {
+      if (isWorker) {
+        return;
+      }
+
+      const prefetch = () => {
+        getChunkPaths().then(
+          (chunkPaths) => {
+            for (const chunkPath of chunkPaths) {
+              prefetchChunk(chunkPath);
+            }
+          },
+          // The import reports the failure when it's reached.
+          () => {}
+        );
+      };
+
+      switch (strategy) {
+        case "idle":
+          if (typeof requestIdleCallback === "function") {
+            requestIdleCallback(prefetch);
+          } else {
+            setTimeout(prefetch, 0);
+          }
+          break;
+        case "visible":
+          if (document.visibilityState === "visible") {
+            prefetch();
+          } else {
+            const onVisibilityChange = () => {
+              if (document.visibilityState === "visible") {
+                document.removeEventListener(
+                  "visibilitychange",
+                  onVisibilityChange
+                );
+                prefetch();
+              }
+            };
+            document.addEventListener("visibilitychange", onVisibilityChange);
+          }
+          break;
+        default:
+          prefetch();
+      }
+    },
+
     restart: () => self.location.reload(),
   };
 
+  /**
+   * Fetches a chunk with a low priority, without executing it. Chunks which
+   * are loaded or already prefetched, e.g. by the HTML page, are skipped.
+   *
+   * @param {ChunkPath} chunkPath
+   */
+  function prefetchChunk(chunkPath) {
+    if (chunkResolvers.get(chunkPath)?.resolved) {
+      return;
+    }
+    if (findChunkElements("link", "href", chunkPath).length > 0) {
+      return;
+    }
+    const link = document.createElement("link");
+    link.rel = "prefetch";
+    link.href = chunkUrl(chunkPath);
+    if (cspNonce) {
+      link.nonce = cspNonce;
+    }
+    document.head.appendChild(link);
+  }
+
+  /**
+   * The version appended to the URLs of chunks which are loaded after an
+   * update, so stale service workers or proxies can't serve outdated chunks.
+   * The dev server ignores it.
+   *
+   * @type {number | undefined}
+   */
+  let chunkVersion;
+
+  function bumpChunkVersion() {
+    chunkVersion = Date.now();
+  }
+
+  /**
+   * @param {ChunkPath} chunkPath
+   * @returns {string}
+   */
+  function chunkUrl(chunkPath) {
+    const url = `/${chunkPath}`;
+    return chunkVersion == null ? url : `${url}?v=${chunkVersion}`;
+  }
+
+  /**
+   * Finds the elements which have loaded a chunk, with any version.
+   *
+   * @param {string} tagName
+   * @param {string} attribute
+   * @param {ChunkPath} chunkPath
+   * @returns {Element[]}
+   */
+  function findChunkElements(tagName, attribute, chunkPath) {
+    const url = `/${chunkPath}`;
+    return Array.from(
+      document.querySelectorAll(`${tagName}[${attribute}^="${url}"]`)
+    ).filter((element) => {
+      const value = element.getAttribute(attribute);
+      return value === url || value.startsWith(`${url}?`);
+    });
+  }
+
   /**
    * Maps chunk paths to the corresponding resolver.
    *
@@ -184,10 +320,22 @@ let BACKEND;
       return resolver.promise;
     }
 
+    if (source.type === SourceTypeUpdate) {
+      bumpChunkVersion();
+    }
+
     if (chunkPath.endsWith(".css")) {
+      if (isWorker) {
+        resolver.resolve();
+        return resolver.promise;
+      }
+
       const link = document.createElement("link");
       link.rel = "stylesheet";
-      link.href = `/${chunkPath}`;
+      link.href = chunkUrl(chunkPath);
+      if (cspNonce) {
+        link.nonce = cspNonce;
+      }
       link.onerror = () => {
         resolver.reject();
       };
@@ -198,15 +346,37 @@ let BACKEND;
       };
       document.body.appendChild(link);
     } else if (chunkPath.endsWith(".js")) {
-      const script = document.createElement("script");
-      script.src = `/${chunkPath}`;
       // We'll only mark the chunk as loaded once the script has been executed,
       // which happens in `registerChunk`. Hence the absence of `resolve()` in
       // this branch.
-      script.onerror = () => {
-        resolver.reject();
-      };
-      document.body.appendChild(script);
+      if (CHUNK_FORMAT === "module") {
+        // When set, the import map of the page maps the specifier to the chunk
+        // URL.
+        const specifier =
+          CHUNK_SPECIFIER_PREFIX != null
+            ? CHUNK_SPECIFIER_PREFIX + chunkPath
+            : chunkUrl(chunkPath);
+        import(specifier).catch((error) => {
+          resolver.reject(error);
+        });
+      } else if (isWorker) {
+        // The chunk registers itself synchronously while it's imported.
+        try {
+          importScripts(chunkUrl(chunkPath));
+        } catch (error) {
+          resolver.reject(error);
+        }
+      } else {
+        const script = document.createElement("script");
+        script.src = chunkUrl(chunkPath);
+        if (cspNonce) {
+          script.nonce = cspNonce;
+        }
+        script.onerror = () => {
+          resolver.reject();
+        };
+        document.body.appendChild(script);
+      }
     } else {
       throw new Error(`can't infer type of chunk from path ${chunkPath}`);
     }
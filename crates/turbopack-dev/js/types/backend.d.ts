@@ -12,4 +12,7 @@ declare global {
   declare const SourceTypeRuntime: SourceType.Runtime;
   declare const SourceTypeParent: SourceType.Parent;
   declare const SourceTypeUpdate: SourceType.Update;
+  declare const CHUNK_SPECIFIER_PREFIX: string | null;
+  declare const CHUNK_FORMAT: "script" | "module";
+  declare const RUNTIME_ERROR_HOOK: string | null;
 }
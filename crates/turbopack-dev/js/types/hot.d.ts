@@ -14,6 +14,11 @@ export interface HotState {
   selfAccepted: boolean | Function;
   selfDeclined: boolean;
   selfInvalidated: boolean;
+  /**
+   * Whether the React Refresh transform found the module to be a potential
+   * Refresh Boundary. `undefined` when it couldn't tell.
+   */
+  refreshBoundary: boolean | undefined;
   disposeHandlers: ((data: object) => void)[];
 }
 
@@ -4,6 +4,17 @@ import { Hot } from "./hot";
 import { DevRuntimeParams } from "./runtime";
 
 export type RefreshHelpers = RefreshRuntimeGlobals["$RefreshHelpers$"];
+/**
+ * The parts of the `react-refresh/runtime` module used by the built-in
+ * refresh helpers.
+ */
+export type RefreshRuntime = {
+  register: (type: unknown, id: string) => void;
+  createSignatureFunctionForTransform: RefreshRuntimeGlobals["$RefreshSig$"];
+  isLikelyComponentType: (type: unknown) => boolean;
+  getFamilyByType: (type: unknown) => unknown;
+  performReactRefresh: () => void;
+};
 
 type ChunkPath = string;
 type ModuleId = string;
@@ -78,6 +89,12 @@ type ExportValue = (value: any) => void;
 
 type LoadChunk = (chunkPath: ChunkPath) => Promise<any> | undefined;
 
+type PrefetchStrategy = "eager" | "idle" | "visible";
+type PrefetchChunks = (
+  strategy: PrefetchStrategy,
+  getChunkPaths: () => Promise<ChunkPath[]>
+) => void;
+
 interface TurbopackContext {
   e: Module["exports"];
   r: CommonJsRequire;
@@ -87,6 +104,7 @@ interface TurbopackContext {
   m: Module;
   c: ModuleCache;
   l: LoadChunk;
+  h: PrefetchChunks;
   p: Partial<NodeJS.Process> & Pick<NodeJS.Process, "env">;
 }
 
@@ -103,6 +121,7 @@ interface RuntimeBackend {
   loadChunk: (chunkPath: ChunkPath, source: SourceInfo) => Promise<void>;
   reloadChunk?: (chunkPath: ChunkPath) => Promise<void>;
   unloadChunk?: (chunkPath: ChunkPath) => void;
+  prefetchChunks?: PrefetchChunks;
 
   restart: () => void;
 }
@@ -157,6 +176,7 @@ export type DevRuntimeParams = {
 
 declare global {
   var TURBOPACK: ChunkRegistration[];
+  var __turbopack__: import("./runtime").TurbopackRuntimeApi;
   var TURBOPACK_CHUNK_UPDATE_LISTENERS:
     | ChunkUpdateProvider
     | [ChunkPath, UpdateCallback][]
@@ -166,6 +186,11 @@ declare global {
   var $RefreshReg$: RefreshRuntimeGlobals["$RefreshReg$"];
   var $RefreshSig$: RefreshRuntimeGlobals["$RefreshSig$"];
   var $RefreshInterceptModuleExecution$: RefreshRuntimeGlobals["$RefreshInterceptModuleExecution$"];
+  /**
+   * The `react-refresh/runtime` module, for apps which don't use a framework
+   * providing `$RefreshHelpers$`.
+   */
+  var $RefreshRuntime$: RefreshRuntime | undefined;
 
   interface NodeModule {
     hot: Hot;
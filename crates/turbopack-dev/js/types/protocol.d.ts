@@ -3,6 +3,18 @@ import { ChunkPath, ModuleFactoryString, ModuleId } from "./index";
 export type ServerMessage = {
   resource: ResourceIdentifier;
   issues: Issue[];
+  /**
+   * The version of the resource after applying the message. Clients should
+   * keep the latest snapshot of each resource and send it back when
+   * resubscribing, e.g. after the server restarted, to receive the changes
+   * since then instead of reloading.
+   */
+  snapshot?: VersionSnapshot;
+  /**
+   * Identifies updates which clients should acknowledge with an `ack` message
+   * once they have been applied, to measure the latency of updates.
+   */
+  updateId?: number;
 } & (
   | {
       type: "restart";
@@ -77,11 +89,36 @@ export type EcmascriptMergedChunkUpdate =
     };
 
 export type EcmascriptModuleEntry = {
-  code: ModuleFactoryString;
+  /** The code of the module, unless it's sent as a `diff`. */
+  code?: ModuleFactoryString;
+  diff?: CodeDiff;
+  /** The hex encoded hash of the code. */
+  hash: string;
   url: string;
   map?: string;
 };
 
+/**
+ * The code of a module as a change of its previous code: the previous code
+ * with everything between the unchanged `prefix` and `suffix` replaced by
+ * `insert`. Lengths are in UTF-16 code units.
+ */
+export type CodeDiff = {
+  baseLength: number;
+  prefix: number;
+  suffix: number;
+  insert: string;
+};
+
+/**
+ * An opaque version of a resource, together with the id of the server process
+ * which computed it. A changed session id means the server was restarted.
+ */
+export type VersionSnapshot = {
+  sessionId: string;
+  version: unknown;
+};
+
 type ResourceIdentifier = {
   path: string;
   headers?: { [string]: string };
@@ -89,13 +126,22 @@ type ResourceIdentifier = {
 
 export type ClientMessageSubscribe = {
   type: "subscribe";
+  resume?: VersionSnapshot;
 } & ResourceIdentifier;
 
 export type ClientMessageUnsubscribe = {
   type: "unsubscribe";
 } & ResourceIdentifier;
 
-export type ClientMessage = ClientMessageSubscribe | ClientMessageUnsubscribe;
+export type ClientMessageAck = {
+  type: "ack";
+  updateId: number;
+};
+
+export type ClientMessage =
+  | ClientMessageSubscribe
+  | ClientMessageUnsubscribe
+  | ClientMessageAck;
 
 export type IssueSeverity =
   | "bug"
@@ -122,6 +168,17 @@ export type IssueSource = {
   end: SourcePos;
 };
 
+export type IssueSuggestionEdit = {
+  /** An empty range inserts the replacement. */
+  source: IssueSource;
+  replacement: string;
+};
+
+export type IssueSuggestion = {
+  title: string;
+  edits: IssueSuggestionEdit[];
+};
+
 export type Issue = {
   severity: IssueSeverity;
   context: string;
@@ -131,8 +188,11 @@ export type Issue = {
   description: string;
   detail: string;
   documentation_link: string;
+  code: string;
+  documentation_slug: string;
 
   source: IssueSource | null;
+  suggestions: IssueSuggestion[];
   sub_issues: Issue[];
   formatted: string;
 };
@@ -18,6 +18,57 @@ export type ModuleEffect =
       outdatedModules: Set<ModuleId>;
     };
 
+/**
+ * Passed to the global callback configured as runtime error hook.
+ */
+export type RuntimeErrorReport =
+  | {
+      type: "chunk-load";
+      chunkPath: ChunkPath;
+      /** Why the chunk was loaded, e.g. `from module [project]/a.js`. */
+      reason: string;
+      error: unknown;
+    }
+  | {
+      type: "module-instantiation";
+      moduleId: ModuleId;
+      error: unknown;
+    };
+
+/**
+ * Passed to the callbacks of `__turbopack__.onChunkLoad` once a chunk has
+ * been loaded, or has failed to load.
+ */
+export type ChunkLoadEvent = {
+  chunkPath: ChunkPath;
+  /**
+   * When the runtime started to load the chunk, as `performance.now()`. It's
+   * `null` for chunks loaded by their `<script>` tag.
+   */
+  startTime: number | null;
+  /** How long the chunk took to load in milliseconds. */
+  duration: number | null;
+  /** Why the chunk failed to load. */
+  error: unknown | null;
+};
+
+export type ChunkLoadListener = (event: ChunkLoadEvent) => void;
+
+/**
+ * The runtime API available as `globalThis.__turbopack__`.
+ */
+export interface TurbopackRuntimeApi {
+  /** The chunks which have been loaded so far. */
+  getLoadedChunks(): ChunkPath[];
+  /** The chunks which are being loaded. */
+  getPendingChunks(): ChunkPath[];
+  /**
+   * Calls the `listener` for every chunk which is loaded from now on. Returns
+   * a function which removes the listener.
+   */
+  onChunkLoad(listener: ChunkLoadListener): () => void;
+}
+
 export type DevRuntimeParams = {
   otherChunks: ChunkPath[];
   runtimeModuleIds: ModuleId[];
@@ -1,16 +1,18 @@
 use std::fmt::Write;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use turbo_tasks::{
-    primitives::{BoolVc, StringVc},
+    primitives::{BoolVc, OptionStringVc, StringVc},
     Value, ValueToString,
 };
-use turbo_tasks_fs::FileSystemPathVc;
+use turbo_tasks_fs::{FileSystemPathOptionVc, FileSystemPathVc};
 use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64, DeterministicHash, Xxh3Hash64Hasher};
 use turbopack_core::{
     asset::{Asset, AssetVc},
-    chunk::{Chunk, ChunkVc, ChunkingContext, ChunkingContextVc},
-    environment::EnvironmentVc,
+    chunk::{
+        reserved_ids::ReservedIdsVc, Chunk, ChunkVc, ChunkingContext, ChunkingContextVc, ModuleIdVc,
+    },
+    environment::{ChunkLoading, EnvironmentVc},
     ident::{AssetIdent, AssetIdentVc},
     resolve::ModulePart,
 };
@@ -19,7 +21,7 @@ use turbopack_ecmascript::chunk::{
     EcmascriptChunkingContextVc,
 };
 
-use crate::ecmascript::runtime::EcmascriptDevChunkRuntimeVc;
+use crate::ecmascript::{runtime::EcmascriptDevChunkRuntimeVc, vendor::vendor_package};
 
 pub struct DevChunkingContextBuilder {
     context: DevChunkingContext,
@@ -51,11 +53,96 @@ impl DevChunkingContextBuilder {
         self
     }
 
+    /// Marks the chunks as legacy chunks, to be loaded via `<script nomodule>`.
+    /// Legacy chunks get a `.legacy` suffix and their runtime is transpiled
+    /// for the chunking context's environment.
+    pub fn legacy(mut self) -> Self {
+        self.context.legacy = true;
+        self
+    }
+
+    /// Loads chunks with native `import()` of specifiers that are mapped to the
+    /// chunk URLs by an import map, instead of inserting `<script>` tags. See
+    /// [crate::import_map::chunk_import_map].
+    pub fn import_map_chunk_loading(mut self) -> Self {
+        self.context.import_map_chunk_loading = true;
+        self
+    }
+
+    /// Prebundles dependencies: the modules of packages from `node_modules`
+    /// are minified, and the code of their chunks is cached in `cache_dir`, so
+    /// it doesn't need to be generated again after a restart. The cache files
+    /// are emitted with the chunks, so `cache_dir` needs to be inside the
+    /// output directory.
+    pub fn vendor_prebundling(mut self, cache_dir: FileSystemPathVc) -> Self {
+        self.context.vendor_cache_dir = Some(cache_dir);
+        self
+    }
+
+    /// Uses short hashes of the readable module ids as module ids. They only
+    /// depend on the readable ids, so they are stable across builds.
+    pub fn mangle_module_ids(mut self) -> Self {
+        self.context.mangle_module_ids = true;
+        self
+    }
+
+    /// Reserves module ids and chunk names, which are always given to the
+    /// modules and chunks they are reserved for and never to others.
+    pub fn reserved_ids(mut self, reserved_ids: ReservedIdsVc) -> Self {
+        self.context.reserved_ids = Some(reserved_ids);
+        self
+    }
+
+    /// Calls the global function with this name with a description of the
+    /// failure when a chunk fails to load or a module factory throws, e.g. to
+    /// report it to a monitoring service.
+    pub fn runtime_error_hook(mut self, global_name: &str) -> Self {
+        self.context.runtime_error_hook = Some(global_name.to_string());
+        self
+    }
+
+    /// Sets the format of the chunks, which determines how the runtime loads
+    /// them. By default, it follows the chunk loading of the environment.
+    pub fn runtime_format(mut self, runtime_format: ChunkRuntimeFormat) -> Self {
+        self.context.runtime_format = runtime_format;
+        self
+    }
+
     pub fn build(self) -> ChunkingContextVc {
         DevChunkingContextVc::new(Value::new(self.context)).into()
     }
 }
 
+/// The format of the chunks of a chunking context, which determines how the
+/// runtime loads them.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(Debug, Default, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum ChunkRuntimeFormat {
+    /// Follows the chunk loading of the environment: classic scripts in the
+    /// browser and CommonJS in Node.js. Other environments don't load chunks,
+    /// they need to be evaluated before the chunks depending on them.
+    #[default]
+    Environment,
+    /// Classic scripts, loaded with `<script>` tags, or with `importScripts()`
+    /// in workers, e.g. service workers.
+    Script,
+    /// ES modules, loaded with `import()`, e.g. in module workers.
+    Module,
+    /// CommonJS modules, loaded with `require()`.
+    CommonJs,
+}
+
+impl ChunkRuntimeFormat {
+    fn name(&self) -> &'static str {
+        match self {
+            ChunkRuntimeFormat::Environment => "environment",
+            ChunkRuntimeFormat::Script => "script",
+            ChunkRuntimeFormat::Module => "module",
+            ChunkRuntimeFormat::CommonJs => "CommonJS",
+        }
+    }
+}
+
 /// A chunking context for development mode.
 /// It uses readable filenames and module ids to improve development.
 /// It also uses a chunking heuristic that is incremental and cacheable.
@@ -84,6 +171,20 @@ pub struct DevChunkingContext {
     enable_hot_module_replacement: bool,
     /// The environment chunks will be evaluated in.
     environment: EnvironmentVc,
+    /// Chunks are legacy variants that are loaded via `<script nomodule>`
+    legacy: bool,
+    /// Chunks are loaded via `import()` and an import map
+    import_map_chunk_loading: bool,
+    /// Code of prebundled dependencies is cached at this path
+    vendor_cache_dir: Option<FileSystemPathVc>,
+    /// Module ids are hashes of the readable ids
+    mangle_module_ids: bool,
+    /// Module ids and chunk names reserved for specific modules and chunks
+    reserved_ids: Option<ReservedIdsVc>,
+    /// Name of the global function the runtime reports failures to
+    runtime_error_hook: Option<String>,
+    /// The format of the chunks
+    runtime_format: ChunkRuntimeFormat,
 }
 
 impl DevChunkingContextVc {
@@ -106,6 +207,13 @@ impl DevChunkingContextVc {
                 layer: None,
                 enable_hot_module_replacement: false,
                 environment,
+                legacy: false,
+                import_map_chunk_loading: false,
+                vendor_cache_dir: None,
+                mangle_module_ids: false,
+                reserved_ids: None,
+                runtime_error_hook: None,
+                runtime_format: ChunkRuntimeFormat::Environment,
             },
         }
     }
@@ -117,6 +225,100 @@ impl DevChunkingContextVc {
     fn new(this: Value<DevChunkingContext>) -> Self {
         this.into_value().cell()
     }
+
+    /// Whether chunks are legacy variants that are loaded via `<script
+    /// nomodule>`.
+    #[turbo_tasks::function]
+    pub async fn is_legacy(self) -> Result<BoolVc> {
+        Ok(BoolVc::cell(self.await?.legacy))
+    }
+
+    /// Whether chunks are loaded via `import()` of specifiers from an import
+    /// map.
+    #[turbo_tasks::function]
+    pub async fn uses_import_map_chunk_loading(self) -> Result<BoolVc> {
+        Ok(BoolVc::cell(self.await?.import_map_chunk_loading))
+    }
+
+    /// The directory the code of prebundled dependencies is cached in, when
+    /// vendor prebundling is enabled.
+    #[turbo_tasks::function]
+    pub async fn vendor_cache_dir(self) -> Result<FileSystemPathOptionVc> {
+        Ok(FileSystemPathOptionVc::cell(self.await?.vendor_cache_dir))
+    }
+
+    /// A copy of this context which emits chunks in another format, e.g. for
+    /// the chunk group of a service worker. The chunks are named differently,
+    /// so they don't collide with the chunks of this context.
+    #[turbo_tasks::function]
+    pub async fn with_runtime_format(
+        self,
+        runtime_format: Value<ChunkRuntimeFormat>,
+    ) -> Result<DevChunkingContextVc> {
+        let mut context = self.await?.clone_value();
+        context.runtime_format = runtime_format.into_value();
+        Ok(DevChunkingContextVc::new(Value::new(context)))
+    }
+
+    /// The format of the chunks, where [ChunkRuntimeFormat::Environment] is
+    /// resolved from the chunk loading of the environment, unless the
+    /// environment doesn't load chunks. Fails when the format can't be loaded
+    /// in the environment, or isn't supported with other options.
+    #[turbo_tasks::function]
+    pub async fn runtime_format(self) -> Result<ChunkRuntimeFormatVc> {
+        let this = self.await?;
+        let chunk_loading = this.environment.chunk_loading().await?;
+        let runtime_format = match (this.runtime_format, &*chunk_loading) {
+            (ChunkRuntimeFormat::Environment, ChunkLoading::None) => {
+                ChunkRuntimeFormat::Environment
+            }
+            (ChunkRuntimeFormat::Environment, ChunkLoading::NodeJs) => ChunkRuntimeFormat::CommonJs,
+            (ChunkRuntimeFormat::Environment, ChunkLoading::Dom) => {
+                if this.import_map_chunk_loading {
+                    ChunkRuntimeFormat::Module
+                } else {
+                    ChunkRuntimeFormat::Script
+                }
+            }
+            (
+                runtime_format @ (ChunkRuntimeFormat::Script | ChunkRuntimeFormat::Module),
+                ChunkLoading::Dom,
+            )
+            | (runtime_format @ ChunkRuntimeFormat::CommonJs, ChunkLoading::NodeJs) => {
+                runtime_format
+            }
+            (runtime_format, chunk_loading) => bail!(
+                "chunks in the {} format can't be loaded {}",
+                runtime_format.name(),
+                match chunk_loading {
+                    ChunkLoading::None => "in an environment without chunk loading",
+                    ChunkLoading::NodeJs => "in Node.js, which only supports CommonJS",
+                    ChunkLoading::Dom => "in the browser, which doesn't support CommonJS",
+                }
+            ),
+        };
+        if this.legacy && runtime_format == ChunkRuntimeFormat::Module {
+            bail!(
+                "legacy chunks are loaded with `<script nomodule>`, so they can't be in the \
+                 module format"
+            );
+        }
+        if this.import_map_chunk_loading && runtime_format != ChunkRuntimeFormat::Module {
+            bail!(
+                "chunk loading via an import map uses `import()`, so chunks need to be in the \
+                 module format, not the {} format",
+                runtime_format.name()
+            );
+        }
+        Ok(runtime_format.cell())
+    }
+
+    /// The name of the global function the runtime reports chunk loading and
+    /// module instantiation failures to, if any.
+    #[turbo_tasks::function]
+    pub async fn runtime_error_hook(self) -> Result<OptionStringVc> {
+        Ok(OptionStringVc::cell(self.await?.runtime_error_hook.clone()))
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -250,7 +452,19 @@ impl ChunkingContext for DevChunkingContext {
         if !removed_extension {
             name += "._";
         }
+        if self.legacy {
+            name += ".legacy";
+        }
+        match self.runtime_format {
+            ChunkRuntimeFormat::Environment => {}
+            ChunkRuntimeFormat::Script => name += ".script",
+            ChunkRuntimeFormat::Module => name += ".module",
+            ChunkRuntimeFormat::CommonJs => name += ".cjs",
+        }
         name += extension;
+        if let Some(reserved_ids) = self.reserved_ids {
+            name = reserved_ids.await?.chunk_name(&name);
+        }
         let mut root_path = self.chunk_root_path;
         #[allow(clippy::single_match, reason = "future extensions")]
         match extension {
@@ -315,6 +529,19 @@ impl ChunkingContext for DevChunkingContext {
             .join(&format!("{content_hash}.{extension}"))
     }
 
+    #[turbo_tasks::function]
+    async fn module_id(&self, ident: AssetIdentVc) -> Result<ModuleIdVc> {
+        let readable_id = ident.to_string().await?;
+        let reserved_ids = match self.reserved_ids {
+            Some(reserved_ids) => reserved_ids,
+            None => ReservedIdsVc::empty(),
+        };
+        Ok(reserved_ids
+            .await?
+            .module_id(&readable_id, self.mangle_module_ids)
+            .cell())
+    }
+
     #[turbo_tasks::function]
     fn is_hot_module_replacement_enabled(&self) -> BoolVc {
         BoolVc::cell(self.enable_hot_module_replacement)
@@ -347,4 +574,15 @@ impl EcmascriptChunkingContext for DevChunkingContext {
     ) -> EcmascriptChunkRuntimeVc {
         EcmascriptDevChunkRuntimeVc::new(self_vc, Some(evaluated_entries)).into()
     }
+
+    #[turbo_tasks::function]
+    async fn minify_chunk_item_code(&self, ident: AssetIdentVc) -> Result<BoolVc> {
+        // Only modules of packages whose chunks are cached are minified.
+        let Some(cache_dir) = self.vendor_cache_dir else {
+            return Ok(BoolVc::cell(false));
+        };
+        Ok(BoolVc::cell(
+            vendor_package(ident.path(), cache_dir).await?.is_some(),
+        ))
+    }
 }
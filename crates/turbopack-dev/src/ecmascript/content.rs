@@ -3,13 +3,13 @@ use std::io::Write;
 use anyhow::{bail, Context, Result};
 use indoc::writedoc;
 use serde::Serialize;
-use turbo_tasks::TryJoinIterExt;
-use turbo_tasks_fs::{embed_file, File, FileContent};
+use turbo_tasks::{primitives::StringVc, TryJoinIterExt};
+use turbo_tasks_fs::{embed_file, rope::Rope, File, FileContent};
 use turbopack_core::{
     asset::AssetContentVc,
     chunk::{Chunk, ChunkGroupVc, ChunkingContext, ModuleId, ModuleIdReadRef},
-    code_builder::{CodeBuilder, CodeVc},
-    environment::ChunkLoading,
+    code_builder::{Code, CodeBuilder, CodeVc},
+    environment::{ChunkLoading, EnvironmentVc},
     source_map::{GenerateSourceMap, GenerateSourceMapVc, OptionSourceMapVc},
     version::{
         MergeableVersionedContent, MergeableVersionedContentVc, UpdateVc, VersionVc,
@@ -21,13 +21,17 @@ use turbopack_ecmascript::{
         EcmascriptChunkPlaceable, EcmascriptChunkPlaceablesVc, EcmascriptChunkRuntimeContent,
         EcmascriptChunkRuntimeContentVc, EcmascriptChunkVc, EcmascriptChunkingContextVc,
     },
+    downlevel::downlevel_script,
     utils::StringifyJs,
 };
 
 use super::{
     content_entry::EcmascriptDevChunkContentEntriesVc,
-    merged::merger::EcmascriptDevChunkContentMergerVc, version::EcmascriptDevChunkVersionVc,
+    merged::merger::EcmascriptDevChunkContentMergerVc,
+    vendor::{chunk_vendor_package, vendor_entries},
+    version::EcmascriptDevChunkVersionVc,
 };
+use crate::{import_map::CHUNK_SPECIFIER_PREFIX, ChunkRuntimeFormat, DevChunkingContextVc};
 
 #[turbo_tasks::value(serialization = "none")]
 pub(super) struct EcmascriptDevChunkContent {
@@ -48,9 +52,12 @@ impl EcmascriptDevChunkContentVc {
         evaluated_entries: Option<EcmascriptChunkPlaceablesVc>,
     ) -> Result<Self> {
         let chunk_content = chunk.chunk_content();
-        let entries = EcmascriptDevChunkContentEntriesVc::new(chunk_content)
-            .resolve()
-            .await?;
+        let entries = match *chunk_vendor_package(chunk, chunking_context).await? {
+            Some(package) => vendor_entries(chunk_content, package),
+            None => EcmascriptDevChunkContentEntriesVc::new(chunk_content),
+        }
+        .resolve()
+        .await?;
         Ok(EcmascriptDevChunkContent {
             entries,
             chunking_context,
@@ -157,12 +164,52 @@ impl EcmascriptDevChunkContentVc {
             "#,
         )?;
 
-        let specific_runtime_code =
-            match &*this.chunking_context.environment().chunk_loading().await? {
-                ChunkLoading::None => embed_file!("js/src/runtime.none.js").await?,
-                ChunkLoading::NodeJs => embed_file!("js/src/runtime.nodejs.js").await?,
-                ChunkLoading::Dom => embed_file!("js/src/runtime.dom.js").await?,
-            };
+        let dev_chunking_context =
+            DevChunkingContextVc::resolve_from(this.chunking_context).await?;
+
+        let runtime_error_hook = match dev_chunking_context {
+            Some(context) => context.runtime_error_hook().await?.clone_value(),
+            None => None,
+        };
+        writeln!(
+            code,
+            "const RUNTIME_ERROR_HOOK = {};",
+            StringifyJs(&runtime_error_hook)
+        )?;
+
+        let runtime_format = match dev_chunking_context {
+            Some(context) => *context.runtime_format().await?,
+            None => match &*this.chunking_context.environment().chunk_loading().await? {
+                ChunkLoading::None => ChunkRuntimeFormat::Environment,
+                ChunkLoading::NodeJs => ChunkRuntimeFormat::CommonJs,
+                ChunkLoading::Dom => ChunkRuntimeFormat::Script,
+            },
+        };
+        let specific_runtime_code = match runtime_format {
+            ChunkRuntimeFormat::Environment => embed_file!("js/src/runtime.none.js").await?,
+            ChunkRuntimeFormat::CommonJs => embed_file!("js/src/runtime.nodejs.js").await?,
+            ChunkRuntimeFormat::Script | ChunkRuntimeFormat::Module => {
+                // With import map chunk loading, the DOM backend imports these
+                // specifiers instead of the URLs of the chunks.
+                let chunk_specifier_prefix = match dev_chunking_context {
+                    Some(context) if *context.uses_import_map_chunk_loading().await? => {
+                        Some(CHUNK_SPECIFIER_PREFIX)
+                    }
+                    _ => None,
+                };
+                writeln!(
+                    code,
+                    "const CHUNK_SPECIFIER_PREFIX = {};",
+                    StringifyJs(&chunk_specifier_prefix)
+                )?;
+                let chunk_format = match runtime_format {
+                    ChunkRuntimeFormat::Module => "module",
+                    _ => "script",
+                };
+                writeln!(code, "const CHUNK_FORMAT = {};", StringifyJs(chunk_format))?;
+                embed_file!("js/src/runtime.dom.js").await?
+            }
+        };
 
         match &*specific_runtime_code {
             FileContent::NotFound => bail!("specific runtime code is not found"),
@@ -183,6 +230,16 @@ impl EcmascriptDevChunkContentVc {
             "#
         )?;
 
+        // Legacy chunks are loaded in environments that might not support the
+        // syntax the runtime is written in.
+        if self.is_legacy().await? {
+            return Ok(CodeVc::cell(
+                downlevel_code(code.build(), this.chunking_context.environment())
+                    .await?
+                    .build(),
+            ));
+        }
+
         Ok(CodeVc::cell(code.build()))
     }
 
@@ -227,6 +284,16 @@ impl EcmascriptDevChunkContentVc {
 
         writeln!(code, "]);")?;
 
+        // The chunk items are only transpiled for the environment of their asset
+        // context, and are wrapped in module factories using arrow functions. So
+        // the chunk items of legacy chunks are transpiled for the environment of
+        // the chunking context as a whole. Their source maps are lost.
+        let mut code = if self.is_legacy().await? {
+            downlevel_code(code.build(), this.chunking_context.environment()).await?
+        } else {
+            code
+        };
+
         // Only include the runtime code when we're evaluating the current chunk.
         if this.evaluated_entries.is_some() {
             writeln!(code)?;
@@ -249,6 +316,32 @@ impl EcmascriptDevChunkContentVc {
     }
 }
 
+impl EcmascriptDevChunkContentVc {
+    /// Whether this is the content of a legacy chunk, which is loaded via
+    /// `<script nomodule>`.
+    async fn is_legacy(self) -> Result<bool> {
+        let chunking_context = self.await?.chunking_context;
+        Ok(
+            match DevChunkingContextVc::resolve_from(chunking_context).await? {
+                Some(chunking_context) => *chunking_context.is_legacy().await?,
+                None => false,
+            },
+        )
+    }
+}
+
+/// Transpiles the code of a legacy chunk so it runs in the `environment`.
+async fn downlevel_code(code: Code, environment: EnvironmentVc) -> Result<CodeBuilder> {
+    let code = downlevel_script(
+        StringVc::cell(code.source_code().to_str()?.into_owned()),
+        environment,
+    )
+    .await?;
+    let mut builder = CodeBuilder::default();
+    builder.push_source(&Rope::from(code.clone_value()), None);
+    Ok(builder)
+}
+
 #[turbo_tasks::value_impl]
 impl VersionedContent for EcmascriptDevChunkContent {
     #[turbo_tasks::function]
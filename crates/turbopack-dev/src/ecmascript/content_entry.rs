@@ -31,13 +31,10 @@ pub(super) struct EcmascriptDevChunkContentEntry {
 }
 
 impl EcmascriptDevChunkContentEntry {
-    pub async fn new(
-        chunk_item: EcmascriptChunkItemVc,
-        availability_info: AvailabilityInfo,
-    ) -> Result<Self> {
-        let code = item_code(chunk_item, Value::new(availability_info))
-            .resolve()
-            .await?;
+    /// Creates an entry for code which has already been generated, e.g. from
+    /// a cache.
+    pub async fn from_code(code: CodeVc) -> Result<Self> {
+        let code = code.resolve().await?;
         Ok(EcmascriptDevChunkContentEntry {
             code,
             hash: code.source_code_hash().resolve().await?,
@@ -59,13 +56,27 @@ impl EcmascriptDevChunkContentEntriesVc {
         let chunk_content = chunk_content.await?;
         let availability_info = chunk_content.availability_info;
 
-        let entries: IndexMap<_, _> = chunk_content
+        // The code of each chunk item is generated by a task of its own. All of
+        // these tasks are started before anything is awaited, so code
+        // generation runs in parallel and doesn't wait for the ids of the chunk
+        // items. The entries are only assembled once all of them are done.
+        let codes = chunk_content
             .chunk_items
             .iter()
-            .map(|chunk_item| async move {
+            .map(|&chunk_item| {
+                (
+                    chunk_item,
+                    item_code(chunk_item, Value::new(availability_info)),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let entries: IndexMap<_, _> = codes
+            .into_iter()
+            .map(|(chunk_item, code)| async move {
                 Ok((
                     chunk_item.id().await?,
-                    EcmascriptDevChunkContentEntry::new(*chunk_item, availability_info).await?,
+                    EcmascriptDevChunkContentEntry::from_code(code).await?,
                 ))
             })
             .try_join()
@@ -1,14 +1,17 @@
 use anyhow::{bail, Result};
-use turbo_tasks::TryJoinIterExt;
+use turbo_tasks::{primitives::JsonValueVc, TryJoinIterExt};
 use turbopack_core::{
     asset::AssetContentVc,
-    version::{UpdateVc, VersionVc, VersionedContent, VersionedContentVc},
+    version::{NotFoundVersionVc, UpdateVc, VersionVc, VersionedContent, VersionedContentVc},
 };
 
 use super::{
     super::content::EcmascriptDevChunkContentVc,
     update::update_ecmascript_merged_chunk,
-    version::{EcmascriptDevMergedChunkVersion, EcmascriptDevMergedChunkVersionVc},
+    version::{
+        EcmascriptDevMergedChunkVersion, EcmascriptDevMergedChunkVersionState,
+        EcmascriptDevMergedChunkVersionVc,
+    },
 };
 
 /// Composite [`EcmascriptChunkContent`] that is the result of merging multiple
@@ -59,4 +62,22 @@ impl VersionedContent for EcmascriptDevMergedChunkContent {
             .await?
             .cell())
     }
+
+    #[turbo_tasks::function]
+    async fn restore_version(
+        _self_vc: EcmascriptDevMergedChunkContentVc,
+        state: JsonValueVc,
+    ) -> Result<VersionVc> {
+        // Restoring is best effort, an invalid state results in a total update.
+        let state = serde_json::from_value::<EcmascriptDevMergedChunkVersionState>(
+            state.await?.clone_value(),
+        );
+        let Ok(state) = state else {
+            return Ok(NotFoundVersionVc::new().into());
+        };
+        let Ok(version) = EcmascriptDevMergedChunkVersion::from_state(state).await else {
+            return Ok(NotFoundVersionVc::new().into());
+        };
+        Ok(version.cell().into())
+    }
 }
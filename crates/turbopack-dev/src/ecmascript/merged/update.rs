@@ -3,9 +3,10 @@ use indexmap::{IndexMap, IndexSet};
 use serde::Serialize;
 use turbo_tasks::{primitives::JsonValueVc, TryJoinIterExt};
 use turbo_tasks_fs::rope::Rope;
+use turbo_tasks_hash::encode_hex;
 use turbopack_core::{
     chunk::{Chunk, ChunkingContext, ModuleId, ModuleIdReadRef},
-    code_builder::CodeReadRef,
+    code_builder::{Code, CodeReadRef},
     version::{PartialUpdate, TotalUpdate, Update, VersionVc},
 };
 
@@ -69,29 +70,106 @@ struct EcmascriptMergedChunkPartial {
     deleted: IndexSet<ModuleIdReadRef>,
 }
 
+/// Modules smaller than this are always sent in full, since a diff wouldn't
+/// save much.
+const MIN_DIFF_SIZE: usize = 16 * 1024;
+
 #[derive(Serialize)]
 struct EcmascriptModuleEntry {
-    code: Rope,
+    /// The code of the module, unless it's sent as a `diff`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<Rope>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    diff: Option<CodeDiff>,
+    /// The hex encoded hash of the code, so clients can skip modules they
+    /// already have the code of.
+    hash: String,
     url: String,
     map: Option<String>,
 }
 
+/// The code of a module as a change of its previous code: the previous code
+/// with everything between the unchanged `prefix` and `suffix` replaced by
+/// `insert`. Lengths are in UTF-16 code units, like the indices of JavaScript
+/// strings.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct CodeDiff {
+    /// The length of the previous code, so clients can check that they apply
+    /// the diff to the code it was computed against.
+    base_length: usize,
+    prefix: usize,
+    suffix: usize,
+    insert: String,
+}
+
+impl CodeDiff {
+    fn new(base: &str, code: &str) -> Self {
+        let prefix_bytes: usize = base
+            .chars()
+            .zip(code.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(c, _)| c.len_utf8())
+            .sum();
+        let suffix_bytes: usize = base[prefix_bytes..]
+            .chars()
+            .rev()
+            .zip(code[prefix_bytes..].chars().rev())
+            .take_while(|(a, b)| a == b)
+            .map(|(c, _)| c.len_utf8())
+            .sum();
+        let utf16_len = |text: &str| text.encode_utf16().count();
+        let suffix_start = code.len() - suffix_bytes;
+        CodeDiff {
+            base_length: utf16_len(base),
+            prefix: utf16_len(&code[..prefix_bytes]),
+            suffix: utf16_len(&code[suffix_start..]),
+            insert: code[prefix_bytes..suffix_start].to_string(),
+        }
+    }
+
+    /// The diff of `code` against `base`, unless it's not much smaller than
+    /// the code itself.
+    fn new_if_smaller(base: &Code, code: &Code) -> Result<Option<Self>> {
+        let code = code.source_code();
+        if code.len() < MIN_DIFF_SIZE {
+            return Ok(None);
+        }
+        let diff = Self::new(&base.source_code().to_str()?, &code.to_str()?);
+        Ok((diff.insert.len() * 2 < code.len()).then_some(diff))
+    }
+}
+
 impl EcmascriptModuleEntry {
-    fn new(id: &ModuleId, code: CodeReadRef, chunk_path: &str) -> Self {
+    /// The entry of a module with the `code`. When the client has the `base`
+    /// code of the module, it can be sent as a diff against it.
+    fn new(
+        id: &ModuleId,
+        hash: u64,
+        code: CodeReadRef,
+        base: Option<&CodeReadRef>,
+        chunk_path: &str,
+    ) -> Result<Self> {
         /// serde_qs can't serialize a lone enum when it's [serde::untagged].
         #[derive(Serialize)]
         struct Id<'a> {
             id: &'a ModuleId,
         }
         let id = serde_qs::to_string(&Id { id }).unwrap();
-        EcmascriptModuleEntry {
+        let diff = match base {
+            Some(base) => CodeDiff::new_if_smaller(base, &code)?,
+            None => None,
+        };
+        Ok(EcmascriptModuleEntry {
             // Cloning a rope is cheap.
-            code: code.source_code().clone(),
+            code: diff.is_none().then(|| code.source_code().clone()),
+            diff,
+            hash: encode_hex(hash),
             url: format!("/{}?{}", chunk_path, &id),
             map: code
                 .has_source_map()
                 .then(|| format!("/__turbopack_sourcemap__/{}.map?{}", chunk_path, &id)),
-        }
+        })
     }
 }
 
@@ -174,61 +252,70 @@ pub(super) async fn update_ecmascript_merged_chunk(
             continue;
         };
 
-        let chunk_update = if let Some(from_version) =
-            from_versions_by_chunk_path.remove(chunk_path)
-        {
-            // The chunk was present in the previous version, so we must update it.
-            let update = update_ecmascript_chunk(*content, from_version).await?;
+        let chunk_update =
+            if let Some(from_version) = from_versions_by_chunk_path.remove(chunk_path) {
+                // The chunk was present in the previous version, so we must update it.
+                let update = update_ecmascript_chunk(*content, from_version).await?;
 
-            match update {
-                EcmascriptChunkUpdate::None => {
-                    // Nothing changed, so we can skip this chunk.
-                    continue;
-                }
-                EcmascriptChunkUpdate::Partial(chunk_partial) => {
-                    // The chunk was updated.
-                    let mut partial = EcmascriptMergedChunkPartial::default();
+                match update {
+                    EcmascriptChunkUpdate::None => {
+                        // Nothing changed, so we can skip this chunk.
+                        continue;
+                    }
+                    EcmascriptChunkUpdate::Partial(chunk_partial) => {
+                        // The chunk was updated.
+                        let mut partial = EcmascriptMergedChunkPartial::default();
+
+                        for (module_id, (module_hash, module_code)) in chunk_partial.added {
+                            partial.added.insert(module_id.clone());
+
+                            if merged_module_map.get(&module_id) != Some(module_hash) {
+                                let entry = EcmascriptModuleEntry::new(
+                                    &module_id,
+                                    module_hash,
+                                    module_code.clone(),
+                                    None,
+                                    chunk_path,
+                                )?;
+                                merged_update.entries.insert(module_id, entry);
+                            }
+                        }
 
-                    for (module_id, (module_hash, module_code)) in chunk_partial.added {
-                        partial.added.insert(module_id.clone());
+                        partial.deleted.extend(chunk_partial.deleted.into_keys());
 
-                        if merged_module_map.get(&module_id) != Some(module_hash) {
+                        for (module_id, (module_hash, module_code)) in chunk_partial.modified {
+                            // The client has the code of the previous version.
+                            let base = from_version.entries_code.0.get(&module_id);
                             let entry = EcmascriptModuleEntry::new(
                                 &module_id,
-                                module_code.clone(),
+                                module_hash,
+                                module_code,
+                                base,
                                 chunk_path,
-                            );
+                            )?;
                             merged_update.entries.insert(module_id, entry);
                         }
-                    }
 
-                    partial.deleted.extend(chunk_partial.deleted.into_keys());
-
-                    for (module_id, module_code) in chunk_partial.modified {
-                        let entry = EcmascriptModuleEntry::new(&module_id, module_code, chunk_path);
-                        merged_update.entries.insert(module_id, entry);
+                        EcmascriptMergedChunkUpdate::Partial(partial)
                     }
-
-                    EcmascriptMergedChunkUpdate::Partial(partial)
                 }
-            }
-        } else {
-            // The chunk was added in this version.
-            let mut added = EcmascriptMergedChunkAdded::default();
-
-            for (id, entry) in &content_ref.entries.await? {
-                let hash = *entry.hash.await?;
-                let code = entry.code.await?;
-                added.modules.insert(id.clone());
-
-                if merged_module_map.get(id) != Some(hash) {
-                    let entry = EcmascriptModuleEntry::new(id, code, chunk_path);
-                    merged_update.entries.insert(id.clone(), entry);
+            } else {
+                // The chunk was added in this version.
+                let mut added = EcmascriptMergedChunkAdded::default();
+
+                for (id, entry) in &content_ref.entries.await? {
+                    let hash = *entry.hash.await?;
+                    let code = entry.code.await?;
+                    added.modules.insert(id.clone());
+
+                    if merged_module_map.get(id) != Some(hash) {
+                        let entry = EcmascriptModuleEntry::new(id, hash, code, None, chunk_path)?;
+                        merged_update.entries.insert(id.clone(), entry);
+                    }
                 }
-            }
 
-            EcmascriptMergedChunkUpdate::Added(added)
-        };
+                EcmascriptMergedChunkUpdate::Added(added)
+            };
 
         merged_update.chunks.insert(chunk_path, chunk_update);
     }
@@ -255,3 +342,168 @@ pub(super) async fn update_ecmascript_merged_chunk(
 
     Ok(update)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        process::{Command, Stdio},
+    };
+
+    use anyhow::{bail, Result};
+    use serde_json::json;
+    use turbo_tasks::TurboTasks;
+    use turbo_tasks_memory::MemoryBackend;
+    use turbopack_ecmascript::chunk::{EcmascriptChunkItemContent, EcmascriptChunkItemOptions};
+
+    use super::CodeDiff;
+    use crate::ecmascript::module_factory::module_factory;
+
+    /// Applies the `diff` to the `base` on UTF-16 code units, like the runtime.
+    fn apply(base: &str, diff: &CodeDiff) -> String {
+        let base: Vec<u16> = base.encode_utf16().collect();
+        assert_eq!(base.len(), diff.base_length);
+        let mut code = base[..diff.prefix].to_vec();
+        code.extend(diff.insert.encode_utf16());
+        code.extend(&base[base.len() - diff.suffix..]);
+        String::from_utf16(&code).unwrap()
+    }
+
+    fn assert_diff(base: &str, code: &str, expected: CodeDiff) {
+        let diff = CodeDiff::new(base, code);
+        assert_eq!(diff, expected);
+        assert_eq!(apply(base, &diff), code);
+    }
+
+    #[test]
+    fn diffs_ascii() {
+        assert_diff(
+            "let a = 1;",
+            "let a = 23;",
+            CodeDiff {
+                base_length: 10,
+                prefix: 8,
+                suffix: 1,
+                insert: "23".to_string(),
+            },
+        );
+        assert_diff(
+            "aa",
+            "aaa",
+            CodeDiff {
+                base_length: 2,
+                prefix: 2,
+                suffix: 0,
+                insert: "a".to_string(),
+            },
+        );
+    }
+
+    #[test]
+    fn counts_non_ascii_in_utf16_code_units() {
+        assert_diff(
+            r#"const s = "héllo 日本";"#,
+            r#"const s = "hällo 日本";"#,
+            CodeDiff {
+                base_length: 21,
+                prefix: 12,
+                suffix: 8,
+                insert: "ä".to_string(),
+            },
+        );
+    }
+
+    #[test]
+    fn keeps_surrogate_pairs_together() {
+        // U+1F600 and U+1F601 only differ in their low surrogate, so a diff of
+        // the code units would split the pair.
+        assert_diff(
+            "a 😀 b 😀 c",
+            "a 😀 b 😁 c",
+            CodeDiff {
+                base_length: 11,
+                prefix: 7,
+                suffix: 2,
+                insert: "😁".to_string(),
+            },
+        );
+    }
+
+    /// The module factory of a chunk item with the `inner_code`.
+    async fn factory_code(inner_code: &str, this: bool) -> Result<String> {
+        let content = EcmascriptChunkItemContent {
+            inner_code: inner_code.to_string().into(),
+            options: EcmascriptChunkItemOptions {
+                this,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+        .cell();
+        Ok(module_factory(content)
+            .await?
+            .source_code()
+            .to_str()?
+            .into_owned())
+    }
+
+    /// Loads a chunk with the `base` factory of a module into the runtime and
+    /// returns the code the runtime resolves the `diff` of the module to.
+    fn resolve_in_runtime(base: &str, diff: &CodeDiff) -> Result<String> {
+        const HARNESS: &str = r#"
+const vm = require("vm");
+const { runtime, chunk, entries } = JSON.parse(require("fs").readFileSync(0, "utf8"));
+vm.runInThisContext(chunk);
+const resolveModuleEntries = new Function(
+  "BACKEND",
+  "RUNTIME_ERROR_HOOK",
+  `${runtime}\nreturn resolveModuleEntries;`
+)({ registerChunk() {} }, null);
+process.stdout.write(JSON.stringify(resolveModuleEntries(entries).module.code));
+"#;
+        let chunk = format!(
+            "(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([\"chunk.js\", \
+             {{\n\"module\": {base},\n}}]);\n"
+        );
+        let input = json!({
+            "runtime": include_str!("../../../js/src/runtime.js"),
+            "chunk": chunk,
+            "entries": { "module": { "diff": diff, "hash": "0" } },
+        });
+        let mut node = Command::new("node")
+            .args(["-e", HARNESS])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        node.stdin
+            .take()
+            .unwrap()
+            .write_all(input.to_string().as_bytes())?;
+        let output = node.wait_with_output()?;
+        if !output.status.success() {
+            bail!(
+                "node failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(serde_json::from_slice(&output.stdout)?)
+    }
+
+    #[tokio::test]
+    async fn applies_to_modules_loaded_from_chunks() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            for this in [false, true] {
+                let base = factory_code("console.log(\"héllo 😀\");", this).await?;
+                let code = factory_code("console.log(\"hällo 😁\");", this).await?;
+                let diff = CodeDiff::new(&base, &code);
+                assert_eq!(resolve_in_runtime(&base, &diff)?, code);
+            }
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+}
@@ -1,9 +1,15 @@
 use anyhow::Result;
-use turbo_tasks::{primitives::StringVc, ReadRef, TryJoinIterExt};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{
+    primitives::{JsonValueVc, StringVc},
+    ReadRef, TryJoinIterExt,
+};
 use turbo_tasks_hash::{encode_hex, Xxh3Hash64Hasher};
 use turbopack_core::version::{Version, VersionVc};
 
-use super::super::version::EcmascriptDevChunkVersionReadRef;
+use super::super::version::{
+    EcmascriptDevChunkVersion, EcmascriptDevChunkVersionReadRef, EcmascriptDevChunkVersionState,
+};
 
 /// The version of a [`super::content::EcmascriptMergedChunkContent`]. This is
 /// essentially a composite [`EcmascriptChunkVersion`].
@@ -13,6 +19,34 @@ pub(super) struct EcmascriptDevMergedChunkVersion {
     pub(super) versions: Vec<EcmascriptDevChunkVersionReadRef>,
 }
 
+/// The serializable state of an [`EcmascriptDevMergedChunkVersion`]. It is
+/// tagged so it can be told apart from the states of other merged versions.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub(super) enum EcmascriptDevMergedChunkVersionState {
+    EcmascriptDevMergedChunkVersion {
+        chunks: Vec<EcmascriptDevChunkVersionState>,
+    },
+}
+
+impl EcmascriptDevMergedChunkVersion {
+    pub(super) async fn from_state(state: EcmascriptDevMergedChunkVersionState) -> Result<Self> {
+        let EcmascriptDevMergedChunkVersionState::EcmascriptDevMergedChunkVersion { chunks } =
+            state;
+        let versions = chunks
+            .into_iter()
+            .map(|chunk| async move {
+                EcmascriptDevChunkVersion::from_state(chunk)
+                    .await?
+                    .cell()
+                    .await
+            })
+            .try_join()
+            .await?;
+        Ok(EcmascriptDevMergedChunkVersion { versions })
+    }
+}
+
 #[turbo_tasks::value_impl]
 impl Version for EcmascriptDevMergedChunkVersion {
     #[turbo_tasks::function]
@@ -36,4 +70,16 @@ impl Version for EcmascriptDevMergedChunkVersion {
         let hex_hash = encode_hex(hash);
         Ok(StringVc::cell(hex_hash))
     }
+
+    #[turbo_tasks::function]
+    fn state(&self) -> Result<JsonValueVc> {
+        let state = EcmascriptDevMergedChunkVersionState::EcmascriptDevMergedChunkVersion {
+            chunks: self
+                .versions
+                .iter()
+                .map(|version| version.to_state())
+                .collect(),
+        };
+        Ok(JsonValueVc::cell(serde_json::to_value(state)?))
+    }
 }
@@ -4,4 +4,5 @@ pub(crate) mod merged;
 pub(crate) mod module_factory;
 pub(crate) mod runtime;
 pub(crate) mod update;
+pub(crate) mod vendor;
 pub(crate) mod version;
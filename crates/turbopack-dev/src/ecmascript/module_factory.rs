@@ -4,6 +4,9 @@ use anyhow::Result;
 use turbopack_core::code_builder::{CodeBuilder, CodeVc};
 use turbopack_ecmascript::{chunk::EcmascriptChunkItemContentVc, utils::FormatIter};
 
+/// Wraps the code of a chunk item in a module factory. The factory is a
+/// parenthesized function expression and nothing else, since the runtime diffs
+/// updates against its source text (see `moduleFactorySource`).
 #[turbo_tasks::function]
 pub(super) async fn module_factory(content: EcmascriptChunkItemContentVc) -> Result<CodeVc> {
     let content = content.await?;
@@ -16,7 +19,9 @@ pub(super) async fn module_factory(content: EcmascriptChunkItemContentVc) -> Res
         "c: __turbopack_cache__",
         "l: __turbopack_load__",
         "k: __turbopack_register_chunk_list__",
+        "h: __turbopack_prefetch__",
         "j: __turbopack_cjs__",
+        "f: __turbopack_refresh__",
         "p: process",
         "g: global",
         // HACK
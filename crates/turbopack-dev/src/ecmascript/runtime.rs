@@ -11,7 +11,10 @@ use turbopack_ecmascript::chunk::{
     EcmascriptChunkRuntimeVc, EcmascriptChunkVc, EcmascriptChunkingContextVc,
 };
 
-use crate::ecmascript::content::EcmascriptDevChunkContentVc;
+use crate::ecmascript::{
+    content::EcmascriptDevChunkContentVc,
+    vendor::{chunk_vendor_package, vendor_cache_reference},
+};
 
 /// Development runtime for Ecmascript chunks.
 #[turbo_tasks::value(shared)]
@@ -113,7 +116,7 @@ impl EcmascriptChunkRuntime for EcmascriptDevChunkRuntime {
     }
 
     #[turbo_tasks::function]
-    fn references(&self, origin_chunk: EcmascriptChunkVc) -> AssetReferencesVc {
+    async fn references(&self, origin_chunk: EcmascriptChunkVc) -> Result<AssetReferencesVc> {
         let Self {
             chunk_group,
             chunking_context,
@@ -128,7 +131,13 @@ impl EcmascriptChunkRuntime for EcmascriptDevChunkRuntime {
                 ChunkListReferenceVc::new(chunking_context.output_root(), chunk_group).into(),
             );
         }
-        AssetReferencesVc::cell(references)
+        if let Some(package) = *chunk_vendor_package(origin_chunk, *chunking_context).await? {
+            references.push(vendor_cache_reference(
+                origin_chunk.chunk_content(),
+                package,
+            ));
+        }
+        Ok(AssetReferencesVc::cell(references))
     }
 
     #[turbo_tasks::function]
@@ -12,7 +12,7 @@ pub(super) enum EcmascriptChunkUpdate {
 pub(super) struct EcmascriptChunkPartialUpdate {
     pub added: IndexMap<ModuleIdReadRef, (u64, CodeReadRef)>,
     pub deleted: IndexMap<ModuleIdReadRef, u64>,
-    pub modified: IndexMap<ModuleIdReadRef, CodeReadRef>,
+    pub modified: IndexMap<ModuleIdReadRef, (u64, CodeReadRef)>,
 }
 
 pub(super) async fn update_ecmascript_chunk(
@@ -37,8 +37,9 @@ pub(super) async fn update_ecmascript_chunk(
 
     for (id, from_hash) in &from.entries_hashes {
         if let Some(entry) = entries.get(id) {
-            if *entry.hash.await? != *from_hash {
-                modified.insert(id.clone(), entry.code.await?);
+            let hash = *entry.hash.await?;
+            if hash != *from_hash {
+                modified.insert(id.clone(), (hash, entry.code.await?));
             }
         } else {
             deleted.insert(id.clone(), *from_hash);
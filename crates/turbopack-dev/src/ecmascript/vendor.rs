@@ -0,0 +1,266 @@
+//! Prebundling of heavy dependencies, like `react-dom` or `lodash`.
+//!
+//! Packages from `node_modules` rarely change during development, but large
+//! ones take a considerable amount of time to generate code for on every
+//! start. When vendor prebundling is enabled, the modules of such packages are
+//! minified, and the code of chunks of a package is cached on disk. The cache
+//! file is emitted alongside the chunk. After a restart, the cached code is
+//! used as long as the modules of the chunk are the same.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{primitives::StringVc, TryJoinIterExt, ValueToString};
+use turbo_tasks_fs::{File, FileContent, FileJsonContent, FileSystemPathVc};
+use turbo_tasks_hash::{encode_hex, DeterministicHash, Xxh3Hash64Hasher};
+use turbopack_core::{
+    asset::{Asset, AssetContentVc, AssetVc},
+    chunk::ChunkItem,
+    code_builder::CodeBuilder,
+    ident::AssetIdentVc,
+    reference::{AssetReferenceVc, SingleAssetReferenceVc},
+    source_map::{GenerateSourceMap, OptionSourceMapVc, SourceMap, SourceMapVc},
+};
+use turbopack_ecmascript::chunk::{
+    EcmascriptChunkContentVc, EcmascriptChunkVc, EcmascriptChunkingContextVc,
+};
+
+use super::content_entry::{EcmascriptDevChunkContentEntriesVc, EcmascriptDevChunkContentEntry};
+use crate::DevChunkingContextVc;
+
+/// A package from `node_modules` whose modules are prebundled.
+#[turbo_tasks::value(shared)]
+pub(crate) struct VendorPackage {
+    pub name: String,
+    pub version: String,
+    pub cache_dir: FileSystemPathVc,
+}
+
+#[turbo_tasks::value(transparent)]
+pub(crate) struct OptionVendorPackage(Option<VendorPackageVc>);
+
+/// The path of the package directory that contains `path`, based on the last
+/// `node_modules` directory in it.
+fn package_root(path: &str) -> Option<&str> {
+    let (_, rest) = path.rsplit_once("node_modules/")?;
+    let start = path.len() - rest.len();
+    let end = if rest.starts_with('@') {
+        rest.match_indices('/').nth(1).map(|(index, _)| index)
+    } else {
+        rest.find('/')
+    };
+    end.map(|end| &path[..start + end])
+}
+
+/// Returns the package that the module or chunk at `path` is prebundled for,
+/// if any. Only packages with a name and a version are prebundled.
+#[turbo_tasks::function]
+pub(crate) async fn vendor_package(
+    path: FileSystemPathVc,
+    cache_dir: FileSystemPathVc,
+) -> Result<OptionVendorPackageVc> {
+    let Some(root) = package_root(&path.await?.path).map(|root| root.to_string()) else {
+        return Ok(OptionVendorPackageVc::cell(None));
+    };
+    let package_json = path
+        .root()
+        .join(&root)
+        .join("package.json")
+        .read_json()
+        .await?;
+    let FileJsonContent::Content(package_json) = &*package_json else {
+        return Ok(OptionVendorPackageVc::cell(None));
+    };
+    let (Some(name), Some(version)) = (
+        package_json["name"].as_str(),
+        package_json["version"].as_str(),
+    ) else {
+        return Ok(OptionVendorPackageVc::cell(None));
+    };
+    Ok(OptionVendorPackageVc::cell(Some(
+        VendorPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            cache_dir,
+        }
+        .cell(),
+    )))
+}
+
+/// Returns the package that `chunk` is prebundled for, if any.
+#[turbo_tasks::function]
+pub(super) async fn chunk_vendor_package(
+    chunk: EcmascriptChunkVc,
+    chunking_context: EcmascriptChunkingContextVc,
+) -> Result<OptionVendorPackageVc> {
+    let Some(chunking_context) =
+        DevChunkingContextVc::resolve_from(chunking_context).await? else {
+        return Ok(OptionVendorPackageVc::cell(None));
+    };
+    Ok(match *chunking_context.vendor_cache_dir().await? {
+        Some(cache_dir) => vendor_package(chunk.ident().path(), cache_dir),
+        None => OptionVendorPackageVc::cell(None),
+    })
+}
+
+/// The cache file of a chunk of `package`. Its name covers everything the code
+/// of the chunk depends on.
+#[turbo_tasks::function]
+async fn vendor_cache_file(
+    chunk_content: EcmascriptChunkContentVc,
+    package: VendorPackageVc,
+) -> Result<FileSystemPathVc> {
+    let package = package.await?;
+    let chunk_items = &chunk_content.await?.chunk_items;
+    let ids = chunk_items.iter().map(|item| item.id()).try_join().await?;
+    let sources = chunk_items
+        .iter()
+        .map(|item| item.asset_ident().path().read())
+        .try_join()
+        .await?;
+
+    let mut hasher = Xxh3Hash64Hasher::new();
+    env!("CARGO_PKG_VERSION").deterministic_hash(&mut hasher);
+    package.name.deterministic_hash(&mut hasher);
+    package.version.deterministic_hash(&mut hasher);
+    for ((item, id), source) in chunk_items.iter().zip(ids.iter()).zip(sources.iter()) {
+        item.asset_ident()
+            .to_string()
+            .await?
+            .deterministic_hash(&mut hasher);
+        id.deterministic_hash(&mut hasher);
+        match &**source {
+            FileContent::Content(file) => file.content().deterministic_hash(&mut hasher),
+            FileContent::NotFound => 0_u8.deterministic_hash(&mut hasher),
+        }
+    }
+    Ok(package.cache_dir.join(&format!(
+        "{}@{}-{}.json",
+        package.name.replace('/', "+"),
+        package.version,
+        encode_hex(hasher.finish())
+    )))
+}
+
+/// The code of a chunk item and its source map, as stored in the cache file.
+#[derive(Serialize, Deserialize)]
+struct CachedEntry {
+    code: String,
+    map: Option<String>,
+}
+
+/// A source map read from the cache file.
+#[turbo_tasks::value(shared)]
+struct CachedSourceMap {
+    map: SourceMapVc,
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for CachedSourceMap {
+    #[turbo_tasks::function]
+    fn generate_source_map(&self) -> OptionSourceMapVc {
+        OptionSourceMapVc::cell(Some(self.map))
+    }
+}
+
+/// The entries of a chunk of `package`. The code of the chunk items is read
+/// from the cache file when it exists.
+#[turbo_tasks::function]
+pub(super) async fn vendor_entries(
+    chunk_content: EcmascriptChunkContentVc,
+    package: VendorPackageVc,
+) -> Result<EcmascriptDevChunkContentEntriesVc> {
+    let cache_file = vendor_cache_file(chunk_content, package);
+    let FileContent::Content(file) = &*cache_file.read().await? else {
+        return Ok(EcmascriptDevChunkContentEntriesVc::new(chunk_content));
+    };
+    let Ok(cached) = serde_json::from_reader::<_, Vec<CachedEntry>>(file.read()) else {
+        return Ok(EcmascriptDevChunkContentEntriesVc::new(chunk_content));
+    };
+    let chunk_items = &chunk_content.await?.chunk_items;
+    if cached.len() != chunk_items.len() {
+        return Ok(EcmascriptDevChunkContentEntriesVc::new(chunk_content));
+    }
+    let entries = chunk_items
+        .iter()
+        .zip(cached)
+        .map(|(item, cached)| async move {
+            let map = match cached.map {
+                Some(map) => Some(
+                    CachedSourceMap {
+                        map: SourceMap::from_json(map.as_bytes())?.cell(),
+                    }
+                    .cell()
+                    .into(),
+                ),
+                None => None,
+            };
+            let mut builder = CodeBuilder::default();
+            builder.push_source(&cached.code.into(), map);
+            Ok((
+                item.id().await?,
+                EcmascriptDevChunkContentEntry::from_code(builder.build().cell()).await?,
+            ))
+        })
+        .try_join()
+        .await?;
+    Ok(EcmascriptDevChunkContentEntriesVc::cell(
+        entries.into_iter().collect(),
+    ))
+}
+
+/// The cache file of a chunk of a prebundled package. Its content is the code
+/// of the chunk items, so it's written when the chunk is emitted.
+#[turbo_tasks::value]
+struct VendorCacheAsset {
+    chunk_content: EcmascriptChunkContentVc,
+    package: VendorPackageVc,
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for VendorCacheAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> AssetIdentVc {
+        AssetIdentVc::from_path(vendor_cache_file(self.chunk_content, self.package))
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<AssetContentVc> {
+        let entries = vendor_entries(self.chunk_content, self.package).await?;
+        let cached = entries
+            .values()
+            .map(|entry| async move {
+                let code = entry.code.await?;
+                let map = match *entry.code.generate_source_map().await? {
+                    Some(map) => Some(map.to_rope().await?.to_str()?.into_owned()),
+                    None => None,
+                };
+                Ok(CachedEntry {
+                    code: code.source_code().to_str()?.into_owned(),
+                    map,
+                })
+            })
+            .try_join()
+            .await?;
+        Ok(File::from(serde_json::to_string(&cached)?).into())
+    }
+}
+
+#[turbo_tasks::function]
+fn vendor_cache_description() -> StringVc {
+    StringVc::cell("vendor cache".to_string())
+}
+
+/// A reference from a chunk of a prebundled package to its cache file.
+#[turbo_tasks::function]
+pub(super) fn vendor_cache_reference(
+    chunk_content: EcmascriptChunkContentVc,
+    package: VendorPackageVc,
+) -> AssetReferenceVc {
+    let asset: AssetVc = VendorCacheAsset {
+        chunk_content,
+        package,
+    }
+    .cell()
+    .into();
+    SingleAssetReferenceVc::new(asset, vendor_cache_description()).into()
+}
@@ -1,21 +1,50 @@
 use anyhow::{bail, Result};
 use indexmap::IndexMap;
-use turbo_tasks::primitives::StringVc;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{
+    primitives::{JsonValueVc, StringVc},
+    ReadRef,
+};
 use turbo_tasks_fs::FileSystemPathVc;
 use turbo_tasks_hash::{encode_hex, Xxh3Hash64Hasher};
 use turbopack_core::{
-    chunk::ModuleIdReadRef,
+    chunk::{ModuleId, ModuleIdReadRef, ModuleIdVc},
+    code_builder::CodeReadRef,
     version::{Version, VersionVc},
 };
 
 use super::content_entry::EcmascriptDevChunkContentEntriesVc;
 
-#[turbo_tasks::value(serialization = "none")]
+#[turbo_tasks::value(serialization = "none", shared)]
 pub(super) struct EcmascriptDevChunkVersion {
     pub(super) chunk_path: String,
     pub(super) entries_hashes: IndexMap<ModuleIdReadRef, u64>,
+    /// The code of the entries, so updates can send diffs against it. It's
+    /// empty for versions restored from a state.
+    #[turbo_tasks(trace_ignore, debug_ignore)]
+    pub(super) entries_code: EntriesCode,
+}
+
+/// The code of the entries of an [EcmascriptDevChunkVersion]. It's compared by
+/// identity, since the `entries_hashes` cover the content already.
+#[derive(Default, Clone)]
+pub(super) struct EntriesCode(pub(super) IndexMap<ModuleIdReadRef, CodeReadRef>);
+
+impl PartialEq for EntriesCode {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && self
+                .0
+                .iter()
+                .zip(other.0.iter())
+                .all(|((id, code), (other_id, other_code))| {
+                    id == other_id && ReadRef::ptr_eq(code, other_code)
+                })
+    }
 }
 
+impl Eq for EntriesCode {}
+
 #[turbo_tasks::value_impl]
 impl EcmascriptDevChunkVersionVc {
     #[turbo_tasks::function]
@@ -37,17 +66,59 @@ impl EcmascriptDevChunkVersionVc {
         };
         let entries = entries.await?;
         let mut entries_hashes = IndexMap::with_capacity(entries.len());
+        let mut entries_code = IndexMap::with_capacity(entries.len());
         for (id, entry) in entries.iter() {
             entries_hashes.insert(id.clone(), *entry.hash.await?);
+            entries_code.insert(id.clone(), entry.code.await?);
         }
         Ok(EcmascriptDevChunkVersion {
             chunk_path: chunk_path.to_string(),
             entries_hashes,
+            entries_code: EntriesCode(entries_code),
         }
         .cell())
     }
 }
 
+/// The serializable state of an [`EcmascriptDevChunkVersion`], from which it
+/// can be restored.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct EcmascriptDevChunkVersionState {
+    chunk_path: String,
+    /// Module ids with their hex encoded hashes. These are strings since
+    /// JavaScript numbers can't represent all 64 bit hashes.
+    entries: Vec<(ModuleId, String)>,
+}
+
+impl EcmascriptDevChunkVersion {
+    pub(super) fn to_state(&self) -> EcmascriptDevChunkVersionState {
+        EcmascriptDevChunkVersionState {
+            chunk_path: self.chunk_path.clone(),
+            entries: self
+                .entries_hashes
+                .iter()
+                .map(|(id, hash)| ((**id).clone(), encode_hex(*hash)))
+                .collect(),
+        }
+    }
+
+    pub(super) async fn from_state(state: EcmascriptDevChunkVersionState) -> Result<Self> {
+        let mut entries_hashes = IndexMap::with_capacity(state.entries.len());
+        for (id, hash) in state.entries {
+            let Ok(hash) = u64::from_str_radix(&hash, 16) else {
+                bail!("invalid hash {} of module {} in chunk version state", hash, id);
+            };
+            entries_hashes.insert(ModuleIdVc::cell(id).await?, hash);
+        }
+        Ok(EcmascriptDevChunkVersion {
+            chunk_path: state.chunk_path,
+            entries_hashes,
+            entries_code: EntriesCode::default(),
+        })
+    }
+}
+
 #[turbo_tasks::value_impl]
 impl Version for EcmascriptDevChunkVersion {
     #[turbo_tasks::function]
@@ -65,4 +136,9 @@ impl Version for EcmascriptDevChunkVersion {
         let hex_hash = encode_hex(hash);
         StringVc::cell(hex_hash)
     }
+
+    #[turbo_tasks::function]
+    fn state(&self) -> Result<JsonValueVc> {
+        Ok(JsonValueVc::cell(serde_json::to_value(self.to_state())?))
+    }
 }
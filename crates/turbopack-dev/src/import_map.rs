@@ -0,0 +1,62 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::Serialize;
+use turbo_tasks::{primitives::StringVc, TryJoinIterExt};
+use turbopack_core::{
+    chunk::{Chunk, ChunkGroupVc, ChunkVc, ChunkingContext, ChunkingContextVc},
+    reference::all_assets,
+};
+
+/// Chunks are imported as `{CHUNK_SPECIFIER_PREFIX}{chunk path}` when using
+/// import map chunk loading.
+pub const CHUNK_SPECIFIER_PREFIX: &str = "@turbopack/chunks/";
+
+#[derive(Serialize)]
+struct ImportMap {
+    imports: BTreeMap<String, String>,
+}
+
+/// Creates the import map (as JSON) for pages that load the chunk groups with
+/// import map chunk loading. It maps the specifiers of all JS chunks that can
+/// be loaded from these chunk groups, including async chunks, to their URLs.
+///
+/// The import map needs to be inlined into a `<script type="importmap">`
+/// before any chunk is loaded.
+#[turbo_tasks::function]
+pub async fn chunk_import_map(
+    chunking_context: ChunkingContextVc,
+    chunk_groups: Vec<ChunkGroupVc>,
+) -> Result<StringVc> {
+    let output_root = chunking_context.output_root().await?;
+    let mut imports = BTreeMap::new();
+    for chunk_group in chunk_groups {
+        for chunk in chunk_group.chunks().await?.iter() {
+            let assets = all_assets(chunk.as_asset()).await?;
+            let chunks = assets
+                .iter()
+                .map(|asset| async move {
+                    Ok(match ChunkVc::resolve_from(*asset).await? {
+                        Some(chunk) => Some(chunk.path().await?),
+                        None => None,
+                    })
+                })
+                .try_join()
+                .await?;
+            for path in chunks.into_iter().flatten() {
+                if !path.path.ends_with(".js") {
+                    continue;
+                }
+                if let Some(chunk_path) = output_root.get_path_to(&path) {
+                    imports.insert(
+                        format!("{CHUNK_SPECIFIER_PREFIX}{chunk_path}"),
+                        format!("/{chunk_path}"),
+                    );
+                }
+            }
+        }
+    }
+    Ok(StringVc::cell(serde_json::to_string(&ImportMap {
+        imports,
+    })?))
+}
@@ -3,8 +3,12 @@
 
 pub(crate) mod chunking_context;
 pub(crate) mod ecmascript;
+pub mod import_map;
 
-pub use chunking_context::{DevChunkingContext, DevChunkingContextBuilder, DevChunkingContextVc};
+pub use chunking_context::{
+    ChunkRuntimeFormat, ChunkRuntimeFormatVc, DevChunkingContext, DevChunkingContextBuilder,
+    DevChunkingContextVc,
+};
 
 pub fn register() {
     turbo_tasks::register();
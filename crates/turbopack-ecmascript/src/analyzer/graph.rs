@@ -1115,6 +1115,31 @@ impl VisitAstPath for Analyzer<'_> {
                 }
             }
         }
+        // new Function("return x"), which behaves like calling Function.
+        if let box Expr::Ident(ref callee) = &new_expr.callee {
+            if &*callee.sym == "Function"
+                && is_unresolved(callee, self.eval_context.unresolved_mark)
+            {
+                let args = new_expr
+                    .args
+                    .iter()
+                    .flatten()
+                    .map(|arg| {
+                        if arg.spread.is_some() {
+                            EffectArg::Spread
+                        } else {
+                            EffectArg::Value(self.eval_context.eval(&arg.expr))
+                        }
+                    })
+                    .collect();
+                self.add_effect(Effect::Call {
+                    func: self.eval_context.eval(&new_expr.callee),
+                    args,
+                    ast_path: as_parent_path(ast_path),
+                    span: new_expr.span(),
+                });
+            }
+        }
         new_expr.visit_children_with_path(self, ast_path);
     }
 
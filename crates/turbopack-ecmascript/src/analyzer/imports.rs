@@ -2,10 +2,13 @@ use std::{collections::BTreeMap, fmt::Display, mem::take};
 
 use indexmap::{IndexMap, IndexSet};
 use once_cell::sync::Lazy;
-use swc_core::ecma::{
-    ast::*,
-    atoms::{js_word, JsWord},
-    visit::{Visit, VisitWith},
+use swc_core::{
+    common::Span,
+    ecma::{
+        ast::*,
+        atoms::{js_word, JsWord},
+        visit::{Visit, VisitWith},
+    },
 };
 
 use super::{JsValue, ModuleValue};
@@ -97,6 +100,10 @@ pub(crate) struct ImportMap {
     /// Ordered list of imported symbols
     references: IndexSet<ImportMapReference>,
 
+    /// The span of the module specifier of the first import of every
+    /// reference, by index in references
+    reference_spans: Vec<Span>,
+
     /// True, when the module has exports
     has_exports: bool,
 }
@@ -156,6 +163,11 @@ impl ImportMap {
         self.references.iter()
     }
 
+    /// The span of the module specifier of the reference at index `i`.
+    pub fn reference_span(&self, i: usize) -> Span {
+        self.reference_spans[i]
+    }
+
     pub fn reexports(&self) -> impl Iterator<Item = (usize, &Reexport)> {
         self.reexports.iter().map(|(i, r)| (*i, r))
     }
@@ -181,6 +193,7 @@ struct Analyzer<'a> {
 impl<'a> Analyzer<'a> {
     fn ensure_reference(
         &mut self,
+        span: Span,
         module_path: JsWord,
         imported_symbol: ImportedSymbol,
         annotations: ImportAnnotations,
@@ -195,6 +208,7 @@ impl<'a> Analyzer<'a> {
         } else {
             let i = self.data.references.len();
             self.data.references.insert(r);
+            self.data.reference_spans.push(span);
             i
         }
     }
@@ -246,6 +260,7 @@ impl Visit for Analyzer<'_> {
         let annotations = take(&mut self.current_annotations);
 
         self.ensure_reference(
+            import.src.span,
             import.src.value.clone(),
             ImportedSymbol::ModuleEvaluation,
             annotations.clone(),
@@ -253,7 +268,12 @@ impl Visit for Analyzer<'_> {
 
         for s in &import.specifiers {
             let symbol = get_import_symbol_from_import(s);
-            let i = self.ensure_reference(import.src.value.clone(), symbol, annotations.clone());
+            let i = self.ensure_reference(
+                import.src.span,
+                import.src.value.clone(),
+                symbol,
+                annotations.clone(),
+            );
 
             let (local, orig_sym) = match s {
                 ImportSpecifier::Named(ImportNamedSpecifier {
@@ -278,11 +298,13 @@ impl Visit for Analyzer<'_> {
 
         let annotations = take(&mut self.current_annotations);
         self.ensure_reference(
+            export.src.span,
             export.src.value.clone(),
             ImportedSymbol::ModuleEvaluation,
             annotations.clone(),
         );
         let i = self.ensure_reference(
+            export.src.span,
             export.src.value.clone(),
             ImportedSymbol::Namespace,
             annotations,
@@ -296,6 +318,7 @@ impl Visit for Analyzer<'_> {
             let annotations = take(&mut self.current_annotations);
 
             self.ensure_reference(
+                src.span,
                 src.value.clone(),
                 ImportedSymbol::ModuleEvaluation,
                 annotations.clone(),
@@ -304,7 +327,8 @@ impl Visit for Analyzer<'_> {
             for spec in export.specifiers.iter() {
                 let symbol = get_import_symbol_from_export(spec);
 
-                let i = self.ensure_reference(src.value.clone(), symbol, annotations.clone());
+                let i =
+                    self.ensure_reference(src.span, src.value.clone(), symbol, annotations.clone());
 
                 match spec {
                     ExportSpecifier::Namespace(n) => {
@@ -3017,7 +3017,7 @@ mod tests {
     };
     use turbo_tasks::{util::FormatDuration, Value};
     use turbopack_core::{
-        compile_time_info::{CompileTimeDefinesVc, CompileTimeInfo},
+        compile_time_info::{CompileTimeDefinesVc, CompileTimeInfo, FeatureFlagsVc},
         environment::{
             EnvironmentIntention, EnvironmentVc, ExecutionEnvironment, NodeJsEnvironment,
         },
@@ -3298,6 +3298,7 @@ mod tests {
                     Value::new(EnvironmentIntention::ServerRendering),
                 ),
                 defines: CompileTimeDefinesVc::empty(),
+                feature_flags: FeatureFlagsVc::empty(),
             }
             .cell();
             link(
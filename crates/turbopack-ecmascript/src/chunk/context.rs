@@ -1,6 +1,9 @@
 use anyhow::Result;
-use turbo_tasks::ValueToString;
-use turbopack_core::chunk::{ChunkItem, ChunkingContext, ChunkingContextVc, ModuleId, ModuleIdVc};
+use turbo_tasks::primitives::BoolVc;
+use turbopack_core::{
+    chunk::{ChunkItem, ChunkingContext, ChunkingContextVc, ModuleIdVc},
+    ident::AssetIdentVc,
+};
 
 use super::{item::EcmascriptChunkItemVc, EcmascriptChunkPlaceablesVc, EcmascriptChunkRuntimeVc};
 
@@ -19,12 +22,17 @@ pub trait EcmascriptChunkingContext: ChunkingContext {
         evaluated_entries: EcmascriptChunkPlaceablesVc,
     ) -> EcmascriptChunkRuntimeVc;
 
+    /// Whether the code generated for the module with `ident` is minified.
+    fn minify_chunk_item_code(&self, _ident: AssetIdentVc) -> BoolVc {
+        BoolVc::cell(false)
+    }
+
     async fn chunk_item_id(&self, chunk_item: EcmascriptChunkItemVc) -> Result<ModuleIdVc> {
         let layer = self.layer();
         let mut ident = chunk_item.asset_ident();
         if !layer.await?.is_empty() {
             ident = ident.with_modifier(layer)
         }
-        Ok(ModuleId::String(ident.to_string().await?.clone_value()).cell())
+        Ok(self.module_id(ident))
     }
 }
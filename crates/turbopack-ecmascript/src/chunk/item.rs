@@ -6,7 +6,8 @@ use turbopack_core::{
     asset::AssetVc,
     chunk::{
         availability_info::AvailabilityInfo, available_assets::AvailableAssetsVc, ChunkItem,
-        ChunkItemVc, ChunkableAssetVc, ChunkingContextVc, FromChunkableAsset, ModuleIdVc,
+        ChunkItemVc, ChunkPrefetch, ChunkableAssetVc, ChunkingContextVc, FromChunkableAsset,
+        ModuleIdVc,
     },
 };
 
@@ -74,6 +75,7 @@ impl FromChunkableAsset for EcmascriptChunkItemVc {
         context: ChunkingContextVc,
         asset: ChunkableAssetVc,
         availability_info: Value<AvailabilityInfo>,
+        prefetch: Option<ChunkPrefetch>,
     ) -> Result<Option<Self>> {
         let Some(context) = EcmascriptChunkingContextVc::resolve_from(context).await? else {
             return Ok(None);
@@ -97,7 +99,12 @@ impl FromChunkableAsset for EcmascriptChunkItemVc {
         };
         let manifest_asset =
             ManifestChunkAssetVc::new(asset, context, Value::new(next_availability_info));
-        let manifest_loader = ManifestLoaderItemVc::new(manifest_asset);
+        let manifest_loader = match prefetch {
+            Some(prefetch) => {
+                ManifestLoaderItemVc::new_prefetch(manifest_asset, Value::new(prefetch))
+            }
+            None => ManifestLoaderItemVc::new(manifest_asset),
+        };
         Ok(Some(manifest_loader.into()))
     }
 }
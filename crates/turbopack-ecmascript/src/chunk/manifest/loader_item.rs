@@ -1,11 +1,11 @@
 use std::io::Write as _;
 
 use anyhow::{anyhow, bail, Result};
-use indoc::writedoc;
-use turbo_tasks::{primitives::StringVc, ValueToString};
+use indoc::{formatdoc, writedoc};
+use turbo_tasks::{primitives::StringVc, Value, ValueToString};
 use turbopack_core::{
     asset::Asset,
-    chunk::{Chunk, ChunkItem, ChunkItemVc, ChunkReferenceVc, ChunkingContext},
+    chunk::{Chunk, ChunkItem, ChunkItemVc, ChunkPrefetch, ChunkReferenceVc, ChunkingContext},
     ident::AssetIdentVc,
     reference::AssetReferencesVc,
 };
@@ -28,6 +28,11 @@ fn modifier() -> StringVc {
     StringVc::cell("loader".to_string())
 }
 
+#[turbo_tasks::function]
+fn prefetch_modifier() -> StringVc {
+    StringVc::cell("prefetch".to_string())
+}
+
 /// The manifest loader item is shipped in the same chunk that uses the dynamic
 /// `import()` expression. Its responsibility is to load the manifest chunk from
 /// the server. The dynamic import has been rewritten to import this manifest
@@ -40,16 +45,32 @@ fn modifier() -> StringVc {
 /// compilation. The traversal won't be performed until the dynamic import is
 /// actually reached, instead of eagerly as part of the chunk that the dynamic
 /// import appears in.
+///
+/// When the chunks are prefetched, the loader also exports a `chunks` function
+/// which loads the manifest chunk and returns the paths of the chunks, so the
+/// module with the dynamic import can prefetch them.
 #[turbo_tasks::value]
 pub struct ManifestLoaderItem {
     manifest: ManifestChunkAssetVc,
+    prefetch: Option<ChunkPrefetch>,
 }
 
 #[turbo_tasks::value_impl]
 impl ManifestLoaderItemVc {
     #[turbo_tasks::function]
     pub fn new(manifest: ManifestChunkAssetVc) -> Self {
-        Self::cell(ManifestLoaderItem { manifest })
+        Self::cell(ManifestLoaderItem {
+            manifest,
+            prefetch: None,
+        })
+    }
+
+    #[turbo_tasks::function]
+    pub fn new_prefetch(manifest: ManifestChunkAssetVc, prefetch: Value<ChunkPrefetch>) -> Self {
+        Self::cell(ManifestLoaderItem {
+            manifest,
+            prefetch: Some(prefetch.into_value()),
+        })
     }
 }
 
@@ -57,15 +78,23 @@ impl ManifestLoaderItemVc {
 impl ChunkItem for ManifestLoaderItem {
     #[turbo_tasks::function]
     fn asset_ident(&self) -> AssetIdentVc {
-        self.manifest.ident().with_modifier(modifier())
+        let ident = self.manifest.ident().with_modifier(modifier());
+        // The prefetching loader has a different content, so it needs its own
+        // module id.
+        match self.prefetch {
+            Some(_) => ident.with_modifier(prefetch_modifier()),
+            None => ident,
+        }
     }
 
     #[turbo_tasks::function]
     async fn references(&self) -> Result<AssetReferencesVc> {
-        Ok(AssetReferencesVc::cell(vec![ChunkReferenceVc::new(
-            self.manifest.manifest_chunk(),
-        )
-        .into()]))
+        let manifest_chunk = self.manifest.manifest_chunk();
+        let reference = match self.prefetch {
+            Some(ChunkPrefetch::Eager) => ChunkReferenceVc::new_prefetch(manifest_chunk),
+            _ => ChunkReferenceVc::new(manifest_chunk),
+        };
+        Ok(AssetReferencesVc::cell(vec![reference.into()]))
     }
 }
 
@@ -126,10 +155,9 @@ impl EcmascriptChunkItem for ManifestLoaderItem {
         // trying to dynamically import.
         // This is similar to what happens when the first evaluated chunk is executed
         // on first page load, but it's happening on-demand instead of eagerly.
-        writedoc!(
-            code,
+        let load = formatdoc!(
             r#"
-                __turbopack_export_value__((__turbopack_import__) => {{
+                (__turbopack_import__) => {{
                     return __turbopack_load__({chunk_server_path}).then(() => {{
                         return __turbopack_require__({item_id});
                     }}).then(({{ chunks, list }}) => {{
@@ -138,12 +166,32 @@ impl EcmascriptChunkItem for ManifestLoaderItem {
                     }}).then(() => {{
                         return __turbopack_import__({dynamic_id});
                     }});
-                }});
-            "#,
+                }}"#,
             chunk_server_path = StringifyJs(chunk_server_path),
             item_id = StringifyJs(item_id),
             dynamic_id = StringifyJs(dynamic_id),
-        )?;
+        );
+
+        if this.prefetch.is_some() {
+            // Prefetching only needs the paths of the chunks, which are exported by the
+            // manifest chunk item.
+            writedoc!(
+                code,
+                r#"
+                    const load = {load};
+                    load.chunks = () => {{
+                        return __turbopack_load__({chunk_server_path}).then(() => {{
+                            return __turbopack_require__({item_id}).chunks;
+                        }});
+                    }};
+                    __turbopack_export_value__(load);
+                "#,
+                chunk_server_path = StringifyJs(chunk_server_path),
+                item_id = StringifyJs(item_id),
+            )?;
+        } else {
+            writeln!(code, "__turbopack_export_value__({load});")?;
+        }
 
         Ok(EcmascriptChunkItemContent {
             inner_code: code.into(),
@@ -27,6 +27,7 @@ use turbopack_core::{
     ident::{AssetIdent, AssetIdentVc},
     introspect::{
         asset::{children_from_asset_references, content_to_details, IntrospectableAssetVc},
+        chunk::{ChunkItemInfo, ChunkItemInfosVc, IntrospectableChunk, IntrospectableChunkVc},
         Introspectable, IntrospectableChildrenVc, IntrospectableVc,
     },
     reference::AssetReferencesVc,
@@ -464,6 +465,32 @@ impl Introspectable for EcmascriptChunk {
     }
 }
 
+#[turbo_tasks::value_impl]
+impl IntrospectableChunk for EcmascriptChunk {
+    #[turbo_tasks::function]
+    async fn chunk_item_infos(&self) -> Result<ChunkItemInfosVc> {
+        let chunk_content = ecmascript_chunk_content(
+            self.context,
+            self.main_entries,
+            self.omit_entries,
+            Value::new(self.availability_info),
+        )
+        .await?;
+        let infos = chunk_content
+            .chunk_items
+            .iter()
+            .map(|chunk_item| async move {
+                Ok(ChunkItemInfo {
+                    ident: chunk_item.asset_ident(),
+                    size: chunk_item.content().await?.inner_code.len(),
+                })
+            })
+            .try_join()
+            .await?;
+        Ok(ChunkItemInfosVc::cell(infos))
+    }
+}
+
 #[turbo_tasks::value_impl]
 impl GenerateSourceMap for EcmascriptChunk {
     #[turbo_tasks::function]
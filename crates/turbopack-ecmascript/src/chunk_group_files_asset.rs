@@ -213,6 +213,10 @@ impl Introspectable for ChunkGroupFilesAsset {
     #[turbo_tasks::function]
     async fn children(self_vc: ChunkGroupFilesAssetVc) -> Result<IntrospectableChildrenVc> {
         let mut children = IndexSet::new();
+        children.insert((
+            StringVc::cell("chunk group".to_string()),
+            self_vc.chunk_group().as_introspectable(),
+        ));
         let chunk_ty = StringVc::cell("chunk".to_string());
         for &chunk in self_vc.chunk_group().chunks().await?.iter() {
             children.insert((chunk_ty, IntrospectableAssetVc::new(chunk.into())));
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use swc_core::{
+    base::SwcComments,
+    common::{chain, input::StringInput, FileName, Globals, Mark, SourceMap, GLOBALS},
+    ecma::{
+        ast::{EsVersion, Program},
+        codegen::{text_writer::JsWriter, Emitter},
+        parser::{lexer::Lexer, Parser, Syntax},
+        preset_env::{self, Targets},
+        transforms::base::{
+            feature::FeatureFlag,
+            fixer::fixer,
+            helpers::{inject_helpers, Helpers, HELPERS},
+            hygiene::hygiene,
+            resolver, Assumptions,
+        },
+        visit::FoldWith,
+    },
+};
+use turbo_tasks::primitives::StringVc;
+use turbopack_core::environment::EnvironmentVc;
+
+/// Transpiles a script (not a module) so that it runs in the given
+/// environment.
+///
+/// This is used for code that is not processed as a module, e.g. the chunk
+/// runtime and the module factories of the chunk items, when emitting legacy
+/// (`nomodule`) chunks. Unlike module
+/// transforms, helpers (including the regenerator runtime) are inlined into the
+/// script since there is no module system to import them from.
+#[turbo_tasks::function]
+pub async fn downlevel_script(code: StringVc, environment: EnvironmentVc) -> Result<StringVc> {
+    let code = code.await?;
+    let versions = environment.runtime_versions().await?;
+
+    let source_map: Arc<SourceMap> = Default::default();
+    let globals = Globals::new();
+    let output = GLOBALS.set(&globals, || {
+        let helpers = Helpers::new(false);
+        HELPERS.set(&helpers, || -> Result<String> {
+            let fm = source_map.new_source_file(FileName::Anon, code.to_string());
+            let comments = SwcComments::default();
+            let lexer = Lexer::new(
+                Syntax::Es(Default::default()),
+                EsVersion::latest(),
+                StringInput::from(&*fm),
+                Some(&comments),
+            );
+            let mut parser = Parser::new_from(lexer);
+            let script = parser
+                .parse_script()
+                .map_err(|e| anyhow!("failed to parse script: {:?}", e.kind()))?;
+
+            let unresolved_mark = Mark::new();
+            let top_level_mark = Mark::new();
+            let config = preset_env::Config {
+                targets: Some(Targets::Versions(*versions)),
+                mode: None, // Don't insert core-js polyfills
+                ..Default::default()
+            };
+            let program = Program::Script(script).fold_with(&mut chain!(
+                resolver(unresolved_mark, top_level_mark, false),
+                preset_env::preset_env(
+                    top_level_mark,
+                    Some(comments.clone()),
+                    config,
+                    Assumptions::default(),
+                    &mut FeatureFlag::empty(),
+                ),
+                inject_helpers(unresolved_mark),
+                hygiene(),
+                fixer(None),
+            ));
+
+            let mut bytes: Vec<u8> = vec![];
+            {
+                let mut emitter = Emitter {
+                    cfg: swc_core::ecma::codegen::Config {
+                        ..Default::default()
+                    },
+                    cm: source_map.clone(),
+                    comments: None,
+                    wr: JsWriter::new(source_map.clone(), "\n", &mut bytes, None),
+                };
+                emitter.emit_program(&program)?;
+            }
+            Ok(String::from_utf8(bytes)?)
+        })
+    })?;
+
+    Ok(StringVc::cell(output))
+}
@@ -10,10 +10,12 @@ pub mod analyzer;
 pub mod chunk;
 pub mod chunk_group_files_asset;
 pub mod code_gen;
+pub mod downlevel;
 mod errors;
 pub mod magic_identifier;
 pub mod parse;
 mod path_visitor;
+pub mod process_env;
 pub(crate) mod references;
 pub mod resolve;
 pub(crate) mod special_cases;
@@ -53,8 +55,8 @@ use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_core::{
     asset::{Asset, AssetContentVc, AssetOptionVc, AssetVc},
     chunk::{
-        availability_info::AvailabilityInfo, ChunkItem, ChunkItemVc, ChunkVc, ChunkableAsset,
-        ChunkableAssetVc, ChunkingContextVc,
+        availability_info::AvailabilityInfo, ChunkItem, ChunkItemVc, ChunkPrefetch, ChunkVc,
+        ChunkableAsset, ChunkableAssetVc, ChunkingContextVc,
     },
     compile_time_info::CompileTimeInfoVc,
     context::AssetContextVc,
@@ -76,6 +78,9 @@ use self::{
         CodeGen, CodeGenerateableWithAvailabilityInfo, CodeGenerateableWithAvailabilityInfoVc,
     },
     parse::ParseResultVc,
+    process_env::{
+        process_env_accesses, process_env_usage, ProcessEnvAccessesVc, ProcessEnvUsageVc,
+    },
 };
 use crate::{
     chunk::{EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc},
@@ -92,6 +97,13 @@ pub struct EcmascriptOptions {
     pub split_into_parts: bool,
     /// imports will import parts of modules
     pub import_parts: bool,
+    /// code that can't be statically analyzed, like `eval` of a variable or a
+    /// `require` of a fully dynamic expression, is reported as an error
+    /// instead of a warning
+    pub strict_analysis: bool,
+    /// the chunks of dynamic imports are prefetched like this, unless a
+    /// `turbopackPrefetch` comment of the import says otherwise
+    pub prefetch: Option<ChunkPrefetch>,
 }
 
 #[turbo_tasks::value(serialization = "auto_for_input")]
@@ -282,6 +294,18 @@ impl EcmascriptModuleAssetVc {
         let this = self.await?;
         Ok(parse(this.source, Value::new(this.ty), this.transforms))
     }
+
+    /// How the module accesses `process.env`.
+    #[turbo_tasks::function]
+    pub fn process_env_usage(self) -> ProcessEnvUsageVc {
+        process_env_usage(self.parse())
+    }
+
+    /// Where the module accesses `process.env` members.
+    #[turbo_tasks::function]
+    pub fn process_env_accesses(self) -> ProcessEnvAccessesVc {
+        process_env_accesses(self.parse())
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -517,8 +541,10 @@ async fn gen_content(
 
         let mut srcmap = vec![];
 
+        let minify = *context.minify_chunk_item_code(ident).await?;
         let mut emitter = Emitter {
             cfg: swc_core::ecma::codegen::Config {
+                minify,
                 ..Default::default()
             },
             cm: source_map.clone(),
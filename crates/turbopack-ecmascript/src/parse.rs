@@ -27,10 +27,11 @@ use turbo_tasks_fs::{FileContent, FileSystemPath};
 use turbo_tasks_hash::hash_xxh3_hash64;
 use turbopack_core::{
     asset::{Asset, AssetContent, AssetVc},
+    compile_report::{measure, CompilePhase},
     source_map::{GenerateSourceMap, GenerateSourceMapVc, OptionSourceMapVc},
     SOURCE_MAP_ROOT_NAME,
 };
-use turbopack_swc_utils::emitter::IssueEmitter;
+use turbopack_swc_utils::{emitter::IssueEmitter, source_file::new_shared_source_file};
 
 use super::EcmascriptModuleAssetType;
 use crate::{
@@ -149,17 +150,21 @@ pub async fn parse(
     Ok(match &*content.await? {
         AssetContent::File(file) => match &*file.await? {
             FileContent::NotFound => ParseResult::NotFound.cell(),
-            FileContent::Content(file) => match file.content().to_str() {
+            FileContent::Content(file) => match file.content().to_shared_str() {
                 Ok(string) => {
                     let transforms = &*transforms.await?;
-                    match parse_content(
-                        string.into_owned(),
-                        fs_path,
-                        ident,
-                        file_path_hash,
-                        source,
-                        ty,
-                        transforms,
+                    match measure(
+                        CompilePhase::Transform,
+                        Some(&fs_path.path),
+                        parse_content(
+                            string,
+                            fs_path,
+                            ident,
+                            file_path_hash,
+                            source,
+                            ty,
+                            transforms,
+                        ),
                     )
                     .await
                     {
@@ -181,7 +186,7 @@ pub async fn parse(
 }
 
 async fn parse_content(
-    string: String,
+    string: Arc<String>,
     fs_path: &FileSystemPath,
     ident: &str,
     file_path_hash: u128,
@@ -210,11 +215,12 @@ async fn parse_content(
         },
         async {
             let file_name = FileName::Custom(ident.to_string());
-            let fm = source_map.new_source_file(file_name.clone(), string);
+            let fm = new_shared_source_file(&source_map, file_name.clone(), string);
 
             let comments = SwcComments::default();
 
             let mut parsed_program = {
+                let _span = tracing::info_span!("parse ecmascript", path = %fs_path.path).entered();
                 let lexer = Lexer::new(
                     match ty {
                         EcmascriptModuleAssetType::Ecmascript => Syntax::Es(EsConfig {
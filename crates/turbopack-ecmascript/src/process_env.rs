@@ -0,0 +1,266 @@
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use swc_core::{
+    common::{Span, Spanned},
+    ecma::{
+        ast::{ComputedPropName, Expr, Lit, MemberExpr, MemberProp},
+        visit::{Visit, VisitWith},
+    },
+};
+use turbo_tasks::trace::TraceRawVcs;
+
+use crate::{
+    parse::{ParseResult, ParseResultVc},
+    utils::unparen,
+};
+
+/// How a module accesses `process.env`.
+#[turbo_tasks::value(shared)]
+#[derive(Default)]
+pub struct ProcessEnvUsage {
+    /// The names of the accessed members, e.g. `NODE_ENV` for
+    /// `process.env.NODE_ENV`.
+    pub keys: BTreeSet<String>,
+    /// Whether `process.env` is accessed in a way that can't be analyzed,
+    /// e.g. `process.env[name]` or `Object.keys(process.env)`.
+    pub dynamic: bool,
+}
+
+impl ProcessEnvUsage {
+    pub fn extend(&mut self, other: &ProcessEnvUsage) {
+        self.keys.extend(other.keys.iter().cloned());
+        self.dynamic |= other.dynamic;
+    }
+}
+
+/// An access of `process.env.NAME` in a module, with the byte offsets of the
+/// member expression in the source.
+#[derive(Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub struct ProcessEnvAccess {
+    pub key: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The accesses of `process.env` members with a static name, in source order.
+#[turbo_tasks::value(transparent)]
+pub struct ProcessEnvAccesses(Vec<ProcessEnvAccess>);
+
+#[turbo_tasks::function]
+pub async fn process_env_usage(parsed: ParseResultVc) -> Result<ProcessEnvUsageVc> {
+    let mut visitor = ProcessEnvVisitor::default();
+    if let ParseResult::Ok { program, .. } = &*parsed.await? {
+        program.visit_with(&mut visitor);
+    }
+    Ok(visitor.usage.cell())
+}
+
+/// The locations of the `process.env.NAME` accesses, e.g. for issues pointing
+/// at them. It's separate from [process_env_usage], which only changes when
+/// the set of accessed names changes.
+#[turbo_tasks::function]
+pub async fn process_env_accesses(parsed: ParseResultVc) -> Result<ProcessEnvAccessesVc> {
+    let mut accesses = Vec::new();
+    if let ParseResult::Ok {
+        program,
+        source_map,
+        ..
+    } = &*parsed.await?
+    {
+        let mut visitor = ProcessEnvVisitor::default();
+        program.visit_with(&mut visitor);
+        for (key, span) in visitor.spans {
+            if span.is_dummy() {
+                continue;
+            }
+            accesses.push(ProcessEnvAccess {
+                key,
+                start: source_map.lookup_byte_offset(span.lo).pos.0 as usize,
+                end: source_map.lookup_byte_offset(span.hi).pos.0 as usize,
+            });
+        }
+    }
+    Ok(ProcessEnvAccessesVc::cell(accesses))
+}
+
+fn is_process_env_member(member: &MemberExpr) -> bool {
+    let MemberProp::Ident(prop) = &member.prop else {
+        return false;
+    };
+    &*prop.sym == "env"
+        && matches!(unparen(&member.obj), Expr::Ident(obj) if &*obj.sym == "process")
+}
+
+fn is_process_env(expr: &Expr) -> bool {
+    matches!(unparen(expr), Expr::Member(member) if is_process_env_member(member))
+}
+
+#[derive(Default)]
+struct ProcessEnvVisitor {
+    usage: ProcessEnvUsage,
+    /// The spans of the accesses of the `usage.keys`.
+    spans: Vec<(String, Span)>,
+}
+
+impl ProcessEnvVisitor {
+    fn add_key(&mut self, key: String, span: Span) {
+        self.usage.keys.insert(key.clone());
+        self.spans.push((key, span));
+    }
+}
+
+impl Visit for ProcessEnvVisitor {
+    fn visit_member_expr(&mut self, member: &MemberExpr) {
+        if is_process_env(&member.obj) {
+            match &member.prop {
+                MemberProp::Ident(ident) => {
+                    self.add_key(ident.sym.to_string(), member.span());
+                }
+                MemberProp::Computed(ComputedPropName { expr, .. }) => {
+                    if let Expr::Lit(Lit::Str(key)) = unparen(expr) {
+                        self.add_key(key.value.to_string(), member.span());
+                    } else {
+                        self.usage.dynamic = true;
+                        expr.visit_with(self);
+                    }
+                }
+                MemberProp::PrivateName(_) => {}
+            }
+            return;
+        }
+        if is_process_env_member(member) {
+            // `process.env` is used as a whole.
+            self.usage.dynamic = true;
+            return;
+        }
+        member.visit_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::{
+        common::FileName,
+        ecma::{ast::EsVersion, parser::parse_file_as_module, visit::VisitWith},
+        testing::run_test,
+    };
+
+    use super::ProcessEnvVisitor;
+
+    /// The keys `code` accesses, the source text of their accesses and
+    /// whether it accesses `process.env` dynamically.
+    fn visit(code: &str) -> (Vec<String>, Vec<String>, bool) {
+        run_test(false, |cm, _handler| {
+            let fm = cm.new_source_file(FileName::Anon, code.to_string());
+            let module = parse_file_as_module(
+                &fm,
+                Default::default(),
+                EsVersion::latest(),
+                None,
+                &mut vec![],
+            )
+            .unwrap();
+            let mut visitor = ProcessEnvVisitor::default();
+            module.visit_with(&mut visitor);
+            let accesses = visitor
+                .spans
+                .iter()
+                .map(|(_, span)| cm.span_to_snippet(*span).unwrap())
+                .collect();
+            Ok((
+                visitor.usage.keys.into_iter().collect(),
+                accesses,
+                visitor.usage.dynamic,
+            ))
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn static_member() {
+        assert_eq!(
+            visit("console.log(process.env.API_URL);"),
+            (
+                vec!["API_URL".to_string()],
+                vec!["process.env.API_URL".to_string()],
+                false
+            )
+        );
+    }
+
+    #[test]
+    fn string_literal_key() {
+        assert_eq!(
+            visit(r#"const url = process.env["API_URL"];"#),
+            (
+                vec!["API_URL".to_string()],
+                vec![r#"process.env["API_URL"]"#.to_string()],
+                false
+            )
+        );
+    }
+
+    #[test]
+    fn computed_key() {
+        assert_eq!(
+            visit("const value = process.env[name];"),
+            (vec![], vec![], true)
+        );
+        // Accesses in the computed key are still found.
+        assert_eq!(
+            visit("const value = process.env[process.env.KEY];"),
+            (
+                vec!["KEY".to_string()],
+                vec!["process.env.KEY".to_string()],
+                true
+            )
+        );
+    }
+
+    #[test]
+    fn whole_env() {
+        assert_eq!(
+            visit("console.log(Object.keys(process.env));"),
+            (vec![], vec![], true)
+        );
+        assert_eq!(visit("const env = process.env;"), (vec![], vec![], true));
+    }
+
+    #[test]
+    fn optional_chaining() {
+        assert_eq!(
+            visit("const url = process.env?.API_URL;"),
+            (
+                vec!["API_URL".to_string()],
+                vec!["process.env?.API_URL".to_string()],
+                false
+            )
+        );
+    }
+
+    #[test]
+    fn keys_are_deduplicated() {
+        assert_eq!(
+            visit("(process.env).A + process.env.B + process.env.A;"),
+            (
+                vec!["A".to_string(), "B".to_string()],
+                vec![
+                    "(process.env).A".to_string(),
+                    "process.env.B".to_string(),
+                    "process.env.A".to_string()
+                ],
+                false
+            )
+        );
+    }
+
+    #[test]
+    fn other_objects() {
+        assert_eq!(
+            visit("config.env.API_URL; process.argv; env.API_URL;"),
+            (vec![], vec![], false)
+        );
+    }
+}
@@ -15,7 +15,7 @@ use crate::{
     chunk::EcmascriptChunkingContextVc,
     code_gen::{CodeGenerateable, CodeGenerateableVc, CodeGeneration, CodeGenerationVc},
     create_visitor,
-    references::{util::throw_module_not_found_expr, AstPathVc},
+    references::{require_esm::check_require_esm, util::throw_module_not_found_expr, AstPathVc},
     resolve::cjs_resolve,
 };
 
@@ -123,6 +123,11 @@ impl CodeGenerateable for CjsRequireAssetReference {
                 *expr = throw_module_not_found_expr(&request_string);
             }));
         } else {
+            if !pm.is_internal_import() {
+                // External packages are required by Node.js at runtime, which fails for
+                // ESM-only packages.
+                check_require_esm(self.origin.origin_path(), self.request).await?;
+            }
             visitors.push(
                 create_visitor!(exact path, visit_mut_call_expr(call_expr: &mut CallExpr) {
                     call_expr.callee = Callee::Expr(
@@ -12,7 +12,8 @@ use turbopack_core::{
         ChunkableAssetReference, ChunkableAssetReferenceVc, ChunkingType, ChunkingTypeOptionVc,
         ModuleId,
     },
-    reference::{AssetReference, AssetReferenceVc},
+    issue::{IssueSourceVc, OptionIssueSourceVc},
+    reference::{AssetReference, AssetReferenceSpan, AssetReferenceSpanVc, AssetReferenceVc},
     reference_type::EcmaScriptModulesReferenceSubType,
     resolve::{
         origin::ResolveOriginVc, parse::RequestVc, ModulePartVc, PrimaryResolveResult,
@@ -101,6 +102,8 @@ pub struct EsmAssetReference {
     pub annotations: ImportAnnotations,
 
     pub export_name: Option<ModulePartVc>,
+    /// The module specifier of the import.
+    pub issue_source: Option<IssueSourceVc>,
 }
 
 impl EsmAssetReference {
@@ -135,12 +138,14 @@ impl EsmAssetReferenceVc {
         request: RequestVc,
         annotations: Value<ImportAnnotations>,
         export_name: Option<ModulePartVc>,
+        issue_source: Option<IssueSourceVc>,
     ) -> Self {
         Self::cell(EsmAssetReference {
             origin,
             request,
             annotations: annotations.into_value(),
             export_name,
+            issue_source,
         })
     }
 }
@@ -158,6 +163,14 @@ impl AssetReference for EsmAssetReference {
     }
 }
 
+#[turbo_tasks::value_impl]
+impl AssetReferenceSpan for EsmAssetReference {
+    #[turbo_tasks::function]
+    fn span(&self) -> OptionIssueSourceVc {
+        OptionIssueSourceVc::cell(self.issue_source)
+    }
+}
+
 #[turbo_tasks::value_impl]
 impl ValueToString for EsmAssetReference {
     #[turbo_tasks::function]
@@ -231,7 +244,7 @@ impl CodeGenerateable for EsmAssetReference {
                                 "var $name = __turbopack_import__($id);" as Stmt,
                                 name = Ident::new(ident.clone().into(), DUMMY_SP),
                                 id: Expr = Expr::Lit(match &*id {
-                                    ModuleId::String(s) => s.clone().into(),
+                                    ModuleId::String(s) => s.to_string().into(),
                                     ModuleId::Number(n) => (*n as f64).into(),
                                 })
                             );
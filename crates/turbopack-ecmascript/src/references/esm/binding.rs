@@ -10,7 +10,7 @@ use swc_core::{
     },
 };
 
-use super::EsmAssetReferenceVc;
+use super::{export::check_imported_export, EsmAssetReferenceVc};
 use crate::{
     chunk::EcmascriptChunkingContextVc,
     code_gen::{CodeGenerateable, CodeGenerateableVc, CodeGeneration, CodeGenerationVc},
@@ -73,6 +73,10 @@ impl CodeGenerateable for EsmBinding {
             }
         }
 
+        if let Some(export) = &this.export {
+            check_imported_export(this.reference, export.clone()).await?;
+        }
+
         let mut ast_path = this.ast_path.await?.clone_value();
         let imported_module = imported_module.await?.get_ident().await?;
 
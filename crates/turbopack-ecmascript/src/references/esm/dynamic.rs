@@ -1,20 +1,27 @@
 use anyhow::Result;
+use lazy_static::lazy_static;
+use regex::Regex;
 use swc_core::{
-    ecma::ast::{Callee, ExprOrSpread},
-    quote_expr,
+    base::SwcComments,
+    common::Span,
+    ecma::ast::{Callee, Expr, ExprOrSpread, Lit},
+    quote, quote_expr,
 };
 use turbo_tasks::{primitives::StringVc, Value, ValueToString, ValueToStringVc};
 use turbopack_core::{
     chunk::{
-        availability_info::AvailabilityInfo, ChunkableAssetReference, ChunkableAssetReferenceVc,
-        ChunkingType, ChunkingTypeOptionVc,
+        availability_info::AvailabilityInfo, ChunkPrefetch, ChunkableAssetReference,
+        ChunkableAssetReferenceVc, ChunkingType, ChunkingTypeOptionVc,
     },
     reference::{AssetReference, AssetReferenceVc},
     reference_type::EcmaScriptModulesReferenceSubType,
     resolve::{origin::ResolveOriginVc, parse::RequestVc, ResolveResultVc},
 };
 
-use super::super::pattern_mapping::{PatternMapping, PatternMappingVc, ResolveType::EsmAsync};
+use super::{
+    super::pattern_mapping::{PatternMapping, PatternMappingVc, ResolveType::EsmAsync},
+    base::insert_hoisted_stmt,
+};
 use crate::{
     chunk::EcmascriptChunkingContextVc,
     code_gen::{
@@ -26,12 +33,52 @@ use crate::{
     resolve::esm_resolve,
 };
 
+/// Reads the prefetch strategy of a dynamic import from a comment within the
+/// `span` of the `import()` call:
+///
+/// - `/* turbopackPrefetch: "eager" */`, `"idle"` or `"visible"` prefetch the
+///   chunks as described by [ChunkPrefetch].
+/// - `/* turbopackPrefetch: false */` never prefetches them, even when the
+///   module options would.
+/// - `/* webpackPrefetch: true */` prefetches them eagerly, like webpack does.
+///
+/// Returns `None` when there's no such comment.
+pub(crate) fn prefetch_pragma(comments: &SwcComments, span: Span) -> Option<Option<ChunkPrefetch>> {
+    lazy_static! {
+        static ref PREFETCH: Regex =
+            Regex::new(r#"(turbopack|webpack)Prefetch\s*:\s*(true|false|["'](\w+)["'])"#).unwrap();
+    }
+    let mut prefetch = None;
+    for entry in comments.leading.iter().chain(comments.trailing.iter()) {
+        if *entry.key() <= span.lo || *entry.key() >= span.hi {
+            continue;
+        }
+        for comment in entry.value().iter() {
+            let Some(m) = PREFETCH.captures(&comment.text) else {
+                continue;
+            };
+            prefetch = match (&m[1], &m[2], m.get(3).map(|m| m.as_str())) {
+                (_, "false", _) => Some(None),
+                ("webpack", "true", _) => Some(Some(ChunkPrefetch::Eager)),
+                ("turbopack", _, Some("eager")) => Some(Some(ChunkPrefetch::Eager)),
+                ("turbopack", _, Some("idle")) => Some(Some(ChunkPrefetch::Idle)),
+                ("turbopack", _, Some("visible")) => Some(Some(ChunkPrefetch::Visible)),
+                _ => continue,
+            };
+        }
+    }
+    prefetch
+}
+
 #[turbo_tasks::value]
 #[derive(Hash, Debug)]
 pub struct EsmAsyncAssetReference {
     pub origin: ResolveOriginVc,
     pub request: RequestVc,
     pub path: AstPathVc,
+    /// When the chunks of the imported module are prefetched, from a
+    /// `turbopackPrefetch` comment or the options of the importing module.
+    pub prefetch: Option<ChunkPrefetch>,
 }
 
 #[turbo_tasks::value_impl]
@@ -42,6 +89,22 @@ impl EsmAsyncAssetReferenceVc {
             origin,
             request,
             path,
+            prefetch: None,
+        })
+    }
+
+    #[turbo_tasks::function]
+    pub fn new_prefetch(
+        origin: ResolveOriginVc,
+        request: RequestVc,
+        path: AstPathVc,
+        prefetch: Value<ChunkPrefetch>,
+    ) -> Self {
+        Self::cell(EsmAsyncAssetReference {
+            origin,
+            request,
+            path,
+            prefetch: Some(prefetch.into_value()),
         })
     }
 }
@@ -69,7 +132,10 @@ impl ValueToString for EsmAsyncAssetReference {
 impl ChunkableAssetReference for EsmAsyncAssetReference {
     #[turbo_tasks::function]
     fn chunking_type(&self) -> ChunkingTypeOptionVc {
-        ChunkingTypeOptionVc::cell(Some(ChunkingType::SeparateAsync))
+        ChunkingTypeOptionVc::cell(Some(match self.prefetch {
+            Some(prefetch) => ChunkingType::SeparateAsyncPrefetch(prefetch),
+            None => ChunkingType::SeparateAsync,
+        }))
     }
 }
 
@@ -90,12 +156,27 @@ impl CodeGenerateableWithAvailabilityInfo for EsmAsyncAssetReference {
                 self.request,
                 Value::new(EcmaScriptModulesReferenceSubType::Undefined),
             ),
-            Value::new(EsmAsync(availability_info.into_value())),
+            Value::new(EsmAsync(availability_info.into_value(), self.prefetch)),
         )
         .await?;
 
         let path = &self.path.await?;
 
+        let mut visitors = Vec::new();
+        if let (Some(prefetch), PatternMapping::SingleLoader(_)) = (self.prefetch, &*pm) {
+            // The runtime schedules the prefetch when the module is evaluated.
+            let loader = pm.create();
+            visitors.push(create_visitor!(visit_mut_program(program: &mut Program) {
+                let stmt = quote!(
+                    "__turbopack_prefetch__($strategy, __turbopack_require__($loader).chunks);"
+                        as Stmt,
+                    strategy: Expr = Expr::Lit(Lit::Str(prefetch.as_str().into())),
+                    loader: Expr = loader.clone(),
+                );
+                insert_hoisted_stmt(program, stmt);
+            }));
+        }
+
         let visitor = match &*pm {
             PatternMapping::Invalid => {
                 create_visitor!(exact path, visit_mut_call_expr(call_expr: &mut CallExpr) {
@@ -167,9 +248,8 @@ impl CodeGenerateableWithAvailabilityInfo for EsmAsyncAssetReference {
             }
         };
 
-        Ok(CodeGeneration {
-            visitors: vec![visitor],
-        }
-        .into())
+        visitors.push(visitor);
+
+        Ok(CodeGeneration { visitors }.into())
     }
 }
@@ -1,6 +1,8 @@
 use std::{
     borrow::Cow,
     collections::{BTreeMap, HashSet},
+    future::Future,
+    pin::Pin,
 };
 
 use anyhow::Result;
@@ -13,10 +15,14 @@ use swc_core::{
     },
     quote, quote_expr,
 };
-use turbo_tasks::{primitives::StringVc, trace::TraceRawVcs, ValueToString};
+use turbo_tasks::{
+    primitives::StringVc, trace::TraceRawVcs, CompletionVc, TryJoinIterExt, ValueToString,
+};
 use turbopack_core::{
     asset::Asset,
+    ident::AssetIdentVc,
     issue::{analyze::AnalyzeIssue, IssueSeverity},
+    resolve::origin::ResolveOrigin,
 };
 
 use super::{base::ReferencedAsset, EsmAssetReferenceVc};
@@ -127,6 +133,140 @@ async fn expand_star_exports(root_asset: EcmascriptChunkPlaceableVc) -> Result<E
     }))
 }
 
+/// What an export name of a module resolves to, following re-exports and star
+/// exports like the `ResolveExport` operation of the ECMAScript spec.
+#[turbo_tasks::value(shared)]
+pub enum ResolvedExport {
+    /// The export is the binding `name` of `asset`, or its namespace when
+    /// `name` is `None`.
+    Binding {
+        asset: EcmascriptChunkPlaceableVc,
+        name: Option<String>,
+    },
+    /// Multiple star exports provide the name with different bindings. Per
+    /// spec, such names are excluded from the exports of the module.
+    Ambiguous,
+    /// The name might be provided by a CommonJS module through a star export,
+    /// which is only known at runtime.
+    Dynamic,
+    NotFound,
+}
+
+type ResolveSet = HashSet<(EcmascriptChunkPlaceableVc, String)>;
+
+fn resolve_export_internal(
+    asset: EcmascriptChunkPlaceableVc,
+    name: String,
+    resolve_set: &mut ResolveSet,
+) -> Pin<Box<dyn Future<Output = Result<ResolvedExport>> + Send + '_>> {
+    Box::pin(async move {
+        // A circular import request, which is handled by the request that reached
+        // the same export first.
+        if !resolve_set.insert((asset, name.clone())) {
+            return Ok(ResolvedExport::NotFound);
+        }
+        let exports = match &*asset.get_exports().await? {
+            EcmascriptExports::EsmExports(exports) => exports.await?,
+            EcmascriptExports::CommonJs => return Ok(ResolvedExport::Dynamic),
+            EcmascriptExports::Value | EcmascriptExports::None => {
+                return Ok(ResolvedExport::NotFound)
+            }
+        };
+        if let Some(export) = exports.exports.get(&name) {
+            let referenced_asset = match export {
+                EsmExport::ImportedBinding(esm_ref, _) | EsmExport::ImportedNamespace(esm_ref) => {
+                    Some(esm_ref.get_referenced_asset().await?)
+                }
+                EsmExport::LocalBinding(_) | EsmExport::Error => None,
+            };
+            return Ok(match (export, referenced_asset.as_deref()) {
+                (
+                    EsmExport::ImportedBinding(_, imported),
+                    Some(ReferencedAsset::Some(imported_asset)),
+                ) => {
+                    resolve_export_internal(*imported_asset, imported.clone(), resolve_set).await?
+                }
+                (EsmExport::ImportedNamespace(_), Some(ReferencedAsset::Some(imported_asset))) => {
+                    ResolvedExport::Binding {
+                        asset: *imported_asset,
+                        name: None,
+                    }
+                }
+                _ => ResolvedExport::Binding {
+                    asset,
+                    name: Some(name),
+                },
+            });
+        }
+        if name == "default" {
+            return Ok(ResolvedExport::NotFound);
+        }
+        let mut resolution = ResolvedExport::NotFound;
+        for esm_ref in exports.star_exports.iter() {
+            let ReferencedAsset::Some(star_asset) = &*esm_ref.get_referenced_asset().await? else {
+                continue;
+            };
+            let star_resolution =
+                resolve_export_internal(*star_asset, name.clone(), resolve_set).await?;
+            resolution = match (resolution, star_resolution) {
+                (ResolvedExport::Ambiguous, _) | (_, ResolvedExport::Ambiguous) => {
+                    return Ok(ResolvedExport::Ambiguous)
+                }
+                (ResolvedExport::NotFound, other) | (other, ResolvedExport::NotFound) => other,
+                // A static binding takes precedence over the properties a CommonJS
+                // module might have at runtime.
+                (ResolvedExport::Dynamic, other) | (other, ResolvedExport::Dynamic) => other,
+                (first, other) if first == other => first,
+                _ => return Ok(ResolvedExport::Ambiguous),
+            };
+        }
+        Ok(resolution)
+    })
+}
+
+/// Resolves the export `name` of `asset`, see [ResolvedExport].
+#[turbo_tasks::function]
+pub async fn resolve_export(
+    asset: EcmascriptChunkPlaceableVc,
+    name: String,
+) -> Result<ResolvedExportVc> {
+    Ok(resolve_export_internal(asset, name, &mut HashSet::new())
+        .await?
+        .cell())
+}
+
+/// Emits an issue when the export `name` imported through `reference` is
+/// ambiguous, which is a `SyntaxError` in engines that implement ESM natively.
+#[turbo_tasks::function]
+pub(super) async fn check_imported_export(
+    reference: EsmAssetReferenceVc,
+    name: String,
+) -> Result<CompletionVc> {
+    let ReferencedAsset::Some(asset) = &*reference.get_referenced_asset().await? else {
+        return Ok(CompletionVc::new());
+    };
+    if let ResolvedExport::Ambiguous = &*resolve_export(*asset, name.clone()).await? {
+        AnalyzeIssue {
+            code: None,
+            category: StringVc::cell("analyze".to_string()),
+            message: StringVc::cell(format!(
+                "The export {name} of module {} is provided by multiple `export *` with different \
+                 bindings, so it's ambiguous and isn't exported at all.\nExport it explicitly \
+                 (`export {{ {name} }} from \"...\"`) to pick one of them.",
+                asset.ident().to_string().await?
+            )),
+            source_ident: AssetIdentVc::from_path(reference.await?.origin.origin_path()),
+            severity: IssueSeverity::Error.into(),
+            source: None,
+            title: StringVc::cell(format!("ambiguous import of {name}")),
+        }
+        .cell()
+        .as_issue()
+        .emit();
+    }
+    Ok(CompletionVc::new())
+}
+
 #[turbo_tasks::value(shared)]
 #[derive(Hash, Debug)]
 pub struct EsmExports {
@@ -144,6 +284,12 @@ impl CodeGenerateable for EsmExports {
         let this = self_vc.await?;
         let mut visitors = Vec::new();
 
+        for export in this.exports.values() {
+            if let EsmExport::ImportedBinding(esm_ref, name) = export {
+                check_imported_export(*esm_ref, name.clone()).await?;
+            }
+        }
+
         let mut all_exports: BTreeMap<Cow<str>, Cow<EsmExport>> = this
             .exports
             .iter()
@@ -151,6 +297,8 @@ impl CodeGenerateable for EsmExports {
             .collect();
         let mut props = Vec::new();
         let mut cjs_exports = Vec::<Box<Expr>>::new();
+        // The star exports which provide each name that isn't exported explicitly.
+        let mut star_export_candidates: BTreeMap<String, Vec<_>> = BTreeMap::new();
 
         for esm_ref in this.star_exports.iter() {
             if let ReferencedAsset::Some(asset) = &*esm_ref.get_referenced_asset().await? {
@@ -158,10 +306,10 @@ impl CodeGenerateable for EsmExports {
                 let export_names = &export_info.star_exports;
                 for export in export_names.iter() {
                     if !all_exports.contains_key(&Cow::<str>::Borrowed(export)) {
-                        all_exports.insert(
-                            Cow::Owned(export.clone()),
-                            Cow::Owned(EsmExport::ImportedBinding(*esm_ref, export.to_string())),
-                        );
+                        star_export_candidates
+                            .entry(export.clone())
+                            .or_default()
+                            .push((*esm_ref, *asset));
                     }
                 }
 
@@ -175,6 +323,38 @@ impl CodeGenerateable for EsmExports {
                 }
             }
         }
+        for (export, candidates) in star_export_candidates {
+            // Names provided by multiple star exports are only exported when all of
+            // them resolve to the same binding.
+            if candidates.len() > 1 {
+                let resolutions = candidates
+                    .iter()
+                    .map(|(_, asset)| resolve_export(*asset, export.clone()))
+                    .try_join()
+                    .await?;
+                let mut bindings =
+                    resolutions
+                        .iter()
+                        .map(|resolution| &**resolution)
+                        .filter(|resolution| {
+                            !matches!(
+                                resolution,
+                                ResolvedExport::NotFound | ResolvedExport::Dynamic
+                            )
+                        });
+                let first = bindings.next();
+                let is_ambiguous = matches!(first, Some(ResolvedExport::Ambiguous))
+                    || bindings.any(|binding| Some(binding) != first);
+                if is_ambiguous {
+                    continue;
+                }
+            }
+            let (esm_ref, _) = candidates[0];
+            all_exports.insert(
+                Cow::Owned(export.clone()),
+                Cow::Owned(EsmExport::ImportedBinding(esm_ref, export)),
+            );
+        }
         for (exported, local) in all_exports.into_iter() {
             let expr = match local.as_ref() {
                 EsmExport::Error => Some(quote!(
@@ -0,0 +1,48 @@
+use anyhow::Result;
+use swc_core::quote;
+
+use super::AstPathVc;
+use crate::{
+    chunk::EcmascriptChunkingContextVc,
+    code_gen::{CodeGenerateable, CodeGenerateableVc, CodeGeneration, CodeGenerationVc},
+    create_visitor,
+};
+
+/// Replaces a read of a feature flag, e.g.
+/// `__turbopack_feature_flags__.newCheckout`, with the value of the flag.
+#[turbo_tasks::value]
+pub struct FeatureFlagAccess {
+    enabled: bool,
+    path: AstPathVc,
+}
+
+#[turbo_tasks::value_impl]
+impl FeatureFlagAccessVc {
+    #[turbo_tasks::function]
+    pub fn new(enabled: bool, path: AstPathVc) -> Self {
+        Self::cell(FeatureFlagAccess { enabled, path })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for FeatureFlagAccess {
+    #[turbo_tasks::function]
+    async fn code_generation(
+        &self,
+        _context: EcmascriptChunkingContextVc,
+    ) -> Result<CodeGenerationVc> {
+        let enabled = self.enabled;
+        let visitors = [
+            create_visitor!(exact &self.path.await?, visit_mut_expr(expr: &mut Expr) {
+                *expr = if enabled {
+                    quote!("(\"TURBOPACK feature flag enabled\", true)" as Expr)
+                } else {
+                    quote!("(\"TURBOPACK feature flag disabled\", false)" as Expr)
+                };
+            }),
+        ]
+        .into();
+
+        Ok(CodeGeneration { visitors }.cell())
+    }
+}
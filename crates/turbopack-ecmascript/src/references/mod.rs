@@ -2,9 +2,11 @@ pub mod amd;
 pub mod cjs;
 pub mod constant_condition;
 pub mod esm;
+pub mod feature_flag;
 pub mod node;
 pub mod pattern_mapping;
 pub mod raw;
+pub mod require_esm;
 pub mod typescript;
 pub mod unreachable;
 pub mod util;
@@ -25,6 +27,7 @@ use lazy_static::lazy_static;
 use parking_lot::Mutex;
 use regex::Regex;
 use swc_core::{
+    base::SwcComments,
     common::{
         comments::CommentKind,
         errors::{DiagnosticId, Handler, HANDLER},
@@ -40,7 +43,9 @@ use turbo_tasks::{primitives::BoolVc, TryJoinIterExt, Value};
 use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_core::{
     asset::{Asset, AssetVc},
-    compile_time_info::CompileTimeInfoVc,
+    chunk::ChunkPrefetch,
+    compile_time_info::{CompileTimeInfoVc, FEATURE_FLAGS_OBJECT},
+    issue::IssueSourceVc,
     reference::{AssetReferenceVc, AssetReferencesVc, SourceMapReferenceVc},
     reference_type::{CommonJsReferenceSubType, ReferenceType},
     resolve::{
@@ -62,9 +67,10 @@ use self::{
     },
     cjs::CjsAssetReferenceVc,
     esm::{
-        export::EsmExport, EsmAssetReferenceVc, EsmAsyncAssetReferenceVc, EsmExports,
-        EsmModuleItemVc, ImportMetaBindingVc, ImportMetaRefVc, UrlAssetReferenceVc,
+        dynamic::prefetch_pragma, export::EsmExport, EsmAssetReferenceVc, EsmAsyncAssetReferenceVc,
+        EsmExports, EsmModuleItemVc, ImportMetaBindingVc, ImportMetaRefVc, UrlAssetReferenceVc,
     },
+    feature_flag::FeatureFlagAccessVc,
     node::{DirAssetReferenceVc, PackageJsonReferenceVc},
     raw::SourceAssetReferenceVc,
     typescript::{
@@ -365,7 +371,8 @@ pub(crate) async fn analyze_ecmascript_module(
                 GLOBALS.set(globals, || create_graph(program, eval_context))
             });
 
-            for r in eval_context.imports.references() {
+            for (i, r) in eval_context.imports.references().enumerate() {
+                let span = eval_context.imports.reference_span(i);
                 let r = EsmAssetReferenceVc::new(
                     origin,
                     RequestVc::parse(Value::new(r.module_path.to_string().into())),
@@ -383,6 +390,14 @@ pub(crate) async fn analyze_ecmascript_module(
                     } else {
                         None
                     },
+                    // Imports which are added by transforms have no location.
+                    (!span.is_dummy()).then(|| {
+                        IssueSourceVc::from_byte_offset(
+                            source,
+                            source_map.lookup_byte_offset(span.lo).pos.0 as usize,
+                            source_map.lookup_byte_offset(span.hi).pos.0 as usize,
+                        )
+                    }),
                 );
                 import_references.push(r);
             }
@@ -526,6 +541,9 @@ pub(crate) async fn analyze_ecmascript_module(
                 add_effects: &'a G,
                 analysis: &'a mut AnalyzeEcmascriptModuleResultBuilder,
                 compile_time_info: CompileTimeInfoVc,
+                strict_analysis: bool,
+                comments: &'a SwcComments,
+                prefetch: Option<ChunkPrefetch>,
             ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
                 Box::pin(handle_call(
                     handler,
@@ -540,6 +558,9 @@ pub(crate) async fn analyze_ecmascript_module(
                     add_effects,
                     analysis,
                     compile_time_info,
+                    strict_analysis,
+                    comments,
+                    prefetch,
                 ))
             }
 
@@ -560,10 +581,22 @@ pub(crate) async fn analyze_ecmascript_module(
                 add_effects: &G,
                 analysis: &mut AnalyzeEcmascriptModuleResultBuilder,
                 compile_time_info: CompileTimeInfoVc,
+                strict_analysis: bool,
+                comments: &SwcComments,
+                prefetch: Option<ChunkPrefetch>,
             ) -> Result<()> {
                 fn explain_args(args: &[JsValue]) -> (String, String) {
                     JsValue::explain_args(args, 10, 2)
                 }
+                // Code that can't be analyzed is only an error with strict analysis,
+                // since it usually still works at runtime.
+                let report_dynamic = |span: Span, message: &str, code: DiagnosticId| {
+                    if strict_analysis {
+                        handler.span_err_with_code(span, message, code)
+                    } else {
+                        handler.span_warn_with_code(span, message, code)
+                    }
+                };
                 let linked_args = |args: Vec<EffectArg>| async move {
                     args.into_iter()
                         .map(|arg| {
@@ -601,6 +634,9 @@ pub(crate) async fn analyze_ecmascript_module(
                                 add_effects,
                                 analysis,
                                 compile_time_info,
+                                strict_analysis,
+                                comments,
+                                prefetch,
                             )
                             .await?;
                         }
@@ -611,7 +647,7 @@ pub(crate) async fn analyze_ecmascript_module(
                             let pat = js_value_to_pattern(&args[0]);
                             if !pat.has_constant_parts() {
                                 let (args, hints) = explain_args(&args);
-                                handler.span_warn_with_code(
+                                report_dynamic(
                                     span,
                                     &format!("import({args}) is very dynamic{hints}",),
                                     DiagnosticId::Lint(
@@ -620,15 +656,22 @@ pub(crate) async fn analyze_ecmascript_module(
                                     ),
                                 )
                             }
-                            analysis.add_reference(EsmAsyncAssetReferenceVc::new(
-                                origin,
-                                RequestVc::parse(Value::new(pat)),
-                                AstPathVc::cell(ast_path.to_vec()),
-                            ));
+                            let request = RequestVc::parse(Value::new(pat));
+                            let path = AstPathVc::cell(ast_path.to_vec());
+                            let prefetch = prefetch_pragma(comments, span).unwrap_or(prefetch);
+                            analysis.add_reference(match prefetch {
+                                Some(prefetch) => EsmAsyncAssetReferenceVc::new_prefetch(
+                                    origin,
+                                    request,
+                                    path,
+                                    Value::new(prefetch),
+                                ),
+                                None => EsmAsyncAssetReferenceVc::new(origin, request, path),
+                            });
                             return Ok(());
                         }
                         let (args, hints) = explain_args(&args);
-                        handler.span_warn_with_code(
+                        report_dynamic(
                             span,
                             &format!("import({args}) is not statically analyse-able{hints}",),
                             DiagnosticId::Error(
@@ -642,7 +685,7 @@ pub(crate) async fn analyze_ecmascript_module(
                             let pat = js_value_to_pattern(&args[0]);
                             if !pat.has_constant_parts() {
                                 let (args, hints) = explain_args(&args);
-                                handler.span_warn_with_code(
+                                report_dynamic(
                                     span,
                                     &format!("require({args}) is very dynamic{hints}",),
                                     DiagnosticId::Lint(
@@ -658,7 +701,7 @@ pub(crate) async fn analyze_ecmascript_module(
                             return Ok(());
                         }
                         let (args, hints) = explain_args(&args);
-                        handler.span_warn_with_code(
+                        report_dynamic(
                             span,
                             &format!("require({args}) is not statically analyse-able{hints}",),
                             DiagnosticId::Error(
@@ -666,6 +709,25 @@ pub(crate) async fn analyze_ecmascript_module(
                             ),
                         )
                     }
+                    JsValue::FreeVar(FreeVarKind::Other(ref name))
+                        if strict_analysis && (&**name == "eval" || &**name == "Function") =>
+                    {
+                        // `new Function(...)` is handled as a call too.
+                        let args = linked_args(args).await?;
+                        if !args.iter().all(|arg| matches!(arg, JsValue::Constant(_))) {
+                            let (args, hints) = explain_args(&args);
+                            handler.span_err_with_code(
+                                span,
+                                &format!(
+                                    "{name}({args}) evaluates code that can't be statically \
+                                     analyzed{hints}",
+                                ),
+                                DiagnosticId::Error(
+                                    errors::failed_to_analyse::ecmascript::EVAL.to_string(),
+                                ),
+                            )
+                        }
+                    }
                     JsValue::WellKnownFunction(WellKnownFunctionKind::Define) => {
                         analyze_amd_define(
                             analysis,
@@ -1154,9 +1216,23 @@ pub(crate) async fn analyze_ecmascript_module(
                 ast_path: &[AstParentKind],
                 obj: JsValue,
                 prop: JsValue,
+                feature_flags: &HashMap<String, bool>,
                 analysis: &mut AnalyzeEcmascriptModuleResultBuilder,
             ) -> Result<()> {
                 match (obj, prop) {
+                    (JsValue::FreeVar(obj), JsValue::Constant(s))
+                        if obj.as_str() == FEATURE_FLAGS_OBJECT && s.as_str().is_some() =>
+                    {
+                        let enabled = s
+                            .as_str()
+                            .and_then(|flag| feature_flags.get(flag))
+                            .copied()
+                            .unwrap_or(false);
+                        analysis.add_code_gen(FeatureFlagAccessVc::new(
+                            enabled,
+                            AstPathVc::cell(ast_path.to_vec()),
+                        ));
+                    }
                     (
                         JsValue::WellKnownFunction(WellKnownFunctionKind::Require),
                         JsValue::Constant(s),
@@ -1194,6 +1270,7 @@ pub(crate) async fn analyze_ecmascript_module(
                 .extend(effects.into_iter().map(Action::Effect).rev());
 
             let linker = |value| value_visitor(origin, value, compile_time_info);
+            let feature_flags = compile_time_info.await?.feature_flags.await?;
             // There can be many references to import.meta, but only the first should hoist
             // the object allocation.
             let mut first_import_meta = true;
@@ -1349,6 +1426,9 @@ pub(crate) async fn analyze_ecmascript_module(
                                     &add_effects,
                                     &mut analysis,
                                     compile_time_info,
+                                    options.strict_analysis,
+                                    comments,
+                                    options.prefetch,
                                 )
                                 .await?;
                             }
@@ -1416,6 +1496,9 @@ pub(crate) async fn analyze_ecmascript_module(
                                     &add_effects,
                                     &mut analysis,
                                     compile_time_info,
+                                    options.strict_analysis,
+                                    comments,
+                                    options.prefetch,
                                 )
                                 .await?;
                             }
@@ -1428,7 +1511,8 @@ pub(crate) async fn analyze_ecmascript_module(
                                 let obj = link_value(obj).await?;
                                 let prop = link_value(prop).await?;
 
-                                handle_member(&ast_path, obj, prop, &mut analysis).await?;
+                                handle_member(&ast_path, obj, prop, &feature_flags, &mut analysis)
+                                    .await?;
                             }
                             Effect::ImportedBinding {
                                 esm_reference_index,
@@ -1697,6 +1781,16 @@ async fn value_visitor_inner(
                 return Ok((value.into(), true));
             }
         }
+        if def_name_len == 2 {
+            let mut name = v.iter_defineable_name_rev();
+            if let (Some(flag), Some(obj)) = (name.next(), name.next()) {
+                if obj == FEATURE_FLAGS_OBJECT {
+                    let feature_flags = compile_time_info.feature_flags.await?;
+                    let enabled = feature_flags.get(&*flag).copied().unwrap_or(false);
+                    return Ok((JsValue::Constant(enabled.into()), true));
+                }
+            }
+        }
     }
     let value = match v {
         JsValue::Call(
@@ -8,7 +8,7 @@ use turbo_tasks::{debug::ValueDebug, primitives::StringVc, Value, ValueToString}
 use turbopack_core::{
     asset::Asset,
     chunk::{
-        availability_info::AvailabilityInfo, ChunkableAssetVc, ChunkingContextVc,
+        availability_info::AvailabilityInfo, ChunkPrefetch, ChunkableAssetVc, ChunkingContextVc,
         FromChunkableAsset, ModuleId,
     },
     issue::{code_gen::CodeGenerationIssue, IssueSeverity},
@@ -65,7 +65,7 @@ pub(crate) enum PatternMapping {
 #[derive(PartialOrd, Ord, Hash, Debug, Copy, Clone)]
 #[turbo_tasks::value(serialization = "auto_for_input")]
 pub(crate) enum ResolveType {
-    EsmAsync(AvailabilityInfo),
+    EsmAsync(AvailabilityInfo, Option<ChunkPrefetch>),
     Cjs,
 }
 
@@ -168,7 +168,7 @@ impl PatternMappingVc {
         };
 
         if let Some(chunkable) = ChunkableAssetVc::resolve_from(asset).await? {
-            if let ResolveType::EsmAsync(availability_info) = *resolve_type {
+            if let ResolveType::EsmAsync(availability_info, prefetch) = *resolve_type {
                 let available = if let Some(available_assets) = availability_info.available_assets()
                 {
                     *available_assets.includes(chunkable.into()).await?
@@ -180,6 +180,7 @@ impl PatternMappingVc {
                         context,
                         chunkable,
                         Value::new(availability_info),
+                        prefetch,
                     )
                     .await?
                     {
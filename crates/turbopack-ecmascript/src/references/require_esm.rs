@@ -0,0 +1,132 @@
+use anyhow::Result;
+use serde_json::Value as JsonValue;
+use turbo_tasks::{
+    primitives::{StringVc, StringsVc},
+    CompletionVc,
+};
+use turbo_tasks_fs::{FileJsonContent, FileSystemPathVc};
+use turbopack_core::{
+    issue::{Issue, IssueSeverity, IssueSeverityVc, IssueVc},
+    resolve::{
+        find_context_file,
+        parse::{Request, RequestVc},
+        FindContextFileResult,
+    },
+};
+
+/// Whether a package can only be imported, i.e. it's an ES module package
+/// whose `exports` don't have a `require` condition. Node.js throws
+/// `ERR_REQUIRE_ESM` when such a package is `require()`d.
+pub(crate) fn is_esm_only(package_json: &JsonValue) -> bool {
+    fn has_require_condition(exports: &JsonValue) -> bool {
+        match exports {
+            JsonValue::Object(map) => map
+                .iter()
+                .any(|(key, value)| key == "require" || has_require_condition(value)),
+            JsonValue::Array(items) => items.iter().any(has_require_condition),
+            _ => false,
+        }
+    }
+
+    package_json["type"].as_str() == Some("module")
+        && !has_require_condition(&package_json["exports"])
+}
+
+/// Emits a [RequireEsmIssue] when `request`, which is `require()`d by the
+/// module at `origin_path` and left external, names an ESM-only package (see
+/// [is_esm_only]) installed in a `node_modules` directory above it.
+#[turbo_tasks::function]
+pub(crate) async fn check_require_esm(
+    origin_path: FileSystemPathVc,
+    request: RequestVc,
+) -> Result<CompletionVc> {
+    let Request::Module { module, .. } = &*request.await? else {
+        return Ok(CompletionVc::new());
+    };
+    let package_json = format!("node_modules/{module}/package.json");
+    let FindContextFileResult::Found(package_json, _) =
+        &*find_context_file(origin_path.parent(), StringsVc::cell(vec![package_json])).await?
+    else {
+        return Ok(CompletionVc::new());
+    };
+    if let FileJsonContent::Content(package_json) = &*package_json.read_json().await? {
+        if is_esm_only(package_json) {
+            RequireEsmIssue {
+                path: origin_path,
+                package: module.clone(),
+            }
+            .cell()
+            .as_issue()
+            .emit();
+        }
+    }
+    Ok(CompletionVc::new())
+}
+
+/// A CommonJS module `require()`s an ESM-only package, which fails at runtime.
+#[turbo_tasks::value(shared)]
+pub struct RequireEsmIssue {
+    /// The module containing the `require()`.
+    pub path: FileSystemPathVc,
+    pub package: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for RequireEsmIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("module".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell(format!(
+            "ESM-only package {} can't be required",
+            self.package
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> StringVc {
+        StringVc::cell(format!(
+            "{package} is an ES module package without a `require` export, so Node.js fails to \
+             load it with ERR_REQUIRE_ESM. Use `await import(\"{package}\")` instead of \
+             `require(\"{package}\")`, or convert this module to ESM and use an `import` \
+             statement.",
+            package = self.package
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::is_esm_only;
+
+    #[test]
+    fn esm_only_packages() {
+        assert!(is_esm_only(
+            &json!({ "type": "module", "main": "index.js" })
+        ));
+        assert!(is_esm_only(&json!({
+            "type": "module",
+            "exports": { ".": { "import": "./index.js", "types": "./index.d.ts" } }
+        })));
+        assert!(!is_esm_only(&json!({
+            "type": "module",
+            "exports": { ".": { "import": "./index.js", "require": "./index.cjs" } }
+        })));
+        assert!(!is_esm_only(&json!({ "main": "index.js" })));
+    }
+}
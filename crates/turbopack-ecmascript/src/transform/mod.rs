@@ -1,6 +1,7 @@
+mod react_refresh;
 mod server_to_client_proxy;
 
-use std::{fmt::Debug, path::Path, sync::Arc};
+use std::{borrow::Cow, fmt::Debug, path::Path, sync::Arc};
 
 use anyhow::Result;
 use swc_core::{
@@ -16,11 +17,15 @@ use swc_core::{
         visit::{FoldWith, VisitMutWith},
     },
 };
+use tracing::Instrument;
 use turbo_tasks::primitives::{OptionStringVc, StringVc};
 use turbo_tasks_fs::json::parse_json_with_source_context;
 use turbopack_core::environment::EnvironmentVc;
 
-use self::server_to_client_proxy::{create_proxy_module, is_client_module};
+use self::{
+    react_refresh::mark_react_refresh_boundary,
+    server_to_client_proxy::{create_proxy_module, is_client_module},
+};
 
 #[turbo_tasks::value(serialization = "auto_for_input")]
 #[derive(Debug, Clone, PartialOrd, Ord, Hash)]
@@ -62,6 +67,12 @@ pub enum EcmascriptInputTransform {
 /// transformer to run over all ECMAScript files imported in the graph.
 pub trait CustomTransformer: Debug {
     fn transform(&self, program: &mut Program, ctx: &TransformContext<'_>) -> Option<Program>;
+
+    /// The name of the transformer in traces, e.g. the name of the SWC plugin
+    /// it runs.
+    fn name(&self) -> &str {
+        "custom"
+    }
 }
 
 /// A wrapper around a CustomTransformer instance, allowing it to operate with
@@ -80,6 +91,10 @@ impl CustomTransformer for CustomTransform {
     fn transform(&self, program: &mut Program, ctx: &TransformContext<'_>) -> Option<Program> {
         self.0.transform(program, ctx)
     }
+
+    fn name(&self) -> &str {
+        self.0.name()
+    }
 }
 
 #[turbo_tasks::value(transparent, serialization = "auto_for_input")]
@@ -107,7 +122,41 @@ pub struct TransformContext<'a> {
 }
 
 impl EcmascriptInputTransform {
+    /// The name of the transform in traces.
+    pub async fn name(&self) -> Result<Cow<'static, str>> {
+        Ok(match self {
+            EcmascriptInputTransform::ClientDirective(_) => "client directive".into(),
+            EcmascriptInputTransform::CommonJs => "commonjs".into(),
+            EcmascriptInputTransform::Custom(transform) => {
+                transform.await?.name().to_string().into()
+            }
+            EcmascriptInputTransform::Emotion => "emotion".into(),
+            EcmascriptInputTransform::PresetEnv(_) => "preset env".into(),
+            EcmascriptInputTransform::React { .. } => "react".into(),
+            EcmascriptInputTransform::StyledComponents => "styled components".into(),
+            EcmascriptInputTransform::StyledJsx => "styled jsx".into(),
+            EcmascriptInputTransform::TypeScript { .. } => "typescript".into(),
+            EcmascriptInputTransform::Decorators { .. } => "decorators".into(),
+        })
+    }
+
+    /// Applies the transform to the `program`, within a `transform ecmascript`
+    /// span that is tagged with the name of the transform and the path of the
+    /// module. The duration of these spans tells which transforms are slow.
     pub async fn apply(&self, program: &mut Program, ctx: &TransformContext<'_>) -> Result<()> {
+        let span = tracing::info_span!(
+            "transform ecmascript",
+            transform = %self.name().await?,
+            path = ctx.file_path_str,
+        );
+        self.apply_untraced(program, ctx).instrument(span).await
+    }
+
+    async fn apply_untraced(
+        &self,
+        program: &mut Program,
+        ctx: &TransformContext<'_>,
+    ) -> Result<()> {
         let &TransformContext {
             comments,
             source_map,
@@ -159,6 +208,10 @@ impl EcmascriptInputTransform {
                     config,
                     top_level_mark,
                 ));
+
+                if *refresh {
+                    mark_react_refresh_boundary(program);
+                }
             }
             EcmascriptInputTransform::CommonJs => {
                 program.visit_mut_with(&mut swc_core::ecma::transforms::module::common_js(
@@ -0,0 +1,238 @@
+use swc_core::{
+    ecma::ast::{
+        Decl, DefaultDecl, ExportSpecifier, Expr, Ident, ModuleDecl, ModuleExportName, ModuleItem,
+        Pat, Program,
+    },
+    quote,
+};
+
+/// Whether a module can be a React Refresh boundary, as far as it can be told
+/// from its source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshBoundary {
+    /// All exports look like components. The runtime still checks the actual
+    /// exports before accepting updates.
+    Components,
+    /// The module has exports which are not components, or no exports at all.
+    /// Updates of the module are never accepted by itself, but bubble up to
+    /// its importers.
+    NotRefreshSafe,
+    /// The exports can't be determined statically, e.g. for CommonJS modules
+    /// or `export *`. The runtime decides based on the actual exports.
+    Unknown,
+}
+
+impl RefreshBoundary {
+    fn and(self, other: RefreshBoundary) -> RefreshBoundary {
+        match (self, other) {
+            (RefreshBoundary::NotRefreshSafe, _) | (_, RefreshBoundary::NotRefreshSafe) => {
+                RefreshBoundary::NotRefreshSafe
+            }
+            (RefreshBoundary::Unknown, _) | (_, RefreshBoundary::Unknown) => {
+                RefreshBoundary::Unknown
+            }
+            _ => RefreshBoundary::Components,
+        }
+    }
+}
+
+/// React Refresh considers a value to be a component when its name starts
+/// with an uppercase letter.
+fn is_component_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+}
+
+fn ident_boundary(ident: &Ident) -> RefreshBoundary {
+    if is_component_name(&ident.sym) {
+        RefreshBoundary::Components
+    } else {
+        RefreshBoundary::NotRefreshSafe
+    }
+}
+
+fn export_name_boundary(name: &ModuleExportName) -> RefreshBoundary {
+    match name {
+        ModuleExportName::Ident(ident) if &*ident.sym == "default" => RefreshBoundary::Unknown,
+        ModuleExportName::Ident(ident) => ident_boundary(ident),
+        ModuleExportName::Str(str) if is_component_name(&str.value) => RefreshBoundary::Components,
+        ModuleExportName::Str(_) => RefreshBoundary::NotRefreshSafe,
+    }
+}
+
+fn decl_boundary(decl: &Decl) -> Option<RefreshBoundary> {
+    Some(match decl {
+        Decl::Class(class) => ident_boundary(&class.ident),
+        Decl::Fn(function) => ident_boundary(&function.ident),
+        Decl::Var(var) => var
+            .decls
+            .iter()
+            .map(|decl| match &decl.name {
+                Pat::Ident(ident) => ident_boundary(&ident.id),
+                _ => RefreshBoundary::Unknown,
+            })
+            .fold(RefreshBoundary::Components, RefreshBoundary::and),
+        // Enums and namespaces are objects at runtime.
+        Decl::TsEnum(_) | Decl::TsModule(_) => RefreshBoundary::NotRefreshSafe,
+        // Types don't exist at runtime.
+        _ => return None,
+    })
+}
+
+fn default_expr_boundary(expr: &Expr) -> RefreshBoundary {
+    match expr {
+        Expr::Ident(ident) => ident_boundary(ident),
+        Expr::Paren(paren) => default_expr_boundary(&paren.expr),
+        Expr::Lit(_) | Expr::Object(_) | Expr::Array(_) | Expr::Tpl(_) => {
+            RefreshBoundary::NotRefreshSafe
+        }
+        // Anonymous functions and wrapped components (e.g. `memo(Component)`)
+        // are checked at runtime.
+        _ => RefreshBoundary::Unknown,
+    }
+}
+
+/// Determines whether the module only exports components, which allows it
+/// to be a React Refresh boundary that accepts its own updates.
+pub fn detect_react_refresh_boundary(program: &Program) -> RefreshBoundary {
+    let Program::Module(module) = program else {
+        return RefreshBoundary::Unknown;
+    };
+    let mut has_exports = false;
+    let mut boundary = RefreshBoundary::Components;
+    for item in &module.body {
+        let ModuleItem::ModuleDecl(decl) = item else {
+            continue;
+        };
+        let item_boundary = match decl {
+            ModuleDecl::ExportDecl(export) => decl_boundary(&export.decl),
+            ModuleDecl::ExportNamed(named) if named.type_only => None,
+            ModuleDecl::ExportNamed(named) => Some(
+                named
+                    .specifiers
+                    .iter()
+                    .filter_map(|specifier| match specifier {
+                        ExportSpecifier::Named(named) if named.is_type_only => None,
+                        ExportSpecifier::Named(named) => Some(export_name_boundary(
+                            named.exported.as_ref().unwrap_or(&named.orig),
+                        )),
+                        ExportSpecifier::Default(default) => {
+                            Some(ident_boundary(&default.exported))
+                        }
+                        ExportSpecifier::Namespace(namespace) => {
+                            Some(export_name_boundary(&namespace.name))
+                        }
+                    })
+                    .fold(RefreshBoundary::Components, RefreshBoundary::and),
+            ),
+            ModuleDecl::ExportDefaultDecl(default) => match &default.decl {
+                DefaultDecl::Class(class) => Some(
+                    class
+                        .ident
+                        .as_ref()
+                        .map_or(RefreshBoundary::Unknown, ident_boundary),
+                ),
+                DefaultDecl::Fn(function) => Some(
+                    function
+                        .ident
+                        .as_ref()
+                        .map_or(RefreshBoundary::Unknown, ident_boundary),
+                ),
+                DefaultDecl::TsInterfaceDecl(_) => None,
+            },
+            ModuleDecl::ExportDefaultExpr(default) => Some(default_expr_boundary(&default.expr)),
+            ModuleDecl::ExportAll(_)
+            | ModuleDecl::TsExportAssignment(_)
+            | ModuleDecl::TsNamespaceExport(_) => Some(RefreshBoundary::Unknown),
+            ModuleDecl::Import(_) | ModuleDecl::TsImportEquals(_) => None,
+        };
+        if let Some(item_boundary) = item_boundary {
+            has_exports = true;
+            boundary = boundary.and(item_boundary);
+        }
+    }
+    if has_exports {
+        boundary
+    } else {
+        RefreshBoundary::NotRefreshSafe
+    }
+}
+
+/// Tells the HMR runtime whether the module can be a React Refresh boundary,
+/// by calling `__turbopack_refresh__` at the end of the module.
+pub fn mark_react_refresh_boundary(program: &mut Program) {
+    let stmt = match detect_react_refresh_boundary(program) {
+        RefreshBoundary::Components => quote!("__turbopack_refresh__(true);" as Stmt),
+        RefreshBoundary::NotRefreshSafe => quote!("__turbopack_refresh__(false);" as Stmt),
+        RefreshBoundary::Unknown => return,
+    };
+    if let Program::Module(module) = program {
+        module.body.push(ModuleItem::Stmt(stmt));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::{
+        common::{FileName, SourceMap},
+        ecma::parser::{parse_file_as_program, Syntax, TsConfig},
+    };
+
+    use super::*;
+
+    fn detect(source: &str) -> RefreshBoundary {
+        let cm = SourceMap::default();
+        let fm = cm.new_source_file(FileName::Anon, source.to_string());
+        let program = parse_file_as_program(
+            &fm,
+            Syntax::Typescript(TsConfig {
+                tsx: true,
+                ..Default::default()
+            }),
+            Default::default(),
+            None,
+            &mut vec![],
+        )
+        .unwrap();
+        detect_react_refresh_boundary(&program)
+    }
+
+    #[test]
+    fn component_only_modules() {
+        assert_eq!(
+            detect("export function App() {} export const Button = () => null;"),
+            RefreshBoundary::Components
+        );
+        assert_eq!(
+            detect("function Page() {} export default Page; export type Props = {};"),
+            RefreshBoundary::Components
+        );
+    }
+
+    #[test]
+    fn modules_with_other_exports() {
+        assert_eq!(
+            detect("export function App() {} export const useApp = () => {};"),
+            RefreshBoundary::NotRefreshSafe
+        );
+        assert_eq!(
+            detect("import './x'; run();"),
+            RefreshBoundary::NotRefreshSafe
+        );
+        assert_eq!(
+            detect("export default { Button };"),
+            RefreshBoundary::NotRefreshSafe
+        );
+    }
+
+    #[test]
+    fn modules_checked_at_runtime() {
+        assert_eq!(
+            detect("export * from './components';"),
+            RefreshBoundary::Unknown
+        );
+        assert_eq!(
+            detect("export default memo(function Button() {});"),
+            RefreshBoundary::Unknown
+        );
+    }
+}
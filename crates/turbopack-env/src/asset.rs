@@ -2,26 +2,49 @@ use std::fmt::Write as _;
 
 use anyhow::Result;
 use turbo_tasks::Value;
-use turbo_tasks_env::{ProcessEnv, ProcessEnvVc};
+use turbo_tasks_env::{EnvMapVc, FilterProcessEnvVc, ProcessEnv, ProcessEnvVc};
 use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_core::{
-    asset::{Asset, AssetContentVc, AssetVc},
+    asset::{Asset, AssetContentVc, AssetVc, AssetsVc},
     chunk::{
         availability_info::AvailabilityInfo, ChunkItem, ChunkItemVc, ChunkVc, ChunkableAsset,
-        ChunkableAssetVc, ChunkingContextVc,
+        ChunkableAssetVc, ChunkingContext, ChunkingContextVc,
     },
+    environment::Rendering,
     ident::AssetIdentVc,
-    reference::AssetReferencesVc,
+    reference::{all_assets, AssetReferencesVc},
 };
 use turbopack_ecmascript::{
     chunk::{
         EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkItemContentVc,
         EcmascriptChunkItemVc, EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc,
-        EcmascriptChunkVc, EcmascriptChunkingContextVc, EcmascriptExports, EcmascriptExportsVc,
+        EcmascriptChunkPlaceablesVc, EcmascriptChunkVc, EcmascriptChunkingContextVc,
+        EcmascriptExports, EcmascriptExportsVc,
     },
+    process_env::{ProcessEnvUsage, ProcessEnvUsageVc},
     utils::StringifyJs,
+    EcmascriptModuleAssetVc,
 };
 
+use crate::{check_client_env_usage, EnvSchemaVc, ProcessEnvTypesAssetVc};
+
+/// How the [ProcessEnvAsset] provides the env to the modules.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(PartialOrd, Ord, Hash, Debug, Default, Copy, Clone)]
+pub enum ProcessEnvMode {
+    /// The values of the env are inlined into the chunk.
+    #[default]
+    Inline,
+    /// The real `process.env` is read at runtime. The values of the env aren't
+    /// embedded, only the declared defaults (see
+    /// [ProcessEnvAssetVc::with_runtime_defaults]) of variables which aren't
+    /// set.
+    Runtime,
+    /// [ProcessEnvMode::Runtime] for chunks which are rendered on a server,
+    /// [ProcessEnvMode::Inline] for all others, e.g. client chunks.
+    RuntimeOnServer,
+}
+
 /// The `process.env` asset, responsible for initializing the env (shared by all
 /// chunks) during app startup.
 #[turbo_tasks::value]
@@ -31,16 +54,237 @@ pub struct ProcessEnvAsset {
 
     /// A HashMap filled with the env key/values.
     env: ProcessEnvVc,
+
+    /// When set, only the env variables which are accessed by the modules
+    /// reachable from these entries are included.
+    entries: Option<AssetsVc>,
+
+    mode: ProcessEnvMode,
+
+    /// When set, chunks which aren't rendered on a server, e.g. browser
+    /// chunks, only get the env variables starting with one of these prefixes
+    /// (ignoring casing). Server chunks still get the full env.
+    client_prefixes: Option<Vec<String>>,
+
+    /// When set, the variables are set by a [ProcessEnvKeyAsset] each instead,
+    /// see [ProcessEnvAssetVc::runtime_entries].
+    per_key: bool,
+
+    /// The values of variables which aren't set when the env is read at
+    /// runtime, as JS literals.
+    runtime_defaults: Option<EnvMapVc>,
 }
 
 #[turbo_tasks::value_impl]
 impl ProcessEnvAssetVc {
     #[turbo_tasks::function]
     pub fn new(root: FileSystemPathVc, env: ProcessEnvVc) -> Self {
-        ProcessEnvAsset { root, env }.cell()
+        ProcessEnvAsset {
+            root,
+            env,
+            entries: None,
+            mode: ProcessEnvMode::Inline,
+            client_prefixes: None,
+            per_key: false,
+            runtime_defaults: None,
+        }
+        .cell()
+    }
+
+    /// Creates an env asset which only includes the env variables the modules
+    /// reachable from the `entries` access as `process.env.NAME`, so changes
+    /// of other variables don't invalidate it. All variables are included
+    /// when a module accesses `process.env` in another way.
+    #[turbo_tasks::function]
+    pub fn new_for_entries(root: FileSystemPathVc, env: ProcessEnvVc, entries: AssetsVc) -> Self {
+        ProcessEnvAsset {
+            root,
+            env,
+            entries: Some(entries),
+            mode: ProcessEnvMode::Inline,
+            client_prefixes: None,
+            per_key: false,
+            runtime_defaults: None,
+        }
+        .cell()
+    }
+
+    /// Changes how the env is provided to the modules, e.g. so server chunks
+    /// read it at runtime instead of having it baked in.
+    #[turbo_tasks::function]
+    pub async fn with_mode(self, mode: Value<ProcessEnvMode>) -> Result<Self> {
+        let this = self.await?;
+        Ok(ProcessEnvAsset {
+            root: this.root,
+            env: this.env,
+            entries: this.entries,
+            mode: mode.into_value(),
+            client_prefixes: this.client_prefixes.clone(),
+            per_key: this.per_key,
+            runtime_defaults: this.runtime_defaults,
+        }
+        .cell())
+    }
+
+    /// Only exposes the env variables starting with one of the `prefixes`
+    /// (e.g. `PUBLIC_`) to client chunks, so secrets meant for server
+    /// rendering can't leak into browser bundles. With `entries`, accesses of
+    /// other variables in client modules are reported.
+    #[turbo_tasks::function]
+    pub async fn with_client_prefixes(self, prefixes: Vec<String>) -> Result<Self> {
+        let this = self.await?;
+        Ok(ProcessEnvAsset {
+            root: this.root,
+            env: this.env,
+            entries: this.entries,
+            mode: this.mode,
+            client_prefixes: Some(prefixes),
+            per_key: this.per_key,
+            runtime_defaults: this.runtime_defaults,
+        }
+        .cell())
+    }
+
+    /// Sets the `defaults` (JS literals, e.g. the
+    /// [defaults of a schema](crate::EnvSchemaVc::defaults)) of variables
+    /// which aren't set when the env is read at runtime. Nothing else is
+    /// embedded into those chunks.
+    #[turbo_tasks::function]
+    pub async fn with_runtime_defaults(self, defaults: EnvMapVc) -> Result<Self> {
+        let this = self.await?;
+        Ok(ProcessEnvAsset {
+            root: this.root,
+            env: this.env,
+            entries: this.entries,
+            mode: this.mode,
+            client_prefixes: this.client_prefixes.clone(),
+            per_key: this.per_key,
+            runtime_defaults: Some(defaults),
+        }
+        .cell())
+    }
+
+    /// The runtime entries providing the env. When the accessed variables are
+    /// known (see [ProcessEnvAssetVc::new_for_entries]), every variable is set
+    /// by its own [ProcessEnvKeyAsset] evaluated after this asset. Changing a
+    /// variable then only invalidates and updates the chunk item of that
+    /// variable instead of the whole env.
+    #[turbo_tasks::function]
+    pub async fn runtime_entries(self) -> Result<EcmascriptChunkPlaceablesVc> {
+        let this = self.await?;
+        let usage = match this.entries {
+            Some(entries) => process_env_usage_of(entries).await?,
+            None => return Ok(EcmascriptChunkPlaceablesVc::cell(vec![self.into()])),
+        };
+        if usage.dynamic {
+            return Ok(EcmascriptChunkPlaceablesVc::cell(vec![self.into()]));
+        }
+        let env_asset = ProcessEnvAsset {
+            root: this.root,
+            env: this.env,
+            entries: this.entries,
+            mode: this.mode,
+            client_prefixes: this.client_prefixes.clone(),
+            per_key: true,
+            runtime_defaults: this.runtime_defaults,
+        }
+        .cell();
+        let mut placeables: Vec<EcmascriptChunkPlaceableVc> = vec![env_asset.into()];
+        for name in &usage.keys {
+            placeables.push(ProcessEnvKeyAssetVc::new(env_asset, name.clone()).into());
+        }
+        Ok(EcmascriptChunkPlaceablesVc::cell(placeables))
+    }
+
+    /// The `env.d.ts` declarations of the variables of the env, next to the
+    /// env asset. Pass the `schema` the env was validated with to declare
+    /// the types the variables are coerced to.
+    #[turbo_tasks::function]
+    pub async fn types(self, schema: Option<EnvSchemaVc>) -> Result<ProcessEnvTypesAssetVc> {
+        let this = self.await?;
+        Ok(ProcessEnvTypesAssetVc::new(
+            this.root.join("env.d.ts"),
+            this.env,
+            schema,
+        ))
     }
 }
 
+impl ProcessEnvAsset {
+    /// The env provided to server or client chunks. When the env is read at
+    /// runtime, it's only the runtime defaults, so no build-time values end up
+    /// in the chunks.
+    fn chunk_env(&self, server: bool) -> ProcessEnvVc {
+        let env = if self.runtime(server) {
+            self.runtime_defaults
+                .unwrap_or_else(EnvMapVc::empty)
+                .as_process_env()
+        } else {
+            self.env
+        };
+        match &self.client_prefixes {
+            Some(prefixes) if !server => {
+                FilterProcessEnvVc::new(env, prefixes.clone()).as_process_env()
+            }
+            _ => env,
+        }
+    }
+
+    /// Whether the real `process.env` is read at runtime in server or client
+    /// chunks.
+    fn runtime(&self, server: bool) -> bool {
+        match self.mode {
+            ProcessEnvMode::Inline => false,
+            ProcessEnvMode::Runtime => true,
+            ProcessEnvMode::RuntimeOnServer => server,
+        }
+    }
+}
+
+/// Whether the chunks of the `context` are rendered on a server.
+async fn is_server(context: EcmascriptChunkingContextVc) -> Result<bool> {
+    Ok(matches!(
+        *context.environment().rendering().await?,
+        Rendering::Server(_)
+    ))
+}
+
+/// Writes the code setting the variable `name` of `env` to the JS literal
+/// `val`. When the env is read at `runtime`, it's a default for a variable
+/// which isn't set.
+fn write_assignment(
+    code: &mut String,
+    env: &str,
+    name: &str,
+    val: &str,
+    runtime: bool,
+) -> Result<()> {
+    let name = StringifyJs(name);
+    if runtime {
+        writeln!(
+            code,
+            "if ({env}[{name}] === undefined) {env}[{name}] = {val};"
+        )?;
+    } else {
+        writeln!(code, "{env}[{name}] = {val};")?;
+    }
+    Ok(())
+}
+
+/// How the modules reachable from the `entries` access `process.env`.
+#[turbo_tasks::function]
+async fn process_env_usage_of(entries: AssetsVc) -> Result<ProcessEnvUsageVc> {
+    let mut usage = ProcessEnvUsage::default();
+    for entry in entries.await?.iter() {
+        for asset in all_assets(*entry).await?.iter() {
+            if let Some(module) = EcmascriptModuleAssetVc::resolve_from(*asset).await? {
+                usage.extend(&*module.process_env_usage().await?);
+            }
+        }
+    }
+    Ok(usage.cell())
+}
+
 #[turbo_tasks::value_impl]
 impl Asset for ProcessEnvAsset {
     #[turbo_tasks::function]
@@ -92,6 +336,37 @@ impl EcmascriptChunkPlaceable for ProcessEnvAsset {
     }
 }
 
+/// Wraps an env in a proxy which looks up variables ignoring casing, resolving
+/// to an existing variable with a different casing when there is one. The
+/// lowercased names are only collected on the first miss and again after
+/// variables were added or removed through the proxy.
+const CASE_INSENSITIVE_ENV: &str = r#"const caseInsensitiveEnv = (target) => {
+  let keys = null;
+  const keyOf = (name) => {
+    if (typeof name !== "string" || name in target) return name;
+    if (keys === null) {
+      keys = new Map();
+      for (const key of Object.keys(target)) keys.set(key.toLowerCase(), key);
+    }
+    return keys.get(name.toLowerCase()) ?? name;
+  };
+  return new Proxy(target, {
+    get: (env, name) => env[keyOf(name)],
+    set: (env, name, value) => {
+      const key = keyOf(name);
+      if (!(key in env)) keys = null;
+      env[key] = value;
+      return true;
+    },
+    has: (env, name) => keyOf(name) in env,
+    deleteProperty: (env, name) => {
+      keys = null;
+      return delete env[keyOf(name)];
+    },
+  });
+};
+"#;
+
 #[turbo_tasks::value]
 struct ProcessEnvChunkItem {
     context: EcmascriptChunkingContextVc,
@@ -121,20 +396,191 @@ impl EcmascriptChunkItem for ProcessEnvChunkItem {
     #[turbo_tasks::function]
     async fn content(&self) -> Result<EcmascriptChunkItemContentVc> {
         let asset = self.inner.await?;
-        let env = asset.env.read_all().await?;
+        let server = is_server(self.context).await?;
+        if !server {
+            if let (Some(prefixes), Some(entries)) = (&asset.client_prefixes, asset.entries) {
+                check_client_env_usage(asset.env, prefixes.clone(), entries).await?;
+            }
+        }
+        let env = asset.chunk_env(server);
+
+        let mut values = Vec::new();
+        if !asset.per_key {
+            let usage = match asset.entries {
+                Some(entries) => Some(process_env_usage_of(entries).await?),
+                None => None,
+            };
+            match usage.filter(|usage| !usage.dynamic) {
+                Some(usage) => {
+                    // Only the accessed variables are read, so the chunk item doesn't
+                    // depend on the others.
+                    for name in &usage.keys {
+                        if let Some(val) = &*env.read(name).await? {
+                            values.push((name.clone(), val.clone()));
+                        }
+                    }
+                }
+                None => {
+                    let env = env.read_all().await?;
+                    values.extend(env.iter().map(|(name, val)| (name.clone(), val.clone())));
+                }
+            }
+        }
+
+        let runtime = asset.runtime(server);
+
+        // It's assumed the env has passed through an EmbeddableProcessEnv, so the value
+        // is ready to be directly embedded. Values _after_ an embeddable
+        // env can be used to inject live code into the output.
+        let env = if runtime {
+            "process.env"
+        } else {
+            // TODO: In SSR, we use the native process.env, which can only contain string
+            // values. We need to inject literal values (to emulate webpack's
+            // DefinePlugin), so create a new regular object out of the old env.
+            "{...process.env}"
+        };
+        let mut code = String::new();
+        if server {
+            // The env is wrapped so `process.env.path === process.env.PATH ===
+            // process.env.PaTh`, like on Windows, on all platforms. Client code
+            // only sees the embedded variables, which it accesses by the
+            // names they're declared with.
+            code.push_str(CASE_INSENSITIVE_ENV);
+            writeln!(
+                code,
+                "const env = process.env = caseInsensitiveEnv({env});\n"
+            )?;
+        } else if runtime {
+            code.push_str("const env = process.env;\n\n");
+        } else {
+            writeln!(code, "const env = process.env = {env};\n")?;
+        }
+        for (name, val) in values {
+            write_assignment(&mut code, "env", &name, &val, runtime)?;
+        }
+
+        Ok(EcmascriptChunkItemContent {
+            inner_code: code.into(),
+            ..Default::default()
+        }
+        .cell())
+    }
+}
+
+/// Sets a single variable of the env set up by a [ProcessEnvAsset], see
+/// [ProcessEnvAssetVc::runtime_entries].
+#[turbo_tasks::value]
+pub struct ProcessEnvKeyAsset {
+    env_asset: ProcessEnvAssetVc,
+    name: String,
+}
 
-        // TODO: In SSR, we use the native process.env, which can only contain string
-        // values. We need to inject literal values (to emulate webpack's
-        // DefinePlugin), so create a new regular object out of the old env.
-        let mut code = "const env = process.env = {...process.env};\n\n".to_string();
+#[turbo_tasks::value_impl]
+impl ProcessEnvKeyAssetVc {
+    #[turbo_tasks::function]
+    pub fn new(env_asset: ProcessEnvAssetVc, name: String) -> Self {
+        ProcessEnvKeyAsset { env_asset, name }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for ProcessEnvKeyAsset {
+    #[turbo_tasks::function]
+    async fn ident(&self) -> Result<AssetIdentVc> {
+        let root = self.env_asset.await?.root;
+        Ok(AssetIdentVc::from_path(
+            root.join(&format!(".env.{}.js", self.name)),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn content(&self) -> AssetContentVc {
+        unimplemented!();
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> AssetReferencesVc {
+        unimplemented!();
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableAsset for ProcessEnvKeyAsset {
+    #[turbo_tasks::function]
+    fn as_chunk(
+        self_vc: ProcessEnvKeyAssetVc,
+        context: ChunkingContextVc,
+        availability_info: Value<AvailabilityInfo>,
+    ) -> ChunkVc {
+        EcmascriptChunkVc::new(context, self_vc.into(), availability_info).into()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for ProcessEnvKeyAsset {
+    #[turbo_tasks::function]
+    fn as_chunk_item(
+        self_vc: ProcessEnvKeyAssetVc,
+        context: EcmascriptChunkingContextVc,
+    ) -> EcmascriptChunkItemVc {
+        ProcessEnvKeyChunkItem {
+            context,
+            inner: self_vc,
+        }
+        .cell()
+        .into()
+    }
+
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> EcmascriptExportsVc {
+        EcmascriptExports::None.cell()
+    }
+}
+
+#[turbo_tasks::value]
+struct ProcessEnvKeyChunkItem {
+    context: EcmascriptChunkingContextVc,
+    inner: ProcessEnvKeyAssetVc,
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for ProcessEnvKeyChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> AssetIdentVc {
+        self.inner.ident()
+    }
 
-        for (name, val) in &*env {
-            // It's assumed the env has passed through an EmbeddableProcessEnv, so the value
-            // is ready to be directly embedded. Values _after_ an embeddable
-            // env can be used to inject live code into the output.
-            // TODO this is not completely correct as env vars need to ignore casing
-            // So `process.env.path === process.env.PATH === process.env.PaTh`
-            writeln!(code, "env[{}] = {};", StringifyJs(name), val)?;
+    #[turbo_tasks::function]
+    fn references(&self) -> AssetReferencesVc {
+        AssetReferencesVc::empty()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for ProcessEnvKeyChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> EcmascriptChunkingContextVc {
+        self.context
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<EcmascriptChunkItemContentVc> {
+        let asset = self.inner.await?;
+        let env_asset = asset.env_asset.await?;
+        let server = is_server(self.context).await?;
+
+        // `process.env` is already set up by the env asset.
+        let mut code = String::new();
+        let runtime = env_asset.runtime(server);
+        match &*env_asset.chunk_env(server).read(&asset.name).await? {
+            Some(val) => write_assignment(&mut code, "process.env", &asset.name, val, runtime)?,
+            // When the variable is removed, the chunk item is updated to remove it from
+            // `process.env` again. A variable which is read at runtime is left alone.
+            None if !runtime => {
+                writeln!(code, "delete process.env[{}];", StringifyJs(&asset.name))?
+            }
+            None => {}
         }
 
         Ok(EcmascriptChunkItemContent {
@@ -144,3 +590,39 @@ impl EcmascriptChunkItem for ProcessEnvChunkItem {
         .cell())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use super::CASE_INSENSITIVE_ENV;
+
+    #[test]
+    fn looks_up_variables_ignoring_casing() {
+        let script = format!(
+            r#"{CASE_INSENSITIVE_ENV}
+const assert = require("assert");
+const target = {{ Path: "/bin" }};
+const env = caseInsensitiveEnv(target);
+assert.strictEqual(env.Path, "/bin");
+assert.strictEqual(env.PATH, "/bin");
+assert.ok("path" in env);
+assert.strictEqual(env.MISSING, undefined);
+assert.ok(!("MISSING" in env));
+env.missing = "added";
+assert.strictEqual(env.MISSING, "added");
+env.PATH = "/usr/bin";
+assert.deepStrictEqual(target, {{ Path: "/usr/bin", missing: "added" }});
+delete env.PATH;
+assert.strictEqual(env.path, undefined);
+assert.deepStrictEqual(target, {{ missing: "added" }});
+"#
+        );
+        let output = Command::new("node").args(["-e", &script]).output().unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
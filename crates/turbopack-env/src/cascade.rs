@@ -0,0 +1,61 @@
+use turbo_tasks_env::ProcessEnvVc;
+use turbo_tasks_fs::FileSystemPathVc;
+
+use crate::TryDotenvProcessEnvVc;
+
+/// The dotenv files loaded from a directory, the ones taking precedence first.
+/// `.env.local` files usually aren't committed and hold secrets and overrides
+/// of a single machine.
+pub fn dotenv_cascade_files(mode: Option<&str>) -> Vec<String> {
+    let mut files = Vec::new();
+    if let Some(mode) = mode {
+        files.push(format!(".env.{mode}.local"));
+    }
+    files.push(".env.local".to_string());
+    if let Some(mode) = mode {
+        files.push(format!(".env.{mode}"));
+    }
+    files.push(".env".to_string());
+    files
+}
+
+/// Loads the dotenv files of `dir` in the order of [dotenv_cascade_files] on
+/// top of `prior`, e.g. `.env.development.local`, `.env.local`,
+/// `.env.development` and `.env` for the `development` mode.
+///
+/// Variables of `prior` and of files earlier in the cascade can't be
+/// overridden by later files, but can be referenced by them. Missing files are
+/// skipped. Every file is read through the file system, so editing, creating
+/// or deleting one of them only recomputes the env and the assets embedding
+/// it, without a restart.
+#[turbo_tasks::function]
+pub fn dotenv_cascade(
+    prior: ProcessEnvVc,
+    dir: FileSystemPathVc,
+    mode: Option<String>,
+) -> ProcessEnvVc {
+    dotenv_cascade_files(mode.as_deref())
+        .into_iter()
+        .fold(prior, |prior, file| {
+            TryDotenvProcessEnvVc::new(prior, dir.join(&file)).as_process_env()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::dotenv_cascade_files;
+
+    #[test]
+    fn cascade_order() {
+        assert_eq!(dotenv_cascade_files(None), vec![".env.local", ".env"]);
+        assert_eq!(
+            dotenv_cascade_files(Some("production")),
+            vec![
+                ".env.production.local",
+                ".env.local",
+                ".env.production",
+                ".env"
+            ]
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use anyhow::Result;
+use turbo_tasks::{primitives::StringVc, CompletionVc};
+use turbo_tasks_env::{ProcessEnv, ProcessEnvVc};
+use turbo_tasks_fs::FileSystemPathVc;
+use turbopack_core::{
+    asset::{Asset, AssetsVc},
+    issue::{Issue, IssueSeverity, IssueSeverityVc, IssueSourceVc, IssueVc, OptionIssueSourceVc},
+    reference::all_assets,
+};
+use turbopack_ecmascript::EcmascriptModuleAssetVc;
+
+/// Emits an [UnembeddedEnvIssue] for every `process.env.NAME` access in the
+/// modules reachable from the client `entries` where `NAME` is set in `env`,
+/// but doesn't start with one of the `prefixes` (ignoring casing), so it's
+/// filtered out of client chunks and `undefined` in the browser.
+#[turbo_tasks::function]
+pub async fn check_client_env_usage(
+    env: ProcessEnvVc,
+    prefixes: Vec<String>,
+    entries: AssetsVc,
+) -> Result<CompletionVc> {
+    for entry in entries.await?.iter() {
+        for asset in all_assets(*entry).await?.iter() {
+            if let Some(module) = EcmascriptModuleAssetVc::resolve_from(*asset).await? {
+                check_module_env_usage(module, env, prefixes.clone()).await?;
+            }
+        }
+    }
+    Ok(CompletionVc::new())
+}
+
+/// [check_client_env_usage] of a single module, so only changed modules are
+/// checked again.
+#[turbo_tasks::function]
+async fn check_module_env_usage(
+    module: EcmascriptModuleAssetVc,
+    env: ProcessEnvVc,
+    prefixes: Vec<String>,
+) -> Result<CompletionVc> {
+    let accesses = module.process_env_accesses().await?;
+    if accesses.is_empty() {
+        return Ok(CompletionVc::new());
+    }
+    let source = module.await?.source;
+    for access in accesses.iter() {
+        let uppercase = access.key.to_uppercase();
+        if prefixes
+            .iter()
+            .any(|prefix| uppercase.starts_with(&prefix.to_uppercase()))
+        {
+            continue;
+        }
+        if env.read(&access.key).await?.is_none() {
+            continue;
+        }
+        UnembeddedEnvIssue {
+            path: source.ident().path(),
+            name: access.key.clone(),
+            prefixes: prefixes.clone(),
+            source: IssueSourceVc::from_byte_offset(source, access.start, access.end),
+        }
+        .cell()
+        .as_issue()
+        .emit();
+    }
+    Ok(CompletionVc::new())
+}
+
+/// A client module accesses an env variable which is filtered out of client
+/// chunks.
+#[turbo_tasks::value(shared)]
+pub struct UnembeddedEnvIssue {
+    pub path: FileSystemPathVc,
+    pub name: String,
+    pub prefixes: Vec<String>,
+    pub source: IssueSourceVc,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for UnembeddedEnvIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("env".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell(format!(
+            "Env variable {} is not available in client code",
+            self.name
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> StringVc {
+        StringVc::cell(format!(
+            "{name} is set, but only env variables starting with {prefixes} are embedded into \
+             client chunks, so `process.env.{name}` is `undefined` in the browser. Rename the \
+             variable if it's safe to expose it, or only access it in server code.",
+            name = self.name,
+            prefixes = self.prefixes.join(", ")
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> OptionIssueSourceVc {
+        OptionIssueSourceVc::cell(Some(self.source))
+    }
+}
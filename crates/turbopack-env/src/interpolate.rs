@@ -0,0 +1,19 @@
+use anyhow::Result;
+use turbo_tasks_env::{interpolate_env, ProcessEnvVc};
+use turbopack_core::compile_time_info::{CompileTimeDefineValue, CompileTimeDefinesVc};
+
+/// Replaces the `${NAME}` references in the string values of `defines` with
+/// the env variables of `env`, see [interpolate_env].
+#[turbo_tasks::function]
+pub async fn interpolate_defines(
+    env: ProcessEnvVc,
+    defines: CompileTimeDefinesVc,
+) -> Result<CompileTimeDefinesVc> {
+    let mut interpolated = defines.await?.clone_value();
+    for value in interpolated.values_mut() {
+        if let CompileTimeDefineValue::String(string) = value {
+            *string = interpolate_env(env, string).await?.clone_value();
+        }
+    }
+    Ok(CompileTimeDefinesVc::cell(interpolated))
+}
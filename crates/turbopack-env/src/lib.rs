@@ -7,20 +7,36 @@
 //! Dotenv file loading is a chain. Dotenv files that come first in the chain
 //! have higher priority to define a environment variable (later dotenv files
 //! cannot override it). Later dotenv files can reference variables prior
-//! defined variables.
+//! defined variables. [dotenv_cascade] builds the usual chain of `.env`,
+//! `.env.local`, `.env.[mode]` and `.env.[mode].local` files.
 
 #![feature(async_closure)]
 #![feature(min_specialization)]
 
 mod asset;
+mod cascade;
+mod client_usage;
 mod embeddable;
+mod interpolate;
 mod issue;
+mod schema;
 mod try_env;
+mod types;
 
-pub use asset::{ProcessEnvAsset, ProcessEnvAssetVc};
+pub use asset::{
+    ProcessEnvAsset, ProcessEnvAssetVc, ProcessEnvKeyAsset, ProcessEnvKeyAssetVc, ProcessEnvMode,
+};
+pub use cascade::{dotenv_cascade, dotenv_cascade_files};
+pub use client_usage::{check_client_env_usage, UnembeddedEnvIssue, UnembeddedEnvIssueVc};
 pub use embeddable::EmbeddableProcessEnvVc;
+pub use interpolate::interpolate_defines;
 pub use issue::{ProcessEnvIssue, ProcessEnvIssueVc};
+pub use schema::{
+    EnvSchema, EnvSchemaIssue, EnvSchemaIssueVc, EnvSchemaVc, EnvVarSchema, EnvVarType,
+    ValidatedProcessEnvVc,
+};
 pub use try_env::TryDotenvProcessEnvVc;
+pub use types::{ProcessEnvTypesAsset, ProcessEnvTypesAssetVc};
 
 pub fn register() {
     turbo_tasks::register();
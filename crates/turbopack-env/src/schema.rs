@@ -0,0 +1,331 @@
+use std::fmt::{Display, Formatter};
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{
+    primitives::{OptionStringVc, StringVc},
+    trace::TraceRawVcs,
+};
+use turbo_tasks_env::{EnvMapVc, ProcessEnv, ProcessEnvVc};
+use turbo_tasks_fs::FileSystemPathVc;
+use turbopack_core::issue::{Issue, IssueSeverity, IssueSeverityVc, IssueVc};
+use turbopack_ecmascript::utils::StringifyJs;
+
+/// The type an env variable is coerced to before it's embedded.
+#[derive(Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub enum EnvVarType {
+    String,
+    /// A finite number, embedded as a number literal.
+    Number,
+    /// `true`/`1`/`yes`/`on` or `false`/`0`/`no`/`off`, ignoring casing,
+    /// embedded as a boolean literal.
+    Boolean,
+    /// An absolute URL, embedded as a string.
+    Url,
+    /// One of the given values, embedded as a string.
+    Enum(Vec<String>),
+}
+
+impl EnvVarType {
+    /// Coerces `value` to this type and encodes it as a JS literal, or returns
+    /// `None` when it's malformed.
+    fn coerce(&self, value: &str) -> Option<String> {
+        match self {
+            EnvVarType::String => Some(StringifyJs(value).to_string()),
+            EnvVarType::Number => value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|number| number.is_finite())
+                .map(|number| number.to_string()),
+            EnvVarType::Boolean => match value.trim().to_lowercase().as_str() {
+                "true" | "1" | "yes" | "on" => Some("true".to_string()),
+                "false" | "0" | "no" | "off" => Some("false".to_string()),
+                _ => None,
+            },
+            EnvVarType::Url => url::Url::parse(value)
+                .ok()
+                .map(|_| StringifyJs(value).to_string()),
+            EnvVarType::Enum(values) => values
+                .iter()
+                .any(|v| v == value)
+                .then(|| StringifyJs(value).to_string()),
+        }
+    }
+}
+
+impl Display for EnvVarType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnvVarType::String => f.write_str("string"),
+            EnvVarType::Number => f.write_str("number"),
+            EnvVarType::Boolean => f.write_str("boolean"),
+            EnvVarType::Url => f.write_str("URL"),
+            EnvVarType::Enum(values) => write!(f, "one of {}", values.join(", ")),
+        }
+    }
+}
+
+/// The declaration of an env variable in an [EnvSchema].
+#[derive(Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub struct EnvVarSchema {
+    pub name: String,
+    pub ty: EnvVarType,
+    /// Whether an [EnvSchemaIssue] is emitted when the variable isn't set and
+    /// has no `default`.
+    pub required: bool,
+    /// The value used when the variable isn't set. It's coerced like a set
+    /// value.
+    pub default: Option<String>,
+}
+
+/// The env variables an app expects, with their types and defaults.
+#[turbo_tasks::value(shared)]
+pub struct EnvSchema {
+    pub variables: Vec<EnvVarSchema>,
+}
+
+#[turbo_tasks::value_impl]
+impl EnvSchemaVc {
+    /// The defaults of the declared variables, encoded as JS literals of their
+    /// type, e.g. as the
+    /// [runtime defaults](crate::ProcessEnvAssetVc::with_runtime_defaults) of
+    /// an env which is read at runtime. Malformed defaults are left out.
+    #[turbo_tasks::function]
+    pub async fn defaults(self) -> Result<EnvMapVc> {
+        Ok(EnvMapVc::cell(coerced_defaults(&self.await?.variables)))
+    }
+}
+
+fn coerced_defaults(variables: &[EnvVarSchema]) -> IndexMap<String, String> {
+    variables
+        .iter()
+        .filter_map(|var| {
+            let default = var.ty.coerce(var.default.as_deref()?)?;
+            Some((var.name.clone(), default))
+        })
+        .collect()
+}
+
+/// Validates the variables declared by a [EnvSchema] and encodes them as JS
+/// literals of their type, e.g. `PORT=3000` as the number `3000`. Other
+/// variables are encoded as strings.
+///
+/// It's used instead of an
+/// [EmbeddableProcessEnv](crate::EmbeddableProcessEnvVc), as its values are
+/// ready to be embedded. Missing or malformed variables are reported with an
+/// [EnvSchemaIssue] and left out.
+#[turbo_tasks::value]
+pub struct ValidatedProcessEnv {
+    prior: ProcessEnvVc,
+    schema: EnvSchemaVc,
+    /// The file the schema is declared in, reported as the context of issues.
+    context: FileSystemPathVc,
+}
+
+#[turbo_tasks::value_impl]
+impl ValidatedProcessEnvVc {
+    #[turbo_tasks::function]
+    pub fn new(prior: ProcessEnvVc, schema: EnvSchemaVc, context: FileSystemPathVc) -> Self {
+        ValidatedProcessEnv {
+            prior,
+            schema,
+            context,
+        }
+        .cell()
+    }
+
+    /// The embeddable value of the variable `name` declared by the schema.
+    /// It's a separate function, so issues are only emitted once for
+    /// `read_all` and `read`.
+    #[turbo_tasks::function]
+    async fn validated(self, name: &str) -> Result<OptionStringVc> {
+        let this = self.await?;
+        let schema = this.schema.await?;
+        let Some(var) = schema
+            .variables
+            .iter()
+            .find(|var| var.name.eq_ignore_ascii_case(name))
+        else {
+            return Ok(OptionStringVc::cell(None));
+        };
+        let value = this.prior.read(&var.name).await?;
+        let value = match (&*value, &var.default) {
+            (Some(value), _) | (None, Some(value)) => value,
+            (None, None) => {
+                if var.required {
+                    EnvSchemaIssue {
+                        context: this.context,
+                        name: var.name.clone(),
+                        expected: var.ty.to_string(),
+                        missing: true,
+                    }
+                    .cell()
+                    .as_issue()
+                    .emit();
+                }
+                return Ok(OptionStringVc::cell(None));
+            }
+        };
+        let coerced = var.ty.coerce(value);
+        if coerced.is_none() {
+            EnvSchemaIssue {
+                context: this.context,
+                name: var.name.clone(),
+                expected: var.ty.to_string(),
+                missing: false,
+            }
+            .cell()
+            .as_issue()
+            .emit();
+        }
+        Ok(OptionStringVc::cell(coerced))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ProcessEnv for ValidatedProcessEnv {
+    #[turbo_tasks::function]
+    async fn read_all(self_vc: ValidatedProcessEnvVc) -> Result<EnvMapVc> {
+        let this = self_vc.await?;
+        let prior = this.prior.read_all().await?;
+        let schema = this.schema.await?;
+
+        let mut encoded = prior
+            .iter()
+            .filter(|(name, _)| {
+                !schema
+                    .variables
+                    .iter()
+                    .any(|var| var.name.eq_ignore_ascii_case(name))
+            })
+            .map(|(name, value)| (name.clone(), StringifyJs(value).to_string()))
+            .collect::<IndexMap<_, _>>();
+        for var in &schema.variables {
+            if let Some(value) = &*self_vc.validated(&var.name).await? {
+                encoded.insert(var.name.clone(), value.clone());
+            }
+        }
+        Ok(EnvMapVc::cell(encoded))
+    }
+
+    #[turbo_tasks::function]
+    async fn read(self_vc: ValidatedProcessEnvVc, name: &str) -> Result<OptionStringVc> {
+        let this = self_vc.await?;
+        let schema = this.schema.await?;
+        if schema
+            .variables
+            .iter()
+            .any(|var| var.name.eq_ignore_ascii_case(name))
+        {
+            return Ok(self_vc.validated(name));
+        }
+        let prior = this.prior.read(name).await?;
+        let encoded = prior.as_deref().map(|s| StringifyJs(s).to_string());
+        Ok(OptionStringVc::cell(encoded))
+    }
+}
+
+/// A variable declared by an [EnvSchema] is missing or can't be coerced to
+/// its type.
+#[turbo_tasks::value(shared)]
+pub struct EnvSchemaIssue {
+    pub context: FileSystemPathVc,
+    pub name: String,
+    pub expected: String,
+    pub missing: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for EnvSchemaIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("env".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell(if self.missing {
+            format!("Missing required env variable {}", self.name)
+        } else {
+            format!(
+                "Env variable {} is not a valid {}",
+                self.name, self.expected
+            )
+        })
+    }
+
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.context
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> StringVc {
+        // The value isn't included, since env variables often hold secrets.
+        StringVc::cell(if self.missing {
+            format!(
+                "{} needs to be set to a {}, e.g. in a .env file, or get a default in the env \
+                 schema.",
+                self.name, self.expected
+            )
+        } else {
+            format!(
+                "The value of {} needs to be a {}. It's left out of the env until it's fixed.",
+                self.name, self.expected
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{coerced_defaults, EnvVarSchema, EnvVarType};
+
+    #[test]
+    fn coerces_values() {
+        assert_eq!(EnvVarType::Number.coerce(" 3000 ").as_deref(), Some("3000"));
+        assert_eq!(EnvVarType::Number.coerce("1.5").as_deref(), Some("1.5"));
+        assert_eq!(EnvVarType::Number.coerce("NaN"), None);
+        assert_eq!(EnvVarType::Boolean.coerce("Yes").as_deref(), Some("true"));
+        assert_eq!(EnvVarType::Boolean.coerce("0").as_deref(), Some("false"));
+        assert_eq!(EnvVarType::Boolean.coerce("maybe"), None);
+        assert_eq!(
+            EnvVarType::Url.coerce("https://example.com").as_deref(),
+            Some("\"https://example.com\"")
+        );
+        assert_eq!(EnvVarType::Url.coerce("example.com"), None);
+        let level = EnvVarType::Enum(vec!["debug".to_string(), "info".to_string()]);
+        assert_eq!(level.coerce("info").as_deref(), Some("\"info\""));
+        assert_eq!(level.coerce("trace"), None);
+    }
+
+    #[test]
+    fn coerces_defaults() {
+        let var = |name: &str, ty: EnvVarType, default: Option<&str>| EnvVarSchema {
+            name: name.to_string(),
+            ty,
+            required: false,
+            default: default.map(str::to_string),
+        };
+        let defaults = coerced_defaults(&[
+            var("PORT", EnvVarType::Number, Some("3000")),
+            var("HOST", EnvVarType::String, Some("localhost")),
+            var("DEBUG", EnvVarType::Boolean, Some("maybe")),
+            var("API_URL", EnvVarType::Url, None),
+        ]);
+        assert_eq!(
+            defaults.into_iter().collect::<Vec<_>>(),
+            [
+                ("PORT".to_string(), "3000".to_string()),
+                ("HOST".to_string(), "\"localhost\"".to_string()),
+            ]
+        );
+    }
+}
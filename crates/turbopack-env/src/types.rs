@@ -0,0 +1,152 @@
+use std::fmt::Write as _;
+
+use anyhow::Result;
+use turbo_tasks::primitives::StringsVc;
+use turbo_tasks_env::{ProcessEnv, ProcessEnvVc};
+use turbo_tasks_fs::{File, FileSystemPathVc};
+use turbopack_core::{
+    asset::{Asset, AssetContentVc},
+    ident::AssetIdentVc,
+};
+use turbopack_ecmascript::utils::StringifyJs;
+
+use crate::{EnvSchemaVc, EnvVarSchema, EnvVarType};
+
+/// The `env.d.ts` declarations of the env variables, so accesses of
+/// `process.env.NAME` are autocompleted and type-checked in user code.
+///
+/// Variables declared by an [EnvSchema](crate::EnvSchema) get the type they
+/// are coerced to, all others are `string`s. The declarations only depend on
+/// the names of the variables, so they are regenerated when a variable is
+/// added or removed, but not when a value changes.
+#[turbo_tasks::value]
+pub struct ProcessEnvTypesAsset {
+    path: FileSystemPathVc,
+    env: ProcessEnvVc,
+    schema: Option<EnvSchemaVc>,
+}
+
+#[turbo_tasks::value_impl]
+impl ProcessEnvTypesAssetVc {
+    #[turbo_tasks::function]
+    pub fn new(path: FileSystemPathVc, env: ProcessEnvVc, schema: Option<EnvSchemaVc>) -> Self {
+        ProcessEnvTypesAsset { path, env, schema }.cell()
+    }
+}
+
+/// The names of the variables of `env`.
+#[turbo_tasks::function]
+async fn env_names(env: ProcessEnvVc) -> Result<StringsVc> {
+    Ok(StringsVc::cell(
+        env.read_all().await?.keys().cloned().collect(),
+    ))
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for ProcessEnvTypesAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> AssetIdentVc {
+        AssetIdentVc::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<AssetContentVc> {
+        let names = env_names(self.env).await?;
+        let schema = match self.schema {
+            Some(schema) => schema.await?.variables.clone(),
+            None => Vec::new(),
+        };
+        Ok(File::from(env_declarations(&names, &schema)?).into())
+    }
+}
+
+/// Declares the `names` and the variables of the `schema` as properties of
+/// the `ProcessEnv` interface of `@types/node`.
+fn env_declarations(names: &[String], schema: &[EnvVarSchema]) -> Result<String> {
+    let mut code = "// This file is generated from the env. Don't edit it.\n\n".to_string();
+    code.push_str("declare namespace NodeJS {\n  interface ProcessEnv {\n");
+    for name in names {
+        if schema.iter().any(|var| var.name.eq_ignore_ascii_case(name)) {
+            continue;
+        }
+        writeln!(code, "    readonly {}: string;", property_name(name))?;
+    }
+    for var in schema {
+        // Variables which may be missing are left out of the env.
+        let optional = if var.required || var.default.is_some() {
+            ""
+        } else {
+            "?"
+        };
+        let ty = match &var.ty {
+            EnvVarType::String | EnvVarType::Url => "string".to_string(),
+            EnvVarType::Number => "number".to_string(),
+            EnvVarType::Boolean => "boolean".to_string(),
+            EnvVarType::Enum(values) => values
+                .iter()
+                .map(|value| StringifyJs(value).to_string())
+                .collect::<Vec<_>>()
+                .join(" | "),
+        };
+        writeln!(
+            code,
+            "    readonly {}{optional}: {ty};",
+            property_name(&var.name)
+        )?;
+    }
+    code.push_str("  }\n}\n");
+    Ok(code)
+}
+
+/// The name as a property name, quoted when it's not an identifier.
+fn property_name(name: &str) -> String {
+    let identifier = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if identifier {
+        name.to_string()
+    } else {
+        StringifyJs(name).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::env_declarations;
+    use crate::{EnvVarSchema, EnvVarType};
+
+    #[test]
+    fn declares_variables() {
+        let names = ["API_URL", "PORT", "my.flag"].map(String::from);
+        let schema = [
+            EnvVarSchema {
+                name: "PORT".to_string(),
+                ty: EnvVarType::Number,
+                required: true,
+                default: None,
+            },
+            EnvVarSchema {
+                name: "LOG_LEVEL".to_string(),
+                ty: EnvVarType::Enum(vec!["debug".to_string(), "info".to_string()]),
+                required: false,
+                default: None,
+            },
+        ];
+        assert_eq!(
+            env_declarations(&names, &schema).unwrap(),
+            r#"// This file is generated from the env. Don't edit it.
+
+declare namespace NodeJS {
+  interface ProcessEnv {
+    readonly API_URL: string;
+    readonly "my.flag": string;
+    readonly PORT: number;
+    readonly LOG_LEVEL?: "debug" | "info";
+  }
+}
+"#
+        );
+    }
+}
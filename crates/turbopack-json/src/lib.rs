@@ -7,11 +7,13 @@
 
 #![feature(min_specialization)]
 
+pub mod schema;
+
 use std::fmt::Write;
 
 use anyhow::{bail, Error, Result};
-use turbo_tasks::{primitives::StringVc, Value, ValueToString};
-use turbo_tasks_fs::{FileContent, FileJsonContent};
+use turbo_tasks::{primitives::StringVc, CompletionVc, Value, ValueToString};
+use turbo_tasks_fs::{FileContent, FileJsonContent, FileSystemPathVc};
 use turbopack_core::{
     asset::{Asset, AssetContentVc, AssetVc},
     chunk::{
@@ -27,6 +29,8 @@ use turbopack_ecmascript::chunk::{
     EcmascriptChunkingContextVc, EcmascriptExports, EcmascriptExportsVc,
 };
 
+use crate::schema::validate_json_schema;
+
 #[turbo_tasks::function]
 fn modifier() -> StringVc {
     StringVc::cell("json".to_string())
@@ -35,13 +39,36 @@ fn modifier() -> StringVc {
 #[turbo_tasks::value]
 pub struct JsonModuleAsset {
     source: AssetVc,
+    schema: Option<FileSystemPathVc>,
 }
 
 #[turbo_tasks::value_impl]
 impl JsonModuleAssetVc {
     #[turbo_tasks::function]
     pub fn new(source: AssetVc) -> Self {
-        Self::cell(JsonModuleAsset { source })
+        Self::cell(JsonModuleAsset {
+            source,
+            schema: None,
+        })
+    }
+
+    /// A JSON module which is validated against the JSON schema at `schema`.
+    #[turbo_tasks::function]
+    pub fn new_with_schema(source: AssetVc, schema: FileSystemPathVc) -> Self {
+        Self::cell(JsonModuleAsset {
+            source,
+            schema: Some(schema),
+        })
+    }
+
+    /// Reports the violations of the schema of this module, if any.
+    #[turbo_tasks::function]
+    async fn validate(self) -> Result<CompletionVc> {
+        let this = self.await?;
+        Ok(match this.schema {
+            Some(schema) => validate_json_schema(this.source, schema),
+            None => CompletionVc::new(),
+        })
     }
 }
 
@@ -129,6 +156,7 @@ impl EcmascriptChunkItem for JsonChunkItem {
         let data = content.parse_json().await?;
         match &*data {
             FileJsonContent::Content(data) => {
+                self.module.validate().await?;
                 let js_str_content = serde_json::to_string(&data.to_string())?;
                 let inner_code =
                     format!("__turbopack_export_value__(JSON.parse({js_str_content}));");
@@ -0,0 +1,281 @@
+//! Validation of JSON modules against a JSON schema, so invalid config files
+//! are reported at build time instead of crashing the code that reads them.
+//!
+//! A subset of JSON Schema is supported: `type`, `enum`, `const`, `required`,
+//! `properties`, `additionalProperties`, `items`, `anyOf`, `minLength`,
+//! `maxLength`, `minItems`, `maxItems`, `minimum` and `maximum`. Other
+//! keywords are ignored.
+
+use std::fmt::Write;
+
+use anyhow::{bail, Result};
+use serde_json::{Map, Value};
+use turbo_tasks::{primitives::StringVc, CompletionVc, ValueToString};
+use turbo_tasks_fs::{FileJsonContent, FileSystemPathVc};
+use turbopack_core::{
+    asset::{Asset, AssetVc},
+    issue::{Issue, IssueSeverity, IssueSeverityVc, IssueVc},
+};
+
+/// A value that doesn't match the schema.
+struct Violation {
+    /// The JSON path of the value, e.g. `$.routes[2].path`.
+    path: String,
+    message: String,
+}
+
+fn type_of(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn has_type(value: &Value, ty: &str) -> bool {
+    let actual = type_of(value);
+    actual == ty || (ty == "number" && actual == "integer")
+}
+
+fn object_path(path: &str, key: &str) -> String {
+    if !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        format!("{path}.{key}")
+    } else {
+        format!("{path}[{}]", Value::String(key.to_string()))
+    }
+}
+
+fn validate(value: &Value, schema: &Value, path: &str, violations: &mut Vec<Violation>) {
+    let Value::Object(schema) = schema else {
+        // `true` and `false` schemas accept and reject everything.
+        if schema == &Value::Bool(false) {
+            violations.push(Violation {
+                path: path.to_string(),
+                message: "no value is allowed here".to_string(),
+            });
+        }
+        return;
+    };
+    let mut violation = |message: String| {
+        violations.push(Violation {
+            path: path.to_string(),
+            message,
+        })
+    };
+
+    if let Some(ty) = schema.get("type") {
+        let types = match ty {
+            Value::String(ty) => vec![ty.as_str()],
+            Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|ty| has_type(value, ty)) {
+            violation(format!(
+                "expected {}, but found {}",
+                types.join(" or "),
+                type_of(value)
+            ));
+            // The other keywords would only report follow-up errors.
+            return;
+        }
+    }
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            let allowed = allowed
+                .iter()
+                .map(Value::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            violation(format!("expected one of {allowed}, but found {value}"));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            violation(format!("expected {expected}, but found {value}"));
+        }
+    }
+    if let Some(Value::Array(schemas)) = schema.get("anyOf") {
+        let matches_any = schemas.iter().any(|schema| {
+            let mut violations = Vec::new();
+            validate(value, schema, path, &mut violations);
+            violations.is_empty()
+        });
+        if !matches_any {
+            violation("doesn't match any of the allowed schemas".to_string());
+        }
+    }
+
+    match value {
+        Value::String(string) => {
+            let length = string.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                if length < min {
+                    violation(format!("expected at least {min} characters"));
+                }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                if length > max {
+                    violation(format!("expected at most {max} characters"));
+                }
+            }
+        }
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+                if number < min {
+                    violation(format!("expected at least {min}, but found {number}"));
+                }
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+                if number > max {
+                    violation(format!("expected at most {max}, but found {number}"));
+                }
+            }
+        }
+        Value::Array(items) => {
+            let length = items.len() as u64;
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                if length < min {
+                    violation(format!("expected at least {min} items, but found {length}"));
+                }
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                if length > max {
+                    violation(format!("expected at most {max} items, but found {length}"));
+                }
+            }
+            if let Some(items_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate(item, items_schema, &format!("{path}[{i}]"), violations);
+                }
+            }
+        }
+        Value::Object(object) => validate_object(object, schema, path, violations),
+        Value::Null | Value::Bool(_) => {}
+    }
+}
+
+fn validate_object(
+    object: &Map<String, Value>,
+    schema: &Map<String, Value>,
+    path: &str,
+    violations: &mut Vec<Violation>,
+) {
+    if let Some(Value::Array(required)) = schema.get("required") {
+        for key in required.iter().filter_map(Value::as_str) {
+            if !object.contains_key(key) {
+                violations.push(Violation {
+                    path: path.to_string(),
+                    message: format!("missing required property {key:?}"),
+                });
+            }
+        }
+    }
+    let properties = schema.get("properties").and_then(Value::as_object);
+    let additional_properties = schema.get("additionalProperties");
+    for (key, value) in object {
+        let key_path = object_path(path, key);
+        if let Some(property_schema) = properties.and_then(|properties| properties.get(key)) {
+            validate(value, property_schema, &key_path, violations);
+        } else if let Some(additional_properties) = additional_properties {
+            if additional_properties == &Value::Bool(false) {
+                violations.push(Violation {
+                    path: key_path,
+                    message: "unknown property".to_string(),
+                });
+            } else {
+                validate(value, additional_properties, &key_path, violations);
+            }
+        }
+    }
+}
+
+/// Validates the JSON `source` against the JSON schema at `schema` and emits
+/// a [JsonSchemaIssue] listing all violations.
+#[turbo_tasks::function]
+pub(crate) async fn validate_json_schema(
+    source: AssetVc,
+    schema: FileSystemPathVc,
+) -> Result<CompletionVc> {
+    let content = source.content().parse_json().await?;
+    let FileJsonContent::Content(value) = &*content else {
+        // Invalid JSON is reported when generating the module.
+        return Ok(CompletionVc::new());
+    };
+    let schema_content = schema.read_json().await?;
+    let schema_value = match &*schema_content {
+        FileJsonContent::Content(schema_value) => schema_value,
+        FileJsonContent::Unparseable(err) => {
+            bail!(
+                "Unable to parse the JSON schema {}: {err}",
+                schema.to_string().await?
+            )
+        }
+        FileJsonContent::NotFound => {
+            bail!("JSON schema not found: {}", schema.to_string().await?)
+        }
+    };
+    let mut violations = Vec::new();
+    validate(value, schema_value, "$", &mut violations);
+    if !violations.is_empty() {
+        JsonSchemaIssue {
+            path: source.ident().path(),
+            schema,
+            violations: violations
+                .into_iter()
+                .map(|violation| (violation.path, violation.message))
+                .collect(),
+        }
+        .cell()
+        .as_issue()
+        .emit();
+    }
+    Ok(CompletionVc::new())
+}
+
+#[turbo_tasks::value(shared)]
+pub struct JsonSchemaIssue {
+    pub path: FileSystemPathVc,
+    pub schema: FileSystemPathVc,
+    /// The JSON paths of the invalid values and what's wrong with them.
+    pub violations: Vec<(String, String)>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for JsonSchemaIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("config".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell("Invalid config file".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    async fn description(&self) -> Result<StringVc> {
+        let mut description = format!(
+            "The file doesn't match the schema {}:",
+            self.schema.to_string().await?
+        );
+        for (path, message) in &self.violations {
+            write!(description, "\n  {path}: {message}")?;
+        }
+        Ok(StringVc::cell(description))
+    }
+}
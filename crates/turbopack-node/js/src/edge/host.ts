@@ -0,0 +1,113 @@
+import { readFileSync } from "node:fs";
+import { dirname, relative, resolve } from "node:path";
+import { webcrypto } from "node:crypto";
+import { compileFunction, createContext, runInContext } from "node:vm";
+
+import { IPC } from "../ipc/index";
+
+// The chunks to evaluate in the sandbox, set by the bootstrap of the renderer.
+declare const TURBOPACK_EDGE_CHUNKS: string[];
+
+// The globals of the edge runtime, following the minimum common API of
+// WinterCG. Node.js builtins, `require`, `Buffer` and the rest of `process` are
+// intentionally missing, so code depending on them fails like it would when
+// deployed.
+const sandbox: Record<string, unknown> = {
+  fetch,
+  Request,
+  Response,
+  Headers,
+  FormData,
+  Blob,
+  URL,
+  URLSearchParams,
+  TextEncoder,
+  TextDecoder,
+  ReadableStream,
+  WritableStream,
+  TransformStream,
+  AbortController,
+  AbortSignal,
+  Event,
+  EventTarget,
+  atob,
+  btoa,
+  crypto: webcrypto,
+  structuredClone,
+  queueMicrotask,
+  // The timers and the console of the host are passed as they are, so
+  // intervals are paused while the process is idle and logs are attributed
+  // to the operation.
+  setTimeout,
+  clearTimeout,
+  setInterval,
+  clearInterval,
+  console,
+  process: { env: { ...process.env } },
+  // The renderer can't connect to the Node.js process itself, so it uses the
+  // connection of the host, see `ipc/edge.ts`.
+  __turbopack_edge_ipc__: IPC,
+  // Hot updates are received by the host, so the runtime of the sandbox
+  // registers its listeners with the host.
+  TURBOPACK_CHUNK_UPDATE_LISTENERS: (globalThis as any)
+    .TURBOPACK_CHUNK_UPDATE_LISTENERS,
+};
+const context = createContext(sandbox);
+runInContext(
+  "globalThis.self = globalThis; Error.stackTraceLimit = 100;",
+  context
+);
+
+type Module = { exports: unknown };
+const cache: Record<string, Module> = {};
+
+// Only the functions the chunk loading of the runtime uses.
+const sandboxPath = { dirname, relative };
+
+function createRequire(filename: string) {
+  const resolveChunk = (request: string) => {
+    if (!request.startsWith("./") && !request.startsWith("../")) {
+      throw new Error(
+        `Node.js module "${request}" is not supported in the edge runtime`
+      );
+    }
+    return resolve(dirname(filename), request);
+  };
+
+  const require = (request: string) => {
+    if (request === "path") {
+      return sandboxPath;
+    }
+    return load(resolveChunk(request));
+  };
+  require.resolve = resolveChunk;
+  require.cache = cache;
+  return require;
+}
+
+function load(filename: string): unknown {
+  const cached = cache[filename];
+  if (cached != null) {
+    return cached.exports;
+  }
+  const module: Module = { exports: {} };
+  cache[filename] = module;
+  // Chunks are evaluated as CommonJS modules would be, but in the sandbox.
+  const fn = compileFunction(
+    readFileSync(filename, "utf8"),
+    ["require", "module", "exports", "__filename", "__dirname"],
+    { filename, parsingContext: context }
+  );
+  fn(
+    createRequire(filename),
+    module,
+    module.exports,
+    filename,
+    dirname(filename)
+  );
+  return module.exports;
+}
+
+for (const chunk of TURBOPACK_EDGE_CHUNKS) {
+  load(chunk);
+}
@@ -0,0 +1,9 @@
+import type { Ipc } from "./index";
+
+/**
+ * The IPC for renderers running in the edge runtime. It's only available in
+ * the sandbox of `edge/host.ts`, which shares its connection to the Node.js
+ * process, so renderers need to import it instead of `./index`.
+ */
+export const IPC: Ipc<unknown, unknown> = (globalThis as any)
+  .__turbopack_edge_ipc__;
@@ -0,0 +1,31 @@
+/**
+ * Runs `render` with `process.env` overridden by the `envOverrides` of a
+ * render request. The previous values are restored once it's done, since the
+ * process is reused for other requests. Streamed responses need to be sent
+ * within `render`.
+ *
+ * It has no dependencies, so renderers in the edge runtime can use it to
+ * override the `process.env` of their sandbox, too.
+ */
+export async function withEnvOverrides<T>(
+  envOverrides: Record<string, string> | undefined,
+  render: () => Promise<T>
+): Promise<T> {
+  const env = process.env;
+  const previous: Record<string, string | undefined> = {};
+  for (const [name, value] of Object.entries(envOverrides ?? {})) {
+    previous[name] = env[name];
+    env[name] = value;
+  }
+  try {
+    return await render();
+  } finally {
+    for (const [name, value] of Object.entries(previous)) {
+      if (value === undefined) {
+        delete env[name];
+      } else {
+        env[name] = value;
+      }
+    }
+  }
+}
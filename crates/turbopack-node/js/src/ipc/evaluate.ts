@@ -1,4 +1,4 @@
-import { IPC, StructuredError } from "./index";
+import { IPC, StructuredError, quiesce, wake } from "./index";
 import type { Ipc as GenericIpc } from "./index";
 
 type IpcIncomingMessage = {
@@ -41,7 +41,10 @@ export const run = async (
   getValue: (ipc: Ipc, ...deserializedArgs: any[]) => any
 ) => {
   while (true) {
+    // The process stays idle in the pool until the next operation.
+    quiesce();
     const msg = await ipc.recv();
+    wake();
 
     switch (msg.type) {
       case "evaluate": {
@@ -0,0 +1,92 @@
+type CachedResponse = {
+  status: number;
+  statusText: string;
+  headers: [string, string][];
+  body: ArrayBuffer;
+};
+
+// Responses with these statuses can't have a body.
+const NULL_BODY_STATUSES = [101, 204, 205, 304];
+
+let cacheGeneration: number | undefined;
+const cache = new Map<string, Promise<CachedResponse>>();
+
+/**
+ * Runs `render` with `fetch` responses cached across renders, so reloading a
+ * page that fetches slow upstream APIs doesn't hit them on every render
+ * during development.
+ *
+ * Only successful `GET` requests are cached, keyed by their URL and headers.
+ * Requests with `cache: "no-store"` or `cache: "reload"` are passed through.
+ * The cache is dropped when `generation` changes, which is the
+ * `fetchCacheGeneration` of the render data. `POST` to the
+ * `/turbopack-fetch-cache` endpoint of the dev server to start a new one.
+ */
+export async function withFetchCache<T>(
+  generation: number,
+  render: () => Promise<T>
+): Promise<T> {
+  if (generation !== cacheGeneration) {
+    cache.clear();
+    cacheGeneration = generation;
+  }
+  const originalFetch = globalThis.fetch;
+  globalThis.fetch = (input, init) => {
+    const request = new Request(input, init);
+    if (
+      request.method !== "GET" ||
+      request.cache === "no-store" ||
+      request.cache === "reload"
+    ) {
+      return originalFetch(request);
+    }
+    const key = cacheKey(request);
+    let cached = cache.get(key);
+    if (cached === undefined) {
+      cached = fetchToCache(originalFetch, request);
+      cache.set(key, cached);
+      // Failed requests are retried by later renders.
+      cached.then(
+        (response) => {
+          if (response.status < 200 || response.status >= 300) {
+            cache.delete(key);
+          }
+        },
+        () => cache.delete(key)
+      );
+    }
+    return cached.then(
+      ({ status, statusText, headers, body }) =>
+        new Response(NULL_BODY_STATUSES.includes(status) ? null : body.slice(0), {
+          status,
+          statusText,
+          headers,
+        })
+    );
+  };
+  try {
+    return await render();
+  } finally {
+    globalThis.fetch = originalFetch;
+  }
+}
+
+function cacheKey(request: Request): string {
+  const headers = [...request.headers].sort(([a], [b]) =>
+    a < b ? -1 : a > b ? 1 : 0
+  );
+  return JSON.stringify([request.url, headers]);
+}
+
+async function fetchToCache(
+  fetch: typeof globalThis.fetch,
+  request: Request
+): Promise<CachedResponse> {
+  const response = await fetch(request);
+  return {
+    status: response.status,
+    statusText: response.statusText,
+    headers: [...response.headers],
+    body: await response.arrayBuffer(),
+  };
+}
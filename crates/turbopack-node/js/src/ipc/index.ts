@@ -34,22 +34,55 @@ export type Ipc<TIncoming, TOutgoing> = {
   sendError(error: Error): Promise<never>;
 };
 
+type HotUpdateMessage = {
+  type: "hotUpdate";
+  chunkListPath: string;
+  instruction: unknown;
+};
+
+type ChunkUpdateListener = [
+  chunkListPath: string,
+  handleApply: (update: { type: "partial"; instruction: unknown }) => void
+];
+
 function createIpc<TIncoming, TOutgoing>(
   port: number
 ): Ipc<TIncoming, TOutgoing> {
   const socket = createConnection(port, "127.0.0.1");
-  const packetQueue: Buffer[] = [];
+  const messageQueue: TIncoming[] = [];
   const recvPromiseResolveQueue: Array<(message: TIncoming) => void> = [];
 
   function pushPacket(packet: Buffer) {
+    const message = JSON.parse(packet.toString("utf8"));
+
+    // Hot updates are sent between operations and are handled here, so
+    // entries never receive them.
+    if (message?.type === "hotUpdate") {
+      applyHotUpdate(message as HotUpdateMessage);
+      return;
+    }
+
     const recvPromiseResolve = recvPromiseResolveQueue.shift();
     if (recvPromiseResolve != null) {
-      recvPromiseResolve(JSON.parse(packet.toString("utf8")) as TIncoming);
+      recvPromiseResolve(message as TIncoming);
     } else {
-      packetQueue.push(packet);
+      messageQueue.push(message as TIncoming);
     }
   }
 
+  // The pool asks the process to exit with SIGTERM, and forwards Ctrl-C as
+  // SIGINT. Closing the connection and stopping the intervals lets the event
+  // loop drain, so `beforeExit` hooks run before the process exits. The pool
+  // kills processes which don't exit within its grace period. A second signal
+  // doesn't exit right away, since Ctrl-C may also reach the process directly.
+  const shutdown = () => {
+    quiesce();
+    intervals.clear();
+    socket.end();
+  };
+  process.on("SIGTERM", shutdown);
+  process.on("SIGINT", shutdown);
+
   let state: State = { type: "waiting" };
   let buffer: Buffer = Buffer.alloc(0);
   socket.once("connect", () => {
@@ -103,11 +136,39 @@ function createIpc<TIncoming, TOutgoing>(
     });
   }
 
+  /**
+   * Applies an update of a chunk list to the chunks loaded in this process,
+   * using the update listeners registered by the runtime.
+   */
+  async function applyHotUpdate({
+    chunkListPath,
+    instruction,
+  }: HotUpdateMessage) {
+    try {
+      const listeners: ChunkUpdateListener[] =
+        (globalThis as any).TURBOPACK_CHUNK_UPDATE_LISTENERS ?? [];
+      let applied = false;
+      for (const [path, handleApply] of listeners) {
+        if (path === chunkListPath) {
+          handleApply({ type: "partial", instruction });
+          applied = true;
+        }
+      }
+      if (!applied) {
+        throw new Error(`chunk list ${chunkListPath} is not loaded`);
+      }
+    } catch (e) {
+      await send({ type: "hotUpdateFailed", ...structuredError(e as Error) });
+      return;
+    }
+    await send({ type: "hotUpdateApplied" });
+  }
+
   return {
     async recv() {
-      const packet = packetQueue.shift();
-      if (packet != null) {
-        return JSON.parse(packet.toString("utf8")) as TIncoming;
+      const message = messageQueue.shift();
+      if (message != null) {
+        return message;
       }
 
       const result = await new Promise<TIncoming>((resolve) => {
@@ -137,6 +198,112 @@ function createIpc<TIncoming, TOutgoing>(
   };
 }
 
+type PausableInterval = {
+  callback: (...args: any[]) => void;
+  delay: number | undefined;
+  args: any[];
+  // The timer currently scheduling the callback, or `null` while paused.
+  timer: NodeJS.Timeout | null;
+};
+
+// Intervals keep running while the process is idle in the pool, e.g. the cache
+// purging of file systems used by loaders. They are paused while idle, so an
+// idle process doesn't wake up at all.
+const intervals = new Map<object, PausableInterval>();
+let quiesced = false;
+
+const originalSetInterval = globalThis.setInterval;
+const originalClearInterval = globalThis.clearInterval;
+const originalClearTimeout = globalThis.clearTimeout;
+
+// @ts-ignore the handle is an object with the same methods as a `Timeout`
+globalThis.setInterval = (
+  callback: (...args: any[]) => void,
+  delay?: number,
+  ...args: any[]
+) => {
+  const interval: PausableInterval = {
+    callback,
+    delay,
+    args,
+    timer: quiesced ? null : originalSetInterval(callback, delay, ...args),
+  };
+  const handle = {
+    ref() {
+      interval.timer?.ref();
+      return handle;
+    },
+    unref() {
+      interval.timer?.unref();
+      return handle;
+    },
+    hasRef() {
+      return interval.timer?.hasRef() ?? false;
+    },
+    refresh() {
+      interval.timer?.refresh();
+      return handle;
+    },
+  };
+  intervals.set(handle, interval);
+  return handle;
+};
+
+globalThis.clearInterval = (handle: any) => {
+  const interval = intervals.get(handle);
+  if (interval == null) {
+    originalClearInterval(handle);
+    return;
+  }
+  intervals.delete(handle);
+  if (interval.timer != null) {
+    originalClearInterval(interval.timer);
+  }
+};
+
+// Intervals can be cleared with `clearTimeout` as well.
+globalThis.clearTimeout = (handle: any) => {
+  if (intervals.has(handle)) {
+    globalThis.clearInterval(handle);
+  } else {
+    originalClearTimeout(handle);
+  }
+};
+
+/**
+ * Pauses intervals until [wake] is called. Called when the process becomes
+ * idle, i.e. waits for the next operation.
+ */
+export function quiesce() {
+  if (quiesced) {
+    return;
+  }
+  quiesced = true;
+  for (const interval of intervals.values()) {
+    if (interval.timer != null) {
+      originalClearInterval(interval.timer);
+      interval.timer = null;
+    }
+  }
+}
+
+/**
+ * Resumes the intervals paused by [quiesce].
+ */
+export function wake() {
+  if (!quiesced) {
+    return;
+  }
+  quiesced = false;
+  for (const interval of intervals.values()) {
+    interval.timer = originalSetInterval(
+      interval.callback,
+      interval.delay,
+      ...interval.args
+    );
+  }
+}
+
 const PORT = process.argv[2];
 
 export const IPC = createIpc<unknown, unknown>(parseInt(PORT, 10));
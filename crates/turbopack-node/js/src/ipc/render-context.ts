@@ -0,0 +1,31 @@
+import { AsyncLocalStorage } from "node:async_hooks";
+
+// The storage lives on `globalThis`, so the render loop and the chunks of the
+// app share it even when each of them bundles its own copy of this module.
+const storage: AsyncLocalStorage<unknown> = ((
+  globalThis as any
+).__turbopack_render_context__ ??= new AsyncLocalStorage<unknown>());
+
+/**
+ * Runs `render` with `renderData` as the context of the request, so user code
+ * can access it with `getRenderData` from anywhere within the render,
+ * including callbacks and promises created during it, without it being passed
+ * down explicitly.
+ *
+ * Renders of concurrent requests in the same process each see their own
+ * render data. Streamed responses need to be sent within `render`.
+ */
+export function withRenderData<D, T>(
+  renderData: D,
+  render: () => Promise<T>
+): Promise<T> {
+  return storage.run(renderData, render);
+}
+
+/**
+ * The render data of the request being rendered, or `undefined` when called
+ * outside of `withRenderData`.
+ */
+export function getRenderData<D>(): D | undefined {
+  return storage.getStore() as D | undefined;
+}
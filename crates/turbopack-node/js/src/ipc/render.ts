@@ -0,0 +1,104 @@
+import { IPC, quiesce, wake } from "./index";
+import type { Ipc as GenericIpc } from "./index";
+import { withEnvOverrides } from "./env";
+import { withRenderData } from "./render-context";
+
+/**
+ * The request to render, see `RenderData` in `src/render/mod.rs`.
+ */
+export type RenderData = {
+  params: Record<string, string | string[]>;
+  method: string;
+  url: string;
+  rawQuery: string;
+  rawHeaders: [string, string][];
+  query: Record<string, unknown>;
+  cookies: Record<string, string>;
+  path: string;
+  staticData?: unknown;
+  cspNonce?: string;
+  requestId?: string;
+  fetchCacheGeneration: number;
+};
+
+type IpcIncomingMessage =
+  | {
+      type: "headers";
+      data: RenderData;
+      envOverrides: Record<string, string>;
+    }
+  | {
+      type: "cancel";
+    };
+
+type IpcOutgoingMessage =
+  | {
+      type: "response";
+      statusCode: number;
+      headers: [string, string][];
+      body: string;
+    }
+  | {
+      type: "responseHeaders";
+      statusCode: number;
+      headers: [string, string][];
+    }
+  | {
+      type: "bodyChunk";
+      data: string;
+    }
+  | {
+      type: "bodyEnd";
+    }
+  | {
+      type: "rewrite";
+      path: string;
+    }
+  | {
+      // Shows the built-in not found page.
+      type: "notFound";
+      headers: [string, string][];
+    };
+
+export type Ipc = GenericIpc<IpcIncomingMessage, IpcOutgoingMessage>;
+const ipc = IPC as Ipc;
+
+/**
+ * The loop of a static renderer. Every request is rendered by `render`, which
+ * sends the response with `ipc`. The env overrides of the request apply and
+ * its render data is available with `getRenderData` while it runs.
+ */
+export const run = async (
+  render: (ipc: Ipc, data: RenderData) => Promise<void>
+) => {
+  while (true) {
+    // The process stays idle in the pool until the next request.
+    quiesce();
+    const msg = await ipc.recv();
+    wake();
+
+    switch (msg.type) {
+      case "headers": {
+        try {
+          await withEnvOverrides(msg.envOverrides, () =>
+            withRenderData(msg.data, () => render(ipc, msg.data))
+          );
+        } catch (e) {
+          await ipc.sendError(e as Error);
+        }
+        break;
+      }
+      case "cancel": {
+        // The render is done by the time this is received, and the process
+        // isn't reused after a cancellation anyway.
+        break;
+      }
+      default: {
+        console.error("unexpected message type", (msg as any).type);
+        process.exit(1);
+      }
+    }
+  }
+};
+
+export type { IpcIncomingMessage, IpcOutgoingMessage };
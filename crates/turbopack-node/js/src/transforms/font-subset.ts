@@ -0,0 +1,52 @@
+declare const __turbopack_external_require__: (id: string) => any;
+
+import type { Ipc } from "../ipc/evaluate";
+
+// Parses a CSS `unicode-range` value like `U+0000-00FF`, `U+4??` or `U+20AC`
+// into an inclusive range of code points.
+function parseUnicodeRange(range: string): [number, number] {
+  const match = /^U\+([0-9a-f?]{1,6})(?:-([0-9a-f]{1,6}))?$/i.exec(
+    range.trim()
+  );
+  if (match === null) {
+    throw new Error(`Invalid unicode range ${range}`);
+  }
+  const [, start, end] = match;
+  if (end !== undefined) {
+    return [parseInt(start, 16), parseInt(end, 16)];
+  }
+  return [
+    parseInt(start.replace(/\?/g, "0"), 16),
+    parseInt(start.replace(/\?/g, "f"), 16),
+  ];
+}
+
+function unicodeRangesToText(ranges: string[]): string {
+  let text = "";
+  for (const range of ranges) {
+    const [start, end] = parseUnicodeRange(range);
+    for (let codePoint = start; codePoint <= end; codePoint++) {
+      // Surrogates aren't valid code points on their own.
+      if (codePoint < 0xd800 || codePoint > 0xdfff) {
+        text += String.fromCodePoint(codePoint);
+      }
+    }
+  }
+  return text;
+}
+
+const transform = async (
+  _ipc: Ipc,
+  content: string,
+  unicodeRanges: string[]
+) => {
+  const subsetFont = __turbopack_external_require__("subset-font");
+  const subset: Buffer = await subsetFont(
+    Buffer.from(content, "base64"),
+    unicodeRangesToText(unicodeRanges),
+    { targetFormat: "woff2" }
+  );
+  return subset.toString("base64");
+};
+
+export { transform as default };
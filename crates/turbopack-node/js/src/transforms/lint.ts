@@ -0,0 +1,152 @@
+declare const __turbopack_external_require__: (id: string) => any;
+
+import type { Ipc } from "../ipc/evaluate";
+import { spawn } from "child_process";
+import { resolve } from "path";
+
+type Linter = "eslint" | "biome";
+
+type LintPosition = { line: number; column: number };
+
+type LintFix = {
+  title: string;
+  start: LintPosition;
+  end: LintPosition;
+  text: string;
+};
+
+type LintMessage = {
+  rule?: string;
+  severity: "error" | "warning";
+  message: string;
+  start: LintPosition;
+  end: LintPosition;
+  fixes: LintFix[];
+};
+
+const contextDir = process.cwd();
+
+// The pooled process is kept alive between files, so the linter instance (and
+// its loaded config and plugins) is reused.
+let eslint: any = undefined;
+
+async function lintWithEslint(
+  content: string,
+  name: string
+): Promise<LintMessage[]> {
+  if (eslint === undefined) {
+    const { ESLint } = __turbopack_external_require__("eslint");
+    eslint = new ESLint({ cwd: contextDir });
+  }
+  const filePath = resolve(contextDir, name);
+  if (await eslint.isPathIgnored(filePath)) {
+    return [];
+  }
+  const results = await eslint.lintText(content, { filePath });
+  const messages: LintMessage[] = [];
+  for (const result of results) {
+    for (const message of result.messages) {
+      // ESLint positions are 1-based, with `endLine`/`endColumn` being
+      // optional.
+      const start = { line: message.line - 1, column: message.column - 1 };
+      messages.push({
+        rule: message.ruleId ?? undefined,
+        severity: message.severity === 2 ? "error" : "warning",
+        message: message.message,
+        start,
+        end:
+          message.endLine !== undefined
+            ? { line: message.endLine - 1, column: message.endColumn - 1 }
+            : start,
+        fixes: eslintFixes(content, message),
+      });
+    }
+  }
+  return messages;
+}
+
+// ESLint fixes replace a range of offsets, while the fixes of suggestions
+// need to be chosen by the user.
+function eslintFixes(content: string, message: any): LintFix[] {
+  const fixes: LintFix[] = [];
+  const push = (
+    title: string,
+    fix: { range: [number, number]; text: string }
+  ) =>
+    fixes.push({
+      title,
+      start: lineAndColumn(content, fix.range[0]),
+      end: lineAndColumn(content, fix.range[1]),
+      text: fix.text,
+    });
+  if (message.fix) {
+    push(
+      message.ruleId ? `Fix ${message.ruleId}` : "Fix the problem",
+      message.fix
+    );
+  }
+  for (const suggestion of message.suggestions ?? []) {
+    push(suggestion.desc, suggestion.fix);
+  }
+  return fixes;
+}
+
+function lineAndColumn(content: string, offset: number) {
+  const before = content.slice(0, offset);
+  const line = before.split("\n").length - 1;
+  return { line, column: offset - (before.lastIndexOf("\n") + 1) };
+}
+
+function lintWithBiome(content: string, name: string): Promise<LintMessage[]> {
+  return new Promise((resolvePromise, reject) => {
+    const child = spawn(
+      "biome",
+      ["lint", "--reporter=json", `--stdin-file-path=${name}`],
+      { cwd: contextDir }
+    );
+    let stdout = "";
+    child.stdout.setEncoding("utf-8");
+    child.stdout.on("data", (data) => (stdout += data));
+    child.on("error", reject);
+    child.on("close", () => {
+      let report;
+      try {
+        report = JSON.parse(stdout);
+      } catch (e) {
+        return reject(new Error(`Unable to parse biome output: ${stdout}`));
+      }
+      const messages: LintMessage[] = [];
+      for (const diagnostic of report.diagnostics ?? []) {
+        const [startOffset, endOffset] = diagnostic.location?.span ?? [0, 0];
+        messages.push({
+          rule: diagnostic.category,
+          severity: diagnostic.severity === "error" ? "error" : "warning",
+          message: diagnostic.description,
+          start: lineAndColumn(content, startOffset),
+          end: lineAndColumn(content, endOffset),
+          fixes: [],
+        });
+      }
+      resolvePromise(messages);
+    });
+    child.stdin.end(content);
+  });
+}
+
+const lint = async (
+  _ipc: Ipc,
+  linter: Linter,
+  content: string,
+  name: string
+) => {
+  switch (linter) {
+    case "eslint":
+      return lintWithEslint(content, name);
+    case "biome":
+      return lintWithBiome(content, name);
+    default:
+      throw new Error(`Unknown linter ${linter}`);
+  }
+};
+
+export { lint as default };
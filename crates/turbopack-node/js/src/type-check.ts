@@ -0,0 +1,177 @@
+declare const __turbopack_external_require__: (id: string) => any;
+
+import type { Ipc } from "./ipc/evaluate";
+import { execFile } from "child_process";
+import { relative, isAbsolute, resolve, sep } from "path";
+
+type Checker = "tsc" | "tsgo";
+
+type Diagnostic = {
+  file?: string;
+  start?: { line: number; column: number };
+  end?: { line: number; column: number };
+  code: number;
+  category: "error" | "warning" | "suggestion" | "message";
+  message: string;
+};
+
+const contextDir = process.cwd();
+const toPath = (file: string) => {
+  const relPath = relative(contextDir, file);
+  if (isAbsolute(relPath)) {
+    return undefined;
+  }
+  return sep !== "/" ? relPath.replaceAll(sep, "/") : relPath;
+};
+
+// The pooled process is kept alive between type checks, so the previous
+// program is reused to only re-check files that have changed.
+let oldProgram: any = undefined;
+
+function checkWithTsc(ipc: Ipc, tsconfig: string): Diagnostic[] {
+  const ts = __turbopack_external_require__("typescript");
+  const configPath = resolve(contextDir, tsconfig);
+  const dependOn = (file: string) => {
+    const path = toPath(file);
+    // Files outside of the project (e.g. the TypeScript lib files) are not
+    // watched.
+    if (path !== undefined) {
+      ipc.send({ type: "fileDependency", path });
+    }
+  };
+  dependOn(configPath);
+
+  const config = ts.getParsedCommandLineOfConfigFile(
+    configPath,
+    { noEmit: true },
+    {
+      ...ts.sys,
+      onUnRecoverableConfigFileDiagnostic: () => {},
+    }
+  );
+  if (!config) {
+    throw new Error(`Unable to read TypeScript config ${tsconfig}`);
+  }
+  for (const file of config.fileNames) {
+    dependOn(file);
+  }
+  for (const directory of Object.keys(config.wildcardDirectories ?? {})) {
+    const path = toPath(directory);
+    if (path !== undefined) {
+      ipc.send({ type: "dirDependency", path, glob: "**" });
+    }
+  }
+
+  const host = ts.createIncrementalCompilerHost(config.options);
+  const program = ts.createEmitAndSemanticDiagnosticsBuilderProgram(
+    config.fileNames,
+    config.options,
+    host,
+    oldProgram,
+    config.errors,
+    config.projectReferences
+  );
+  oldProgram = program;
+  for (const sourceFile of program.getSourceFiles()) {
+    dependOn(sourceFile.fileName);
+  }
+
+  const diagnostics = [
+    ...config.errors,
+    ...program.getConfigFileParsingDiagnostics(),
+    ...program.getSyntacticDiagnostics(),
+    ...program.getGlobalDiagnostics(),
+    ...program.getSemanticDiagnostics(),
+  ];
+  const categories: Diagnostic["category"][] = [
+    "warning",
+    "error",
+    "suggestion",
+    "message",
+  ];
+  return diagnostics.map((diagnostic: any): Diagnostic => {
+    const result: Diagnostic = {
+      code: diagnostic.code,
+      category: categories[diagnostic.category] ?? "error",
+      message: ts.flattenDiagnosticMessageText(diagnostic.messageText, "\n"),
+    };
+    if (diagnostic.file) {
+      result.file = toPath(diagnostic.file.fileName);
+      if (diagnostic.start !== undefined) {
+        const toPos = (offset: number) => {
+          const { line, character } =
+            diagnostic.file.getLineAndCharacterOfPosition(offset);
+          return { line, column: character };
+        };
+        result.start = toPos(diagnostic.start);
+        result.end = toPos(diagnostic.start + (diagnostic.length ?? 0));
+      }
+    }
+    return result;
+  });
+}
+
+// Matches `src/index.ts(1,7): error TS2322: Type 'number' is not ...`.
+const TSGO_DIAGNOSTIC =
+  /^(.+)\((\d+),(\d+)\): (error|warning|message) TS(\d+): (.*)$/;
+
+function checkWithTsgo(ipc: Ipc, tsconfig: string): Promise<Diagnostic[]> {
+  const configPath = toPath(resolve(contextDir, tsconfig));
+  if (configPath !== undefined) {
+    ipc.send({ type: "fileDependency", path: configPath });
+  }
+  // tsgo doesn't expose the list of files it read, so any change in the
+  // project invalidates the check.
+  ipc.send({ type: "dirDependency", path: "", glob: "**/*.{ts,tsx,mts,cts}" });
+
+  return new Promise((resolvePromise, reject) => {
+    execFile(
+      "tsgo",
+      ["--noEmit", "--pretty", "false", "--project", tsconfig],
+      { cwd: contextDir, maxBuffer: 64 * 1024 * 1024 },
+      (error, stdout) => {
+        if (error && typeof error.code !== "number") {
+          // The process couldn't be started at all.
+          return reject(error);
+        }
+        const diagnostics: Diagnostic[] = [];
+        for (const line of stdout.split(/\r?\n/)) {
+          const match = TSGO_DIAGNOSTIC.exec(line);
+          if (match) {
+            const [, file, lineNumber, columnNumber, category, code, message] =
+              match;
+            const start = {
+              line: Number(lineNumber) - 1,
+              column: Number(columnNumber) - 1,
+            };
+            diagnostics.push({
+              file: toPath(resolve(contextDir, file)),
+              start,
+              end: start,
+              code: Number(code),
+              category: category as Diagnostic["category"],
+              message,
+            });
+          } else if (line.startsWith(" ") && diagnostics.length > 0) {
+            // Continuation of a multiline message.
+            diagnostics[diagnostics.length - 1].message += "\n" + line.trim();
+          }
+        }
+        resolvePromise(diagnostics);
+      }
+    );
+  });
+}
+
+const typeCheck = async (ipc: Ipc, checker: Checker, tsconfig: string) => {
+  switch (checker) {
+    case "tsc":
+      return checkWithTsc(ipc, tsconfig);
+    case "tsgo":
+      return checkWithTsgo(ipc, tsconfig);
+    default:
+      throw new Error(`Unknown type checker ${checker}`);
+  }
+};
+
+export { typeCheck as default };
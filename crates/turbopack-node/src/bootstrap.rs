@@ -1,7 +1,7 @@
 use std::fmt::Write;
 
 use anyhow::Result;
-use turbo_tasks_fs::{File, FileSystemPathVc};
+use turbo_tasks_fs::{File, FileSystemPath, FileSystemPathVc};
 use turbopack_core::{
     asset::{Asset, AssetContentVc, AssetVc},
     chunk::{Chunk, ChunkGroupVc, ChunkReferenceVc},
@@ -31,13 +31,51 @@ impl Asset for NodeJsBootstrapAsset {
         // but until then this is a simple hack to make it work for now
         let mut output = "Error.stackTraceLimit = 100;\nglobal.self = global;\n".to_string();
 
-        for chunk in self.chunk_group.chunks().await?.iter() {
-            let path = &*chunk.path().await?;
-            if let Some(p) = context_path.get_relative_path_to(path) {
-                if p.ends_with(".js") {
-                    writeln!(&mut output, "require({});", StringifyJs(&p))?;
-                }
-            }
+        for p in js_chunk_paths(self.chunk_group, &context_path).await? {
+            writeln!(&mut output, "require({});", StringifyJs(&p))?;
+        }
+
+        Ok(File::from(output).into())
+    }
+
+    #[turbo_tasks::function]
+    async fn references(&self) -> Result<AssetReferencesVc> {
+        chunk_group_references(&[self.chunk_group]).await
+    }
+}
+
+/// Bootstraps a renderer running in the edge runtime. The chunks of the
+/// `host_chunk_group` run in Node.js and evaluate the chunks of the
+/// `chunk_group` in a sandbox with the globals of the edge runtime only.
+#[turbo_tasks::value(shared)]
+pub(super) struct EdgeBootstrapAsset {
+    pub(super) path: FileSystemPathVc,
+    pub(super) host_chunk_group: ChunkGroupVc,
+    pub(super) chunk_group: ChunkGroupVc,
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for EdgeBootstrapAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> AssetIdentVc {
+        AssetIdentVc::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<AssetContentVc> {
+        let context_path = self.path.parent().await?;
+
+        let mut output = "Error.stackTraceLimit = 100;\nglobal.self = global;\n".to_string();
+
+        let chunks = js_chunk_paths(self.chunk_group, &context_path).await?;
+        writeln!(
+            &mut output,
+            "global.TURBOPACK_EDGE_CHUNKS = {}.map((chunk) => require(\"path\").join(__dirname, \
+             chunk));",
+            StringifyJs(&chunks)
+        )?;
+        for p in js_chunk_paths(self.host_chunk_group, &context_path).await? {
+            writeln!(&mut output, "require({});", StringifyJs(&p))?;
         }
 
         Ok(File::from(output).into())
@@ -45,11 +83,33 @@ impl Asset for NodeJsBootstrapAsset {
 
     #[turbo_tasks::function]
     async fn references(&self) -> Result<AssetReferencesVc> {
-        let chunks = self.chunk_group.chunks().await?;
-        let mut references = Vec::new();
-        for chunk in chunks.iter() {
+        chunk_group_references(&[self.host_chunk_group, self.chunk_group]).await
+    }
+}
+
+/// The paths of the JS chunks of `chunk_group`, relative to `context_path`.
+async fn js_chunk_paths(
+    chunk_group: ChunkGroupVc,
+    context_path: &FileSystemPath,
+) -> Result<Vec<String>> {
+    let mut paths = Vec::new();
+    for chunk in chunk_group.chunks().await?.iter() {
+        let path = &*chunk.path().await?;
+        if let Some(p) = context_path.get_relative_path_to(path) {
+            if p.ends_with(".js") {
+                paths.push(p);
+            }
+        }
+    }
+    Ok(paths)
+}
+
+async fn chunk_group_references(chunk_groups: &[ChunkGroupVc]) -> Result<AssetReferencesVc> {
+    let mut references = Vec::new();
+    for chunk_group in chunk_groups {
+        for chunk in chunk_group.chunks().await?.iter() {
             references.push(ChunkReferenceVc::new(*chunk).into());
         }
-        Ok(AssetReferencesVc::cell(references))
     }
+    Ok(AssetReferencesVc::cell(references))
 }
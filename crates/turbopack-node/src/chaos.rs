@@ -0,0 +1,158 @@
+//! Failure injection for the operations of a `NodeJsPool`, so the error
+//! handling of rendering (the error pages, retries and stack traces) can be
+//! covered by tests.
+//!
+//! Only available with the `chaos` feature and configured with
+//! `TURBOPACK_NODE_CHAOS`, e.g.
+//! `seed=42,kill=0.1,delay=0.2,delay_ms=500,corrupt=0.05`. The probabilities
+//! apply to every IPC message. With the same seed and the same sequence of
+//! messages, the same faults are injected.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use once_cell::sync::Lazy;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use tokio::{process::Child, time::sleep};
+
+/// How often and which faults are injected.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChaosConfig {
+    pub seed: u64,
+    /// The probability of killing the process before a message.
+    pub kill: f64,
+    /// The probability of delaying a message by `delay_duration`.
+    pub delay: f64,
+    pub delay_duration: Duration,
+    /// The probability of corrupting a received message.
+    pub corrupt: f64,
+}
+
+impl ChaosConfig {
+    /// Parses a comma separated list of `key=value` pairs.
+    pub fn parse(config: &str) -> Result<Self> {
+        let mut result = ChaosConfig::default();
+        for pair in config.split(',').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair
+                .split_once('=')
+                .with_context(|| format!("expected key=value, got {pair}"))?;
+            let value = value.trim();
+            match key.trim() {
+                "seed" => result.seed = value.parse()?,
+                "kill" => result.kill = value.parse()?,
+                "delay" => result.delay = value.parse()?,
+                "delay_ms" => result.delay_duration = Duration::from_millis(value.parse()?),
+                "corrupt" => result.corrupt = value.parse()?,
+                key => bail!("unknown key {key}"),
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// The faults injected into the operations of a pool.
+pub(crate) struct Chaos {
+    config: ChaosConfig,
+    rng: Mutex<StdRng>,
+}
+
+/// The faults configured with `TURBOPACK_NODE_CHAOS`. They are shared by all
+/// pools, so a sequence of operations is affected deterministically.
+static CHAOS: Lazy<Option<Arc<Chaos>>> = Lazy::new(|| {
+    let config = std::env::var("TURBOPACK_NODE_CHAOS").ok()?;
+    match ChaosConfig::parse(&config) {
+        Ok(config) => Some(Arc::new(Chaos::new(config))),
+        Err(err) => {
+            println!("ignoring invalid TURBOPACK_NODE_CHAOS={config}: {err}");
+            None
+        }
+    }
+});
+
+impl Chaos {
+    /// The faults configured with `TURBOPACK_NODE_CHAOS`, if any.
+    pub(crate) fn current() -> Option<Arc<Self>> {
+        CHAOS.clone()
+    }
+
+    pub(crate) fn new(config: ChaosConfig) -> Self {
+        Chaos {
+            rng: Mutex::new(StdRng::seed_from_u64(config.seed)),
+            config,
+        }
+    }
+
+    fn roll(&self, probability: f64) -> bool {
+        probability > 0.0 && self.rng.lock().unwrap().gen_bool(probability.min(1.0))
+    }
+
+    /// Possibly kills the `child` or delays the message that's about to be
+    /// exchanged with it.
+    pub(crate) async fn before_message(&self, child: Option<&mut Child>) {
+        if self.roll(self.config.kill) {
+            if let Some(child) = child {
+                println!("[chaos] killing Node.js process");
+                let _ = child.start_kill();
+                let _ = child.wait().await;
+            }
+        }
+        if self.roll(self.config.delay) {
+            println!("[chaos] delaying IPC message");
+            sleep(self.config.delay_duration).await;
+        }
+    }
+
+    /// Possibly corrupts a received `message`.
+    pub(crate) fn after_recv(&self, message: &mut Vec<u8>) {
+        if self.roll(self.config.corrupt) {
+            println!("[chaos] corrupting IPC message");
+            message.truncate(message.len() / 2);
+            message.push(0xff);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::ChaosConfig;
+
+    #[test]
+    fn parses_config() {
+        assert_eq!(
+            ChaosConfig::parse("seed=42, kill=0.1,delay=0.2,delay_ms=500,corrupt=0.05").unwrap(),
+            ChaosConfig {
+                seed: 42,
+                kill: 0.1,
+                delay: 0.2,
+                delay_duration: Duration::from_millis(500),
+                corrupt: 0.05,
+            }
+        );
+    }
+
+    #[test]
+    fn parses_partial_config() {
+        assert_eq!(ChaosConfig::parse("").unwrap(), ChaosConfig::default());
+        assert_eq!(
+            ChaosConfig::parse("kill=1,").unwrap(),
+            ChaosConfig {
+                kill: 1.0,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_config() {
+        let error = |config| ChaosConfig::parse(config).unwrap_err().to_string();
+        assert_eq!(error("kill"), "expected key=value, got kill");
+        assert_eq!(error("explode=1"), "unknown key explode");
+        assert!(ChaosConfig::parse("delay_ms=-1").is_err());
+        assert!(ChaosConfig::parse("kill=often").is_err());
+    }
+}
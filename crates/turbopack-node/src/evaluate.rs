@@ -1,4 +1,4 @@
-use std::{borrow::Cow, ops::ControlFlow, thread::available_parallelism, time::Duration};
+use std::{borrow::Cow, ops::ControlFlow, time::Duration};
 
 use anyhow::{anyhow, Result};
 use async_stream::try_stream as generator;
@@ -40,7 +40,10 @@ use crate::{
     bootstrap::NodeJsBootstrapAsset,
     embed_js::embed_file_path,
     emit, emit_package_json, internal_assets_for_source_mapping,
-    pool::{FormattingMode, NodeJsOperation, NodeJsPool, NodeJsPoolVc},
+    pool::{
+        FormattingMode, NodeJsOperation, NodeJsPool, NodeJsPoolOptions, NodeJsPoolStartupError,
+        NodeJsPoolVc,
+    },
     source_map::StructuredError,
     AssetsForSourceMappingVc,
 };
@@ -206,7 +209,7 @@ pub async fn get_evaluate_pool(
         assets_for_source_mapping,
         output_root,
         chunking_context.context_path().root(),
-        available_parallelism().map_or(1, |v| v.get()),
+        &NodeJsPoolOptions::default(),
         debug,
     );
     additional_invalidation.await?;
@@ -224,7 +227,8 @@ impl futures_retry::ErrorHandler<anyhow::Error> for PoolErrorHandler {
     type OutError = anyhow::Error;
 
     fn handle(&mut self, attempt: usize, err: anyhow::Error) -> RetryPolicy<Self::OutError> {
-        if attempt >= MAX_ATTEMPTS {
+        // The pool already waits before it starts a process again.
+        if attempt >= MAX_ATTEMPTS || err.is::<NodeJsPoolStartupError>() {
             RetryPolicy::ForwardError(err)
         } else if attempt >= MAX_FAST_ATTEMPTS {
             RetryPolicy::WaitRetry(Duration::from_secs(1))
@@ -2,16 +2,24 @@
 #![feature(min_specialization)]
 #![feature(lint_reasons)]
 
-use std::{collections::HashMap, iter::once, thread::available_parallelism};
+use std::{collections::HashMap, iter::once};
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use indexmap::IndexSet;
 pub use node_entry::{
     NodeEntry, NodeEntryVc, NodeRenderingEntriesVc, NodeRenderingEntry, NodeRenderingEntryVc,
+    RuntimeKind,
+};
+pub use pool::{
+    shutdown_node_js_pools, shutdown_node_js_pools_on_ctrl_c, BunExecutor, DenoExecutor, JsRuntime,
+    JsRuntimeExecutor, NodeJsExecutor, NodeJsPool, NodeJsPoolBusyError, NodeJsPoolOptions,
+    NodeJsPoolOptionsVc, NodeJsPoolQueueing, NodeJsPoolRecycling, NodeJsPoolResourceLimits,
+    NodeJsPoolStartupError, NodeJsPoolVc, NodeJsResourceLimitError, OperationTimings,
+    DEFAULT_SHUTDOWN_GRACE_PERIOD,
 };
 use turbo_tasks::{
     graph::{GraphTraversal, ReverseTopological, SkipDuplicates},
-    CompletionVc, CompletionsVc, TryJoinIterExt, ValueToString,
+    CompletionVc, CompletionsVc, IntoTraitRef, TraitRef, TryJoinIterExt, Value, ValueToString,
 };
 use turbo_tasks_env::{ProcessEnv, ProcessEnvVc};
 use turbo_tasks_fs::{to_sys_path, File, FileContent, FileSystemPathVc};
@@ -19,19 +27,28 @@ use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64};
 use turbopack_core::{
     asset::{Asset, AssetVc, AssetsSetVc},
     chunk::{ChunkGroupVc, ChunkVc, ChunkingContextVc},
+    context::AssetContext,
     reference::primary_referenced_assets,
+    source_asset::SourceAssetVc,
     source_map::GenerateSourceMapVc,
+    version::{PartialUpdate, Update, VersionedContent, VersionedContentVc},
     virtual_asset::VirtualAssetVc,
 };
-use turbopack_ecmascript::{chunk::EcmascriptChunkPlaceablesVc, EcmascriptModuleAssetVc};
+use turbopack_ecmascript::{
+    chunk::EcmascriptChunkPlaceablesVc, EcmascriptInputTransform, EcmascriptInputTransformsVc,
+    EcmascriptModuleAssetType, EcmascriptModuleAssetVc,
+};
 
 use self::{
-    bootstrap::NodeJsBootstrapAsset,
-    pool::{NodeJsPool, NodeJsPoolVc},
+    bootstrap::{EdgeBootstrapAsset, NodeJsBootstrapAsset, NodeJsBootstrapAssetVc},
+    embed_js::embed_file_path,
+    pool::{HotUpdate, NodeJsPool, NodeJsPoolVc},
     source_map::StructuredError,
 };
 
 pub mod bootstrap;
+#[cfg(feature = "chaos")]
+pub mod chaos;
 mod embed_js;
 pub mod evaluate;
 pub mod execution_context;
@@ -41,6 +58,7 @@ pub mod render;
 pub mod route_matcher;
 pub mod source_map;
 pub mod transforms;
+pub mod type_check;
 
 #[turbo_tasks::function]
 async fn emit(
@@ -215,7 +233,9 @@ pub(self) fn emit_package_json(dir: FileSystemPathVc) -> CompletionVc {
     )
 }
 
-/// Creates a node.js renderer pool for an entrypoint.
+/// Creates a node.js renderer pool for an entrypoint. The number of processes
+/// and what happens when all of them are busy is configured by
+/// `pool_options`.
 #[turbo_tasks::function]
 pub async fn get_renderer_pool(
     cwd: FileSystemPathVc,
@@ -224,6 +244,7 @@ pub async fn get_renderer_pool(
     intermediate_output_path: FileSystemPathVc,
     output_root: FileSystemPathVc,
     project_dir: FileSystemPathVc,
+    pool_options: NodeJsPoolOptionsVc,
     debug: bool,
 ) -> Result<NodeJsPoolVc> {
     emit_package_json(intermediate_output_path).await?;
@@ -253,12 +274,154 @@ pub async fn get_renderer_pool(
         assets_for_source_mapping,
         output_root,
         project_dir,
-        available_parallelism().map_or(1, |v| v.get()),
+        &*pool_options.await?,
         debug,
     )
     .cell())
 }
 
+/// Creates a node.js renderer pool for an entrypoint, which keeps its
+/// processes when the entrypoint changes.
+///
+/// Instead of starting new processes for every change, idle processes apply
+/// the changes to their chunks in place as hot updates before they are reused.
+/// That keeps module caches and compiled code of the processes warm. A process
+/// is replaced when the update can't be applied, e.g. because an updated
+/// module isn't accepted by any of its importers.
+///
+/// Hot updates need the chunking context of the entrypoint to create chunk
+/// lists. Without them, every change replaces the processes.
+#[turbo_tasks::function]
+pub async fn get_hot_renderer_pool(
+    cwd: FileSystemPathVc,
+    env: ProcessEnvVc,
+    intermediate_asset: AssetVc,
+    intermediate_output_path: FileSystemPathVc,
+    output_root: FileSystemPathVc,
+    project_dir: FileSystemPathVc,
+    pool_options: NodeJsPoolOptionsVc,
+    debug: bool,
+) -> Result<NodeJsPoolVc> {
+    let chunk_list = renderer_chunk_list(intermediate_asset, output_root).await?;
+    let Some((chunk_list_path, content)) = &*chunk_list else {
+        return Ok(get_renderer_pool(
+            cwd,
+            env,
+            intermediate_asset,
+            intermediate_output_path,
+            output_root,
+            project_dir,
+            pool_options,
+            debug,
+        ));
+    };
+
+    emit_package_json(intermediate_output_path).await?;
+
+    let emit = emit(intermediate_asset, output_root);
+    let pool = hot_renderer_pool(
+        cwd,
+        env,
+        internal_assets_for_source_mapping(intermediate_asset, output_root),
+        intermediate_asset.ident().path(),
+        output_root,
+        project_dir,
+        pool_options,
+        debug,
+    )
+    .await?;
+
+    emit.await?;
+
+    let version = content.version().into_trait_ref().await?;
+    let update = match pool.hot_version() {
+        Some(previous) => match &*content.update(TraitRef::cell(previous)).await? {
+            Update::Partial(PartialUpdate { instruction, .. }) => Some(HotUpdate {
+                chunk_list_path: chunk_list_path.clone(),
+                instruction: (*instruction.await?).clone(),
+            }),
+            Update::Total(_) => None,
+            Update::None => return Ok(NodeJsPool::clone(&pool).cell()),
+        },
+        None => None,
+    };
+    pool.push_hot_update(version, update);
+
+    // A new cell for the same pool, so that the renderers run again with the
+    // updated code.
+    Ok(NodeJsPool::clone(&pool).cell())
+}
+
+/// The pool of [get_hot_renderer_pool], which doesn't depend on the content of
+/// the entrypoint.
+#[turbo_tasks::function]
+async fn hot_renderer_pool(
+    cwd: FileSystemPathVc,
+    env: ProcessEnvVc,
+    assets_for_source_mapping: AssetsForSourceMappingVc,
+    entrypoint: FileSystemPathVc,
+    output_root: FileSystemPathVc,
+    project_dir: FileSystemPathVc,
+    pool_options: NodeJsPoolOptionsVc,
+    debug: bool,
+) -> Result<NodeJsPoolVc> {
+    let Some(cwd) = to_sys_path(cwd).await? else {
+        bail!("can only render from a disk filesystem, but `cwd = {}`", cwd.to_string().await?);
+    };
+    let Some(entrypoint) = to_sys_path(entrypoint).await? else {
+        bail!("can only render from a disk filesystem, but `entrypoint = {}`", entrypoint.to_string().await?);
+    };
+
+    Ok(NodeJsPool::new(
+        cwd,
+        entrypoint,
+        env.read_all()
+            .await?
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
+        assets_for_source_mapping,
+        output_root,
+        project_dir,
+        &*pool_options.await?,
+        debug,
+    )
+    .cell())
+}
+
+#[turbo_tasks::value(transparent)]
+struct OptionRendererChunkList(Option<(String, VersionedContentVc)>);
+
+/// Finds the chunk list of the chunks a renderer entrypoint loads, together
+/// with its path relative to the `output_root`.
+#[turbo_tasks::function]
+async fn renderer_chunk_list(
+    intermediate_asset: AssetVc,
+    output_root: FileSystemPathVc,
+) -> Result<OptionRendererChunkListVc> {
+    let Some(bootstrap) = NodeJsBootstrapAssetVc::resolve_from(intermediate_asset).await? else {
+        return Ok(OptionRendererChunkListVc::cell(None));
+    };
+    let chunk_list_path = bootstrap.await?.chunk_group.chunk_list_path().await?;
+    for asset in internal_assets(intermediate_asset, output_root)
+        .await?
+        .iter()
+    {
+        if *asset.ident().path().await? == *chunk_list_path {
+            let path = output_root
+                .await?
+                .get_path_to(&chunk_list_path)
+                .context("chunk list path is not in output root")?
+                .to_string();
+            return Ok(OptionRendererChunkListVc::cell(Some((
+                path,
+                asset.versioned_content(),
+            ))));
+        }
+    }
+    Ok(OptionRendererChunkListVc::cell(None))
+}
+
 /// Converts a module graph into node.js executable assets
 #[turbo_tasks::function]
 pub async fn get_intermediate_asset(
@@ -278,6 +441,42 @@ pub async fn get_intermediate_asset(
     .into())
 }
 
+/// Converts a module graph into node.js executable assets which evaluate it in
+/// a sandbox of the edge runtime, see [RuntimeKind::Edge].
+#[turbo_tasks::function]
+pub async fn get_edge_intermediate_asset(
+    module: EcmascriptModuleAssetVc,
+    runtime_entries: Option<EcmascriptChunkPlaceablesVc>,
+    chunking_context: ChunkingContextVc,
+    intermediate_output_path: FileSystemPathVc,
+) -> Result<AssetVc> {
+    let entry_chunk = module.as_evaluated_chunk(chunking_context, runtime_entries);
+    // The host runs in Node.js like any other renderer, so it's compiled in
+    // the context of the rendered module.
+    let context = module.await?.context;
+    let host = EcmascriptModuleAssetVc::new(
+        SourceAssetVc::new(embed_file_path("edge/host.ts")).into(),
+        context,
+        Value::new(EcmascriptModuleAssetType::Typescript),
+        EcmascriptInputTransformsVc::cell(vec![EcmascriptInputTransform::TypeScript {
+            use_define_for_class_fields: false,
+        }]),
+        Value::new(Default::default()),
+        context.compile_time_info(),
+    );
+    let mut hash = encode_hex(hash_xxh3_hash64(
+        entry_chunk.ident().path().to_string().await?.as_str(),
+    ));
+    hash.push_str(".edge.js");
+    Ok(EdgeBootstrapAsset {
+        path: intermediate_output_path.join(&hash),
+        host_chunk_group: ChunkGroupVc::from_chunk(host.as_evaluated_chunk(chunking_context, None)),
+        chunk_group: ChunkGroupVc::from_chunk(entry_chunk),
+    }
+    .cell()
+    .into())
+}
+
 #[turbo_tasks::value(shared)]
 pub struct ResponseHeaders {
     pub status: u16,
@@ -1,10 +1,13 @@
 use anyhow::Result;
 use turbo_tasks::Value;
+use turbo_tasks_env::ProcessEnvVc;
 use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_core::chunk::ChunkingContextVc;
 use turbopack_dev_server::source::ContentSourceData;
 use turbopack_ecmascript::EcmascriptModuleAssetVc;
 
+use crate::{pool::NodeJsPoolOptionsVc, render::static_data::StaticDataModuleVc};
+
 #[turbo_tasks::value(shared)]
 pub struct NodeRenderingEntry {
     pub module: EcmascriptModuleAssetVc,
@@ -12,6 +15,42 @@ pub struct NodeRenderingEntry {
     pub intermediate_output_path: FileSystemPathVc,
     pub output_root: FileSystemPathVc,
     pub project_dir: FileSystemPathVc,
+    /// A module that computes data for the page before it's rendered.
+    pub data_module: Option<StaticDataModuleVc>,
+    /// The time in milliseconds rendering may take. The Node.js process is
+    /// killed and a timeout error page is shown when it takes longer.
+    pub render_timeout: Option<u64>,
+    /// The time in seconds a rendered page is served from a cache before it's
+    /// rendered again in the background, see [RenderCache]. Pages aren't
+    /// cached when it's `None`.
+    ///
+    /// [RenderCache]: crate::render::render_cache::RenderCache
+    pub revalidate: Option<u64>,
+    /// Limits for the Node.js processes rendering this entry.
+    pub pool_options: NodeJsPoolOptionsVc,
+    /// The runtime the entry is rendered in. Only static rendering supports
+    /// [RuntimeKind::Edge].
+    pub runtime: RuntimeKind,
+    /// Variables which override the environment of the renderer while it
+    /// renders this entry, e.g. depending on the request for A/B tests. Only
+    /// static rendering supports them.
+    pub env_overrides: Option<ProcessEnvVc>,
+}
+
+/// The runtime a renderer is executed in.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(Debug, Default, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum RuntimeKind {
+    /// Node.js, with all of its builtins.
+    #[default]
+    NodeJs,
+    /// A sandbox with the globals of edge runtimes only, e.g. `fetch`,
+    /// `Request` and `Response`, but without Node.js builtins. It's still
+    /// executed by Node.js, so it's meant to verify that a page is edge
+    /// compatible, not to emulate a specific edge runtime.
+    ///
+    /// Renderers need to use the IPC of `@vercel/turbopack-node/ipc/edge`.
+    Edge,
 }
 
 #[turbo_tasks::value(transparent)]
@@ -6,15 +6,20 @@ use std::{
     mem::take,
     path::{Path, PathBuf},
     process::{ExitStatus, Stdio},
-    sync::{Arc, Mutex},
-    time::Duration,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, Weak,
+    },
+    thread::available_parallelism,
+    time::{Duration, Instant},
 };
 
 use anyhow::{bail, Context, Result};
-use futures::join;
+use futures::{future::join_all, join, stream, Stream, StreamExt};
 use indexmap::IndexSet;
+use once_cell::sync::Lazy;
 use owo_colors::{OwoColorize, Style};
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use tokio::{
     io::{
         stderr, stdout, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt,
@@ -24,12 +29,19 @@ use tokio::{
     process::{Child, ChildStderr, ChildStdout, Command},
     select,
     sync::{OwnedSemaphorePermit, Semaphore},
-    time::{sleep, timeout},
+    time::{sleep, sleep_until, timeout},
 };
+use turbo_tasks::{concurrency::ConcurrencyClass, trace::TraceRawVcs, TraitRef};
 use turbo_tasks_fs::FileSystemPathVc;
+use turbopack_core::{error::PrettyPrintError, version::VersionVc};
 use turbopack_ecmascript::magic_identifier::unmangle_identifiers;
 
-use crate::{source_map::apply_source_mapping, AssetsForSourceMappingVc};
+#[cfg(feature = "chaos")]
+use crate::chaos::Chaos;
+use crate::{
+    source_map::{apply_source_mapping, StructuredError},
+    AssetsForSourceMappingVc,
+};
 
 #[derive(Clone, Copy)]
 pub enum FormattingMode {
@@ -64,6 +76,38 @@ impl FormattingMode {
     }
 }
 
+/// Prints where to attach a debugger to a process spawned with
+/// `--inspect-brk`. Node.js and Deno print the WebSocket URL of the inspector
+/// as the first line of their error output.
+async fn print_devtools_url(child: &mut Child, inspect_port: u16, runtime_name: &str) {
+    let mut line = Vec::new();
+    if let Some(stderr) = child.stderr.as_mut() {
+        // The output is read byte by byte, so nothing after the line is
+        // buffered here. The rest is handled like any other output.
+        let _ = timeout(CONNECT_TIMEOUT, async {
+            while let Ok(byte) = stderr.read_u8().await {
+                if byte == b'\n' {
+                    break;
+                }
+                line.push(byte);
+            }
+        })
+        .await;
+    }
+    let line = String::from_utf8_lossy(&line);
+    match line.split_once("ws://") {
+        Some((_, address)) => println!(
+            "Waiting for a debugger to attach to the {runtime_name} process. Open \
+             devtools://devtools/bundled/js_app.html?ws={} in Chrome, or chrome://inspect",
+            address.trim()
+        ),
+        None => println!(
+            "Waiting for a debugger to attach to the {runtime_name} process at \
+             127.0.0.1:{inspect_port}, e.g. with chrome://inspect"
+        ),
+    }
+}
+
 enum NodeJsPoolProcess {
     Spawned(SpawnedNodeJsPoolProcess),
     Running(RunningNodeJsPoolProcess),
@@ -77,7 +121,9 @@ struct SpawnedNodeJsPoolProcess {
     project_dir: FileSystemPathVc,
     shared_stdout: SharedOutputSet,
     shared_stderr: SharedOutputSet,
-    debug: bool,
+    inspect_port: Option<u16>,
+    runtime_name: &'static str,
+    generation: usize,
 }
 
 struct RunningNodeJsPoolProcess {
@@ -88,9 +134,27 @@ struct RunningNodeJsPoolProcess {
     project_dir: FileSystemPathVc,
     stdout_handler: OutputStreamHandler<ChildStdout, Stdout>,
     stderr_handler: OutputStreamHandler<ChildStderr, Stderr>,
+    /// The number of hot updates of the pool which have been applied to the
+    /// code running in this process.
+    generation: usize,
+    /// When the process has been returned to the pool.
+    idle_since: Instant,
+    /// The number of operations the process has started.
+    operations: u32,
 }
 
 impl RunningNodeJsPoolProcess {
+    fn set_output_prefix(&mut self, prefix: Option<String>) {
+        self.stdout_handler.prefix = prefix.clone();
+        self.stderr_handler.prefix = prefix;
+    }
+
+    fn set_capture_output(&mut self, capture: bool) {
+        let captured = || capture.then(Vec::new);
+        self.stdout_handler.captured = captured();
+        self.stderr_handler.captured = captured();
+    }
+
     pub async fn apply_source_mapping<'a>(
         &self,
         text: &'a str,
@@ -125,6 +189,40 @@ impl RunningNodeJsPoolProcess {
 
 const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
 
+/// The port of the inspector of pools created in debug mode, unless
+/// [NodeJsPoolOptions::inspect_port] is set.
+const DEFAULT_INSPECT_PORT: u16 = 9229;
+
+/// The time a pool waits before it starts a process again after a process
+/// failed to start. It's doubled with every failure in a row, up to
+/// [MAX_STARTUP_BACKOFF].
+const STARTUP_BACKOFF: Duration = Duration::from_secs(1);
+
+const MAX_STARTUP_BACKOFF: Duration = Duration::from_secs(60);
+
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// How long processes may take to exit on their own when the pools are shut
+/// down, e.g. to run their `beforeExit` hooks, before they are killed.
+pub const DEFAULT_SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// How long a process may stay idle in a pool before it exits. Can be set in
+/// seconds with `TURBOPACK_NODE_IDLE_TIMEOUT`, where `0` keeps idle processes
+/// alive forever.
+static IDLE_TIMEOUT: Lazy<Option<Duration>> = Lazy::new(|| {
+    let Ok(seconds) = std::env::var("TURBOPACK_NODE_IDLE_TIMEOUT") else {
+        return Some(DEFAULT_IDLE_TIMEOUT);
+    };
+    match seconds.parse::<u64>() {
+        Ok(0) => None,
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(_) => {
+            println!("ignoring invalid TURBOPACK_NODE_IDLE_TIMEOUT={seconds}");
+            Some(DEFAULT_IDLE_TIMEOUT)
+        }
+    }
+});
+
 #[derive(Clone, PartialEq, Eq, Hash)]
 struct OutputEntry {
     data: Arc<[u8]>,
@@ -144,6 +242,12 @@ struct OutputStreamHandler<R: AsyncRead + Unpin, W: AsyncWrite + Unpin> {
     root: FileSystemPathVc,
     project_dir: FileSystemPathVc,
     final_stream: W,
+    /// Prepended to every line of output, e.g. to tell which request caused
+    /// it.
+    prefix: Option<String>,
+    /// The output of the current operation, source mapped and without
+    /// colors, when it's captured.
+    captured: Option<Vec<String>>,
 }
 
 impl<R: AsyncRead + Unpin, W: AsyncWrite + Unpin> OutputStreamHandler<R, W> {
@@ -159,6 +263,8 @@ impl<R: AsyncRead + Unpin, W: AsyncWrite + Unpin> OutputStreamHandler<R, W> {
             root,
             project_dir,
             final_stream,
+            prefix,
+            captured,
         } = self;
 
         async fn write_final<W: AsyncWrite + Unpin>(
@@ -181,12 +287,20 @@ impl<R: AsyncRead + Unpin, W: AsyncWrite + Unpin> OutputStreamHandler<R, W> {
             assets_for_source_mapping: AssetsForSourceMappingVc,
             root: FileSystemPathVc,
             project_dir: FileSystemPathVc,
+            prefix: Option<&str>,
             final_stream: &mut W,
         ) -> Result<()> {
             if let Ok(text) = std::str::from_utf8(bytes) {
                 let text = unmangle_identifiers(text, |content| {
                     format!("{{{}}}", content).italic().to_string()
                 });
+                let with_prefix = |text: &str| match prefix {
+                    Some(prefix) => text
+                        .split_inclusive('\n')
+                        .map(|line| format!("{prefix}{line}"))
+                        .collect(),
+                    None => text.to_string(),
+                };
                 match apply_source_mapping(
                     text.as_ref(),
                     assets_for_source_mapping,
@@ -198,14 +312,15 @@ impl<R: AsyncRead + Unpin, W: AsyncWrite + Unpin> OutputStreamHandler<R, W> {
                 {
                     Err(e) => {
                         write_final(
-                            format!("Error applying source mapping: {e}\n").as_bytes(),
+                            with_prefix(&format!("Error applying source mapping: {e}\n"))
+                                .as_bytes(),
                             final_stream,
                         )
                         .await?;
-                        write_final(text.as_bytes(), final_stream).await?;
+                        write_final(with_prefix(&text).as_bytes(), final_stream).await?;
                     }
                     Ok(text) => {
-                        write_final(text.as_bytes(), final_stream).await?;
+                        write_final(with_prefix(&text).as_bytes(), final_stream).await?;
                     }
                 }
             } else {
@@ -214,6 +329,33 @@ impl<R: AsyncRead + Unpin, W: AsyncWrite + Unpin> OutputStreamHandler<R, W> {
             Ok(())
         }
 
+        async fn capture(
+            bytes: &[u8],
+            assets_for_source_mapping: AssetsForSourceMappingVc,
+            root: FileSystemPathVc,
+            project_dir: FileSystemPathVc,
+            captured: &mut Option<Vec<String>>,
+        ) {
+            let Some(captured) = captured else {
+                return;
+            };
+            let text = String::from_utf8_lossy(bytes);
+            let text = unmangle_identifiers(&text, |content| format!("{{{content}}}"));
+            let text = match apply_source_mapping(
+                text.as_ref(),
+                assets_for_source_mapping,
+                root,
+                project_dir,
+                FormattingMode::Plain,
+            )
+            .await
+            {
+                Ok(mapped) => mapped.into_owned(),
+                Err(_) => text.to_string(),
+            };
+            captured.push(text);
+        }
+
         let mut buffer = Vec::new();
         let mut own_output = HashMap::new();
         let mut nesting: u32 = 0;
@@ -266,6 +408,14 @@ impl<R: AsyncRead + Unpin, W: AsyncWrite + Unpin> OutputStreamHandler<R, W> {
                                 data: line,
                                 stack_trace,
                             };
+                            capture(
+                                &entry.data,
+                                *assets_for_source_mapping,
+                                *root,
+                                *project_dir,
+                                captured,
+                            )
+                            .await;
                             let occurrence_number = *own_output
                                 .entry(entry.clone())
                                 .and_modify(|c| *c += 1)
@@ -284,6 +434,7 @@ impl<R: AsyncRead + Unpin, W: AsyncWrite + Unpin> OutputStreamHandler<R, W> {
                                 *assets_for_source_mapping,
                                 *root,
                                 *project_dir,
+                                prefix.as_deref(),
                                 final_stream,
                             )
                             .await?;
@@ -306,11 +457,21 @@ impl<R: AsyncRead + Unpin, W: AsyncWrite + Unpin> OutputStreamHandler<R, W> {
                 continue;
             }
 
+            capture(
+                &buffer,
+                *assets_for_source_mapping,
+                *root,
+                *project_dir,
+                captured,
+            )
+            .await;
+
             write_source_mapped_final(
                 &buffer,
                 *assets_for_source_mapping,
                 *root,
                 *project_dir,
+                prefix.as_deref(),
                 final_stream,
             )
             .await?;
@@ -324,25 +485,24 @@ impl NodeJsPoolProcess {
     async fn new(
         cwd: &Path,
         env: &HashMap<String, String>,
+        executor: &dyn JsRuntimeExecutor,
+        runtime_args: &[String],
         entrypoint: &Path,
         assets_for_source_mapping: AssetsForSourceMappingVc,
         assets_root: FileSystemPathVc,
         project_dir: FileSystemPathVc,
         shared_stdout: SharedOutputSet,
         shared_stderr: SharedOutputSet,
-        debug: bool,
+        inspect_port: Option<u16>,
+        resource_limits: NodeJsPoolResourceLimits,
+        generation: usize,
     ) -> Result<Self> {
         let listener = TcpListener::bind("127.0.0.1:0")
             .await
             .context("binding to a port")?;
         let port = listener.local_addr().context("getting port")?.port();
-        let mut cmd = Command::new("node");
+        let mut cmd = executor.command(runtime_args, entrypoint, port, inspect_port);
         cmd.current_dir(cwd);
-        if debug {
-            cmd.arg("--inspect-brk");
-        }
-        cmd.arg(entrypoint);
-        cmd.arg(port.to_string());
         cmd.env_clear();
         cmd.env(
             "PATH",
@@ -358,18 +518,41 @@ impl NodeJsPoolProcess {
         cmd.stderr(Stdio::piped());
         cmd.stdout(Stdio::piped());
         cmd.kill_on_drop(true);
+        #[cfg(target_os = "linux")]
+        if let Some(max_data) = resource_limits.max_data_segment() {
+            // SAFETY: `setrlimit` is async-signal-safe, so it may be called
+            // between `fork` and `exec`.
+            unsafe {
+                cmd.pre_exec(move || {
+                    let limit = libc::rlimit {
+                        rlim_cur: max_data,
+                        rlim_max: max_data,
+                    };
+                    if libc::setrlimit(libc::RLIMIT_DATA, &limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = resource_limits;
 
-        let child = cmd.spawn().context("spawning node pooled process")?;
+        let child = cmd
+            .spawn()
+            .with_context(|| format!("spawning {} pooled process", executor.name()))?;
 
         Ok(Self::Spawned(SpawnedNodeJsPoolProcess {
             listener,
             child,
-            debug,
+            inspect_port,
+            runtime_name: executor.name(),
             assets_for_source_mapping,
             assets_root,
             project_dir,
             shared_stdout,
             shared_stderr,
+            generation,
         }))
     }
 
@@ -383,9 +566,13 @@ impl NodeJsPoolProcess {
                 project_dir,
                 shared_stdout,
                 shared_stderr,
-                debug,
+                inspect_port,
+                runtime_name,
+                generation,
             }) => {
-                let timeout = if debug {
+                // The process waits for a debugger to attach before it connects.
+                let timeout = if let Some(inspect_port) = inspect_port {
+                    print_devtools_url(&mut child, inspect_port, runtime_name).await;
                     Duration::MAX
                 } else {
                     CONNECT_TIMEOUT
@@ -453,6 +640,8 @@ impl NodeJsPoolProcess {
                     root: assets_root,
                     project_dir,
                     final_stream: stdout(),
+                    prefix: None,
+                    captured: None,
                 };
                 let stderr_handler = OutputStreamHandler {
                     stream: child_stderr,
@@ -461,6 +650,8 @@ impl NodeJsPoolProcess {
                     root: assets_root,
                     project_dir,
                     final_stream: stderr(),
+                    prefix: None,
+                    captured: None,
                 };
 
                 RunningNodeJsPoolProcess {
@@ -471,6 +662,9 @@ impl NodeJsPoolProcess {
                     project_dir,
                     stdout_handler,
                     stderr_handler,
+                    generation,
+                    idle_since: Instant::now(),
+                    operations: 0,
                 }
             }
             NodeJsPoolProcess::Running(running) => running,
@@ -479,48 +673,583 @@ impl NodeJsPoolProcess {
 }
 
 impl RunningNodeJsPoolProcess {
-    async fn recv(&mut self) -> Result<Vec<u8>> {
-        let connection = &mut self.connection;
-        let recv_future = async move {
-            let packet_len = connection
-                .read_u32()
-                .await
-                .context("reading packet length")?
+    async fn recv(&mut self) -> Result<(Vec<u8>, Duration)> {
+        recv_packet(
+            &mut self.connection,
+            &mut self.stdout_handler,
+            &mut self.stderr_handler,
+        )
+        .await
+    }
+
+    async fn send(&mut self, packet_data: Vec<u8>) -> Result<()> {
+        send_packet(&mut self.connection, &packet_data).await
+    }
+}
+
+/// Receives a packet from the `connection` of a process, while handling the
+/// output the process printed before it sent the packet. Also returns how long
+/// the packet took to transfer once its length was received.
+async fn recv_packet(
+    connection: &mut (impl AsyncRead + Unpin),
+    stdout_handler: &mut OutputStreamHandler<ChildStdout, Stdout>,
+    stderr_handler: &mut OutputStreamHandler<ChildStderr, Stderr>,
+) -> Result<(Vec<u8>, Duration)> {
+    let recv_future = async move {
+        let packet_len = connection
+            .read_u32()
+            .await
+            .context("reading packet length")?
+            .try_into()
+            .context("storing packet length")?;
+        let start = Instant::now();
+        let mut packet_data = vec![0; packet_len];
+        connection
+            .read_exact(&mut packet_data)
+            .await
+            .context("reading packet data")?;
+        Ok::<_, anyhow::Error>((packet_data, start.elapsed()))
+    };
+    let (result, stdout, stderr) = join!(
+        recv_future,
+        stdout_handler.handle_operation(),
+        stderr_handler.handle_operation(),
+    );
+    let result = result?;
+    stdout.context("unable to handle stdout from the Node.js process in a structured way")?;
+    stderr.context("unable to handle stderr from the Node.js process in a structured way")?;
+    Ok(result)
+}
+
+async fn send_packet(connection: &mut (impl AsyncWrite + Unpin), packet_data: &[u8]) -> Result<()> {
+    connection
+        .write_u32(
+            packet_data
+                .len()
                 .try_into()
-                .context("storing packet length")?;
-            let mut packet_data = vec![0; packet_len];
-            connection
-                .read_exact(&mut packet_data)
-                .await
-                .context("reading packet data")?;
-            Ok::<_, anyhow::Error>(packet_data)
+                .context("packet length does not fit into u32")?,
+        )
+        .await
+        .context("writing packet length")?;
+    connection
+        .write_all(packet_data)
+        .await
+        .context("writing packet data")?;
+    Ok(())
+}
+
+/// An update of the code running in the processes of a [NodeJsPool], which
+/// the Node.js runtime can apply in place.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HotUpdate {
+    /// The path of the updated chunk list, relative to the output root.
+    pub chunk_list_path: String,
+    /// The instruction of the partial update of the chunk list.
+    pub instruction: serde_json::Value,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum HotUpdateMessage<'a> {
+    HotUpdate(&'a HotUpdate),
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum HotUpdateResponse {
+    HotUpdateApplied,
+    HotUpdateFailed(StructuredError),
+}
+
+#[derive(Default)]
+struct HotUpdates {
+    /// The version of the code the latest processes are running.
+    version: Option<TraitRef<VersionVc>>,
+    /// `updates[n]` brings a process from generation `n` to `n + 1`. It's
+    /// `None` when the update can't be applied in place.
+    updates: Vec<Option<HotUpdate>>,
+}
+
+/// What an operation does when all processes of a [NodeJsPool] are busy.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub enum NodeJsPoolQueueing {
+    /// Waits until a process is free.
+    #[default]
+    Wait,
+    /// Fails with [NodeJsPoolBusyError], e.g. so a server can respond with 503
+    /// instead of piling up requests.
+    Reject,
+}
+
+/// When a [NodeJsPool] replaces its processes, so memory leaked by long
+/// running processes is reclaimed. Processes are only retired between
+/// operations, and the next operation starts a new process instead.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub struct NodeJsPoolRecycling {
+    /// Retires a process after it handled this many operations.
+    pub max_operations: Option<u32>,
+    /// Retires a process once its resident set size exceeds this many bytes.
+    /// It's only supported on Linux and macOS.
+    pub max_rss: Option<u64>,
+}
+
+impl NodeJsPoolRecycling {
+    async fn should_retire(&self, process: &RunningNodeJsPoolProcess) -> bool {
+        if let Some(max_operations) = self.max_operations {
+            if process.operations >= max_operations {
+                return true;
+            }
+        }
+        let (Some(max_rss), Some(pid)) = (
+            self.max_rss,
+            process.child.as_ref().and_then(|child| child.id()),
+        ) else {
+            return false;
         };
-        let (result, stdout, stderr) = join!(
-            recv_future,
-            self.stdout_handler.handle_operation(),
-            self.stderr_handler.handle_operation(),
-        );
-        let result = result?;
-        stdout.context("unable to handle stdout from the Node.js process in a structured way")?;
-        stderr.context("unable to handle stderr from the Node.js process in a structured way")?;
-        Ok(result)
+        resident_set_size(pid)
+            .await
+            .map_or(false, |rss| rss > max_rss)
     }
+}
 
-    async fn send(&mut self, packet_data: Vec<u8>) -> Result<()> {
-        self.connection
-            .write_u32(
-                packet_data
-                    .len()
-                    .try_into()
-                    .context("packet length does not fit into u32")?,
-            )
+/// The resident set size of the process with the `pid` in bytes, when the
+/// platform allows reading it.
+async fn resident_set_size(pid: u32) -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let status = tokio::fs::read_to_string(format!("/proc/{pid}/status"))
             .await
-            .context("writing packet length")?;
-        self.connection
-            .write_all(&packet_data)
+            .ok()?;
+        let rss = status
+            .lines()
+            .find_map(|line| line.strip_prefix("VmRSS:"))?
+            .trim()
+            .strip_suffix("kB")?;
+        Some(rss.trim().parse::<u64>().ok()? * 1024)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let output = Command::new("ps")
+            .args(["-o", "rss=", "-p", &pid.to_string()])
+            .output()
             .await
-            .context("writing packet data")?;
-        Ok(())
+            .ok()?;
+        let rss = String::from_utf8(output.stdout).ok()?;
+        Some(rss.trim().parse::<u64>().ok()? * 1024)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+/// Limits on the resources the processes of a [NodeJsPool] may use, so a
+/// runaway render can't take down the machine. A process which exceeds them is
+/// killed, and its operation fails with [NodeJsResourceLimitError].
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub struct NodeJsPoolResourceLimits {
+    /// The memory a process may use, in megabytes. The JavaScript heap is
+    /// limited with `--max-old-space-size` (Node.js and Deno). On Linux, the
+    /// data segment of the process is limited to twice as much, which also
+    /// covers memory allocated outside of the heap, e.g. by native addons.
+    pub max_memory_mb: Option<u64>,
+    /// The CPU time a process may use for a single operation. It's only
+    /// enforced on Linux and macOS.
+    pub max_cpu_time: Option<Duration>,
+}
+
+impl NodeJsPoolResourceLimits {
+    /// The limit of the data segment of a process in bytes.
+    #[cfg(target_os = "linux")]
+    fn max_data_segment(&self) -> Option<u64> {
+        self.max_memory_mb
+            .map(|max_memory_mb| max_memory_mb.saturating_mul(2 * 1024 * 1024))
+    }
+
+    /// Checks whether the `child` ran out of memory after receiving a message
+    /// from it failed, i.e. it has been aborted by V8 or killed by the kernel.
+    async fn out_of_memory(&self, child: Option<&mut Child>) -> Option<NodeJsResourceLimitError> {
+        let max_memory_mb = self.max_memory_mb?;
+        // The connection may be closed before the process has exited.
+        let status = timeout(Duration::from_secs(1), child?.wait())
+            .await
+            .ok()?
+            .ok()?;
+        #[cfg(unix)]
+        let aborted = {
+            use std::os::unix::process::ExitStatusExt;
+            matches!(status.signal(), Some(libc::SIGABRT | libc::SIGKILL))
+        };
+        #[cfg(not(unix))]
+        let aborted = status.code() == Some(134);
+        aborted.then_some(NodeJsResourceLimitError::Memory { max_memory_mb })
+    }
+
+    /// Resolves once the process with the `pid` used more than the CPU time
+    /// limit since `start`. Never resolves without a limit.
+    async fn watch_cpu_time(
+        &self,
+        pid: Option<u32>,
+        start: Option<Duration>,
+    ) -> NodeJsResourceLimitError {
+        let (Some(max_cpu_time), Some(pid), Some(start)) = (self.max_cpu_time, pid, start) else {
+            return futures::future::pending().await;
+        };
+        loop {
+            sleep(CPU_TIME_POLL_INTERVAL).await;
+            if let Some(cpu_time) = cpu_time(pid).await {
+                if cpu_time.saturating_sub(start) > max_cpu_time {
+                    return NodeJsResourceLimitError::CpuTime { max_cpu_time };
+                }
+            }
+        }
+    }
+}
+
+/// How often the CPU time of a process is checked against
+/// [NodeJsPoolResourceLimits::max_cpu_time].
+const CPU_TIME_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The CPU time the process with the `pid` has used so far, user and system
+/// time combined, when the platform allows reading it.
+async fn cpu_time(pid: u32) -> Option<Duration> {
+    #[cfg(target_os = "linux")]
+    {
+        let stat = tokio::fs::read_to_string(format!("/proc/{pid}/stat"))
+            .await
+            .ok()?;
+        // The name of the process may contain spaces, the fields after it
+        // don't. `utime` and `stime` are the 14th and 15th field.
+        let mut fields = stat.rsplit_once(')')?.1.split_whitespace().skip(11);
+        let utime = fields.next()?.parse::<u64>().ok()?;
+        let stime = fields.next()?.parse::<u64>().ok()?;
+        // SAFETY: `sysconf` has no memory safety requirements.
+        let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        if ticks_per_second <= 0 {
+            return None;
+        }
+        Some(Duration::from_secs_f64(
+            (utime + stime) as f64 / ticks_per_second as f64,
+        ))
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let output = Command::new("ps")
+            .args(["-o", "time=", "-p", &pid.to_string()])
+            .output()
+            .await
+            .ok()?;
+        // The time is printed as `[[hours:]minutes:]seconds.fraction`.
+        let time = String::from_utf8(output.stdout).ok()?;
+        let mut seconds = 0.0;
+        for component in time.trim().split(':') {
+            seconds = seconds * 60.0 + component.parse::<f64>().ok()?;
+        }
+        Some(Duration::from_secs_f64(seconds))
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+/// Spawns the processes of a [NodeJsPool] with a JavaScript runtime. The
+/// runtime needs to support the `node:net` module, which the entrypoint uses
+/// to connect to the pool.
+pub trait JsRuntimeExecutor: Send + Sync {
+    /// The name of the runtime in messages, e.g. "Node.js".
+    fn name(&self) -> &'static str;
+
+    /// Creates the command which runs the `entrypoint` with the `runtime_args`
+    /// of the pool. The entrypoint expects the `port` of the pool as its first
+    /// argument. With an `inspect_port`, the process waits for a debugger to
+    /// attach on that port before it runs the entrypoint.
+    fn command(
+        &self,
+        runtime_args: &[String],
+        entrypoint: &Path,
+        port: u16,
+        inspect_port: Option<u16>,
+    ) -> Command;
+}
+
+/// Runs the processes with the `node` binary.
+pub struct NodeJsExecutor {
+    pub binary: String,
+}
+
+impl JsRuntimeExecutor for NodeJsExecutor {
+    fn name(&self) -> &'static str {
+        "Node.js"
+    }
+
+    fn command(
+        &self,
+        runtime_args: &[String],
+        entrypoint: &Path,
+        port: u16,
+        inspect_port: Option<u16>,
+    ) -> Command {
+        node_compatible_command(&self.binary, runtime_args, entrypoint, port, inspect_port)
+    }
+}
+
+/// A command for a runtime which takes the same arguments as `node`.
+fn node_compatible_command(
+    binary: &str,
+    runtime_args: &[String],
+    entrypoint: &Path,
+    port: u16,
+    inspect_port: Option<u16>,
+) -> Command {
+    let mut cmd = Command::new(binary);
+    if let Some(inspect_port) = inspect_port {
+        cmd.arg(format!("--inspect-brk=127.0.0.1:{inspect_port}"));
+    }
+    cmd.args(runtime_args);
+    cmd.arg(entrypoint);
+    cmd.arg(port.to_string());
+    cmd
+}
+
+/// Runs the processes with the `bun` binary, which implements the Node.js
+/// APIs the entrypoint uses.
+pub struct BunExecutor {
+    pub binary: String,
+}
+
+impl JsRuntimeExecutor for BunExecutor {
+    fn name(&self) -> &'static str {
+        "Bun"
+    }
+
+    fn command(
+        &self,
+        runtime_args: &[String],
+        entrypoint: &Path,
+        port: u16,
+        inspect_port: Option<u16>,
+    ) -> Command {
+        node_compatible_command(&self.binary, runtime_args, entrypoint, port, inspect_port)
+    }
+}
+
+/// Runs the processes with the `deno` binary. The entrypoint is a CommonJS
+/// script which needs access to the network, the file system and the
+/// environment, so it's run with all permissions.
+pub struct DenoExecutor {
+    pub binary: String,
+}
+
+impl JsRuntimeExecutor for DenoExecutor {
+    fn name(&self) -> &'static str {
+        "Deno"
+    }
+
+    fn command(
+        &self,
+        runtime_args: &[String],
+        entrypoint: &Path,
+        port: u16,
+        inspect_port: Option<u16>,
+    ) -> Command {
+        let mut cmd = Command::new(&self.binary);
+        // Permission flags and the inspector are options of `run`.
+        cmd.args(["run", "--allow-all", "--unstable-detect-cjs"]);
+        if let Some(inspect_port) = inspect_port {
+            cmd.arg(format!("--inspect-brk=127.0.0.1:{inspect_port}"));
+        }
+        cmd.args(runtime_args);
+        cmd.arg(entrypoint);
+        cmd.arg(port.to_string());
+        cmd
+    }
+}
+
+/// The JavaScript runtime of the processes of a [NodeJsPool].
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub enum JsRuntime {
+    #[default]
+    NodeJs,
+    Bun,
+    Deno,
+}
+
+impl JsRuntime {
+    /// The executor of the runtime, which runs the `binary` or the binary of
+    /// the runtime in `PATH`.
+    pub fn executor(&self, binary: Option<&str>) -> Arc<dyn JsRuntimeExecutor> {
+        match self {
+            JsRuntime::NodeJs => Arc::new(NodeJsExecutor {
+                binary: binary.unwrap_or("node").to_string(),
+            }),
+            JsRuntime::Bun => Arc::new(BunExecutor {
+                binary: binary.unwrap_or("bun").to_string(),
+            }),
+            JsRuntime::Deno => Arc::new(DenoExecutor {
+                binary: binary.unwrap_or("deno").to_string(),
+            }),
+        }
+    }
+
+    /// The flag which limits the JavaScript heap to `max_memory_mb`, when the
+    /// runtime has one. Bun's engine has no such limit.
+    fn heap_limit_arg(&self, max_memory_mb: u64) -> Option<String> {
+        match self {
+            JsRuntime::NodeJs => Some(format!("--max-old-space-size={max_memory_mb}")),
+            JsRuntime::Bun => None,
+            JsRuntime::Deno => Some(format!("--v8-flags=--max-old-space-size={max_memory_mb}")),
+        }
+    }
+}
+
+/// Limits for the processes of a [NodeJsPool].
+///
+/// A process always handles a single operation at a time: an operation owns
+/// the connection to its process until it's done, and the messages of the
+/// IPC protocol don't say which operation they belong to. Concurrency is only
+/// configured across processes, with `max_processes`.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Default, Debug)]
+pub struct NodeJsPoolOptions {
+    /// The number of processes the pool may run at the same time. Since every
+    /// process handles one operation at a time, this is also the number of
+    /// operations in flight. Defaults to the available parallelism.
+    pub max_processes: Option<usize>,
+    pub queueing: NodeJsPoolQueueing,
+    /// Flags passed to the runtime before the entrypoint, e.g.
+    /// `--max-old-space-size=4096` or `--experimental-vm-modules` for Node.js.
+    pub node_args: Vec<String>,
+    /// Options appended to the `NODE_OPTIONS` of the environment, which are
+    /// inherited by processes spawned by the renderer, too.
+    pub node_options: Option<String>,
+    /// Spawns the processes with `--inspect-brk` on this port, so a debugger
+    /// can attach to them, e.g. to set breakpoints in server-side code. The
+    /// pool runs a single process at a time and operations don't time out
+    /// then. The port needs to be unique across the pools that are debugged
+    /// at the same time.
+    pub inspect_port: Option<u16>,
+    pub recycling: NodeJsPoolRecycling,
+    /// Runs the processes with another runtime than Node.js, e.g. Bun.
+    pub runtime: JsRuntime,
+    /// The path of the binary of the `runtime`. Defaults to the binary of the
+    /// runtime in `PATH`.
+    pub runtime_binary: Option<String>,
+    pub resource_limits: NodeJsPoolResourceLimits,
+    pub placeholder_for_future_extensions: (),
+}
+
+#[turbo_tasks::value_impl]
+impl NodeJsPoolOptionsVc {
+    #[turbo_tasks::function]
+    pub fn default() -> Self {
+        Self::cell(Default::default())
+    }
+}
+
+impl Default for NodeJsPoolOptionsVc {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl NodeJsPoolOptions {
+    fn max_processes(&self) -> usize {
+        self.max_processes
+            .unwrap_or_else(|| available_parallelism().map_or(1, |v| v.get()))
+            .max(1)
+    }
+}
+
+/// The error of an operation which was rejected because all processes of the
+/// pool were busy. See [NodeJsPoolQueueing::Reject].
+#[derive(Debug)]
+pub struct NodeJsPoolBusyError;
+
+impl Display for NodeJsPoolBusyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "all Node.js processes are busy")
+    }
+}
+
+impl std::error::Error for NodeJsPoolBusyError {}
+
+/// The error of an operation which was rejected because processes of the pool
+/// failed to start repeatedly, and the pool waits before it tries again.
+/// Operations fail right away meanwhile, instead of each of them starting a
+/// process that fails the same way.
+#[derive(Debug)]
+pub struct NodeJsPoolStartupError {
+    /// The number of processes that failed to start in a row.
+    pub failures: u32,
+    /// The error of the last process that failed to start.
+    pub error: String,
+    /// The time until the pool starts a process again.
+    pub retry_in: Duration,
+}
+
+impl Display for NodeJsPoolStartupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the Node.js process failed to start {} times in a row, retrying in {}s. The last \
+             failure was:\n{}",
+            self.failures,
+            self.retry_in.as_secs_f64().ceil(),
+            self.error
+        )
+    }
+}
+
+impl std::error::Error for NodeJsPoolStartupError {}
+
+/// The error of an operation whose process exceeded the
+/// [NodeJsPoolResourceLimits] of the pool. The process is killed.
+#[derive(Debug)]
+pub enum NodeJsResourceLimitError {
+    /// The process ran out of memory.
+    Memory { max_memory_mb: u64 },
+    /// The process used more CPU time than allowed for the operation.
+    CpuTime { max_cpu_time: Duration },
+}
+
+impl Display for NodeJsResourceLimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeJsResourceLimitError::Memory { max_memory_mb } => write!(
+                f,
+                "render exceeded resource limits: the Node.js process ran out of memory (limit \
+                 {max_memory_mb} MB)"
+            ),
+            NodeJsResourceLimitError::CpuTime { max_cpu_time } => write!(
+                f,
+                "render exceeded resource limits: the Node.js process used more than {}s of CPU \
+                 time",
+                max_cpu_time.as_secs_f64()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NodeJsResourceLimitError {}
+
+/// The processes of a pool that failed to start in a row.
+#[derive(Default)]
+struct StartupFailures {
+    count: u32,
+    last_error: String,
+    /// No process is started before this time.
+    retry_at: Option<Instant>,
+}
+
+impl StartupFailures {
+    fn backoff(&self) -> Duration {
+        STARTUP_BACKOFF
+            .saturating_mul(1 << self.count.saturating_sub(1).min(16))
+            .min(MAX_STARTUP_BACKOFF)
     }
 }
 
@@ -529,11 +1258,12 @@ impl RunningNodeJsPoolProcess {
 ///
 /// The pool will spawn processes when needed and reuses old ones. It will never
 /// spawn more then a certain number of concurrent processes. This is specified
-/// with the `concurrency` argument in the constructor.
+/// with the `options` argument in the constructor.
 ///
 /// The worker will *not* use the env of the parent process by default. All env
 /// vars need to be provided to make the execution as pure as possible.
 #[turbo_tasks::value(into = "new", cell = "new", serialization = "none", eq = "manual")]
+#[derive(Clone)]
 pub struct NodeJsPool {
     cwd: PathBuf,
     entrypoint: PathBuf,
@@ -549,22 +1279,65 @@ pub struct NodeJsPool {
     shared_stdout: SharedOutputSet,
     #[turbo_tasks(trace_ignore, debug_ignore)]
     shared_stderr: SharedOutputSet,
-    debug: bool,
+    #[turbo_tasks(trace_ignore, debug_ignore)]
+    hot_updates: Arc<Mutex<HotUpdates>>,
+    #[turbo_tasks(trace_ignore, debug_ignore)]
+    startup_failures: Arc<Mutex<StartupFailures>>,
+    #[turbo_tasks(trace_ignore, debug_ignore)]
+    idle_reaper: IdleReaper,
+    #[turbo_tasks(trace_ignore, debug_ignore)]
+    shutdown: PoolShutdown,
+    queueing: NodeJsPoolQueueing,
+    recycling: NodeJsPoolRecycling,
+    resource_limits: NodeJsPoolResourceLimits,
+    #[turbo_tasks(trace_ignore, debug_ignore)]
+    executor: Arc<dyn JsRuntimeExecutor>,
+    node_args: Vec<String>,
+    inspect_port: Option<u16>,
 }
 
 impl NodeJsPool {
     /// * debug: Whether to automatically enable Node's `--inspect-brk` when
-    ///   spawning it. Note: automatically overrides concurrency to 1.
+    ///   spawning it, on the [NodeJsPoolOptions::inspect_port] or port 9229.
+    ///   Note: automatically overrides the number of processes to 1.
     pub(super) fn new(
         cwd: PathBuf,
         entrypoint: PathBuf,
-        env: HashMap<String, String>,
+        mut env: HashMap<String, String>,
         assets_for_source_mapping: AssetsForSourceMappingVc,
         assets_root: FileSystemPathVc,
         project_dir: FileSystemPathVc,
-        concurrency: usize,
+        options: &NodeJsPoolOptions,
         debug: bool,
     ) -> Self {
+        if let Some(node_options) = &options.node_options {
+            let env_options = env.entry("NODE_OPTIONS".to_string()).or_default();
+            if !env_options.is_empty() {
+                env_options.push(' ');
+            }
+            env_options.push_str(node_options);
+        }
+        let inspect_port = options
+            .inspect_port
+            .or_else(|| debug.then_some(DEFAULT_INSPECT_PORT));
+        let node_args = options
+            .resource_limits
+            .max_memory_mb
+            .and_then(|max_memory_mb| options.runtime.heap_limit_arg(max_memory_mb))
+            .into_iter()
+            .chain(options.node_args.iter().cloned())
+            .collect();
+        let processes = Arc::new(Mutex::new(Vec::new()));
+        // A pool created after the shutdown is shut down right away.
+        let shutdown = Arc::new(Mutex::new(*SHUTDOWN.lock().unwrap()));
+        {
+            let mut pools = POOLS.lock().unwrap();
+            pools.retain(|pool| pool.processes.strong_count() > 0);
+            pools.push(RegisteredPool {
+                processes: Arc::downgrade(&processes),
+                shutdown: shutdown.clone(),
+            });
+        }
         Self {
             cwd,
             entrypoint,
@@ -572,59 +1345,274 @@ impl NodeJsPool {
             assets_for_source_mapping,
             assets_root,
             project_dir,
-            processes: Arc::new(Mutex::new(Vec::new())),
-            semaphore: Arc::new(Semaphore::new(if debug { 1 } else { concurrency })),
+            idle_reaper: IdleReaper::new(&processes, *IDLE_TIMEOUT),
+            shutdown,
+            processes,
+            semaphore: Arc::new(Semaphore::new(if inspect_port.is_some() {
+                1
+            } else {
+                options.max_processes()
+            })),
             shared_stdout: Arc::new(Mutex::new(IndexSet::new())),
             shared_stderr: Arc::new(Mutex::new(IndexSet::new())),
-            debug,
+            hot_updates: Arc::new(Mutex::new(HotUpdates::default())),
+            startup_failures: Arc::new(Mutex::new(StartupFailures::default())),
+            queueing: options.queueing,
+            recycling: options.recycling,
+            resource_limits: options.resource_limits,
+            executor: options.runtime.executor(options.runtime_binary.as_deref()),
+            node_args,
+            inspect_port,
+        }
+    }
+
+    /// Records that the code of the processes has changed to `version`.
+    /// Processes are spawned with the latest code, and idle processes apply
+    /// the `update` before they are reused. When there is no `update` (i.e.
+    /// the change can't be applied in place), idle processes are replaced
+    /// instead.
+    ///
+    /// The new code might fix processes failing to start, so the next
+    /// operation starts a process right away.
+    pub(super) fn push_hot_update(&self, version: TraitRef<VersionVc>, update: Option<HotUpdate>) {
+        let mut hot_updates = self.hot_updates.lock().unwrap();
+        if hot_updates.version.replace(version).is_some() {
+            hot_updates.updates.push(update);
+            self.startup_failures.lock().unwrap().retry_at = None;
+        }
+    }
+
+    /// The version of the code the latest processes are running, as recorded
+    /// with [NodeJsPool::push_hot_update].
+    pub(super) fn hot_version(&self) -> Option<TraitRef<VersionVc>> {
+        self.hot_updates.lock().unwrap().version.clone()
+    }
+
+    fn generation(&self) -> usize {
+        self.hot_updates.lock().unwrap().updates.len()
+    }
+
+    /// Brings an idle process up to date with the latest generation. Returns
+    /// `false` when that's not possible and the process needs to be replaced.
+    async fn apply_hot_updates(&self, process: &mut RunningNodeJsPoolProcess) -> Result<bool> {
+        let updates = self.hot_updates.lock().unwrap().updates[process.generation..].to_vec();
+        for update in updates {
+            let Some(update) = update else {
+                return Ok(false);
+            };
+            let message = serde_json::to_vec(&HotUpdateMessage::HotUpdate(&update))?;
+            timeout(Duration::from_secs(30), process.send(message))
+                .await
+                .context("timeout while sending hot update")??;
+            let (response, _) = timeout(Duration::from_secs(30), process.recv())
+                .await
+                .context("timeout while applying hot update")??;
+            match serde_json::from_slice(&response)? {
+                HotUpdateResponse::HotUpdateApplied => process.generation += 1,
+                HotUpdateResponse::HotUpdateFailed(error) => {
+                    let message = error
+                        .print(
+                            self.assets_for_source_mapping,
+                            self.assets_root,
+                            self.project_dir,
+                            FormattingMode::AnsiColors,
+                        )
+                        .await?;
+                    println!("unable to apply hot update, restarting Node.js process\n{message}");
+                    return Ok(false);
+                }
+            }
         }
+        Ok(true)
     }
 
     async fn acquire_process(&self) -> Result<(NodeJsPoolProcess, OwnedSemaphorePermit)> {
-        let permit = self.semaphore.clone().acquire_owned().await?;
+        if self.shutdown.lock().unwrap().is_some() {
+            bail!("the Node.js pool is shutting down");
+        }
+        let permit = match self.queueing {
+            NodeJsPoolQueueing::Wait => self.semaphore.clone().acquire_owned().await?,
+            NodeJsPoolQueueing::Reject => self
+                .semaphore
+                .clone()
+                .try_acquire_owned()
+                .map_err(|_| NodeJsPoolBusyError)?,
+        };
 
-        let popped = {
-            let mut processes = self.processes.lock().unwrap();
-            processes.pop()
+        let generation = self.generation();
+        let popped = loop {
+            let popped = {
+                let mut processes = self.processes.lock().unwrap();
+                processes.pop()
+            };
+            let Some(NodeJsPoolProcess::Running(mut process)) = popped else {
+                break popped;
+            };
+            // Retired processes are dropped (and killed), and so are outdated
+            // processes when they can't be updated.
+            let reusable = if self.recycling.should_retire(&process).await {
+                false
+            } else if process.generation < generation {
+                matches!(self.apply_hot_updates(&mut process).await, Ok(true))
+            } else {
+                true
+            };
+            if reusable {
+                break Some(NodeJsPoolProcess::Running(process));
+            }
+            // The new process needs the port of the inspector.
+            if let (Some(_), Some(child)) = (self.inspect_port, process.child.as_mut()) {
+                let _ = child.kill().await;
+            }
         };
         let process = match popped {
             Some(process) => process,
-            None => NodeJsPoolProcess::new(
+            None => NodeJsPoolProcess::Running(self.start_process(generation).await?),
+        };
+        Ok((process, permit))
+    }
+
+    /// Starts a new process, unless processes failed to start recently. After
+    /// a failure, only a single operation retries once the backoff has
+    /// passed, while the others keep failing with [NodeJsPoolStartupError].
+    async fn start_process(&self, generation: usize) -> Result<RunningNodeJsPoolProcess> {
+        {
+            let mut failures = self.startup_failures.lock().unwrap();
+            if let Some(retry_at) = failures.retry_at {
+                let now = Instant::now();
+                if now < retry_at {
+                    return Err(NodeJsPoolStartupError {
+                        failures: failures.count,
+                        error: failures.last_error.clone(),
+                        retry_in: retry_at - now,
+                    }
+                    .into());
+                }
+                // Other operations keep failing fast until this retry is done.
+                failures.retry_at = Some(now + failures.backoff());
+            }
+        }
+
+        let result = async {
+            // Limits how many processes start up at the same time across all pools.
+            // The process is connected before the permit is released, since that's
+            // when its startup is done.
+            let _spawn_permit = ConcurrencyClass::ProcessSpawn.acquire().await;
+            let process = NodeJsPoolProcess::new(
                 self.cwd.as_path(),
                 &self.env,
+                &*self.executor,
+                &self.node_args,
                 self.entrypoint.as_path(),
                 self.assets_for_source_mapping,
                 self.assets_root,
                 self.project_dir,
                 self.shared_stdout.clone(),
                 self.shared_stderr.clone(),
-                self.debug,
+                self.inspect_port,
+                self.resource_limits,
+                generation,
             )
             .await
-            .context("creating new process")?,
-        };
-        Ok((process, permit))
-    }
+            .context("creating new process")?;
+            process.run().await
+        }
+        .await;
 
-    pub async fn operation(&self) -> Result<NodeJsOperation> {
-        let (process, permit) = self.acquire_process().await?;
+        let mut failures = self.startup_failures.lock().unwrap();
+        match &result {
+            Ok(_) => *failures = StartupFailures::default(),
+            Err(err) => {
+                failures.count += 1;
+                failures.last_error = PrettyPrintError(err).to_string();
+                failures.retry_at = Some(Instant::now() + failures.backoff());
+            }
+        }
+        result
+    }
+
+    pub async fn operation(&self) -> Result<NodeJsOperation> {
+        let start = Instant::now();
+        let (process, permit) = self.acquire_process().await?;
+        let mut process = process.run().await?;
+        process.operations += 1;
+        // The prefix of a previous operation doesn't apply anymore.
+        process.set_output_prefix(None);
+        process.set_capture_output(false);
+        let cpu_time_start = match (
+            self.resource_limits.max_cpu_time,
+            process.child.as_ref().and_then(|child| child.id()),
+        ) {
+            (Some(_), Some(pid)) => cpu_time(pid).await,
+            _ => None,
+        };
 
         Ok(NodeJsOperation {
-            process: Some(process.run().await?),
+            process: Some(process),
             permit,
             processes: self.processes.clone(),
+            idle_reaper: self.idle_reaper.clone(),
+            shutdown: self.shutdown.clone(),
             allow_process_reuse: true,
+            deadline: None,
+            inspected: self.inspect_port.is_some(),
+            resource_limits: self.resource_limits,
+            cpu_time_start,
+            timings: OperationTimings {
+                queue: start.elapsed(),
+                ..Default::default()
+            },
+            #[cfg(feature = "chaos")]
+            chaos: Chaos::current(),
         })
     }
 }
 
+/// The output of a process during an operation, one entry per `console` call
+/// or line.
+#[derive(Debug, Default)]
+pub struct CapturedOutput {
+    pub stdout: Vec<String>,
+    pub stderr: Vec<String>,
+}
+
+/// Where the time of a [NodeJsOperation] went. Messages are measured on this
+/// side of the connection, so the time the process needs to (de)serialize them
+/// counts as execution.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub struct OperationTimings {
+    /// Waiting for a process of the pool to become available, including
+    /// starting it.
+    pub queue: Duration,
+    /// Transferring messages to and from the process.
+    pub ipc: Duration,
+    /// Waiting for the process to respond.
+    pub execution: Duration,
+    /// Serializing and deserializing messages.
+    pub serialization: Duration,
+}
+
 pub struct NodeJsOperation {
     process: Option<RunningNodeJsPoolProcess>,
     // This is used for drop
     #[allow(dead_code)]
     permit: OwnedSemaphorePermit,
     processes: Arc<Mutex<Vec<NodeJsPoolProcess>>>,
+    idle_reaper: IdleReaper,
+    shutdown: PoolShutdown,
     allow_process_reuse: bool,
+    /// When set, receiving messages fails once this point in time is reached.
+    deadline: Option<Instant>,
+    /// Whether a debugger may be attached to the process, which can pause it
+    /// for any amount of time, so messages don't time out.
+    inspected: bool,
+    resource_limits: NodeJsPoolResourceLimits,
+    /// The CPU time the process had used when the operation started.
+    cpu_time_start: Option<Duration>,
+    timings: OperationTimings,
+    /// Faults injected into the messages of this operation.
+    #[cfg(feature = "chaos")]
+    chaos: Option<Arc<Chaos>>,
 }
 
 impl NodeJsOperation {
@@ -648,34 +1636,97 @@ impl NodeJsOperation {
         result
     }
 
+    /// Limits the time all following messages may take to be received to
+    /// `duration`, instead of limiting each message on its own. Once it has
+    /// passed, [NodeJsOperation::recv] fails with a timeout error.
+    pub fn set_timeout(&mut self, duration: Duration) {
+        self.deadline = Some(Instant::now() + duration);
+    }
+
     pub async fn recv<M>(&mut self) -> Result<M>
     where
         M: DeserializeOwned,
     {
-        let message = self
+        let recv_timeout = match self.deadline {
+            _ if self.inspected => Duration::MAX,
+            Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+            None => Duration::from_secs(30),
+        };
+        #[cfg(feature = "chaos")]
+        let chaos = self.chaos.clone();
+        let resource_limits = self.resource_limits;
+        let cpu_time_start = self.cpu_time_start.filter(|_| !self.inspected);
+        let start = Instant::now();
+        let (message, transfer) = self
             .with_process(|process| async move {
-                timeout(Duration::from_secs(30), process.recv())
-                    .await
+                #[cfg(feature = "chaos")]
+                if let Some(chaos) = &chaos {
+                    chaos.before_message(process.child.as_mut()).await;
+                }
+                let pid = process.child.as_ref().and_then(|child| child.id());
+                let received = select! {
+                    received = timeout(recv_timeout, process.recv()) => received,
+                    exceeded = resource_limits.watch_cpu_time(pid, cpu_time_start) => {
+                        return Err(exceeded.into());
+                    }
+                };
+                if matches!(received, Ok(Err(_))) {
+                    if let Some(exceeded) =
+                        resource_limits.out_of_memory(process.child.as_mut()).await
+                    {
+                        return Err(exceeded.into());
+                    }
+                }
+                #[allow(unused_mut)]
+                let (mut message, transfer) = received
                     .context("timeout while receiving message from process")?
-                    .context("failed to receive message")
+                    .context("failed to receive message")?;
+                #[cfg(feature = "chaos")]
+                if let Some(chaos) = &chaos {
+                    chaos.after_recv(&mut message);
+                }
+                Ok((message, transfer))
             })
             .await?;
-        serde_json::from_slice(&message).context("failed to deserialize message")
+        // Until the message starts to arrive, the process is busy with it.
+        self.timings.execution += start.elapsed().saturating_sub(transfer);
+        self.timings.ipc += transfer;
+        let start = Instant::now();
+        let message = serde_json::from_slice(&message).context("failed to deserialize message");
+        self.timings.serialization += start.elapsed();
+        message
     }
 
     pub async fn send<M>(&mut self, message: M) -> Result<()>
     where
         M: Serialize,
     {
+        let start = Instant::now();
         let message = serde_json::to_vec(&message).context("failed to serialize message")?;
-        self.with_process(|process| async move {
-            timeout(Duration::from_secs(30), process.send(message))
-                .await
-                .context("timeout while sending message")?
-                .context("failed to send message")?;
-            Ok(())
-        })
-        .await
+        self.timings.serialization += start.elapsed();
+        #[cfg(feature = "chaos")]
+        let chaos = self.chaos.clone();
+        let start = Instant::now();
+        let result = self
+            .with_process(|process| async move {
+                #[cfg(feature = "chaos")]
+                if let Some(chaos) = &chaos {
+                    chaos.before_message(process.child.as_mut()).await;
+                }
+                timeout(Duration::from_secs(30), process.send(message))
+                    .await
+                    .context("timeout while sending message")?
+                    .context("failed to send message")?;
+                Ok(())
+            })
+            .await;
+        self.timings.ipc += start.elapsed();
+        result
+    }
+
+    /// Where the time of this operation went so far.
+    pub fn timings(&self) -> OperationTimings {
+        self.timings
     }
 
     pub async fn wait_or_kill(mut self) -> Result<ExitStatus> {
@@ -699,6 +1750,94 @@ impl NodeJsOperation {
         Ok(status)
     }
 
+    /// Sends the `outgoing` messages to the process while receiving its
+    /// messages, so neither side waits for the other, e.g. to relay a
+    /// WebSocket connection. Every received message is passed to `incoming`,
+    /// and the relay ends once it returns `false`. Messages don't time out,
+    /// since either side may be idle for any amount of time.
+    pub async fn relay<I, O>(
+        &mut self,
+        outgoing: &mut (impl Stream<Item = O> + Unpin + Send),
+        mut incoming: impl FnMut(I) -> bool + Send,
+    ) -> Result<()>
+    where
+        I: DeserializeOwned,
+        O: Serialize + Send,
+    {
+        self.with_process(|process| async move {
+            let RunningNodeJsPoolProcess {
+                connection,
+                stdout_handler,
+                stderr_handler,
+                ..
+            } = process;
+            let (reader, mut writer) = connection.split();
+            // Receiving a packet can't be cancelled halfway, so it's continued
+            // by the stream after a message has been sent.
+            let mut packets = Box::pin(stream::unfold(
+                (reader, stdout_handler, stderr_handler),
+                |(mut reader, stdout_handler, stderr_handler)| async move {
+                    let packet = recv_packet(&mut reader, stdout_handler, stderr_handler)
+                        .await
+                        .map(|(packet, _)| packet);
+                    Some((packet, (reader, stdout_handler, stderr_handler)))
+                },
+            ));
+            let mut outgoing_done = false;
+            loop {
+                select! {
+                    Some(packet) = packets.next() => {
+                        let message = serde_json::from_slice(&packet?)
+                            .context("failed to deserialize message")?;
+                        if !incoming(message) {
+                            return Ok(());
+                        }
+                    }
+                    message = outgoing.next(), if !outgoing_done => match message {
+                        Some(message) => {
+                            let message = serde_json::to_vec(&message)
+                                .context("failed to serialize message")?;
+                            send_packet(&mut writer, &message).await?;
+                        }
+                        None => outgoing_done = true,
+                    },
+                }
+            }
+        })
+        .await
+    }
+
+    /// Prepends `prefix` to every line the process prints until the operation
+    /// is done, e.g. to tell which request caused it.
+    pub fn set_output_prefix(&mut self, prefix: String) {
+        if let Some(process) = self.process.as_mut() {
+            process.set_output_prefix(Some(prefix));
+        }
+    }
+
+    /// Keeps the output the process prints from now on, in addition to
+    /// printing it, so it can be taken with [NodeJsOperation::take_output].
+    pub fn capture_output(&mut self) {
+        if let Some(process) = self.process.as_mut() {
+            process.set_capture_output(true);
+        }
+    }
+
+    /// Stops capturing the output and returns the output captured since
+    /// [NodeJsOperation::capture_output]. Stack traces in it are source
+    /// mapped.
+    pub fn take_output(&mut self) -> CapturedOutput {
+        let Some(process) = self.process.as_mut() else {
+            return CapturedOutput::default();
+        };
+        let take_captured =
+            |captured: &mut Option<Vec<String>>| captured.take().unwrap_or_default();
+        CapturedOutput {
+            stdout: take_captured(&mut process.stdout_handler.captured),
+            stderr: take_captured(&mut process.stderr_handler.captured),
+        }
+    }
+
     pub fn disallow_reuse(&mut self) {
         self.allow_process_reuse = false;
     }
@@ -716,15 +1855,962 @@ impl NodeJsOperation {
     }
 }
 
+/// Drops (and kills) the processes which have been idle for `idle_timeout`.
+/// Returns when the next of the remaining processes will have been idle for
+/// that long, if there are any.
+fn evict_idle_processes(
+    processes: &Mutex<Vec<NodeJsPoolProcess>>,
+    idle_timeout: Duration,
+) -> Option<Instant> {
+    let mut processes = processes.lock().unwrap();
+    processes.retain(|process| match process {
+        NodeJsPoolProcess::Running(process) => process.idle_since.elapsed() < idle_timeout,
+        NodeJsPoolProcess::Spawned(_) => true,
+    });
+    processes
+        .iter()
+        .filter_map(|process| match process {
+            NodeJsPoolProcess::Running(process) => Some(process.idle_since + idle_timeout),
+            NodeJsPoolProcess::Spawned(_) => None,
+        })
+        .min()
+}
+
+/// Lets the idle processes of a pool exit once they have been idle for the
+/// idle timeout. A single task per pool evicts them, which only runs while
+/// the pool has idle processes.
+#[derive(Clone)]
+struct IdleReaper {
+    processes: Weak<Mutex<Vec<NodeJsPoolProcess>>>,
+    /// Whether the task is running.
+    running: Arc<AtomicBool>,
+    /// Idle processes are kept forever without a timeout.
+    idle_timeout: Option<Duration>,
+}
+
+impl IdleReaper {
+    fn new(processes: &Arc<Mutex<Vec<NodeJsPoolProcess>>>, idle_timeout: Option<Duration>) -> Self {
+        IdleReaper {
+            processes: Arc::downgrade(processes),
+            running: Arc::new(AtomicBool::new(false)),
+            idle_timeout,
+        }
+    }
+
+    /// Starts the task, unless it's already running. Called after a process
+    /// has been returned to the pool.
+    fn start(&self) {
+        let (Some(idle_timeout), Ok(handle)) =
+            (self.idle_timeout, tokio::runtime::Handle::try_current()) else {
+            return;
+        };
+        if self.running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        handle.spawn(self.clone().run(idle_timeout));
+    }
+
+    async fn run(self, idle_timeout: Duration) {
+        loop {
+            let Some(processes) = self.processes.upgrade() else {
+                // The pool has been dropped, and its processes with it.
+                return;
+            };
+            let next_eviction = evict_idle_processes(&processes, idle_timeout);
+            drop(processes);
+            if let Some(next_eviction) = next_eviction {
+                sleep_until(next_eviction.into()).await;
+                continue;
+            }
+            self.running.store(false, Ordering::SeqCst);
+            // A process might have been returned after the eviction, while the
+            // task was still marked as running, so it wasn't started again.
+            let has_idle_processes = self.processes.upgrade().map_or(false, |processes| {
+                processes
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .any(|process| matches!(process, NodeJsPoolProcess::Running(_)))
+            });
+            if !has_idle_processes || self.running.swap(true, Ordering::SeqCst) {
+                return;
+            }
+        }
+    }
+}
+
+/// Set once a pool is shut down. It's shared with the operations of the pool,
+/// so operations which are still running shut down their process when they
+/// are done.
+type PoolShutdown = Arc<Mutex<Option<Shutdown>>>;
+
+/// A pool in [POOLS].
+struct RegisteredPool {
+    processes: Weak<Mutex<Vec<NodeJsPoolProcess>>>,
+    shutdown: PoolShutdown,
+}
+
+/// All pools, so they can be shut down together.
+static POOLS: Lazy<Mutex<Vec<RegisteredPool>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Set once all pools are shut down.
+static SHUTDOWN: Mutex<Option<Shutdown>> = Mutex::new(None);
+
+#[derive(Clone, Copy)]
+struct Shutdown {
+    signal: ShutdownSignal,
+    grace_period: Duration,
+}
+
+#[derive(Clone, Copy)]
+enum ShutdownSignal {
+    /// `SIGINT`, when Ctrl-C is forwarded.
+    Interrupt,
+    /// `SIGTERM`.
+    Terminate,
+}
+
+impl ShutdownSignal {
+    /// Sends the signal to the `child`. Windows has no signals, but the
+    /// process exits on its own once its connection is closed.
+    fn send(self, child: &Child) {
+        #[cfg(unix)]
+        if let Some(pid) = child.id() {
+            let signal = match self {
+                ShutdownSignal::Interrupt => libc::SIGINT,
+                ShutdownSignal::Terminate => libc::SIGTERM,
+            };
+            // SAFETY: `kill` has no memory safety requirements. The child hasn't
+            // been waited for, so the pid still belongs to it.
+            unsafe {
+                libc::kill(pid as libc::pid_t, signal);
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = child;
+    }
+}
+
+impl RunningNodeJsPoolProcess {
+    /// Asks the process to exit with the `signal` and closes its connection.
+    /// The process is killed when it hasn't exited after the `grace_period`.
+    /// Its output is printed until then.
+    async fn shutdown(
+        self,
+        Shutdown {
+            signal,
+            grace_period,
+        }: Shutdown,
+    ) {
+        let RunningNodeJsPoolProcess {
+            child,
+            connection,
+            mut stdout_handler,
+            mut stderr_handler,
+            ..
+        } = self;
+        let Some(mut child) = child else {
+            return;
+        };
+        signal.send(&child);
+        drop(connection);
+        // The handlers fail once the process closed its output.
+        let output = async {
+            join!(
+                async { while stdout_handler.handle_operation().await.is_ok() {} },
+                async { while stderr_handler.handle_operation().await.is_ok() {} },
+            )
+        };
+        let exited = timeout(grace_period, async { join!(output, child.wait()) }).await;
+        if exited.is_err() {
+            let _ = child.kill().await;
+        }
+    }
+}
+
+/// Shuts down the processes of all pools. Every process is asked to exit with
+/// `SIGTERM`, so it can run its `beforeExit` hooks and close its sockets, and
+/// is killed when it hasn't exited after the `grace_period`. Operations fail
+/// from now on, and processes of operations which are still running are shut
+/// down once their operation is done.
+pub async fn shutdown_node_js_pools(grace_period: Duration) {
+    shutdown_pools(Shutdown {
+        signal: ShutdownSignal::Terminate,
+        grace_period,
+    })
+    .await
+}
+
+/// Waits for Ctrl-C and forwards it to the processes of all pools as `SIGINT`,
+/// then shuts them down like [shutdown_node_js_pools]. The caller exits once
+/// this returns, e.g. by selecting it together with the server.
+pub async fn shutdown_node_js_pools_on_ctrl_c(grace_period: Duration) -> Result<()> {
+    tokio::signal::ctrl_c()
+        .await
+        .context("listening for Ctrl-C")?;
+    shutdown_pools(Shutdown {
+        signal: ShutdownSignal::Interrupt,
+        grace_period,
+    })
+    .await;
+    Ok(())
+}
+
+async fn shutdown_pools(shutdown: Shutdown) {
+    *SHUTDOWN.lock().unwrap() = Some(shutdown);
+    let pools = POOLS
+        .lock()
+        .unwrap()
+        .drain(..)
+        .filter_map(|pool| Some((pool.processes.upgrade()?, pool.shutdown)))
+        .collect::<Vec<_>>();
+    join_all(
+        pools
+            .iter()
+            .map(|(processes, pool_shutdown)| shutdown_pool(processes, pool_shutdown, shutdown)),
+    )
+    .await;
+}
+
+/// Shuts down the idle `processes` of a pool, and marks it as shut down.
+async fn shutdown_pool(
+    processes: &Mutex<Vec<NodeJsPoolProcess>>,
+    pool_shutdown: &Mutex<Option<Shutdown>>,
+    shutdown: Shutdown,
+) {
+    *pool_shutdown.lock().unwrap() = Some(shutdown);
+    let processes = take(&mut *processes.lock().unwrap())
+        .into_iter()
+        .filter_map(|process| match process {
+            NodeJsPoolProcess::Running(process) => Some(process),
+            // Processes which haven't connected yet are killed when dropped.
+            NodeJsPoolProcess::Spawned(_) => None,
+        })
+        .collect::<Vec<_>>();
+    join_all(
+        processes
+            .into_iter()
+            .map(|process| process.shutdown(shutdown)),
+    )
+    .await;
+}
+
 impl Drop for NodeJsOperation {
     fn drop(&mut self) {
+        let shutdown = *self.shutdown.lock().unwrap();
+        if let Some(shutdown) = shutdown {
+            // Without a runtime, the process is killed when it's dropped.
+            if let (Some(process), Ok(handle)) =
+                (self.process.take(), tokio::runtime::Handle::try_current())
+            {
+                handle.spawn(process.shutdown(shutdown));
+            }
+            return;
+        }
         if self.allow_process_reuse {
-            if let Some(process) = self.process.take() {
+            if let Some(mut process) = self.process.take() {
+                process.idle_since = Instant::now();
                 self.processes
                     .lock()
                     .unwrap()
                     .push(NodeJsPoolProcess::Running(process));
+
+                // The process pauses its own work while it's idle (see
+                // `ipc/evaluate.ts`), so the only thing left to do is to let it
+                // exit eventually. A new process is spawned on the next operation.
+                self.idle_reaper.start();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::{
+        collections::HashMap,
+        fs,
+        path::Path,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use anyhow::Result;
+    use serde::Deserialize;
+    use serde_json::json;
+    use tempfile::TempDir;
+    use tokio::{
+        process::Command,
+        time::{sleep, timeout},
+    };
+    use turbo_tasks::TurboTasks;
+    use turbo_tasks_fs::{FileSystem, NullFileSystem, NullFileSystemVc};
+    use turbo_tasks_memory::MemoryBackend;
+
+    use super::{
+        node_compatible_command, IdleReaper, JsRuntimeExecutor, NodeJsPool, NodeJsPoolBusyError,
+        NodeJsPoolOptions, NodeJsPoolQueueing, NodeJsPoolRecycling, NodeJsPoolStartupError,
+        STARTUP_BACKOFF,
+    };
+    use crate::AssetsForSourceMappingVc;
+
+    /// The protocol of the pool, for the scripts of test workers. A script
+    /// defines `handle(message)`, which is called with every message the
+    /// worker receives, and answers with `send(message)`.
+    const PROTOCOL: &str = r#"
+const net = require("net");
+
+const socket = net.createConnection(Number(process.argv[2]), "127.0.0.1");
+let buffer = Buffer.alloc(0);
+
+function send(message) {
+  // Ends the output of the operation.
+  process.stdout.write("TURBOPACK_OUTPUT_D\n");
+  process.stderr.write("TURBOPACK_OUTPUT_D\n");
+  const data = Buffer.from(JSON.stringify(message));
+  const length = Buffer.alloc(4);
+  length.writeUInt32BE(data.length);
+  socket.write(Buffer.concat([length, data]));
+}
+
+socket.on("data", (chunk) => {
+  buffer = Buffer.concat([buffer, chunk]);
+  while (buffer.length >= 4 && buffer.length >= 4 + buffer.readUInt32BE(0)) {
+    const length = buffer.readUInt32BE(0);
+    const message = JSON.parse(buffer.subarray(4, 4 + length).toString());
+    buffer = buffer.subarray(4 + length);
+    handle(message);
+  }
+});
+"#;
+
+    /// A worker which answers `pid` messages with its process id and never
+    /// answers other messages, e.g. `hang`.
+    const WORKER: &str = r#"
+function handle(message) {
+  if (message.type === "pid") {
+    send({ pid: process.pid });
+  }
+}
+"#;
+
+    #[derive(Deserialize)]
+    struct Pid {
+        pid: u32,
+    }
+
+    /// A pool of workers running the `script` (see [PROTOCOL]) with the
+    /// `options`. Must be called within turbo tasks.
+    pub(crate) fn script_pool(
+        script: &str,
+        options: &NodeJsPoolOptions,
+    ) -> Result<(NodeJsPool, TempDir)> {
+        let dir = tempfile::tempdir()?;
+        let entrypoint = dir.path().join("worker.js");
+        fs::write(&entrypoint, format!("{PROTOCOL}{script}"))?;
+        let fs: NullFileSystemVc = NullFileSystem.into();
+        let pool = NodeJsPool::new(
+            dir.path().to_path_buf(),
+            entrypoint,
+            HashMap::new(),
+            AssetsForSourceMappingVc::cell(HashMap::new()),
+            fs.root(),
+            fs.root(),
+            options,
+            false,
+        );
+        Ok((pool, dir))
+    }
+
+    /// A pool of [WORKER] processes, whose idle processes exit after
+    /// `idle_timeout`. Must be called within turbo tasks.
+    pub(crate) fn worker_pool(idle_timeout: Option<Duration>) -> Result<(NodeJsPool, TempDir)> {
+        let (mut pool, dir) = script_pool(WORKER, &NodeJsPoolOptions::default())?;
+        pool.idle_reaper = IdleReaper::new(&pool.processes, idle_timeout);
+        Ok((pool, dir))
+    }
+
+    /// The process id of the process an operation of the `pool` runs in.
+    async fn pid(pool: &NodeJsPool) -> Result<u32> {
+        let mut operation = pool.operation().await?;
+        operation.send(json!({ "type": "pid" })).await?;
+        Ok(operation.recv::<Pid>().await?.pid)
+    }
+
+    pub(crate) fn idle_processes(pool: &NodeJsPool) -> usize {
+        pool.processes.lock().unwrap().len()
+    }
+
+    #[tokio::test]
+    async fn reuses_idle_processes() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let (pool, _dir) = worker_pool(None)?;
+            let first = pid(&pool).await?;
+            assert_eq!(idle_processes(&pool), 1);
+            assert_eq!(pid(&pool).await?, first);
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn evicts_processes_after_idle_timeout() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let (pool, _dir) = worker_pool(Some(Duration::from_millis(500)))?;
+            let first = pid(&pool).await?;
+            assert_eq!(idle_processes(&pool), 1);
+            assert!(pool.idle_reaper.running.load(Ordering::SeqCst));
+
+            sleep(Duration::from_millis(1000)).await;
+            assert_eq!(idle_processes(&pool), 0);
+            // The reaper stops once there are no idle processes left.
+            assert!(!pool.idle_reaper.running.load(Ordering::SeqCst));
+
+            // A new process is started for the next operation.
+            assert_ne!(pid(&pool).await?, first);
+            assert!(pool.idle_reaper.running.load(Ordering::SeqCst));
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn idle_timeout_restarts_when_a_process_is_reused() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let (pool, _dir) = worker_pool(Some(Duration::from_millis(1000)))?;
+            let first = pid(&pool).await?;
+            // Each operation is within the timeout of the previous one, but the
+            // last one is not within the timeout of the first one.
+            for _ in 0..3 {
+                sleep(Duration::from_millis(600)).await;
+                assert_eq!(pid(&pool).await?, first);
             }
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    /// Runs an operation of the `pool` which exchanges a message with its
+    /// process, with the faults of the `chaos` config injected.
+    #[cfg(feature = "chaos")]
+    async fn chaotic_pid(pool: &NodeJsPool, chaos: &str) -> Result<u32> {
+        use std::sync::Arc;
+
+        use crate::chaos::{Chaos, ChaosConfig};
+
+        let mut operation = pool.operation().await?;
+        operation.chaos = Some(Arc::new(Chaos::new(ChaosConfig::parse(chaos)?)));
+        operation.send(json!({ "type": "pid" })).await?;
+        Ok(operation.recv::<Pid>().await?.pid)
+    }
+
+    #[cfg(feature = "chaos")]
+    #[tokio::test]
+    async fn chaos_crash_drops_the_process() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let (pool, _dir) = worker_pool(None)?;
+            let first = pid(&pool).await?;
+
+            let error = chaotic_pid(&pool, "kill=1").await.unwrap_err();
+            let error = format!("{error:#}");
+            assert!(
+                error.contains("failed to send message")
+                    || error.contains("failed to receive message"),
+                "{error}"
+            );
+            assert_eq!(idle_processes(&pool), 0);
+            assert_ne!(pid(&pool).await?, first);
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    #[cfg(feature = "chaos")]
+    #[tokio::test]
+    async fn chaos_truncated_message_keeps_the_process() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let (pool, _dir) = worker_pool(None)?;
+            let first = pid(&pool).await?;
+
+            let error = chaotic_pid(&pool, "corrupt=1").await.unwrap_err();
+            assert!(
+                format!("{error:#}").starts_with("failed to deserialize message"),
+                "{error:#}"
+            );
+            // The message has been received completely, so the connection is
+            // still in a usable state.
+            assert_eq!(idle_processes(&pool), 1);
+            assert_eq!(pid(&pool).await?, first);
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    #[cfg(feature = "chaos")]
+    #[tokio::test]
+    async fn chaos_delay_keeps_the_process() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let (pool, _dir) = worker_pool(None)?;
+            let first = pid(&pool).await?;
+
+            let start = std::time::Instant::now();
+            // Both the sent and the received message are delayed.
+            assert_eq!(chaotic_pid(&pool, "delay=1,delay_ms=200").await?, first);
+            assert!(start.elapsed() >= Duration::from_millis(400));
+            assert_eq!(idle_processes(&pool), 1);
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn waits_for_a_process_when_all_are_busy() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let options = NodeJsPoolOptions {
+                max_processes: Some(2),
+                ..Default::default()
+            };
+            let (pool, _dir) = script_pool(WORKER, &options)?;
+            let first = pool.operation().await?;
+            let second = pool.operation().await?;
+
+            // The third operation waits for the process of the first one.
+            let third = pid(&pool);
+            tokio::pin!(third);
+            assert!(
+                timeout(Duration::from_millis(500), &mut third)
+                    .await
+                    .is_err(),
+                "an operation started while all processes were busy"
+            );
+            drop(first);
+            timeout(Duration::from_secs(10), third).await??;
+            drop(second);
+            assert_eq!(idle_processes(&pool), 2);
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn rejects_operations_when_all_processes_are_busy() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let options = NodeJsPoolOptions {
+                max_processes: Some(1),
+                queueing: NodeJsPoolQueueing::Reject,
+                ..Default::default()
+            };
+            let (pool, _dir) = script_pool(WORKER, &options)?;
+            let operation = pool.operation().await?;
+            let Err(error) = pool.operation().await else {
+                panic!("an operation started while the process was busy");
+            };
+            assert!(error.is::<NodeJsPoolBusyError>(), "{error:#}");
+
+            drop(operation);
+            pid(&pool).await?;
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn retires_processes_after_max_operations() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let options = NodeJsPoolOptions {
+                recycling: NodeJsPoolRecycling {
+                    max_operations: Some(2),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let (pool, _dir) = script_pool(WORKER, &options)?;
+            let first = pid(&pool).await?;
+            assert_eq!(pid(&pool).await?, first);
+            // The process is only retired once the next operation needs one.
+            assert_eq!(idle_processes(&pool), 1);
+
+            let second = pid(&pool).await?;
+            assert_ne!(second, first);
+            assert_eq!(pid(&pool).await?, second);
+            assert_ne!(pid(&pool).await?, second);
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    /// Runs processes which exit right away, so they never connect to the
+    /// pool. Counts the processes it spawned.
+    #[derive(Default)]
+    struct FailingExecutor {
+        spawned: AtomicUsize,
+    }
+
+    impl JsRuntimeExecutor for FailingExecutor {
+        fn name(&self) -> &'static str {
+            "failing"
+        }
+
+        fn command(
+            &self,
+            _runtime_args: &[String],
+            _entrypoint: &Path,
+            _port: u16,
+            _inspect_port: Option<u16>,
+        ) -> Command {
+            self.spawned.fetch_add(1, Ordering::SeqCst);
+            let mut cmd = Command::new("node");
+            cmd.args(["-e", "process.exit(3)"]);
+            cmd
         }
     }
+
+    #[tokio::test]
+    async fn backs_off_when_processes_fail_to_start() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let (mut pool, _dir) = script_pool(WORKER, &NodeJsPoolOptions::default())?;
+            let executor = Arc::new(FailingExecutor::default());
+            pool.executor = executor.clone();
+
+            let error = pool.operation().await.unwrap_err();
+            assert!(
+                format!("{error:#}").contains("exited before we could connect"),
+                "{error:#}"
+            );
+            assert_eq!(executor.spawned.load(Ordering::SeqCst), 1);
+
+            // Operations fail right away during the backoff, without spawning.
+            let error = pool.operation().await.unwrap_err();
+            let error = error
+                .downcast_ref::<NodeJsPoolStartupError>()
+                .expect("the operation should fail with a startup error");
+            assert_eq!(error.failures, 1);
+            assert!(error.retry_in <= STARTUP_BACKOFF);
+            assert_eq!(executor.spawned.load(Ordering::SeqCst), 1);
+
+            // After the backoff, a process is started again.
+            sleep(STARTUP_BACKOFF).await;
+            pool.operation().await.unwrap_err();
+            assert_eq!(executor.spawned.load(Ordering::SeqCst), 2);
+
+            // The backoff doubles with every failure in a row.
+            let error = pool.operation().await.unwrap_err();
+            let error = error
+                .downcast_ref::<NodeJsPoolStartupError>()
+                .expect("the operation should fail with a startup error");
+            assert_eq!(error.failures, 2);
+            assert!(error.retry_in > STARTUP_BACKOFF);
+            assert_eq!(executor.spawned.load(Ordering::SeqCst), 2);
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    /// Runs the processes with `node`, and passes them an extra argument after
+    /// the port. Counts the processes it spawned.
+    #[derive(Default)]
+    struct CustomExecutor {
+        spawned: AtomicUsize,
+    }
+
+    impl JsRuntimeExecutor for CustomExecutor {
+        fn name(&self) -> &'static str {
+            "custom"
+        }
+
+        fn command(
+            &self,
+            runtime_args: &[String],
+            entrypoint: &Path,
+            port: u16,
+            inspect_port: Option<u16>,
+        ) -> Command {
+            self.spawned.fetch_add(1, Ordering::SeqCst);
+            let mut cmd =
+                node_compatible_command("node", runtime_args, entrypoint, port, inspect_port);
+            cmd.arg("spawned-by-custom-executor");
+            cmd
+        }
+    }
+
+    #[tokio::test]
+    async fn spawns_processes_with_a_custom_executor() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let script = r#"
+function handle(message) {
+  send({ args: process.argv.slice(3) });
+}
+"#;
+            let (mut pool, _dir) = script_pool(script, &NodeJsPoolOptions::default())?;
+            let executor = Arc::new(CustomExecutor::default());
+            pool.executor = executor.clone();
+
+            #[derive(Deserialize)]
+            struct Args {
+                args: Vec<String>,
+            }
+            for _ in 0..2 {
+                let mut operation = pool.operation().await?;
+                operation.send(json!({ "type": "args" })).await?;
+                let Args { args } = operation.recv().await?;
+                assert_eq!(args, ["spawned-by-custom-executor"]);
+            }
+            // The process is reused like with the default executor.
+            assert_eq!(executor.spawned.load(Ordering::SeqCst), 1);
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn retires_processes_above_max_rss() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let options = NodeJsPoolOptions {
+                recycling: NodeJsPoolRecycling {
+                    // Every process is above the limit.
+                    max_rss: Some(1),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let (pool, _dir) = script_pool(WORKER, &options)?;
+            let first = pid(&pool).await?;
+            assert_ne!(pid(&pool).await?, first);
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    /// Whether the process with the `pid` is still running (or waiting to be
+    /// reaped).
+    #[cfg(unix)]
+    fn is_running(pid: u32) -> bool {
+        // SAFETY: `kill` has no memory safety requirements, and signal 0 only
+        // checks whether the process exists.
+        unsafe { libc::kill(pid as libc::pid_t, 0) == 0 }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn shutdown_kills_processes_and_rejects_operations() {
+        use super::{shutdown_pool, Shutdown, ShutdownSignal};
+
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            // The worker ignores `SIGTERM` and keeps running after its
+            // connection has been closed, so it's only stopped by being killed.
+            let script = format!(
+                r#"{WORKER}
+process.on("SIGTERM", () => {{}});
+socket.on("error", () => {{}});
+setInterval(() => {{}}, 1000);
+"#
+            );
+            let (pool, _dir) = script_pool(&script, &NodeJsPoolOptions::default())?;
+            let mut running = pool.operation().await?;
+            running.send(json!({ "type": "pid" })).await?;
+            let running_pid = running.recv::<Pid>().await?.pid;
+            let idle_pid = pid(&pool).await?;
+            assert_ne!(idle_pid, running_pid);
+
+            let grace_period = Duration::from_millis(300);
+            let start = std::time::Instant::now();
+            shutdown_pool(
+                &pool.processes,
+                &pool.shutdown,
+                Shutdown {
+                    signal: ShutdownSignal::Terminate,
+                    grace_period,
+                },
+            )
+            .await;
+            assert!(start.elapsed() >= grace_period);
+            assert!(!is_running(idle_pid));
+            assert_eq!(idle_processes(&pool), 0);
+
+            let error = pool.operation().await.unwrap_err();
+            assert!(format!("{error:#}").contains("shutting down"), "{error:#}");
+
+            // The process of the running operation is shut down once it's done.
+            assert!(is_running(running_pid));
+            drop(running);
+            wait_for_exit(running_pid).await;
+            assert_eq!(idle_processes(&pool), 0);
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    /// A worker which busy-loops on `spin` messages and allocates memory until
+    /// it runs out of it on `allocate` messages.
+    #[cfg(target_os = "linux")]
+    const RUNAWAY_WORKER: &str = r#"
+function handle(message) {
+  switch (message.type) {
+    case "pid":
+      send({ pid: process.pid });
+      break;
+    case "spin":
+      while (true) {}
+    case "allocate": {
+      const arrays = [];
+      while (true) arrays.push(new Array(1e6).fill(1));
+    }
+  }
+}
+"#;
+
+    /// Sends the runaway `message` to a process of the `pool`. Returns the
+    /// error of the operation and the process id of its process.
+    #[cfg(target_os = "linux")]
+    async fn run_away(pool: &NodeJsPool, message: &str) -> Result<(anyhow::Error, u32)> {
+        let mut operation = pool.operation().await?;
+        operation.send(json!({ "type": "pid" })).await?;
+        let pid = operation.recv::<Pid>().await?.pid;
+        operation.send(json!({ "type": message })).await?;
+        let error = operation.recv::<Pid>().await.unwrap_err();
+        Ok((error, pid))
+    }
+
+    /// Waits until the process with the `pid` has exited.
+    #[cfg(unix)]
+    async fn wait_for_exit(pid: u32) {
+        let start = std::time::Instant::now();
+        while is_running(pid) {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "the process was not killed"
+            );
+            sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn kills_processes_exceeding_the_cpu_time_limit() {
+        use super::{NodeJsPoolResourceLimits, NodeJsResourceLimitError};
+
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let max_cpu_time = Duration::from_millis(300);
+            let options = NodeJsPoolOptions {
+                resource_limits: NodeJsPoolResourceLimits {
+                    max_cpu_time: Some(max_cpu_time),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let (pool, _dir) = script_pool(RUNAWAY_WORKER, &options)?;
+            let start = std::time::Instant::now();
+            let (error, runaway_pid) = run_away(&pool, "spin").await?;
+            assert!(
+                matches!(
+                    error.downcast_ref::<NodeJsResourceLimitError>(),
+                    Some(NodeJsResourceLimitError::CpuTime { .. })
+                ),
+                "{error:#}"
+            );
+            assert!(format!("{error}").starts_with("render exceeded resource limits"));
+            // The operation fails long before it would time out.
+            assert!(start.elapsed() < Duration::from_secs(10));
+
+            wait_for_exit(runaway_pid).await;
+            assert_eq!(idle_processes(&pool), 0);
+            // The next operation starts a new process.
+            assert_ne!(pid(&pool).await?, runaway_pid);
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn kills_processes_exceeding_the_memory_limit() {
+        use super::{NodeJsPoolResourceLimits, NodeJsResourceLimitError};
+
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let options = NodeJsPoolOptions {
+                resource_limits: NodeJsPoolResourceLimits {
+                    max_memory_mb: Some(64),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let (pool, _dir) = script_pool(RUNAWAY_WORKER, &options)?;
+            let (error, runaway_pid) = run_away(&pool, "allocate").await?;
+            assert!(
+                matches!(
+                    error.downcast_ref::<NodeJsResourceLimitError>(),
+                    Some(NodeJsResourceLimitError::Memory { max_memory_mb: 64 })
+                ),
+                "{error:#}"
+            );
+
+            wait_for_exit(runaway_pid).await;
+            assert_eq!(idle_processes(&pool), 0);
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn keeps_idle_processes_without_timeout() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let (pool, _dir) = worker_pool(None)?;
+            pid(&pool).await?;
+            assert!(!pool.idle_reaper.running.load(Ordering::SeqCst));
+            assert_eq!(idle_processes(&pool), 1);
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
 }
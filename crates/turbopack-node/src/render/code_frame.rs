@@ -0,0 +1,202 @@
+use std::fmt::{self, Display, Write};
+
+use crate::source_map::CodeFrame;
+
+/// An error thrown by a renderer, with the code frames of the project files
+/// in its stack, so the error page can show them.
+#[derive(Debug)]
+pub(super) struct RenderError {
+    /// The source mapped error, including code frames as text.
+    pub message: String,
+    /// The source mapped error, without code frames.
+    pub summary: String,
+    pub code_frames: Vec<CodeFrame>,
+}
+
+impl Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+/// JavaScript and TypeScript keywords, which are highlighted in code frames.
+const KEYWORDS: &[&str] = &[
+    "as",
+    "async",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "from",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "of",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "type",
+    "typeof",
+    "undefined",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+/// Renders code frames as HTML for a `<pre>` element. The line of the
+/// location is marked and its column is pointed at. The code is highlighted
+/// with `tp-keyword`, `tp-string`, `tp-number` and `tp-comment` classes.
+pub(super) fn code_frames_html(code_frames: &[CodeFrame]) -> String {
+    let mut html = String::new();
+    for code_frame in code_frames {
+        // Writing to a String can't fail.
+        let _ = write_code_frame(&mut html, code_frame);
+    }
+    html
+}
+
+fn write_code_frame(html: &mut String, code_frame: &CodeFrame) -> fmt::Result {
+    writeln!(
+        html,
+        "<span class=\"tp-frame-path\">{}:{}:{}</span>",
+        escape(&code_frame.path),
+        code_frame.line,
+        code_frame.column
+    )?;
+    let mut in_block_comment = false;
+    for (i, line) in code_frame.lines.iter().enumerate() {
+        let number = code_frame.first_line + i;
+        let highlighted = highlight_line(line, &mut in_block_comment);
+        if number == code_frame.line {
+            writeln!(
+                html,
+                "<span class=\"tp-frame-line\">&gt; {number:>5} | {highlighted}</span>"
+            )?;
+            // The caret is placed by characters, which matches the columns of
+            // the stack trace for most code.
+            writeln!(
+                html,
+                "        | {}<span class=\"tp-frame-caret\">^</span>",
+                " ".repeat(code_frame.column.saturating_sub(1))
+            )?;
+        } else {
+            writeln!(html, "  {number:>5} | {highlighted}")?;
+        }
+    }
+    html.push('\n');
+    Ok(())
+}
+
+/// Highlights a line of JavaScript. It's a rough approximation of the
+/// grammar, which is good enough for a few lines of context: strings and
+/// template literals end with the line, and regular expressions aren't
+/// recognized. `in_block_comment` tracks block comments across lines.
+fn highlight_line(line: &str, in_block_comment: &mut bool) -> String {
+    let mut html = String::with_capacity(line.len() * 2);
+    let mut rest = line;
+    while !rest.is_empty() {
+        if *in_block_comment {
+            let end = rest.find("*/").map_or(rest.len(), |end| {
+                *in_block_comment = false;
+                end + 2
+            });
+            push_span(&mut html, "tp-comment", &rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+        if rest.starts_with("//") {
+            push_span(&mut html, "tp-comment", rest);
+            break;
+        }
+        if rest.starts_with("/*") {
+            *in_block_comment = true;
+            push_span(&mut html, "tp-comment", "/*");
+            rest = &rest[2..];
+            continue;
+        }
+        let c = rest.chars().next().unwrap();
+        let len = if c == '"' || c == '\'' || c == '`' {
+            let len = string_len(rest, c);
+            push_span(&mut html, "tp-string", &rest[..len]);
+            len
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '.' && c != '_')
+                .unwrap_or(rest.len());
+            push_span(&mut html, "tp-number", &rest[..len]);
+            len
+        } else if c.is_alphabetic() || c == '_' || c == '$' {
+            let len = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '_' && c != '$')
+                .unwrap_or(rest.len());
+            let word = &rest[..len];
+            if KEYWORDS.contains(&word) {
+                push_span(&mut html, "tp-keyword", word);
+            } else {
+                html.push_str(word);
+            }
+            len
+        } else {
+            html.push_str(&escape(&rest[..c.len_utf8()]));
+            c.len_utf8()
+        };
+        rest = &rest[len..];
+    }
+    html
+}
+
+/// The length of the string literal at the start of `text`, including its
+/// quotes. Unterminated strings end with the line.
+fn string_len(text: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == quote {
+            return i + 1;
+        }
+    }
+    text.len()
+}
+
+fn push_span(html: &mut String, class: &str, text: &str) {
+    let _ = write!(html, "<span class=\"{class}\">{}</span>", escape(text));
+}
+
+pub(super) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('>', "&gt;")
+        .replace('<', "&lt;")
+}
@@ -1,14 +1,225 @@
 use anyhow::{Context, Result};
-use turbo_tasks::primitives::StringVc;
-use turbo_tasks_fs::embed_file;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use tokio::time::error::Elapsed;
+use turbo_tasks::{primitives::StringVc, trace::TraceRawVcs, Value};
+use turbo_tasks_fs::{embed_file, FileSystemPathVc};
+
+use super::code_frame::escape;
+use crate::pool::{NodeJsPoolBusyError, NodeJsPoolStartupError};
+
+/// Customizes the pages shown when rendering fails or finds nothing to
+/// render.
+#[turbo_tasks::value(shared)]
+#[derive(Default)]
+pub struct ErrorPageOptions {
+    /// HTML templates replacing the built-in page, by status code. They can
+    /// use the same `${TITLE}`, `${STATUS_CODE}`, `${DETAILS}`,
+    /// `${DETAILS_HEADING}` and `${LANG}` placeholders.
+    pub templates: IndexMap<u16, FileSystemPathVc>,
+    /// Localized titles, by status code.
+    pub titles: IndexMap<u16, String>,
+    /// Localized heading of the error details.
+    pub details_heading: Option<String>,
+    /// The language of the localized strings.
+    pub lang: Option<String>,
+    /// An error page replacing the built-in one, including the templates
+    /// above, when rendering fails with a 5xx status code.
+    pub asset: Option<ErrorPageAssetVc>,
+    /// Whether API requests which fail get an error page or a JSON body.
+    pub response_format: ErrorResponseFormat,
+}
+
+/// The body of the response of a proxied API request which failed.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub enum ErrorResponseFormat {
+    /// JSON when the `Accept` header of the request prefers it over HTML,
+    /// otherwise an error page.
+    #[default]
+    Negotiate,
+    /// Always an error page.
+    Html,
+    /// Always JSON, `{ "error", "stack", "statusCode" }`.
+    Json,
+}
+
+impl ErrorResponseFormat {
+    /// Whether the error response to a request with the `headers` is JSON.
+    pub(super) fn is_json(self, headers: &[(String, String)]) -> bool {
+        match self {
+            ErrorResponseFormat::Html => false,
+            ErrorResponseFormat::Json => true,
+            ErrorResponseFormat::Negotiate => headers
+                .iter()
+                .filter(|(name, _)| name.eq_ignore_ascii_case("accept"))
+                .any(|(_, accept)| accepts_json(accept)),
+        }
+    }
+}
+
+/// Whether an `Accept` header prefers JSON over HTML. Without a preference,
+/// the media type listed first wins.
+fn accepts_json(accept: &str) -> bool {
+    let mut json = None;
+    let mut html = None;
+    for (index, range) in accept.split(',').enumerate() {
+        let mut parts = range.split(';');
+        let media_type = parts.next().unwrap_or_default().trim();
+        let quality = parts
+            .find_map(|param| param.trim().strip_prefix("q="))
+            .and_then(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
+        let preference = match media_type {
+            "application/json" => &mut json,
+            media_type if media_type.ends_with("+json") => &mut json,
+            "text/html" => &mut html,
+            _ => continue,
+        };
+        // Earlier ranges win ties, so they are ranked higher.
+        let rank = (quality, -(index as i64));
+        if preference.map_or(true, |previous| rank > previous) {
+            *preference = Some(rank);
+        }
+    }
+    match (json, html) {
+        (Some((quality, _)), _) if quality <= 0.0 => false,
+        (Some(json), Some(html)) => html.0 <= 0.0 || json > html,
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ErrorPageOptionsVc {
+    #[turbo_tasks::function]
+    pub fn empty() -> Self {
+        ErrorPageOptions::default().cell()
+    }
+}
+
+/// The error a page is rendered for by an [ErrorPageAsset].
+#[turbo_tasks::value(shared, serialization = "auto_for_input")]
+#[derive(Debug, Clone, Hash, PartialOrd, Ord)]
+pub struct ErrorPageError {
+    pub status_code: u16,
+    /// The source mapped message of the error.
+    pub message: String,
+    /// The source mapped stack of the error, one frame per line. It's empty
+    /// when the error doesn't come from JavaScript.
+    pub stack: String,
+}
+
+impl ErrorPageError {
+    /// Splits the `details` of a failed render into the message and the
+    /// stack of the error.
+    pub(super) fn new(status_code: u16, details: &str) -> Self {
+        let (message, stack) = details.split_once('\n').unwrap_or((details, ""));
+        ErrorPageError {
+            status_code,
+            message: message.to_string(),
+            stack: stack.trim_start_matches('\n').to_string(),
+        }
+    }
+}
+
+/// An error page provided by a framework, e.g. to match its design or to
+/// bootstrap its client-side error overlay. It's used by [ErrorPageOptions]
+/// for responses with a 5xx status code, instead of the built-in page and the
+/// fallback page.
+#[turbo_tasks::value_trait]
+pub trait ErrorPageAsset {
+    /// The HTML document shown for `error`. The message and the stack aren't
+    /// escaped.
+    fn html(&self, error: Value<ErrorPageError>) -> StringVc;
+}
+
+/// An [ErrorPageAsset] from an HTML template with `${STATUS_CODE}`,
+/// `${MESSAGE}` and `${STACK}` placeholders, which are replaced with escaped
+/// values.
+#[turbo_tasks::value(shared)]
+pub struct ErrorPageTemplate {
+    pub path: FileSystemPathVc,
+}
+
+#[turbo_tasks::value_impl]
+impl ErrorPageTemplateVc {
+    #[turbo_tasks::function]
+    pub fn new(path: FileSystemPathVc) -> Self {
+        ErrorPageTemplate { path }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ErrorPageAsset for ErrorPageTemplate {
+    #[turbo_tasks::function]
+    async fn html(&self, error: Value<ErrorPageError>) -> Result<StringVc> {
+        let content = self.path.read().await?;
+        let file = content
+            .as_content()
+            .context("error page template not found")?;
+        let template = file
+            .content()
+            .to_str()
+            .context("couldn't convert error page template to string")?;
+        Ok(StringVc::cell(
+            template
+                .replace("${STATUS_CODE}", &error.status_code.to_string())
+                .replace("${MESSAGE}", &escape(&error.message))
+                .replace("${STACK}", &escape(&error.stack)),
+        ))
+    }
+}
+
+/// The document of the [ErrorPageAsset] of the `options` for a failed render
+/// with the `status_code`, if there is one.
+pub(super) async fn custom_error_html(
+    options: ErrorPageOptionsVc,
+    status_code: u16,
+    details: &str,
+) -> Result<Option<StringVc>> {
+    if status_code < 500 {
+        return Ok(None);
+    }
+    let Some(asset) = options.await?.asset else {
+        return Ok(None);
+    };
+    Ok(Some(asset.html(Value::new(ErrorPageError::new(
+        status_code,
+        details,
+    )))))
+}
+
+/// The status code of the page for an `error` that occurred while rendering.
+pub(super) fn error_status_code(error: &anyhow::Error) -> u16 {
+    if error.chain().any(|cause| cause.is::<Elapsed>()) {
+        504
+    } else if error
+        .chain()
+        .any(|cause| cause.is::<NodeJsPoolBusyError>() || cause.is::<NodeJsPoolStartupError>())
+    {
+        503
+    } else {
+        500
+    }
+}
+
+/// The title of the page shown when rendering a page failed with the
+/// `status_code`.
+pub(super) fn render_error_title(status_code: u16) -> &'static str {
+    match status_code {
+        504 => "Timed out rendering page",
+        _ => "Error rendering page",
+    }
+}
 
 #[turbo_tasks::function]
 pub(super) async fn error_html(
     status_code: u16,
     title: String,
     details: String,
+    options: ErrorPageOptionsVc,
 ) -> Result<StringVc> {
-    let html = create_html(status_code, title, details).await?;
+    let html = create_html(status_code, title, details, options).await?;
 
     Ok(StringVc::cell(html))
 }
@@ -18,8 +229,9 @@ pub(super) async fn error_html_body(
     status_code: u16,
     title: String,
     details: String,
+    options: ErrorPageOptionsVc,
 ) -> Result<StringVc> {
-    let html = create_html(status_code, title, details).await?;
+    let html = create_html(status_code, title, details, options).await?;
 
     let (_, body) = html.split_once("<body>").context("no body in html")?;
     let (body, _) = body.split_once("</body>").context("no body in html")?;
@@ -27,20 +239,120 @@ pub(super) async fn error_html_body(
     Ok(StringVc::cell(body.to_string()))
 }
 
-async fn create_html(status_code: u16, title: String, details: String) -> Result<String> {
-    let file_content = embed_file!("src/render/error.html").await?;
+async fn create_html(
+    status_code: u16,
+    title: String,
+    details: String,
+    options: ErrorPageOptionsVc,
+) -> Result<String> {
+    let options = options.await?;
+    let file_content = match options.templates.get(&status_code) {
+        Some(template) => template.read(),
+        None => embed_file!("src/render/error.html"),
+    }
+    .await?;
     let file = file_content
         .as_content()
-        .context("embedded file not found (this should not happen)")?;
+        .with_context(|| format!("error page template for status {status_code} not found"))?;
 
     let html = file
         .content()
         .to_str()
-        .context("couldn't convert embedded html to string")?;
+        .context("couldn't convert error page template to string")?;
+
+    let title = options.titles.get(&status_code).unwrap_or(&title);
+    let details_heading = options.details_heading.as_deref().unwrap_or("Details");
+    let lang = options.lang.as_deref().unwrap_or("en");
 
-    let html = html.replace("${TITLE}", &title);
+    let html = html.replace("${TITLE}", title);
     let html = html.replace("${STATUS_CODE}", &status_code.to_string());
+    let html = html.replace("${DETAILS_HEADING}", details_heading);
+    let html = html.replace("${LANG}", lang);
     let html = html.replace("${DETAILS}", &details);
 
     Ok(html)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use anyhow::{anyhow, Context};
+    use serde_json::json;
+    use tokio::time::timeout;
+    use turbo_tasks::TurboTasks;
+    use turbo_tasks_memory::MemoryBackend;
+
+    use super::{error_html_body, error_status_code, render_error_title, ErrorPageOptionsVc};
+    use crate::pool::{
+        tests::{idle_processes, worker_pool},
+        NodeJsPoolBusyError,
+    };
+
+    #[tokio::test]
+    async fn maps_timeouts_to_504() {
+        let elapsed = timeout(Duration::ZERO, std::future::pending::<()>())
+            .await
+            .unwrap_err();
+        let error = Err::<(), _>(elapsed)
+            .context("timeout while receiving message from process")
+            .context("rendering failed")
+            .unwrap_err();
+        assert_eq!(error_status_code(&error), 504);
+    }
+
+    #[test]
+    fn maps_busy_pools_to_503() {
+        let error = anyhow::Error::new(NodeJsPoolBusyError).context("rendering failed");
+        assert_eq!(error_status_code(&error), 503);
+    }
+
+    #[test]
+    fn maps_other_errors_to_500() {
+        assert_eq!(error_status_code(&anyhow!("rendering failed")), 500);
+    }
+
+    #[tokio::test]
+    async fn renderer_which_never_answers_gets_504_page() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let (pool, _dir) = worker_pool(None)?;
+            let mut operation = pool.operation().await?;
+            operation.set_timeout(Duration::from_millis(200));
+            operation.send(json!({ "type": "hang" })).await?;
+
+            let start = Instant::now();
+            let error = operation
+                .recv::<serde_json::Value>()
+                .await
+                .map(|_| ())
+                .unwrap_err();
+            assert!(start.elapsed() < Duration::from_secs(5));
+            drop(operation);
+            // The process might still answer later, so it's not reused.
+            assert_eq!(idle_processes(&pool), 0);
+
+            let status_code = error_status_code(&error);
+            assert_eq!(status_code, 504);
+            let body = error_html_body(
+                status_code,
+                render_error_title(status_code).to_string(),
+                format!("{error:#}"),
+                ErrorPageOptionsVc::empty(),
+            )
+            .await?;
+            assert!(body.contains(">504</h1>"), "{body}");
+            assert!(body.contains("Timed out rendering page"), "{body}");
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    #[test]
+    fn render_error_titles() {
+        assert_eq!(render_error_title(504), "Timed out rendering page");
+        assert_eq!(render_error_title(500), "Error rendering page");
+    }
+}
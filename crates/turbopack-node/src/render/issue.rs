@@ -1,7 +1,12 @@
 use anyhow::Result;
 use turbo_tasks::primitives::StringVc;
 use turbo_tasks_fs::FileSystemPathVc;
-use turbopack_core::issue::{Issue, IssueVc};
+use turbopack_core::{
+    error::PrettyPrintError,
+    issue::{Issue, IssueSeverity, IssueSeverityVc, IssueVc},
+};
+
+use crate::pool::{CapturedOutput, NodeJsPoolStartupError, NodeJsResourceLimitError};
 
 #[turbo_tasks::value(shared)]
 #[derive(Copy, Clone)]
@@ -9,6 +14,23 @@ pub struct RenderingIssue {
     pub context: FileSystemPathVc,
     pub message: StringVc,
     pub status: Option<i32>,
+    /// The id of the request that caused the issue.
+    pub request_id: Option<StringVc>,
+}
+
+impl RenderingIssue {
+    /// The message of the issue for the `error` of a render. When the process
+    /// exceeded its resource limits, the error which happened to be in flight
+    /// when it was killed isn't helpful, so only the limit is reported.
+    pub(super) fn message_of(error: &anyhow::Error) -> String {
+        match error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<NodeJsResourceLimitError>())
+        {
+            Some(limit_error) => limit_error.to_string(),
+            None => PrettyPrintError(error).to_string(),
+        }
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -40,9 +62,148 @@ impl Issue for RenderingIssue {
         if let Some(status) = self.status {
             details.push(format!("Node.js exit code: {status}"));
         }
+        if let Some(request_id) = self.request_id {
+            details.push(format!("Request ID: {}", request_id.await?));
+        }
 
         Ok(StringVc::cell(details.join("\n")))
     }
 
     // TODO parse stack trace into source location
 }
+
+/// Emitted instead of a [RenderingIssue] while the Node.js processes of a
+/// renderer keep failing to start, so the failure stays visible while the
+/// pool waits before it tries again.
+#[turbo_tasks::value(shared)]
+pub struct RendererStartupIssue {
+    pub context: FileSystemPathVc,
+    pub failures: u32,
+    /// The error of the last process that failed to start.
+    pub error: String,
+    /// The time until a process is started again, in seconds.
+    pub retry_in: u64,
+}
+
+impl RendererStartupIssue {
+    pub(super) fn new(context: FileSystemPathVc, error: &NodeJsPoolStartupError) -> Self {
+        RendererStartupIssue {
+            context,
+            failures: error.failures,
+            error: error.error.clone(),
+            retry_in: error.retry_in.as_secs_f64().ceil() as u64,
+        }
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for RendererStartupIssue {
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell("The renderer failed to start".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("rendering".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.context
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> StringVc {
+        StringVc::cell(self.error.clone())
+    }
+
+    #[turbo_tasks::function]
+    fn detail(&self) -> StringVc {
+        StringVc::cell(format!(
+            "The Node.js process failed to start {} times in a row. It's started again in {}s, or \
+             once the code of the renderer changes.",
+            self.failures, self.retry_in
+        ))
+    }
+}
+
+/// The output a renderer printed while rendering a page, e.g. with
+/// `console.log`. Stack traces in it are source mapped.
+#[turbo_tasks::value(shared)]
+pub struct RendererOutputIssue {
+    pub context: FileSystemPathVc,
+    /// Whether the output was printed to stderr instead of stdout.
+    pub stderr: bool,
+    pub output: Vec<String>,
+    /// The id of the request the page was rendered for.
+    pub request_id: Option<StringVc>,
+}
+
+impl RendererOutputIssue {
+    /// Emits the `output` of the renderer of the page at `context`.
+    pub(super) fn emit_all(
+        context: FileSystemPathVc,
+        output: CapturedOutput,
+        request_id: Option<&str>,
+    ) {
+        for (stderr, output) in [(false, output.stdout), (true, output.stderr)] {
+            if output.is_empty() {
+                continue;
+            }
+            RendererOutputIssue {
+                context,
+                stderr,
+                output,
+                request_id: request_id.map(|id| StringVc::cell(id.to_string())),
+            }
+            .cell()
+            .as_issue()
+            .emit();
+        }
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for RendererOutputIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        if self.stderr {
+            IssueSeverity::Note.into()
+        } else {
+            IssueSeverity::Info.into()
+        }
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell(if self.stderr {
+            "Error output while rendering".to_string()
+        } else {
+            "Output while rendering".to_string()
+        })
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("rendering".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.context
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> StringVc {
+        StringVc::cell(self.output.concat())
+    }
+
+    #[turbo_tasks::function]
+    async fn detail(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(match self.request_id {
+            Some(request_id) => format!("Request ID: {}", request_id.await?),
+            None => String::new(),
+        }))
+    }
+}
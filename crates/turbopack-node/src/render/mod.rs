@@ -1,16 +1,31 @@
+use std::future::Future;
+
+use anyhow::Result;
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
+use tokio::select;
+use turbopack_dev_server::{
+    abort::abort_signal, source::query::Query, websocket_proxy::WebSocketMessage,
+};
 
-use crate::{route_matcher::Param, ResponseHeaders, StructuredError};
+use crate::{route_matcher::Param, OperationTimings, ResponseHeaders, StructuredError};
 
-pub(crate) mod error_page;
+mod code_frame;
+pub mod error_page;
 pub mod issue;
 pub mod node_api_source;
+pub mod render_cache;
 pub mod render_proxy;
 pub mod render_static;
 pub mod rendered_source;
+pub mod static_data;
 
+/// The request a renderer renders. Static renderers built on `run` of
+/// `@vercel/turbopack-node/ipc/render` make it available to user code, which
+/// reads it with `getRenderData` of
+/// `@vercel/turbopack-node/ipc/render-context`.
 #[turbo_tasks::value(shared)]
+#[derive(Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct RenderData {
     params: IndexMap<String, Param>,
@@ -18,31 +33,204 @@ pub struct RenderData {
     url: String,
     raw_query: String,
     raw_headers: Vec<(String, String)>,
+    /// The parsed query string, so renderers don't need to parse `raw_query`.
+    query: Query,
+    /// The cookies of the request, parsed from its `cookie` headers.
+    cookies: IndexMap<String, String>,
     path: String,
+    /// The result of the entry's static data module, if it has one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    static_data: Option<serde_json::Value>,
+    /// The nonce to apply to inline scripts and styles. This is a placeholder
+    /// that is replaced by the dev server for every request, so it can also be
+    /// used in a `Content-Security-Policy` header.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    csp_nonce: Option<String>,
+    /// Identifies the request in logs and issues. It's echoed in the
+    /// `x-turbopack-request-id` response header.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+    /// The generation of the dev server's `fetch` cache. Renderers that
+    /// cache `fetch` responses, e.g. with `withFetchCache` of
+    /// `@vercel/turbopack-node/ipc/fetch-cache`, drop them when it changes.
+    fetch_cache_generation: u64,
+}
+
+/// Parses the cookies from the `cookie` headers in `raw_headers`. Values are
+/// percent-decoded when possible, like the `cookie` npm package does. When a
+/// cookie occurs multiple times, the first occurrence wins.
+fn parse_cookies(raw_headers: &[(String, String)]) -> IndexMap<String, String> {
+    let mut cookies = IndexMap::new();
+    for (_, header) in raw_headers
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case("cookie"))
+    {
+        for pair in header.split(';') {
+            let Some((name, value)) = pair.split_once('=') else {
+                continue;
+            };
+            let name = name.trim();
+            if name.is_empty() || cookies.contains_key(name) {
+                continue;
+            }
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+            let value = urlencoding::decode(value)
+                .map(|value| value.into_owned())
+                .unwrap_or_else(|_| value.to_string());
+            cookies.insert(name.to_string(), value);
+        }
+    }
+    cookies
+}
+
+/// Where the time of a render went, up to the response or, for streamed
+/// responses, up to their headers.
+#[turbo_tasks::value(transparent)]
+pub struct RenderTimings(OperationTimings);
+
+/// Formats `timings` as the value of a `Server-Timing` header, which browser
+/// devtools show in the timing breakdown of the request.
+fn server_timing(timings: &OperationTimings) -> String {
+    [
+        ("queue", "Queue wait", timings.queue),
+        ("ipc", "IPC", timings.ipc),
+        ("execution", "JS execution", timings.execution),
+        ("serialization", "Serialization", timings.serialization),
+    ]
+    .iter()
+    .map(|(name, description, duration)| {
+        format!(
+            "{name};desc=\"{description}\";dur={:.3}",
+            duration.as_secs_f64() * 1000.0
+        )
+    })
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+/// Adds the `Server-Timing` header of the `timings` to the `headers`.
+async fn with_server_timing(
+    mut headers: Vec<(String, String)>,
+    timings: Option<RenderTimingsVc>,
+) -> Result<Vec<(String, String)>> {
+    if let Some(timings) = timings {
+        headers.push(("server-timing".to_string(), server_timing(&*timings.await?)));
+    }
+    Ok(headers)
+}
+
+/// The status of renders that were aborted because their client
+/// disconnected, as used by nginx. It never reaches the client.
+const CLIENT_CLOSED_REQUEST: u16 = 499;
+
+/// Waits for the `future` unless the client of the request with the
+/// `request_id` disconnects first, in which case the `future` is dropped and
+/// `None` is returned.
+async fn unless_aborted<T>(request_id: Option<&str>, future: impl Future<Output = T>) -> Option<T> {
+    let Some(signal) = request_id.and_then(abort_signal) else {
+        return Some(future.await);
+    };
+    select! {
+        result = future => Some(result),
+        _ = signal.cancelled() => None,
+    }
 }
 
 #[derive(Serialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
 enum RenderStaticOutgoingMessage<'a> {
-    Headers { data: &'a RenderData },
+    /// The request to render. The renderer applies the `env_overrides` to
+    /// `process.env` while it renders this request only, e.g. with
+    /// `withEnvOverrides` of `@vercel/turbopack-node/ipc/env`, which `run` of
+    /// `@vercel/turbopack-node/ipc/render` does.
+    #[serde(rename_all = "camelCase")]
+    Headers {
+        data: &'a RenderData,
+        env_overrides: &'a IndexMap<String, String>,
+    },
+    /// The client of the request disconnected, so the render isn't needed
+    /// anymore. No response is expected, and the process isn't reused, since
+    /// it might still be busy with the render.
+    Cancel,
 }
 
+/// The request to a proxied renderer. The request body follows the headers as
+/// [BodyChunk]s and ends with [BodyEnd]. Chunks are forwarded as they arrive
+/// from the client, so large uploads aren't buffered.
+///
+/// A renderer accepts a request with `Upgrade: websocket` by responding with
+/// [Upgrade] instead of [Headers]. The messages of the WebSocket connection
+/// are then sent in both directions as [WebSocketProxyMessage]s until either
+/// side sends [WebSocketClose].
+///
+/// The renderer is sent [Cancel] when the client disconnects before the
+/// response is ready. The process isn't reused afterwards.
+///
+/// [BodyChunk]: RenderProxyOutgoingMessage::BodyChunk
+/// [BodyEnd]: RenderProxyOutgoingMessage::BodyEnd
+/// [Cancel]: RenderProxyOutgoingMessage::Cancel
+/// [Upgrade]: RenderProxyIncomingMessage::Upgrade
+/// [Headers]: RenderProxyIncomingMessage::Headers
+/// [WebSocketClose]: WebSocketProxyMessage::WebSocketClose
 #[derive(Serialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
 enum RenderProxyOutgoingMessage<'a> {
     Headers { data: &'a RenderData },
     BodyChunk { data: &'a [u8] },
     BodyEnd,
+    Cancel,
 }
 
 #[derive(Deserialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
 enum RenderProxyIncomingMessage {
-    Headers { data: ResponseHeaders },
-    Body { data: Vec<u8> },
+    Headers {
+        data: ResponseHeaders,
+    },
+    /// Accepts a WebSocket upgrade, with the headers of the `101 Switching
+    /// Protocols` response.
+    Upgrade {
+        data: ResponseHeaders,
+    },
+    Body {
+        data: Vec<u8>,
+    },
     Error(StructuredError),
 }
 
+/// A message of a WebSocket connection proxied to a renderer.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum WebSocketProxyMessage {
+    WebSocketText { data: String },
+    WebSocketBinary { data: Vec<u8> },
+    WebSocketClose,
+}
+
+impl From<WebSocketMessage> for WebSocketProxyMessage {
+    fn from(message: WebSocketMessage) -> Self {
+        match message {
+            WebSocketMessage::Text(data) => WebSocketProxyMessage::WebSocketText { data },
+            WebSocketMessage::Binary(data) => WebSocketProxyMessage::WebSocketBinary { data },
+            WebSocketMessage::Close => WebSocketProxyMessage::WebSocketClose,
+        }
+    }
+}
+
+impl From<WebSocketProxyMessage> for WebSocketMessage {
+    fn from(message: WebSocketProxyMessage) -> Self {
+        match message {
+            WebSocketProxyMessage::WebSocketText { data } => WebSocketMessage::Text(data),
+            WebSocketProxyMessage::WebSocketBinary { data } => WebSocketMessage::Binary(data),
+            WebSocketProxyMessage::WebSocketClose => WebSocketMessage::Close,
+        }
+    }
+}
+
 #[derive(Deserialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
 enum RenderStaticIncomingMessage {
@@ -52,8 +240,30 @@ enum RenderStaticIncomingMessage {
         headers: Vec<(String, String)>,
         body: String,
     },
+    /// Starts a streamed response. The body follows as [BodyChunk]s and ends
+    /// with [BodyEnd].
+    ///
+    /// [BodyChunk]: RenderStaticIncomingMessage::BodyChunk
+    /// [BodyEnd]: RenderStaticIncomingMessage::BodyEnd
+    #[serde(rename_all = "camelCase")]
+    ResponseHeaders {
+        status_code: u16,
+        headers: Vec<(String, String)>,
+    },
+    BodyChunk {
+        data: String,
+    },
+    BodyEnd,
     Rewrite {
         path: String,
     },
+    /// There is nothing to render for the request, so the built-in not found
+    /// page is shown. A [Response] with status 404 is sent as it is instead,
+    /// even when its body is empty.
+    ///
+    /// [Response]: RenderStaticIncomingMessage::Response
+    NotFound {
+        headers: Vec<(String, String)>,
+    },
     Error(StructuredError),
 }
@@ -6,21 +6,28 @@ use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_core::introspect::{
     asset::IntrospectableAssetVc, Introspectable, IntrospectableChildrenVc, IntrospectableVc,
 };
-use turbopack_dev_server::source::{
-    specificity::SpecificityVc, ContentSource, ContentSourceContent, ContentSourceContentVc,
-    ContentSourceData, ContentSourceDataVary, ContentSourceDataVaryVc, ContentSourceResult,
-    ContentSourceResultVc, ContentSourceVc, GetContentSourceContent, GetContentSourceContentVc,
+use turbopack_dev_server::{
+    fetch_cache,
+    source::{
+        specificity::SpecificityVc, ContentSource, ContentSourceContent, ContentSourceContentVc,
+        ContentSourceData, ContentSourceDataFilter, ContentSourceDataVary, ContentSourceDataVaryVc,
+        ContentSourceResult, ContentSourceResultVc, ContentSourceVc, GetContentSourceContent,
+        GetContentSourceContentVc,
+    },
 };
 use turbopack_ecmascript::chunk::EcmascriptChunkPlaceablesVc;
 
-use super::{render_proxy::render_proxy, RenderData};
+use super::{
+    error_page::ErrorPageOptionsVc, parse_cookies, render_proxy::render_proxy, RenderData,
+};
 use crate::{
     get_intermediate_asset,
     node_entry::{NodeEntry, NodeEntryVc},
     route_matcher::{RouteMatcher, RouteMatcherVc},
 };
 
-/// Creates a [NodeApiContentSource].
+/// Creates a [NodeApiContentSource]. Pages shown when a request fails are
+/// customized by `error_page`.
 #[turbo_tasks::function]
 pub fn create_node_api_source(
     cwd: FileSystemPathVc,
@@ -31,6 +38,7 @@ pub fn create_node_api_source(
     pathname: StringVc,
     entry: NodeEntryVc,
     runtime_entries: EcmascriptChunkPlaceablesVc,
+    error_page: ErrorPageOptionsVc,
 ) -> ContentSourceVc {
     NodeApiContentSource {
         cwd,
@@ -41,6 +49,7 @@ pub fn create_node_api_source(
         route_match,
         entry,
         runtime_entries,
+        error_page,
     }
     .cell()
     .into()
@@ -62,6 +71,7 @@ pub struct NodeApiContentSource {
     route_match: RouteMatcherVc,
     entry: NodeEntryVc,
     runtime_entries: EcmascriptChunkPlaceablesVc,
+    error_page: ErrorPageOptionsVc,
 }
 
 #[turbo_tasks::value_impl]
@@ -111,9 +121,11 @@ impl GetContentSourceContent for NodeApiGetContentResult {
             method: true,
             url: true,
             raw_headers: true,
+            query: Some(ContentSourceDataFilter::All),
             raw_query: true,
             body: true,
             cache_buster: true,
+            request_id: true,
             ..Default::default()
         }
         .cell()
@@ -128,6 +140,7 @@ impl GetContentSourceContent for NodeApiGetContentResult {
             method: Some(method),
             url: Some(url),
             raw_headers: Some(raw_headers),
+            query: Some(query),
             raw_query: Some(raw_query),
             body: Some(body),
             ..
@@ -145,13 +158,22 @@ impl GetContentSourceContent for NodeApiGetContentResult {
             entry.intermediate_output_path,
             entry.output_root,
             entry.project_dir,
+            entry.render_timeout,
+            entry.pool_options,
+            source.error_page,
             RenderData {
                 params: params.clone(),
                 method: method.clone(),
                 url: url.clone(),
                 raw_query: raw_query.clone(),
                 raw_headers: raw_headers.clone(),
+                query: query.clone(),
+                cookies: parse_cookies(raw_headers),
                 path: format!("/{}", self.path),
+                static_data: None,
+                csp_nonce: None,
+                request_id: data.request_id.clone(),
+                fetch_cache_generation: fetch_cache::generation(),
             }
             .cell(),
             *body,
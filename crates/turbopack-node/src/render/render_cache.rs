@@ -0,0 +1,285 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use turbo_tasks::{trace::TraceRawVcs, State};
+use turbopack_core::asset::AssetContentVc;
+use turbopack_dev_server::source::HeaderListVc;
+
+use super::{
+    render_static::{StaticResult, StaticResultVc},
+    RenderData,
+};
+
+/// The number of pages a [RenderCache] keeps. When more pages are rendered,
+/// the least recently used page is evicted.
+const RENDER_CACHE_CAPACITY: usize = 500;
+
+/// A page in a [RenderCache].
+#[derive(Clone, Debug, TraceRawVcs)]
+struct CachedRender {
+    content: AssetContentVc,
+    status_code: u16,
+    headers: HeaderListVc,
+    /// The path of the url the page was rendered for, without the query.
+    path: String,
+    #[turbo_tasks(trace_ignore)]
+    rendered_at: Instant,
+    /// Whether the page is being re-rendered in the background.
+    revalidating: bool,
+}
+
+/// Caches rendered pages, like incremental static regeneration does. A page
+/// is served from the cache until it's older than the `revalidate` time of
+/// its entry. Stale pages are still served, while they are re-rendered in
+/// the background.
+///
+/// Pages are keyed by their [RenderData], except for the request id. Only
+/// pages which are rendered at once with a status code below 500 are cached,
+/// so streamed and failed renders are repeated for every request.
+///
+/// The cache isn't invalidated when the code of the page changes, so a change
+/// shows up once the page is revalidated, or after [RenderCache::invalidate].
+///
+/// At most [RENDER_CACHE_CAPACITY] pages are kept, as every distinct url and
+/// set of env overrides has its own page.
+#[turbo_tasks::value(serialization = "none", eq = "manual", cell = "new")]
+pub struct RenderCache {
+    /// Read untracked, so requests don't depend on the renders of others.
+    /// Ordered from the least to the most recently used page.
+    entries: State<IndexMap<String, CachedRender>>,
+    capacity: usize,
+}
+
+impl RenderCacheVc {
+    pub(crate) fn empty() -> Self {
+        RenderCache::new(RENDER_CACHE_CAPACITY).cell()
+    }
+}
+
+impl RenderCache {
+    fn new(capacity: usize) -> Self {
+        RenderCache {
+            entries: State::new(IndexMap::new()),
+            capacity,
+        }
+    }
+
+    /// The cached page for `key`, and whether it needs to be revalidated
+    /// because it's older than `revalidate`. A page is only revalidated by a
+    /// single request at a time.
+    pub(crate) fn get(&self, key: &str, revalidate: Duration) -> Option<(StaticResultVc, bool)> {
+        let mut entries = self.entries.get_untracked();
+        let (key, mut entry) = entries.shift_remove_entry(key)?;
+        let revalidating = !entry.revalidating && entry.rendered_at.elapsed() >= revalidate;
+        if revalidating {
+            entry.revalidating = true;
+        }
+        let result = StaticResultVc::content(entry.content, entry.status_code, entry.headers);
+        // Marks the page as the most recently used one.
+        entries.insert(key, entry);
+        Some((result, revalidating))
+    }
+
+    /// Caches the page `result` rendered for `key`, if it's cacheable.
+    /// Otherwise a stale page of `key` is kept until it's revalidated again.
+    pub(crate) fn insert(&self, key: String, url: &str, result: &StaticResult) {
+        let &StaticResult::Content {
+            content,
+            status_code,
+            headers,
+            ..
+        } = result else {
+            self.revalidation_failed(&key);
+            return;
+        };
+        if status_code >= 500 {
+            self.revalidation_failed(&key);
+            return;
+        }
+        self.entries.update_conditionally(|entries| {
+            entries.shift_remove(&key);
+            entries.insert(
+                key,
+                CachedRender {
+                    content,
+                    status_code,
+                    headers,
+                    path: url_path(url).to_string(),
+                    rendered_at: Instant::now(),
+                    revalidating: false,
+                },
+            );
+            while entries.len() > self.capacity {
+                entries.shift_remove_index(0);
+            }
+            true
+        });
+    }
+
+    /// Allows the stale page of `key` to be revalidated again by the next
+    /// request.
+    pub(crate) fn revalidation_failed(&self, key: &str) {
+        self.entries.update_conditionally(|entries| {
+            let Some(entry) = entries.get_mut(key) else {
+                return false;
+            };
+            entry.revalidating = false;
+            true
+        });
+    }
+
+    /// Removes the pages rendered for urls with the `path`, so they are
+    /// rendered again by the next request.
+    pub fn invalidate(&self, path: &str) {
+        self.entries.update_conditionally(|entries| {
+            let len = entries.len();
+            entries.retain(|_, entry| entry.path != path);
+            entries.len() != len
+        });
+    }
+
+    /// Removes all pages.
+    pub fn invalidate_all(&self) {
+        self.entries.update_conditionally(|entries| {
+            let changed = !entries.is_empty();
+            entries.clear();
+            changed
+        });
+    }
+}
+
+/// The key of the page rendered for `data` with the `env_overrides`.
+pub(crate) fn render_cache_key(
+    data: &RenderData,
+    env_overrides: Option<&IndexMap<String, String>>,
+) -> Result<String> {
+    Ok(serde_json::to_string(&(
+        RenderData {
+            request_id: None,
+            ..data.clone()
+        },
+        env_overrides,
+    ))?)
+}
+
+fn url_path(url: &str) -> &str {
+    url.split_once('?').map_or(url, |(path, _)| path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use anyhow::{bail, Result};
+    use turbo_tasks::TurboTasks;
+    use turbo_tasks_fs::File;
+    use turbo_tasks_memory::MemoryBackend;
+    use turbopack_dev_server::source::HeaderListVc;
+
+    use super::{RenderCache, StaticResult, StaticResultVc};
+
+    /// A page rendered with the `status_code`.
+    fn page(status_code: u16) -> StaticResult {
+        StaticResult::Content {
+            content: File::from("page").into(),
+            status_code,
+            headers: HeaderListVc::empty(),
+            timings: None,
+        }
+    }
+
+    async fn status_code(result: StaticResultVc) -> Result<u16> {
+        let StaticResult::Content { status_code, .. } = *result.await? else {
+            bail!("the page has no content");
+        };
+        Ok(status_code)
+    }
+
+    /// The keys of the cached pages, ordered from the least to the most
+    /// recently used one.
+    fn keys(cache: &RenderCache) -> Vec<String> {
+        cache.entries.get_untracked().keys().cloned().collect()
+    }
+
+    #[tokio::test]
+    async fn serves_stale_pages_while_revalidating() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let cache = RenderCache::new(10);
+            cache.insert("key".to_string(), "/page", &page(200));
+
+            let (result, revalidating) = cache.get("key", Duration::from_secs(60)).unwrap();
+            assert_eq!(status_code(result).await?, 200);
+            assert!(!revalidating);
+
+            // The stale page is served, and only the first request revalidates
+            // it.
+            let (result, revalidating) = cache.get("key", Duration::ZERO).unwrap();
+            assert_eq!(status_code(result).await?, 200);
+            assert!(revalidating);
+            let (_, revalidating) = cache.get("key", Duration::ZERO).unwrap();
+            assert!(!revalidating);
+
+            // A failed revalidation keeps the stale page and lets the next
+            // request try again.
+            cache.insert("key".to_string(), "/page", &page(500));
+            let (result, revalidating) = cache.get("key", Duration::ZERO).unwrap();
+            assert_eq!(status_code(result).await?, 200);
+            assert!(revalidating);
+
+            cache.insert("key".to_string(), "/page", &page(404));
+            let (result, revalidating) = cache.get("key", Duration::from_secs(60)).unwrap();
+            assert_eq!(status_code(result).await?, 404);
+            assert!(!revalidating);
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn invalidates_the_pages_of_a_path() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let cache = RenderCache::new(10);
+            cache.insert("a".to_string(), "/page?a", &page(200));
+            cache.insert("b".to_string(), "/page?b", &page(200));
+            cache.insert("other".to_string(), "/other", &page(200));
+
+            cache.invalidate("/page");
+            assert_eq!(keys(&cache), ["other"]);
+            assert!(cache.get("a", Duration::from_secs(60)).is_none());
+
+            cache.invalidate_all();
+            assert!(keys(&cache).is_empty());
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn evicts_the_least_recently_used_page() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let cache = RenderCache::new(2);
+            cache.insert("a".to_string(), "/a", &page(200));
+            cache.insert("b".to_string(), "/b", &page(200));
+            cache.get("a", Duration::from_secs(60)).unwrap();
+
+            cache.insert("c".to_string(), "/c", &page(200));
+            assert_eq!(keys(&cache), ["a", "c"]);
+
+            // Rendering a cached page again doesn't evict another one.
+            cache.insert("a".to_string(), "/a", &page(200));
+            assert_eq!(keys(&cache), ["c", "a"]);
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+}
@@ -1,22 +1,49 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
 use anyhow::{bail, Result};
-use futures::StreamExt;
+use futures::{stream, StreamExt};
+use serde_json::json;
 use turbo_tasks::primitives::StringVc;
 use turbo_tasks_env::ProcessEnvVc;
 use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_core::{asset::AssetVc, chunk::ChunkingContextVc, error::PrettyPrintError};
-use turbopack_dev_server::source::{BodyVc, ProxyResult, ProxyResultVc};
+use turbopack_dev_server::{
+    source::{BodyVc, ProxyResult, ProxyResultVc},
+    websocket_proxy::{self, WebSocketMessage, WebSocketProxy},
+};
 use turbopack_ecmascript::{chunk::EcmascriptChunkPlaceablesVc, EcmascriptModuleAssetVc};
 
 use super::{
-    issue::RenderingIssue, RenderDataVc, RenderProxyIncomingMessage, RenderProxyOutgoingMessage,
-    ResponseHeaders,
+    issue::{RendererStartupIssue, RenderingIssue},
+    unless_aborted, RenderDataVc, RenderProxyIncomingMessage, RenderProxyOutgoingMessage,
+    ResponseHeaders, WebSocketProxyMessage, CLIENT_CLOSED_REQUEST,
 };
 use crate::{
-    get_intermediate_asset, get_renderer_pool, pool::NodeJsOperation,
-    render::error_page::error_html, source_map::trace_stack,
+    get_hot_renderer_pool, get_intermediate_asset,
+    pool::{NodeJsOperation, NodeJsPoolOptionsVc, NodeJsPoolStartupError},
+    render::error_page::{
+        custom_error_html, error_html, error_status_code, ErrorPageError, ErrorPageOptionsVc,
+    },
+    source_map::trace_stack,
 };
 
+/// Identifies WebSocket upgrades of requests without a request id.
+static NEXT_UPGRADE_ID: AtomicU64 = AtomicU64::new(0);
+
 /// Renders a module as static HTML in a node.js process.
+///
+/// When rendering takes longer than `render_timeout` milliseconds, the
+/// Node.js process is killed and a timeout error page is returned instead.
+/// The error page can be replaced with the [ErrorPageAsset] of the
+/// `error_page`.
+///
+/// A WebSocket upgrade accepted by the renderer is relayed to the operation,
+/// which keeps the Node.js process until the connection is closed.
+///
+/// [ErrorPageAsset]: super::error_page::ErrorPageAsset
 #[turbo_tasks::function]
 pub async fn render_proxy(
     cwd: FileSystemPathVc,
@@ -28,47 +55,134 @@ pub async fn render_proxy(
     intermediate_output_path: FileSystemPathVc,
     output_root: FileSystemPathVc,
     project_dir: FileSystemPathVc,
+    render_timeout: Option<u64>,
+    pool_options: NodeJsPoolOptionsVc,
+    error_page: ErrorPageOptionsVc,
     data: RenderDataVc,
     body: BodyVc,
 ) -> Result<ProxyResultVc> {
+    let request_id = data.await?.request_id.clone();
+    let upgrade_id = request_id.clone().unwrap_or_else(|| {
+        format!(
+            "websocket-{}",
+            NEXT_UPGRADE_ID.fetch_add(1, Ordering::Relaxed)
+        )
+    });
     let intermediate_asset = get_intermediate_asset(
         module.as_evaluated_chunk(chunking_context, Some(runtime_entries)),
         intermediate_output_path,
     );
 
-    let pool = get_renderer_pool(
+    let pool = get_hot_renderer_pool(
         cwd,
         env,
         intermediate_asset,
         intermediate_output_path,
         output_root,
         project_dir,
-        /* debug */ false,
+        pool_options,
+        // Debugging is enabled by the `inspect_port` of the `pool_options`.
+        false,
     )
     .await?;
 
     let mut operation = match pool.operation().await {
         Ok(operation) => operation,
         Err(err) => {
-            return proxy_error(path, err, None).await;
+            return proxy_error(path, err, None, error_page, data).await;
         }
     };
+    if let Some(request_id) = &request_id {
+        operation.set_output_prefix(format!("[{request_id}] "));
+    }
+    if let Some(render_timeout) = render_timeout {
+        operation.set_timeout(Duration::from_millis(render_timeout));
+    }
 
-    match run_proxy_operation(
-        &mut operation,
-        data,
-        body,
-        intermediate_asset,
-        intermediate_output_path,
-        project_dir,
+    let result = unless_aborted(
+        request_id.as_deref(),
+        run_proxy_operation(
+            &mut operation,
+            data,
+            body,
+            intermediate_asset,
+            intermediate_output_path,
+            project_dir,
+        ),
     )
-    .await
-    {
-        Ok(proxy_result) => Ok(proxy_result.cell()),
-        Err(err) => Ok(proxy_error(path, err, Some(operation)).await?),
+    .await;
+    let Some(result) = result else {
+        // The client is gone, see `render_static`.
+        let _ = operation.send(RenderProxyOutgoingMessage::Cancel).await;
+        operation.disallow_reuse();
+        return Ok(ProxyResult {
+            status: CLIENT_CLOSED_REQUEST,
+            headers: vec![],
+            body: Default::default(),
+            upgrade: None,
+        }
+        .cell());
+    };
+
+    match result {
+        Ok(ProxyOperationResult::Response(proxy_result)) => Ok(proxy_result.cell()),
+        Ok(ProxyOperationResult::Upgrade(ResponseHeaders { headers, .. })) => {
+            relay_websocket(operation, upgrade_id.clone());
+            Ok(ProxyResult {
+                status: 101,
+                headers,
+                body: Default::default(),
+                upgrade: Some(upgrade_id),
+            }
+            .cell())
+        }
+        Err(err) => Ok(proxy_error(path, err, Some(operation), error_page, data).await?),
     }
 }
 
+enum ProxyOperationResult {
+    Response(ProxyResult),
+    /// The renderer accepted a WebSocket upgrade.
+    Upgrade(ResponseHeaders),
+}
+
+/// Registers a [WebSocketProxy] under `id` and relays its messages to the
+/// `operation` until either side closes the connection.
+fn relay_websocket(mut operation: NodeJsOperation, id: String) {
+    let (client_sender, mut client_receiver) = tokio::sync::mpsc::unbounded_channel();
+    let (renderer_sender, renderer_receiver) = tokio::sync::mpsc::unbounded_channel();
+    websocket_proxy::register(
+        id,
+        WebSocketProxy {
+            sender: client_sender,
+            receiver: renderer_receiver,
+        },
+    );
+    tokio::spawn(async move {
+        // The renderer is told to close the connection when the client is gone.
+        let mut outgoing = stream::poll_fn(move |cx| client_receiver.poll_recv(cx))
+            .map(WebSocketProxyMessage::from)
+            .chain(stream::once(async {
+                WebSocketProxyMessage::WebSocketClose
+            }))
+            .boxed();
+        let result = operation
+            .relay(&mut outgoing, |message: WebSocketProxyMessage| {
+                let message = WebSocketMessage::from(message);
+                let closed = message == WebSocketMessage::Close;
+                renderer_sender.send(message).is_ok() && !closed
+            })
+            .await;
+        if let Err(err) = result {
+            let _ = renderer_sender.send(WebSocketMessage::Close);
+            eprintln!(
+                "WebSocket connection to the Node.js process failed: {}",
+                PrettyPrintError(&err)
+            );
+        }
+    });
+}
+
 async fn run_proxy_operation(
     operation: &mut NodeJsOperation,
     data: RenderDataVc,
@@ -76,7 +190,7 @@ async fn run_proxy_operation(
     intermediate_asset: AssetVc,
     intermediate_output_path: FileSystemPathVc,
     project_dir: FileSystemPathVc,
-) -> Result<ProxyResult> {
+) -> Result<ProxyOperationResult> {
     let data = data.await?;
     // First, send the render data.
     operation
@@ -84,7 +198,7 @@ async fn run_proxy_operation(
         .await?;
 
     let mut body = body.await?.read();
-    // Then, send the binary body in chunks.
+    // Then, send the binary body in chunks, as they arrive from the client.
     while let Some(data) = body.next().await {
         operation
             .send(RenderProxyOutgoingMessage::BodyChunk { data: &data? })
@@ -97,6 +211,9 @@ async fn run_proxy_operation(
         RenderProxyIncomingMessage::Headers {
             data: ResponseHeaders { status, headers },
         } => (status, headers),
+        RenderProxyIncomingMessage::Upgrade { data } => {
+            return Ok(ProxyOperationResult::Upgrade(data));
+        }
         RenderProxyIncomingMessage::Error(error) => {
             bail!(
                 trace_stack(
@@ -131,19 +248,27 @@ async fn run_proxy_operation(
         }
     };
 
-    Ok(ProxyResult {
+    Ok(ProxyOperationResult::Response(ProxyResult {
         status,
         headers,
         body: body.into(),
-    })
+        upgrade: None,
+    }))
 }
 
+/// Responds to a request which failed with an error page, or with a JSON body
+/// depending on the [ErrorResponseFormat] of the `error_page`.
+///
+/// [ErrorResponseFormat]: super::error_page::ErrorResponseFormat
 async fn proxy_error(
     path: FileSystemPathVc,
     error: anyhow::Error,
     operation: Option<NodeJsOperation>,
+    error_page: ErrorPageOptionsVc,
+    data: RenderDataVc,
 ) -> Result<ProxyResultVc> {
-    let message = format!("{}", PrettyPrintError(&error));
+    let data = data.await?;
+    let message = RenderingIssue::message_of(&error);
 
     let status = match operation {
         Some(operation) => Some(operation.wait_or_kill().await?),
@@ -155,30 +280,52 @@ async fn proxy_error(
         details.push(format!("status: {status}"));
     }
 
-    let status_code = 500;
-    let body = &*error_html(
-        status_code,
-        "An error occurred while proxying the request to Node.js".to_string(),
-        format!("{message}\n\n{}", details.join("\n")),
-    )
-    .await?;
-
-    RenderingIssue {
-        context: path,
-        message: StringVc::cell(message),
-        status: status.and_then(|status| status.code()),
+    let startup_error = error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<NodeJsPoolStartupError>());
+    match startup_error {
+        Some(startup_error) => RendererStartupIssue::new(path, startup_error)
+            .cell()
+            .as_issue()
+            .emit(),
+        None => RenderingIssue {
+            context: path,
+            message: StringVc::cell(message.clone()),
+            status: status.and_then(|status| status.code()),
+            request_id: data.request_id.clone().map(StringVc::cell),
+        }
+        .cell()
+        .as_issue()
+        .emit(),
     }
-    .cell()
-    .as_issue()
-    .emit();
+
+    let status_code = error_status_code(&error);
+    let (content_type, body) = if error_page.await?.response_format.is_json(&data.raw_headers) {
+        let ErrorPageError { message, stack, .. } = ErrorPageError::new(status_code, &message);
+        let body = json!({
+            "error": message,
+            "stack": stack,
+            "statusCode": status_code,
+        });
+        ("application/json", serde_json::to_string(&body)?)
+    } else {
+        let html = match custom_error_html(error_page, status_code, &message).await? {
+            Some(html) => html,
+            None => error_html(
+                status_code,
+                "An error occurred while proxying the request to Node.js".to_string(),
+                format!("{message}\n\n{}", details.join("\n")),
+                error_page,
+            ),
+        };
+        ("text/html; charset=utf-8", html.await?.clone_value())
+    };
 
     Ok(ProxyResult {
         status: status_code,
-        headers: vec![(
-            "content-type".to_string(),
-            "text/html; charset=utf-8".to_string(),
-        )],
-        body: body.clone().into(),
+        headers: vec![("content-type".to_string(), content_type.to_string())],
+        body: body.into(),
+        upgrade: None,
     }
     .cell())
 }
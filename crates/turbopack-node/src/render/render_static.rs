@@ -1,7 +1,13 @@
+use std::time::Duration;
+
 use anyhow::{bail, Context, Result};
-use turbo_tasks::primitives::StringVc;
-use turbo_tasks_env::ProcessEnvVc;
+use futures::channel::mpsc::unbounded;
+use mime::TEXT_HTML_UTF_8;
+use turbo_tasks::{primitives::StringVc, Value};
+use turbo_tasks_bytes::Bytes;
+use turbo_tasks_env::{ProcessEnv, ProcessEnvVc};
 use turbo_tasks_fs::{File, FileContent, FileSystemPathVc};
+use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64};
 use turbopack_core::{
     asset::{Asset, AssetContentVc, AssetVc},
     chunk::ChunkingContextVc,
@@ -9,24 +15,43 @@ use turbopack_core::{
 };
 use turbopack_dev_server::{
     html::DevHtmlAssetVc,
-    source::{HeaderListVc, RewriteBuilder, RewriteVc},
+    source::{Body, BodyError, HeaderListVc, RewriteBuilder, RewriteVc},
 };
 use turbopack_ecmascript::{chunk::EcmascriptChunkPlaceablesVc, EcmascriptModuleAssetVc};
 
 use super::{
-    issue::RenderingIssue, RenderDataVc, RenderStaticIncomingMessage, RenderStaticOutgoingMessage,
+    code_frame::{code_frames_html, RenderError},
+    error_page::{custom_error_html, error_status_code, render_error_title, ErrorPageOptionsVc},
+    issue::{RendererOutputIssue, RendererStartupIssue, RenderingIssue},
+    static_data::{embed_static_data, load_static_data, StaticDataModuleVc},
+    unless_aborted, RenderDataVc, RenderStaticIncomingMessage, RenderStaticOutgoingMessage,
+    RenderTimingsVc, CLIENT_CLOSED_REQUEST,
 };
 use crate::{
-    get_intermediate_asset, get_renderer_pool, pool::NodeJsOperation,
-    render::error_page::error_html_body, source_map::trace_stack,
+    get_edge_intermediate_asset, get_hot_renderer_pool, get_intermediate_asset,
+    pool::{NodeJsOperation, NodeJsPoolOptionsVc, NodeJsPoolStartupError},
+    render::error_page::error_html_body,
+    source_map::{trace_stack, trace_stack_with_code_frames},
+    RuntimeKind,
 };
 
 #[turbo_tasks::value]
+#[derive(Clone)]
 pub enum StaticResult {
     Content {
         content: AssetContentVc,
         status_code: u16,
         headers: HeaderListVc,
+        /// Pages served from the render cache have none.
+        timings: Option<RenderTimingsVc>,
+    },
+    /// A response whose body is streamed while the page is still being
+    /// rendered, so its start can be sent to the browser early.
+    StreamedContent {
+        status_code: u16,
+        headers: HeaderListVc,
+        body: Body,
+        timings: Option<RenderTimingsVc>,
     },
     Rewrite(RewriteVc),
 }
@@ -39,10 +64,25 @@ impl StaticResultVc {
             content,
             status_code,
             headers,
+            timings: None,
         }
         .cell()
     }
 
+    /// Attaches the `timings` of the render to a rendered page.
+    #[turbo_tasks::function]
+    pub async fn with_timings(self, timings: RenderTimingsVc) -> Result<Self> {
+        let mut result = self.await?.clone_value();
+        if let StaticResult::Content {
+            timings: content_timings,
+            ..
+        } = &mut result
+        {
+            *content_timings = Some(timings);
+        }
+        Ok(result.cell())
+    }
+
     #[turbo_tasks::function]
     pub fn rewrite(rewrite: RewriteVc) -> Self {
         StaticResult::Rewrite(rewrite).cell()
@@ -50,6 +90,22 @@ impl StaticResultVc {
 }
 
 /// Renders a module as static HTML in a node.js process.
+///
+/// When a `data_module` is passed, it's executed first and its result is
+/// passed to the renderer and embedded into the rendered HTML.
+///
+/// The renderer can either respond with the whole body at once, or stream it
+/// by sending the headers first, followed by body chunks.
+///
+/// When rendering takes longer than `render_timeout` milliseconds, the
+/// Node.js process is killed and a timeout error page is shown instead.
+///
+/// The `runtime` decides whether the module is executed in Node.js itself or
+/// in a sandbox of the edge runtime.
+///
+/// The `env_overrides` replace variables of `env` for this render only, e.g.
+/// for preview deployments, without creating another pool of Node.js
+/// processes. They aren't applied to the `data_module`.
 #[turbo_tasks::function]
 pub async fn render_static(
     cwd: FileSystemPathVc,
@@ -58,24 +114,56 @@ pub async fn render_static(
     module: EcmascriptModuleAssetVc,
     runtime_entries: EcmascriptChunkPlaceablesVc,
     fallback_page: DevHtmlAssetVc,
+    error_page: ErrorPageOptionsVc,
     chunking_context: ChunkingContextVc,
     intermediate_output_path: FileSystemPathVc,
     output_root: FileSystemPathVc,
     project_dir: FileSystemPathVc,
+    data_module: Option<StaticDataModuleVc>,
+    render_timeout: Option<u64>,
+    pool_options: NodeJsPoolOptionsVc,
+    runtime: Value<RuntimeKind>,
+    env_overrides: ProcessEnvVc,
     data: RenderDataVc,
 ) -> Result<StaticResultVc> {
-    let intermediate_asset = get_intermediate_asset(
-        module.as_evaluated_chunk(chunking_context, Some(runtime_entries)),
-        intermediate_output_path,
-    );
-    let renderer_pool = get_renderer_pool(
+    let request_id = data.await?.request_id.clone();
+    let static_data = match data_module {
+        Some(data_module) => load_static_data(data_module, chunking_context)
+            .await?
+            .clone_value(),
+        None => None,
+    };
+    let data = match &static_data {
+        Some(static_data) => {
+            let mut data = data.await?.clone_value();
+            data.static_data = Some(serde_json::from_str(static_data)?);
+            data.cell()
+        }
+        None => data,
+    };
+
+    let intermediate_asset = match runtime.into_value() {
+        RuntimeKind::NodeJs => get_intermediate_asset(
+            module.as_evaluated_chunk(chunking_context, Some(runtime_entries)),
+            intermediate_output_path,
+        ),
+        RuntimeKind::Edge => get_edge_intermediate_asset(
+            module,
+            Some(runtime_entries),
+            chunking_context,
+            intermediate_output_path,
+        ),
+    };
+    let renderer_pool = get_hot_renderer_pool(
         cwd,
         env,
         intermediate_asset,
         intermediate_output_path,
         output_root,
         project_dir,
-        /* debug */ false,
+        pool_options,
+        // Debugging is enabled by the `inspect_port` of the `pool_options`.
+        false,
     );
     // Read this strongly consistent, since we don't want to run inconsistent
     // node.js code.
@@ -83,48 +171,169 @@ pub async fn render_static(
     let mut operation = match pool.operation().await {
         Ok(operation) => operation,
         Err(err) => {
-            return Ok(StaticResultVc::content(
-                static_error(path, err, None, fallback_page).await?,
-                500,
-                HeaderListVc::empty(),
-            ))
+            return static_error(path, err, None, fallback_page, error_page, request_id).await
         }
     };
+    if let Some(request_id) = &request_id {
+        operation.set_output_prefix(format!("[{request_id}] "));
+    }
+    if let Some(render_timeout) = render_timeout {
+        operation.set_timeout(Duration::from_millis(render_timeout));
+    }
+    operation.capture_output();
 
-    Ok(
-        match run_static_operation(
+    let result = unless_aborted(
+        request_id.as_deref(),
+        run_static_operation(
             &mut operation,
             data,
+            env_overrides,
+            static_data.as_deref(),
             intermediate_asset,
             intermediate_output_path,
             project_dir,
-        )
-        .await
-        {
-            Ok(result) => result,
-            Err(err) => StaticResultVc::content(
-                static_error(path, err, Some(operation), fallback_page).await?,
-                500,
-                HeaderListVc::empty(),
-            ),
-        },
+            fallback_page,
+            error_page,
+        ),
     )
+    .await;
+    let Some(result) = result else {
+        // Nobody reads the response anymore, so the process is freed for
+        // other requests instead of finishing the render.
+        cancel(operation).await;
+        return Ok(StaticResultVc::content(
+            FileContent::NotFound.into(),
+            CLIENT_CLOSED_REQUEST,
+            HeaderListVc::cell(vec![]),
+        ));
+    };
+    // The output of streamed responses after their headers is only printed,
+    // since issues can't be emitted anymore once the response is returned.
+    RendererOutputIssue::emit_all(path, operation.take_output(), request_id.as_deref());
+    let timings = RenderTimingsVc::cell(operation.timings());
+
+    Ok(match result {
+        Ok(StaticOperationResult::Done(result)) => result.with_timings(timings),
+        Ok(StaticOperationResult::Streaming {
+            status_code,
+            headers,
+        }) => StaticResult::StreamedContent {
+            status_code,
+            headers: HeaderListVc::cell(headers),
+            body: stream_body(operation, static_data),
+            timings: Some(timings),
+        }
+        .cell(),
+        Err(err) => {
+            static_error(
+                path,
+                err,
+                Some(operation),
+                fallback_page,
+                error_page,
+                request_id,
+            )
+            .await?
+        }
+    })
+}
+
+/// Adds an `ETag` header with the hash of the rendered `body`, unless the
+/// renderer has set one, so repeated requests can be answered with
+/// `304 Not Modified`.
+fn with_etag(mut headers: Vec<(String, String)>, body: &str) -> Vec<(String, String)> {
+    if !headers
+        .iter()
+        .any(|(name, _)| name.eq_ignore_ascii_case("etag"))
+    {
+        let hash = encode_hex(hash_xxh3_hash64(body));
+        headers.push(("etag".to_string(), format!("\"{hash}\"")));
+    }
+    headers
+}
+
+/// Streams the body chunks of a response from the Node.js process. The
+/// operation is owned by a separate task until the body has ended, so the
+/// process is released even when the response isn't read to the end. Static
+/// data is embedded into the first chunk.
+///
+/// Once the headers have been sent, errors can't change the response anymore,
+/// so they abort the body instead of showing an error page.
+fn stream_body(mut operation: NodeJsOperation, mut static_data: Option<String>) -> Body {
+    let (sender, receiver) = unbounded();
+    tokio::spawn(async move {
+        let error = loop {
+            match operation.recv().await {
+                Ok(RenderStaticIncomingMessage::BodyChunk { data }) => {
+                    let data = match static_data.take() {
+                        Some(static_data) => embed_static_data(&data, &static_data),
+                        None => data,
+                    };
+                    // The body isn't read anymore when the browser disconnected,
+                    // so the rest of it isn't rendered.
+                    if sender.unbounded_send(Ok(Bytes::from(data))).is_err() {
+                        cancel(operation).await;
+                        return;
+                    }
+                }
+                Ok(RenderStaticIncomingMessage::BodyEnd) => return,
+                Ok(RenderStaticIncomingMessage::Error(error)) => {
+                    break format!("{}: {}", error.name, error.message);
+                }
+                Ok(_) => {
+                    break "unexpected response from the Node.js process while streaming the \
+                           response body"
+                        .to_string();
+                }
+                Err(err) => break format!("{}", PrettyPrintError(&err)),
+            }
+        };
+        let _ = sender.unbounded_send(Err(BodyError::new(error)));
+        // The process is in an unknown state after an error.
+        let _ = operation.wait_or_kill().await;
+    });
+    Body::from_stream(receiver)
+}
+
+/// Tells the renderer to stop rendering. The process isn't reused, since it
+/// might still be busy, and is killed when the `operation` is dropped.
+async fn cancel(mut operation: NodeJsOperation) {
+    let _ = operation.send(RenderStaticOutgoingMessage::Cancel).await;
+    operation.disallow_reuse();
+}
+
+/// The result of a render operation. Streamed responses keep using the
+/// operation after the headers have been received.
+enum StaticOperationResult {
+    Done(StaticResultVc),
+    Streaming {
+        status_code: u16,
+        headers: Vec<(String, String)>,
+    },
 }
 
 async fn run_static_operation(
     operation: &mut NodeJsOperation,
     data: RenderDataVc,
+    env_overrides: ProcessEnvVc,
+    static_data: Option<&str>,
     intermediate_asset: AssetVc,
     intermediate_output_path: FileSystemPathVc,
     project_dir: FileSystemPathVc,
-) -> Result<StaticResultVc> {
+    fallback_page: DevHtmlAssetVc,
+    error_page: ErrorPageOptionsVc,
+) -> Result<StaticOperationResult> {
     let data = data.await?;
+    let env_overrides = env_overrides.read_all().await?;
 
     operation
-        .send(RenderStaticOutgoingMessage::Headers { data: &data })
+        .send(RenderStaticOutgoingMessage::Headers {
+            data: &data,
+            env_overrides: &env_overrides,
+        })
         .await
         .context("sending headers to node.js process")?;
-    Ok(
+    Ok(StaticOperationResult::Done(
         match operation
             .recv()
             .await
@@ -133,28 +342,73 @@ async fn run_static_operation(
             RenderStaticIncomingMessage::Rewrite { path } => {
                 StaticResultVc::rewrite(RewriteBuilder::new(path).build())
             }
+            RenderStaticIncomingMessage::NotFound { headers } => {
+                let body = error_html_body(
+                    404,
+                    "This page could not be found".to_string(),
+                    String::new(),
+                    error_page,
+                )
+                .await?;
+                StaticResultVc::content(
+                    fallback_page.with_body(body.clone_value()).content(),
+                    404,
+                    HeaderListVc::cell(headers),
+                )
+            }
             RenderStaticIncomingMessage::Response {
                 status_code,
                 headers,
                 body,
-            } => StaticResultVc::content(
-                FileContent::Content(File::from(body)).into(),
+            } => {
+                let body = match static_data {
+                    Some(static_data) => embed_static_data(&body, static_data),
+                    None => body,
+                };
+                let headers = with_etag(headers, &body);
+                StaticResultVc::content(
+                    FileContent::Content(File::from(body)).into(),
+                    status_code,
+                    HeaderListVc::cell(headers),
+                )
+            }
+            RenderStaticIncomingMessage::ResponseHeaders {
                 status_code,
-                HeaderListVc::cell(headers),
-            ),
+                headers,
+            } => {
+                return Ok(StaticOperationResult::Streaming {
+                    status_code,
+                    headers,
+                })
+            }
+            RenderStaticIncomingMessage::BodyChunk { .. }
+            | RenderStaticIncomingMessage::BodyEnd => {
+                bail!("unexpected response from the Node.js process while waiting for the response")
+            }
             RenderStaticIncomingMessage::Error(error) => {
-                bail!(
-                    trace_stack(
-                        error,
-                        intermediate_asset,
-                        intermediate_output_path,
-                        project_dir
-                    )
-                    .await?
+                let (summary, code_frames) = trace_stack_with_code_frames(
+                    &error,
+                    intermediate_asset,
+                    intermediate_output_path,
+                    project_dir,
+                )
+                .await?;
+                let message = trace_stack(
+                    error,
+                    intermediate_asset,
+                    intermediate_output_path,
+                    project_dir,
                 )
+                .await?;
+                return Err(RenderError {
+                    message,
+                    summary,
+                    code_frames,
+                }
+                .into());
             }
         },
-    )
+    ))
 }
 
 async fn static_error(
@@ -162,18 +416,67 @@ async fn static_error(
     error: anyhow::Error,
     operation: Option<NodeJsOperation>,
     fallback_page: DevHtmlAssetVc,
-) -> Result<AssetContentVc> {
+    error_page: ErrorPageOptionsVc,
+    request_id: Option<String>,
+) -> Result<StaticResultVc> {
+    let status_code = error_status_code(&error);
+    let title = render_error_title(status_code);
     let status = match operation {
         Some(operation) => Some(operation.wait_or_kill().await?),
         None => None,
     };
 
-    let error = format!("{}", PrettyPrintError(&error));
-    let mut message = error
-        // TODO this is pretty inefficient
-        .replace('&', "&amp;")
-        .replace('>', "&gt;")
-        .replace('<', "&lt;");
+    // Errors of the renderer are shown with the code frames of their stack,
+    // instead of the text of the code frames.
+    let render_error = error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<RenderError>());
+    let (code_frames, details) = match render_error {
+        Some(render_error) => (
+            code_frames_html(&render_error.code_frames),
+            render_error.summary.clone(),
+        ),
+        None => (String::new(), format!("{}", PrettyPrintError(&error))),
+    };
+    let startup_error = error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<NodeJsPoolStartupError>());
+    let error = RenderingIssue::message_of(&error);
+    match startup_error {
+        Some(startup_error) => RendererStartupIssue::new(path, startup_error)
+            .cell()
+            .as_issue()
+            .emit(),
+        None => RenderingIssue {
+            context: path,
+            message: StringVc::cell(error),
+            status: status.and_then(|status| status.code()),
+            request_id: request_id.map(StringVc::cell),
+        }
+        .cell()
+        .as_issue()
+        .emit(),
+    }
+
+    if let Some(html) = custom_error_html(error_page, status_code, &details).await? {
+        return Ok(StaticResultVc::content(
+            FileContent::Content(
+                File::from(html.await?.as_str()).with_content_type(TEXT_HTML_UTF_8),
+            )
+            .into(),
+            status_code,
+            HeaderListVc::empty(),
+        ));
+    }
+
+    let mut message = code_frames;
+    message.push_str(
+        &details
+            // TODO this is pretty inefficient
+            .replace('&', "&amp;")
+            .replace('>', "&gt;")
+            .replace('<', "&lt;"),
+    );
 
     if let Some(status) = status {
         message.push_str(&format!("\n\nStatus: {}", status));
@@ -184,20 +487,128 @@ async fn static_error(
         .to_string();
 
     body.push_str(
-        error_html_body(500, "Error rendering page".to_string(), message)
+        error_html_body(status_code, title.to_string(), message, error_page)
             .await?
             .as_str(),
     );
 
-    let issue = RenderingIssue {
-        context: path,
-        message: StringVc::cell(error),
-        status: status.and_then(|status| status.code()),
+    let html = fallback_page.with_body(body);
+
+    Ok(StaticResultVc::content(
+        html.content(),
+        status_code,
+        HeaderListVc::empty(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{bail, Result};
+    use indexmap::IndexMap;
+    use turbo_tasks::TurboTasks;
+    use turbo_tasks_env::EnvMapVc;
+    use turbo_tasks_fs::{FileSystem, NullFileSystem, NullFileSystemVc};
+    use turbo_tasks_memory::MemoryBackend;
+    use turbopack_core::source_asset::SourceAssetVc;
+    use turbopack_dev_server::{html::DevHtmlAssetVc, source::query::Query};
+
+    use super::{run_static_operation, StaticOperationResult, StaticResult};
+    use crate::{
+        pool::{tests::script_pool, NodeJsPoolOptions},
+        render::{error_page::ErrorPageOptionsVc, RenderData},
     };
 
-    issue.cell().as_issue().emit();
+    /// A renderer which finds nothing to render for `/missing`, and renders an
+    /// empty page with status 404 for `/empty`.
+    const RENDERER: &str = r#"
+function handle(message) {
+  if (message.type !== "headers") {
+    return;
+  }
+  if (message.data.path === "/missing") {
+    send({ type: "notFound", headers: [] });
+  } else {
+    send({ type: "response", statusCode: 404, headers: [], body: "" });
+  }
+}
+"#;
 
-    let html = fallback_page.with_body(body);
+    /// Renders the `path` with the [RENDERER] and returns the status code and
+    /// the body of the response.
+    async fn render(path: &str) -> Result<(u16, String)> {
+        let (pool, _dir) = script_pool(RENDERER, &NodeJsPoolOptions::default())?;
+        let fs: NullFileSystemVc = NullFileSystem.into();
+        let data = RenderData {
+            params: IndexMap::new(),
+            method: "GET".to_string(),
+            url: path.to_string(),
+            raw_query: String::new(),
+            raw_headers: Vec::new(),
+            query: Query::default(),
+            cookies: IndexMap::new(),
+            path: path.to_string(),
+            static_data: None,
+            csp_nonce: None,
+            request_id: None,
+            fetch_cache_generation: 0,
+        }
+        .cell();
+        let mut operation = pool.operation().await?;
+        let result = run_static_operation(
+            &mut operation,
+            data,
+            EnvMapVc::empty().as_process_env(),
+            None,
+            SourceAssetVc::new(fs.root().join("renderer.js")).into(),
+            fs.root(),
+            fs.root(),
+            DevHtmlAssetVc::new(fs.root().join("index.html"), vec![]),
+            ErrorPageOptionsVc::empty(),
+        )
+        .await?;
+        let StaticOperationResult::Done(result) = result else {
+            bail!("the response is not streamed");
+        };
+        let StaticResult::Content {
+            content,
+            status_code,
+            ..
+        } = &*result.await?
+        else {
+            bail!("the response has no content");
+        };
+        let body = match content.file_content().await?.as_content() {
+            Some(file) => file.content().to_str()?.into_owned(),
+            None => String::new(),
+        };
+        Ok((*status_code, body))
+    }
 
-    Ok(html.content())
+    #[tokio::test]
+    async fn shows_the_not_found_page_when_the_renderer_asks_for_it() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let (status_code, body) = render("/missing").await?;
+            assert_eq!(status_code, 404);
+            assert!(body.contains("This page could not be found"), "{body}");
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn keeps_empty_404_responses() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let (status_code, body) = render("/empty").await?;
+            assert_eq!(status_code, 404);
+            assert_eq!(body, "");
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
 }
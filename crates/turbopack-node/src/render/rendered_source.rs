@@ -1,7 +1,9 @@
+use std::time::Duration;
+
 use anyhow::{anyhow, Result};
 use indexmap::IndexSet;
-use turbo_tasks::{primitives::StringVc, Value};
-use turbo_tasks_env::ProcessEnvVc;
+use turbo_tasks::{primitives::StringVc, ReadRef, Value};
+use turbo_tasks_env::{EnvMapVc, ProcessEnv, ProcessEnvVc};
 use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_core::{
     asset::{Asset, AssetsSetVc},
@@ -13,6 +15,8 @@ use turbopack_core::{
     resolve::PrimaryResolveResult,
 };
 use turbopack_dev_server::{
+    csp::CSP_NONCE_PLACEHOLDER,
+    fetch_cache,
     html::DevHtmlAssetVc,
     source::{
         asset_graph::AssetGraphContentSourceVc,
@@ -20,19 +24,23 @@ use turbopack_dev_server::{
         lazy_instantiated::{GetContentSource, GetContentSourceVc, LazyInstantiatedContentSource},
         specificity::SpecificityVc,
         ContentSource, ContentSourceContent, ContentSourceContentVc, ContentSourceData,
-        ContentSourceDataVary, ContentSourceDataVaryVc, ContentSourceResult, ContentSourceResultVc,
-        ContentSourceVc, GetContentSourceContent, GetContentSourceContentVc,
+        ContentSourceDataFilter, ContentSourceDataVary, ContentSourceDataVaryVc,
+        ContentSourceResult, ContentSourceResultVc, ContentSourceVc, GetContentSourceContent,
+        GetContentSourceContentVc, HeaderListVc, ProxyResult,
     },
 };
 use turbopack_ecmascript::chunk::EcmascriptChunkPlaceablesVc;
 
 use super::{
-    render_static::{render_static, StaticResult},
-    RenderData,
+    error_page::ErrorPageOptionsVc,
+    parse_cookies,
+    render_cache::{render_cache_key, RenderCacheVc},
+    render_static::{render_static, StaticResult, StaticResultVc},
+    with_server_timing, RenderData, RenderDataVc,
 };
 use crate::{
     external_asset_entrypoints, get_intermediate_asset,
-    node_entry::{NodeEntry, NodeEntryVc},
+    node_entry::{NodeEntry, NodeEntryVc, NodeRenderingEntry},
     route_matcher::{RouteMatcher, RouteMatcherVc},
 };
 
@@ -41,7 +49,8 @@ use crate::{
 /// all assets referenced by the `entry` that are within the `server_root`.
 /// It needs a temporary directory (`intermediate_output_path`) to place file
 /// for Node.js execution during rendering. The `chunking_context` should emit
-/// to this directory.
+/// to this directory. Pages shown when rendering fails are customized by
+/// `error_page`.
 #[turbo_tasks::function]
 pub fn create_node_rendered_source(
     cwd: FileSystemPathVc,
@@ -53,6 +62,7 @@ pub fn create_node_rendered_source(
     entry: NodeEntryVc,
     runtime_entries: EcmascriptChunkPlaceablesVc,
     fallback_page: DevHtmlAssetVc,
+    error_page: ErrorPageOptionsVc,
 ) -> ContentSourceVc {
     let source = NodeRenderContentSource {
         cwd,
@@ -64,6 +74,8 @@ pub fn create_node_rendered_source(
         entry,
         runtime_entries,
         fallback_page,
+        error_page,
+        render_cache: RenderCacheVc::empty(),
     }
     .cell();
     ConditionalContentSourceVc::new(
@@ -89,6 +101,8 @@ pub struct NodeRenderContentSource {
     entry: NodeEntryVc,
     runtime_entries: EcmascriptChunkPlaceablesVc,
     fallback_page: DevHtmlAssetVc,
+    error_page: ErrorPageOptionsVc,
+    render_cache: RenderCacheVc,
 }
 
 #[turbo_tasks::value_impl]
@@ -97,6 +111,13 @@ impl NodeRenderContentSourceVc {
     pub async fn get_pathname(self) -> Result<StringVc> {
         Ok(self.await?.pathname)
     }
+
+    /// The cache of the pages rendered by this source, e.g. to invalidate
+    /// them when their data changes.
+    #[turbo_tasks::function]
+    pub async fn render_cache(self) -> Result<RenderCacheVc> {
+        Ok(self.await?.render_cache)
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -183,7 +204,9 @@ impl GetContentSourceContent for NodeRenderGetContentResult {
             method: true,
             url: true,
             raw_headers: true,
+            query: Some(ContentSourceDataFilter::All),
             raw_query: true,
+            request_id: true,
             ..Default::default()
         }
         .cell()
@@ -199,49 +222,155 @@ impl GetContentSourceContent for NodeRenderGetContentResult {
             method: Some(method),
             url: Some(url),
             raw_headers: Some(raw_headers),
+            query: Some(query),
             raw_query: Some(raw_query),
             ..
         } = &*data else {
             return Err(anyhow!("Missing request data"));
         };
         let entry = source.entry.entry(data.clone()).await?;
-        let result = render_static(
-            source.cwd,
-            source.env,
-            source.server_root.join(&self.path),
-            entry.module,
-            source.runtime_entries,
-            source.fallback_page,
-            entry.chunking_context,
-            entry.intermediate_output_path,
-            entry.output_root,
-            entry.project_dir,
-            RenderData {
-                params: params.clone(),
-                method: method.clone(),
-                url: url.clone(),
-                raw_query: raw_query.clone(),
-                raw_headers: raw_headers.clone(),
-                path: format!("/{}", source.pathname.await?),
+        let render_data = RenderData {
+            params: params.clone(),
+            method: method.clone(),
+            url: url.clone(),
+            raw_query: raw_query.clone(),
+            raw_headers: raw_headers.clone(),
+            query: query.clone(),
+            cookies: parse_cookies(raw_headers),
+            path: format!("/{}", source.pathname.await?),
+            static_data: None,
+            csp_nonce: Some(CSP_NONCE_PLACEHOLDER.to_string()),
+            request_id: data.request_id.clone(),
+            fetch_cache_generation: fetch_cache::generation(),
+        };
+        let result = match entry.revalidate {
+            Some(revalidate) => {
+                let env_overrides = match entry.env_overrides {
+                    Some(env_overrides) => Some(env_overrides.read_all().await?),
+                    None => None,
+                };
+                let key = render_cache_key(&render_data, env_overrides.as_deref())?;
+                let cache = source.render_cache.await?;
+                match cache.get(&key, Duration::from_secs(revalidate)) {
+                    Some((result, revalidating)) => {
+                        if revalidating {
+                            revalidate_in_background(
+                                source.clone(),
+                                self.path.clone(),
+                                entry.clone(),
+                                key,
+                                render_data,
+                            );
+                        }
+                        result
+                    }
+                    None => {
+                        let result =
+                            render(&source, &self.path, &entry, render_data.clone().cell()).await?;
+                        cache.insert(key, &render_data.url, &*result.await?);
+                        result
+                    }
+                }
             }
-            .cell(),
-        )
-        .issue_context(
-            entry.module.ident().path(),
-            format!("server-side rendering /{}", source.pathname.await?),
-        )
-        .await?;
+            None => render(&source, &self.path, &entry, render_data.cell()).await?,
+        };
         Ok(match *result.await? {
             StaticResult::Content {
                 content,
                 status_code,
                 headers,
-            } => ContentSourceContentVc::static_with_headers(content.into(), status_code, headers),
+                timings,
+            } => ContentSourceContentVc::static_with_headers(
+                content.into(),
+                status_code,
+                HeaderListVc::cell(
+                    with_server_timing(headers.await?.clone_value(), timings).await?,
+                ),
+            ),
+            StaticResult::StreamedContent {
+                status_code,
+                headers,
+                ref body,
+                timings,
+            } => ContentSourceContent::HttpProxy(
+                ProxyResult {
+                    status: status_code,
+                    headers: with_server_timing(headers.await?.clone_value(), timings).await?,
+                    body: body.clone(),
+                    upgrade: None,
+                }
+                .cell(),
+            )
+            .cell(),
             StaticResult::Rewrite(rewrite) => ContentSourceContent::Rewrite(rewrite).cell(),
         })
     }
 }
 
+/// Renders the page of `source` at `path`.
+async fn render(
+    source: &NodeRenderContentSource,
+    path: &str,
+    entry: &NodeRenderingEntry,
+    data: RenderDataVc,
+) -> Result<StaticResultVc> {
+    render_static(
+        source.cwd,
+        source.env,
+        source.server_root.join(path),
+        entry.module,
+        source.runtime_entries,
+        source.fallback_page,
+        source.error_page,
+        entry.chunking_context,
+        entry.intermediate_output_path,
+        entry.output_root,
+        entry.project_dir,
+        entry.data_module,
+        entry.render_timeout,
+        entry.pool_options,
+        Value::new(entry.runtime),
+        entry
+            .env_overrides
+            .unwrap_or_else(|| EnvMapVc::empty().into()),
+        data,
+    )
+    .issue_context(
+        entry.module.ident().path(),
+        format!("server-side rendering /{}", source.pathname.await?),
+    )
+    .await
+}
+
+/// Renders a stale page of the render cache again, without blocking the
+/// request that serves it. Until it's rendered, the stale page is served.
+fn revalidate_in_background(
+    source: ReadRef<NodeRenderContentSource>,
+    path: String,
+    entry: ReadRef<NodeRenderingEntry>,
+    key: String,
+    data: RenderData,
+) {
+    let tt = turbo_tasks::turbo_tasks();
+    tokio::spawn(async move {
+        let _ = turbo_tasks::run_once(tt, async move {
+            let cache = source.render_cache.await?;
+            let result = async {
+                render(&source, &path, &entry, data.clone().cell())
+                    .await?
+                    .await
+            }
+            .await;
+            match result {
+                Ok(result) => cache.insert(key, &data.url, &result),
+                Err(_) => cache.revalidation_failed(&key),
+            }
+            Ok(())
+        })
+        .await;
+    });
+}
+
 #[turbo_tasks::function]
 fn introspectable_type() -> StringVc {
     StringVc::cell("node render content source".to_string())
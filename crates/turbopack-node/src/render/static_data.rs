@@ -0,0 +1,187 @@
+use anyhow::{Context, Result};
+use indexmap::indexmap;
+use turbo_tasks::{primitives::OptionStringVc, CompletionVc, Value};
+use turbo_tasks_bytes::stream::SingleValue;
+use turbo_tasks_env::ProcessEnvVc;
+use turbo_tasks_fs::{json::parse_json_with_source_context, File, FileSystemPathVc};
+use turbopack_core::{
+    asset::{Asset, AssetVc},
+    chunk::ChunkingContextVc,
+    context::{AssetContext, AssetContextVc},
+    virtual_asset::VirtualAssetVc,
+};
+use turbopack_dev_server::csp::CSP_NONCE_PLACEHOLDER;
+use turbopack_ecmascript::{
+    EcmascriptInputTransformsVc, EcmascriptModuleAssetType, EcmascriptModuleAssetVc, InnerAssetsVc,
+};
+
+use crate::evaluate::evaluate;
+
+/// The id of the script tag that contains the serialized static data in the
+/// rendered HTML.
+pub const STATIC_DATA_SCRIPT_ID: &str = "__turbopack_static_data__";
+
+/// A module that is executed before rendering to compute data for a page.
+///
+/// Its default export is called without arguments and returns a JSON
+/// serializable value (or a promise of one). The value is passed to the
+/// renderer as `staticData` and embedded into the rendered HTML, where client
+/// code can read it from `globalThis.__TURBOPACK_STATIC_DATA__`.
+///
+/// The data doesn't depend on the request, so it's computed once and only
+/// computed again when the module or one of its dependencies changes.
+#[turbo_tasks::value(shared)]
+pub struct StaticDataModule {
+    pub module: AssetVc,
+    /// The context used to process the module for execution in Node.js.
+    pub context: AssetContextVc,
+    /// The working directory of the Node.js process executing the module.
+    pub cwd: FileSystemPathVc,
+    /// The env of the Node.js process executing the module.
+    pub env: ProcessEnvVc,
+}
+
+#[turbo_tasks::value_impl]
+impl StaticDataModuleVc {
+    #[turbo_tasks::function]
+    pub fn new(
+        module: AssetVc,
+        context: AssetContextVc,
+        cwd: FileSystemPathVc,
+        env: ProcessEnvVc,
+    ) -> Self {
+        StaticDataModule {
+            module,
+            context,
+            cwd,
+            env,
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn executor(self) -> Result<AssetVc> {
+        let this = self.await?;
+        // `evaluate` calls the default export with the IPC channel as first
+        // argument, which data modules don't need to know about.
+        Ok(EcmascriptModuleAssetVc::new_with_inner_assets(
+            VirtualAssetVc::new(
+                this.module.ident().path().join("static-data.js"),
+                File::from("import * as data from 'DATA'; export default () => data.default();")
+                    .into(),
+            )
+            .into(),
+            this.context,
+            Value::new(EcmascriptModuleAssetType::Ecmascript),
+            EcmascriptInputTransformsVc::cell(vec![]),
+            Value::new(Default::default()),
+            this.context.compile_time_info(),
+            InnerAssetsVc::cell(indexmap! {
+                "DATA".to_string() => this.module
+            }),
+        )
+        .into())
+    }
+}
+
+/// Executes the data module in a Node.js process and returns the serialized
+/// JSON value it produced. Returns `None` when the execution failed, in which
+/// case an issue has been emitted.
+///
+/// This only depends on the data module and the chunking context, so all
+/// requests share the result.
+#[turbo_tasks::function]
+pub async fn load_static_data(
+    data_module: StaticDataModuleVc,
+    chunking_context: ChunkingContextVc,
+) -> Result<OptionStringVc> {
+    let this = data_module.await?;
+    let result = evaluate(
+        data_module.executor(),
+        this.cwd,
+        this.env,
+        this.module.ident(),
+        this.context,
+        chunking_context,
+        None,
+        vec![],
+        CompletionVc::immutable(),
+        /* debug */ false,
+    )
+    .await?;
+
+    let SingleValue::Single(value) = result.try_into_single().await? else {
+        // An error happened, which has already been converted into an issue.
+        return Ok(OptionStringVc::cell(None));
+    };
+    let value = value.to_str()?;
+    // Make sure this is valid JSON before embedding it.
+    let _: serde_json::Value = parse_json_with_source_context(value)
+        .context("Unable to deserialize the result of the static data module")?;
+    Ok(OptionStringVc::cell(Some(value.to_string())))
+}
+
+/// Embeds the serialized static data into a rendered HTML page, right before
+/// the closing `</head>` tag if there is one.
+pub(super) fn embed_static_data(html: &str, static_data: &str) -> String {
+    // The JSON is embedded into a script, so it must not close the tag. `<` can
+    // only appear within JSON strings, where it can be escaped.
+    let static_data = static_data.replace('<', "\\u003c");
+    let script = format!(
+        "<script id=\"{STATIC_DATA_SCRIPT_ID}\" \
+         nonce=\"{CSP_NONCE_PLACEHOLDER}\">globalThis.__TURBOPACK_STATIC_DATA__ = \
+         {static_data};</script>"
+    );
+    match html.find("</head>") {
+        Some(index) => format!("{}{}{}", &html[..index], script, &html[index..]),
+        None => format!("{}{}", script, html),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{embed_static_data, STATIC_DATA_SCRIPT_ID};
+
+    #[test]
+    fn embeds_before_head_end() {
+        let html = embed_static_data(
+            "<html><head><title>Page</title></head><body></body></html>",
+            r#"{"title":"Page"}"#,
+        );
+        assert_eq!(
+            html,
+            format!(
+                "<html><head><title>Page</title><script id=\"{STATIC_DATA_SCRIPT_ID}\" \
+                 nonce=\"{}\">globalThis.__TURBOPACK_STATIC_DATA__ = \
+                 {{\"title\":\"Page\"}};</script></head><body></body></html>",
+                super::CSP_NONCE_PLACEHOLDER
+            )
+        );
+    }
+
+    #[test]
+    fn embeds_at_start_without_head() {
+        let html = embed_static_data("<p>Page</p>", "1");
+        assert!(html.starts_with(&format!("<script id=\"{STATIC_DATA_SCRIPT_ID}\"")));
+        assert!(html.ends_with("__TURBOPACK_STATIC_DATA__ = 1;</script><p>Page</p>"));
+    }
+
+    #[test]
+    fn escapes_closing_script_tags() {
+        let data = serde_json::json!({ "html": "</script><script>alert(1)</script>" });
+        let html = embed_static_data("", &data.to_string());
+        // The only closing tag is the one of the embedding script.
+        assert_eq!(html.matches("</script>").count(), 1);
+        assert!(!html.contains("<script>"));
+
+        // The escaped JSON still evaluates to the same value.
+        let embedded = html
+            .split_once(" = ")
+            .and_then(|(_, rest)| rest.strip_suffix(";</script>"))
+            .unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(embedded).unwrap(),
+            data
+        );
+    }
+}
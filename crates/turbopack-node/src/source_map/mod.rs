@@ -235,6 +235,46 @@ async fn resolve_source_mapping(
     }
 }
 
+/// The source around a location in a project file.
+#[derive(Clone, Debug)]
+pub struct CodeFrame {
+    /// The path of the file, relative to the project directory.
+    pub path: String,
+    /// The 1-based line of the location.
+    pub line: usize,
+    /// The 1-based column of the location.
+    pub column: usize,
+    /// The 1-based line of the first of the `lines`.
+    pub first_line: usize,
+    /// The lines around the location.
+    pub lines: Vec<String>,
+}
+
+impl CodeFrame {
+    /// The lines of context before and after the location.
+    const CONTEXT_LINES: usize = 4;
+
+    fn new<'a>(
+        path: &str,
+        lines: impl Iterator<Item = &'a str>,
+        line: usize,
+        column: usize,
+    ) -> Self {
+        let first_line = line.saturating_sub(Self::CONTEXT_LINES).max(1);
+        CodeFrame {
+            path: path.to_string(),
+            line,
+            column,
+            first_line,
+            lines: lines
+                .skip(first_line - 1)
+                .take(line + Self::CONTEXT_LINES + 1 - first_line)
+                .map(|line| line.to_string())
+                .collect(),
+        }
+    }
+}
+
 #[turbo_tasks::value(shared)]
 #[derive(Clone, Debug)]
 pub struct StructuredError {
@@ -282,6 +322,63 @@ impl StructuredError {
         }
         Ok(message)
     }
+
+    /// Like [StructuredError::print], but without code frames in the text.
+    /// The code frames of the project files are returned separately instead,
+    /// e.g. to render them as HTML.
+    pub async fn print_with_code_frames(
+        &self,
+        assets_for_source_mapping: AssetsForSourceMappingVc,
+        root: FileSystemPathVc,
+        project_dir: FileSystemPathVc,
+    ) -> Result<(String, Vec<CodeFrame>)> {
+        let mut message = String::new();
+        let mut code_frames = Vec::new();
+
+        let magic = |content| FormattingMode::Plain.magic_identifier(content);
+
+        write!(
+            message,
+            "{}: {}",
+            self.name,
+            unmangle_identifiers(&self.message, magic)
+        )?;
+
+        let mut first_error = true;
+        // Suppresses the code frames in the text.
+        let mut visible_code_frames = MAX_CODE_FRAMES;
+
+        for frame in &self.stack {
+            let frame = frame.unmangle_identifiers(magic);
+            let resolved =
+                resolve_source_mapping(assets_for_source_mapping, root, project_dir.root(), &frame)
+                    .await;
+            if let Ok(ResolvedSourceMapping::MappedProject {
+                frame: mapped_frame,
+                project_path,
+                lines,
+            }) = &resolved
+            {
+                if let (FileLinesContent::Lines(lines), Some((line, column))) =
+                    (&**lines, mapped_frame.get_pos())
+                {
+                    if code_frames.len() < MAX_CODE_FRAMES {
+                        let lines = lines.iter().map(|l| l.content.as_str());
+                        code_frames.push(CodeFrame::new(&project_path.path, lines, line, column));
+                    }
+                }
+            }
+            write_resolved(
+                &mut message,
+                resolved,
+                &frame,
+                &mut first_error,
+                &mut visible_code_frames,
+                FormattingMode::Plain,
+            )?;
+        }
+        Ok((message, code_frames))
+    }
 }
 
 pub async fn trace_stack(
@@ -301,3 +398,18 @@ pub async fn trace_stack(
         )
         .await
 }
+
+/// Like [trace_stack], but returns the code frames of the project files
+/// separately, see [StructuredError::print_with_code_frames].
+pub async fn trace_stack_with_code_frames(
+    error: &StructuredError,
+    root_asset: AssetVc,
+    output_path: FileSystemPathVc,
+    project_dir: FileSystemPathVc,
+) -> Result<(String, Vec<CodeFrame>)> {
+    let assets_for_source_mapping = internal_assets_for_source_mapping(root_asset, output_path);
+
+    error
+        .print_with_code_frames(assets_for_source_mapping, output_path, project_dir)
+        .await
+}
@@ -0,0 +1,162 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use turbo_tasks::{primitives::JsonValueVc, CompletionVc, Value};
+use turbo_tasks_bytes::stream::SingleValue;
+use turbo_tasks_fs::{json::parse_json_with_source_context, File, FileContent};
+use turbopack_core::{
+    asset::{Asset, AssetContent, AssetContentVc, AssetVc},
+    context::{AssetContext, AssetContextVc},
+    ident::AssetIdentVc,
+    source_asset::SourceAssetVc,
+    source_transform::{SourceTransform, SourceTransformVc},
+};
+use turbopack_ecmascript::{
+    EcmascriptInputTransform, EcmascriptInputTransformsVc, EcmascriptModuleAssetType,
+    EcmascriptModuleAssetVc,
+};
+
+use crate::{
+    embed_js::embed_file_path,
+    evaluate::evaluate,
+    execution_context::{ExecutionContext, ExecutionContextVc},
+};
+
+/// A source transform that subsets woff2 fonts to the glyphs of the given
+/// unicode ranges, e.g. `U+0000-00FF` or `U+4??`, using the `subset-font`
+/// package of the project.
+///
+/// The glyphs a page renders can't be determined statically in general, so
+/// the ranges need to be configured. When subsetting fails, e.g. because
+/// `subset-font` isn't installed, the font is emitted unchanged.
+#[turbo_tasks::value]
+pub struct FontSubsetTransform {
+    evaluate_context: AssetContextVc,
+    execution_context: ExecutionContextVc,
+    unicode_ranges: Vec<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl FontSubsetTransformVc {
+    #[turbo_tasks::function]
+    pub fn new(
+        evaluate_context: AssetContextVc,
+        execution_context: ExecutionContextVc,
+        unicode_ranges: Vec<String>,
+    ) -> Self {
+        FontSubsetTransform {
+            evaluate_context,
+            execution_context,
+            unicode_ranges,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl SourceTransform for FontSubsetTransform {
+    #[turbo_tasks::function]
+    fn transform(&self, source: AssetVc) -> AssetVc {
+        SubsetFontAsset {
+            evaluate_context: self.evaluate_context,
+            execution_context: self.execution_context,
+            unicode_ranges: self.unicode_ranges.clone(),
+            source,
+        }
+        .cell()
+        .into()
+    }
+}
+
+#[turbo_tasks::value]
+struct SubsetFontAsset {
+    evaluate_context: AssetContextVc,
+    execution_context: ExecutionContextVc,
+    unicode_ranges: Vec<String>,
+    source: AssetVc,
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for SubsetFontAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> AssetIdentVc {
+        self.source.ident()
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<AssetContentVc> {
+        if self.unicode_ranges.is_empty() {
+            return Ok(self.source.content());
+        }
+        Ok(subset_font(
+            self.evaluate_context,
+            self.execution_context,
+            self.unicode_ranges.clone(),
+            self.source,
+        ))
+    }
+}
+
+#[turbo_tasks::function]
+fn font_subset_executor(context: AssetContextVc) -> AssetVc {
+    EcmascriptModuleAssetVc::new(
+        SourceAssetVc::new(embed_file_path("transforms/font-subset.ts")).into(),
+        context,
+        Value::new(EcmascriptModuleAssetType::Typescript),
+        EcmascriptInputTransformsVc::cell(vec![EcmascriptInputTransform::TypeScript {
+            use_define_for_class_fields: false,
+        }]),
+        Value::new(Default::default()),
+        context.compile_time_info(),
+    )
+    .into()
+}
+
+#[turbo_tasks::function]
+async fn subset_font(
+    evaluate_context: AssetContextVc,
+    execution_context: ExecutionContextVc,
+    unicode_ranges: Vec<String>,
+    source: AssetVc,
+) -> Result<AssetContentVc> {
+    let ExecutionContext {
+        project_path,
+        chunking_context,
+        env,
+    } = *execution_context.await?;
+    let AssetContent::File(file) = *source.content().await? else {
+        return Ok(source.content());
+    };
+    let FileContent::Content(content) = &*file.await? else {
+        return Ok(source.content());
+    };
+    let engine = base64::engine::general_purpose::STANDARD;
+    let encoded = engine.encode(content.content().to_bytes()?);
+
+    let result = evaluate(
+        font_subset_executor(evaluate_context),
+        project_path,
+        env,
+        source.ident(),
+        evaluate_context,
+        chunking_context,
+        None,
+        vec![
+            JsonValueVc::cell(encoded.into()),
+            JsonValueVc::cell(unicode_ranges.into()),
+        ],
+        CompletionVc::immutable(),
+        /* debug */ false,
+    )
+    .await?;
+
+    let SingleValue::Single(val) = result.try_into_single().await? else {
+        // An error happened, which has already been converted into an issue.
+        return Ok(source.content());
+    };
+    let subset: String = parse_json_with_source_context(val.to_str()?)
+        .context("Unable to deserialize response from font subsetting")?;
+    let subset = engine
+        .decode(subset)
+        .context("Font subsetting returned invalid base64")?;
+    Ok(File::from(subset).into())
+}
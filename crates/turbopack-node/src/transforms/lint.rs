@@ -0,0 +1,318 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{
+    primitives::{JsonValueVc, StringVc},
+    trace::TraceRawVcs,
+    CompletionVc, Value,
+};
+use turbo_tasks_bytes::stream::SingleValue;
+use turbo_tasks_fs::{json::parse_json_with_source_context, FileContent, FileSystemPathVc};
+use turbopack_core::{
+    asset::{Asset, AssetContent, AssetContentVc, AssetVc},
+    context::{AssetContext, AssetContextVc},
+    ident::AssetIdentVc,
+    issue::{
+        Issue, IssueSeverity, IssueSeverityVc, IssueSource, IssueSourceVc, IssueSuggestion,
+        IssueSuggestionEdit, IssueSuggestionVc, IssueSuggestionsVc, IssueVc, OptionIssueSourceVc,
+    },
+    source_asset::SourceAssetVc,
+    source_pos::SourcePos,
+    source_transform::{SourceTransform, SourceTransformVc},
+};
+use turbopack_ecmascript::{
+    EcmascriptInputTransform, EcmascriptInputTransformsVc, EcmascriptModuleAssetType,
+    EcmascriptModuleAssetVc,
+};
+
+use crate::{
+    embed_js::embed_file_path,
+    evaluate::evaluate,
+    execution_context::{ExecutionContext, ExecutionContextVc},
+};
+
+/// The linter that is run on source files.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(Debug, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum Linter {
+    /// Uses the `eslint` package of the project and its configuration.
+    Eslint,
+    /// Runs the `biome` binary, which needs to be available in `PATH`.
+    Biome,
+}
+
+impl Linter {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Linter::Eslint => "eslint",
+            Linter::Biome => "biome",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+struct LintPosition {
+    line: usize,
+    column: usize,
+}
+
+impl From<LintPosition> for SourcePos {
+    fn from(position: LintPosition) -> Self {
+        SourcePos {
+            line: position.line,
+            column: position.column,
+        }
+    }
+}
+
+/// A fix of a [LintMessage], which replaces the code between `start` and
+/// `end` with `text`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+struct LintFix {
+    title: String,
+    start: LintPosition,
+    end: LintPosition,
+    text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+struct LintMessage {
+    rule: Option<String>,
+    message: String,
+    start: LintPosition,
+    end: LintPosition,
+    #[serde(default)]
+    fixes: Vec<LintFix>,
+}
+
+/// A source transform that lints files without changing them.
+///
+/// Linting happens in a separate task which the transformed content doesn't
+/// wait for, so it runs in parallel to the rest of the compilation and never
+/// delays emitting. Lint messages are reported as [LintIssue]s once the linter
+/// has finished.
+#[turbo_tasks::value]
+pub struct LintTransform {
+    evaluate_context: AssetContextVc,
+    execution_context: ExecutionContextVc,
+    linter: Linter,
+}
+
+#[turbo_tasks::value_impl]
+impl LintTransformVc {
+    #[turbo_tasks::function]
+    pub fn new(
+        evaluate_context: AssetContextVc,
+        execution_context: ExecutionContextVc,
+        linter: Value<Linter>,
+    ) -> Self {
+        LintTransform {
+            evaluate_context,
+            execution_context,
+            linter: linter.into_value(),
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl SourceTransform for LintTransform {
+    #[turbo_tasks::function]
+    fn transform(&self, source: AssetVc) -> AssetVc {
+        LintedAsset {
+            evaluate_context: self.evaluate_context,
+            execution_context: self.execution_context,
+            linter: self.linter,
+            source,
+        }
+        .cell()
+        .into()
+    }
+}
+
+#[turbo_tasks::value]
+struct LintedAsset {
+    evaluate_context: AssetContextVc,
+    execution_context: ExecutionContextVc,
+    linter: Linter,
+    source: AssetVc,
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for LintedAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> AssetIdentVc {
+        self.source.ident()
+    }
+
+    #[turbo_tasks::function]
+    fn content(&self) -> AssetContentVc {
+        // Intentionally not awaited, see [LintTransform].
+        let _ = lint(
+            self.evaluate_context,
+            self.execution_context,
+            Value::new(self.linter),
+            self.source,
+        );
+        self.source.content()
+    }
+}
+
+#[turbo_tasks::function]
+fn lint_executor(context: AssetContextVc) -> AssetVc {
+    EcmascriptModuleAssetVc::new(
+        SourceAssetVc::new(embed_file_path("transforms/lint.ts")).into(),
+        context,
+        Value::new(EcmascriptModuleAssetType::Typescript),
+        EcmascriptInputTransformsVc::cell(vec![EcmascriptInputTransform::TypeScript {
+            use_define_for_class_fields: false,
+        }]),
+        Value::new(Default::default()),
+        context.compile_time_info(),
+    )
+    .into()
+}
+
+#[turbo_tasks::function]
+async fn lint(
+    evaluate_context: AssetContextVc,
+    execution_context: ExecutionContextVc,
+    linter: Value<Linter>,
+    source: AssetVc,
+) -> Result<CompletionVc> {
+    let ExecutionContext {
+        project_path,
+        chunking_context,
+        env,
+    } = *execution_context.await?;
+    let AssetContent::File(file) = *source.content().await? else {
+        return Ok(CompletionVc::new());
+    };
+    let FileContent::Content(content) = &*file.await? else {
+        return Ok(CompletionVc::new());
+    };
+    let content = content.content().to_str()?;
+    let source_fs_path = source.ident().path().await?;
+
+    let result = evaluate(
+        lint_executor(evaluate_context),
+        project_path,
+        env,
+        source.ident(),
+        evaluate_context,
+        chunking_context,
+        None,
+        vec![
+            JsonValueVc::cell(linter.as_str().into()),
+            JsonValueVc::cell(content.into()),
+            JsonValueVc::cell(source_fs_path.path.as_str().into()),
+        ],
+        CompletionVc::immutable(),
+        /* debug */ false,
+    )
+    .await?;
+
+    let SingleValue::Single(val) = result.try_into_single().await? else {
+        // An error happened, which has already been converted into an issue.
+        return Ok(CompletionVc::new());
+    };
+    let messages: Vec<LintMessage> = parse_json_with_source_context(val.to_str()?)
+        .context("Unable to deserialize response from lint operation")?;
+
+    for message in messages {
+        let suggestions = message
+            .fixes
+            .into_iter()
+            .map(|fix| {
+                IssueSuggestion {
+                    title: fix.title,
+                    edits: vec![IssueSuggestionEdit {
+                        source: IssueSource {
+                            asset: source,
+                            start: fix.start.into(),
+                            end: fix.end.into(),
+                        }
+                        .cell(),
+                        replacement: fix.text,
+                    }],
+                }
+                .cell()
+            })
+            .collect();
+        LintIssue {
+            context: source.ident().path(),
+            linter: *linter,
+            rule: message.rule,
+            message: message.message,
+            source: IssueSource {
+                asset: source,
+                start: message.start.into(),
+                end: message.end.into(),
+            }
+            .cell(),
+            suggestions,
+        }
+        .cell()
+        .as_issue()
+        .emit();
+    }
+    Ok(CompletionVc::new())
+}
+
+/// A message reported by a [Linter]. Lint messages never fail the build, so
+/// they are always reported as warnings.
+#[turbo_tasks::value(shared)]
+pub struct LintIssue {
+    pub context: FileSystemPathVc,
+    pub linter: Linter,
+    pub rule: Option<String>,
+    pub message: String,
+    pub source: IssueSourceVc,
+    /// The fixes offered by the linter.
+    pub suggestions: Vec<IssueSuggestionVc>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for LintIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("lint".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell(match &self.rule {
+            Some(rule) => format!("{} {}", self.linter.as_str(), rule),
+            None => self.linter.as_str().to_string(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.context
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> StringVc {
+        StringVc::cell(self.message.clone())
+    }
+
+    #[turbo_tasks::function]
+    fn code(&self) -> StringVc {
+        StringVc::cell(self.rule.clone().unwrap_or_default())
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> OptionIssueSourceVc {
+        OptionIssueSourceVc::cell(Some(self.source))
+    }
+
+    #[turbo_tasks::function]
+    fn suggestions(&self) -> IssueSuggestionsVc {
+        IssueSuggestionsVc::cell(self.suggestions.clone())
+    }
+}
@@ -1,3 +1,5 @@
+pub mod font_subset;
+pub mod lint;
 pub mod postcss;
 mod util;
 pub mod webpack;
@@ -0,0 +1,341 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use turbo_tasks::{
+    primitives::{JsonValueVc, StringVc},
+    trace::TraceRawVcs,
+    CompletionVc, Value,
+};
+use turbo_tasks_bytes::stream::SingleValue;
+use turbo_tasks_fs::{json::parse_json_with_source_context, File, FileSystemPathVc};
+use turbopack_core::{
+    asset::{AssetContentVc, AssetVc},
+    context::{AssetContext, AssetContextVc},
+    ident::AssetIdentVc,
+    introspect::{Introspectable, IntrospectableVc},
+    issue::{Issue, IssueSeverity, IssueSeverityVc, IssueSource, IssueVc, OptionIssueSourceVc},
+    source_asset::SourceAssetVc,
+    source_pos::SourcePos,
+};
+use turbopack_dev_server::source::{
+    ContentSource, ContentSourceContentVc, ContentSourceData, ContentSourceResultVc,
+    ContentSourceVc,
+};
+use turbopack_ecmascript::{
+    EcmascriptInputTransform, EcmascriptInputTransformsVc, EcmascriptModuleAssetType,
+    EcmascriptModuleAssetVc,
+};
+
+use crate::{
+    embed_js::embed_file_path,
+    evaluate::evaluate,
+    execution_context::{ExecutionContext, ExecutionContextVc},
+};
+
+/// The program used to type-check a project.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(Debug, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum TypeChecker {
+    /// Uses the `typescript` package of the project. The compiler stays alive
+    /// in the pooled Node.js process, so subsequent checks are incremental.
+    Tsc,
+    /// Runs the native `tsgo` binary, which needs to be available in `PATH`.
+    Tsgo,
+}
+
+impl TypeChecker {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TypeChecker::Tsc => "tsc",
+            TypeChecker::Tsgo => "tsgo",
+        }
+    }
+}
+
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct TypeCheckOptions {
+    pub checker: TypeChecker,
+    /// The path of the `tsconfig.json`, relative to the project path.
+    pub tsconfig: String,
+}
+
+impl Default for TypeCheckOptions {
+    fn default() -> Self {
+        TypeCheckOptions {
+            checker: TypeChecker::Tsc,
+            tsconfig: "tsconfig.json".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+enum TypeCheckDiagnosticCategory {
+    Error,
+    Warning,
+    Suggestion,
+    Message,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+struct TypeCheckPosition {
+    line: usize,
+    column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+struct TypeCheckDiagnostic {
+    /// The file relative to the project path.
+    file: Option<String>,
+    start: Option<TypeCheckPosition>,
+    end: Option<TypeCheckPosition>,
+    code: u32,
+    category: TypeCheckDiagnosticCategory,
+    message: String,
+}
+
+/// A summary of a type check. The diagnostics themselves are emitted as
+/// [TypeCheckIssue]s.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Default)]
+pub struct TypeCheckResult {
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+#[turbo_tasks::function]
+fn type_check_executor(context: AssetContextVc) -> AssetVc {
+    EcmascriptModuleAssetVc::new(
+        SourceAssetVc::new(embed_file_path("type-check.ts")).into(),
+        context,
+        Value::new(EcmascriptModuleAssetType::Typescript),
+        EcmascriptInputTransformsVc::cell(vec![EcmascriptInputTransform::TypeScript {
+            use_define_for_class_fields: false,
+        }]),
+        Value::new(Default::default()),
+        context.compile_time_info(),
+    )
+    .into()
+}
+
+/// Type-checks the project in a pooled Node.js process and emits a
+/// [TypeCheckIssue] for every diagnostic.
+///
+/// The checker reports all files it read as dependencies, so the check is
+/// invalidated and re-run when one of them changes. Nothing depends on the type
+/// check when building, so it needs to be run separately (e.g. through
+/// [TypeCheckContentSource]) and never blocks compilation or HMR.
+#[turbo_tasks::function]
+pub async fn type_check(
+    evaluate_context: AssetContextVc,
+    execution_context: ExecutionContextVc,
+    options: TypeCheckOptionsVc,
+) -> Result<TypeCheckResultVc> {
+    let ExecutionContext {
+        project_path,
+        chunking_context,
+        env,
+    } = *execution_context.await?;
+    let options = options.await?;
+    let tsconfig_path = project_path.join(&options.tsconfig);
+
+    let result = evaluate(
+        type_check_executor(evaluate_context),
+        project_path,
+        env,
+        AssetIdentVc::from_path(tsconfig_path),
+        evaluate_context,
+        chunking_context,
+        None,
+        vec![
+            JsonValueVc::cell(options.checker.as_str().into()),
+            JsonValueVc::cell(options.tsconfig.clone().into()),
+        ],
+        CompletionVc::immutable(),
+        /* debug */ false,
+    )
+    .await?;
+
+    let SingleValue::Single(val) = result.try_into_single().await? else {
+        // An error happened, which has already been converted into an issue.
+        return Ok(TypeCheckResult::default().cell());
+    };
+    let diagnostics: Vec<TypeCheckDiagnostic> = parse_json_with_source_context(val.to_str()?)
+        .context("Unable to deserialize response from type check operation")?;
+
+    let mut result = TypeCheckResult::default();
+    for diagnostic in diagnostics {
+        let severity = match diagnostic.category {
+            TypeCheckDiagnosticCategory::Error => {
+                result.errors += 1;
+                IssueSeverity::Error
+            }
+            TypeCheckDiagnosticCategory::Warning => {
+                result.warnings += 1;
+                IssueSeverity::Warning
+            }
+            TypeCheckDiagnosticCategory::Suggestion | TypeCheckDiagnosticCategory::Message => {
+                IssueSeverity::Hint
+            }
+        };
+        let (context, source) = match &diagnostic.file {
+            Some(file) => {
+                let path = project_path.join(file);
+                let source = diagnostic.start.as_ref().map(|start| {
+                    let end = diagnostic.end.as_ref().unwrap_or(start);
+                    IssueSource {
+                        asset: SourceAssetVc::new(path).into(),
+                        start: SourcePos {
+                            line: start.line,
+                            column: start.column,
+                        },
+                        end: SourcePos {
+                            line: end.line,
+                            column: end.column,
+                        },
+                    }
+                    .cell()
+                });
+                (path, source)
+            }
+            None => (tsconfig_path, None),
+        };
+        TypeCheckIssue {
+            context,
+            severity: severity.cell(),
+            code: diagnostic.code,
+            message: diagnostic.message,
+            source: OptionIssueSourceVc::cell(source),
+        }
+        .cell()
+        .as_issue()
+        .emit();
+    }
+    Ok(result.cell())
+}
+
+#[turbo_tasks::value(shared)]
+pub struct TypeCheckIssue {
+    pub context: FileSystemPathVc,
+    pub severity: IssueSeverityVc,
+    pub code: u32,
+    pub message: String,
+    pub source: OptionIssueSourceVc,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for TypeCheckIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        self.severity
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("type-check".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell(format!("Type error TS{}", self.code))
+    }
+
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.context
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> StringVc {
+        StringVc::cell(self.message.clone())
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> OptionIssueSourceVc {
+        self.source
+    }
+}
+
+/// Serves the summary of a [type_check] at a single path.
+///
+/// Clients can subscribe to this path through the update server to receive
+/// type errors as issues. This keeps the type check on its own update stream,
+/// so it runs asynchronously and doesn't delay updates of chunks.
+#[turbo_tasks::value(shared)]
+pub struct TypeCheckContentSource {
+    pub path: String,
+    pub evaluate_context: AssetContextVc,
+    pub execution_context: ExecutionContextVc,
+    pub options: TypeCheckOptionsVc,
+}
+
+#[turbo_tasks::value_impl]
+impl TypeCheckContentSourceVc {
+    #[turbo_tasks::function]
+    pub fn new(
+        path: String,
+        evaluate_context: AssetContextVc,
+        execution_context: ExecutionContextVc,
+        options: TypeCheckOptionsVc,
+    ) -> Self {
+        TypeCheckContentSource {
+            path,
+            evaluate_context,
+            execution_context,
+            options,
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn content(self) -> Result<AssetContentVc> {
+        let this = self.await?;
+        let result =
+            type_check(this.evaluate_context, this.execution_context, this.options).await?;
+        Ok(File::from(
+            json!({
+                "errors": result.errors,
+                "warnings": result.warnings,
+            })
+            .to_string(),
+        )
+        .into())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ContentSource for TypeCheckContentSource {
+    #[turbo_tasks::function]
+    async fn get(
+        self_vc: TypeCheckContentSourceVc,
+        path: &str,
+        _data: Value<ContentSourceData>,
+    ) -> Result<ContentSourceResultVc> {
+        let this = self_vc.await?;
+        if path != this.path {
+            return Ok(ContentSourceResultVc::not_found());
+        }
+        Ok(ContentSourceResultVc::exact(
+            ContentSourceContentVc::static_content(self_vc.content().into()).into(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Introspectable for TypeCheckContentSource {
+    #[turbo_tasks::function]
+    fn ty(&self) -> StringVc {
+        StringVc::cell("type check content source".to_string())
+    }
+
+    #[turbo_tasks::function]
+    async fn details(&self) -> Result<StringVc> {
+        let options = self.options.await?;
+        Ok(StringVc::cell(format!(
+            "checker: {}\ntsconfig: {}",
+            options.checker.as_str(),
+            options.tsconfig
+        )))
+    }
+}
@@ -2,3 +2,4 @@
 #![feature(str_split_remainder)]
 
 pub mod emitter;
+pub mod source_file;
@@ -0,0 +1,15 @@
+use swc_core::common::{sync::Lrc, FileName, SourceFile, SourceMap};
+
+/// Adds a source file with the `text` of a file to the `source_map`. Unlike
+/// [SourceMap::new_source_file], the text is shared instead of copied, unless
+/// it starts with a byte order mark, which swc expects to be removed.
+pub fn new_shared_source_file(
+    source_map: &SourceMap,
+    name: FileName,
+    text: Lrc<String>,
+) -> Lrc<SourceFile> {
+    match text.strip_prefix('\u{feff}') {
+        Some(without_bom) => source_map.new_source_file(name, without_bom.to_string()),
+        None => source_map.new_source_file_from(name, text),
+    }
+}
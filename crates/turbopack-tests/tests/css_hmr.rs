@@ -0,0 +1,71 @@
+#![cfg(test)]
+
+mod util;
+
+use anyhow::Result;
+use turbo_tasks::TurboTasks;
+use turbo_tasks_memory::MemoryBackend;
+use util::{
+    asset_context, browser_env, chunking_context_builder, entry_module, fixture_root, text_content,
+};
+
+fn register() {
+    turbopack::register();
+    turbopack_dev::register();
+    include!(concat!(env!("OUT_DIR"), "/register_test_css_hmr.rs"));
+}
+
+/// The evaluated chunk of `tests/css_hmr/index.js`, which imports a CSS
+/// module composing a class of another one.
+async fn chunk_code(hmr: bool) -> Result<String> {
+    let root = fixture_root("css_hmr")?;
+    let env = browser_env("last 1 Chrome versions");
+    let mut builder = chunking_context_builder(root, env);
+    if hmr {
+        builder = builder.hot_module_replacement();
+    }
+    let chunking_context = builder.build();
+
+    let module = entry_module(asset_context(env), root, "index.js").await?;
+    let chunk = module.as_evaluated_chunk(chunking_context, None);
+    text_content(chunk.into()).await
+}
+
+#[tokio::test]
+async fn css_module_updates_patch_the_class_map() {
+    register();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let code = tt
+        .run_once(async move { chunk_code(true).await })
+        .await
+        .unwrap();
+    assert!(code.contains("let classes = {\n"), "{code}");
+    // Composed classes are looked up on access, so updates of the composed
+    // module are picked up.
+    assert!(code.contains("  get \"button\"() { return "), "{code}");
+    assert!(code.contains("  \"title\": "), "{code}");
+    // The class map of the previous instance is patched, so the importers
+    // aren't re-evaluated.
+    assert!(
+        code.contains("const prevClasses = module.hot.data.classes;"),
+        "{code}"
+    );
+    assert!(code.contains("classes = prevClasses;"), "{code}");
+    assert!(code.contains("data.classes = classes;"), "{code}");
+    assert!(
+        code.contains("__turbopack_export_value__(classes);"),
+        "{code}"
+    );
+}
+
+#[tokio::test]
+async fn css_modules_export_the_class_map_without_hmr() {
+    register();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let code = tt
+        .run_once(async move { chunk_code(false).await })
+        .await
+        .unwrap();
+    assert!(code.contains("__turbopack_export_value__({\n"), "{code}");
+    assert!(!code.contains("prevClasses"), "{code}");
+}
@@ -0,0 +1,3 @@
+import styles from "./style.module.css";
+
+console.log(styles.button);
@@ -0,0 +1,128 @@
+#![cfg(test)]
+
+mod util;
+
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use serde_json::{json, Value as JsonValue};
+use tempfile::TempDir;
+use turbo_tasks::{TurboTasks, Value};
+use turbo_tasks_env::EnvMapVc;
+use turbo_tasks_memory::MemoryBackend;
+use turbopack::{
+    ecmascript::EcmascriptModuleAssetVc,
+    evaluate_context::{node_build_environment, node_evaluate_asset_context},
+};
+use turbopack_core::{
+    context::AssetContext,
+    reference_type::{EntryReferenceSubType, ReferenceType},
+    source_asset::SourceAssetVc,
+};
+use turbopack_dev::DevChunkingContextVc;
+use turbopack_node::{
+    get_edge_intermediate_asset, get_renderer_pool, NodeJsPoolOptions, NodeJsPoolVc,
+};
+use util::{disk_root, fixture_root};
+
+fn register() {
+    turbopack::register();
+    turbopack_dev::register();
+    include!(concat!(env!("OUT_DIR"), "/register_test_edge.rs"));
+}
+
+/// A pool of processes which run the renderer `tests/edge/{entry}` in the
+/// sandbox of the edge runtime, with the `EDGE_GREETING` env var. The renderer
+/// and its host are emitted to `output`.
+async fn edge_pool(entry: &str, output: &Path) -> Result<NodeJsPoolVc> {
+    let root = fixture_root("edge")?;
+    let output = disk_root(output)?;
+    let module = node_evaluate_asset_context(root, None, None).process(
+        SourceAssetVc::new(root.join(entry)).into(),
+        Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+    );
+    let module = EcmascriptModuleAssetVc::resolve_from(module)
+        .await?
+        .ok_or_else(|| anyhow!("{entry} is not an ecmascript module"))?;
+    let chunking_context = DevChunkingContextVc::builder(
+        root,
+        output,
+        output.join("chunks"),
+        output.join("assets"),
+        node_build_environment(),
+    )
+    .build();
+    let intermediate_output_path = output.join("build");
+    let env = EnvMapVc::cell(IndexMap::from([(
+        "EDGE_GREETING".to_string(),
+        "hello".to_string(),
+    )]))
+    .as_process_env();
+    Ok(get_renderer_pool(
+        root,
+        env,
+        get_edge_intermediate_asset(module, None, chunking_context, intermediate_output_path),
+        intermediate_output_path,
+        output,
+        root,
+        NodeJsPoolOptions::default().cell(),
+        false,
+    ))
+}
+
+#[tokio::test]
+async fn renders_in_the_edge_sandbox() {
+    register();
+    let dir = TempDir::new().unwrap();
+    let output = dir.path().to_path_buf();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let response = tt
+        .run_once(async move {
+            let pool = edge_pool("render.js", &output)
+                .await?
+                .strongly_consistent()
+                .await?;
+            let mut operation = pool.operation().await?;
+            operation.send(json!({ "name": "edge" })).await?;
+            operation.recv::<JsonValue>().await
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(response["type"], "rendered", "{response}");
+    assert_eq!(response["html"], "<h1>Hello edge</h1>");
+    // Web APIs are available, while Node.js globals are not.
+    assert_eq!(response["fetch"], "function");
+    assert_eq!(response["buffer"], "undefined");
+    // The env is copied into the sandbox.
+    assert_eq!(response["greeting"], "hello");
+}
+
+#[tokio::test]
+async fn node_builtins_fail_to_load_in_the_edge_sandbox() {
+    register();
+    let dir = TempDir::new().unwrap();
+    let output = dir.path().to_path_buf();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let response = tt
+        .run_once(async move {
+            let pool = edge_pool("builtin.js", &output)
+                .await?
+                .strongly_consistent()
+                .await?;
+            let mut operation = pool.operation().await?;
+            // The renderer fails while it's loaded, before it receives any
+            // message.
+            operation.recv::<JsonValue>().await
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(response["type"], "error", "{response}");
+    let message = response["message"].as_str().unwrap();
+    assert!(
+        message.contains("Node.js module \"fs\" is not supported in the edge runtime"),
+        "{message}"
+    );
+}
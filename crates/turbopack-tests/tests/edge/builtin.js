@@ -0,0 +1,7 @@
+import { readFileSync } from "fs";
+
+const ipc = globalThis.__turbopack_edge_ipc__;
+
+ipc.recv().then(() =>
+  ipc.send({ type: "rendered", html: readFileSync(__filename, "utf8") })
+);
@@ -0,0 +1,16 @@
+const ipc = globalThis.__turbopack_edge_ipc__;
+
+async function run() {
+  while (true) {
+    const { name } = await ipc.recv();
+    await ipc.send({
+      type: "rendered",
+      html: `<h1>Hello ${name}</h1>`,
+      fetch: typeof globalThis.fetch,
+      buffer: typeof globalThis.Buffer,
+      greeting: process.env.EDGE_GREETING,
+    });
+  }
+}
+
+run();
@@ -0,0 +1,129 @@
+#![cfg(test)]
+
+mod util;
+
+use anyhow::Result;
+use tempfile::TempDir;
+use turbo_tasks::{primitives::StringVc, TurboTasks};
+use turbo_tasks_fs::{File, FileSystemPathVc};
+use turbo_tasks_memory::MemoryBackend;
+use turbopack::emit_with_completion;
+use turbopack_core::{
+    asset::{Asset, AssetContentVc, AssetVc},
+    ident::AssetIdentVc,
+    reference::{AssetReferencesVc, SingleAssetReferenceVc},
+    virtual_asset::VirtualAssetVc,
+};
+use util::disk_root;
+
+fn register() {
+    turbopack::register();
+    include!(concat!(env!("OUT_DIR"), "/register_test_emit.rs"));
+}
+
+/// An asset outside of the output directory which references the `assets`.
+#[turbo_tasks::value]
+struct TestEntry {
+    path: FileSystemPathVc,
+    assets: Vec<AssetVc>,
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for TestEntry {
+    #[turbo_tasks::function]
+    fn ident(&self) -> AssetIdentVc {
+        AssetIdentVc::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    fn content(&self) -> AssetContentVc {
+        File::from("").into()
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> AssetReferencesVc {
+        AssetReferencesVc::cell(
+            self.assets
+                .iter()
+                .map(|&asset| {
+                    SingleAssetReferenceVc::new(asset, StringVc::cell("test".to_string())).into()
+                })
+                .collect(),
+        )
+    }
+}
+
+/// An asset emitted to `{root}/output/{name}`, which is told apart from other
+/// assets at the same path by the `producer` modifier.
+fn output_asset(root: FileSystemPathVc, name: &str, producer: &str) -> AssetVc {
+    VirtualAssetVc::new_with_ident(
+        AssetIdentVc::from_path(root.join("output").join(name))
+            .with_modifier(StringVc::cell(producer.to_string())),
+        File::from(producer).into(),
+    )
+    .into()
+}
+
+/// Emits the `files` (name and producer) into `{dir}/output`.
+async fn emit(dir: &TempDir, files: &'static [(&'static str, &'static str)]) -> Result<()> {
+    let dir = dir.path().to_path_buf();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.run_once(async move {
+        let root = disk_root(&dir)?;
+        let assets = files
+            .iter()
+            .map(|(name, producer)| output_asset(root, name, producer))
+            .collect();
+        let entry: AssetVc = TestEntry {
+            path: root.join("entry"),
+            assets,
+        }
+        .cell()
+        .into();
+        emit_with_completion(entry, root.join("output")).await?;
+        Ok(())
+    })
+    .await
+}
+
+#[tokio::test]
+async fn emits_assets_with_distinct_paths() {
+    register();
+    let dir = TempDir::new().unwrap();
+
+    emit(&dir, &[("app.js", "static"), ("chunk.js", "chunk")])
+        .await
+        .unwrap();
+
+    let output = dir.path().join("output");
+    assert_eq!(
+        std::fs::read_to_string(output.join("app.js")).unwrap(),
+        "static"
+    );
+    assert_eq!(
+        std::fs::read_to_string(output.join("chunk.js")).unwrap(),
+        "chunk"
+    );
+}
+
+#[tokio::test]
+async fn assets_emitted_to_the_same_path_bail() {
+    register();
+    let dir = TempDir::new().unwrap();
+
+    let error = emit(&dir, &[("app.js", "static"), ("app.js", "chunk")])
+        .await
+        .unwrap_err();
+
+    let message = format!("{error:?}");
+    assert!(
+        message.contains("2 assets are emitted to") && message.contains("output/app.js"),
+        "{message}"
+    );
+    assert!(
+        message.contains("output/app.js (chunk), ") && message.contains("output/app.js (static)"),
+        "{message}"
+    );
+    // Nothing is written, so neither asset silently wins.
+    assert!(!dir.path().join("output/app.js").exists());
+}
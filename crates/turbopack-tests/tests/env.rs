@@ -0,0 +1,282 @@
+#![cfg(test)]
+
+mod util;
+
+use std::collections::HashSet;
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use turbo_tasks::{TurboTasks, Value, ValueToString};
+use turbo_tasks_env::{EnvMapVc, ProcessEnvVc};
+use turbo_tasks_fs::FileSystemPathVc;
+use turbo_tasks_memory::MemoryBackend;
+use turbopack::ecmascript::{
+    chunk::{
+        EcmascriptChunkItem, EcmascriptChunkPlaceable, EcmascriptChunkPlaceablesVc,
+        EcmascriptChunkingContextVc,
+    },
+    EcmascriptModuleAssetVc,
+};
+use turbopack_core::{
+    asset::{Asset, AssetsVc},
+    chunk::ChunkingContextVc,
+};
+use turbopack_env::{ProcessEnvAssetVc, ProcessEnvMode};
+use util::{
+    asset_context, browser_env, chunking_context_builder, entry_module, fixture_root, node_env,
+    text_content,
+};
+
+fn register() {
+    turbopack::register();
+    turbopack_dev::register();
+    include!(concat!(env!("OUT_DIR"), "/register_test_env.rs"));
+}
+
+/// An env with the `variables`. Their values are JS literals, like the values
+/// of an embeddable env.
+fn process_env(variables: &[(&str, &str)]) -> ProcessEnvVc {
+    let variables: IndexMap<String, String> = variables
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    EnvMapVc::cell(variables).as_process_env()
+}
+
+/// A module of `tests/env`, processed and chunked for a browser or a server.
+struct Fixture {
+    root: FileSystemPathVc,
+    module: EcmascriptModuleAssetVc,
+    chunking_context: ChunkingContextVc,
+}
+
+impl Fixture {
+    async fn new(entry: &str, server: bool) -> Result<Self> {
+        let root = fixture_root("env")?;
+        let env = if server {
+            node_env()
+        } else {
+            browser_env("last 1 Chrome versions")
+        };
+        Ok(Fixture {
+            root,
+            module: entry_module(asset_context(env), root, entry).await?,
+            chunking_context: chunking_context_builder(root, env).build(),
+        })
+    }
+
+    /// The `process.env` asset of the `env` for the module.
+    fn env_asset(&self, env: ProcessEnvVc) -> ProcessEnvAssetVc {
+        ProcessEnvAssetVc::new_for_entries(self.root, env, AssetsVc::cell(vec![self.module.into()]))
+    }
+
+    /// The code of the evaluated chunk of the module, which evaluates the
+    /// `env_asset` first.
+    async fn chunk_code(&self, env_asset: ProcessEnvAssetVc) -> Result<String> {
+        let runtime_entries =
+            EcmascriptChunkPlaceablesVc::cell(vec![env_asset.as_ecmascript_chunk_placeable()]);
+        let chunk = self
+            .module
+            .as_evaluated_chunk(self.chunking_context, Some(runtime_entries));
+        text_content(chunk.into()).await
+    }
+}
+
+#[tokio::test]
+async fn only_accessed_variables_are_embedded() {
+    register();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let code = tt
+        .run_once(async move {
+            let fixture = Fixture::new("static.js", false).await?;
+            let env = process_env(&[("USED", r#""used""#), ("UNUSED", r#""unused""#)]);
+            fixture.chunk_code(fixture.env_asset(env)).await
+        })
+        .await
+        .unwrap();
+    assert!(code.contains(r#"env["USED"] = "used";"#), "{code}");
+    assert!(!code.contains("UNUSED"), "{code}");
+}
+
+#[tokio::test]
+async fn all_variables_are_embedded_for_dynamic_accesses() {
+    register();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let code = tt
+        .run_once(async move {
+            let fixture = Fixture::new("dynamic.js", false).await?;
+            let env = process_env(&[("USED", r#""used""#), ("UNUSED", r#""unused""#)]);
+            fixture.chunk_code(fixture.env_asset(env)).await
+        })
+        .await
+        .unwrap();
+    assert!(code.contains(r#"env["USED"] = "used";"#), "{code}");
+    assert!(code.contains(r#"env["UNUSED"] = "unused";"#), "{code}");
+}
+
+#[tokio::test]
+async fn runtime_env_embeds_no_values() {
+    register();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let code = tt
+        .run_once(async move {
+            let fixture = Fixture::new("static.js", true).await?;
+            let env = process_env(&[("USED", r#""secret""#)]);
+            let env_asset = fixture
+                .env_asset(env)
+                .with_mode(Value::new(ProcessEnvMode::Runtime));
+            fixture.chunk_code(env_asset).await
+        })
+        .await
+        .unwrap();
+    assert!(code.contains("caseInsensitiveEnv(process.env)"), "{code}");
+    assert!(!code.contains("secret"), "{code}");
+    assert!(!code.contains(r#"env["USED"]"#), "{code}");
+}
+
+#[tokio::test]
+async fn runtime_env_embeds_declared_defaults() {
+    register();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let code = tt
+        .run_once(async move {
+            let fixture = Fixture::new("static.js", true).await?;
+            let env = process_env(&[("USED", r#""secret""#)]);
+            let defaults = EnvMapVc::cell(IndexMap::from([(
+                "USED".to_string(),
+                r#""fallback""#.to_string(),
+            )]));
+            let env_asset = fixture
+                .env_asset(env)
+                .with_mode(Value::new(ProcessEnvMode::Runtime))
+                .with_runtime_defaults(defaults);
+            fixture.chunk_code(env_asset).await
+        })
+        .await
+        .unwrap();
+    assert!(
+        code.contains(r#"if (env["USED"] === undefined) env["USED"] = "fallback";"#),
+        "{code}"
+    );
+    assert!(!code.contains("secret"), "{code}");
+}
+
+/// The chunk code of `prefixed.js` with client prefixes, for a browser or a
+/// server.
+async fn prefixed_chunk_code(server: bool) -> Result<String> {
+    let fixture = Fixture::new("prefixed.js", server).await?;
+    let env = process_env(&[
+        ("PUBLIC_URL", r#""https://example.com""#),
+        ("SECRET", r#""s3cr3t""#),
+    ]);
+    let env_asset = fixture
+        .env_asset(env)
+        .with_client_prefixes(vec!["PUBLIC_".to_string()]);
+    fixture.chunk_code(env_asset).await
+}
+
+#[tokio::test]
+async fn client_chunks_only_get_prefixed_variables() {
+    register();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let code = tt
+        .run_once(async move { prefixed_chunk_code(false).await })
+        .await
+        .unwrap();
+    assert!(
+        code.contains(r#"env["PUBLIC_URL"] = "https://example.com";"#),
+        "{code}"
+    );
+    assert!(!code.contains("s3cr3t"), "{code}");
+}
+
+#[tokio::test]
+async fn server_chunks_get_all_variables() {
+    register();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let code = tt
+        .run_once(async move { prefixed_chunk_code(true).await })
+        .await
+        .unwrap();
+    assert!(
+        code.contains(r#"env["PUBLIC_URL"] = "https://example.com";"#),
+        "{code}"
+    );
+    assert!(code.contains(r#"env["SECRET"] = "s3cr3t";"#), "{code}");
+}
+
+#[tokio::test]
+async fn only_server_chunks_look_up_variables_ignoring_casing() {
+    register();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let (client, server) = tt
+        .run_once(async move {
+            let env = process_env(&[("USED", r#""used""#)]);
+            let client = Fixture::new("static.js", false).await?;
+            let server = Fixture::new("static.js", true).await?;
+            Ok((
+                client.chunk_code(client.env_asset(env)).await?,
+                server.chunk_code(server.env_asset(env)).await?,
+            ))
+        })
+        .await
+        .unwrap();
+    assert!(!client.contains("caseInsensitiveEnv"), "{client}");
+    assert!(
+        client.contains("const env = process.env = {...process.env};"),
+        "{client}"
+    );
+    assert!(
+        server.contains("const env = process.env = caseInsensitiveEnv({...process.env});"),
+        "{server}"
+    );
+}
+
+/// The idents and code of the chunk items of the runtime entries of the env
+/// asset of `keys.js` and the `env`.
+async fn runtime_entry_items(env: ProcessEnvVc) -> Result<Vec<(String, String)>> {
+    let fixture = Fixture::new("keys.js", false).await?;
+    let context = EcmascriptChunkingContextVc::resolve_from(fixture.chunking_context)
+        .await?
+        .ok_or_else(|| anyhow!("not an ecmascript chunking context"))?;
+    let mut items = Vec::new();
+    for entry in fixture.env_asset(env).runtime_entries().await?.iter() {
+        let ident = (*entry.ident().to_string().await?).clone();
+        let content = entry.as_chunk_item(context).content().await?;
+        items.push((ident, content.inner_code.to_str()?.into_owned()));
+    }
+    Ok(items)
+}
+
+#[tokio::test]
+async fn changing_a_variable_only_changes_its_chunk_item() {
+    register();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let (before, changed, removed) = tt
+        .run_once(async move {
+            Ok((
+                runtime_entry_items(process_env(&[("A", "1"), ("B", "2")])).await?,
+                runtime_entry_items(process_env(&[("A", "1"), ("B", "3")])).await?,
+                runtime_entry_items(process_env(&[("A", "1")])).await?,
+            ))
+        })
+        .await
+        .unwrap();
+    // The env asset, which sets up `process.env`, and one asset per variable.
+    assert_eq!(before.len(), 3, "{before:?}");
+    let idents = before
+        .iter()
+        .map(|(ident, _)| ident)
+        .collect::<HashSet<_>>();
+    assert_eq!(idents.len(), 3, "{before:?}");
+    assert_eq!(before[1].1, "process.env[\"A\"] = 1;\n");
+    assert_eq!(before[2].1, "process.env[\"B\"] = 2;\n");
+
+    assert_eq!(changed[0], before[0]);
+    assert_eq!(changed[1], before[1]);
+    assert_eq!(changed[2].1, "process.env[\"B\"] = 3;\n");
+
+    assert_eq!(removed[0], before[0]);
+    assert_eq!(removed[1], before[1]);
+    assert_eq!(removed[2].1, "delete process.env[\"B\"];\n");
+}
@@ -0,0 +1,2 @@
+const name = "USED";
+console.log(process.env[name]);
@@ -0,0 +1 @@
+console.log(process.env.A, process.env.B);
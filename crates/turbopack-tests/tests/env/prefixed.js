@@ -0,0 +1 @@
+console.log(process.env.PUBLIC_URL, process.env.SECRET);
@@ -0,0 +1,112 @@
+#![cfg(test)]
+
+mod util;
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use turbo_tasks::{TryJoinIterExt, TurboTasks};
+use turbo_tasks_memory::MemoryBackend;
+use turbopack::{
+    module_options::ModuleOptionsContext, resolve_options_context::ResolveOptionsContext,
+    transition::TransitionsByNameVc, ModuleAssetContextVc,
+};
+use turbopack_core::{
+    asset::Asset,
+    compile_time_info::{CompileTimeDefinesVc, CompileTimeInfo, FeatureFlagsVc},
+    reference::all_assets,
+};
+use util::{browser_env, chunking_context_builder, entry_module, fixture_root, text_content};
+
+fn register() {
+    turbopack::register();
+    turbopack_dev::register();
+    include!(concat!(env!("OUT_DIR"), "/register_test_feature_flags.rs"));
+}
+
+struct Chunk {
+    code: String,
+    /// The paths of all assets referenced from the chunk.
+    assets: Vec<String>,
+}
+
+/// The evaluated chunk of `tests/feature_flags/index.js` with the `flags`,
+/// which loads either `new-checkout.js` or `old-checkout.js` depending on the
+/// `newCheckout` flag.
+async fn chunk(flags: &[(&str, bool)]) -> Result<Chunk> {
+    let root = fixture_root("feature_flags")?;
+    let env = browser_env("last 1 Chrome versions");
+    let feature_flags = flags
+        .iter()
+        .map(|(flag, enabled)| (flag.to_string(), *enabled))
+        .collect::<HashMap<_, _>>();
+    let context = ModuleAssetContextVc::new(
+        TransitionsByNameVc::cell(HashMap::new()),
+        CompileTimeInfo {
+            environment: env,
+            defines: CompileTimeDefinesVc::empty(),
+            feature_flags: FeatureFlagsVc::cell(feature_flags),
+        }
+        .cell(),
+        ModuleOptionsContext::default().cell(),
+        ResolveOptionsContext::default().cell(),
+    );
+    let chunking_context = chunking_context_builder(root, env).build();
+
+    let module = entry_module(context, root, "index.js").await?;
+    let chunk = module.as_evaluated_chunk(chunking_context, None);
+    let assets = all_assets(chunk.into())
+        .await?
+        .iter()
+        .map(|asset| async move { Ok(asset.ident().path().await?.path.clone()) })
+        .try_join()
+        .await?;
+    Ok(Chunk {
+        code: text_content(chunk.into()).await?,
+        assets,
+    })
+}
+
+/// Whether an asset whose path contains `name` is referenced from the chunk.
+fn references(chunk: &Chunk, name: &str) -> bool {
+    chunk.assets.iter().any(|path| path.contains(name))
+}
+
+#[tokio::test]
+async fn enabled_flags_remove_the_else_branch() {
+    register();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let chunk = tt
+        .run_once(async move { chunk(&[("newCheckout", true)]).await })
+        .await
+        .unwrap();
+
+    let code = &chunk.code;
+    // The dynamic import in the other branch is removed with it.
+    assert!(code.contains("\"TURBOPACK unreachable\";"), "{code}");
+    assert!(
+        code.contains("console.log((\"TURBOPACK feature flag enabled\", true));"),
+        "{code}"
+    );
+    assert!(!code.contains("__turbopack_feature_flags__"), "{code}");
+
+    assert!(references(&chunk, "new-checkout"), "{:?}", chunk.assets);
+    assert!(!references(&chunk, "old-checkout"), "{:?}", chunk.assets);
+}
+
+#[tokio::test]
+async fn unlisted_flags_are_disabled() {
+    register();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let chunk = tt.run_once(async move { chunk(&[]).await }).await.unwrap();
+
+    let code = &chunk.code;
+    assert!(code.contains("\"TURBOPACK unreachable\";"), "{code}");
+    assert!(
+        code.contains("console.log((\"TURBOPACK feature flag disabled\", false));"),
+        "{code}"
+    );
+
+    assert!(references(&chunk, "old-checkout"), "{:?}", chunk.assets);
+    assert!(!references(&chunk, "new-checkout"), "{:?}", chunk.assets);
+}
@@ -0,0 +1,7 @@
+if (__turbopack_feature_flags__.newCheckout) {
+  import("./new-checkout.js").then(({ checkout }) => checkout());
+} else {
+  import("./old-checkout.js").then(({ checkout }) => checkout());
+}
+
+console.log(__turbopack_feature_flags__.newCheckout);
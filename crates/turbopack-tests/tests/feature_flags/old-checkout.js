@@ -0,0 +1,3 @@
+export function checkout() {
+  console.log("old checkout");
+}
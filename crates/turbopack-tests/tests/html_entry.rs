@@ -0,0 +1,68 @@
+#![cfg(test)]
+
+mod util;
+
+use anyhow::Result;
+use turbo_tasks::TurboTasks;
+use turbo_tasks_memory::MemoryBackend;
+use turbopack_core::source_asset::SourceAssetVc;
+use turbopack_dev_server::html_entry::HtmlEntryAssetVc;
+use util::{asset_context, browser_env, chunking_context_builder, fixture_root, text_content};
+
+fn register() {
+    turbopack::register();
+    turbopack_dev::register();
+    turbopack_dev_server::register();
+    include!(concat!(env!("OUT_DIR"), "/register_test_html_entry.rs"));
+}
+
+/// Renders `tests/html_entry/index.html` with the given asset prefix.
+async fn render(asset_prefix: Option<&str>) -> Result<String> {
+    let root = fixture_root("html_entry")?;
+    let env = browser_env("Chrome 102");
+    let chunking_context = chunking_context_builder(root, env).build();
+
+    let mut html = HtmlEntryAssetVc::new(
+        SourceAssetVc::new(root.join("index.html")).into(),
+        root.join("output/index.html"),
+        asset_context(env).into(),
+        chunking_context,
+        None,
+    );
+    if let Some(asset_prefix) = asset_prefix {
+        html = html.with_asset_prefix(asset_prefix.to_string());
+    }
+    text_content(html.into()).await
+}
+
+#[tokio::test]
+async fn urls_are_relative_to_the_output_root() {
+    register();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let html = tt
+        .run_once(async move { render(None).await })
+        .await
+        .unwrap();
+    assert!(html.contains("<script src=\"/output/"), "{html}");
+    assert!(
+        html.contains("<link data-turbopack rel=\"stylesheet\" href=\"/output/"),
+        "{html}"
+    );
+    assert!(!html.contains("src=\"/main.js\""), "{html}");
+    assert!(!html.contains("href=\"/style.css\""), "{html}");
+}
+
+#[tokio::test]
+async fn urls_are_prefixed() {
+    register();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let html = tt
+        .run_once(async move { render(Some("https://cdn.example.com/app")).await })
+        .await
+        .unwrap();
+    assert!(
+        html.contains("<script src=\"https://cdn.example.com/app/output/"),
+        "{html}"
+    );
+    assert!(!html.contains("src=\"/output/"), "{html}");
+}
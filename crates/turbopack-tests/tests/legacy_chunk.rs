@@ -0,0 +1,60 @@
+#![cfg(test)]
+
+mod util;
+
+use anyhow::Result;
+use turbo_tasks::TurboTasks;
+use turbo_tasks_memory::MemoryBackend;
+use util::{
+    asset_context, browser_env, chunking_context_builder, entry_module, fixture_root, text_content,
+};
+
+fn register() {
+    turbopack::register();
+    turbopack_dev::register();
+    include!(concat!(env!("OUT_DIR"), "/register_test_legacy_chunk.rs"));
+}
+
+/// The evaluated chunk of `tests/legacy_chunk/index.js`, when it's chunked
+/// for a browser matching `browserslist`.
+async fn chunk_code(browserslist: &str, legacy: bool) -> Result<String> {
+    let root = fixture_root("legacy_chunk")?;
+    let env = browser_env(browserslist);
+    // The modules themselves are not transpiled, so everything in the
+    // output that's ES5 is the result of transpiling the legacy chunk.
+    let mut builder = chunking_context_builder(root, env);
+    if legacy {
+        builder = builder.legacy();
+    }
+    let chunking_context = builder.build();
+
+    let module = entry_module(asset_context(env), root, "index.js").await?;
+    let chunk = module.as_evaluated_chunk(chunking_context, None);
+    text_content(chunk.into()).await
+}
+
+#[tokio::test]
+async fn legacy_chunks_are_es5() {
+    register();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let code = tt
+        .run_once(async move { chunk_code("ie 11", true).await })
+        .await
+        .unwrap();
+    assert!(code.contains("hello "), "{code}");
+    for syntax in ["=>", "`", "const ", "let ", "class "] {
+        assert!(!code.contains(syntax), "{syntax} in legacy chunk:\n{code}");
+    }
+}
+
+#[tokio::test]
+async fn module_chunks_are_not_transpiled() {
+    register();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let code = tt
+        .run_once(async move { chunk_code("ie 11", false).await })
+        .await
+        .unwrap();
+    assert!(code.contains("class Greeter"), "{code}");
+    assert!(code.contains("=>"), "{code}");
+}
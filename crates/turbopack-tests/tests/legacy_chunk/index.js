@@ -0,0 +1,9 @@
+const greet = (name = "world") => `hello ${name}`;
+
+class Greeter {
+  greet() {
+    return greet();
+  }
+}
+
+console.log(new Greeter().greet());
@@ -1,7 +1,7 @@
 #![cfg(test)]
 
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
     env, fs,
     path::{Path, PathBuf},
 };
@@ -13,8 +13,8 @@ use serde::Deserialize;
 use turbo_tasks::{debug::ValueDebug, NothingVc, TryJoinIterExt, TurboTasks, Value, ValueToString};
 use turbo_tasks_env::DotenvProcessEnvVc;
 use turbo_tasks_fs::{
-    json::parse_json_with_source_context, util::sys_to_unix, DiskFileSystemVc, FileSystem,
-    FileSystemPathVc,
+    json::parse_json_with_source_context, util::sys_to_unix, DiskFileSystemVc, File, FileSystem,
+    FileSystemPath, FileSystemPathVc,
 };
 use turbo_tasks_memory::MemoryBackend;
 use turbopack::{
@@ -29,7 +29,7 @@ use turbopack_core::{
     asset::{Asset, AssetVc},
     chunk::{availability_info::AvailabilityInfo, ChunkableAsset, ChunkableAssetVc},
     compile_time_defines,
-    compile_time_info::CompileTimeInfo,
+    compile_time_info::{CompileTimeInfo, FeatureFlagsVc},
     context::{AssetContext, AssetContextVc},
     environment::{BrowserEnvironment, EnvironmentIntention, EnvironmentVc, ExecutionEnvironment},
     issue::IssueVc,
@@ -66,13 +66,22 @@ struct SnapshotOptions {
     browserslist: String,
     #[serde(default = "default_entry")]
     entry: String,
+    /// Whether to snapshot a [MANIFEST] of the emitted assets.
+    #[serde(default)]
+    manifest: bool,
 }
 
+/// The file the emitted assets and the assets they reference are written to,
+/// within the test directory, so changes to chunking show up as a diff of
+/// chunk names.
+const MANIFEST: &str = "manifest.json";
+
 impl Default for SnapshotOptions {
     fn default() -> Self {
         SnapshotOptions {
             browserslist: default_browserslist(),
             entry: default_entry(),
+            manifest: false,
         }
     }
 }
@@ -159,7 +168,7 @@ async fn run_test(resource: &str) -> Result<FileSystemPathVc> {
     let entry_asset = project_path.join(&options.entry);
     let entry_paths = vec![entry_asset];
 
-    let runtime_entries = maybe_load_env(project_path).await?;
+    let runtime_entries = maybe_load_env(project_path, path).await?;
 
     let env = EnvironmentVc::new(
         Value::new(ExecutionEnvironment::Browser(
@@ -183,6 +192,7 @@ async fn run_test(resource: &str) -> Result<FileSystemPathVc> {
             A.VERY.LONG.DEFINED.VALUE = "value",
         )
         .cell(),
+        feature_flags: FeatureFlagsVc::empty(),
     }
     .cell();
 
@@ -274,8 +284,10 @@ async fn run_test(resource: &str) -> Result<FileSystemPathVc> {
         queue.push_back(chunk.as_asset());
     }
 
+    let test_root = path.await?;
+    let mut manifest = BTreeMap::new();
     while let Some(asset) = queue.pop_front() {
-        walk_asset(asset, &mut seen, &mut queue)
+        walk_asset(asset, &test_root, &mut seen, &mut queue, &mut manifest)
             .await
             .context(format!(
                 "Failed to walk asset {}",
@@ -291,13 +303,30 @@ async fn run_test(resource: &str) -> Result<FileSystemPathVc> {
         .await
         .context("Actual assets doesn't match with expected assets")?;
 
+    if options.manifest {
+        let manifest = serde_json::to_string_pretty(&manifest)? + "\n";
+        diff(path.join(MANIFEST), File::from(manifest).into()).await?;
+    }
+
     Ok(path)
 }
 
+/// The path of `path` relative to the test directory at `root`, which is
+/// stable across machines.
+async fn relative_path(root: &FileSystemPath, path: FileSystemPathVc) -> Result<String> {
+    let path = path.await?;
+    Ok(root
+        .get_path_to(&path)
+        .map(|relative| relative.to_string())
+        .unwrap_or_else(|| path.path.clone()))
+}
+
 async fn walk_asset(
     asset: AssetVc,
+    root: &FileSystemPath,
     seen: &mut HashSet<FileSystemPathVc>,
     queue: &mut VecDeque<AssetVc>,
+    manifest: &mut BTreeMap<String, Vec<String>>,
 ) -> Result<()> {
     let path = asset.ident().path().resolve().await?;
 
@@ -306,13 +335,27 @@ async fn walk_asset(
     }
 
     diff(path, asset.content()).await?;
-    queue.extend(&*all_referenced_assets(asset).await?);
+    let references = all_referenced_assets(asset).await?;
+    let mut referenced = references
+        .iter()
+        .map(|reference| relative_path(root, reference.ident().path()))
+        .try_join()
+        .await?;
+    referenced.sort();
+    referenced.dedup();
+    manifest.insert(relative_path(root, path).await?, referenced);
+    queue.extend(&*references);
 
     Ok(())
 }
 
-async fn maybe_load_env(path: FileSystemPathVc) -> Result<Option<EcmascriptChunkPlaceablesVc>> {
-    let dotenv_path = path.join("input/.env");
+/// Loads the `input/.env` of the test, if any, and snapshots the `env.d.ts`
+/// declarations of its variables in the test directory.
+async fn maybe_load_env(
+    project_path: FileSystemPathVc,
+    path: FileSystemPathVc,
+) -> Result<Option<EcmascriptChunkPlaceablesVc>> {
+    let dotenv_path = project_path.join("input/.env");
 
     if !dotenv_path.read().await?.is_content() {
         return Ok(None);
@@ -320,7 +363,6 @@ async fn maybe_load_env(path: FileSystemPathVc) -> Result<Option<EcmascriptChunk
 
     let env = DotenvProcessEnvVc::new(None, dotenv_path);
     let asset = ProcessEnvAssetVc::new(dotenv_path, env.into());
-    Ok(Some(EcmascriptChunkPlaceablesVc::cell(vec![
-        asset.as_ecmascript_chunk_placeable()
-    ])))
+    diff(path.join("env.d.ts"), asset.types(None).content()).await?;
+    Ok(Some(asset.runtime_entries()))
 }
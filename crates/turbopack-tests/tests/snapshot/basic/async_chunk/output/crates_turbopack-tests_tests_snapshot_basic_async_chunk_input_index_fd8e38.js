@@ -1,6 +1,6 @@
 (globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["output/crates_turbopack-tests_tests_snapshot_basic_async_chunk_input_index_fd8e38.js", {
 
-"[project]/crates/turbopack-tests/tests/snapshot/basic/async_chunk/input/import.js (ecmascript, manifest chunk, loader)": (({ r: __turbopack_require__, x: __turbopack_external_require__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, c: __turbopack_cache__, l: __turbopack_load__, k: __turbopack_register_chunk_list__, j: __turbopack_cjs__, p: process, g: global, __dirname }) => (() => {
+"[project]/crates/turbopack-tests/tests/snapshot/basic/async_chunk/input/import.js (ecmascript, manifest chunk, loader)": (({ r: __turbopack_require__, x: __turbopack_external_require__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, c: __turbopack_cache__, l: __turbopack_load__, k: __turbopack_register_chunk_list__, h: __turbopack_prefetch__, j: __turbopack_cjs__, f: __turbopack_refresh__, p: process, g: global, __dirname }) => (() => {
 
 __turbopack_export_value__((__turbopack_import__) => {
     return __turbopack_load__("output/crates_turbopack-tests_tests_snapshot_basic_async_chunk_input_import_571bb9.js").then(() => {
@@ -14,11 +14,11 @@ __turbopack_export_value__((__turbopack_import__) => {
 });
 
 })()),
-"[project]/crates/turbopack-tests/tests/snapshot/basic/async_chunk/input/shared.js (ecmascript)": (function({ r: __turbopack_require__, x: __turbopack_external_require__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, c: __turbopack_cache__, l: __turbopack_load__, k: __turbopack_register_chunk_list__, j: __turbopack_cjs__, p: process, g: global, __dirname, m: module, e: exports }) { !function() {
+"[project]/crates/turbopack-tests/tests/snapshot/basic/async_chunk/input/shared.js (ecmascript)": (function({ r: __turbopack_require__, x: __turbopack_external_require__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, c: __turbopack_cache__, l: __turbopack_load__, k: __turbopack_register_chunk_list__, h: __turbopack_prefetch__, j: __turbopack_cjs__, f: __turbopack_refresh__, p: process, g: global, __dirname, m: module, e: exports }) { !function() {
 
 
 }.call(this) }),
-"[project]/crates/turbopack-tests/tests/snapshot/basic/async_chunk/input/index.js (ecmascript)": (({ r: __turbopack_require__, x: __turbopack_external_require__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, c: __turbopack_cache__, l: __turbopack_load__, k: __turbopack_register_chunk_list__, j: __turbopack_cjs__, p: process, g: global, __dirname }) => (() => {
+"[project]/crates/turbopack-tests/tests/snapshot/basic/async_chunk/input/index.js (ecmascript)": (({ r: __turbopack_require__, x: __turbopack_external_require__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, c: __turbopack_cache__, l: __turbopack_load__, k: __turbopack_register_chunk_list__, h: __turbopack_prefetch__, j: __turbopack_cjs__, f: __turbopack_refresh__, p: process, g: global, __dirname }) => (() => {
 
 var __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$basic$2f$async_chunk$2f$input$2f$node_modules$2f$bar$2f$index$2e$js__$28$ecmascript$29$__ = __turbopack_import__("[project]/crates/turbopack-tests/tests/snapshot/basic/async_chunk/input/node_modules/bar/index.js (ecmascript)");
 var __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$basic$2f$async_chunk$2f$input$2f$shared$2e$js__$28$ecmascript$29$__ = __turbopack_import__("[project]/crates/turbopack-tests/tests/snapshot/basic/async_chunk/input/shared.js (ecmascript)");
@@ -47,15 +47,38 @@ __turbopack_require__("[project]/crates/turbopack-tests/tests/snapshot/basic/asy
 if (!Array.isArray(globalThis.TURBOPACK)) {
     return;
 }
+const RUNTIME_ERROR_HOOK = null;
+const CHUNK_SPECIFIER_PREFIX = null;
+const CHUNK_FORMAT = "script";
 /** @typedef {import('../types/backend').RuntimeBackend} RuntimeBackend */
 /** @typedef {import('../types/dom').ChunkResolver} ChunkResolver */
 /** @typedef {import('../types').ChunkPath} ChunkPath */
 /** @typedef {import('../types').SourceInfo} SourceInfo */
+/** @typedef {import('../types').PrefetchStrategy} PrefetchStrategy */
 
 /** @type {RuntimeBackend} */
 let BACKEND;
 
 (() => {
+  /**
+   * Whether the runtime runs in a worker, e.g. a service worker, where
+   * classic chunks are loaded with `importScripts()` and there are no
+   * stylesheets.
+   */
+  const isWorker = typeof document === "undefined";
+
+  /**
+   * The nonce of the page's Content-Security-Policy. It's applied to all
+   * scripts and stylesheets that are inserted to load chunks.
+   *
+   * @type {string | undefined}
+   */
+  const cspNonce = isWorker
+    ? undefined
+    : document.currentScript?.nonce ||
+      document.querySelector("script[nonce]")?.nonce ||
+      undefined;
+
   BACKEND = {
     async registerChunk(chunkPath, params) {
       const resolver = getOrCreateResolver(chunkPath);
@@ -93,9 +116,12 @@ let BACKEND;
     unloadChunk(chunkPath) {
       deleteResolver(chunkPath);
 
+      if (isWorker) {
+        return;
+      }
+
       if (chunkPath.endsWith(".css")) {
-        const links = document.querySelectorAll(`link[href="/${chunkPath}"]`);
-        for (const link of Array.from(links)) {
+        for (const link of findChunkElements("link", "href", chunkPath)) {
           link.remove();
         }
       } else if (chunkPath.endsWith(".js")) {
@@ -103,10 +129,7 @@ let BACKEND;
         // runtime once evaluated.
         // However, we still want to remove the script tag from the DOM to keep
         // the HTML somewhat consistent from the user's perspective.
-        const scripts = document.querySelectorAll(
-          `script[src="/${chunkPath}"]`
-        );
-        for (const script of Array.from(scripts)) {
+        for (const script of findChunkElements("script", "src", chunkPath)) {
           script.remove();
         }
       } else {
@@ -121,9 +144,13 @@ let BACKEND;
           return;
         }
 
-        const previousLink = document.querySelector(
-          `link[href^="/${chunkPath}"]`
-        );
+        if (isWorker) {
+          // Workers don't apply stylesheets.
+          resolve();
+          return;
+        }
+
+        const [previousLink] = findChunkElements("link", "href", chunkPath);
 
         if (previousLink == null) {
           reject(new Error(`No link element found for chunk ${chunkPath}`));
@@ -132,7 +159,11 @@ let BACKEND;
 
         const link = document.createElement("link");
         link.rel = "stylesheet";
-        link.href = `/${chunkPath}?t=${Date.now()}`;
+        bumpChunkVersion();
+        link.href = chunkUrl(chunkPath);
+        if (cspNonce) {
+          link.nonce = cspNonce;
+        }
         link.onerror = () => {
           reject();
         };
@@ -153,9 +184,117 @@ let BACKEND;
       });
     },
 
+    prefetchChunks(strategy, getChunkPaths) {
+      if (isWorker) {
+        return;
+      }
+
+      const prefetch = () => {
+        getChunkPaths().then(
+          (chunkPaths) => {
+            for (const chunkPath of chunkPaths) {
+              prefetchChunk(chunkPath);
+            }
+          },
+          // The import reports the failure when it's reached.
+          () => {}
+        );
+      };
+
+      switch (strategy) {
+        case "idle":
+          if (typeof requestIdleCallback === "function") {
+            requestIdleCallback(prefetch);
+          } else {
+            setTimeout(prefetch, 0);
+          }
+          break;
+        case "visible":
+          if (document.visibilityState === "visible") {
+            prefetch();
+          } else {
+            const onVisibilityChange = () => {
+              if (document.visibilityState === "visible") {
+                document.removeEventListener(
+                  "visibilitychange",
+                  onVisibilityChange
+                );
+                prefetch();
+              }
+            };
+            document.addEventListener("visibilitychange", onVisibilityChange);
+          }
+          break;
+        default:
+          prefetch();
+      }
+    },
+
     restart: () => self.location.reload(),
   };
 
+  /**
+   * Fetches a chunk with a low priority, without executing it. Chunks which
+   * are loaded or already prefetched, e.g. by the HTML page, are skipped.
+   *
+   * @param {ChunkPath} chunkPath
+   */
+  function prefetchChunk(chunkPath) {
+    if (chunkResolvers.get(chunkPath)?.resolved) {
+      return;
+    }
+    if (findChunkElements("link", "href", chunkPath).length > 0) {
+      return;
+    }
+    const link = document.createElement("link");
+    link.rel = "prefetch";
+    link.href = chunkUrl(chunkPath);
+    if (cspNonce) {
+      link.nonce = cspNonce;
+    }
+    document.head.appendChild(link);
+  }
+
+  /**
+   * The version appended to the URLs of chunks which are loaded after an
+   * update, so stale service workers or proxies can't serve outdated chunks.
+   * The dev server ignores it.
+   *
+   * @type {number | undefined}
+   */
+  let chunkVersion;
+
+  function bumpChunkVersion() {
+    chunkVersion = Date.now();
+  }
+
+  /**
+   * @param {ChunkPath} chunkPath
+   * @returns {string}
+   */
+  function chunkUrl(chunkPath) {
+    const url = `/${chunkPath}`;
+    return chunkVersion == null ? url : `${url}?v=${chunkVersion}`;
+  }
+
+  /**
+   * Finds the elements which have loaded a chunk, with any version.
+   *
+   * @param {string} tagName
+   * @param {string} attribute
+   * @param {ChunkPath} chunkPath
+   * @returns {Element[]}
+   */
+  function findChunkElements(tagName, attribute, chunkPath) {
+    const url = `/${chunkPath}`;
+    return Array.from(
+      document.querySelectorAll(`${tagName}[${attribute}^="${url}"]`)
+    ).filter((element) => {
+      const value = element.getAttribute(attribute);
+      return value === url || value.startsWith(`${url}?`);
+    });
+  }
+
   /**
    * Maps chunk paths to the corresponding resolver.
    *
@@ -233,10 +372,22 @@ let BACKEND;
       return resolver.promise;
     }
 
+    if (source.type === SourceTypeUpdate) {
+      bumpChunkVersion();
+    }
+
     if (chunkPath.endsWith(".css")) {
+      if (isWorker) {
+        resolver.resolve();
+        return resolver.promise;
+      }
+
       const link = document.createElement("link");
       link.rel = "stylesheet";
-      link.href = `/${chunkPath}`;
+      link.href = chunkUrl(chunkPath);
+      if (cspNonce) {
+        link.nonce = cspNonce;
+      }
       link.onerror = () => {
         resolver.reject();
       };
@@ -247,15 +398,37 @@ let BACKEND;
       };
       document.body.appendChild(link);
     } else if (chunkPath.endsWith(".js")) {
-      const script = document.createElement("script");
-      script.src = `/${chunkPath}`;
       // We'll only mark the chunk as loaded once the script has been executed,
       // which happens in `registerChunk`. Hence the absence of `resolve()` in
       // this branch.
-      script.onerror = () => {
-        resolver.reject();
-      };
-      document.body.appendChild(script);
+      if (CHUNK_FORMAT === "module") {
+        // When set, the import map of the page maps the specifier to the chunk
+        // URL.
+        const specifier =
+          CHUNK_SPECIFIER_PREFIX != null
+            ? CHUNK_SPECIFIER_PREFIX + chunkPath
+            : chunkUrl(chunkPath);
+        import(specifier).catch((error) => {
+          resolver.reject(error);
+        });
+      } else if (isWorker) {
+        // The chunk registers itself synchronously while it's imported.
+        try {
+          importScripts(chunkUrl(chunkPath));
+        } catch (error) {
+          resolver.reject(error);
+        }
+      } else {
+        const script = document.createElement("script");
+        script.src = chunkUrl(chunkPath);
+        if (cspNonce) {
+          script.nonce = cspNonce;
+        }
+        script.onerror = () => {
+          resolver.reject();
+        };
+        document.body.appendChild(script);
+      }
     } else {
       throw new Error(`can't infer type of chunk from path ${chunkPath}`);
     }
@@ -274,6 +447,7 @@ let BACKEND;
 
 /** @typedef {import('../types').Module} Module */
 /** @typedef {import('../types').SourceInfo} SourceInfo */
+/** @typedef {import('../types').PrefetchStrategy} PrefetchStrategy */
 /** @typedef {import('../types').SourceType} SourceType */
 /** @typedef {import('../types').SourceType.Runtime} SourceTypeRuntime */
 /** @typedef {import('../types').SourceType.Parent} SourceTypeParent */
@@ -282,6 +456,7 @@ let BACKEND;
 /** @typedef {import('../types').EsmInteropNamespace} EsmInteropNamespace */
 
 /** @typedef {import('../types').RefreshHelpers} RefreshHelpers */
+/** @typedef {import('../types').RefreshRuntime} RefreshRuntime */
 /** @typedef {import('../types/hot').Hot} Hot */
 /** @typedef {import('../types/hot').HotData} HotData */
 /** @typedef {import('../types/hot').AcceptCallback} AcceptCallback */
@@ -295,6 +470,9 @@ let BACKEND;
 
 /** @typedef {import('../types/runtime').ModuleEffect} ModuleEffect */
 /** @typedef {import('../types/runtime').DevRuntimeParams} DevRuntimeParams */
+/** @typedef {import('../types/runtime').RuntimeErrorReport} RuntimeErrorReport */
+/** @typedef {import('../types/runtime').ChunkLoadEvent} ChunkLoadEvent */
+/** @typedef {import('../types/runtime').ChunkLoadListener} ChunkLoadListener */
 
 /** @type {Object.<ModuleId, ModuleFactory>} */
 const moduleFactories = { __proto__: null };
@@ -341,6 +519,13 @@ const chunkModulesMap = new Map();
  * @type {Set<ChunkPath>}
  */
 const runtimeChunkLists = new Set();
+/**
+ * The code of modules received in updates, with its hash. Later updates can
+ * send diffs against it, and modules whose code is unchanged are skipped.
+ *
+ * @type {Map<ModuleId, { code: string, hash: string }>}
+ */
+const moduleUpdateCode = new Map();
 /**
  * Map from chunk list to the chunk paths it contains.
  * @type {Map<ChunkPath, Set<ChunkPath>>}
@@ -351,6 +536,24 @@ const chunkListChunksMap = new Map();
  * @type {Map<ChunkPath, Set<ChunkPath>>}
  */
 const chunkChunkListsMap = new Map();
+/**
+ * Chunks which have been loaded or registered by their `<script>` tag.
+ *
+ * @type {Set<ChunkPath>}
+ */
+const loadedChunks = new Set();
+/**
+ * Map from the chunks which are being loaded to the time their load started.
+ *
+ * @type {Map<ChunkPath, number>}
+ */
+const pendingChunkLoads = new Map();
+/**
+ * Callbacks registered with `__turbopack__.onChunkLoad`.
+ *
+ * @type {Set<ChunkLoadListener>}
+ */
+const chunkLoadListeners = new Set();
 
 const hOP = Object.prototype.hasOwnProperty;
 const _process =
@@ -365,6 +568,43 @@ const _process =
 
 const toStringTag = typeof Symbol !== "undefined" && Symbol.toStringTag;
 
+/**
+ * Errors which have already been reported. A module factory that throws makes
+ * the factories of all modules requiring it throw the same error.
+ *
+ * @type {WeakSet<object>}
+ */
+const reportedErrors = new WeakSet();
+
+/**
+ * Reports a failure to the global callback named `RUNTIME_ERROR_HOOK`, so
+ * apps can forward it to their monitoring. The callback is looked up on every
+ * failure, so it can be installed after the runtime has loaded.
+ *
+ * @param {RuntimeErrorReport} report
+ */
+function reportRuntimeError(report) {
+  if (RUNTIME_ERROR_HOOK == null) {
+    return;
+  }
+  const { error } = report;
+  if (typeof error === "object" && error !== null) {
+    if (reportedErrors.has(error)) {
+      return;
+    }
+    reportedErrors.add(error);
+  }
+  const hook = globalThis[RUNTIME_ERROR_HOOK];
+  if (typeof hook !== "function") {
+    return;
+  }
+  try {
+    hook(report);
+  } catch (_) {
+    // A failing hook must not hide the original error.
+  }
+}
+
 /**
  * @param {any} obj
  * @param {PropertyKey} name
@@ -486,9 +726,28 @@ externalRequire.resolve = (name, opt) => {
  * @returns {Promise<any> | undefined}
  */
 async function loadChunk(source, chunkPath) {
+  // Only the first load of a chunk is observed by `__turbopack__`.
+  const observed =
+    !loadedChunks.has(chunkPath) && !pendingChunkLoads.has(chunkPath);
+  const startTime = now();
+  if (observed) {
+    pendingChunkLoads.set(chunkPath, startTime);
+  }
   try {
     await BACKEND.loadChunk(chunkPath, source);
+    if (observed) {
+      markChunkLoaded(chunkPath, startTime);
+    }
   } catch (error) {
+    if (observed) {
+      pendingChunkLoads.delete(chunkPath);
+      notifyChunkLoadListeners({
+        chunkPath,
+        startTime,
+        duration: now() - startTime,
+        error,
+      });
+    }
     let loadReason;
     switch (source.type) {
       case SourceTypeRuntime:
@@ -501,6 +760,12 @@ async function loadChunk(source, chunkPath) {
         loadReason = "from an HMR update";
         break;
     }
+    reportRuntimeError({
+      type: "chunk-load",
+      chunkPath,
+      reason: loadReason,
+      error,
+    });
     throw new Error(
       `Failed to load chunk ${chunkPath} ${loadReason}${
         error ? `: ${error}` : ""
@@ -509,6 +774,60 @@ async function loadChunk(source, chunkPath) {
   }
 }
 
+/**
+ * @returns {number}
+ */
+function now() {
+  return typeof performance !== "undefined" ? performance.now() : Date.now();
+}
+
+/**
+ * Records that a chunk has been loaded. `startTime` is `null` for chunks that
+ * have been loaded by their `<script>` tag instead of the runtime.
+ *
+ * @param {ChunkPath} chunkPath
+ * @param {number | null} startTime
+ */
+function markChunkLoaded(chunkPath, startTime) {
+  if (loadedChunks.has(chunkPath)) {
+    return;
+  }
+  loadedChunks.add(chunkPath);
+  pendingChunkLoads.delete(chunkPath);
+  notifyChunkLoadListeners({
+    chunkPath,
+    startTime,
+    duration: startTime == null ? null : now() - startTime,
+    error: null,
+  });
+}
+
+/**
+ * @param {ChunkLoadEvent} event
+ */
+function notifyChunkLoadListeners(event) {
+  for (const listener of chunkLoadListeners) {
+    try {
+      listener(event);
+    } catch (error) {
+      // A broken listener must not break loading chunks.
+      console.error("Error in a chunk load listener:", error);
+    }
+  }
+}
+
+/**
+ * Prefetches the chunks of a dynamic import, so they're in the HTTP cache
+ * when the import is reached. Backends which can't fetch chunks without
+ * executing them don't prefetch.
+ *
+ * @param {PrefetchStrategy} strategy
+ * @param {() => Promise<ChunkPath[]>} getChunkPaths
+ */
+function prefetchChunks(strategy, getChunkPaths) {
+  BACKEND.prefetchChunks?.(strategy, getChunkPaths);
+}
+
 /** @type {SourceTypeRuntime} */
 const SourceTypeRuntime = 0;
 /** @type {SourceTypeParent} */
@@ -577,22 +896,29 @@ function instantiateModule(id, source) {
   }
 
   runModuleExecutionHooks(module, () => {
-    moduleFactory.call(module.exports, {
-      e: module.exports,
-      r: commonJsRequire.bind(null, module),
-      x: externalRequire,
-      i: esmImport.bind(null, module),
-      s: esm.bind(null, module.exports),
-      j: cjs.bind(null, module.exports),
-      v: exportValue.bind(null, module),
-      m: module,
-      c: moduleCache,
-      l: loadChunk.bind(null, { type: SourceTypeParent, parentId: id }),
-      k: registerChunkList,
-      p: _process,
-      g: globalThis,
-      __dirname: module.id.replace(/(^|\/)[\/]+$/, ""),
-    });
+    try {
+      moduleFactory.call(module.exports, {
+        e: module.exports,
+        r: commonJsRequire.bind(null, module),
+        x: externalRequire,
+        i: esmImport.bind(null, module),
+        s: esm.bind(null, module.exports),
+        j: cjs.bind(null, module.exports),
+        v: exportValue.bind(null, module),
+        m: module,
+        c: moduleCache,
+        l: loadChunk.bind(null, { type: SourceTypeParent, parentId: id }),
+        k: registerChunkList,
+        h: prefetchChunks,
+        f: markRefreshBoundary.bind(null, module),
+        p: _process,
+        g: globalThis,
+        __dirname: module.id.replace(/(^|\/)[\/]+$/, ""),
+      });
+    } catch (error) {
+      reportRuntimeError({ type: "module-instantiation", moduleId: id, error });
+      throw error;
+    }
   });
 
   module.loaded = true;
@@ -613,25 +939,191 @@ function instantiateModule(id, source) {
  * @param {() => void} executeModule
  */
 function runModuleExecutionHooks(module, executeModule) {
-  const cleanupReactRefreshIntercept =
-    typeof globalThis.$RefreshInterceptModuleExecution$ === "function"
-      ? globalThis.$RefreshInterceptModuleExecution$(module.id)
-      : () => {};
+  const cleanupReactRefreshIntercept = interceptModuleExecution(module.id);
 
   executeModule();
 
-  if ("$RefreshHelpers$" in globalThis) {
+  const refreshHelpers = getRefreshHelpers();
+  if (refreshHelpers) {
     // This pattern can also be used to register the exports of
     // a module with the React Refresh runtime.
-    registerExportsAndSetupBoundaryForReactRefresh(
-      module,
-      globalThis.$RefreshHelpers$
-    );
+    registerExportsAndSetupBoundaryForReactRefresh(module, refreshHelpers);
   }
 
   cleanupReactRefreshIntercept();
 }
 
+/**
+ * Called by modules processed by the React Refresh transform, with whether
+ * their exports statically look like a Refresh Boundary. Modules which the
+ * transform can't tell about don't call this.
+ *
+ * @param {Module} module
+ * @param {boolean} isBoundary
+ */
+function markRefreshBoundary(module, isBoundary) {
+  moduleHotState.get(module).refreshBoundary = isBoundary;
+}
+
+/**
+ * Sets up the globals that the React Refresh transform registers components
+ * and hook signatures with during the execution of a module.
+ *
+ * @param {ModuleId} moduleId
+ * @returns {() => void} restores the previous globals
+ */
+function interceptModuleExecution(moduleId) {
+  if (typeof globalThis.$RefreshInterceptModuleExecution$ === "function") {
+    return globalThis.$RefreshInterceptModuleExecution$(moduleId);
+  }
+
+  const refreshRuntime = globalThis.$RefreshRuntime$;
+  if (refreshRuntime == null) {
+    return () => {};
+  }
+
+  const prevRefreshReg = globalThis.$RefreshReg$;
+  const prevRefreshSig = globalThis.$RefreshSig$;
+  globalThis.$RefreshReg$ = (type, id) => {
+    refreshRuntime.register(type, moduleId + " " + id);
+  };
+  globalThis.$RefreshSig$ = refreshRuntime.createSignatureFunctionForTransform;
+
+  return () => {
+    globalThis.$RefreshReg$ = prevRefreshReg;
+    globalThis.$RefreshSig$ = prevRefreshSig;
+  };
+}
+
+/** @type {RefreshHelpers | undefined} */
+let builtInRefreshHelpers;
+
+/**
+ * Returns the React Refresh helpers, either the ones provided by the
+ * framework, or built-in ones when the app installed the `react-refresh`
+ * runtime as `globalThis.$RefreshRuntime$`.
+ *
+ * @returns {RefreshHelpers | undefined}
+ */
+function getRefreshHelpers() {
+  if ("$RefreshHelpers$" in globalThis) {
+    return globalThis.$RefreshHelpers$;
+  }
+
+  const refreshRuntime = globalThis.$RefreshRuntime$;
+  if (refreshRuntime == null) {
+    return undefined;
+  }
+
+  if (builtInRefreshHelpers === undefined) {
+    builtInRefreshHelpers = createRefreshHelpers(refreshRuntime);
+  }
+  return builtInRefreshHelpers;
+}
+
+/**
+ * This is adapted from https://github.com/vercel/next.js/blob/3466862d9dc9c8bb3131712134d38757b918d1c0/packages/react-refresh-utils/internal/helpers.ts
+ *
+ * @param {RefreshRuntime} refreshRuntime
+ * @returns {RefreshHelpers}
+ */
+function createRefreshHelpers(refreshRuntime) {
+  /**
+   * @param {string} key
+   * @returns {boolean}
+   */
+  function isSafeExport(key) {
+    return key === "__esModule";
+  }
+
+  /**
+   * @param {Exports} moduleExports
+   * @returns {unknown[]}
+   */
+  function getRefreshBoundarySignature(moduleExports) {
+    const signature = [refreshRuntime.getFamilyByType(moduleExports)];
+    if (moduleExports == null || typeof moduleExports !== "object") {
+      return signature;
+    }
+    for (const key in moduleExports) {
+      if (isSafeExport(key)) {
+        continue;
+      }
+      signature.push(key);
+      signature.push(refreshRuntime.getFamilyByType(moduleExports[key]));
+    }
+    return signature;
+  }
+
+  let isUpdateScheduled = false;
+
+  return {
+    registerExportsForReactRefresh(moduleExports, moduleId) {
+      refreshRuntime.register(moduleExports, moduleId + " %exports%");
+      if (moduleExports == null || typeof moduleExports !== "object") {
+        return;
+      }
+      for (const key in moduleExports) {
+        if (isSafeExport(key)) {
+          continue;
+        }
+        refreshRuntime.register(
+          moduleExports[key],
+          moduleId + " %exports% " + key
+        );
+      }
+    },
+
+    isReactRefreshBoundary(moduleExports) {
+      if (refreshRuntime.isLikelyComponentType(moduleExports)) {
+        return true;
+      }
+      if (moduleExports == null || typeof moduleExports !== "object") {
+        return false;
+      }
+      let hasExports = false;
+      let areAllExportsComponents = true;
+      for (const key in moduleExports) {
+        hasExports = true;
+        if (isSafeExport(key)) {
+          continue;
+        }
+        if (!refreshRuntime.isLikelyComponentType(moduleExports[key])) {
+          areAllExportsComponents = false;
+        }
+      }
+      return hasExports && areAllExportsComponents;
+    },
+
+    shouldInvalidateReactRefreshBoundary(prevExports, nextExports) {
+      const prevSignature = getRefreshBoundarySignature(prevExports);
+      const nextSignature = getRefreshBoundarySignature(nextExports);
+      if (prevSignature.length !== nextSignature.length) {
+        return true;
+      }
+      for (let i = 0; i < nextSignature.length; i++) {
+        if (prevSignature[i] !== nextSignature[i]) {
+          return true;
+        }
+      }
+      return false;
+    },
+
+    scheduleUpdate() {
+      if (isUpdateScheduled) {
+        return;
+      }
+      isUpdateScheduled = true;
+      // All modules of an HMR update are executed synchronously, so they are
+      // refreshed at once.
+      Promise.resolve().then(() => {
+        isUpdateScheduled = false;
+        refreshRuntime.performReactRefresh();
+      });
+    },
+  };
+}
+
 /**
  * Retrieves a module from the cache, or instantiate it if it is not cached.
  *
@@ -679,8 +1171,15 @@ function registerExportsAndSetupBoundaryForReactRefresh(module, helpers) {
   helpers.registerExportsForReactRefresh(currentExports, module.id);
 
   // A module can be accepted automatically based on its exports, e.g. when
-  // it is a Refresh Boundary.
-  if (helpers.isReactRefreshBoundary(currentExports)) {
+  // it is a Refresh Boundary. Modules which the React Refresh transform found
+  // to not be refresh safe are never accepted, so their updates bubble up to
+  // their importers.
+  const isStaticallyRefreshSafe =
+    moduleHotState.get(module).refreshBoundary !== false;
+  if (
+    isStaticallyRefreshSafe &&
+    helpers.isReactRefreshBoundary(currentExports)
+  ) {
     // Save the previous exports on update so we can compare the boundary
     // signatures.
     module.hot.dispose((data) => {
@@ -1044,7 +1543,8 @@ function applyChunkListUpdate(chunkListPath, update) {
  * @param {EcmascriptMergedUpdate} update
  */
 function applyEcmascriptMergedUpdate(chunkPath, update) {
-  const { entries = {}, chunks = {} } = update;
+  const { chunks = {} } = update;
+  const entries = resolveModuleEntries(update.entries ?? {});
   const { added, modified, deleted, chunksAdded, chunksDeleted } =
     computeChangedModules(entries, chunks);
   const { outdatedModules, newModuleFactories } = computeOutdatedModules(
@@ -1064,6 +1564,61 @@ function applyEcmascriptMergedUpdate(chunkPath, update) {
     newModuleFactories,
     outdatedModuleParents
   );
+
+  for (const [moduleId, { code, hash }] of Object.entries(entries)) {
+    moduleUpdateCode.set(moduleId, { code, hash });
+  }
+}
+
+/**
+ * Resolves the code of module entries that are sent as diffs against the
+ * previous code of their module.
+ *
+ * @param {Record<ModuleId, EcmascriptModuleEntry>} entries
+ * @returns {Record<ModuleId, EcmascriptModuleEntry & { code: string }>}
+ */
+function resolveModuleEntries(entries) {
+  const resolved = {};
+  for (const [moduleId, entry] of Object.entries(entries)) {
+    const { diff } = entry;
+    if (diff == null) {
+      resolved[moduleId] = entry;
+      continue;
+    }
+    const previous = moduleUpdateCode.get(moduleId);
+    if (previous?.hash === entry.hash) {
+      // The module is skipped, see `computeChangedModules`.
+      resolved[moduleId] = { ...entry, code: previous.code };
+      continue;
+    }
+    const base = previous?.code ?? moduleFactorySource(moduleId);
+    if (base == null || base.length !== diff.baseLength) {
+      throw new Error(
+        `cannot apply update: the code a diff of module ${moduleId} was computed against is not available.`
+      );
+    }
+    resolved[moduleId] = {
+      ...entry,
+      code:
+        base.slice(0, diff.prefix) +
+        diff.insert +
+        base.slice(base.length - diff.suffix),
+    };
+  }
+  return resolved;
+}
+
+/**
+ * The code of the factory of a module that was loaded with its chunk.
+ *
+ * @param {ModuleId} moduleId
+ * @returns {string | undefined}
+ */
+function moduleFactorySource(moduleId) {
+  const factory = moduleFactories[moduleId];
+  // Module factories are parenthesized function expressions, and the source
+  // text of a function doesn't include the parentheses.
+  return factory == null ? undefined : `(${factory.toString()})`;
 }
 
 /**
@@ -1140,7 +1695,12 @@ function computeChangedModules(entries, updates) {
     // to be modified.
     // This needs to be under the previous loop, as we need it to get rid of modules
     // that were added and deleted in the same update.
-    if (!added.has(moduleId)) {
+    // Modules whose code is unchanged are skipped, e.g. when an update of
+    // another chunk list changed them already.
+    if (
+      !added.has(moduleId) &&
+      moduleUpdateCode.get(moduleId)?.hash !== entry.hash
+    ) {
       modified.set(moduleId, entry);
     }
   }
@@ -1279,6 +1839,7 @@ function createModuleHot(hotData) {
     selfAccepted: false,
     selfDeclined: false,
     selfInvalidated: false,
+    refreshBoundary: undefined,
     disposeHandlers: [],
   };
 
@@ -1572,11 +2133,30 @@ async function registerChunk([chunkPath, chunkModules, runtimeParams]) {
   }
 
   BACKEND.registerChunk(chunkPath, runtimeParams);
+
+  if (!pendingChunkLoads.has(chunkPath)) {
+    markChunkLoaded(chunkPath, null);
+  }
 }
 
 globalThis.TURBOPACK_CHUNK_UPDATE_LISTENERS =
   globalThis.TURBOPACK_CHUNK_UPDATE_LISTENERS || [];
 
+/**
+ * Lets application code and performance tooling observe the chunks loaded by
+ * the runtime.
+ */
+globalThis.__turbopack__ = {
+  getLoadedChunks: () => [...loadedChunks],
+  getPendingChunks: () => [...pendingChunkLoads.keys()],
+  onChunkLoad(listener) {
+    chunkLoadListeners.add(listener);
+    return () => {
+      chunkLoadListeners.delete(listener);
+    };
+  },
+};
+
 const chunksToRegister = globalThis.TURBOPACK;
 globalThis.TURBOPACK = {
   push: registerChunk,
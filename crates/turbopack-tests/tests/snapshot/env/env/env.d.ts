@@ -0,0 +1,8 @@
+// This file is generated from the env. Don't edit it.
+
+declare namespace NodeJS {
+  interface ProcessEnv {
+    readonly FOO: string;
+    readonly FOOBAR: string;
+  }
+}
@@ -0,0 +1,110 @@
+#![cfg(test)]
+
+mod util;
+
+use anyhow::{anyhow, Result};
+use turbo_tasks::{TryJoinIterExt, TurboTasks};
+use turbo_tasks_memory::MemoryBackend;
+use turbopack_core::{
+    asset::Asset,
+    issue::{IssueSeverity, IssueVc},
+};
+use util::{
+    asset_context, browser_env, chunking_context_builder, entry_module, fixture_root, text_content,
+};
+
+fn register() {
+    turbopack::register();
+    turbopack_dev::register();
+    include!(concat!(env!("OUT_DIR"), "/register_test_star_exports.rs"));
+}
+
+struct Chunk {
+    code: String,
+    /// The severity, title and description of the issues of the chunk.
+    issues: Vec<(IssueSeverity, String, String)>,
+}
+
+/// The evaluated chunk of `tests/star_exports/index.js`, which imports from a
+/// module whose two `export *` both provide `shared`, with different bindings.
+async fn chunk() -> Result<Chunk> {
+    let root = fixture_root("star_exports")?;
+    let env = browser_env("last 1 Chrome versions");
+    let chunking_context = chunking_context_builder(root, env).build();
+
+    let module = entry_module(asset_context(env), root, "index.js").await?;
+    let chunk = module.as_evaluated_chunk(chunking_context, None);
+    let code = text_content(chunk.into()).await?;
+
+    let captured_issues = IssueVc::peek_issues_with_path(chunk.content())
+        .await?
+        .strongly_consistent()
+        .await?;
+    let issues = captured_issues
+        .iter_with_shortest_path()
+        .map(|(issue, _)| async move {
+            Ok((
+                *issue.severity().await?,
+                issue.title().await?.clone_value(),
+                issue.description().await?.clone_value(),
+            ))
+        })
+        .try_join()
+        .await?;
+    Ok(Chunk { code, issues })
+}
+
+/// The code of the module at `path` in the chunk `code`.
+fn module_code<'a>(code: &'a str, path: &str) -> Result<&'a str> {
+    let key = format!("/{path} (ecmascript)\": ");
+    let start = code
+        .find(&key)
+        .ok_or_else(|| anyhow!("{path} is not in the chunk"))?;
+    let module = &code[start + key.len()..];
+    Ok(match module.find("\n\"[") {
+        Some(end) => &module[..end],
+        None => module,
+    })
+}
+
+#[tokio::test]
+async fn ambiguous_star_exports_are_excluded() {
+    register();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let chunk = tt.run_once(async move { chunk().await }).await.unwrap();
+
+    let reexports = module_code(&chunk.code, "reexports.js").unwrap();
+    assert!(reexports.contains("\"onlyA\": "), "{reexports}");
+    assert!(!reexports.contains("\"shared\""), "{reexports}");
+}
+
+#[tokio::test]
+async fn imports_of_ambiguous_star_exports_are_reported() {
+    register();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let chunk = tt.run_once(async move { chunk().await }).await.unwrap();
+
+    let ambiguous = chunk
+        .issues
+        .iter()
+        .filter(|(_, title, _)| title == "ambiguous import of shared")
+        .collect::<Vec<_>>();
+    assert_eq!(ambiguous.len(), 1, "{:?}", chunk.issues);
+    let (severity, _, description) = ambiguous[0];
+    assert_eq!(*severity, IssueSeverity::Error);
+    assert!(
+        description.contains("The export shared of module")
+            && description.contains("reexports.js")
+            && description.contains("provided by multiple `export *`"),
+        "{description}"
+    );
+    // Names provided by a single star export are not ambiguous.
+    assert!(
+        chunk
+            .issues
+            .iter()
+            .all(|(_, title, _)| title != "ambiguous import of onlyA"),
+        "{:?}",
+        chunk.issues
+    );
+}
@@ -0,0 +1,3 @@
+import { onlyA, shared } from "./reexports.js";
+
+console.log(onlyA, shared);
@@ -0,0 +1,125 @@
+//! Setup shared by the tests that process the files of a fixture directory in
+//! `tests/` and chunk them with the dev chunking context.
+
+// Each test crate only uses some of the helpers.
+#![allow(dead_code)]
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use dunce::canonicalize;
+use turbo_tasks::Value;
+use turbo_tasks_fs::{DiskFileSystemVc, FileSystem, FileSystemPathVc};
+use turbopack::{
+    ecmascript::EcmascriptModuleAssetVc, module_options::ModuleOptionsContext,
+    resolve_options_context::ResolveOptionsContext, transition::TransitionsByNameVc,
+    ModuleAssetContextVc,
+};
+use turbopack_core::{
+    asset::{Asset, AssetVc},
+    compile_time_info::CompileTimeInfoVc,
+    context::AssetContext,
+    environment::{
+        BrowserEnvironment, EnvironmentIntention, EnvironmentVc, ExecutionEnvironment,
+        NodeJsEnvironment,
+    },
+    reference_type::{EntryReferenceSubType, ReferenceType},
+    source_asset::SourceAssetVc,
+};
+use turbopack_dev::{DevChunkingContextBuilder, DevChunkingContextVc};
+
+/// The root of the fixture directory `tests/{name}`.
+pub fn fixture_root(name: &str) -> Result<FileSystemPathVc> {
+    disk_root(
+        &PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join(name),
+    )
+}
+
+/// The root of a disk file system at `path`.
+pub fn disk_root(path: &Path) -> Result<FileSystemPathVc> {
+    let root = canonicalize(path)?;
+    let fs = DiskFileSystemVc::new("test".to_string(), root.to_str().unwrap().to_string());
+    Ok(fs.root())
+}
+
+/// A client environment for a browser matching `browserslist`.
+pub fn browser_env(browserslist: &str) -> EnvironmentVc {
+    EnvironmentVc::new(
+        Value::new(ExecutionEnvironment::Browser(
+            BrowserEnvironment {
+                dom: true,
+                web_worker: false,
+                service_worker: false,
+                browserslist_query: browserslist.to_string(),
+            }
+            .into(),
+        )),
+        Value::new(EnvironmentIntention::Client),
+    )
+}
+
+/// A server rendering environment for Node.js.
+pub fn node_env() -> EnvironmentVc {
+    EnvironmentVc::new(
+        Value::new(ExecutionEnvironment::NodeJsLambda(
+            NodeJsEnvironment::default().into(),
+        )),
+        Value::new(EnvironmentIntention::ServerRendering),
+    )
+}
+
+/// An asset context with the default module and resolve options. The modules
+/// themselves are not transpiled.
+pub fn asset_context(env: EnvironmentVc) -> ModuleAssetContextVc {
+    ModuleAssetContextVc::new(
+        TransitionsByNameVc::cell(HashMap::new()),
+        CompileTimeInfoVc::new(env),
+        ModuleOptionsContext::default().cell(),
+        ResolveOptionsContext::default().cell(),
+    )
+}
+
+/// A dev chunking context which writes chunks to `{root}/output`.
+pub fn chunking_context_builder(
+    root: FileSystemPathVc,
+    env: EnvironmentVc,
+) -> DevChunkingContextBuilder {
+    DevChunkingContextVc::builder(
+        root,
+        root,
+        root.join("output"),
+        root.join("output/static"),
+        env,
+    )
+}
+
+/// The file `entry` of the `root`, processed as an entry by the `context`.
+pub async fn entry_module(
+    context: ModuleAssetContextVc,
+    root: FileSystemPathVc,
+    entry: &str,
+) -> Result<EcmascriptModuleAssetVc> {
+    let module = context.process(
+        SourceAssetVc::new(root.join(entry)).into(),
+        Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+    );
+    EcmascriptModuleAssetVc::resolve_from(module)
+        .await?
+        .ok_or_else(|| anyhow!("{entry} is not an ecmascript module"))
+}
+
+/// The content of the `asset` as a string.
+pub async fn text_content(asset: AssetVc) -> Result<String> {
+    let content = asset.content().file_content().await?;
+    Ok(content
+        .as_content()
+        .ok_or_else(|| anyhow!("the asset has no content"))?
+        .content()
+        .to_str()?
+        .to_string())
+}
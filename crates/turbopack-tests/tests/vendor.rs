@@ -0,0 +1,174 @@
+#![cfg(test)]
+
+mod util;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use tempfile::TempDir;
+use turbo_tasks::TurboTasks;
+use turbo_tasks_memory::MemoryBackend;
+use turbopack::emit_with_completion;
+use turbopack_core::source_map::GenerateSourceMapVc;
+use util::{
+    asset_context, browser_env, chunking_context_builder, disk_root, entry_module, text_content,
+};
+
+fn register() {
+    turbopack::register();
+    turbopack_dev::register();
+    include!(concat!(env!("OUT_DIR"), "/register_test_vendor.rs"));
+}
+
+/// Writes the `heavy` package to `dir`. Its entry imports `a.js`, whose code
+/// is `a`.
+fn write_package(dir: &Path, a: &str) {
+    let package = dir.join("node_modules/heavy");
+    fs::create_dir_all(&package).unwrap();
+    fs::write(
+        package.join("package.json"),
+        r#"{ "name": "heavy", "version": "1.0.0" }"#,
+    )
+    .unwrap();
+    fs::write(
+        package.join("index.js"),
+        "import { greeting } from \"./a.js\";\nconsole.log(greeting);\n",
+    )
+    .unwrap();
+    fs::write(package.join("a.js"), a).unwrap();
+}
+
+struct Build {
+    code: String,
+    source_map: String,
+}
+
+/// Chunks the entry of the `heavy` package in `dir` with vendor prebundling
+/// and emits the chunk. Every build uses a new turbo tasks instance, like
+/// after a restart.
+async fn build(dir: &Path) -> Build {
+    let dir = dir.to_path_buf();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.run_once(async move {
+        let root = disk_root(&dir)?;
+        let env = browser_env("last 1 Chrome versions");
+        let chunking_context = chunking_context_builder(root, env)
+            .vendor_prebundling(root.join("output/vendor"))
+            .build();
+        let module = entry_module(asset_context(env), root, "node_modules/heavy/index.js").await?;
+        let chunk = module.as_evaluated_chunk(chunking_context, None);
+        emit_with_completion(chunk.into(), root.join("output")).await?;
+
+        let source_map = GenerateSourceMapVc::resolve_from(chunk)
+            .await?
+            .ok_or_else(|| anyhow!("the chunk has no source map"))?
+            .generate_source_map()
+            .await?
+            .ok_or_else(|| anyhow!("the chunk has no source map"))?;
+        Ok(Build {
+            code: text_content(chunk.into()).await?,
+            source_map: source_map.to_rope().await?.to_str()?.into_owned(),
+        })
+    })
+    .await
+    .unwrap()
+}
+
+/// The cache files written to `dir`.
+fn cache_files(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir.join("output/vendor"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect()
+}
+
+/// Changes the greeting in the cached code, so code read from the cache can
+/// be told apart from generated code.
+fn mark_cached_code(file: &Path) -> Result<()> {
+    let mut entries: serde_json::Value = serde_json::from_slice(&fs::read(file)?)?;
+    for entry in entries
+        .as_array_mut()
+        .ok_or_else(|| anyhow!("the cache file is not an array"))?
+    {
+        let code = entry["code"]
+            .as_str()
+            .ok_or_else(|| anyhow!("a cache entry has no code"))?
+            .replace("hello from a", "hello from the cache");
+        entry["code"] = code.into();
+    }
+    fs::write(file, serde_json::to_vec(&entries)?)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn cached_code_is_used_after_a_restart() {
+    register();
+    let dir = TempDir::new().unwrap();
+    write_package(dir.path(), "export const greeting = \"hello from a\";\n");
+
+    let first = build(dir.path()).await;
+    assert!(first.code.contains("hello from a"), "{}", first.code);
+    let files = cache_files(dir.path());
+    assert_eq!(files.len(), 1, "{files:?}");
+    mark_cached_code(&files[0]).unwrap();
+
+    let second = build(dir.path()).await;
+    assert!(
+        second.code.contains("hello from the cache"),
+        "{}",
+        second.code
+    );
+    assert!(!second.code.contains("hello from a"), "{}", second.code);
+}
+
+#[tokio::test]
+async fn source_maps_are_cached() {
+    register();
+    let dir = TempDir::new().unwrap();
+    write_package(dir.path(), "export const greeting = \"hello from a\";\n");
+
+    let first = build(dir.path()).await;
+    assert!(
+        first.source_map.contains("heavy/a.js"),
+        "{}",
+        first.source_map
+    );
+    mark_cached_code(&cache_files(dir.path())[0]).unwrap();
+
+    let second = build(dir.path()).await;
+    assert!(
+        second.code.contains("hello from the cache"),
+        "{}",
+        second.code
+    );
+    assert!(
+        second.source_map.contains("heavy/a.js"),
+        "{}",
+        second.source_map
+    );
+    assert!(
+        second.source_map.contains("heavy/index.js"),
+        "{}",
+        second.source_map
+    );
+}
+
+#[tokio::test]
+async fn changed_modules_are_not_read_from_the_cache() {
+    register();
+    let dir = TempDir::new().unwrap();
+    write_package(dir.path(), "export const greeting = \"hello from a\";\n");
+
+    build(dir.path()).await;
+    mark_cached_code(&cache_files(dir.path())[0]).unwrap();
+    write_package(dir.path(), "export const greeting = \"changed\";\n");
+
+    let second = build(dir.path()).await;
+    assert!(second.code.contains("changed"), "{}", second.code);
+    assert!(!second.code.contains("hello from"), "{}", second.code);
+    // The chunk has a new cache file, and the old one is left alone.
+    assert_eq!(cache_files(dir.path()).len(), 2);
+}
@@ -3,7 +3,7 @@ use turbo_tasks::Value;
 use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_core::{
     compile_time_defines,
-    compile_time_info::CompileTimeInfo,
+    compile_time_info::{CompileTimeInfo, FeatureFlagsVc},
     context::AssetContextVc,
     environment::{EnvironmentIntention, EnvironmentVc, ExecutionEnvironment, NodeJsEnvironment},
     resolve::options::ImportMapVc,
@@ -39,6 +39,7 @@ pub async fn node_evaluate_asset_context(
                 process.env.NODE_ENV = "development",
             )
             .cell(),
+            feature_flags: FeatureFlagsVc::empty(),
         }
         .cell(),
         ModuleOptionsContext {
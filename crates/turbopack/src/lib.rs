@@ -13,7 +13,7 @@ use std::{
     mem::swap,
 };
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use css::{CssModuleAssetVc, ModuleCssModuleAssetVc};
 use ecmascript::{
     tree_shake::asset::EcmascriptModulePartAssetVc,
@@ -22,19 +22,21 @@ use ecmascript::{
 };
 use graph::{aggregate, AggregatedGraphNodeContent, AggregatedGraphVc};
 use lazy_static::lazy_static;
+use locale_messages::locale_messages_loader;
 use module_options::{
     ModuleOptionsContextVc, ModuleOptionsVc, ModuleRuleEffect, ModuleType, ModuleTypeVc,
 };
 pub use resolve::resolve_options;
 use turbo_tasks::{
     primitives::{BoolVc, StringVc},
-    CompletionVc, Value,
+    CompletionVc, Value, ValueToString,
 };
 use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_core::{
     asset::{Asset, AssetVc},
     compile_time_info::CompileTimeInfoVc,
     context::{AssetContext, AssetContextVc},
+    emitted_paths::{emitted_path_collisions, EmittedPathsVc},
     ident::AssetIdentVc,
     issue::{unsupported_module::UnsupportedModuleIssue, Issue, IssueVc},
     reference::all_referenced_assets,
@@ -44,8 +46,9 @@ use turbopack_core::{
         origin::PlainResolveOriginVc,
         parse::{Request, RequestVc},
         pattern::Pattern,
-        resolve, ModulePartVc, ResolveResultVc,
+        resolve, ModulePartVc, ResolveResult, ResolveResultVc,
     },
+    source_asset::SourceAssetVc,
 };
 
 use crate::transition::Transition;
@@ -53,11 +56,13 @@ use crate::transition::Transition;
 pub mod condition;
 pub mod evaluate_context;
 mod graph;
+pub mod locale_messages;
 pub mod module_options;
 pub mod rebase;
 pub mod resolve;
 pub mod resolve_options_context;
 pub mod transition;
+pub mod wintercg;
 
 pub use turbopack_css as css;
 pub use turbopack_ecmascript as ecmascript;
@@ -165,6 +170,9 @@ async fn apply_module_type(
         )
         .into(),
         ModuleType::Json => JsonModuleAssetVc::new(source).into(),
+        ModuleType::JsonWithSchema(schema) => {
+            JsonModuleAssetVc::new_with_schema(source, *schema).into()
+        }
         ModuleType::Raw => source,
         ModuleType::Css(transforms) => {
             CssModuleAssetVc::new(source, context.into(), *transforms).into()
@@ -173,6 +181,10 @@ async fn apply_module_type(
             ModuleCssModuleAssetVc::new(source, context.into(), *transforms).into()
         }
         ModuleType::Static => StaticModuleAssetVc::new(source, context.into()).into(),
+        ModuleType::InlinedStatic { size_limit } => {
+            StaticModuleAssetVc::new_with_inline_size_limit(source, context.into(), *size_limit)
+                .into()
+        }
         ModuleType::Mdx(transforms) => {
             MdxModuleAssetVc::new(source, context.into(), *transforms).into()
         }
@@ -344,6 +356,16 @@ impl ModuleAssetContextVc {
                                 }
                             };
                         }
+                        ModuleRuleEffect::Replace(replacement) => {
+                            // A mock can match its own rule, e.g. when it has the
+                            // file name of the mocked module.
+                            if replacement.resolve().await? != ident.path().resolve().await? {
+                                return Ok(self_vc.process_default(
+                                    SourceAssetVc::new(*replacement).into(),
+                                    Value::new(reference_type),
+                                ));
+                            }
+                        }
                         ModuleRuleEffect::Custom => {
                             todo!("Custom module rule effects are not yet supported");
                         }
@@ -395,6 +417,17 @@ impl AssetContext for ModuleAssetContext {
 
         let context_path = origin_path.parent().resolve().await?;
 
+        if self_vc
+            .module_options_context()
+            .await?
+            .enable_locale_messages
+        {
+            if let Some(loader) = *locale_messages_loader(context_path, request).await? {
+                return Ok(self_vc
+                    .process_resolve_result(ResolveResult::asset(loader).cell(), reference_type));
+            }
+        }
+
         let result = resolve(context_path, request, resolve_options);
         let result = self_vc.process_resolve_result(result, reference_type);
 
@@ -471,9 +504,43 @@ pub async fn emit_with_completion(asset: AssetVc, output_dir: FileSystemPathVc)
 }
 
 #[turbo_tasks::function]
-async fn emit_assets_aggregated(asset: AssetVc, output_dir: FileSystemPathVc) -> CompletionVc {
+async fn emit_assets_aggregated(
+    asset: AssetVc,
+    output_dir: FileSystemPathVc,
+) -> Result<CompletionVc> {
     let aggregated = aggregate(asset);
-    emit_aggregated_assets(aggregated, output_dir)
+    // Writing colliding assets would leave whichever is written last on disk,
+    // so nothing is emitted until every output path has a single producer.
+    let collisions =
+        emitted_path_collisions(emitted_aggregated_paths(aggregated, output_dir)).await?;
+    if let Some(collision) = collisions.first() {
+        bail!(
+            "{} assets are emitted to {}: {}",
+            collision.producers.len(),
+            collision.path.to_string().await?,
+            collision.producers.join(", ")
+        );
+    }
+    Ok(emit_aggregated_assets(aggregated, output_dir))
+}
+
+#[turbo_tasks::function]
+async fn emitted_aggregated_paths(
+    aggregated: AggregatedGraphVc,
+    output_dir: FileSystemPathVc,
+) -> Result<EmittedPathsVc> {
+    Ok(match &*aggregated.content().await? {
+        AggregatedGraphNodeContent::Asset(asset) => EmittedPathsVc::of_asset(*asset, output_dir),
+        AggregatedGraphNodeContent::Children(children) => {
+            EmittedPathsVc::merge(
+                children
+                    .iter()
+                    .map(|aggregated| emitted_aggregated_paths(*aggregated, output_dir))
+                    .collect::<Vec<_>>(),
+            )
+            .await?
+        }
+    })
 }
 
 #[turbo_tasks::function]
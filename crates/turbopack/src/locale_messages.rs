@@ -0,0 +1,111 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use turbo_tasks_fs::{File, FileSystemPathVc};
+use turbopack_core::{
+    asset::{AssetOptionVc, AssetVc},
+    resolve::{
+        parse::{Request, RequestVc},
+        pattern::Pattern,
+    },
+    virtual_asset::VirtualAssetVc,
+};
+use turbopack_ecmascript::utils::StringifyJs;
+
+/// A request for a set of message catalogs, with one catalog per locale.
+///
+/// ### Example
+/// ```js
+/// import loadMessages from "./messages/{en,de}.json";
+///
+/// const messages = await loadMessages("de");
+/// ```
+///
+/// Instead of bundling all catalogs, the request resolves to a loader module
+/// that dynamically imports the catalog of the requested locale. This places
+/// every catalog in its own async chunk, so only the catalog of the current
+/// locale is loaded at runtime. When no locale is passed to the loader,
+/// `globalThis.__TURBOPACK_LOCALE__` is used.
+struct LocaleMessagesRequest<'a> {
+    prefix: &'a str,
+    locales: Vec<&'a str>,
+    suffix: &'a str,
+}
+
+impl<'a> LocaleMessagesRequest<'a> {
+    fn parse(request: &'a str) -> Option<Self> {
+        let (prefix, rest) = request.split_once('{')?;
+        let (locales, suffix) = rest.split_once('}')?;
+        if suffix.contains(['{', '}']) {
+            return None;
+        }
+        let locales: Vec<_> = locales.split(',').map(str::trim).collect();
+        if locales.len() < 2 || locales.iter().any(|locale| locale.is_empty()) {
+            return None;
+        }
+        Some(Self {
+            prefix,
+            locales,
+            suffix,
+        })
+    }
+
+    fn loader_code(&self, request: &str) -> Result<String> {
+        let mut code = String::new();
+        writeln!(code, "const catalogs = {{")?;
+        for locale in &self.locales {
+            writeln!(
+                code,
+                "    {}: () => import({}),",
+                StringifyJs(locale),
+                StringifyJs(&format!("{}{}{}", self.prefix, locale, self.suffix))
+            )?;
+        }
+        writeln!(code, "}};")?;
+        writeln!(
+            code,
+            "export const locales = {};",
+            StringifyJs(&self.locales)
+        )?;
+        write!(
+            code,
+            r#"export default function loadMessages(locale = globalThis.__TURBOPACK_LOCALE__) {{
+    const load = Object.prototype.hasOwnProperty.call(catalogs, locale) ? catalogs[locale] : undefined;
+    if (load === undefined) {{
+        return Promise.reject(new Error("No messages for locale " + JSON.stringify(locale) + " in " + {request}));
+    }}
+    return load().then((module) => module.default);
+}}
+"#,
+            request = StringifyJs(request),
+        )?;
+        Ok(code)
+    }
+}
+
+/// Creates the loader module for requests like `./messages/{en,de}.json`.
+/// Returns `None` when the request doesn't refer to per-locale catalogs.
+#[turbo_tasks::function]
+pub async fn locale_messages_loader(
+    context_path: FileSystemPathVc,
+    request: RequestVc,
+) -> Result<AssetOptionVc> {
+    let Request::Relative { path: Pattern::Constant(path), .. } = &*request.await? else {
+        return Ok(AssetOptionVc::cell(None));
+    };
+    let Some(locale_request) = LocaleMessagesRequest::parse(path) else {
+        return Ok(AssetOptionVc::cell(None));
+    };
+
+    // The loader is placed next to the importing module, so the relative
+    // catalog requests resolve the same way as the original request.
+    let file_name = path
+        .trim_start_matches("./")
+        .replace(['/', '{', '}', ','], "_");
+    let asset: AssetVc = VirtualAssetVc::new(
+        context_path.join(&format!("{file_name}.locales.js")),
+        File::from(locale_request.loader_code(path)?).into(),
+    )
+    .into();
+    Ok(AssetOptionVc::cell(Some(asset)))
+}
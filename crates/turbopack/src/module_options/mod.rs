@@ -6,8 +6,8 @@ use anyhow::{Context, Result};
 pub use module_options_context::*;
 pub use module_rule::*;
 pub use rule_condition::*;
-use turbo_tasks::primitives::OptionStringVc;
-use turbo_tasks_fs::FileSystemPathVc;
+use turbo_tasks::{primitives::OptionStringVc, Value};
+use turbo_tasks_fs::{glob::GlobVc, FileSystemPathVc};
 use turbopack_core::{
     reference_type::{ReferenceType, UrlReferenceSubType},
     resolve::options::{ImportMap, ImportMapVc, ImportMapping, ImportMappingVc},
@@ -17,7 +17,10 @@ use turbopack_css::{CssInputTransform, CssInputTransformsVc};
 use turbopack_ecmascript::{
     EcmascriptInputTransform, EcmascriptInputTransformsVc, EcmascriptOptions,
 };
-use turbopack_node::transforms::{postcss::PostCssTransformVc, webpack::WebpackLoadersVc};
+use turbopack_node::transforms::{
+    font_subset::FontSubsetTransformVc, lint::LintTransformVc, postcss::PostCssTransformVc,
+    webpack::WebpackLoadersVc,
+};
 
 use crate::evaluate_context::node_evaluate_asset_context;
 
@@ -67,17 +70,24 @@ impl ModuleOptionsVc {
             enable_styled_components,
             enable_types,
             enable_tree_shaking,
+            enable_strict_analysis,
+            chunk_prefetch,
             ref enable_typescript_transform,
             ref decorators,
             enable_mdx,
             ref enable_postcss_transform,
             ref enable_webpack_loaders,
+            ref enable_lint,
+            ref enable_font_subsetting,
             preset_env_versions,
             ref custom_ecmascript_app_transforms,
             ref custom_ecmascript_transforms,
             ref custom_rules,
             execution_context,
             ref rules,
+            ref json_schemas,
+            ref module_mocks,
+            inline_static_assets_size_limit,
             ..
         } = *context.await?;
         if !rules.is_empty() {
@@ -114,6 +124,8 @@ impl ModuleOptionsVc {
         let ecmascript_options = EcmascriptOptions {
             split_into_parts: enable_tree_shaking,
             import_parts: enable_tree_shaking,
+            strict_analysis: enable_strict_analysis,
+            prefetch: chunk_prefetch,
         };
 
         if let Some(env) = preset_env_versions {
@@ -201,6 +213,11 @@ impl ModuleOptionsVc {
             .collect(),
         );
 
+        let static_module_type = match inline_static_assets_size_limit {
+            Some(size_limit) => ModuleType::InlinedStatic { size_limit },
+            None => ModuleType::Static,
+        };
+
         let mut rules = vec![
             ModuleRule::new(
                 ModuleRuleCondition::ResourcePathEndsWith(".json".to_string()),
@@ -296,12 +313,17 @@ impl ModuleOptionsVc {
                     ModuleRuleCondition::ResourcePathEndsWith(".ico".to_string()),
                     ModuleRuleCondition::ResourcePathEndsWith(".jpg".to_string()),
                     ModuleRuleCondition::ResourcePathEndsWith(".jpeg".to_string()),
+                    ModuleRuleCondition::ResourcePathEndsWith(".mp3".to_string()),
+                    ModuleRuleCondition::ResourcePathEndsWith(".otf".to_string()),
                     ModuleRuleCondition::ResourcePathEndsWith(".png".to_string()),
                     ModuleRuleCondition::ResourcePathEndsWith(".svg".to_string()),
+                    ModuleRuleCondition::ResourcePathEndsWith(".ttf".to_string()),
+                    ModuleRuleCondition::ResourcePathEndsWith(".wav".to_string()),
                     ModuleRuleCondition::ResourcePathEndsWith(".webp".to_string()),
+                    ModuleRuleCondition::ResourcePathEndsWith(".woff".to_string()),
                     ModuleRuleCondition::ResourcePathEndsWith(".woff2".to_string()),
                 ]),
-                vec![ModuleRuleEffect::ModuleType(ModuleType::Static)],
+                vec![ModuleRuleEffect::ModuleType(static_module_type)],
             ),
             ModuleRule::new(
                 ModuleRuleCondition::ResourcePathHasNoExtension,
@@ -314,10 +336,22 @@ impl ModuleOptionsVc {
                 ModuleRuleCondition::ReferenceType(ReferenceType::Url(
                     UrlReferenceSubType::Undefined,
                 )),
-                vec![ModuleRuleEffect::ModuleType(ModuleType::Static)],
+                vec![ModuleRuleEffect::ModuleType(static_module_type)],
             ),
         ];
 
+        for JsonSchemaRule { file_name, schema } in json_schemas {
+            rules.push(ModuleRule::new(
+                ModuleRuleCondition::All(vec![
+                    ModuleRuleCondition::ResourcePathEndsWith(".json".to_string()),
+                    ModuleRuleCondition::ResourceFileNameGlob(GlobVc::new(file_name).await?),
+                ]),
+                vec![ModuleRuleEffect::ModuleType(ModuleType::JsonWithSchema(
+                    *schema,
+                ))],
+            ));
+        }
+
         if enable_mdx {
             rules.push(ModuleRule::new(
                 ModuleRuleCondition::ResourcePathEndsWith(".mdx".to_string()),
@@ -366,6 +400,79 @@ impl ModuleOptionsVc {
             }
         }
 
+        if let Some(lint_options) = enable_lint {
+            let execution_context = execution_context
+                .context("execution_context is required for linting")?
+                .with_layer("lint");
+            // Linting sees the original source, so it needs to happen before
+            // any other source transforms.
+            rules.insert(
+                0,
+                ModuleRule::new(
+                    ModuleRuleCondition::All(vec![
+                        ModuleRuleCondition::Any(
+                            lint_options
+                                .extensions
+                                .iter()
+                                .map(|ext| ModuleRuleCondition::ResourcePathEndsWith(ext.clone()))
+                                .collect(),
+                        ),
+                        ModuleRuleCondition::not(ModuleRuleCondition::ResourceIsVirtualAsset),
+                        ModuleRuleCondition::not(ModuleRuleCondition::ResourcePathInDirectory(
+                            "node_modules".to_string(),
+                        )),
+                    ]),
+                    vec![ModuleRuleEffect::SourceTransforms(
+                        SourceTransformsVc::cell(vec![LintTransformVc::new(
+                            node_evaluate_asset_context(
+                                execution_context.project_path(),
+                                None,
+                                None,
+                            ),
+                            execution_context,
+                            Value::new(lint_options.linter),
+                        )
+                        .into()]),
+                    )],
+                ),
+            );
+        }
+
+        if let Some(font_subsetting) = enable_font_subsetting {
+            let execution_context = execution_context
+                .context("execution_context is required for font subsetting")?
+                .with_layer("font_subset");
+            rules.insert(
+                0,
+                ModuleRule::new(
+                    ModuleRuleCondition::All(vec![
+                        ModuleRuleCondition::ResourcePathEndsWith(".woff2".to_string()),
+                        ModuleRuleCondition::not(ModuleRuleCondition::ResourceIsVirtualAsset),
+                    ]),
+                    vec![ModuleRuleEffect::SourceTransforms(
+                        SourceTransformsVc::cell(vec![FontSubsetTransformVc::new(
+                            node_evaluate_asset_context(
+                                execution_context.project_path(),
+                                None,
+                                None,
+                            ),
+                            execution_context,
+                            font_subsetting.unicode_ranges.clone(),
+                        )
+                        .into()]),
+                    )],
+                ),
+            );
+        }
+
+        // The mocked module is replaced before any other rule applies to it.
+        rules.splice(
+            0..0,
+            module_mocks.iter().map(|ModuleMock { condition, mock }| {
+                ModuleRule::new(condition.clone(), vec![ModuleRuleEffect::Replace(*mock)])
+            }),
+        );
+
         rules.extend(custom_rules.iter().cloned());
 
         Ok(ModuleOptionsVc::cell(ModuleOptions { rules }))
@@ -1,13 +1,18 @@
+use anyhow::Result;
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use turbo_tasks::trace::TraceRawVcs;
-use turbopack_core::{environment::EnvironmentVc, resolve::options::ImportMappingVc};
+use turbo_tasks_fs::FileSystemPathVc;
+use turbopack_core::{
+    chunk::ChunkPrefetch, environment::EnvironmentVc, resolve::options::ImportMappingVc,
+};
 use turbopack_ecmascript::EcmascriptInputTransform;
 use turbopack_node::{
-    execution_context::ExecutionContextVc, transforms::webpack::WebpackLoaderConfigItemsVc,
+    execution_context::ExecutionContextVc,
+    transforms::{lint::Linter, webpack::WebpackLoaderConfigItemsVc},
 };
 
-use super::ModuleRule;
+use super::{ModuleRule, ModuleRuleCondition};
 use crate::condition::ContextCondition;
 
 #[derive(Default, Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
@@ -16,6 +21,22 @@ pub struct PostCssTransformOptions {
     pub placeholder_for_future_extensions: (),
 }
 
+#[derive(Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub struct LintOptions {
+    pub linter: Linter,
+    /// The file extensions (e.g. `.ts`) of source files that are linted.
+    pub extensions: Vec<String>,
+    pub placeholder_for_future_extensions: (),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub struct FontSubsettingOptions {
+    /// The unicode ranges (e.g. `U+0000-00FF`) of the glyphs which are kept in
+    /// woff2 fonts.
+    pub unicode_ranges: Vec<String>,
+    pub placeholder_for_future_extensions: (),
+}
+
 #[turbo_tasks::value(shared)]
 #[derive(Default, Clone, Debug)]
 pub struct WebpackLoadersOptions {
@@ -24,6 +45,29 @@ pub struct WebpackLoadersOptions {
     pub placeholder_for_future_extensions: (),
 }
 
+/// Validates imported JSON files against a JSON schema, so invalid config
+/// files are reported at build time.
+#[derive(Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub struct JsonSchemaRule {
+    /// A glob matching the file names of the validated files, e.g.
+    /// `*.routes.json`.
+    pub file_name: String,
+    /// The path of the JSON schema.
+    pub schema: FileSystemPathVc,
+}
+
+/// Replaces the modules matching the `condition` with the module of the
+/// `mock` file, e.g. an analytics SDK with a no-op implementation during
+/// development.
+#[derive(Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub struct ModuleMock {
+    pub condition: ModuleRuleCondition,
+    pub mock: FileSystemPathVc,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct ModuleMocks(Vec<ModuleMock>);
+
 /// The kind of decorators transform to use.
 /// [TODO]: might need bikeshed for the name (Ecma)
 #[derive(Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
@@ -129,6 +173,14 @@ pub struct ModuleOptionsContext {
     #[serde(default)]
     pub enable_webpack_loaders: Option<WebpackLoadersOptions>,
     #[serde(default)]
+    /// Lints source files outside of `node_modules` in parallel to the
+    /// compilation and reports the results as warnings.
+    pub enable_lint: Option<LintOptions>,
+    #[serde(default)]
+    /// Subsets woff2 fonts to the configured unicode ranges using the
+    /// `subset-font` package of the project.
+    pub enable_font_subsetting: Option<FontSubsettingOptions>,
+    #[serde(default)]
     pub enable_types: bool,
     #[serde(default)]
     pub enable_typescript_transform: Option<TypescriptTransformOptionsVc>,
@@ -155,6 +207,35 @@ pub struct ModuleOptionsContext {
     pub placeholder_for_future_extensions: (),
     #[serde(default)]
     pub enable_tree_shaking: bool,
+    #[serde(default)]
+    /// Resolves requests like `./messages/{en,de}.json` to a loader that
+    /// loads the catalog of a single locale from its own chunk.
+    pub enable_locale_messages: bool,
+    #[serde(default)]
+    /// Static assets like images and fonts which are smaller than this number
+    /// of bytes are inlined as data URIs in JS and CSS, instead of being
+    /// emitted as files.
+    pub inline_static_assets_size_limit: Option<u64>,
+    #[serde(default)]
+    /// JSON schemas to validate imported JSON files against. The last
+    /// matching rule is used.
+    pub json_schemas: Vec<JsonSchemaRule>,
+    #[serde(default)]
+    /// Reports code that defeats static analysis, like `eval` of a variable,
+    /// `new Function` or a `require` of a fully dynamic expression, as errors
+    /// instead of warnings. This guarantees a static module graph, and output
+    /// that doesn't need `unsafe-eval` in its CSP.
+    pub enable_strict_analysis: bool,
+    #[serde(default)]
+    /// Prefetches the chunks of dynamic imports in the modules, unless an
+    /// import has a `turbopackPrefetch` comment. Different strategies for
+    /// different modules can be configured with [ModuleOptionsContext::rules].
+    pub chunk_prefetch: Option<ChunkPrefetch>,
+    #[serde(default)]
+    /// Modules which are replaced with mock implementations. The first
+    /// matching mock is used. They're usually only set for development, see
+    /// [crate::transition::module_mocks::ModuleMocksTransition].
+    pub module_mocks: Vec<ModuleMock>,
 }
 
 #[turbo_tasks::value_impl]
@@ -163,6 +244,20 @@ impl ModuleOptionsContextVc {
     pub fn default() -> Self {
         Self::cell(Default::default())
     }
+
+    /// Adds the `mocks` to this context and the contexts of its
+    /// [ModuleOptionsContext::rules], before the mocks they already have.
+    #[turbo_tasks::function]
+    pub async fn with_module_mocks(self, mocks: ModuleMocksVc) -> Result<Self> {
+        let mut context = self.await?.clone_value();
+        let mut module_mocks = mocks.await?.clone_value();
+        module_mocks.append(&mut context.module_mocks);
+        context.module_mocks = module_mocks;
+        for (_, rule_context) in context.rules.iter_mut() {
+            *rule_context = rule_context.with_module_mocks(mocks);
+        }
+        Ok(context.cell())
+    }
 }
 
 impl Default for ModuleOptionsContextVc {
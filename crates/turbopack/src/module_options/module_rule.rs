@@ -1,7 +1,7 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use turbo_tasks::trace::TraceRawVcs;
-use turbo_tasks_fs::FileSystemPath;
+use turbo_tasks_fs::{FileSystemPath, FileSystemPathVc};
 use turbopack_core::{
     asset::AssetVc, reference_type::ReferenceType, source_transform::SourceTransformsVc,
 };
@@ -41,6 +41,10 @@ pub enum ModuleRuleEffect {
     ModuleType(ModuleType),
     AddEcmascriptTransforms(EcmascriptInputTransformsVc),
     SourceTransforms(SourceTransformsVc),
+    /// Replaces the module with the module of the file at the path, which is
+    /// processed with the rules of its own path. Used for
+    /// [super::ModuleOptionsContext::module_mocks].
+    Replace(FileSystemPathVc),
     Custom,
 }
 
@@ -56,11 +60,18 @@ pub enum ModuleType {
     TypescriptWithTypes(EcmascriptInputTransformsVc),
     TypescriptDeclaration(EcmascriptInputTransformsVc),
     Json,
+    /// JSON validated against the JSON schema at the path.
+    JsonWithSchema(FileSystemPathVc),
     Raw,
     Mdx(EcmascriptInputTransformsVc),
     Css(CssInputTransformsVc),
     CssModule(CssInputTransformsVc),
     Static,
+    /// Static assets which are inlined as data URIs when they are smaller
+    /// than `size_limit` bytes.
+    InlinedStatic {
+        size_limit: u64,
+    },
     // TODO allow custom function when we support function pointers
     Custom(u8),
 }
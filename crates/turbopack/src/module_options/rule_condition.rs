@@ -2,7 +2,7 @@ use anyhow::Result;
 use async_recursion::async_recursion;
 use serde::{Deserialize, Serialize};
 use turbo_tasks::{primitives::Regex, trace::TraceRawVcs};
-use turbo_tasks_fs::{FileSystemPath, FileSystemPathReadRef};
+use turbo_tasks_fs::{glob::GlobReadRef, FileSystemPath, FileSystemPathReadRef};
 use turbopack_core::{
     asset::AssetVc, reference_type::ReferenceType, virtual_asset::VirtualAssetVc,
 };
@@ -17,6 +17,8 @@ pub enum ModuleRuleCondition {
     ResourcePathEquals(FileSystemPathReadRef),
     ResourcePathHasNoExtension,
     ResourcePathEndsWith(String),
+    /// Matches the file name of the resource against a glob.
+    ResourceFileNameGlob(GlobReadRef),
     ResourcePathInDirectory(String),
     ResourcePathInExactDirectory(FileSystemPathReadRef),
     ResourcePathRegex(#[turbo_tasks(trace_ignore)] Regex),
@@ -67,6 +69,7 @@ impl ModuleRuleCondition {
             }
             ModuleRuleCondition::ResourcePathEquals(other) => path == &**other,
             ModuleRuleCondition::ResourcePathEndsWith(end) => path.path.ends_with(end),
+            ModuleRuleCondition::ResourceFileNameGlob(glob) => glob.execute(path.file_name()),
             ModuleRuleCondition::ResourcePathHasNoExtension => {
                 if let Some(i) = path.path.rfind('.') {
                     if let Some(j) = path.path.rfind('/') {
@@ -16,7 +16,7 @@ use turbopack_ecmascript::typescript::resolve::{
 
 use crate::resolve_options_context::ResolveOptionsContextVc;
 
-const NODE_EXTERNALS: [&str; 51] = [
+pub(crate) const NODE_EXTERNALS: [&str; 51] = [
     "assert",
     "async_hooks",
     "buffer",
@@ -109,6 +109,32 @@ async fn base_resolve_options(
     }
     let import_map = import_map.cell();
 
+    let mut modules = if let Some(environment) = emulating {
+        if *environment.resolve_node_modules().await? {
+            vec![ResolveModules::Nested(
+                root,
+                vec!["node_modules".to_string()],
+            )]
+        } else {
+            Vec::new()
+        }
+    } else {
+        let mut mods = Vec::new();
+        if let Some(dir) = opt.enable_node_modules {
+            mods.push(ResolveModules::Nested(
+                dir,
+                vec!["node_modules".to_string()],
+            ));
+        }
+        mods
+    };
+    if let Some(dir) = opt.enable_workspace {
+        modules.insert(0, ResolveModules::Workspace(dir));
+    }
+    if let Some(dir) = opt.enable_pnp {
+        modules.insert(0, ResolveModules::PnP(dir));
+    }
+
     Ok(ResolveOptions {
         extensions: if let Some(environment) = emulating {
             environment.resolve_extensions().await?.clone_value()
@@ -130,25 +156,7 @@ async fn base_resolve_options(
             ext.push(".json".to_string());
             ext
         },
-        modules: if let Some(environment) = emulating {
-            if *environment.resolve_node_modules().await? {
-                vec![ResolveModules::Nested(
-                    root,
-                    vec!["node_modules".to_string()],
-                )]
-            } else {
-                Vec::new()
-            }
-        } else {
-            let mut mods = Vec::new();
-            if let Some(dir) = opt.enable_node_modules {
-                mods.push(ResolveModules::Nested(
-                    dir,
-                    vec!["node_modules".to_string()],
-                ));
-            }
-            mods
-        },
+        modules,
         into_package: {
             let mut resolve_into = Vec::new();
             resolve_into.push(ResolveIntoPackage::ExportsField {
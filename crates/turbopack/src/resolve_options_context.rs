@@ -28,6 +28,14 @@ pub struct ResolveOptionsContext {
     /// directory
     pub enable_node_modules: Option<FileSystemPathVc>,
     #[serde(default)]
+    /// Enable resolving `workspace:` dependencies to the packages of the pnpm,
+    /// yarn or npm workspace at the provided directory
+    pub enable_workspace: Option<FileSystemPathVc>,
+    #[serde(default)]
+    /// Enable resolving packages with the Yarn Plug'n'Play manifest of the
+    /// project at the provided directory
+    pub enable_pnp: Option<FileSystemPathVc>,
+    #[serde(default)]
     /// Mark well-known Node.js modules as external imports and load them using
     /// native `require`. e.g. url, querystring, os
     pub enable_node_externals: bool,
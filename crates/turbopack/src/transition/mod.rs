@@ -1,3 +1,5 @@
+pub mod module_mocks;
+
 use std::collections::HashMap;
 
 use anyhow::Result;
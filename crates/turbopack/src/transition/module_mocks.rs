@@ -0,0 +1,34 @@
+use crate::{
+    module_options::{ModuleMocksVc, ModuleOptionsContextVc},
+    transition::{Transition, TransitionVc},
+};
+
+/// Replaces modules with mock implementations, e.g. an analytics SDK with a
+/// no-op. Using it as the transition of the development context keeps the
+/// mocks out of production builds.
+///
+/// The mocks are read like any other source file, so changing them updates
+/// the modules that use them.
+#[turbo_tasks::value(shared)]
+pub struct ModuleMocksTransition {
+    pub mocks: ModuleMocksVc,
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleMocksTransitionVc {
+    #[turbo_tasks::function]
+    pub fn new(mocks: ModuleMocksVc) -> Self {
+        ModuleMocksTransition { mocks }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Transition for ModuleMocksTransition {
+    #[turbo_tasks::function]
+    fn process_module_options_context(
+        &self,
+        context: ModuleOptionsContextVc,
+    ) -> ModuleOptionsContextVc {
+        context.with_module_mocks(self.mocks)
+    }
+}
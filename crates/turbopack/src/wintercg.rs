@@ -0,0 +1,125 @@
+//! A preset for bundling server code for runtimes implementing the WinterCG
+//! Minimum Common Web Platform API, e.g. Cloudflare Workers or Deno Deploy.
+//!
+//! These runtimes don't provide Node.js builtins, or only a few of them behind
+//! a compatibility flag, and limit the size of the deployed script.
+
+use anyhow::Result;
+use turbo_tasks::Value;
+use turbopack_core::{
+    environment::{
+        EdgeWorkerEnvironment, EnvironmentIntention, EnvironmentVc, ExecutionEnvironment,
+        ServerAddrVc,
+    },
+    resolve::options::{ImportMap, ImportMapVc, ImportMapping},
+};
+
+use crate::{resolve::NODE_EXTERNALS, resolve_options_context::ResolveOptionsContextVc};
+
+/// The script size limit of Cloudflare Workers on the free plan, in bytes.
+pub const DEFAULT_SCRIPT_SIZE_LIMIT: u64 = 1024 * 1024;
+
+/// The Node.js builtins which WinterCG runtimes commonly provide as `node:`
+/// imports behind a compatibility flag, e.g. `nodejs_compat` of Cloudflare
+/// Workers.
+pub const NODE_COMPAT_BUILTINS: [&str; 11] = [
+    "assert",
+    "async_hooks",
+    "buffer",
+    "crypto",
+    "diagnostics_channel",
+    "events",
+    "path",
+    "process",
+    "stream",
+    "string_decoder",
+    "util",
+];
+
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+pub struct WinterCgOptions {
+    /// Whether the [NODE_COMPAT_BUILTINS] are left as `node:` imports for the
+    /// runtime to provide.
+    pub node_compat: bool,
+    /// Requests of modules replacing Node.js builtins, by the name of the
+    /// builtin, e.g. `("path", "path-browserify")`. They take precedence over
+    /// `node_compat`.
+    pub builtin_shims: Vec<(String, String)>,
+    /// The maximum size of the script, in bytes. Pass it to
+    /// [script_size](turbopack_core::bundle_stats::script_size) to report
+    /// scripts exceeding it.
+    pub script_size_limit: u64,
+}
+
+impl Default for WinterCgOptions {
+    fn default() -> Self {
+        WinterCgOptions {
+            node_compat: false,
+            builtin_shims: Vec::new(),
+            script_size_limit: DEFAULT_SCRIPT_SIZE_LIMIT,
+        }
+    }
+}
+
+/// The import map of Node.js builtins for a WinterCG runtime. Shimmed
+/// builtins are aliased to their shims, and builtins provided by the runtime
+/// are left as `node:` imports. Other builtins aren't mapped, so importing
+/// them fails to resolve at build time instead of at runtime.
+#[turbo_tasks::function]
+pub async fn wintercg_import_map(options: WinterCgOptionsVc) -> Result<ImportMapVc> {
+    let options = options.await?;
+    let mut import_map = ImportMap::empty();
+    for builtin in NODE_EXTERNALS {
+        let shim = options
+            .builtin_shims
+            .iter()
+            .find(|(name, _)| name == builtin)
+            .map(|(_, shim)| shim);
+        let mapping = if let Some(shim) = shim {
+            ImportMapping::PrimaryAlternative(shim.clone(), None).cell()
+        } else if options.node_compat && NODE_COMPAT_BUILTINS.contains(&builtin) {
+            ImportMapping::External(Some(format!("node:{builtin}"))).cell()
+        } else {
+            continue;
+        };
+        import_map.insert_exact_alias(builtin, mapping);
+        import_map.insert_exact_alias(format!("node:{builtin}"), mapping);
+    }
+    Ok(import_map.cell())
+}
+
+/// The environment of a WinterCG runtime.
+#[turbo_tasks::function]
+pub fn wintercg_environment(server_addr: ServerAddrVc) -> EnvironmentVc {
+    EnvironmentVc::new(
+        Value::new(ExecutionEnvironment::EdgeWorker(
+            EdgeWorkerEnvironment { server_addr }.cell(),
+        )),
+        Value::new(EnvironmentIntention::ServerRendering),
+    )
+}
+
+/// Resolve options for server code running in a WinterCG runtime: packages
+/// are resolved with the `worker` and `workerd` conditions, and Node.js
+/// builtins with the [wintercg_import_map].
+#[turbo_tasks::function]
+pub async fn wintercg_resolve_options_context(
+    environment: EnvironmentVc,
+    options: WinterCgOptionsVc,
+    resolve_options_context: ResolveOptionsContextVc,
+) -> Result<ResolveOptionsContextVc> {
+    let mut context = resolve_options_context.await?.clone_value();
+    context.emulate_environment = Some(environment);
+    context.enable_node_externals = false;
+    context.browser = false;
+    context.module = true;
+    for condition in ["worker", "workerd"] {
+        if !context.custom_conditions.iter().any(|c| c == condition) {
+            context.custom_conditions.push(condition.to_string());
+        }
+    }
+    Ok(context
+        .cell()
+        .with_extended_import_map(wintercg_import_map(options)))
+}